@@ -0,0 +1,160 @@
+extern crate krill;
+
+use std::{path::PathBuf, str::FromStr};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use rpki::uri;
+
+use krill::{
+    commons::{error::Error, util::file, KrillResult},
+    constants::{KRILL_TA_APP, KRILL_TA_DEFAULT_CONFIG_FILE, KRILL_VERSION},
+    ta::{TrustAnchorProxyInfo, TrustAnchorSignedMessage, TrustAnchorSigner, TrustAnchorSignerConfig},
+};
+
+fn main() {
+    let matches = App::new(KRILL_TA_APP)
+        .version(KRILL_VERSION)
+        .about("\nThis tool manages the key of an offline Trust Anchor. It signs requests made by the Trust Anchor proxy in Krill, and returns responses which are processed by that proxy. It does not need network access.")
+        .arg(
+            Arg::with_name("config")
+                .short("c")
+                .long("config")
+                .value_name("FILE")
+                .help(&format!(
+                    "Override the path to the config file (default: '{}')",
+                    KRILL_TA_DEFAULT_CONFIG_FILE
+                ))
+                .required(false),
+        )
+        .subcommand(
+            SubCommand::with_name("init")
+                .about("Initialize the signer, creating the TA key and certificate")
+                .arg(
+                    Arg::with_name("proxy")
+                        .long("proxy")
+                        .value_name("FILE")
+                        .help("The proxy ID and repository JSON, as shown by 'krillc ta proxy id'")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("tal_https")
+                        .long("tal-https")
+                        .value_name("URI")
+                        .help("The HTTPS URI(s) for the TA certificate to include in the TAL")
+                        .multiple(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("tal_rsync")
+                        .long("tal-rsync")
+                        .value_name("URI")
+                        .help("The rsync URI for the TA certificate, defaults to 'ta.cer' in the proxy repository")
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show")
+                .about("Show the signer ID and TA certificate, as JSON for 'krillc ta proxy signer add'"),
+        )
+        .subcommand(
+            SubCommand::with_name("process")
+                .about("Process a request from the proxy, and save the response")
+                .arg(
+                    Arg::with_name("request")
+                        .long("request")
+                        .value_name("FILE")
+                        .help("The request JSON, as shown by 'krillc ta proxy signer show-request'")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("response")
+                        .long("response")
+                        .value_name("FILE")
+                        .help("The file to save the response JSON to")
+                        .required(true),
+                ),
+        )
+        .get_matches();
+
+    if let Err(e) = run(&matches) {
+        eprintln!("*** ERROR *** {}", e);
+        ::std::process::exit(1);
+    }
+}
+
+fn run(matches: &ArgMatches) -> KrillResult<()> {
+    let config_file = matches.value_of("config").unwrap_or(KRILL_TA_DEFAULT_CONFIG_FILE);
+    let config = TrustAnchorSignerConfig::parse(config_file)?;
+
+    if let Some(m) = matches.subcommand_matches("init") {
+        init(&config, m)
+    } else if matches.subcommand_matches("show").is_some() {
+        show(&config)
+    } else if let Some(m) = matches.subcommand_matches("process") {
+        process(&config, m)
+    } else {
+        Err(Error::custom("Unrecognized sub-command. Use 'help'"))
+    }
+}
+
+fn init(config: &TrustAnchorSignerConfig, matches: &ArgMatches) -> KrillResult<()> {
+    if TrustAnchorSigner::exists(&config.data_dir) {
+        return Err(Error::TaAlreadyInitialized);
+    }
+
+    let proxy_info: TrustAnchorProxyInfo = read_json(matches.value_of("proxy").unwrap())?;
+
+    let mut tal_https = vec![];
+    for uri_str in matches.values_of("tal_https").unwrap() {
+        let uri = uri::Https::from_str(uri_str)
+            .map_err(|e| Error::Custom(format!("Invalid HTTPS URI '{}': {}", uri_str, e)))?;
+        tal_https.push(uri);
+    }
+
+    let tal_rsync = match matches.value_of("tal_rsync") {
+        Some(uri_str) => Some(
+            uri::Rsync::from_str(uri_str)
+                .map_err(|e| Error::Custom(format!("Invalid rsync URI '{}': {}", uri_str, e)))?,
+        ),
+        None => None,
+    };
+
+    let signer = config.signer()?;
+    let ta_signer = TrustAnchorSigner::create(proxy_info, tal_https, tal_rsync, &config.issuance_timing(), &signer)?;
+    ta_signer.save(&config.data_dir)?;
+
+    println!("{}", ta_signer.info());
+    Ok(())
+}
+
+fn show(config: &TrustAnchorSignerConfig) -> KrillResult<()> {
+    let ta_signer = TrustAnchorSigner::load(&config.data_dir)?;
+    println!("{}", ta_signer.info());
+    Ok(())
+}
+
+fn process(config: &TrustAnchorSignerConfig, matches: &ArgMatches) -> KrillResult<()> {
+    let mut ta_signer = TrustAnchorSigner::load(&config.data_dir)?;
+    let request: TrustAnchorSignedMessage = read_json(matches.value_of("request").unwrap())?;
+
+    let signer = config.signer()?;
+    let response = ta_signer.process(&request, &config.issuance_timing(), &signer)?;
+    ta_signer.save(&config.data_dir)?;
+
+    let response_path = PathBuf::from(matches.value_of("response").unwrap());
+    file::save(&response.to_bytes(), &response_path)?;
+
+    println!(
+        "Saved response to '{}'. The TA manifest and CRL will expire at: {}",
+        response_path.to_string_lossy(),
+        ta_signer.next_update().to_rfc3339()
+    );
+    println!("Process a new request before that time, to avoid that the TA objects become invalid.");
+    Ok(())
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> KrillResult<T> {
+    let bytes = file::read(&PathBuf::from(path))?;
+    serde_json::from_slice(bytes.as_ref()).map_err(Error::JsonError)
+}
@@ -1,26 +1,32 @@
 use std::{env, fmt};
 
+use bytes::Bytes;
 use serde::{de::DeserializeOwned, Serialize};
 
 use rpki::ca::idexchange;
+use urlparse::quote;
 
 use crate::{
     cli::{
-        options::{BulkCaCommand, CaCommand, Command, KrillInitDetails, Options, PubServerCommand},
+        options::{BulkCaCommand, CaCommand, Command, KrillInitDetails, Options, PubServerCommand, TrustAnchorCommand},
         report::{ApiResponse, ReportError},
     },
     commons::{
         api::{
-            AllCertAuthIssues, ApiRepositoryContact, AspaDefinitionUpdates, BgpSecDefinitionUpdates, CaRepoDetails,
-            CertAuthIssues, ChildCaInfo, ChildrenConnectionStats, ParentCaContact, ParentStatuses, PublisherDetails,
-            PublisherList, RepoStatus, Token,
+            AllCertAuthIssues, ApiRepositoryContact, AspaDefinitionUpdates, BgpSecDefinitionUpdates,
+            CaPublishedObjects, CaRepoDetails, CertAuthIssues, ChildCaInfo, ChildrenConnectionStats, ParentCaContact,
+            ParentStatuses, PrefixListDefinitionUpdates, PublisherDetails, PublisherList, RepoStatus,
+            RoaConfigurationFilter, Token,
         },
         bgp::BgpAnalysisAdvice,
         error::KrillIoError,
         util::{file, httpclient},
     },
     constants::KRILL_CLI_API_ENV,
-    daemon::config::Config,
+    daemon::{
+        ca::{ResourceSignedChecklist, SignedGeofeed, SignedObjectVerifyRequest},
+        config::Config,
+    },
 };
 
 #[cfg(feature = "multi-user")]
@@ -40,6 +46,20 @@ async fn get_json<T: DeserializeOwned>(server: &idexchange::ServiceUri, token: &
         .map_err(Error::HttpClientError)
 }
 
+async fn get_text(server: &idexchange::ServiceUri, token: &Token, path: &str) -> Result<String, Error> {
+    let uri = resolve_uri(server, path);
+    httpclient::get_text(&uri, Some(token))
+        .await
+        .map_err(Error::HttpClientError)
+}
+
+async fn get_binary(server: &idexchange::ServiceUri, token: &Token, path: &str) -> Result<Bytes, Error> {
+    let uri = resolve_uri(server, path);
+    httpclient::get_binary(&uri, Some(token))
+        .await
+        .map_err(Error::HttpClientError)
+}
+
 async fn post_empty(server: &idexchange::ServiceUri, token: &Token, path: &str) -> Result<(), Error> {
     let uri = resolve_uri(server, path);
     httpclient::post_empty(&uri, Some(token))
@@ -90,6 +110,36 @@ async fn delete(server: &idexchange::ServiceUri, token: &Token, uri: &str) -> Re
         .map_err(Error::HttpClientError)
 }
 
+/// Returns the query string, including the leading '?', to select ROA
+/// configurations using the filter. Returns an empty string if the
+/// filter is empty.
+fn roa_filter_query(filter: &RoaConfigurationFilter) -> Result<String, Error> {
+    let mut params = vec![];
+    if let Some(owner) = filter.owner() {
+        params.push(("owner", owner.clone()));
+    }
+    if let Some(ticket) = filter.ticket() {
+        params.push(("ticket", ticket.clone()));
+    }
+    if let Some(customer) = filter.customer() {
+        params.push(("customer", customer.clone()));
+    }
+    for tag in filter.tags() {
+        params.push(("tag", tag));
+    }
+
+    if params.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut parts = vec![];
+    for (key, value) in params {
+        let value = quote(value, b"").map_err(|e| Error::InputError(e.to_string()))?;
+        parts.push(format!("{}={}", key, value));
+    }
+    Ok(format!("?{}", parts.join("&")))
+}
+
 /// Command line tool for Krill admin tasks
 pub struct KrillClient {
     server: idexchange::ServiceUri,
@@ -132,9 +182,11 @@ impl KrillClient {
             Command::Bulk(cmd) => client.bulk(cmd).await,
             Command::CertAuth(cmd) => client.certauth(cmd).await,
             Command::PubServer(cmd) => client.publishers(cmd).await,
+            Command::TrustAnchor(cmd) => client.trust_anchor(cmd).await,
             Command::Init(details) => client.init_config(details),
             #[cfg(feature = "multi-user")]
             Command::User(cmd) => client.user(cmd),
+            Command::Verify(request) => client.verify(request).await,
             Command::NotSet => Err(Error::MissingCommand),
         }
     }
@@ -149,6 +201,11 @@ impl KrillClient {
         Ok(ApiResponse::Info(info))
     }
 
+    async fn verify(&self, request: SignedObjectVerifyRequest) -> Result<ApiResponse, Error> {
+        let verification = post_json_with_response(&self.server, &self.token, "api/v1/verify", request).await?;
+        Ok(ApiResponse::SignedObjectVerification(verification))
+    }
+
     async fn bulk(&self, command: BulkCaCommand) -> Result<ApiResponse, Error> {
         match command {
             BulkCaCommand::Refresh => {
@@ -224,6 +281,19 @@ impl KrillClient {
                 Ok(ApiResponse::RepoStatus(status))
             }
 
+            CaCommand::PublishedObjects(ca) => {
+                let uri = format!("api/v1/cas/{}/objects", ca);
+                let objects: CaPublishedObjects = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::PublishedObjects(objects))
+            }
+
+            CaCommand::PublishedObjectDownload(ca, key, name, out) => {
+                let uri = format!("api/v1/cas/{}/objects/{}/{}", ca, key, name);
+                let bytes = get_binary(&self.server, &self.token, &uri).await?;
+                file::save(&bytes, &out)?;
+                Ok(ApiResponse::Empty)
+            }
+
             CaCommand::RepoUpdate(handle, update) => {
                 let uri = format!("api/v1/cas/{}/repo", handle);
                 let api_contact = ApiRepositoryContact::new(update);
@@ -231,18 +301,55 @@ impl KrillClient {
                 Ok(ApiResponse::Empty)
             }
 
+            CaCommand::RepoMirrorAdd(handle, response) => {
+                let uri = format!("api/v1/cas/{}/repo/mirrors", handle);
+                let api_contact = ApiRepositoryContact::new(response);
+                post_json(&self.server, &self.token, &uri, api_contact).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::RepoMirrorRemove(handle, service_uri) => {
+                let uri = format!("api/v1/cas/{}/repo/mirrors/remove", handle);
+                post_json(&self.server, &self.token, &uri, service_uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+
             CaCommand::AddParent(handle, parent_req) => {
                 let uri = format!("api/v1/cas/{}/parents", handle);
                 post_json(&self.server, &self.token, &uri, parent_req).await?;
                 Ok(ApiResponse::Empty)
             }
 
+            CaCommand::ParentOnboard(handle, token) => {
+                let uri = format!("api/v1/cas/{}/onboarding/redeem", handle);
+                post_json(&self.server, &self.token, &uri, token).await?;
+                Ok(ApiResponse::Empty)
+            }
+
             CaCommand::RemoveParent(handle, parent) => {
                 let uri = format!("api/v1/cas/{}/parents/{}", handle, parent);
                 delete(&self.server, &self.token, &uri).await?;
                 Ok(ApiResponse::Empty)
             }
 
+            CaCommand::ParentResourceLimitShow(handle, parent) => {
+                let uri = format!("api/v1/cas/{}/parents/{}/limit", handle, parent);
+                let limit = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::ParentResourceLimit(limit))
+            }
+
+            CaCommand::ParentResourceLimitSet(handle, parent, limit) => {
+                let uri = format!("api/v1/cas/{}/parents/{}/limit", handle, parent);
+                post_json(&self.server, &self.token, &uri, limit).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::ParentResourceLimitRemove(handle, parent) => {
+                let uri = format!("api/v1/cas/{}/parents/{}/limit", handle, parent);
+                delete(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+
             CaCommand::ParentStatuses(handle) => {
                 let uri = format!("api/v1/cas/{}/parents", handle);
                 let statuses: ParentStatuses = get_json(&self.server, &self.token, &uri).await?;
@@ -287,6 +394,21 @@ impl KrillClient {
                 let stats: ChildrenConnectionStats = get_json(&self.server, &self.token, &uri).await?;
                 Ok(ApiResponse::ChildrenStats(stats))
             }
+            CaCommand::OnboardingTokenList(handle) => {
+                let uri = format!("api/v1/cas/{}/onboarding/tokens", handle);
+                let tokens = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::OnboardingTokens(tokens))
+            }
+            CaCommand::OnboardingTokenAdd(handle, req) => {
+                let uri = format!("api/v1/cas/{}/onboarding/tokens", handle);
+                let token = post_json_with_response(&self.server, &self.token, &uri, req).await?;
+                Ok(ApiResponse::OnboardingToken(token))
+            }
+            CaCommand::OnboardingTokenRemove(handle, id) => {
+                let uri = format!("api/v1/cas/{}/onboarding/tokens/{}", handle, id);
+                delete(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::Empty)
+            }
 
             CaCommand::KeyRollInit(handle) => {
                 let uri = format!("api/v1/cas/{}/keys/roll_init", handle);
@@ -298,9 +420,14 @@ impl KrillClient {
                 post_empty(&self.server, &self.token, &uri).await?;
                 Ok(ApiResponse::Empty)
             }
+            CaCommand::KeyRollFinish(handle) => {
+                let uri = format!("api/v1/cas/{}/keys/roll_finish", handle);
+                post_empty(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::Empty)
+            }
 
-            CaCommand::RouteAuthorizationsList(handle) => {
-                let uri = format!("api/v1/cas/{}/routes", handle);
+            CaCommand::RouteAuthorizationsList(handle, filter) => {
+                let uri = format!("api/v1/cas/{}/routes{}", handle, roa_filter_query(&filter)?);
                 let roas = get_json(&self.server, &self.token, &uri).await?;
                 Ok(ApiResponse::RouteAuthorizations(roas))
             }
@@ -345,6 +472,72 @@ impl KrillClient {
                 Ok(ApiResponse::BgpAnalysisSuggestions(suggestions))
             }
 
+            CaCommand::RouteAuthorizationsSchedule(handle) => {
+                let uri = format!("api/v1/cas/{}/routes/schedule", handle);
+                let changes = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::TimedRoaChanges(changes))
+            }
+
+            CaCommand::RouteAuthorizationsDraftList(handle) => {
+                let uri = format!("api/v1/cas/{}/routes/drafts", handle);
+                let drafts = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::RoaDrafts(drafts))
+            }
+
+            CaCommand::RouteAuthorizationsDraftShow(handle, name) => {
+                let uri = format!("api/v1/cas/{}/routes/drafts/{}", handle, name);
+                let draft = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::RoaDraft(draft))
+            }
+
+            CaCommand::RouteAuthorizationsDraftUpdate(handle, name, updates) => {
+                let uri = format!("api/v1/cas/{}/routes/drafts/{}", handle, name);
+                post_json(&self.server, &self.token, &uri, updates).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::RouteAuthorizationsDraftRemove(handle, name) => {
+                let uri = format!("api/v1/cas/{}/routes/drafts/{}", handle, name);
+                delete(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::RouteAuthorizationsDraftDryRun(handle, name) => {
+                let uri = format!("api/v1/cas/{}/routes/drafts/{}/dryrun", handle, name);
+                let report = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::BgpAnalysisFull(report))
+            }
+
+            CaCommand::RouteAuthorizationsDraftCommit(handle, name) => {
+                let uri = format!("api/v1/cas/{}/routes/drafts/{}/commit", handle, name);
+                post_empty(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::RouteAuthorizationsImportRpsl(handle, request) => {
+                let uri = format!("api/v1/cas/{}/routes/import/rpsl", handle);
+                let report = post_json_with_response(&self.server, &self.token, &uri, request).await?;
+                Ok(ApiResponse::RpslImportReport(report))
+            }
+
+            CaCommand::RouteAuthorizationsExport(handle, format) => {
+                let uri = format!("api/v1/cas/{}/routes/export?format={}", handle, format);
+                let export = get_text(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::GenericBody(export))
+            }
+
+            CaCommand::RouteAuthorizationsAs0Status(handle) => {
+                let uri = format!("api/v1/cas/{}/routes/as0", handle);
+                let status = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::RoaAs0Status(status))
+            }
+
+            CaCommand::RouteAuthorizationsAs0Policy(handle, policy) => {
+                let uri = format!("api/v1/cas/{}/routes/as0", handle);
+                post_json(&self.server, &self.token, &uri, policy).await?;
+                Ok(ApiResponse::Empty)
+            }
+
             CaCommand::BgpSecList(handle) => {
                 let uri = format!("api/v1/cas/{}/bgpsec", handle);
                 let bgpsec_list = get_json(&self.server, &self.token, &uri).await?;
@@ -358,6 +551,12 @@ impl KrillClient {
                 Ok(ApiResponse::Empty)
             }
 
+            CaCommand::BgpSecGenerate(handle, request) => {
+                let uri = format!("api/v1/cas/{}/bgpsec/router_key", handle);
+                let router_key = post_json_with_response(&self.server, &self.token, &uri, request).await?;
+                Ok(ApiResponse::BgpSecRouterKey(router_key))
+            }
+
             CaCommand::BgpSecRemove(handle, removal) => {
                 let uri = format!("api/v1/cas/{}/bgpsec", handle);
                 let update = BgpSecDefinitionUpdates::new(vec![], vec![removal]);
@@ -365,6 +564,44 @@ impl KrillClient {
                 Ok(ApiResponse::Empty)
             }
 
+            CaCommand::PrefixListsList(handle) => {
+                let uri = format!("api/v1/cas/{}/prefix_lists", handle);
+                let definitions = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::PrefixListDefinitions(definitions))
+            }
+
+            CaCommand::PrefixListsAddOrReplace(handle, definition) => {
+                let uri = format!("api/v1/cas/{}/prefix_lists", handle);
+                let updates = PrefixListDefinitionUpdates::new(vec![definition], vec![]);
+                post_json(&self.server, &self.token, &uri, updates).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::PrefixListsRemove(handle, asn) => {
+                let uri = format!("api/v1/cas/{}/prefix_lists", handle);
+                let updates = PrefixListDefinitionUpdates::new(vec![], vec![asn]);
+                post_json(&self.server, &self.token, &uri, updates).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::GhostbusterShow(handle) => {
+                let uri = format!("api/v1/cas/{}/ghostbuster", handle);
+                let contact = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::GhostbusterContact(contact))
+            }
+
+            CaCommand::GhostbusterSet(handle, contact) => {
+                let uri = format!("api/v1/cas/{}/ghostbuster", handle);
+                post_json(&self.server, &self.token, &uri, contact).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::GhostbusterRemove(handle) => {
+                let uri = format!("api/v1/cas/{}/ghostbuster", handle);
+                delete(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+
             CaCommand::AspasList(handle) => {
                 let uri = format!("api/v1/cas/{}/aspas", handle);
                 let aspas = get_json(&self.server, &self.token, &uri).await?;
@@ -391,6 +628,24 @@ impl KrillClient {
                 Ok(ApiResponse::Empty)
             }
 
+            CaCommand::AspasImport(handle, request) => {
+                let uri = format!("api/v1/cas/{}/aspas/import", handle);
+                let report = post_json_with_response(&self.server, &self.token, &uri, request).await?;
+                Ok(ApiResponse::AspaImportReport(report))
+            }
+
+            CaCommand::AspasAnalysis(handle) => {
+                let uri = format!("api/v1/cas/{}/aspas/analysis/full", handle);
+                let report = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::AspaAnalysis(report))
+            }
+
+            CaCommand::AspasSuggest(handle) => {
+                let uri = format!("api/v1/cas/{}/aspas/analysis/suggest", handle);
+                let suggestion = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::AspaSuggestion(suggestion))
+            }
+
             CaCommand::Show(handle) => {
                 let uri = format!("api/v1/cas/{}", handle);
                 let ca_info = get_json(&self.server, &self.token, &uri).await?;
@@ -429,6 +684,38 @@ impl KrillClient {
                 }
             },
 
+            CaCommand::ResourceShrinks(ca) => {
+                let uri = format!("api/v1/cas/{}/shrinks", ca);
+                let report = get_json(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::ResourceShrinks(report))
+            }
+
+            CaCommand::ResourceShrinksClear(ca) => {
+                let uri = format!("api/v1/cas/{}/shrinks", ca);
+                delete(&self.server, &self.token, &uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::RscSign(ca, request, out) => {
+                let uri = format!("api/v1/cas/{}/rsc/sign", ca);
+                let rsc: ResourceSignedChecklist =
+                    post_json_with_response(&self.server, &self.token, &uri, request).await?;
+                file::save(rsc.as_ref(), &out)?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::GeofeedSign(ca, request, out) => {
+                let uri = format!("api/v1/cas/{}/geofeed/sign", ca);
+                let geofeed: SignedGeofeed = post_json_with_response(&self.server, &self.token, &uri, request).await?;
+                match out {
+                    Some(out) => {
+                        file::save(geofeed.as_str().as_bytes(), &out)?;
+                        Ok(ApiResponse::Empty)
+                    }
+                    None => Ok(ApiResponse::GenericBody(geofeed.to_string())),
+                }
+            }
+
             CaCommand::RtaList(ca) => {
                 let uri = format!("api/v1/cas/{}/rta/", ca);
                 let list = get_json(&self.server, &self.token, &uri).await?;
@@ -506,6 +793,11 @@ impl KrillClient {
                 post_empty(&self.server, &self.token, uri).await?;
                 Ok(ApiResponse::Empty)
             }
+            PubServerCommand::RepositoryValidate => {
+                let uri = "api/v1/pubd/validate";
+                let report = post_json_with_response(&self.server, &self.token, uri, ()).await?;
+                Ok(ApiResponse::RepositoryValidation(report))
+            }
             PubServerCommand::AddPublisher(req) => {
                 let res = post_json_with_response(&self.server, &self.token, "api/v1/pubd/publishers", req).await?;
                 Ok(ApiResponse::Rfc8183RepositoryResponse(res))
@@ -528,6 +820,46 @@ impl KrillClient {
         }
     }
 
+    pub async fn trust_anchor(&self, command: TrustAnchorCommand) -> Result<ApiResponse, Error> {
+        match command {
+            TrustAnchorCommand::ProxyInit => {
+                post_empty(&self.server, &self.token, "api/v1/ta/proxy/init").await?;
+                Ok(ApiResponse::Empty)
+            }
+            TrustAnchorCommand::ProxyId => {
+                let info = get_json(&self.server, &self.token, "api/v1/ta/proxy/id").await?;
+                Ok(ApiResponse::TrustAnchorProxyInfo(info))
+            }
+            TrustAnchorCommand::ProxyStatus => {
+                let status = get_json(&self.server, &self.token, "api/v1/ta/proxy/status").await?;
+                Ok(ApiResponse::TrustAnchorProxyStatus(status))
+            }
+            TrustAnchorCommand::ProxyChildAdd(req) => {
+                let uri = "api/v1/ta/proxy/children";
+                let res = post_json_with_response(&self.server, &self.token, uri, req).await?;
+                Ok(ApiResponse::Rfc8183ParentResponse(res))
+            }
+            TrustAnchorCommand::ProxySignerAdd(info) => {
+                post_json(&self.server, &self.token, "api/v1/ta/proxy/signer/add", info).await?;
+                Ok(ApiResponse::Empty)
+            }
+            TrustAnchorCommand::ProxySignerMakeRequest => {
+                let uri = "api/v1/ta/proxy/signer/request";
+                post_empty(&self.server, &self.token, uri).await?;
+                let request = get_json(&self.server, &self.token, uri).await?;
+                Ok(ApiResponse::TrustAnchorSignedMessage(request))
+            }
+            TrustAnchorCommand::ProxySignerShowRequest => {
+                let request = get_json(&self.server, &self.token, "api/v1/ta/proxy/signer/request").await?;
+                Ok(ApiResponse::TrustAnchorSignedMessage(request))
+            }
+            TrustAnchorCommand::ProxySignerProcessResponse(response) => {
+                post_json(&self.server, &self.token, "api/v1/ta/proxy/signer/response", response).await?;
+                Ok(ApiResponse::Empty)
+            }
+        }
+    }
+
     fn init_config(&self, details: KrillInitDetails) -> Result<ApiResponse, Error> {
         let defaults = include_str!("../../defaults/krill.conf");
         let multi_add_on = include_str!("../../defaults/krill-multi-user.conf");
@@ -30,16 +30,24 @@ use crate::{
     cli::report::{ReportError, ReportFormat},
     commons::{
         api::{
-            AddChildRequest, AspaCustomer, AspaDefinition, AspaDefinitionFormatError, AspaProvidersUpdate,
-            AuthorizationFmtError, BgpSecAsnKey, BgpSecDefinition, CertAuthInit, ParentCaReq, PublicationServerUris,
-            RoaConfiguration, RoaConfigurationUpdates, RoaPayload, RtaName, Token, UpdateChildRequest,
+            AddChildRequest, AspaCustomer, AspaDefinition, AspaDefinitionFormatError, AspaImportAction,
+            AspaImportRequest, AspaProvidersUpdate, AuthorizationFmtError, BgpSecAsnKey, BgpSecDefinition,
+            BgpSecRouterKeyRequest, CertAuthInit, GhostbusterContact, ObjectName, OnboardingToken, OnboardingTokenId,
+            OnboardingTokenRequest, ParentCaReq, PrefixListDefinition, PrefixListDefinitionFormatError,
+            PublicationServerUris, RoaAs0Policy, RoaConfiguration, RoaConfigurationFilter, RoaConfigurationUpdates,
+            RoaDraftName, RoaMetadata, RoaPayload, RpslImportAction, RpslImportRequest, RtaName, Timestamp, Token,
+            UpdateChildRequest, VrpExportFormat,
         },
         crypto::SignSupport,
         error::KrillIoError,
         util::file,
     },
     constants::*,
-    daemon::ca::{ResourceTaggedAttestation, RtaContentRequest, RtaPrepareRequest},
+    daemon::ca::{
+        GeofeedSignRequest, ResourceTaggedAttestation, RscChecklistItem, RscRequest, RtaContentRequest,
+        RtaPrepareRequest, SignedObjectVerifyRequest,
+    },
+    ta::{TrustAnchorSignedMessage, TrustAnchorSignerInfo},
 };
 
 struct GeneralArgs {
@@ -51,6 +59,25 @@ struct GeneralArgs {
 
 impl GeneralArgs {
     fn from_matches(matches: &ArgMatches) -> Result<Self, Error> {
+        let format = {
+            let mut format = match env::var(KRILL_CLI_FORMAT_ENV) {
+                Ok(fmt_str) => Some(ReportFormat::from_str(&fmt_str)?),
+                Err(_) => None,
+            };
+
+            if let Some(fmt_str) = matches.value_of(KRILL_CLI_FORMAT_ARG) {
+                format = Some(ReportFormat::from_str(fmt_str)?);
+            }
+
+            format.unwrap_or(ReportFormat::Text)
+        };
+
+        Self::from_matches_with_format(matches, format)
+    }
+
+    /// Used for commands which use their own --format argument, and
+    /// therefore do not support choosing the report format.
+    fn from_matches_with_format(matches: &ArgMatches, format: ReportFormat) -> Result<Self, Error> {
         let server = {
             let mut server = match env::var(KRILL_CLI_SERVER_ENV) {
                 Ok(server_str) => Some(idexchange::ServiceUri::from_str(&server_str)?),
@@ -74,19 +101,6 @@ impl GeneralArgs {
             token.ok_or_else(|| Error::missing_arg_with_env(KRILL_CLI_ADMIN_TOKEN_ARG, KRILL_CLI_TOKEN_ENV))?
         };
 
-        let format = {
-            let mut format = match env::var(KRILL_CLI_FORMAT_ENV) {
-                Ok(fmt_str) => Some(ReportFormat::from_str(&fmt_str)?),
-                Err(_) => None,
-            };
-
-            if let Some(fmt_str) = matches.value_of(KRILL_CLI_FORMAT_ARG) {
-                format = Some(ReportFormat::from_str(fmt_str)?);
-            }
-
-            format.unwrap_or(ReportFormat::Text)
-        };
-
         let api = env::var(KRILL_CLI_API_ENV).is_ok() || matches.is_present(KRILL_CLI_API_ARG);
 
         Ok(GeneralArgs {
@@ -146,6 +160,18 @@ impl Options {
     }
 
     fn add_general_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        Self::add_connection_args(app).arg(
+            Arg::with_name(KRILL_CLI_FORMAT_ARG)
+                .short("f")
+                .long(KRILL_CLI_FORMAT_ARG)
+                .value_name("type")
+                .help("Report format: none|json|text (default). Or set env: KRILL_CLI_FORMAT")
+                .required(false),
+        )
+    }
+
+    /// Adds the general arguments, except for the report format.
+    fn add_connection_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         app.arg(
             Arg::with_name(KRILL_CLI_SERVER_ARG)
                 .short("s")
@@ -162,14 +188,6 @@ impl Options {
                 .help("The secret token for the Krill server. Or set env: KRILL_CLI_TOKEN")
                 .required(false),
         )
-        .arg(
-            Arg::with_name(KRILL_CLI_FORMAT_ARG)
-                .short("f")
-                .long(KRILL_CLI_FORMAT_ARG)
-                .value_name("type")
-                .help("Report format: none|json|text (default). Or set env: KRILL_CLI_FORMAT")
-                .required(false),
-        )
         .arg(
             Arg::with_name(KRILL_CLI_API_ARG)
                 .long(KRILL_CLI_API_ARG)
@@ -510,6 +528,60 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_children_tokens_list_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("list").about("Show the unused onboarding tokens of a CA");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_children_tokens_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("add")
+            .about("Add a single-use token which lets a new child add itself with the given resources");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_resource_args(sub);
+        sub = sub.arg(
+            Arg::with_name("days")
+                .long("days")
+                .short("d")
+                .value_name("number of days")
+                .help("The number of days that the token can be used. Default: 7")
+                .required(false),
+        );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_children_tokens_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("remove").about("Remove an unused onboarding token");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = sub.arg(
+            Arg::with_name("id")
+                .long("id")
+                .value_name("token id")
+                .help("The id of the token, as shown in the list of tokens")
+                .required(true),
+        );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_children_tokens_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("tokens").about("Manage tokens which let new children add themselves");
+
+        sub = Self::make_cas_children_tokens_list_sc(sub);
+        sub = Self::make_cas_children_tokens_add_sc(sub);
+        sub = Self::make_cas_children_tokens_remove_sc(sub);
+
+        app.subcommand(sub)
+    }
+
     fn make_cas_children_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("children").about("Manage children for a CA");
 
@@ -521,6 +593,7 @@ impl Options {
         sub = Self::make_cas_children_connections_sc(sub);
         sub = Self::make_cas_children_suspend_sc(sub);
         sub = Self::make_cas_children_unsuspend_sc(sub);
+        sub = Self::make_cas_children_tokens_sc(sub);
 
         app.subcommand(sub)
     }
@@ -552,6 +625,24 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_parents_onboard_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub =
+            SubCommand::with_name("onboard").about("Add a parent to a CA using an onboarding token from the parent");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = sub.arg(
+            Arg::with_name("token")
+                .long("token")
+                .short("t")
+                .help("The location of the onboarding token JSON file received from the parent")
+                .value_name("<JSON file>")
+                .required(true),
+        );
+
+        app.subcommand(sub)
+    }
+
     fn make_cas_parents_statuses_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("statuses").about("Show overview of all parent statuses of a CA");
 
@@ -581,14 +672,58 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_parents_limit_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("show").about("Show the limit on resources requested from a parent");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_parent_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_parents_limit_set_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("set").about("Only request the given resources from a parent");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_parent_arg(sub);
+        sub = Self::add_resource_args(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_parents_limit_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("remove")
+            .about("Remove the limit, i.e. request all entitled resources from a parent");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_parent_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_parents_limit_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("limit").about("Manage the resources a CA requests from a parent");
+
+        sub = Self::make_cas_parents_limit_show_sc(sub);
+        sub = Self::make_cas_parents_limit_set_sc(sub);
+        sub = Self::make_cas_parents_limit_remove_sc(sub);
+
+        app.subcommand(sub)
+    }
+
     fn make_cas_parents_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("parents").about("Manage parents for a CA");
 
         sub = Self::make_cas_parents_request_sc(sub);
         sub = Self::make_cas_parents_add_sc(sub);
+        sub = Self::make_cas_parents_onboard_sc(sub);
         sub = Self::make_cas_parents_contact_sc(sub);
         sub = Self::make_cas_parents_statuses_sc(sub);
         sub = Self::make_cas_parents_remove_sc(sub);
+        sub = Self::make_cas_parents_limit_sc(sub);
 
         app.subcommand(sub)
     }
@@ -611,11 +746,22 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_keyroll_finish_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub =
+            SubCommand::with_name("finish").about("Retire the old key of a Trust Anchor after an activated roll");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
     fn make_cas_keyroll_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("keyroll").about("Perform a manual key rollover for a CA");
 
         sub = Self::make_cas_keyroll_init_sc(sub);
         sub = Self::make_cas_keyroll_activate_sc(sub);
+        sub = Self::make_cas_keyroll_finish_sc(sub);
 
         app.subcommand(sub)
     }
@@ -625,17 +771,184 @@ impl Options {
 
         sub = Self::add_general_args(sub);
         sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_roa_metadata_args(sub, false);
 
         app.subcommand(sub)
     }
 
-    fn make_cas_routes_update_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("update").about("Update authorizations");
+    fn make_cas_routes_schedule_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("schedule").about("Show upcoming activations and expirations of ROAs");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_as0_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("show").about("Show the AS0 policy and the resulting AS0 ROAs");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_as0_enable_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("enable").about("Issue AS0 ROAs for held space not covered by any ROA");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_as0_disable_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("disable").about("Stop issuing AS0 ROAs for unrouted space");
 
         sub = Self::add_general_args(sub);
         sub = Self::add_my_ca_arg(sub);
 
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_as0_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("as0").about("Manage AS0 ROAs for held but unrouted space");
+
+        sub = Self::make_cas_routes_as0_show_sc(sub);
+        sub = Self::make_cas_routes_as0_enable_sc(sub);
+        sub = Self::make_cas_routes_as0_disable_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_export_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("export").about("Export the effective VRPs for the configured ROAs");
+
+        sub = Self::add_connection_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
         sub = sub.arg(
+            Arg::with_name(KRILL_CLI_FORMAT_ARG)
+                .short("f")
+                .long(KRILL_CLI_FORMAT_ARG)
+                .value_name("type")
+                .possible_values(&["slurm", "csv", "rpsl", "json"])
+                .default_value("json")
+                .help("Export format")
+                .required(false),
+        );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_import_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("import").about(
+            "Import ROAs for held prefixes from RPSL route and route6 objects, by default only shows the updates",
+        );
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("rpsl")
+                    .long("rpsl")
+                    .value_name("<file>")
+                    .help("File containing RPSL route and route6 objects")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("apply")
+                    .long("apply")
+                    .help("Apply the updates immediately")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("draft")
+                    .long("draft")
+                    .value_name("string")
+                    .help("Stage the updates in the named draft")
+                    .conflicts_with("apply")
+                    .required(false),
+            );
+
+        app.subcommand(sub)
+    }
+
+    /// Adds the arguments for ROA metadata. These are used to set the
+    /// metadata on added ROAs in updates, or to filter the list of ROAs.
+    fn add_roa_metadata_args<'a, 'b>(mut app: App<'a, 'b>, update: bool) -> App<'a, 'b> {
+        app = app.arg(
+            Arg::with_name("owner")
+                .long("owner")
+                .help("The owner (e.g. team) responsible for the ROA")
+                .value_name("<owner>")
+                .required(false),
+        );
+
+        app = app.arg(
+            Arg::with_name("ticket")
+                .long("ticket")
+                .help("The ticket reference for the ROA")
+                .value_name("<ticket>")
+                .required(false),
+        );
+
+        app = app.arg(
+            Arg::with_name("customer")
+                .long("customer")
+                .help("The customer ID for the ROA")
+                .value_name("<customer>")
+                .required(false),
+        );
+
+        if update {
+            app = app.arg(
+                Arg::with_name("not_before")
+                    .long("not-before")
+                    .help("Only add the ROA(s) at this RFC 3339 time, e.g.: 2022-10-18T12:00:00Z")
+                    .value_name("<time>")
+                    .required(false),
+            );
+
+            app = app.arg(
+                Arg::with_name("not_after")
+                    .long("not-after")
+                    .help("Remove the ROA(s) at this RFC 3339 time, e.g.: 2022-10-19T12:00:00Z")
+                    .value_name("<time>")
+                    .required(false),
+            );
+
+            app = app.arg(
+                Arg::with_name("expiry_note")
+                    .long("expiry-note")
+                    .help("A note about when the ROA is expected to be removed")
+                    .value_name("<note>")
+                    .required(false),
+            );
+
+            app = app.arg(
+                Arg::with_name("clear_metadata")
+                    .long("clear-metadata")
+                    .help("Clear the metadata of existing ROA(s). By default existing metadata is kept")
+                    .conflicts_with_all(&["owner", "ticket", "customer", "expiry_note", "tag"])
+                    .required(false),
+            );
+        }
+
+        app.arg(
+            Arg::with_name("tag")
+                .long("tag")
+                .help("One or more tags, e.g.: service=cdn. A filter tag without a value matches any value")
+                .value_name("<key[=value]>")
+                .multiple(true)
+                .required(false),
+        )
+    }
+
+    fn add_roa_updates_args<'a, 'b>(mut app: App<'a, 'b>) -> App<'a, 'b> {
+        app = app.arg(
             Arg::with_name("delta")
                 .long("delta")
                 .help(concat!(
@@ -651,7 +964,7 @@ impl Options {
                 .required(false),
         );
 
-        sub = sub.arg(
+        app = app.arg(
             Arg::with_name("add")
                 .long("add")
                 .help("One or more ROAs to add, e.g.: 192.168.0.0/16 => 64496")
@@ -660,7 +973,7 @@ impl Options {
                 .required(false),
         );
 
-        sub = sub.arg(
+        app = app.arg(
             Arg::with_name("remove")
                 .long("remove")
                 .help("One or more ROAs to remove, e.g.: 192.168.0.0/16 => 64496")
@@ -669,6 +982,16 @@ impl Options {
                 .required(false),
         );
 
+        Self::add_roa_metadata_args(app, true)
+    }
+
+    fn make_cas_routes_update_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("update").about("Update authorizations");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_roa_updates_args(sub);
+
         sub = sub.arg(
             Arg::with_name("dryrun")
                 .long("dryrun")
@@ -731,27 +1054,117 @@ impl Options {
         app.subcommand(sub)
     }
 
-    fn make_cas_routes_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("roas").about("Manage ROAs for a CA");
+    fn add_roa_draft_name_arg<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        app.arg(
+            Arg::with_name("name")
+                .long("name")
+                .short("n")
+                .value_name("string")
+                .help("The name of the draft")
+                .required(true),
+        )
+    }
 
-        sub = Self::make_cas_routes_list_sc(sub);
-        sub = Self::make_cas_routes_update_sc(sub);
-        sub = Self::make_cas_routes_bgp_sc(sub);
+    fn make_cas_routes_drafts_list_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("list").about("List staged drafts");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
 
         app.subcommand(sub)
     }
 
-    fn make_cas_bgpsec_list_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("list").about("Show current BGPSec configurations");
+    fn make_cas_routes_drafts_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("show").about("Show the updates staged in a draft");
 
         sub = Self::add_general_args(sub);
         sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_roa_draft_name_arg(sub);
 
         app.subcommand(sub)
     }
 
-    fn make_cas_bgpsec_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("add").about("Add BGPSec configurations");
+    fn make_cas_routes_drafts_update_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("update").about("Stage updates in a draft, the draft is created if needed");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_roa_draft_name_arg(sub);
+        sub = Self::add_roa_updates_args(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_drafts_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("remove").about("Discard a draft");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_roa_draft_name_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_drafts_dryrun_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("dryrun")
+            .about("Show the BGP analysis for the effect of all updates in a draft, scoped to the updates");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_roa_draft_name_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_drafts_commit_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("commit").about("Apply all updates in a draft as a single update");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_roa_draft_name_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_drafts_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("draft").about("Stage ROA updates in named drafts, and apply them at once");
+
+        sub = Self::make_cas_routes_drafts_list_sc(sub);
+        sub = Self::make_cas_routes_drafts_show_sc(sub);
+        sub = Self::make_cas_routes_drafts_update_sc(sub);
+        sub = Self::make_cas_routes_drafts_remove_sc(sub);
+        sub = Self::make_cas_routes_drafts_dryrun_sc(sub);
+        sub = Self::make_cas_routes_drafts_commit_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_routes_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("roas").about("Manage ROAs for a CA");
+
+        sub = Self::make_cas_routes_list_sc(sub);
+        sub = Self::make_cas_routes_update_sc(sub);
+        sub = Self::make_cas_routes_bgp_sc(sub);
+        sub = Self::make_cas_routes_drafts_sc(sub);
+        sub = Self::make_cas_routes_schedule_sc(sub);
+        sub = Self::make_cas_routes_import_sc(sub);
+        sub = Self::make_cas_routes_export_sc(sub);
+        sub = Self::make_cas_routes_as0_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_bgpsec_list_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("list").about("Show current BGPSec configurations");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_bgpsec_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("add").about("Add BGPSec configurations");
 
         sub = Self::add_general_args(sub);
         sub = Self::add_my_ca_arg(sub);
@@ -776,6 +1189,33 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_bgpsec_generate_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("generate")
+            .about("Generate a router key and BGPSec certificate, and export the encrypted private key once");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("asn")
+                    .short("a")
+                    .long("asn")
+                    .value_name("ASN")
+                    .help("The ASN of the router. E.g. AS65000")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("passphrase")
+                    .long("passphrase")
+                    .value_name("file")
+                    .help("File containing the passphrase used to encrypt the exported PKCS#8 private key")
+                    .required(true),
+            );
+
+        app.subcommand(sub)
+    }
+
     fn make_cas_bgpsec_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("remove").about("Remove a BGPSec definition");
 
@@ -807,11 +1247,144 @@ impl Options {
 
         sub = Self::make_cas_bgpsec_list_sc(sub);
         sub = Self::make_cas_bgpsec_add_sc(sub);
+        sub = Self::make_cas_bgpsec_generate_sc(sub);
         sub = Self::make_cas_bgpsec_remove_sc(sub);
 
         app.subcommand(sub)
     }
 
+    fn make_cas_ghostbuster_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("show").about("Show the current Ghostbuster contact");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_ghostbuster_set_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("set")
+            .about("Set the Ghostbuster contact. At least one of address, tel or email is required");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("full_name")
+                    .long("full-name")
+                    .value_name("name")
+                    .help("The full name of the contact, e.g. the name of a role like 'RPKI Operations'")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("org")
+                    .long("org")
+                    .value_name("organization")
+                    .help("The name of the organization")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("address")
+                    .long("address")
+                    .value_name("address")
+                    .help("The postal address")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("tel")
+                    .long("tel")
+                    .value_name("number")
+                    .help("The telephone number")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("email")
+                    .long("email")
+                    .value_name("email")
+                    .help("The email address")
+                    .required(false),
+            );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_ghostbuster_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("remove").about("Remove the Ghostbuster contact");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_ghostbuster_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("ghostbuster").about("Manage the Ghostbuster contact (RFC 6493)");
+
+        sub = Self::make_cas_ghostbuster_show_sc(sub);
+        sub = Self::make_cas_ghostbuster_set_sc(sub);
+        sub = Self::make_cas_ghostbuster_remove_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    #[cfg(feature = "prefixlist")]
+    fn make_cas_prefix_lists_list_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("list").about("Show current Signed Prefix List definitions");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    #[cfg(feature = "prefixlist")]
+    fn make_cas_prefix_lists_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("add").about("Add or replace the Signed Prefix List for an ASN");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub.arg(
+            Arg::with_name("prefix_list")
+                .long("prefix-list")
+                .help("Prefix list formatted like: AS65000 => 10.0.0.0/16, 2001:db8::/32")
+                .value_name("definition")
+                .required(true),
+        );
+
+        app.subcommand(sub)
+    }
+
+    #[cfg(feature = "prefixlist")]
+    fn make_cas_prefix_lists_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("remove").about("Remove the Signed Prefix List for an ASN");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub.arg(
+            Arg::with_name("asn")
+                .long("asn")
+                .help("ASN for an existing Signed Prefix List definition")
+                .value_name("ASN")
+                .required(true),
+        );
+
+        app.subcommand(sub)
+    }
+
+    #[cfg(feature = "prefixlist")]
+    fn make_cas_prefix_lists_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("prefixlists").about("Manage Signed Prefix Lists for a CA (experimental)");
+
+        sub = Self::make_cas_prefix_lists_list_sc(sub);
+        sub = Self::make_cas_prefix_lists_add_sc(sub);
+        sub = Self::make_cas_prefix_lists_remove_sc(sub);
+
+        app.subcommand(sub)
+    }
+
     #[cfg(feature = "aspa")]
     fn make_cas_aspas_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("add").about("Add or replace an ASPA configuration");
@@ -894,6 +1467,52 @@ impl Options {
         app.subcommand(sub)
     }
 
+    #[cfg(feature = "aspa")]
+    fn make_cas_aspas_import_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("import")
+            .about("Derive ASPAs for held customer ASNs from AS relationships, by default only shows the changes");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("relationships")
+                    .long("relationships")
+                    .value_name("<file>")
+                    .help("File containing CAIDA style AS relationships, e.g. 65001|65000|-1")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("apply")
+                    .long("apply")
+                    .help("Apply the changes immediately")
+                    .required(false),
+            );
+
+        app.subcommand(sub)
+    }
+
+    #[cfg(feature = "aspa")]
+    fn make_cas_aspas_analyze_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("analyze").about("Show full report of ASPAs vs known AS paths");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    #[cfg(feature = "aspa")]
+    fn make_cas_aspas_suggest_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("suggest").about("Show ASPA suggestions based on known AS paths");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
     #[cfg(feature = "aspa")]
     fn make_cas_aspas_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("aspas").about("Manage ASPAs for a CA (experimental)");
@@ -902,6 +1521,9 @@ impl Options {
         sub = Self::make_cas_aspas_remove_sc(sub);
         sub = Self::make_cas_aspas_update_sc(sub);
         sub = Self::make_cas_aspas_list_sc(sub);
+        sub = Self::make_cas_aspas_analyze_sc(sub);
+        sub = Self::make_cas_aspas_suggest_sc(sub);
+        sub = Self::make_cas_aspas_import_sc(sub);
 
         app.subcommand(sub)
     }
@@ -950,6 +1572,48 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_repo_mirror_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("add").about("Also publish the objects of a CA to a mirror repository");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = sub.arg(
+            Arg::with_name("response")
+                .value_name("file")
+                .long("response")
+                .short("r")
+                .help("The location of the RFC 8183 Publisher Response XML file of the mirror")
+                .required(true),
+        );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_repo_mirror_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("remove").about("Stop publishing to a mirror repository");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = sub.arg(
+            Arg::with_name("service_uri")
+                .value_name("uri")
+                .long("service-uri")
+                .help("The RFC 8181 service URI of the mirror repository")
+                .required(true),
+        );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_repo_mirror_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("mirror").about("Manage the mirror repositories for a CA");
+
+        sub = Self::make_cas_repo_mirror_add_sc(sub);
+        sub = Self::make_cas_repo_mirror_remove_sc(sub);
+
+        app.subcommand(sub)
+    }
+
     fn make_cas_repo_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("repo").about("Manage the repository for a CA");
 
@@ -957,6 +1621,7 @@ impl Options {
         sub = Self::make_cas_repo_show_sc(sub);
         sub = Self::make_cas_repo_status_sc(sub);
         sub = Self::make_cas_repo_configure_sc(sub);
+        sub = Self::make_cas_repo_mirror_sc(sub);
 
         app.subcommand(sub)
     }
@@ -970,6 +1635,237 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_shrinks_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("show")
+            .about("Show resources removed by parents, and the effect on ROAs, children and announcements");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_shrinks_clear_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("clear").about("Clear the reports of resources removed by parents");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_objects_list_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub =
+            SubCommand::with_name("list").about("Show all objects published by this CA, with their decoded content");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_objects_download_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("download").about("Save a published object in its DER encoded form");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub.arg(
+            Arg::with_name("key")
+                .long("key")
+                .value_name("key identifier")
+                .help("The key identifier of the key under which the object is published")
+                .required(true),
+        );
+
+        sub = sub.arg(
+            Arg::with_name("name")
+                .long("name")
+                .short("n")
+                .value_name("file name")
+                .help("The file name of the object, e.g. AS65000.roa")
+                .required(true),
+        );
+
+        sub = sub.arg(
+            Arg::with_name("out")
+                .long("out")
+                .short("o")
+                .value_name("path")
+                .help("File to write the object to")
+                .required(true),
+        );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_objects_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("objects").about("Inspect the objects published by this CA");
+
+        sub = Self::make_cas_objects_list_sc(sub);
+        sub = Self::make_cas_objects_download_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_shrinks_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("shrinks").about("Manage reports of resources removed by parents");
+
+        sub = Self::make_cas_shrinks_show_sc(sub);
+        sub = Self::make_cas_shrinks_clear_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_rsc_sign_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("sign").about("Create a Resource Signed Checklist (RFC 9323)");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = Self::add_resource_args(sub);
+
+        sub = sub.arg(
+            Arg::with_name("days")
+                .long("days")
+                .short("d")
+                .value_name("number of days")
+                .help("Validity time of the RSC in days")
+                .required(true),
+        );
+
+        sub = sub.arg(
+            Arg::with_name("file")
+                .long("file")
+                .short("f")
+                .value_name("path")
+                .multiple(true)
+                .number_of_values(1)
+                .help("File to include in the checklist, it is sent to Krill and listed by its file name")
+                .required_unless("digest"),
+        );
+
+        sub = sub.arg(
+            Arg::with_name("digest")
+                .long("digest")
+                .value_name("[name=]hex")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Hex encoded SHA-256 digest to include in the checklist, optionally with a file name")
+                .required(false),
+        );
+
+        sub = sub.arg(
+            Arg::with_name("out")
+                .long("out")
+                .short("o")
+                .value_name("path")
+                .help("File to write the RSC to")
+                .required(true),
+        );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_rsc_verify_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("verify").about("Verify a Resource Signed Checklist");
+
+        sub = Self::add_general_args(sub);
+
+        sub = sub.arg(
+            Arg::with_name("in")
+                .long("in")
+                .short("i")
+                .value_name("path")
+                .help("RSC to verify")
+                .required(true),
+        );
+
+        sub = Self::add_verify_args(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_rsc_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("rsc").about("Sign and verify Resource Signed Checklists");
+        sub = Self::make_cas_rsc_sign_sc(sub);
+        sub = Self::make_cas_rsc_verify_sc(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_cas_geofeed_sign_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("sign").about("Sign a geofeed CSV file (RFC 9632)");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub.arg(
+            Arg::with_name("csv")
+                .long("csv")
+                .value_name("path")
+                .help("The geofeed CSV file to sign, all prefixes in it must be held by the CA")
+                .required(true),
+        );
+
+        sub = sub.arg(
+            Arg::with_name("days")
+                .long("days")
+                .short("d")
+                .value_name("number of days")
+                .help("Validity time of the signature in days")
+                .required(true),
+        );
+
+        sub = sub.arg(
+            Arg::with_name("out")
+                .long("out")
+                .short("o")
+                .value_name("path")
+                .help("File to write the signed geofeed to, it is printed if omitted")
+                .required(false),
+        );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_geofeed_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("geofeed").about("Sign geofeed files");
+        sub = Self::make_cas_geofeed_sign_sc(sub);
+        app.subcommand(sub)
+    }
+
+    /// Adds the trust anchor and certificate arguments for verifying
+    /// RTAs and RSCs.
+    fn add_verify_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        app.arg(
+            Arg::with_name("tal")
+                .long("tal")
+                .value_name("path")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Trust Anchor Locator (TAL) file to verify against")
+                .required_unless("ta_cert"),
+        )
+        .arg(
+            Arg::with_name("ta_cert")
+                .long("ta-cert")
+                .value_name("path")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Trust anchor certificate file to verify against")
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("cert")
+                .long("cert")
+                .value_name("path")
+                .multiple(true)
+                .number_of_values(1)
+                .help("CA certificate file needed to build the certificate path, if not held in Krill")
+                .required(false),
+        )
+    }
+
     #[cfg(feature = "rta")]
     fn make_cas_rta_list<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("list").about("List RTAs");
@@ -1125,6 +2021,26 @@ impl Options {
         app.subcommand(sub)
     }
 
+    #[cfg(feature = "rta")]
+    fn make_cas_rta_verify_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("verify").about("Verify an RTA, e.g. one received from another party");
+
+        sub = Self::add_general_args(sub);
+
+        sub = sub.arg(
+            Arg::with_name("in")
+                .long("in")
+                .short("i")
+                .value_name("path")
+                .help("RTA to verify")
+                .required(true),
+        );
+
+        sub = Self::add_verify_args(sub);
+
+        app.subcommand(sub)
+    }
+
     #[cfg(feature = "rta")]
     fn make_cas_rta_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("rta").about("Manage Resource Tagged Attestations");
@@ -1132,6 +2048,7 @@ impl Options {
         sub = Self::make_cas_rta_show(sub);
         sub = Self::make_cas_rta_sign_sc(sub);
         sub = Self::make_cas_rta_multi_sc(sub);
+        sub = Self::make_cas_rta_verify_sc(sub);
         app.subcommand(sub)
     }
 
@@ -1295,12 +2212,21 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_publication_server_validate_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub =
+            SubCommand::with_name("validate").about("Validate the content of the repository as a relying party would");
+        sub = Options::add_general_args(sub);
+
+        app.subcommand(sub)
+    }
+
     fn make_publication_server_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("server").about("Manage the Publication Server (init/stats)");
         sub = Self::make_publication_server_stats_sc(sub);
         sub = Self::make_publication_server_init_sc(sub);
         sub = Self::make_publication_server_clear_sc(sub);
         sub = Self::make_publication_server_session_reset_sc(sub);
+        sub = Self::make_publication_server_validate_sc(sub);
         app.subcommand(sub)
     }
 
@@ -1327,6 +2253,103 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_ta_proxy_init_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("init").about(
+            "Initialize the proxy for an offline Trust Anchor. Requires that the Publication Server is initialized",
+        );
+        sub = Options::add_general_args(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_ta_proxy_id_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("id")
+            .about("Show the proxy ID and repository, as JSON for initializing the signer with 'krillta init'");
+        sub = Options::add_general_args(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_ta_proxy_status_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("status").about("Show the status of the proxy and the TA objects");
+        sub = Options::add_general_args(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_ta_proxy_children_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("children").about("Manage children of the Trust Anchor");
+
+        let mut add = SubCommand::with_name("add").about("Add a child to the Trust Anchor");
+        add = Self::add_general_args(add);
+        add = Self::add_child_arg(add);
+        add = Self::add_resource_args(add);
+        add = add.arg(
+            Arg::with_name("request")
+                .long("request")
+                .short("r")
+                .help("The location of the RFC 8183 Child Request XML file")
+                .value_name("<XML file>")
+                .required(true),
+        );
+
+        sub = sub.subcommand(add);
+        app.subcommand(sub)
+    }
+
+    fn make_ta_proxy_signer_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("signer").about("Exchange requests and responses with the signer");
+
+        let mut add = SubCommand::with_name("add").about("Associate the signer with the proxy");
+        add = Self::add_general_args(add);
+        add = add.arg(
+            Arg::with_name("info")
+                .long("info")
+                .value_name("file")
+                .help("The location of the signer info JSON file, as shown by 'krillta show'")
+                .required(true),
+        );
+
+        let mut make_request = SubCommand::with_name("make-request")
+            .about("Make a new request for the signer, including any pending child requests");
+        make_request = Self::add_general_args(make_request);
+
+        let mut show_request = SubCommand::with_name("show-request").about("Show the open request for the signer");
+        show_request = Self::add_general_args(show_request);
+
+        let mut process_response =
+            SubCommand::with_name("process-response").about("Process the response from the signer");
+        process_response = Self::add_general_args(process_response);
+        process_response = process_response.arg(
+            Arg::with_name("response")
+                .long("response")
+                .value_name("file")
+                .help("The location of the signer response JSON file")
+                .required(true),
+        );
+
+        sub = sub
+            .subcommand(add)
+            .subcommand(make_request)
+            .subcommand(show_request)
+            .subcommand(process_response);
+
+        app.subcommand(sub)
+    }
+
+    fn make_ta_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut proxy = SubCommand::with_name("proxy").about("Manage the proxy for an offline Trust Anchor");
+
+        proxy = Self::make_ta_proxy_init_sc(proxy);
+        proxy = Self::make_ta_proxy_id_sc(proxy);
+        proxy = Self::make_ta_proxy_status_sc(proxy);
+        proxy = Self::make_ta_proxy_children_sc(proxy);
+        proxy = Self::make_ta_proxy_signer_sc(proxy);
+
+        let sub = SubCommand::with_name("ta")
+            .about("Manage an offline Trust Anchor (only needed if you operate your own TA)")
+            .subcommand(proxy);
+
+        app.subcommand(sub)
+    }
+
     fn make_matches<'a>() -> ArgMatches<'a> {
         let mut app = App::new(KRILL_CLIENT_APP).version(KRILL_VERSION);
 
@@ -1341,15 +2364,28 @@ impl Options {
         app = Self::make_cas_keyroll_sc(app);
         app = Self::make_cas_routes_sc(app);
         app = Self::make_cas_bgpsec_sc(app);
+        app = Self::make_cas_ghostbuster_sc(app);
         app = Self::make_cas_repo_sc(app);
+        app = Self::make_cas_objects_sc(app);
         app = Self::make_cas_issues_sc(app);
+        app = Self::make_cas_shrinks_sc(app);
         app = Self::make_pubserver_sc(app);
+        app = Self::make_ta_sc(app);
 
         #[cfg(feature = "aspa")]
         {
             app = Self::make_cas_aspas_sc(app);
         }
 
+        #[cfg(feature = "prefixlist")]
+        {
+            app = Self::make_cas_prefix_lists_sc(app);
+        }
+
+        app = Self::make_cas_rsc_sc(app);
+
+        app = Self::make_cas_geofeed_sc(app);
+
         #[cfg(feature = "rta")]
         {
             app = Self::make_cas_rta_sc(app);
@@ -1664,6 +2700,55 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_cas_children_tokens_list(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::OnboardingTokenList(my_ca));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_children_tokens_add(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let resources = Self::parse_resource_args(matches)?.ok_or(Error::MissingResources)?;
+
+        let days = match matches.value_of("days") {
+            Some(days) => i64::from_str(days)
+                .map_err(|e| Error::GeneralArgumentError(format!("Invalid number of days: {}", e)))?,
+            None => 7,
+        };
+        let expires = Timestamp::now_plus_hours(days * 24);
+
+        let request = OnboardingTokenRequest::new(resources, expires);
+        let command = Command::CertAuth(CaCommand::OnboardingTokenAdd(my_ca, request));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_children_tokens_remove(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let id = matches.value_of("id").unwrap();
+        let id = OnboardingTokenId::from_str(id).map_err(Error::GeneralArgumentError)?;
+
+        let command = Command::CertAuth(CaCommand::OnboardingTokenRemove(my_ca, id));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_children_tokens(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("list") {
+            Self::parse_matches_cas_children_tokens_list(m)
+        } else if let Some(m) = matches.subcommand_matches("add") {
+            Self::parse_matches_cas_children_tokens_add(m)
+        } else if let Some(m) = matches.subcommand_matches("remove") {
+            Self::parse_matches_cas_children_tokens_remove(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
     fn parse_matches_cas_children(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("add") {
             Self::parse_matches_cas_children_add(m)
@@ -1681,6 +2766,8 @@ impl Options {
             Self::parse_matches_cas_children_suspend(m)
         } else if let Some(m) = matches.subcommand_matches("unsuspend") {
             Self::parse_matches_cas_children_unsuspend(m)
+        } else if let Some(m) = matches.subcommand_matches("tokens") {
+            Self::parse_matches_cas_children_tokens(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -1711,6 +2798,18 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_cas_parents_onboard(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let bytes = Self::read_file_arg(matches.value_of("token").unwrap())?;
+        let token: OnboardingToken = serde_json::from_slice(bytes.as_ref())
+            .map_err(|e| Error::GeneralArgumentError(format!("Invalid onboarding token: {}", e)))?;
+
+        let command = Command::CertAuth(CaCommand::ParentOnboard(my_ca, token));
+        Ok(Options::make(general_args, command))
+    }
+
     fn parse_matches_cas_parents_info(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
@@ -1739,17 +2838,64 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_cas_parents_limit_show(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let parent = matches.value_of("parent").unwrap();
+        let parent = ParentHandle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
+
+        let command = Command::CertAuth(CaCommand::ParentResourceLimitShow(my_ca, parent));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_parents_limit_set(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let parent = matches.value_of("parent").unwrap();
+        let parent = ParentHandle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
+        let limit = Self::parse_resource_args(matches)?.ok_or(Error::MissingResources)?;
+
+        let command = Command::CertAuth(CaCommand::ParentResourceLimitSet(my_ca, parent, limit));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_parents_limit_remove(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let parent = matches.value_of("parent").unwrap();
+        let parent = ParentHandle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
+
+        let command = Command::CertAuth(CaCommand::ParentResourceLimitRemove(my_ca, parent));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_parents_limit(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("show") {
+            Self::parse_matches_cas_parents_limit_show(m)
+        } else if let Some(m) = matches.subcommand_matches("set") {
+            Self::parse_matches_cas_parents_limit_set(m)
+        } else if let Some(m) = matches.subcommand_matches("remove") {
+            Self::parse_matches_cas_parents_limit_remove(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
     fn parse_matches_cas_parents(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("request") {
             Self::parse_matches_cas_parents_request(m)
         } else if let Some(m) = matches.subcommand_matches("add") {
             Self::parse_matches_cas_parents_add(m)
+        } else if let Some(m) = matches.subcommand_matches("onboard") {
+            Self::parse_matches_cas_parents_onboard(m)
         } else if let Some(m) = matches.subcommand_matches("contact") {
             Self::parse_matches_cas_parents_info(m)
         } else if let Some(m) = matches.subcommand_matches("statuses") {
             Self::parse_matches_cas_parents_statuses(m)
         } else if let Some(m) = matches.subcommand_matches("remove") {
             Self::parse_matches_cas_parents_remove(m)
+        } else if let Some(m) = matches.subcommand_matches("limit") {
+            Self::parse_matches_cas_parents_limit(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -1773,11 +2919,22 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_cas_keyroll_finish(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::KeyRollFinish(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
     fn parse_matches_cas_keyroll(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("init") {
             Self::parse_matches_cas_keyroll_init(m)
         } else if let Some(m) = matches.subcommand_matches("activate") {
             Self::parse_matches_cas_keyroll_activate(m)
+        } else if let Some(m) = matches.subcommand_matches("finish") {
+            Self::parse_matches_cas_keyroll_finish(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -1787,15 +2944,14 @@ impl Options {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let command = Command::CertAuth(CaCommand::RouteAuthorizationsList(my_ca));
+        let filter = Self::parse_roa_filter(matches)?;
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsList(my_ca, filter));
 
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_routes_update(matches: &ArgMatches) -> Result<Options, Error> {
-        let general_args = GeneralArgs::from_matches(matches)?;
-        let my_ca = Self::parse_my_ca(matches)?;
-
+    fn parse_roa_updates(matches: &ArgMatches) -> Result<RoaConfigurationUpdates, Error> {
         let updates = if let Some(path) = matches.value_of("delta") {
             if matches.is_present("add") || matches.is_present("remove") {
                 return Err(Error::general("Cannot use --add or --remove if --delta is specified"));
@@ -1831,6 +2987,63 @@ impl Options {
             RoaConfigurationUpdates::new(added, removed)
         };
 
+        let metadata = Self::parse_roa_metadata(matches)?;
+        let updates = if metadata.is_empty() && !matches.is_present("clear_metadata") {
+            updates
+        } else {
+            updates.with_metadata(metadata)
+        };
+
+        let not_before = Self::parse_timestamp_arg(matches, "not_before")?;
+        let not_after = Self::parse_timestamp_arg(matches, "not_after")?;
+        if not_before.is_some() || not_after.is_some() {
+            Ok(updates.with_time_bounds(not_before, not_after))
+        } else {
+            Ok(updates)
+        }
+    }
+
+    fn parse_timestamp_arg(matches: &ArgMatches, name: &str) -> Result<Option<Timestamp>, Error> {
+        match matches.value_of(name) {
+            None => Ok(None),
+            Some(time) => Timestamp::from_str(time)
+                .map(Some)
+                .map_err(|e| Error::general(&format!("Invalid RFC 3339 time '{}': {}", time, e))),
+        }
+    }
+
+    fn parse_roa_metadata(matches: &ArgMatches) -> Result<RoaMetadata, Error> {
+        let mut metadata = RoaMetadata::default();
+        metadata.set_owner(matches.value_of("owner").map(|s| s.to_string()));
+        metadata.set_ticket(matches.value_of("ticket").map(|s| s.to_string()));
+        metadata.set_customer(matches.value_of("customer").map(|s| s.to_string()));
+        metadata.set_expiry_note(matches.value_of("expiry_note").map(|s| s.to_string()));
+        if let Some(tags) = matches.values_of("tag") {
+            for tag in tags {
+                metadata.add_tag_str(tag)?;
+            }
+        }
+        Ok(metadata)
+    }
+
+    fn parse_roa_filter(matches: &ArgMatches) -> Result<RoaConfigurationFilter, Error> {
+        let mut filter = RoaConfigurationFilter::default();
+        filter.set_owner(matches.value_of("owner").map(|s| s.to_string()));
+        filter.set_ticket(matches.value_of("ticket").map(|s| s.to_string()));
+        filter.set_customer(matches.value_of("customer").map(|s| s.to_string()));
+        if let Some(tags) = matches.values_of("tag") {
+            for tag in tags {
+                filter.add_tag_str(tag)?;
+            }
+        }
+        Ok(filter)
+    }
+
+    fn parse_matches_cas_routes_update(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let updates = Self::parse_roa_updates(matches)?;
+
         if matches.is_present("dryrun") && matches.is_present("try") {
             return Err(Error::general("You cannot use both --dryrun and --try"));
         }
@@ -1883,6 +3096,154 @@ impl Options {
         }
     }
 
+    fn parse_matches_cas_routes_drafts_list(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsDraftList(my_ca));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_drafts_show(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let name = matches.value_of("name").unwrap().to_string();
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsDraftShow(my_ca, name));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_drafts_update(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let name = matches.value_of("name").unwrap().to_string();
+        let updates = Self::parse_roa_updates(matches)?;
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsDraftUpdate(my_ca, name, updates));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_drafts_remove(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let name = matches.value_of("name").unwrap().to_string();
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsDraftRemove(my_ca, name));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_drafts_dryrun(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let name = matches.value_of("name").unwrap().to_string();
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsDraftDryRun(my_ca, name));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_drafts_commit(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let name = matches.value_of("name").unwrap().to_string();
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsDraftCommit(my_ca, name));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_drafts(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("list") {
+            Self::parse_matches_cas_routes_drafts_list(m)
+        } else if let Some(m) = matches.subcommand_matches("show") {
+            Self::parse_matches_cas_routes_drafts_show(m)
+        } else if let Some(m) = matches.subcommand_matches("update") {
+            Self::parse_matches_cas_routes_drafts_update(m)
+        } else if let Some(m) = matches.subcommand_matches("remove") {
+            Self::parse_matches_cas_routes_drafts_remove(m)
+        } else if let Some(m) = matches.subcommand_matches("dryrun") {
+            Self::parse_matches_cas_routes_drafts_dryrun(m)
+        } else if let Some(m) = matches.subcommand_matches("commit") {
+            Self::parse_matches_cas_routes_drafts_commit(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
+    fn parse_matches_cas_routes_schedule(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsSchedule(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_export(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches_with_format(matches, ReportFormat::Text)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let format = VrpExportFormat::from_str(matches.value_of(KRILL_CLI_FORMAT_ARG).unwrap())
+            .map_err(Error::GeneralArgumentError)?;
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsExport(my_ca, format));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_import(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let path = matches.value_of("rpsl").unwrap();
+        let bytes = Self::read_file_arg(path)?;
+        let rpsl = String::from_utf8(bytes.to_vec())
+            .map_err(|_| Error::GeneralArgumentError(format!("File '{}' does not contain valid UTF-8", path)))?;
+
+        let action = if matches.is_present("apply") {
+            RpslImportAction::Apply
+        } else if let Some(name) = matches.value_of("draft") {
+            RpslImportAction::Stage(name.to_string())
+        } else {
+            RpslImportAction::Propose
+        };
+
+        let request = RpslImportRequest::new(rpsl, action);
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsImportRpsl(my_ca, request));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_as0_show(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsAs0Status(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_as0_policy(matches: &ArgMatches, enabled: bool) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsAs0Policy(
+            my_ca,
+            RoaAs0Policy::new(enabled),
+        ));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_as0(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("show") {
+            Self::parse_matches_cas_routes_as0_show(m)
+        } else if let Some(m) = matches.subcommand_matches("enable") {
+            Self::parse_matches_cas_routes_as0_policy(m, true)
+        } else if let Some(m) = matches.subcommand_matches("disable") {
+            Self::parse_matches_cas_routes_as0_policy(m, false)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
     fn parse_matches_cas_routes(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("list") {
             Self::parse_matches_cas_routes_list(m)
@@ -1890,6 +3251,16 @@ impl Options {
             Self::parse_matches_cas_routes_update(m)
         } else if let Some(m) = matches.subcommand_matches("bgp") {
             Self::parse_matches_cas_routes_bgp(m)
+        } else if let Some(m) = matches.subcommand_matches("draft") {
+            Self::parse_matches_cas_routes_drafts(m)
+        } else if let Some(m) = matches.subcommand_matches("schedule") {
+            Self::parse_matches_cas_routes_schedule(m)
+        } else if let Some(m) = matches.subcommand_matches("import") {
+            Self::parse_matches_cas_routes_import(m)
+        } else if let Some(m) = matches.subcommand_matches("export") {
+            Self::parse_matches_cas_routes_export(m)
+        } else if let Some(m) = matches.subcommand_matches("as0") {
+            Self::parse_matches_cas_routes_as0(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -1923,37 +3294,151 @@ impl Options {
             Error::GeneralArgumentError(format!("CSR in file '{}' is not valid. Error: {}", csr_file, e))
         })?;
 
-        let definition = BgpSecDefinition::new(asn, csr);
+        let definition = BgpSecDefinition::new(asn, csr);
+
+        let command = Command::CertAuth(CaCommand::BgpSecAdd(my_ca, definition));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_bgpsec_generate(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let asn_str = matches.value_of("asn").unwrap();
+        let asn = Asn::from_str(asn_str).map_err(|_| Error::invalid_asn(asn_str))?;
+
+        let path = matches.value_of("passphrase").unwrap();
+        let bytes = Self::read_file_arg(path)?;
+        let passphrase = String::from_utf8(bytes.to_vec())
+            .map_err(|_| Error::GeneralArgumentError(format!("File '{}' does not contain valid UTF-8", path)))?;
+        let passphrase = passphrase.trim_end_matches(&['\r', '\n'][..]).to_string();
+
+        let request = BgpSecRouterKeyRequest::new(asn, passphrase);
+        let command = Command::CertAuth(CaCommand::BgpSecGenerate(my_ca, request));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_bgpsec_remove(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let asn_str = matches.value_of("asn").unwrap();
+        let asn = Asn::from_str(asn_str).map_err(|_| Error::invalid_asn(asn_str))?;
+
+        let key_str = matches.value_of("key").unwrap();
+        let key = KeyIdentifier::from_str(key_str).map_err(|_| Error::general("Cannot parse key identifier"))?;
+
+        let definition = BgpSecAsnKey::new(asn, key);
+
+        let command = Command::CertAuth(CaCommand::BgpSecRemove(my_ca, definition));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_bgpsec(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("list") {
+            Self::parse_matches_cas_bgpsec_list(m)
+        } else if let Some(m) = matches.subcommand_matches("add") {
+            Self::parse_matches_cas_bgpsec_add(m)
+        } else if let Some(m) = matches.subcommand_matches("generate") {
+            Self::parse_matches_cas_bgpsec_generate(m)
+        } else if let Some(m) = matches.subcommand_matches("remove") {
+            Self::parse_matches_cas_bgpsec_remove(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
+    fn parse_matches_cas_ghostbuster_show(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::GhostbusterShow(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_ghostbuster_set(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let full_name = matches.value_of("full_name").unwrap().to_string();
+        let org = matches.value_of("org").map(|s| s.to_string());
+        let address = matches.value_of("address").map(|s| s.to_string());
+        let tel = matches.value_of("tel").map(|s| s.to_string());
+        let email = matches.value_of("email").map(|s| s.to_string());
+
+        let contact = GhostbusterContact::new(full_name, org, address, tel, email);
+        contact.validate().map_err(Error::GeneralArgumentError)?;
+
+        let command = Command::CertAuth(CaCommand::GhostbusterSet(my_ca, contact));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_ghostbuster_remove(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::GhostbusterRemove(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_ghostbuster(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("show") {
+            Self::parse_matches_cas_ghostbuster_show(m)
+        } else if let Some(m) = matches.subcommand_matches("set") {
+            Self::parse_matches_cas_ghostbuster_set(m)
+        } else if let Some(m) = matches.subcommand_matches("remove") {
+            Self::parse_matches_cas_ghostbuster_remove(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
+    fn parse_matches_cas_prefix_lists_list(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::PrefixListsList(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_prefix_lists_add(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let definition_str = matches.value_of("prefix_list").unwrap(); // required argument
+        let definition = PrefixListDefinition::from_str(definition_str)?;
 
-        let command = Command::CertAuth(CaCommand::BgpSecAdd(my_ca, definition));
+        let command = Command::CertAuth(CaCommand::PrefixListsAddOrReplace(my_ca, definition));
 
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_bgpsec_remove(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_prefix_lists_remove(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let asn_str = matches.value_of("asn").unwrap();
+        let asn_str = matches.value_of("asn").unwrap(); // required argument
         let asn = Asn::from_str(asn_str).map_err(|_| Error::invalid_asn(asn_str))?;
 
-        let key_str = matches.value_of("key").unwrap();
-        let key = KeyIdentifier::from_str(key_str).map_err(|_| Error::general("Cannot parse key identifier"))?;
-
-        let definition = BgpSecAsnKey::new(asn, key);
-
-        let command = Command::CertAuth(CaCommand::BgpSecRemove(my_ca, definition));
+        let command = Command::CertAuth(CaCommand::PrefixListsRemove(my_ca, asn));
 
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_bgpsec(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_prefix_lists(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("list") {
-            Self::parse_matches_cas_bgpsec_list(m)
+            Self::parse_matches_cas_prefix_lists_list(m)
         } else if let Some(m) = matches.subcommand_matches("add") {
-            Self::parse_matches_cas_bgpsec_add(m)
+            Self::parse_matches_cas_prefix_lists_add(m)
         } else if let Some(m) = matches.subcommand_matches("remove") {
-            Self::parse_matches_cas_bgpsec_remove(m)
+            Self::parse_matches_cas_prefix_lists_remove(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -2031,6 +3516,45 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_cas_aspas_import(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let path = matches.value_of("relationships").unwrap();
+        let bytes = Self::read_file_arg(path)?;
+        let relationships = String::from_utf8(bytes.to_vec())
+            .map_err(|_| Error::GeneralArgumentError(format!("File '{}' does not contain valid UTF-8", path)))?;
+
+        let action = if matches.is_present("apply") {
+            AspaImportAction::Apply
+        } else {
+            AspaImportAction::Propose
+        };
+
+        let request = AspaImportRequest::new(relationships, action);
+        let command = Command::CertAuth(CaCommand::AspasImport(my_ca, request));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_aspas_analyze(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::AspasAnalysis(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_aspas_suggest(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::AspasSuggest(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
     fn parse_matches_cas_aspas(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("add") {
             Self::parse_matches_cas_aspas_add(m)
@@ -2040,6 +3564,12 @@ impl Options {
             Self::parse_matches_cas_aspas_update(m)
         } else if let Some(m) = matches.subcommand_matches("list") {
             Self::parse_matches_cas_aspas_list(m)
+        } else if let Some(m) = matches.subcommand_matches("analyze") {
+            Self::parse_matches_cas_aspas_analyze(m)
+        } else if let Some(m) = matches.subcommand_matches("suggest") {
+            Self::parse_matches_cas_aspas_suggest(m)
+        } else if let Some(m) = matches.subcommand_matches("import") {
+            Self::parse_matches_cas_aspas_import(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -2085,6 +3615,41 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_cas_repo_mirror_add(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let path = matches.value_of("response").unwrap();
+        let bytes = Self::read_file_arg(path)?;
+        let response = idexchange::RepositoryResponse::parse(bytes.as_ref())?;
+
+        let command = Command::CertAuth(CaCommand::RepoMirrorAdd(my_ca, response));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_repo_mirror_remove(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let service_uri = matches.value_of("service_uri").unwrap();
+        let service_uri = idexchange::ServiceUri::from_str(service_uri)?;
+
+        let command = Command::CertAuth(CaCommand::RepoMirrorRemove(my_ca, service_uri));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_repo_mirror(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("add") {
+            Self::parse_matches_cas_repo_mirror_add(m)
+        } else if let Some(m) = matches.subcommand_matches("remove") {
+            Self::parse_matches_cas_repo_mirror_remove(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
     fn parse_matches_cas_repo(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("request") {
             Self::parse_matches_cas_repo_request(m)
@@ -2094,6 +3659,8 @@ impl Options {
             Self::parse_matches_cas_repo_status(m)
         } else if let Some(m) = matches.subcommand_matches("configure") {
             Self::parse_matches_cas_repo_configure(m)
+        } else if let Some(m) = matches.subcommand_matches("mirror") {
+            Self::parse_matches_cas_repo_mirror(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -2109,6 +3676,79 @@ impl Options {
         Ok(Options::make(general, command))
     }
 
+    fn parse_matches_cas_shrinks_show(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::ResourceShrinks(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_shrinks_clear(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::ResourceShrinksClear(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_objects_list(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::PublishedObjects(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_objects_download(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let key_str = matches.value_of("key").unwrap();
+        let key = KeyIdentifier::from_str(key_str).map_err(|_| Error::general("Invalid key identifier"))?;
+
+        let name = ObjectName::from(matches.value_of("name").unwrap());
+
+        let out_file = matches.value_of("out").unwrap();
+        let out_file = PathBuf::from_str(out_file)
+            .map_err(|_| Error::GeneralArgumentError(format!("Invalid filename: {}", out_file)))?;
+
+        file::save(&[], &out_file).map_err(|e| {
+            Error::GeneralArgumentError(format!(
+                "Cannot save to file: {}, error: {}",
+                out_file.to_string_lossy(),
+                e
+            ))
+        })?;
+
+        let command = Command::CertAuth(CaCommand::PublishedObjectDownload(my_ca, key, name, out_file));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_objects(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("list") {
+            Self::parse_matches_cas_objects_list(m)
+        } else if let Some(m) = matches.subcommand_matches("download") {
+            Self::parse_matches_cas_objects_download(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
+    fn parse_matches_cas_shrinks(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("show") {
+            Self::parse_matches_cas_shrinks_show(m)
+        } else if let Some(m) = matches.subcommand_matches("clear") {
+            Self::parse_matches_cas_shrinks_clear(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
     fn parse_matches_cas_rta_list(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let ca = Self::parse_my_ca(matches)?;
@@ -2224,6 +3864,129 @@ impl Options {
         }
     }
 
+    fn parse_matches_cas_rsc_sign(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let ca = Self::parse_my_ca(matches)?;
+
+        let days = matches.value_of("days").unwrap();
+        let days =
+            i64::from_str(days).map_err(|e| Error::GeneralArgumentError(format!("Invalid number of days: {}", e)))?;
+        let validity = SignSupport::sign_validity_days(days);
+
+        let resources = Self::parse_resource_args(matches)?
+            .ok_or_else(|| Error::general("You must specify at least one of --ipv4, --ipv6 or --asn"))?;
+
+        let mut checklist = vec![];
+
+        if let Some(files) = matches.values_of("file") {
+            for path in files {
+                let content = Self::read_file_arg(path)?;
+                let file_name = PathBuf::from(path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string());
+                checklist.push(RscChecklistItem::for_content(file_name, content));
+            }
+        }
+
+        if let Some(digests) = matches.values_of("digest") {
+            for digest in digests {
+                let item = match digest.split_once('=') {
+                    Some((name, hex)) => RscChecklistItem::for_digest(Some(name.to_string()), hex.to_string()),
+                    None => RscChecklistItem::for_digest(None, digest.to_string()),
+                };
+                checklist.push(item);
+            }
+        }
+
+        let out_file = matches.value_of("out").unwrap();
+        let out_file = PathBuf::from_str(out_file)
+            .map_err(|_| Error::GeneralArgumentError(format!("Invalid filename: {}", out_file)))?;
+
+        let request = RscRequest::new(resources, validity, checklist);
+        let command = Command::CertAuth(CaCommand::RscSign(ca, request, out_file));
+        Ok(Options::make(general_args, command))
+    }
+
+    /// Parses the object to verify and the trust anchors and certificates
+    /// to verify it with.
+    fn parse_matches_verify(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+
+        let object = Self::read_file_arg(matches.value_of("in").unwrap())?;
+
+        let mut tals = vec![];
+        if let Some(paths) = matches.values_of("tal") {
+            for path in paths {
+                let tal = Self::read_file_arg(path)?;
+                let tal = String::from_utf8(tal.to_vec())
+                    .map_err(|_| Error::GeneralArgumentError(format!("Invalid TAL file: {}", path)))?;
+                tals.push(tal);
+            }
+        }
+
+        let mut ta_certs = vec![];
+        if let Some(paths) = matches.values_of("ta_cert") {
+            for path in paths {
+                ta_certs.push(Self::read_file_arg(path)?);
+            }
+        }
+
+        let mut certs = vec![];
+        if let Some(paths) = matches.values_of("cert") {
+            for path in paths {
+                certs.push(Self::read_file_arg(path)?);
+            }
+        }
+
+        let request = SignedObjectVerifyRequest::new(object, tals, ta_certs, certs);
+        let command = Command::Verify(request);
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_rsc(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("sign") {
+            Self::parse_matches_cas_rsc_sign(m)
+        } else if let Some(m) = matches.subcommand_matches("verify") {
+            Self::parse_matches_verify(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
+    fn parse_matches_cas_geofeed_sign(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let ca = Self::parse_my_ca(matches)?;
+
+        let csv = Self::read_file_arg(matches.value_of("csv").unwrap())?;
+        let csv = String::from_utf8(csv.to_vec())
+            .map_err(|_| Error::general("The geofeed CSV file must be UTF-8 encoded"))?;
+
+        let days = matches.value_of("days").unwrap();
+        let days =
+            i64::from_str(days).map_err(|e| Error::GeneralArgumentError(format!("Invalid number of days: {}", e)))?;
+        let validity = SignSupport::sign_validity_days(days);
+
+        let out_file = match matches.value_of("out") {
+            Some(out_file) => Some(
+                PathBuf::from_str(out_file)
+                    .map_err(|_| Error::GeneralArgumentError(format!("Invalid filename: {}", out_file)))?,
+            ),
+            None => None,
+        };
+
+        let request = GeofeedSignRequest::new(validity, csv);
+        let command = Command::CertAuth(CaCommand::GeofeedSign(ca, request, out_file));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_geofeed(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("sign") {
+            Self::parse_matches_cas_geofeed_sign(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
     fn parse_matches_cas_rta(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("list") {
             Self::parse_matches_cas_rta_list(m)
@@ -2233,6 +3996,8 @@ impl Options {
             Self::parse_matches_cas_rta_sign(m)
         } else if let Some(m) = matches.subcommand_matches("multi") {
             Self::parse_matches_cas_rta_multi(m)
+        } else if let Some(m) = matches.subcommand_matches("verify") {
+            Self::parse_matches_verify(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -2372,6 +4137,12 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_publication_server_validate(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let command = Command::PubServer(PubServerCommand::RepositoryValidate);
+        Ok(Options::make(general_args, command))
+    }
+
     fn parse_matches_publication_server(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("stats") {
             Self::parse_matches_publication_server_stats(m)
@@ -2381,6 +4152,8 @@ impl Options {
             Self::parse_matches_publication_server_clear(m)
         } else if let Some(m) = matches.subcommand_matches("session-reset") {
             Self::parse_matches_publication_server_server_reset(m)
+        } else if let Some(m) = matches.subcommand_matches("validate") {
+            Self::parse_matches_publication_server_validate(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -2414,6 +4187,92 @@ impl Options {
         }
     }
 
+    fn parse_matches_ta_proxy_children_add(matches: &ArgMatches) -> Result<Options, Error> {
+        let path = matches.value_of("request").unwrap();
+        let bytes = Self::read_file_arg(path)?;
+        let child_request = idexchange::ChildRequest::parse(bytes.as_ref())?;
+
+        let general_args = GeneralArgs::from_matches(matches)?;
+
+        let child = matches.value_of("child").unwrap();
+        let child = ChildHandle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+
+        let resources = Self::parse_resource_args(matches)?.ok_or(Error::MissingResources)?;
+
+        let id_cert = child_request.validate()?;
+        let add_child_request = AddChildRequest::new(child, resources, id_cert);
+        let command = Command::TrustAnchor(TrustAnchorCommand::ProxyChildAdd(add_child_request));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_ta_proxy_signer_add(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+
+        let bytes = Self::read_file_arg(matches.value_of("info").unwrap())?;
+        let info: TrustAnchorSignerInfo = serde_json::from_slice(bytes.as_ref())
+            .map_err(|e| Error::GeneralArgumentError(format!("Invalid signer info: {}", e)))?;
+
+        let command = Command::TrustAnchor(TrustAnchorCommand::ProxySignerAdd(info));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_ta_proxy_signer_process_response(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+
+        let bytes = Self::read_file_arg(matches.value_of("response").unwrap())?;
+        let response: TrustAnchorSignedMessage = serde_json::from_slice(bytes.as_ref())
+            .map_err(|e| Error::GeneralArgumentError(format!("Invalid signer response: {}", e)))?;
+
+        let command = Command::TrustAnchor(TrustAnchorCommand::ProxySignerProcessResponse(response));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_ta_proxy_simple(matches: &ArgMatches, command: TrustAnchorCommand) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        Ok(Options::make(general_args, Command::TrustAnchor(command)))
+    }
+
+    fn parse_matches_ta_proxy_signer(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("add") {
+            Self::parse_matches_ta_proxy_signer_add(m)
+        } else if let Some(m) = matches.subcommand_matches("make-request") {
+            Self::parse_matches_ta_proxy_simple(m, TrustAnchorCommand::ProxySignerMakeRequest)
+        } else if let Some(m) = matches.subcommand_matches("show-request") {
+            Self::parse_matches_ta_proxy_simple(m, TrustAnchorCommand::ProxySignerShowRequest)
+        } else if let Some(m) = matches.subcommand_matches("process-response") {
+            Self::parse_matches_ta_proxy_signer_process_response(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
+    fn parse_matches_ta_proxy(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("init") {
+            Self::parse_matches_ta_proxy_simple(m, TrustAnchorCommand::ProxyInit)
+        } else if let Some(m) = matches.subcommand_matches("id") {
+            Self::parse_matches_ta_proxy_simple(m, TrustAnchorCommand::ProxyId)
+        } else if let Some(m) = matches.subcommand_matches("status") {
+            Self::parse_matches_ta_proxy_simple(m, TrustAnchorCommand::ProxyStatus)
+        } else if let Some(m) = matches.subcommand_matches("children") {
+            match m.subcommand_matches("add") {
+                Some(m) => Self::parse_matches_ta_proxy_children_add(m),
+                None => Err(Error::UnrecognizedSubCommand),
+            }
+        } else if let Some(m) = matches.subcommand_matches("signer") {
+            Self::parse_matches_ta_proxy_signer(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
+    fn parse_matches_ta(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("proxy") {
+            Self::parse_matches_ta_proxy(m)
+        } else {
+            Err(Error::UnrecognizedSubCommand)
+        }
+    }
+
     fn parse_matches(matches: ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("config") {
             Self::parse_matches_config(m)
@@ -2437,12 +4296,24 @@ impl Options {
             Self::parse_matches_cas_routes(m)
         } else if let Some(m) = matches.subcommand_matches("bgpsec") {
             Self::parse_matches_cas_bgpsec(m)
+        } else if let Some(m) = matches.subcommand_matches("ghostbuster") {
+            Self::parse_matches_cas_ghostbuster(m)
         } else if let Some(m) = matches.subcommand_matches("aspas") {
             Self::parse_matches_cas_aspas(m)
+        } else if let Some(m) = matches.subcommand_matches("prefixlists") {
+            Self::parse_matches_cas_prefix_lists(m)
         } else if let Some(m) = matches.subcommand_matches("repo") {
             Self::parse_matches_cas_repo(m)
+        } else if let Some(m) = matches.subcommand_matches("objects") {
+            Self::parse_matches_cas_objects(m)
         } else if let Some(m) = matches.subcommand_matches("issues") {
             Self::parse_matches_cas_issues(m)
+        } else if let Some(m) = matches.subcommand_matches("shrinks") {
+            Self::parse_matches_cas_shrinks(m)
+        } else if let Some(m) = matches.subcommand_matches("rsc") {
+            Self::parse_matches_cas_rsc(m)
+        } else if let Some(m) = matches.subcommand_matches("geofeed") {
+            Self::parse_matches_cas_geofeed(m)
         } else if let Some(m) = matches.subcommand_matches("rta") {
             Self::parse_matches_cas_rta(m)
         } else if let Some(m) = matches.subcommand_matches("bulk") {
@@ -2453,6 +4324,8 @@ impl Options {
             Self::parse_matches_info(m)
         } else if let Some(m) = matches.subcommand_matches("pubserver") {
             Self::parse_matches_pubserver(m)
+        } else if let Some(m) = matches.subcommand_matches("ta") {
+            Self::parse_matches_ta(m)
         } else {
             Err(Error::UnrecognizedSubCommand)
         }
@@ -2473,9 +4346,11 @@ pub enum Command {
     Bulk(BulkCaCommand),
     CertAuth(CaCommand),
     PubServer(PubServerCommand),
+    TrustAnchor(TrustAnchorCommand),
     Init(KrillInitDetails),
     #[cfg(feature = "multi-user")]
     User(KrillUserDetails),
+    Verify(SignedObjectVerifyRequest),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -2490,13 +4365,19 @@ pub enum CaCommand {
     RepoDetails(CaHandle),
     RepoUpdate(CaHandle, idexchange::RepositoryResponse),
     RepoStatus(CaHandle),
+    RepoMirrorAdd(CaHandle, idexchange::RepositoryResponse),
+    RepoMirrorRemove(CaHandle, idexchange::ServiceUri),
 
     // Parents (to this CA)
     ChildRequest(CaHandle), // Get the RFC 8183 Child Request
     AddParent(CaHandle, ParentCaReq),
+    ParentOnboard(CaHandle, OnboardingToken), // Use an onboarding token from the parent
     MyParentCaContact(CaHandle, ParentHandle),
     ParentStatuses(CaHandle),
     RemoveParent(CaHandle, ParentHandle),
+    ParentResourceLimitShow(CaHandle, ParentHandle),
+    ParentResourceLimitSet(CaHandle, ParentHandle, ResourceSet),
+    ParentResourceLimitRemove(CaHandle, ParentHandle),
     Refresh(CaHandle), // Refresh with all parents
 
     // Children
@@ -2506,36 +4387,81 @@ pub enum CaCommand {
     ChildUpdate(CaHandle, ChildHandle, UpdateChildRequest),
     ChildDelete(CaHandle, ChildHandle),
     ChildConnections(CaHandle),
+    OnboardingTokenList(CaHandle),
+    OnboardingTokenAdd(CaHandle, OnboardingTokenRequest),
+    OnboardingTokenRemove(CaHandle, OnboardingTokenId),
 
     // Key Management
     KeyRollInit(CaHandle),
     KeyRollActivate(CaHandle),
+    KeyRollFinish(CaHandle),
 
     // Authorizations
-    RouteAuthorizationsList(CaHandle),
+    RouteAuthorizationsList(CaHandle, RoaConfigurationFilter),
     RouteAuthorizationsUpdate(CaHandle, RoaConfigurationUpdates),
     RouteAuthorizationsTryUpdate(CaHandle, RoaConfigurationUpdates),
     RouteAuthorizationsDryRunUpdate(CaHandle, RoaConfigurationUpdates),
     BgpAnalysisFull(CaHandle),
     BgpAnalysisSuggest(CaHandle, Option<ResourceSet>),
 
+    // Staged ROA drafts
+    RouteAuthorizationsSchedule(CaHandle),
+    RouteAuthorizationsDraftList(CaHandle),
+    RouteAuthorizationsDraftShow(CaHandle, RoaDraftName),
+    RouteAuthorizationsDraftUpdate(CaHandle, RoaDraftName, RoaConfigurationUpdates),
+    RouteAuthorizationsDraftRemove(CaHandle, RoaDraftName),
+    RouteAuthorizationsDraftDryRun(CaHandle, RoaDraftName),
+    RouteAuthorizationsDraftCommit(CaHandle, RoaDraftName),
+    RouteAuthorizationsImportRpsl(CaHandle, RpslImportRequest),
+    RouteAuthorizationsExport(CaHandle, VrpExportFormat),
+    RouteAuthorizationsAs0Status(CaHandle),
+    RouteAuthorizationsAs0Policy(CaHandle, RoaAs0Policy),
+
     // ASPAs
     AspasList(CaHandle),
     AspasAddOrReplace(CaHandle, AspaDefinition),
     AspasUpdate(CaHandle, AspaCustomer, AspaProvidersUpdate),
     AspasRemove(CaHandle, AspaCustomer),
+    AspasAnalysis(CaHandle),
+    AspasSuggest(CaHandle),
+    AspasImport(CaHandle, AspaImportRequest),
 
     // BGPSec
     BgpSecList(CaHandle),
     BgpSecAdd(CaHandle, BgpSecDefinition),
+    BgpSecGenerate(CaHandle, BgpSecRouterKeyRequest),
     BgpSecRemove(CaHandle, BgpSecAsnKey),
 
+    // Signed Prefix Lists
+    PrefixListsList(CaHandle),
+    PrefixListsAddOrReplace(CaHandle, PrefixListDefinition),
+    PrefixListsRemove(CaHandle, Asn),
+
+    // Ghostbusters
+    GhostbusterShow(CaHandle),
+    GhostbusterSet(CaHandle, GhostbusterContact),
+    GhostbusterRemove(CaHandle),
+
     // Show details for this CA
     Show(CaHandle),
     ShowHistoryCommands(CaHandle, HistoryOptions),
     ShowHistoryDetails(CaHandle, String),
     Issues(Option<CaHandle>),
 
+    // Published objects
+    PublishedObjects(CaHandle),
+    PublishedObjectDownload(CaHandle, KeyIdentifier, ObjectName, PathBuf),
+
+    // Resources removed by parents
+    ResourceShrinks(CaHandle),
+    ResourceShrinksClear(CaHandle),
+
+    // RSC
+    RscSign(CaHandle, RscRequest, PathBuf),
+
+    // Signed geofeeds
+    GeofeedSign(CaHandle, GeofeedSignRequest, Option<PathBuf>),
+
     // RTA
     RtaList(CaHandle),
     RtaShow(CaHandle, RtaName, Option<PathBuf>),
@@ -2672,6 +4598,20 @@ pub enum PubServerCommand {
     RepositoryInit(PublicationServerUris),
     RepositoryClear,
     RepositorySessionReset,
+    RepositoryValidate,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum TrustAnchorCommand {
+    ProxyInit,
+    ProxyId,
+    ProxyStatus,
+    ProxyChildAdd(AddChildRequest),
+    ProxySignerAdd(TrustAnchorSignerInfo),
+    ProxySignerMakeRequest,
+    ProxySignerShowRequest,
+    ProxySignerProcessResponse(TrustAnchorSignedMessage),
 }
 
 //------------ Error ---------------------------------------------------------
@@ -2687,6 +4627,7 @@ pub enum Error {
     InvalidAsn(String),
     DuplicateAspaProvider(DuplicateProviderAs),
     InvalidAspaConfig(AspaDefinitionFormatError),
+    InvalidPrefixList(PrefixListDefinitionFormatError),
     InvalidHandle,
     InvalidSeconds,
     MissingArgWithEnv(String, String),
@@ -2714,6 +4655,7 @@ impl fmt::Display for Error {
             Error::InvalidAsn(s) => write!(f, "Invalid ASN format. Expected 'AS#', got: {}", s),
             Error::DuplicateAspaProvider(e) => e.fmt(f),
             Error::InvalidAspaConfig(e) => e.fmt(f),
+            Error::InvalidPrefixList(e) => e.fmt(f),
             Error::InvalidHandle => write!(
                 f,
                 "The publisher handle may only contain -_A-Za-z0-9, (\\ /) see issue #83"
@@ -2778,6 +4720,12 @@ impl From<AspaDefinitionFormatError> for Error {
     }
 }
 
+impl From<PrefixListDefinitionFormatError> for Error {
+    fn from(e: PrefixListDefinitionFormatError) -> Self {
+        Error::InvalidPrefixList(e)
+    }
+}
+
 impl From<DuplicateProviderAs> for Error {
     fn from(e: DuplicateProviderAs) -> Self {
         Error::DuplicateAspaProvider(e)
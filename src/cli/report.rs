@@ -3,20 +3,26 @@ use std::str::FromStr;
 
 use serde::Serialize;
 
-use rpki::ca::idexchange;
+use rpki::{ca::idexchange, repository::resources::ResourceSet};
 
 use crate::{
     commons::{
         api::{
-            AllCertAuthIssues, AspaDefinitionList, BgpSecCsrInfoList, CaCommandDetails, CaRepoDetails, CertAuthInfo,
-            CertAuthIssues, CertAuthList, ChildCaInfo, ChildrenConnectionStats, CommandHistory, ConfiguredRoas,
-            ParentCaContact, ParentStatuses, PublisherDetails, PublisherList, RepoStatus, RtaList, RtaPrepResponse,
-            ServerInfo,
+            AllCertAuthIssues, AspaDefinitionList, AspaImportReport, BgpSecCsrInfoList, BgpSecRouterKey,
+            CaCommandDetails, CaPublishedObjects, CaRepoDetails, CertAuthInfo, CertAuthIssues, CertAuthList,
+            ChildCaInfo, ChildrenConnectionStats, CommandHistory, ConfiguredRoas, GhostbusterContact, OnboardingToken,
+            OnboardingTokenList, ParentCaContact, ParentStatuses, PrefixListDefinitionList, PublisherDetails,
+            PublisherList, RepoStatus, RepositoryValidationReport, RoaAs0Status, RoaDraft, RoaDraftList,
+            RpslImportReport, RtaList, RtaPrepResponse, ServerInfo, TimedRoaChanges,
+        },
+        bgp::{
+            AspaAnalysisReport, AspaSuggestion, BgpAnalysisAdvice, BgpAnalysisReport, BgpAnalysisSuggestion,
+            ResourceShrinkReport,
         },
-        bgp::{BgpAnalysisAdvice, BgpAnalysisReport, BgpAnalysisSuggestion},
     },
-    daemon::ca::ResourceTaggedAttestation,
+    daemon::ca::{ResourceTaggedAttestation, SignedObjectVerification},
     pubd::RepoStats,
+    ta::{TrustAnchorProxyInfo, TrustAnchorProxyStatus, TrustAnchorSignedMessage},
 };
 
 //------------ ApiResponse ---------------------------------------------------
@@ -38,22 +44,41 @@ pub enum ApiResponse {
     BgpAnalysisAdvice(BgpAnalysisAdvice),
     BgpAnalysisFull(BgpAnalysisReport),
     BgpAnalysisSuggestions(BgpAnalysisSuggestion),
+    RoaDrafts(RoaDraftList),
+    RoaDraft(RoaDraft),
+    TimedRoaChanges(TimedRoaChanges),
+    RpslImportReport(RpslImportReport),
+    RoaAs0Status(RoaAs0Status),
 
     // ASPA related
     AspaDefinitions(AspaDefinitionList),
+    AspaAnalysis(AspaAnalysisReport),
+    AspaSuggestion(AspaSuggestion),
+    AspaImportReport(AspaImportReport),
 
     // BGPSec related
     BgpSecDefinitions(BgpSecCsrInfoList),
+    BgpSecRouterKey(BgpSecRouterKey),
+
+    // Signed Prefix List related
+    PrefixListDefinitions(PrefixListDefinitionList),
+
+    // Ghostbuster related
+    GhostbusterContact(Option<GhostbusterContact>),
 
     ParentCaContact(ParentCaContact),
     ParentStatuses(ParentStatuses),
+    ParentResourceLimit(Option<ResourceSet>),
 
     ChildInfo(ChildCaInfo),
     ChildrenStats(ChildrenConnectionStats),
+    OnboardingTokens(OnboardingTokenList),
+    OnboardingToken(OnboardingToken),
 
     PublisherDetails(PublisherDetails),
     PublisherList(PublisherList),
     RepoStats(RepoStats),
+    RepositoryValidation(RepositoryValidationReport),
 
     Rfc8183ParentResponse(idexchange::ParentResponse),
     Rfc8183RepositoryResponse(idexchange::RepositoryResponse),
@@ -62,14 +87,23 @@ pub enum ApiResponse {
 
     RepoDetails(CaRepoDetails),
     RepoStatus(RepoStatus),
+    PublishedObjects(CaPublishedObjects),
 
     CertAuthIssues(CertAuthIssues),
     AllCertAuthIssues(AllCertAuthIssues),
+    ResourceShrinks(ResourceShrinkReport),
 
     RtaList(RtaList),
     RtaMultiPrep(RtaPrepResponse),
     Rta(ResourceTaggedAttestation),
 
+    SignedObjectVerification(SignedObjectVerification),
+
+    // Offline Trust Anchor related
+    TrustAnchorProxyInfo(TrustAnchorProxyInfo),
+    TrustAnchorProxyStatus(TrustAnchorProxyStatus),
+    TrustAnchorSignedMessage(TrustAnchorSignedMessage),
+
     Empty,               // Typically a successful post just gets an empty 200 response
     GenericBody(String), // For when the server echos Json to a successful post
 }
@@ -88,28 +122,63 @@ impl ApiResponse {
                 ApiResponse::CertAuthAction(details) => Ok(Some(details.report(fmt)?)),
                 ApiResponse::CertAuthIssues(issues) => Ok(Some(issues.report(fmt)?)),
                 ApiResponse::AllCertAuthIssues(issues) => Ok(Some(issues.report(fmt)?)),
+                ApiResponse::ResourceShrinks(report) => Ok(Some(report.report(fmt)?)),
                 ApiResponse::RouteAuthorizations(definitions) => Ok(Some(definitions.report(fmt)?)),
                 ApiResponse::BgpAnalysisAdvice(analysis) => Ok(Some(analysis.report(fmt)?)),
                 ApiResponse::BgpAnalysisFull(table) => Ok(Some(table.report(fmt)?)),
                 ApiResponse::BgpAnalysisSuggestions(suggestions) => Ok(Some(suggestions.report(fmt)?)),
+                ApiResponse::RoaDrafts(drafts) => Ok(Some(drafts.report(fmt)?)),
+                ApiResponse::RoaDraft(draft) => Ok(Some(draft.report(fmt)?)),
+                ApiResponse::TimedRoaChanges(changes) => Ok(Some(changes.report(fmt)?)),
+                ApiResponse::RpslImportReport(report) => Ok(Some(report.report(fmt)?)),
+                ApiResponse::RoaAs0Status(status) => Ok(Some(status.report(fmt)?)),
                 ApiResponse::AspaDefinitions(definitions) => Ok(Some(definitions.report(fmt)?)),
+                ApiResponse::AspaAnalysis(report) => Ok(Some(report.report(fmt)?)),
+                ApiResponse::AspaSuggestion(suggestion) => Ok(Some(suggestion.report(fmt)?)),
+                ApiResponse::AspaImportReport(report) => Ok(Some(report.report(fmt)?)),
                 ApiResponse::BgpSecDefinitions(definitions) => Ok(Some(definitions.report(fmt)?)),
+                ApiResponse::BgpSecRouterKey(router_key) => Ok(Some(router_key.report(fmt)?)),
+                ApiResponse::PrefixListDefinitions(definitions) => Ok(Some(definitions.report(fmt)?)),
+                ApiResponse::GhostbusterContact(contact) => match contact {
+                    Some(contact) => Ok(Some(contact.report(fmt)?)),
+                    None => match fmt {
+                        ReportFormat::Json => Ok(Some("null".to_string())),
+                        _ => Ok(Some("No Ghostbuster contact configured".to_string())),
+                    },
+                },
                 ApiResponse::ParentCaContact(contact) => Ok(Some(contact.report(fmt)?)),
                 ApiResponse::ParentStatuses(statuses) => Ok(Some(statuses.report(fmt)?)),
+                ApiResponse::ParentResourceLimit(limit) => match limit {
+                    Some(limit) => Ok(Some(limit.report(fmt)?)),
+                    None => match fmt {
+                        ReportFormat::Json => Ok(Some("null".to_string())),
+                        _ => Ok(Some(
+                            "No resource limit configured, all entitlements are requested".to_string(),
+                        )),
+                    },
+                },
                 ApiResponse::ChildInfo(info) => Ok(Some(info.report(fmt)?)),
                 ApiResponse::ChildrenStats(stats) => Ok(Some(stats.report(fmt)?)),
+                ApiResponse::OnboardingTokens(tokens) => Ok(Some(tokens.report(fmt)?)),
+                ApiResponse::OnboardingToken(token) => Ok(Some(token.report(fmt)?)),
                 ApiResponse::PublisherList(list) => Ok(Some(list.report(fmt)?)),
                 ApiResponse::PublisherDetails(details) => Ok(Some(details.report(fmt)?)),
                 ApiResponse::RepoStats(stats) => Ok(Some(stats.report(fmt)?)),
+                ApiResponse::RepositoryValidation(report) => Ok(Some(report.report(fmt)?)),
                 ApiResponse::Rfc8183ParentResponse(res) => Ok(Some(res.report(fmt)?)),
                 ApiResponse::Rfc8183ChildRequest(req) => Ok(Some(req.report(fmt)?)),
                 ApiResponse::Rfc8183PublisherRequest(req) => Ok(Some(req.report(fmt)?)),
                 ApiResponse::Rfc8183RepositoryResponse(res) => Ok(Some(res.report(fmt)?)),
                 ApiResponse::RepoDetails(details) => Ok(Some(details.report(fmt)?)),
                 ApiResponse::RepoStatus(status) => Ok(Some(status.report(fmt)?)),
+                ApiResponse::PublishedObjects(objects) => Ok(Some(objects.report(fmt)?)),
                 ApiResponse::Rta(rta) => Ok(Some(rta.report(fmt)?)),
                 ApiResponse::RtaList(list) => Ok(Some(list.report(fmt)?)),
                 ApiResponse::RtaMultiPrep(res) => Ok(Some(res.report(fmt)?)),
+                ApiResponse::SignedObjectVerification(verification) => Ok(Some(verification.report(fmt)?)),
+                ApiResponse::TrustAnchorProxyInfo(info) => Ok(Some(info.report(fmt)?)),
+                ApiResponse::TrustAnchorProxyStatus(status) => Ok(Some(status.report(fmt)?)),
+                ApiResponse::TrustAnchorSignedMessage(message) => Ok(Some(message.report(fmt)?)),
                 ApiResponse::GenericBody(body) => Ok(Some(body.clone())),
                 ApiResponse::Empty => Ok(None),
             }
@@ -185,8 +254,18 @@ impl Report for CertAuthInfo {}
 
 impl Report for ChildCaInfo {}
 
+impl Report for OnboardingTokenList {}
+
+impl Report for OnboardingToken {
+    // The token is meant to be given to the child as is, so always use JSON.
+    fn text(&self) -> Result<String, ReportError> {
+        Ok(self.json())
+    }
+}
+
 impl Report for ParentCaContact {}
 impl Report for ParentStatuses {}
+impl Report for ResourceSet {}
 
 impl Report for CommandHistory {}
 impl Report for CaCommandDetails {}
@@ -194,6 +273,7 @@ impl Report for CaCommandDetails {}
 impl Report for PublisherList {}
 
 impl Report for RepoStats {}
+impl Report for RepositoryValidationReport {}
 impl Report for ChildrenConnectionStats {}
 
 impl Report for PublisherDetails {}
@@ -223,17 +303,36 @@ impl Report for idexchange::PublisherRequest {
 }
 
 impl Report for ConfiguredRoas {}
+impl Report for RoaDraftList {}
+impl Report for TimedRoaChanges {}
+impl Report for RoaDraft {}
+impl Report for RpslImportReport {}
+impl Report for RoaAs0Status {}
 
 impl Report for BgpAnalysisAdvice {}
 impl Report for BgpAnalysisReport {}
 impl Report for BgpAnalysisSuggestion {}
 
+impl Report for TrustAnchorProxyInfo {}
+impl Report for TrustAnchorProxyStatus {}
+impl Report for TrustAnchorSignedMessage {}
+
 impl Report for AspaDefinitionList {}
+impl Report for AspaAnalysisReport {}
+impl Report for AspaSuggestion {}
+impl Report for ResourceShrinkReport {}
+impl Report for AspaImportReport {}
 
 impl Report for BgpSecCsrInfoList {}
+impl Report for BgpSecRouterKey {}
+
+impl Report for PrefixListDefinitionList {}
+
+impl Report for GhostbusterContact {}
 
 impl Report for CaRepoDetails {}
 impl Report for RepoStatus {}
+impl Report for CaPublishedObjects {}
 
 impl Report for CertAuthIssues {}
 
@@ -244,3 +343,5 @@ impl Report for ServerInfo {}
 impl Report for ResourceTaggedAttestation {}
 impl Report for RtaList {}
 impl Report for RtaPrepResponse {}
+
+impl Report for SignedObjectVerification {}
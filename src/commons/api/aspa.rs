@@ -57,6 +57,10 @@ impl AspaDefinitionList {
     pub fn new(definitions: Vec<AspaDefinition>) -> Self {
         AspaDefinitionList(definitions)
     }
+
+    pub fn as_slice(&self) -> &[AspaDefinition] {
+        self.0.as_slice()
+    }
 }
 
 impl fmt::Display for AspaDefinitionList {
@@ -0,0 +1,299 @@
+//! Support for deriving ASPA definitions from AS relationship files, using
+//! the format published by CAIDA: https://www.caida.org/catalog/datasets/as-relationships/
+//!
+//! Each line holds a relationship: "<provider-as>|<customer-as>|-1" or
+//! "<peer-as>|<peer-as>|0", optionally followed by "|<source>".
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use rpki::repository::{aspa::ProviderAs, resources::ResourceSet};
+
+use super::{AspaCustomer, AspaDefinition, AspaDefinitionUpdates};
+
+//------------ AspaImportRequest ---------------------------------------------
+
+/// A request to import ASPA provider lists from AS relationships.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaImportRequest {
+    relationships: String,
+    #[serde(default)]
+    action: AspaImportAction,
+}
+
+impl AspaImportRequest {
+    pub fn new(relationships: String, action: AspaImportAction) -> Self {
+        AspaImportRequest { relationships, action }
+    }
+
+    pub fn relationships(&self) -> &str {
+        &self.relationships
+    }
+
+    pub fn action(&self) -> AspaImportAction {
+        self.action
+    }
+
+    pub fn unpack(self) -> (String, AspaImportAction) {
+        (self.relationships, self.action)
+    }
+}
+
+//------------ AspaImportAction ----------------------------------------------
+
+/// What to do with the ASPA definitions derived from AS relationships.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AspaImportAction {
+    /// Only report the proposed updates, do not change anything.
+    Propose,
+    /// Apply the proposed updates immediately.
+    Apply,
+}
+
+impl Default for AspaImportAction {
+    fn default() -> Self {
+        AspaImportAction::Propose
+    }
+}
+
+impl fmt::Display for AspaImportAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AspaImportAction::Propose => write!(f, "propose"),
+            AspaImportAction::Apply => write!(f, "apply"),
+        }
+    }
+}
+
+//------------ AsRelationships -----------------------------------------------
+
+/// The providers for each customer ASN found in an AS relationship file.
+/// Peer relationships are ignored. Lines which cannot be parsed are kept,
+/// so that they can be reported.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AsRelationships {
+    providers: HashMap<AspaCustomer, Vec<AspaCustomer>>,
+    invalid: Vec<String>,
+}
+
+impl AsRelationships {
+    /// Parses AS relationships, skipping empty lines and comments.
+    pub fn parse(relationships: &str) -> Self {
+        let mut res = AsRelationships::default();
+
+        for (idx, line) in relationships.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split('|');
+            let first = fields.next().map(AspaCustomer::from_str);
+            let second = fields.next().map(AspaCustomer::from_str);
+            let relationship = fields.next();
+
+            match (first, second, relationship) {
+                (Some(Ok(provider)), Some(Ok(customer)), Some("-1")) => {
+                    let providers = res.providers.entry(customer).or_default();
+                    if !providers.contains(&provider) {
+                        providers.push(provider);
+                    }
+                }
+                (Some(Ok(_)), Some(Ok(_)), Some("0")) => {}
+                _ => res.invalid.push(format!("line {}: {}", idx + 1, line)),
+            }
+        }
+
+        res
+    }
+
+    /// Returns the providers found for a customer ASN, ordered by ASN.
+    pub fn providers(&self, customer: AspaCustomer) -> Vec<AspaCustomer> {
+        let mut providers = self.providers.get(&customer).cloned().unwrap_or_default();
+        providers.sort();
+        providers
+    }
+
+    pub fn invalid(&self) -> &Vec<String> {
+        &self.invalid
+    }
+
+    /// Turns the relationships into a report with the changes needed to
+    /// make the definitions for held customer ASNs match the providers
+    /// found. Existing AFI limits are kept for providers which are found.
+    /// Definitions for customers which are not found are left alone, as
+    /// relationship files are known to be incomplete.
+    pub fn into_import_report(self, resources: &ResourceSet, current: &[AspaDefinition]) -> AspaImportReport {
+        let mut customers: Vec<AspaCustomer> = self
+            .providers
+            .keys()
+            .copied()
+            .filter(|customer| resources.contains_asn(*customer))
+            .collect();
+        customers.sort();
+
+        let mut changes = vec![];
+        for customer in customers {
+            let current = current.iter().find(|def| def.customer() == customer).cloned();
+
+            let providers: Vec<ProviderAs> = self
+                .providers(customer)
+                .into_iter()
+                .map(|provider| {
+                    current
+                        .as_ref()
+                        .and_then(|def| def.providers().iter().find(|p| p.provider() == provider).copied())
+                        .unwrap_or_else(|| ProviderAs::new(provider))
+                })
+                .collect();
+
+            let proposed = AspaDefinition::new(customer, providers);
+            if current.as_ref() != Some(&proposed) {
+                changes.push(AspaImportChange { current, proposed });
+            }
+        }
+
+        AspaImportReport {
+            changes,
+            invalid: self.invalid,
+        }
+    }
+}
+
+//------------ AspaImportChange ----------------------------------------------
+
+/// A proposed ASPA definition, and the current definition it replaces, if
+/// there is one.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaImportChange {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    current: Option<AspaDefinition>,
+    proposed: AspaDefinition,
+}
+
+impl AspaImportChange {
+    pub fn current(&self) -> Option<&AspaDefinition> {
+        self.current.as_ref()
+    }
+
+    pub fn proposed(&self) -> &AspaDefinition {
+        &self.proposed
+    }
+}
+
+impl fmt::Display for AspaImportChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(current) = &self.current {
+            writeln!(f, "- {}", current)?;
+        }
+        writeln!(f, "+ {}", self.proposed)
+    }
+}
+
+//------------ AspaImportReport ----------------------------------------------
+
+/// The proposed changes to ASPA definitions for an import of AS
+/// relationships, and the lines which could not be parsed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaImportReport {
+    changes: Vec<AspaImportChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    invalid: Vec<String>,
+}
+
+impl AspaImportReport {
+    pub fn changes(&self) -> &Vec<AspaImportChange> {
+        &self.changes
+    }
+
+    pub fn invalid(&self) -> &Vec<String> {
+        &self.invalid
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the updates which add or replace the proposed definitions.
+    pub fn updates(&self) -> AspaDefinitionUpdates {
+        let add_or_replace = self.changes.iter().map(|change| change.proposed.clone()).collect();
+        AspaDefinitionUpdates::new(add_or_replace, vec![])
+    }
+}
+
+impl fmt::Display for AspaImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            writeln!(f, "# no changes to ASPA definitions")?;
+        }
+        for change in &self.changes {
+            write!(f, "{}", change)?;
+        }
+        for invalid in &self.invalid {
+            writeln!(f, "# skipped invalid {}", invalid)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(s: &str) -> AspaDefinition {
+        AspaDefinition::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn aspa_import_report() {
+        let relationships = concat!(
+            "# source:topology|BGP\n",
+            "65001|65000|-1\n",
+            "65002|65000|-1|bgp\n",
+            "65001|65002|0\n",
+            "65003|65010|-1\n",
+            "65004|65011|-1\n",
+            "65005|65012|-1\n",
+            "65006|64496|-1\n",
+            "not|a|relationship\n",
+            "65001|65000|1\n",
+        );
+
+        let resources = ResourceSet::from_strs("AS65000, AS65010-AS65012", "", "").unwrap();
+        let current = vec![
+            definition("AS65010 => AS65004"),
+            definition("AS65011 => AS65004(v4)"),
+            definition("AS65012 => AS65005"),
+            definition("AS65013 => AS65005"),
+        ];
+
+        let report = AsRelationships::parse(relationships).into_import_report(&resources, &current);
+
+        let (add_or_replace, remove) = report.updates().unpack();
+        assert_eq!(
+            add_or_replace,
+            vec![
+                definition("AS65000 => AS65001, AS65002"),
+                definition("AS65010 => AS65003"),
+            ]
+        );
+        assert!(remove.is_empty());
+
+        assert_eq!(report.changes()[0].current(), None);
+        assert_eq!(report.changes()[1].current(), Some(&current[0]));
+        assert_eq!(report.invalid().len(), 2);
+
+        let expected = concat!(
+            "+ AS65000 => AS65001, AS65002\n",
+            "- AS65010 => AS65004\n",
+            "+ AS65010 => AS65003\n",
+            "# skipped invalid line 9: not|a|relationship\n",
+            "# skipped invalid line 10: 65001|65000|1\n",
+        );
+        assert_eq!(report.to_string(), expected);
+    }
+}
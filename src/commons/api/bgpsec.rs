@@ -216,11 +216,94 @@ impl fmt::Display for BgpSecCsrInfoList {
     }
 }
 
+//------------ BgpSecRouterKeyRequest --------------------------------------
+
+/// Requests that Krill generates a key pair for a BGPsec router, for routers
+/// which cannot generate their own keys (RFC 8635). The passphrase is used to
+/// encrypt the exported private key.
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BgpSecRouterKeyRequest {
+    asn: Asn,
+    passphrase: String,
+}
+
+impl BgpSecRouterKeyRequest {
+    pub fn new(asn: Asn, passphrase: String) -> Self {
+        BgpSecRouterKeyRequest { asn, passphrase }
+    }
+
+    pub fn asn(&self) -> Asn {
+        self.asn
+    }
+
+    pub fn passphrase(&self) -> &str {
+        &self.passphrase
+    }
+}
+
+/// The passphrase is left out, so that it does not end up in logs.
+impl fmt::Debug for BgpSecRouterKeyRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BgpSecRouterKeyRequest")
+            .field("asn", &self.asn)
+            .field("passphrase", &"<redacted>")
+            .finish()
+    }
+}
+
+//------------ BgpSecRouterKey ---------------------------------------------
+
+/// A BGPsec router key generated by Krill. The private key is included in an
+/// encrypted PKCS#8 PEM container. It is only returned once, Krill does not
+/// keep a copy.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BgpSecRouterKey {
+    asn: Asn,
+    key_identifier: KeyIdentifier,
+    private_key: String,
+}
+
+impl BgpSecRouterKey {
+    pub fn new(asn: Asn, key_identifier: KeyIdentifier, private_key: String) -> Self {
+        BgpSecRouterKey {
+            asn,
+            key_identifier,
+            private_key,
+        }
+    }
+
+    pub fn asn(&self) -> Asn {
+        self.asn
+    }
+
+    pub fn key_identifier(&self) -> KeyIdentifier {
+        self.key_identifier
+    }
+
+    pub fn private_key(&self) -> &str {
+        &self.private_key
+    }
+
+    pub fn object_name(&self) -> ObjectName {
+        ObjectName::bgpsec(self.asn, self.key_identifier)
+    }
+}
+
+/// Only the PEM is shown, so that the text output can be saved to a file
+/// and handed to the router as is.
+impl fmt::Display for BgpSecRouterKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.private_key)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use super::BgpSecAsnKey;
+    use rpki::repository::resources::Asn;
+
+    use super::{BgpSecAsnKey, BgpSecRouterKeyRequest};
 
     #[test]
     fn bgp_sec_to_from_str() {
@@ -229,4 +312,12 @@ mod tests {
         let to_string = key.to_string();
         assert_eq!(string, &to_string);
     }
+
+    #[test]
+    fn bgp_sec_router_key_request_debug_redacts_passphrase() {
+        let request = BgpSecRouterKeyRequest::new(Asn::from_u32(65000), "secret".to_string());
+        let debug = format!("{:?}", request);
+        assert!(debug.contains("65000"));
+        assert!(!debug.contains("secret"));
+    }
 }
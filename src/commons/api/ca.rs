@@ -8,7 +8,7 @@ use std::sync::Arc;
 use std::{fmt, str};
 
 use bytes::Bytes;
-use chrono::{Duration, TimeZone, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use rpki::repository::x509::{Name, Validity};
 use serde::{Deserialize, Serialize};
 
@@ -41,8 +41,8 @@ use crate::daemon::ca::BgpSecCertInfo;
 use crate::{
     commons::{
         api::{
-            rrdp::PublishElement, AspaDefinition, ErrorResponse, ParentCaContact, RepositoryContact, RoaAggregateKey,
-            RoaPayload,
+            rrdp::PublishElement, AspaDefinition, ErrorResponse, ParentCaContact, RepositoryContact, ResourceShrink,
+            RoaAggregateKey, RoaConfiguration, RoaPayload, ValidationIssue,
         },
         util::KrillVersion,
     },
@@ -506,6 +506,19 @@ impl TrustAnchorLocator {
             encoded_ski,
         }
     }
+
+    pub fn uris(&self) -> &Vec<uri::Https> {
+        &self.uris
+    }
+
+    pub fn rsync_uri(&self) -> &uri::Rsync {
+        &self.rsync_uri
+    }
+
+    /// Returns whether the given key is the trust anchor key in this TAL.
+    pub fn matches_key(&self, key: &PublicKey) -> bool {
+        self.encoded_ski == Base64::from_content(&key.to_info_bytes())
+    }
 }
 
 impl fmt::Display for TrustAnchorLocator {
@@ -602,6 +615,10 @@ impl ObjectName {
         ObjectName::new(ki, "crl")
     }
 
+    pub fn tak_for_key(ki: &KeyIdentifier) -> Self {
+        ObjectName::new(ki, "tak")
+    }
+
     pub fn aspa(customer: Asn) -> Self {
         ObjectName(format!("{}.asa", customer).into())
     }
@@ -609,6 +626,16 @@ impl ObjectName {
     pub fn bgpsec(asn: Asn, key: KeyIdentifier) -> Self {
         ObjectName(format!("ROUTER-{:08X}-{}.cer", asn.into_u32(), key).into())
     }
+
+    pub fn prefix_list(asn: Asn) -> Self {
+        ObjectName(format!("{}.spl", asn).into())
+    }
+
+    /// A CA has at most one Ghostbuster record in each resource class, so
+    /// it can use a fixed name. This way a new record replaces the old one.
+    pub fn ghostbuster() -> Self {
+        ObjectName("contact.gbr".into())
+    }
 }
 
 impl From<&Cert> for ObjectName {
@@ -1198,6 +1225,11 @@ pub struct RepoStatus {
     last_success: Option<Timestamp>,
     next_exchange_before: Timestamp,
     published: Vec<PublishElement>,
+
+    // The status of each repository mirror, which is kept separately from the
+    // status of the primary repository.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    mirrors: Vec<RepoMirrorStatus>,
 }
 
 impl Default for RepoStatus {
@@ -1207,6 +1239,7 @@ impl Default for RepoStatus {
             last_success: None,
             next_exchange_before: Timestamp::now_plus_hours(1),
             published: vec![],
+            mirrors: vec![],
         }
     }
 }
@@ -1227,6 +1260,14 @@ impl RepoStatus {
     pub fn to_failure_opt(&self) -> Option<ErrorResponse> {
         self.last_exchange.as_ref().and_then(|e| e.to_failure_opt())
     }
+
+    pub fn mirrors(&self) -> &Vec<RepoMirrorStatus> {
+        &self.mirrors
+    }
+
+    pub fn mirror(&self, uri: &ServiceUri) -> Option<&RepoMirrorStatus> {
+        self.mirrors.iter().find(|mirror| &mirror.uri == uri)
+    }
 }
 
 impl RepoStatus {
@@ -1262,6 +1303,27 @@ impl RepoStatus {
         self.last_success = Some(timestamp);
         self.next_exchange_before = next_update;
     }
+
+    pub fn set_mirror_failure(&mut self, uri: ServiceUri, error: ErrorResponse) {
+        self.mirror_mut(&uri).set_failure(uri, error);
+    }
+
+    pub fn set_mirror_updated(&mut self, uri: ServiceUri, next_update: Timestamp) {
+        self.mirror_mut(&uri).set_updated(uri, next_update);
+    }
+
+    pub fn remove_mirror(&mut self, uri: &ServiceUri) {
+        self.mirrors.retain(|mirror| &mirror.uri != uri);
+    }
+
+    fn mirror_mut(&mut self, uri: &ServiceUri) -> &mut RepoMirrorStatus {
+        if let Some(pos) = self.mirrors.iter().position(|mirror| &mirror.uri == uri) {
+            &mut self.mirrors[pos]
+        } else {
+            self.mirrors.push(RepoMirrorStatus::new(uri.clone()));
+            self.mirrors.last_mut().unwrap() // safe, we just added it
+        }
+    }
 }
 
 impl fmt::Display for RepoStatus {
@@ -1284,6 +1346,96 @@ impl fmt::Display for RepoStatus {
                 )?;
             }
         }
+        for mirror in &self.mirrors {
+            writeln!(f)?;
+            write!(f, "{}", mirror)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ RepoMirrorStatus ----------------------------------------------
+
+/// The status of the exchanges with a repository mirror. The objects
+/// published to a mirror are the same as for the primary repository.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RepoMirrorStatus {
+    uri: ServiceUri,
+    last_exchange: Option<ParentExchange>,
+    last_success: Option<Timestamp>,
+    next_exchange_before: Timestamp,
+}
+
+impl RepoMirrorStatus {
+    fn new(uri: ServiceUri) -> Self {
+        RepoMirrorStatus {
+            uri,
+            last_exchange: None,
+            last_success: None,
+            next_exchange_before: Timestamp::now_plus_hours(1),
+        }
+    }
+
+    pub fn uri(&self) -> &ServiceUri {
+        &self.uri
+    }
+
+    pub fn next_exchange_before(&self) -> Timestamp {
+        self.next_exchange_before
+    }
+
+    pub fn last_exchange(&self) -> Option<&ParentExchange> {
+        self.last_exchange.as_ref()
+    }
+
+    pub fn last_success(&self) -> Option<Timestamp> {
+        self.last_success
+    }
+
+    pub fn to_failure_opt(&self) -> Option<ErrorResponse> {
+        self.last_exchange.as_ref().and_then(|e| e.to_failure_opt())
+    }
+
+    fn set_failure(&mut self, uri: ServiceUri, error: ErrorResponse) {
+        let timestamp = Timestamp::now();
+        self.last_exchange = Some(ParentExchange {
+            timestamp,
+            uri,
+            result: ExchangeResult::Failure(error),
+        });
+        self.next_exchange_before = timestamp.plus_minutes(5);
+    }
+
+    fn set_updated(&mut self, uri: ServiceUri, next_update: Timestamp) {
+        let timestamp = Timestamp::now();
+        self.last_exchange = Some(ParentExchange {
+            timestamp,
+            uri,
+            result: ExchangeResult::Success,
+        });
+        self.last_success = Some(timestamp);
+        self.next_exchange_before = next_update;
+    }
+}
+
+impl fmt::Display for RepoMirrorStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Mirror URI: {}", self.uri)?;
+        match &self.last_exchange {
+            None => writeln!(f, "Status: connection still pending")?,
+            Some(exchange) => {
+                writeln!(f, "Status: {}", exchange.result)?;
+                writeln!(f, "Last contacted: {}", exchange.timestamp().to_rfc3339())?;
+                if let Some(success) = self.last_success() {
+                    writeln!(f, "Last successful contact: {}", success.to_rfc3339())?;
+                }
+                writeln!(
+                    f,
+                    "Next contact on or before: {}",
+                    self.next_exchange_before().to_rfc3339()
+                )?;
+            }
+        }
         Ok(())
     }
 }
@@ -1373,23 +1525,29 @@ impl ChildrenConnectionStats {
 impl fmt::Display for ChildrenConnectionStats {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if !self.children.is_empty() {
-            writeln!(f, "handle,user_agent,last_exchange,result,state")?;
+            writeln!(f, "handle,user_agent,last_exchange,result,state,last_refused")?;
             for child in &self.children {
+                let last_refused = child
+                    .last_refused
+                    .map(|timestamp| timestamp.to_rfc3339())
+                    .unwrap_or_else(|| "never".to_string());
+
                 match &child.last_exchange {
                     None => {
-                        writeln!(f, "{},n/a,never,n/a,{}", child.handle, child.state)?;
+                        writeln!(f, "{},n/a,never,n/a,{},{}", child.handle, child.state, last_refused)?;
                     }
                     Some(exchange) => {
                         let agent = exchange.user_agent.as_deref().unwrap_or("");
 
                         writeln!(
                             f,
-                            "{},{},{},{},{}",
+                            "{},{},{},{},{},{}",
                             child.handle,
                             agent,
                             exchange.timestamp.to_rfc3339(),
                             exchange.result,
-                            child.state
+                            child.state,
+                            last_refused
                         )?;
                     }
                 }
@@ -1404,14 +1562,22 @@ pub struct ChildConnectionStats {
     handle: ChildHandle,
     last_exchange: Option<ChildExchange>,
     state: ChildState,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    last_refused: Option<Timestamp>,
 }
 
 impl ChildConnectionStats {
-    pub fn new(handle: ChildHandle, last_exchange: Option<ChildExchange>, state: ChildState) -> Self {
+    pub fn new(
+        handle: ChildHandle,
+        last_exchange: Option<ChildExchange>,
+        state: ChildState,
+        last_refused: Option<Timestamp>,
+    ) -> Self {
         ChildConnectionStats {
             handle,
             last_exchange,
             state,
+            last_refused,
         }
     }
 
@@ -1438,6 +1604,8 @@ pub struct ChildStatus {
     last_exchange: Option<ChildExchange>,
     last_success: Option<Timestamp>,
     suspended: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    last_refused: Option<Timestamp>,
 }
 
 impl ChildStatus {
@@ -1461,6 +1629,13 @@ impl ChildStatus {
         self.suspended = None;
     }
 
+    /// Records that a request from the child was refused, because it sent
+    /// too many requests.
+    pub fn set_refused(&mut self, user_agent: Option<String>, error_response: ErrorResponse) {
+        self.set_failure(user_agent, error_response);
+        self.last_refused = self.last_exchange.as_ref().map(|exchange| exchange.timestamp);
+    }
+
     pub fn set_suspended(&mut self) {
         self.suspended = Some(Timestamp::now())
     }
@@ -1477,6 +1652,10 @@ impl ChildStatus {
         self.suspended
     }
 
+    pub fn last_refused(&self) -> Option<Timestamp> {
+        self.last_refused
+    }
+
     pub fn child_state(&self) -> ChildState {
         if self.suspended.is_none() {
             ChildState::Active
@@ -1539,7 +1718,7 @@ impl ChildExchange {
 //------------ Timestamp -----------------------------------------------------
 
 /// A wrapper for unix timestamps with second precision, with some convenient stuff.
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Timestamp(i64);
 
 impl Timestamp {
@@ -1614,6 +1793,15 @@ impl From<Timestamp> for i64 {
     }
 }
 
+impl FromStr for Timestamp {
+    type Err = chrono::ParseError;
+
+    /// Parses an RFC 3339 time, e.g.: 2022-10-18T12:00:00Z
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DateTime::parse_from_rfc3339(s).map(|time| Timestamp(time.timestamp()))
+    }
+}
+
 //--- Display
 
 impl fmt::Display for Timestamp {
@@ -1936,16 +2124,22 @@ impl fmt::Display for ResourceClassKeysInfo {
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CaRepoDetails {
     contact: RepositoryContact,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    mirrors: Vec<RepositoryContact>,
 }
 
 impl CaRepoDetails {
-    pub fn new(contact: RepositoryContact) -> Self {
-        CaRepoDetails { contact }
+    pub fn new(contact: RepositoryContact, mirrors: Vec<RepositoryContact>) -> Self {
+        CaRepoDetails { contact, mirrors }
     }
 
     pub fn contact(&self) -> &RepositoryContact {
         &self.contact
     }
+
+    pub fn mirrors(&self) -> &Vec<RepositoryContact> {
+        &self.mirrors
+    }
 }
 
 impl fmt::Display for CaRepoDetails {
@@ -1961,6 +2155,14 @@ impl fmt::Display for CaRepoDetails {
         writeln!(f, "  rpki_notify:    {}", rrdp_uri)?;
         writeln!(f)?;
 
+        for mirror in &self.mirrors {
+            let server_info = mirror.server_info();
+            writeln!(f, "Mirror Details:")?;
+            writeln!(f, "  service uri:    {}", server_info.service_uri())?;
+            writeln!(f, "  key identifier: {}", server_info.public_key().key_identifier())?;
+            writeln!(f)?;
+        }
+
         Ok(())
     }
 }
@@ -2016,6 +2218,14 @@ impl fmt::Display for AllCertAuthIssues {
 pub struct CertAuthIssues {
     repo_issue: Option<ErrorResponse>,
     parent_issues: Vec<CertAuthParentIssue>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    resource_shrinks: Vec<ResourceShrink>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    validation_issues: Vec<ValidationIssue>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    roa_max_length_exposed: Vec<RoaConfiguration>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    roa_activation_refused: Vec<RoaConfiguration>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -2042,8 +2252,50 @@ impl CertAuthIssues {
         &self.parent_issues
     }
 
+    pub fn add_resource_shrink(&mut self, shrink: ResourceShrink) {
+        self.resource_shrinks.push(shrink);
+    }
+
+    pub fn resource_shrinks(&self) -> &Vec<ResourceShrink> {
+        &self.resource_shrinks
+    }
+
+    pub fn add_validation_issues(&mut self, issues: Vec<ValidationIssue>) {
+        self.validation_issues.extend(issues);
+    }
+
+    pub fn validation_issues(&self) -> &Vec<ValidationIssue> {
+        &self.validation_issues
+    }
+
+    /// Adds a ROA configuration with a max length which allows more specific
+    /// announcements that are not seen in BGP. See RFC 9319.
+    pub fn add_roa_max_length_exposed(&mut self, configuration: RoaConfiguration) {
+        self.roa_max_length_exposed.push(configuration);
+    }
+
+    pub fn roa_max_length_exposed(&self) -> &Vec<RoaConfiguration> {
+        &self.roa_max_length_exposed
+    }
+
+    /// Adds a scheduled ROA configuration which was not activated, because
+    /// its max length allows more specific announcements that are not seen
+    /// in BGP.
+    pub fn add_roa_activation_refused(&mut self, configuration: RoaConfiguration) {
+        self.roa_activation_refused.push(configuration);
+    }
+
+    pub fn roa_activation_refused(&self) -> &Vec<RoaConfiguration> {
+        &self.roa_activation_refused
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.repo_issue.is_none() && self.parent_issues.is_empty()
+        self.repo_issue.is_none()
+            && self.parent_issues.is_empty()
+            && self.resource_shrinks.is_empty()
+            && self.validation_issues.is_empty()
+            && self.roa_max_length_exposed.is_empty()
+            && self.roa_activation_refused.is_empty()
     }
 }
 
@@ -2061,6 +2313,26 @@ impl fmt::Display for CertAuthIssues {
                     writeln!(f, "Parent '{}' has issue: {}", parent_issue.parent, parent_issue.issue)?;
                 }
             }
+            for shrink in self.resource_shrinks.iter() {
+                write!(f, "{}", shrink)?;
+            }
+            for issue in self.validation_issues.iter() {
+                writeln!(f, "Validation issue: {}", issue)?;
+            }
+            for configuration in self.roa_max_length_exposed.iter() {
+                writeln!(
+                    f,
+                    "ROA configuration '{}' allows more specific announcements that are not seen (see RFC 9319)",
+                    configuration
+                )?;
+            }
+            for configuration in self.roa_activation_refused.iter() {
+                writeln!(
+                    f,
+                    "Scheduled ROA configuration '{}' was not activated, it allows more specific announcements that are not seen (see RFC 9319)",
+                    configuration
+                )?;
+            }
         }
         Ok(())
     }
@@ -2107,6 +2379,8 @@ pub struct BgpStats {
     pub announcements_disallowed: usize,
     pub announcements_not_found: usize,
     pub roas_too_permissive: usize,
+    #[serde(default)]
+    pub roas_max_length_exposed: usize,
     pub roas_redundant: usize,
     pub roas_stale: usize,
     pub roas_disallowing: usize,
@@ -2139,6 +2413,10 @@ impl BgpStats {
         self.roas_too_permissive += 1;
     }
 
+    pub fn increment_roas_max_length_exposed(&mut self) {
+        self.roas_max_length_exposed += 1;
+    }
+
     pub fn increment_roas_redundant(&mut self) {
         self.roas_redundant += 1;
     }
@@ -2363,6 +2641,7 @@ mod test {
                 handle: child.convert(),
                 last_exchange: None,
                 state: ChildState::Active,
+                last_refused: None,
             }
         }
 
@@ -2371,6 +2650,7 @@ mod test {
                 handle: child.convert(),
                 last_exchange: Some(exchange),
                 state: ChildState::Active,
+                last_refused: None,
             }
         }
 
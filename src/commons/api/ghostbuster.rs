@@ -0,0 +1,188 @@
+//! Contact details published as Ghostbuster records.
+//!
+//! See: https://www.rfc-editor.org/rfc/rfc6493.html
+use std::fmt;
+
+//------------ GhostbusterContact ------------------------------------------
+
+/// The contact details for a CA. These are published in a Ghostbuster
+/// record, so that relying party operators who see issues with objects
+/// issued by the CA know whom to contact.
+///
+/// RFC 6493 requires a full name, and at least one of: an address, a
+/// telephone number, or an email address.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GhostbusterContact {
+    full_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    org: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+}
+
+impl GhostbusterContact {
+    pub fn new(
+        full_name: String,
+        org: Option<String>,
+        address: Option<String>,
+        tel: Option<String>,
+        email: Option<String>,
+    ) -> Self {
+        GhostbusterContact {
+            full_name,
+            org,
+            address,
+            tel,
+            email,
+        }
+    }
+
+    pub fn full_name(&self) -> &str {
+        &self.full_name
+    }
+
+    pub fn org(&self) -> Option<&String> {
+        self.org.as_ref()
+    }
+
+    pub fn address(&self) -> Option<&String> {
+        self.address.as_ref()
+    }
+
+    pub fn tel(&self) -> Option<&String> {
+        self.tel.as_ref()
+    }
+
+    pub fn email(&self) -> Option<&String> {
+        self.email.as_ref()
+    }
+
+    /// Verifies that this contact meets the profile in section 5 of
+    /// RFC 6493. Returns a description of the problem otherwise.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.full_name.trim().is_empty() {
+            return Err("a full name is required".to_string());
+        }
+
+        let fields = [&self.org, &self.address, &self.tel, &self.email];
+        if fields
+            .iter()
+            .any(|field| field.as_ref().map(|f| f.trim().is_empty()).unwrap_or(false))
+        {
+            return Err("fields may not be empty if they are specified".to_string());
+        }
+
+        if self.address.is_none() && self.tel.is_none() && self.email.is_none() {
+            return Err("at least one of address, telephone or email is required".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the vCard (version 4.0) for this contact, using only the
+    /// properties permitted by RFC 6493.
+    pub fn to_vcard(&self) -> String {
+        let mut vcard = String::new();
+        vcard.push_str("BEGIN:VCARD\r\n");
+        vcard.push_str("VERSION:4.0\r\n");
+        vcard.push_str(&format!("FN:{}\r\n", Self::escape(&self.full_name)));
+        if let Some(org) = &self.org {
+            vcard.push_str(&format!("ORG:{}\r\n", Self::escape(org)));
+        }
+        if let Some(address) = &self.address {
+            // The address is used as the street address component, all other
+            // components of the structured ADR value are left empty.
+            vcard.push_str(&format!("ADR;TYPE=WORK:;;{};;;;\r\n", Self::escape(address)));
+        }
+        if let Some(tel) = &self.tel {
+            vcard.push_str(&format!("TEL;TYPE=VOICE,MSG,WORK:{}\r\n", Self::escape(tel)));
+        }
+        if let Some(email) = &self.email {
+            vcard.push_str(&format!("EMAIL;TYPE=WORK:{}\r\n", Self::escape(email)));
+        }
+        vcard.push_str("END:VCARD\r\n");
+        vcard
+    }
+
+    /// Escapes text values as described in section 3.4 of RFC 6350.
+    fn escape(value: &str) -> String {
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.trim().chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                ',' => escaped.push_str("\\,"),
+                ';' => escaped.push_str("\\;"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => {}
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+}
+
+impl fmt::Display for GhostbusterContact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Full name: {}", self.full_name)?;
+        if let Some(org) = &self.org {
+            writeln!(f, "Organization: {}", org)?;
+        }
+        if let Some(address) = &self.address {
+            writeln!(f, "Address: {}", address)?;
+        }
+        if let Some(tel) = &self.tel {
+            writeln!(f, "Telephone: {}", tel)?;
+        }
+        if let Some(email) = &self.email {
+            writeln!(f, "Email: {}", email)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(address: Option<&str>, tel: Option<&str>, email: Option<&str>) -> GhostbusterContact {
+        GhostbusterContact::new(
+            "Network Operations".to_string(),
+            Some("Example, Inc.".to_string()),
+            address.map(|s| s.to_string()),
+            tel.map(|s| s.to_string()),
+            email.map(|s| s.to_string()),
+        )
+    }
+
+    #[test]
+    fn vcard_for_contact() {
+        let contact = contact(Some("1 Main Street; Anytown"), None, Some("noc@example.com"));
+        assert!(contact.validate().is_ok());
+
+        let expected = "BEGIN:VCARD\r\n\
+            VERSION:4.0\r\n\
+            FN:Network Operations\r\n\
+            ORG:Example\\, Inc.\r\n\
+            ADR;TYPE=WORK:;;1 Main Street\\; Anytown;;;;\r\n\
+            EMAIL;TYPE=WORK:noc@example.com\r\n\
+            END:VCARD\r\n";
+
+        assert_eq!(contact.to_vcard(), expected);
+    }
+
+    #[test]
+    fn contact_needs_address_tel_or_email() {
+        assert!(contact(None, None, None).validate().is_err());
+        assert!(contact(None, Some("+31 20 555 1234"), None).validate().is_ok());
+        assert!(contact(None, Some(" "), None).validate().is_err());
+
+        let no_name = GhostbusterContact::new(String::new(), None, None, None, Some("noc@example.com".to_string()));
+        assert!(no_name.validate().is_err());
+    }
+}
@@ -14,15 +14,15 @@ use rpki::{
 use crate::{
     commons::{
         api::{
-            ArgKey, ArgVal, AspaCustomer, AspaProvidersUpdate, Label, Message, RoaConfigurationUpdates, RtaName,
-            StorableParentContact,
+            ArgKey, ArgVal, AspaCustomer, AspaProvidersUpdate, Label, Message, OnboardingTokenId, RoaAs0Policy,
+            RoaConfigurationUpdates, RoaDraftName, RoaPayload, RtaName, StorableParentContact, Timestamp,
         },
         eventsourcing::{CommandKey, CommandKeyError, StoredCommand, WithStorableDetails},
     },
     daemon::ca::{self, DropReason},
 };
 
-use super::{AspaDefinitionUpdates, ResourceSetSummary};
+use super::{AspaDefinitionUpdates, GhostbusterContact, PrefixListDefinitionUpdates, ResourceSetSummary};
 
 //------------ CaCommandDetails ----------------------------------------------
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -284,6 +284,10 @@ impl CommandSummary {
     pub fn with_rta_name(self, name: &str) -> Self {
         self.with_arg("rta_name", name)
     }
+
+    pub fn with_roa_draft(self, name: &str) -> Self {
+        self.with_arg("roa_draft", name)
+    }
 }
 
 //------------ CommandHistoryCriteria ----------------------------------------
@@ -440,6 +444,19 @@ pub enum StorableCaCommand {
     ChildUnsuspend {
         child: ChildHandle,
     },
+    OnboardingTokenAdd {
+        id: OnboardingTokenId,
+        resources: ResourceSet,
+        expires: Timestamp,
+    },
+    OnboardingTokenRemove {
+        id: OnboardingTokenId,
+    },
+    OnboardingTokenRedeem {
+        id: OnboardingTokenId,
+        child: ChildHandle,
+        ski: String,
+    },
     GenerateNewIdKey,
     AddParent {
         parent: ParentHandle,
@@ -452,6 +469,10 @@ pub enum StorableCaCommand {
     RemoveParent {
         parent: ParentHandle,
     },
+    UpdateParentResourceLimit {
+        parent: ParentHandle,
+        limit: Option<ResourceSet>,
+    },
     UpdateResourceEntitlements {
         parent: ParentHandle,
         entitlements: Vec<StorableRcEntitlement>,
@@ -464,6 +485,7 @@ pub enum StorableCaCommand {
         resource_class_name: ResourceClassName,
         reason: DropReason,
     },
+    ResourceShrinksClear,
     KeyRollInitiate {
         older_than_seconds: i64,
     },
@@ -473,9 +495,26 @@ pub enum StorableCaCommand {
     KeyRollFinish {
         resource_class_name: ResourceClassName,
     },
+    TaKeyRollFinish,
     RoaDefinitionUpdates {
         updates: RoaConfigurationUpdates,
     },
+    RoaDraftUpdate {
+        name: RoaDraftName,
+        updates: RoaConfigurationUpdates,
+    },
+    RoaDraftRemove {
+        name: RoaDraftName,
+    },
+    RoaDraftCommit {
+        name: RoaDraftName,
+    },
+    RoaActivationRefused {
+        refused: Vec<RoaPayload>,
+    },
+    RoaAs0Policy {
+        policy: RoaAs0Policy,
+    },
     ReissueBeforeExpiring,
     ForceReissue,
     AspasUpdate {
@@ -489,9 +528,21 @@ pub enum StorableCaCommand {
         customer: AspaCustomer,
     },
     BgpSecDefinitionUpdates, // details in events
+    PrefixListsUpdate {
+        updates: PrefixListDefinitionUpdates,
+    },
+    GhostbusterUpdate {
+        contact: Option<GhostbusterContact>,
+    },
     RepoUpdate {
         service_uri: ServiceUri,
     },
+    RepoMirrorAdd {
+        service_uri: ServiceUri,
+    },
+    RepoMirrorRemove {
+        service_uri: ServiceUri,
+    },
     RtaPrepare {
         name: RtaName,
     },
@@ -544,6 +595,21 @@ impl WithStorableDetails for StorableCaCommand {
             StorableCaCommand::ChildUnsuspend { child } => {
                 CommandSummary::new("cmd-ca-child-unsuspend", &self).with_child(child)
             }
+            StorableCaCommand::OnboardingTokenAdd { id, resources, expires } => {
+                CommandSummary::new("cmd-ca-onboarding-token-add", &self)
+                    .with_arg("id", id)
+                    .with_resources(resources)
+                    .with_arg("expires", expires.to_rfc3339())
+            }
+            StorableCaCommand::OnboardingTokenRemove { id } => {
+                CommandSummary::new("cmd-ca-onboarding-token-remove", &self).with_arg("id", id)
+            }
+            StorableCaCommand::OnboardingTokenRedeem { id, child, ski } => {
+                CommandSummary::new("cmd-ca-onboarding-token-redeem", &self)
+                    .with_arg("id", id)
+                    .with_child(child)
+                    .with_id_ski(ski)
+            }
             StorableCaCommand::ChildRevokeKey { child, revoke_req } => {
                 CommandSummary::new("cmd-ca-child-revoke", &self)
                     .with_child(child)
@@ -562,6 +628,12 @@ impl WithStorableDetails for StorableCaCommand {
             StorableCaCommand::RemoveParent { parent } => {
                 CommandSummary::new("cmd-ca-parent-remove", &self).with_parent(parent)
             }
+            StorableCaCommand::UpdateParentResourceLimit { parent, limit } => match limit {
+                Some(limit) => CommandSummary::new("cmd-ca-parent-limit-update", &self)
+                    .with_parent(parent)
+                    .with_resources(limit),
+                None => CommandSummary::new("cmd-ca-parent-limit-update", &self).with_parent(parent),
+            },
             StorableCaCommand::UpdateResourceEntitlements { parent, .. } => {
                 CommandSummary::new("cmd-ca-parent-entitlements", &self).with_parent(parent)
             }
@@ -577,6 +649,7 @@ impl WithStorableDetails for StorableCaCommand {
             } => CommandSummary::new("cmd-ca-rc-drop", &self)
                 .with_rcn(resource_class_name)
                 .with_arg("reason", reason),
+            StorableCaCommand::ResourceShrinksClear => CommandSummary::new("cmd-ca-resource-shrinks-clear", &self),
 
             // Key rolls
             StorableCaCommand::KeyRollInitiate { older_than_seconds } => {
@@ -588,11 +661,30 @@ impl WithStorableDetails for StorableCaCommand {
             StorableCaCommand::KeyRollFinish { resource_class_name } => {
                 CommandSummary::new("cmd-ca-keyroll-finish", &self).with_rcn(resource_class_name)
             }
+            StorableCaCommand::TaKeyRollFinish => CommandSummary::new("cmd-ca-ta-keyroll-finish", &self),
 
             // ROA
             StorableCaCommand::RoaDefinitionUpdates { updates } => CommandSummary::new("cmd-ca-roas-updated", &self)
                 .with_added(updates.added().len())
                 .with_removed(updates.removed().len()),
+            StorableCaCommand::RoaDraftUpdate { name, updates } => {
+                CommandSummary::new("cmd-ca-roa-draft-update", &self)
+                    .with_roa_draft(name)
+                    .with_added(updates.added().len())
+                    .with_removed(updates.removed().len())
+            }
+            StorableCaCommand::RoaDraftRemove { name } => {
+                CommandSummary::new("cmd-ca-roa-draft-remove", &self).with_roa_draft(name)
+            }
+            StorableCaCommand::RoaDraftCommit { name } => {
+                CommandSummary::new("cmd-ca-roa-draft-commit", &self).with_roa_draft(name)
+            }
+            StorableCaCommand::RoaActivationRefused { refused } => {
+                CommandSummary::new("cmd-ca-roa-activation-refused", &self).with_arg("refused", refused.len())
+            }
+            StorableCaCommand::RoaAs0Policy { policy } => {
+                CommandSummary::new("cmd-ca-roa-as0-policy", &self).with_arg("enabled", policy.enabled())
+            }
 
             // ASPA
             StorableCaCommand::AspasUpdate { .. } => CommandSummary::new("cmd-ca-aspas-update", &self),
@@ -602,10 +694,22 @@ impl WithStorableDetails for StorableCaCommand {
             // BGPSec
             StorableCaCommand::BgpSecDefinitionUpdates => CommandSummary::new("cmd-bgpsec-update", &self),
 
+            // Signed Prefix Lists
+            StorableCaCommand::PrefixListsUpdate { .. } => CommandSummary::new("cmd-ca-prefix-lists-update", &self),
+
+            // Ghostbusters
+            StorableCaCommand::GhostbusterUpdate { .. } => CommandSummary::new("cmd-ca-ghostbuster-update", &self),
+
             // REPO
             StorableCaCommand::RepoUpdate { service_uri } => {
                 CommandSummary::new("cmd-ca-repo-update", &self).with_service_uri(service_uri)
             }
+            StorableCaCommand::RepoMirrorAdd { service_uri } => {
+                CommandSummary::new("cmd-ca-repo-mirror-add", &self).with_service_uri(service_uri)
+            }
+            StorableCaCommand::RepoMirrorRemove { service_uri } => {
+                CommandSummary::new("cmd-ca-repo-mirror-remove", &self).with_service_uri(service_uri)
+            }
 
             StorableCaCommand::ReissueBeforeExpiring => CommandSummary::new("cmd-ca-reissue-before-expiring", &self),
             StorableCaCommand::ForceReissue => CommandSummary::new("cmd-ca-force-reissue", &self),
@@ -670,6 +774,22 @@ impl fmt::Display for StorableCaCommand {
             StorableCaCommand::ChildUnsuspend { child } => {
                 write!(f, "Unsuspend child '{}': publish its unexpired certs", child)
             }
+            StorableCaCommand::OnboardingTokenAdd { id, resources, expires } => {
+                let summary = ResourceSetSummary::from(resources);
+                write!(
+                    f,
+                    "Add onboarding token '{}' for resources '{}' expiring '{}'",
+                    id,
+                    summary,
+                    expires.to_rfc3339()
+                )
+            }
+            StorableCaCommand::OnboardingTokenRemove { id } => write!(f, "Remove onboarding token '{}'", id),
+            StorableCaCommand::OnboardingTokenRedeem { id, child, ski } => write!(
+                f,
+                "Add child '{}' with RFC8183 key '{}' using onboarding token '{}'",
+                child, ski, id
+            ),
 
             // ------------------------------------------------------------
             // Being a child (only allowed if this CA is not self-signed)
@@ -680,6 +800,10 @@ impl fmt::Display for StorableCaCommand {
                 write!(f, "Update contact for parent '{}' to '{}'", parent, contact)
             }
             StorableCaCommand::RemoveParent { parent } => write!(f, "Remove parent '{}'", parent),
+            StorableCaCommand::UpdateParentResourceLimit { parent, limit } => match limit {
+                Some(limit) => write!(f, "Limit resources requested from parent '{}' to '{}'", parent, limit),
+                None => write!(f, "Remove resource limit for parent '{}'", parent),
+            },
 
             StorableCaCommand::UpdateResourceEntitlements { parent, entitlements } => {
                 write!(f, "Update entitlements under parent '{}': ", parent)?;
@@ -710,6 +834,7 @@ impl fmt::Display for StorableCaCommand {
                 "Removing resource class '{}' because of reason: {}",
                 resource_class_name, reason
             ),
+            StorableCaCommand::ResourceShrinksClear => write!(f, "Clear reports of resources removed by parents"),
 
             // ------------------------------------------------------------
             // Key rolls
@@ -732,6 +857,7 @@ impl fmt::Display for StorableCaCommand {
             StorableCaCommand::KeyRollFinish { resource_class_name } => {
                 write!(f, "Retire old revoked key in RC '{}'", resource_class_name)
             }
+            StorableCaCommand::TaKeyRollFinish => write!(f, "Retire old trust anchor key"),
 
             // ------------------------------------------------------------
             // ROA Support
@@ -752,6 +878,38 @@ impl fmt::Display for StorableCaCommand {
                 }
                 Ok(())
             }
+            StorableCaCommand::RoaDraftUpdate { name, updates } => {
+                write!(f, "Update ROA draft '{}'", name)?;
+                if !updates.added().is_empty() {
+                    write!(f, "  ADD:",)?;
+                    for addition in updates.added() {
+                        write!(f, " {}", addition)?;
+                    }
+                }
+                if !updates.removed().is_empty() {
+                    write!(f, "  REMOVE:",)?;
+                    for rem in updates.removed() {
+                        write!(f, " {}", rem)?;
+                    }
+                }
+                Ok(())
+            }
+            StorableCaCommand::RoaDraftRemove { name } => write!(f, "Remove ROA draft '{}'", name),
+            StorableCaCommand::RoaDraftCommit { name } => write!(f, "Commit ROA draft '{}'", name),
+            StorableCaCommand::RoaActivationRefused { refused } => {
+                write!(f, "Refuse activation of scheduled ROAs:")?;
+                for payload in refused {
+                    write!(f, " {}", payload)?;
+                }
+                Ok(())
+            }
+            StorableCaCommand::RoaAs0Policy { policy } => {
+                if policy.enabled() {
+                    write!(f, "Enable AS0 ROAs for unrouted space")
+                } else {
+                    write!(f, "Disable AS0 ROAs for unrouted space")
+                }
+            }
             StorableCaCommand::ReissueBeforeExpiring => {
                 write!(f, "Automatically re-issue objects before they would expire")
             }
@@ -777,10 +935,29 @@ impl fmt::Display for StorableCaCommand {
             // ------------------------------------------------------------
             StorableCaCommand::BgpSecDefinitionUpdates => write!(f, "Update BGPSec definitions"),
 
+            // ------------------------------------------------------------
+            // Signed Prefix Lists
+            // ------------------------------------------------------------
+            StorableCaCommand::PrefixListsUpdate { updates } => write!(f, "{}", updates),
+
+            // ------------------------------------------------------------
+            // Ghostbusters
+            // ------------------------------------------------------------
+            StorableCaCommand::GhostbusterUpdate { contact } => match contact {
+                Some(contact) => write!(f, "Update Ghostbuster contact to: {}", contact.full_name()),
+                None => write!(f, "Remove Ghostbuster contact"),
+            },
+
             // ------------------------------------------------------------
             // Publishing
             // ------------------------------------------------------------
             StorableCaCommand::RepoUpdate { service_uri } => write!(f, "Update repo to server at: {}", service_uri),
+            StorableCaCommand::RepoMirrorAdd { service_uri } => {
+                write!(f, "Add repo mirror at server: {}", service_uri)
+            }
+            StorableCaCommand::RepoMirrorRemove { service_uri } => {
+                write!(f, "Remove repo mirror at server: {}", service_uri)
+            }
 
             // ------------------------------------------------------------
             // RTA
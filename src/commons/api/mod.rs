@@ -6,18 +6,45 @@ pub use self::admin::*;
 mod aspa;
 pub use self::aspa::*;
 
+mod asrel;
+pub use self::asrel::*;
+
 mod bgpsec;
 pub use self::bgpsec::*;
 
 mod ca;
 pub use self::ca::*;
 
+mod ghostbuster;
+pub use self::ghostbuster::*;
+
 mod history;
 pub use self::history::*;
 
+mod objects;
+pub use self::objects::*;
+
+mod onboarding;
+pub use self::onboarding::*;
+
+mod prefixlist;
+pub use self::prefixlist::*;
+
 mod roas;
 pub use self::roas::*;
 
+mod rpsl;
+pub use self::rpsl::*;
+
+mod shrink;
+pub use self::shrink::*;
+
+mod validation;
+pub use self::validation::*;
+
+mod vrps;
+pub use self::vrps::*;
+
 pub mod rrdp;
 
 use std::{collections::HashMap, fmt};
@@ -114,6 +141,10 @@ impl ErrorResponse {
         res
     }
 
+    pub fn with_roa_draft(self, name: &str) -> Self {
+        self.with_arg("draft", name)
+    }
+
     pub fn with_asn(self, asn: Asn) -> Self {
         self.with_arg("asn", asn)
     }
@@ -141,6 +172,10 @@ impl ErrorResponse {
         self.with_arg("class_name", class_name)
     }
 
+    pub fn with_object_name(self, name: &ObjectName) -> Self {
+        self.with_arg("name", name)
+    }
+
     pub fn label(&self) -> &str {
         &self.label
     }
@@ -0,0 +1,317 @@
+//! Information about the objects published by a CA.
+//!
+//! This lets operators see exactly what a CA publishes: for each object the
+//! URI, hash, serial, validity and the decoded content.
+use std::fmt;
+
+use rpki::{
+    ca::{idexchange::CaHandle, provisioning::ResourceClassName},
+    crypto::KeyIdentifier,
+    repository::{
+        resources::ResourceSet,
+        x509::{Serial, Time, Validity},
+    },
+    rrdp::Hash,
+    uri,
+};
+
+use crate::commons::api::{AspaDefinition, RoaPayload};
+
+//------------ CaPublishedObjects --------------------------------------------
+
+/// All objects published by a CA, per resource class and key.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CaPublishedObjects {
+    ca: CaHandle,
+    classes: Vec<ResourceClassPublishedObjects>,
+}
+
+impl CaPublishedObjects {
+    pub fn new(ca: CaHandle, classes: Vec<ResourceClassPublishedObjects>) -> Self {
+        CaPublishedObjects { ca, classes }
+    }
+
+    pub fn ca(&self) -> &CaHandle {
+        &self.ca
+    }
+
+    pub fn classes(&self) -> &Vec<ResourceClassPublishedObjects> {
+        &self.classes
+    }
+
+    /// Returns all objects, for all resource classes and keys.
+    pub fn objects(&self) -> impl Iterator<Item = &PublishedObjectInfo> {
+        self.classes
+            .iter()
+            .flat_map(|class| class.keys.iter())
+            .flat_map(|key| key.objects.iter())
+    }
+}
+
+impl fmt::Display for CaPublishedObjects {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.classes.is_empty() {
+            return writeln!(f, "CA '{}' does not publish any objects", self.ca);
+        }
+        for class in &self.classes {
+            writeln!(f, "Resource class: {}", class.class_name)?;
+            for key in &class.keys {
+                writeln!(f, "  Key: {} ({})", key.key_id, key.state)?;
+                for object in &key.objects {
+                    write!(f, "{}", object)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//------------ ResourceClassPublishedObjects ---------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResourceClassPublishedObjects {
+    class_name: ResourceClassName,
+    keys: Vec<KeyPublishedObjects>,
+}
+
+impl ResourceClassPublishedObjects {
+    pub fn new(class_name: ResourceClassName, keys: Vec<KeyPublishedObjects>) -> Self {
+        ResourceClassPublishedObjects { class_name, keys }
+    }
+
+    pub fn class_name(&self) -> &ResourceClassName {
+        &self.class_name
+    }
+
+    pub fn keys(&self) -> &Vec<KeyPublishedObjects> {
+        &self.keys
+    }
+}
+
+//------------ KeyPublishedObjects -------------------------------------------
+
+/// The objects published under a key. The manifest and CRL come first.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct KeyPublishedObjects {
+    key_id: KeyIdentifier,
+    state: PublishedKeyState,
+    objects: Vec<PublishedObjectInfo>,
+}
+
+impl KeyPublishedObjects {
+    pub fn new(key_id: KeyIdentifier, state: PublishedKeyState, objects: Vec<PublishedObjectInfo>) -> Self {
+        KeyPublishedObjects { key_id, state, objects }
+    }
+
+    pub fn key_id(&self) -> &KeyIdentifier {
+        &self.key_id
+    }
+
+    pub fn state(&self) -> PublishedKeyState {
+        self.state
+    }
+
+    pub fn objects(&self) -> &Vec<PublishedObjectInfo> {
+        &self.objects
+    }
+}
+
+//------------ PublishedKeyState ---------------------------------------------
+
+/// The state of a key in its resource class. Only the current key publishes
+/// objects other than its manifest and CRL.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishedKeyState {
+    Current,
+    Staging,
+    Old,
+}
+
+impl fmt::Display for PublishedKeyState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PublishedKeyState::Current => write!(f, "current"),
+            PublishedKeyState::Staging => write!(f, "staging"),
+            PublishedKeyState::Old => write!(f, "old"),
+        }
+    }
+}
+
+//------------ PublishedObjectInfo -------------------------------------------
+
+/// A published object. For CRLs the validity is the period between their
+/// "this update" and "next update" times.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublishedObjectInfo {
+    uri: uri::Rsync,
+    hash: Hash,
+    serial: Serial,
+    validity: Validity,
+    content: PublishedObjectContent,
+}
+
+impl PublishedObjectInfo {
+    pub fn new(
+        uri: uri::Rsync,
+        hash: Hash,
+        serial: Serial,
+        validity: Validity,
+        content: PublishedObjectContent,
+    ) -> Self {
+        PublishedObjectInfo {
+            uri,
+            hash,
+            serial,
+            validity,
+            content,
+        }
+    }
+
+    pub fn uri(&self) -> &uri::Rsync {
+        &self.uri
+    }
+
+    pub fn hash(&self) -> &Hash {
+        &self.hash
+    }
+
+    pub fn serial(&self) -> Serial {
+        self.serial
+    }
+
+    pub fn validity(&self) -> &Validity {
+        &self.validity
+    }
+
+    pub fn content(&self) -> &PublishedObjectContent {
+        &self.content
+    }
+}
+
+impl fmt::Display for PublishedObjectInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    {}", self.uri)?;
+        writeln!(f, "      hash:     {}", self.hash)?;
+        writeln!(f, "      serial:   {}", self.serial)?;
+        writeln!(
+            f,
+            "      validity: {} - {}",
+            self.validity.not_before().to_rfc3339(),
+            self.validity.not_after().to_rfc3339()
+        )?;
+        write!(f, "{}", self.content)
+    }
+}
+
+//------------ PublishedObjectContent ----------------------------------------
+
+/// The decoded content of a published object.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PublishedObjectContent {
+    Manifest {
+        number: Serial,
+        this_update: Time,
+        next_update: Time,
+        entries: Vec<ManifestEntry>,
+    },
+    Crl {
+        number: Serial,
+        this_update: Time,
+        next_update: Time,
+        revoked: Vec<Serial>,
+    },
+    Roa {
+        authorizations: Vec<RoaPayload>,
+    },
+    Aspa {
+        definition: AspaDefinition,
+    },
+    RouterCertificate {
+        subject_key: KeyIdentifier,
+        resources: ResourceSet,
+    },
+    ChildCertificate {
+        subject_key: KeyIdentifier,
+        resources: ResourceSet,
+    },
+
+    /// Ghostbuster records, prefix lists and TAKs are listed, but their
+    /// content is not decoded.
+    Other,
+}
+
+impl fmt::Display for PublishedObjectContent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PublishedObjectContent::Manifest {
+                number,
+                this_update,
+                next_update,
+                entries,
+            } => {
+                writeln!(f, "      manifest number: {}", number)?;
+                writeln!(f, "      this update:     {}", this_update.to_rfc3339())?;
+                writeln!(f, "      next update:     {}", next_update.to_rfc3339())?;
+                for entry in entries {
+                    writeln!(f, "        {} {}", entry.hash, entry.file)?;
+                }
+                Ok(())
+            }
+            PublishedObjectContent::Crl {
+                number,
+                this_update,
+                next_update,
+                revoked,
+            } => {
+                writeln!(f, "      crl number:  {}", number)?;
+                writeln!(f, "      this update: {}", this_update.to_rfc3339())?;
+                writeln!(f, "      next update: {}", next_update.to_rfc3339())?;
+                for serial in revoked {
+                    writeln!(f, "        revoked: {}", serial)?;
+                }
+                Ok(())
+            }
+            PublishedObjectContent::Roa { authorizations } => {
+                for auth in authorizations {
+                    writeln!(f, "      roa: {}", auth)?;
+                }
+                Ok(())
+            }
+            PublishedObjectContent::Aspa { definition } => writeln!(f, "      aspa: {}", definition),
+            PublishedObjectContent::RouterCertificate { subject_key, resources } => {
+                writeln!(f, "      router key: {}", subject_key)?;
+                writeln!(f, "      asns:       {}", resources.asn())
+            }
+            PublishedObjectContent::ChildCertificate { subject_key, resources } => {
+                writeln!(f, "      child key: {}", subject_key)?;
+                writeln!(f, "      resources: {}", resources)
+            }
+            PublishedObjectContent::Other => Ok(()),
+        }
+    }
+}
+
+//------------ ManifestEntry -------------------------------------------------
+
+/// A file listed on a manifest, and its hex encoded SHA-256 hash.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ManifestEntry {
+    file: String,
+    hash: String,
+}
+
+impl ManifestEntry {
+    pub fn new(file: String, hash: String) -> Self {
+        ManifestEntry { file, hash }
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+}
@@ -0,0 +1,256 @@
+//! Self-service onboarding of children using pre-authorised tokens.
+//!
+//! A parent CA can mint single-use tokens which are bound to the resources
+//! for a new child and an expiry time. The operator of the child hands the
+//! token to their Krill, which then sends its RFC 8183 child request along
+//! with the token to the parent, and adds the parent using the RFC 8183
+//! parent response it gets back.
+use std::{fmt, str::FromStr};
+
+use rpki::{
+    ca::idexchange::{self, ParentHandle},
+    repository::resources::ResourceSet,
+    uri,
+};
+
+use crate::commons::{api::Timestamp, util::sha256};
+
+//------------ OnboardingTokenRequest ----------------------------------------
+
+/// A request to mint a token for a new child which will be entitled to the
+/// given resources. The token can be used until it expires.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OnboardingTokenRequest {
+    resources: ResourceSet,
+    expires: Timestamp,
+}
+
+impl OnboardingTokenRequest {
+    pub fn new(resources: ResourceSet, expires: Timestamp) -> Self {
+        OnboardingTokenRequest { resources, expires }
+    }
+
+    pub fn unpack(self) -> (ResourceSet, Timestamp) {
+        (self.resources, self.expires)
+    }
+}
+
+impl fmt::Display for OnboardingTokenRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "resources '{}' expires '{}'",
+            self.resources,
+            self.expires.to_rfc3339()
+        )
+    }
+}
+
+//------------ OnboardingToken -----------------------------------------------
+
+/// A newly minted onboarding token, and everything a child needs to know
+/// in order to use it.
+///
+/// Note that the parent CA only keeps the hash of the token, so this is
+/// the only time that the token itself is shown. It can be given to the
+/// operator of the child as is.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OnboardingToken {
+    parent: ParentHandle,
+    uri: uri::Https,
+    token: String,
+    resources: ResourceSet,
+    expires: Timestamp,
+}
+
+impl OnboardingToken {
+    pub fn new(
+        parent: ParentHandle,
+        uri: uri::Https,
+        token: String,
+        resources: ResourceSet,
+        expires: Timestamp,
+    ) -> Self {
+        OnboardingToken {
+            parent,
+            uri,
+            token,
+            resources,
+            expires,
+        }
+    }
+
+    pub fn parent(&self) -> &ParentHandle {
+        &self.parent
+    }
+
+    /// The URI where the child should send its request.
+    pub fn uri(&self) -> &uri::Https {
+        &self.uri
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn resources(&self) -> &ResourceSet {
+        &self.resources
+    }
+
+    pub fn expires(&self) -> Timestamp {
+        self.expires
+    }
+
+    pub fn id(&self) -> OnboardingTokenId {
+        OnboardingTokenId::for_token(&self.token)
+    }
+}
+
+impl fmt::Display for OnboardingToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "parent:    {}", self.parent)?;
+        writeln!(f, "uri:       {}", self.uri)?;
+        writeln!(f, "token:     {}", self.token)?;
+        writeln!(f, "resources: {}", self.resources)?;
+        writeln!(f, "expires:   {}", self.expires.to_rfc3339())
+    }
+}
+
+//------------ OnboardingTokenId ---------------------------------------------
+
+/// Identifies an onboarding token by the hex encoded SHA-256 hash of the
+/// token. This lets the parent CA recognise a token, without having to
+/// store the token itself.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct OnboardingTokenId(String);
+
+impl OnboardingTokenId {
+    pub fn for_token(token: &str) -> Self {
+        OnboardingTokenId(hex::encode(sha256(token.as_bytes())))
+    }
+}
+
+impl FromStr for OnboardingTokenId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(OnboardingTokenId(s.to_ascii_lowercase()))
+        } else {
+            Err(format!("invalid onboarding token id: {}", s))
+        }
+    }
+}
+
+impl fmt::Display for OnboardingTokenId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+//------------ OnboardingTokenInfo -------------------------------------------
+
+/// The details kept by a parent CA for an onboarding token which was not
+/// yet used.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OnboardingTokenInfo {
+    id: OnboardingTokenId,
+    resources: ResourceSet,
+    expires: Timestamp,
+}
+
+impl OnboardingTokenInfo {
+    pub fn new(id: OnboardingTokenId, resources: ResourceSet, expires: Timestamp) -> Self {
+        OnboardingTokenInfo { id, resources, expires }
+    }
+
+    pub fn id(&self) -> &OnboardingTokenId {
+        &self.id
+    }
+
+    pub fn resources(&self) -> &ResourceSet {
+        &self.resources
+    }
+
+    pub fn expires(&self) -> Timestamp {
+        self.expires
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires <= Timestamp::now()
+    }
+}
+
+//------------ OnboardingTokenList -------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OnboardingTokenList(Vec<OnboardingTokenInfo>);
+
+impl OnboardingTokenList {
+    pub fn new(tokens: Vec<OnboardingTokenInfo>) -> Self {
+        OnboardingTokenList(tokens)
+    }
+
+    pub fn tokens(&self) -> &Vec<OnboardingTokenInfo> {
+        &self.0
+    }
+}
+
+impl fmt::Display for OnboardingTokenList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            writeln!(f, "No unused onboarding tokens")
+        } else {
+            for token in &self.0 {
+                writeln!(f, "id:        {}", token.id)?;
+                writeln!(f, "resources: {}", token.resources)?;
+                if token.is_expired() {
+                    writeln!(f, "expired:   {}", token.expires.to_rfc3339())?;
+                } else {
+                    writeln!(f, "expires:   {}", token.expires.to_rfc3339())?;
+                }
+                writeln!(f)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+//------------ OnboardingTokenRedeem -----------------------------------------
+
+/// Sent by a child to its parent to use an onboarding token. The parent
+/// will add the child using the ID certificate in the child request, and
+/// reply with its RFC 8183 parent response.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OnboardingTokenRedeem {
+    token: String,
+    child_request: idexchange::ChildRequest,
+}
+
+impl OnboardingTokenRedeem {
+    pub fn new(token: String, child_request: idexchange::ChildRequest) -> Self {
+        OnboardingTokenRedeem { token, child_request }
+    }
+
+    pub fn unpack(self) -> (String, idexchange::ChildRequest) {
+        (self.token, self.child_request)
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_id_is_hash_of_token() {
+        let id = OnboardingTokenId::for_token("secret");
+        assert_eq!(
+            id.to_string(),
+            "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b"
+        );
+        assert_eq!(OnboardingTokenId::from_str(&id.to_string()).unwrap(), id);
+        assert!(OnboardingTokenId::from_str("secret").is_err());
+    }
+}
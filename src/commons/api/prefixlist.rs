@@ -0,0 +1,211 @@
+//! Signed Prefix Lists
+//!
+//! This is still being discussed in the IETF. No RFC just yet.
+//! See the following draft:
+//! https://datatracker.ietf.org/doc/draft-ietf-sidrops-rpki-prefixlist/
+
+use std::{fmt, str::FromStr};
+
+use rpki::repository::resources::Asn;
+
+use crate::commons::api::TypedPrefix;
+
+//------------ PrefixListDefinitionUpdates -------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrefixListDefinitionUpdates {
+    add_or_replace: Vec<PrefixListDefinition>,
+    remove: Vec<Asn>,
+}
+
+impl PrefixListDefinitionUpdates {
+    pub fn new(add_or_replace: Vec<PrefixListDefinition>, remove: Vec<Asn>) -> Self {
+        PrefixListDefinitionUpdates { add_or_replace, remove }
+    }
+
+    pub fn unpack(self) -> (Vec<PrefixListDefinition>, Vec<Asn>) {
+        (self.add_or_replace, self.remove)
+    }
+}
+
+impl fmt::Display for PrefixListDefinitionUpdates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Update prefix list definitions:")?;
+        if !self.add_or_replace.is_empty() {
+            write!(f, " add or replace:")?;
+            for definition in &self.add_or_replace {
+                write!(f, " {}", definition)?;
+            }
+        }
+        if !self.remove.is_empty() {
+            write!(f, " remove where ASN is:")?;
+            for asn in &self.remove {
+                write!(f, " {}", asn)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//------------ PrefixListDefinitionList ----------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrefixListDefinitionList(Vec<PrefixListDefinition>);
+
+impl PrefixListDefinitionList {
+    pub fn new(mut definitions: Vec<PrefixListDefinition>) -> Self {
+        definitions.sort_by_key(|def| def.asn());
+        PrefixListDefinitionList(definitions)
+    }
+
+    pub fn as_slice(&self) -> &[PrefixListDefinition] {
+        self.0.as_slice()
+    }
+}
+
+impl fmt::Display for PrefixListDefinitionList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for def in self.0.iter() {
+            writeln!(f, "{}", def)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ PrefixListDefinition --------------------------------------
+
+/// The prefixes which an ASN may originate, to be published in a Signed
+/// Prefix List. The prefixes need not be held by the CA, only the ASN.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrefixListDefinition {
+    asn: Asn,
+    prefixes: Vec<TypedPrefix>,
+}
+
+impl PrefixListDefinition {
+    /// Creates a new definition. The prefixes are sorted, IPv4 before IPv6,
+    /// and duplicates are removed, so that equal lists result in equal
+    /// definitions.
+    pub fn new(asn: Asn, mut prefixes: Vec<TypedPrefix>) -> Self {
+        prefixes.sort_by_key(|prefix| (!prefix.is_v4(), *prefix));
+        prefixes.dedup();
+        PrefixListDefinition { asn, prefixes }
+    }
+
+    pub fn asn(&self) -> Asn {
+        self.asn
+    }
+
+    pub fn prefixes(&self) -> &Vec<TypedPrefix> {
+        &self.prefixes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+}
+
+impl fmt::Display for PrefixListDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // example: AS65000 => 10.0.0.0/16, 2001:db8::/32
+        write!(f, "{} => ", self.asn)?;
+        for (i, prefix) in self.prefixes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", prefix)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PrefixListDefinition {
+    type Err = PrefixListDefinitionFormatError;
+
+    // example: AS65000 => 10.0.0.0/16, 2001:db8::/32
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split("=>");
+
+        let asn = {
+            let asn_str = parts.next().ok_or(PrefixListDefinitionFormatError::AsnMissing)?.trim();
+            Asn::from_str(asn_str).map_err(|_| PrefixListDefinitionFormatError::AsnInvalid(asn_str.to_string()))?
+        };
+
+        let prefixes_str = parts.next().ok_or(PrefixListDefinitionFormatError::PrefixesMissing)?;
+
+        // unexpected extra bits are not acceptable
+        if parts.next().is_some() {
+            return Err(PrefixListDefinitionFormatError::ExtraParts);
+        }
+
+        let mut prefixes = vec![];
+        for prefix_str in prefixes_str.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let prefix = TypedPrefix::from_str(prefix_str)
+                .map_err(|_| PrefixListDefinitionFormatError::PrefixInvalid(prefix_str.to_string()))?;
+            prefixes.push(prefix);
+        }
+
+        if prefixes.is_empty() {
+            Err(PrefixListDefinitionFormatError::PrefixesMissing)
+        } else {
+            Ok(PrefixListDefinition::new(asn, prefixes))
+        }
+    }
+}
+
+//------------ PrefixListDefinitionFormatError ---------------------------
+
+#[derive(Clone, Debug)]
+pub enum PrefixListDefinitionFormatError {
+    AsnMissing,
+    AsnInvalid(String),
+    PrefixesMissing,
+    PrefixInvalid(String),
+    ExtraParts,
+}
+
+impl fmt::Display for PrefixListDefinitionFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Prefix list format invalid: ")?;
+        match self {
+            PrefixListDefinitionFormatError::AsnMissing => write!(f, "ASN missing"),
+            PrefixListDefinitionFormatError::AsnInvalid(s) => write!(f, "cannot parse ASN: {}", s),
+            PrefixListDefinitionFormatError::PrefixesMissing => write!(f, "at least one prefix is required"),
+            PrefixListDefinitionFormatError::PrefixInvalid(s) => write!(f, "cannot parse prefix: {}", s),
+            PrefixListDefinitionFormatError::ExtraParts => write!(f, "found more than one '=>'"),
+        }
+    }
+}
+
+impl std::error::Error for PrefixListDefinitionFormatError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn prefix_list_definition_to_from_str() {
+        let definition = PrefixListDefinition::new(
+            Asn::from_u32(65000),
+            vec![
+                TypedPrefix::from_str("2001:db8::/32").unwrap(),
+                TypedPrefix::from_str("10.0.0.0/16").unwrap(),
+            ],
+        );
+        let definition_str = "AS65000 => 10.0.0.0/16, 2001:db8::/32";
+
+        assert_eq!(definition_str, definition.to_string().as_str());
+
+        let from_str = PrefixListDefinition::from_str("AS65000 => 2001:db8::/32, 10.0.0.0/16, 10.0.0.0/16").unwrap();
+        assert_eq!(definition, from_str);
+    }
+
+    #[test]
+    fn prefix_list_definition_needs_prefixes() {
+        assert!(PrefixListDefinition::from_str("AS65000").is_err());
+        assert!(PrefixListDefinition::from_str("AS65000 => ").is_err());
+        assert!(PrefixListDefinition::from_str("AS65000 => 10.0.0.0/16 => 10.1.0.0/16").is_err());
+    }
+}
@@ -1,4 +1,11 @@
-use std::{cmp::Ordering, fmt, net::IpAddr, ops::Deref, str::FromStr};
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    ops::Deref,
+    str::FromStr,
+};
 
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
@@ -7,7 +14,7 @@ use rpki::repository::{
     roa::RoaIpAddress,
 };
 
-use crate::daemon::ca::RoaInfo;
+use crate::{commons::api::Timestamp, daemon::ca::RoaInfo};
 
 //------------ RoaAggregateKey ---------------------------------------------
 
@@ -320,9 +327,8 @@ impl AsRef<TypedPrefix> for RoaPayload {
 /// It includes the actual ROA payload that needs be authorized on an RFC 6482
 /// ROA object, as well as other information that is only visible to the Krill
 /// users - like the optional comment field, which can be used to store useful
-/// reminders of the purpose of this configuration. And in future perhaps other
-/// things such as tags used for classification/monitoring/bpp analysis could
-/// be added.
+/// reminders of the purpose of this configuration, and [`RoaMetadata`] which
+/// can be used to record who asked for the ROA and why.
 ///
 /// Note that the [`ConfiguredRoa`] type defines an *existing* configured ROA.
 /// Existing ROAs may contain other information that the Krill system is
@@ -343,11 +349,39 @@ pub struct RoaConfiguration {
     payload: RoaPayload,
     #[serde(default)] // missing is same as no comment
     comment: Option<String>,
+    // Missing metadata leaves the metadata of an existing configuration
+    // unchanged in updates. Use empty metadata to clear it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metadata: Option<RoaMetadata>,
+    // Optional time bounds. A configuration with a 'not_before' time in
+    // the future is scheduled, and will only be added at that time. A
+    // configuration with a 'not_after' time will be removed at that time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    not_before: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    not_after: Option<Timestamp>,
 }
 
 impl RoaConfiguration {
     pub fn new(payload: RoaPayload, comment: Option<String>) -> Self {
-        RoaConfiguration { payload, comment }
+        RoaConfiguration {
+            payload,
+            comment,
+            metadata: None,
+            not_before: None,
+            not_after: None,
+        }
+    }
+
+    pub fn with_time_bounds(mut self, not_before: Option<Timestamp>, not_after: Option<Timestamp>) -> Self {
+        self.not_before = not_before;
+        self.not_after = not_after;
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: RoaMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
     }
 
     pub fn unpack(self) -> (RoaPayload, Option<String>) {
@@ -362,11 +396,47 @@ impl RoaConfiguration {
         self.comment.as_ref()
     }
 
+    pub fn metadata(&self) -> Option<&RoaMetadata> {
+        self.metadata.as_ref()
+    }
+
+    pub fn not_before(&self) -> Option<Timestamp> {
+        self.not_before
+    }
+
+    pub fn not_after(&self) -> Option<Timestamp> {
+        self.not_after
+    }
+
+    /// Returns true if this configuration should only be added at a
+    /// later time.
+    pub fn is_scheduled(&self, now: Timestamp) -> bool {
+        self.not_before.map(|not_before| not_before > now).unwrap_or(false)
+    }
+
+    /// Returns true if this configuration should no longer be active.
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.not_after.map(|not_after| not_after <= now).unwrap_or(false)
+    }
+
+    /// Returns false if the configuration has a 'not_after' time which is
+    /// not after its 'not_before' time (if set), or which is in the past.
+    pub fn time_bounds_valid(&self, now: Timestamp) -> bool {
+        match (self.not_before, self.not_after) {
+            (_, None) => true,
+            (None, Some(not_after)) => not_after > now,
+            (Some(not_before), Some(not_after)) => not_after > now && not_after > not_before,
+        }
+    }
+
     /// Ensures that the payload uses an explicit max length
     pub fn into_explicit_max_length(self) -> Self {
         RoaConfiguration {
             payload: self.payload.into_explicit_max_length(),
             comment: self.comment,
+            metadata: self.metadata,
+            not_before: self.not_before,
+            not_after: self.not_after,
         }
     }
 }
@@ -393,7 +463,7 @@ impl FromStr for RoaConfiguration {
         let payload = RoaPayload::from_str(payload_part)?;
         let comment = parts.next().map(|s| s.trim().to_string());
 
-        Ok(RoaConfiguration { payload, comment })
+        Ok(RoaConfiguration::new(payload, comment))
     }
 }
 
@@ -411,7 +481,209 @@ impl PartialOrd for RoaConfiguration {
 
 impl From<RoaPayload> for RoaConfiguration {
     fn from(payload: RoaPayload) -> Self {
-        RoaConfiguration { payload, comment: None }
+        RoaConfiguration::new(payload, None)
+    }
+}
+
+//------------ RoaMetadata -------------------------------------------------
+
+/// Structured information about a ROA configuration which is only visible
+/// to Krill users. It is not published in the ROA objects, but it helps to
+/// answer questions like: who asked for this ROA, and why?
+///
+/// Tags are free form key/value pairs. A tag without a value uses an empty
+/// string as its value.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RoaMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ticket: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    customer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expiry_note: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tags: BTreeMap<String, String>,
+}
+
+impl RoaMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.owner.is_none()
+            && self.ticket.is_none()
+            && self.customer.is_none()
+            && self.expiry_note.is_none()
+            && self.tags.is_empty()
+    }
+
+    pub fn owner(&self) -> Option<&String> {
+        self.owner.as_ref()
+    }
+
+    pub fn set_owner(&mut self, owner: Option<String>) {
+        self.owner = owner;
+    }
+
+    pub fn ticket(&self) -> Option<&String> {
+        self.ticket.as_ref()
+    }
+
+    pub fn set_ticket(&mut self, ticket: Option<String>) {
+        self.ticket = ticket;
+    }
+
+    pub fn customer(&self) -> Option<&String> {
+        self.customer.as_ref()
+    }
+
+    pub fn set_customer(&mut self, customer: Option<String>) {
+        self.customer = customer;
+    }
+
+    pub fn expiry_note(&self) -> Option<&String> {
+        self.expiry_note.as_ref()
+    }
+
+    pub fn set_expiry_note(&mut self, expiry_note: Option<String>) {
+        self.expiry_note = expiry_note;
+    }
+
+    pub fn tags(&self) -> &BTreeMap<String, String> {
+        &self.tags
+    }
+
+    pub fn tag(&self, key: &str) -> Option<&String> {
+        self.tags.get(key)
+    }
+
+    /// Adds a tag, replacing any existing value for the same key.
+    pub fn add_tag(&mut self, key: String, value: String) {
+        self.tags.insert(key, value);
+    }
+
+    /// Adds a tag using "key=value" or "key" notation.
+    pub fn add_tag_str(&mut self, s: &str) -> Result<(), AuthorizationFmtError> {
+        let (key, value) = parse_tag(s)?;
+        self.add_tag(key, value.unwrap_or_default());
+        Ok(())
+    }
+}
+
+/// Shows the metadata as key=value pairs on a single line, e.g.:
+/// `owner=noc ticket=T-1 tag:service=cdn tag:reviewed`
+impl fmt::Display for RoaMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = vec![];
+        if let Some(owner) = &self.owner {
+            parts.push(format!("owner={}", owner));
+        }
+        if let Some(ticket) = &self.ticket {
+            parts.push(format!("ticket={}", ticket));
+        }
+        if let Some(customer) = &self.customer {
+            parts.push(format!("customer={}", customer));
+        }
+        if let Some(expiry_note) = &self.expiry_note {
+            parts.push(format!("expiry_note={}", expiry_note));
+        }
+        for (key, value) in self.tags.iter() {
+            if value.is_empty() {
+                parts.push(format!("tag:{}", key));
+            } else {
+                parts.push(format!("tag:{}={}", key, value));
+            }
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+// Parses "key=value" or "key" into a tag key and optional value.
+fn parse_tag(s: &str) -> Result<(String, Option<String>), AuthorizationFmtError> {
+    let mut parts = s.splitn(2, '=');
+    let key = parts.next().unwrap_or_default().trim();
+    if key.is_empty() {
+        return Err(AuthorizationFmtError::tag(s));
+    }
+    let value = parts.next().map(|v| v.trim().to_string());
+    Ok((key.to_string(), value))
+}
+
+//------------ RoaConfigurationFilter --------------------------------------
+
+/// Selects ROA configurations based on their [`RoaMetadata`]. All criteria
+/// which are set must match. An empty filter matches all configurations.
+///
+/// A tag criterion without a value matches any configuration which has the
+/// tag, regardless of its value.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RoaConfigurationFilter {
+    owner: Option<String>,
+    ticket: Option<String>,
+    customer: Option<String>,
+    tags: Vec<(String, Option<String>)>,
+}
+
+impl RoaConfigurationFilter {
+    pub fn is_empty(&self) -> bool {
+        self.owner.is_none() && self.ticket.is_none() && self.customer.is_none() && self.tags.is_empty()
+    }
+
+    pub fn owner(&self) -> Option<&String> {
+        self.owner.as_ref()
+    }
+
+    pub fn set_owner(&mut self, owner: Option<String>) {
+        self.owner = owner;
+    }
+
+    pub fn ticket(&self) -> Option<&String> {
+        self.ticket.as_ref()
+    }
+
+    pub fn set_ticket(&mut self, ticket: Option<String>) {
+        self.ticket = ticket;
+    }
+
+    pub fn customer(&self) -> Option<&String> {
+        self.customer.as_ref()
+    }
+
+    pub fn set_customer(&mut self, customer: Option<String>) {
+        self.customer = customer;
+    }
+
+    /// Returns the tag criteria in "key=value" or "key" notation.
+    pub fn tags(&self) -> Vec<String> {
+        self.tags
+            .iter()
+            .map(|(key, value)| match value {
+                Some(value) => format!("{}={}", key, value),
+                None => key.clone(),
+            })
+            .collect()
+    }
+
+    /// Adds a tag criterion using "key=value" or "key" notation.
+    pub fn add_tag_str(&mut self, s: &str) -> Result<(), AuthorizationFmtError> {
+        self.tags.push(parse_tag(s)?);
+        Ok(())
+    }
+
+    pub fn matches(&self, configuration: &RoaConfiguration) -> bool {
+        let no_metadata = RoaMetadata::default();
+        let metadata = configuration.metadata().unwrap_or(&no_metadata);
+
+        fn field_matches(criterion: Option<&String>, value: Option<&String>) -> bool {
+            criterion.is_none() || criterion == value
+        }
+
+        field_matches(self.owner.as_ref(), metadata.owner())
+            && field_matches(self.ticket.as_ref(), metadata.ticket())
+            && field_matches(self.customer.as_ref(), metadata.customer())
+            && self.tags.iter().all(|(key, value)| match metadata.tag(key) {
+                None => false,
+                Some(found) => value.is_none() || value.as_ref() == Some(found),
+            })
     }
 }
 
@@ -477,9 +749,20 @@ impl AsRef<RoaConfiguration> for ConfiguredRoa {
     }
 }
 
+/// Shows the configuration on a single line. Any metadata is shown after
+/// the comment, e.g.: `192.168.0.0/16-16 => 64496 # cdn owner=noc tag:reviewed`
 impl fmt::Display for ConfiguredRoa {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.roa_configuration)
+        write!(f, "{}", self.roa_configuration)?;
+
+        if let Some(metadata) = self.roa_configuration.metadata().filter(|m| !m.is_empty()) {
+            if self.roa_configuration.comment().is_none() {
+                write!(f, " #")?;
+            }
+            write!(f, " {}", metadata)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -503,6 +786,10 @@ impl PartialOrd for ConfiguredRoa {
 pub struct ConfiguredRoas(Vec<ConfiguredRoa>);
 
 impl ConfiguredRoas {
+    pub fn new(roas: Vec<ConfiguredRoa>) -> Self {
+        ConfiguredRoas(roas)
+    }
+
     pub fn unpack(self) -> Vec<ConfiguredRoa> {
         self.0
     }
@@ -584,6 +871,63 @@ impl RoaConfigurationUpdates {
     pub fn remove(&mut self, rem: RoaPayload) {
         self.removed.push(rem);
     }
+
+    /// Sets the metadata on all added configurations.
+    pub fn with_metadata(self, metadata: RoaMetadata) -> Self {
+        let added = self
+            .added
+            .into_iter()
+            .map(|added| added.with_metadata(metadata.clone()))
+            .collect();
+        RoaConfigurationUpdates {
+            added,
+            removed: self.removed,
+        }
+    }
+
+    /// Sets the time bounds on all added configurations.
+    pub fn with_time_bounds(self, not_before: Option<Timestamp>, not_after: Option<Timestamp>) -> Self {
+        let added = self
+            .added
+            .into_iter()
+            .map(|added| added.with_time_bounds(not_before, not_after))
+            .collect();
+        RoaConfigurationUpdates {
+            added,
+            removed: self.removed,
+        }
+    }
+
+    /// Merges other updates into this, e.g. when staging a draft change
+    /// over multiple API calls. Both self and other are expected to use
+    /// explicit max lengths.
+    ///
+    /// - An addition replaces an earlier addition for the same payload,
+    ///   so that comments can be amended.
+    /// - A removal of a payload that was added earlier cancels that
+    ///   addition. It is only recorded as a removal as well if the payload
+    ///   is currently configured, as reported by `is_configured`, because
+    ///   in that case the earlier addition only amended the existing
+    ///   configuration.
+    /// - Duplicate removals are ignored.
+    pub fn merge(&mut self, other: RoaConfigurationUpdates, is_configured: impl Fn(&RoaPayload) -> bool) {
+        let (added, removed) = other.unpack();
+
+        for rem in removed {
+            let before = self.added.len();
+            self.added.retain(|a| a.payload() != rem);
+            let cancelled = self.added.len() != before;
+
+            if (!cancelled || is_configured(&rem)) && !self.removed.contains(&rem) {
+                self.removed.push(rem);
+            }
+        }
+
+        for add in added {
+            self.added.retain(|a| a.payload() != add.payload());
+            self.added.push(add);
+        }
+    }
 }
 
 impl fmt::Display for RoaConfigurationUpdates {
@@ -630,6 +974,240 @@ impl FromStr for RoaConfigurationUpdates {
     }
 }
 
+//------------ RoaDraft ----------------------------------------------------
+
+pub type RoaDraftName = String;
+
+/// A named set of ROA configuration updates which is staged in a CA,
+/// but not yet applied. A draft can be built up over multiple API calls,
+/// evaluated as a whole using a BGP analysis dry run, and then committed
+/// as a single [`RoaConfigurationUpdates`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoaDraft {
+    name: RoaDraftName,
+    updates: RoaConfigurationUpdates,
+}
+
+impl RoaDraft {
+    pub fn new(name: RoaDraftName, updates: RoaConfigurationUpdates) -> Self {
+        RoaDraft { name, updates }
+    }
+
+    pub fn name(&self) -> &RoaDraftName {
+        &self.name
+    }
+
+    pub fn updates(&self) -> &RoaConfigurationUpdates {
+        &self.updates
+    }
+
+    pub fn unpack(self) -> (RoaDraftName, RoaConfigurationUpdates) {
+        (self.name, self.updates)
+    }
+
+    /// Returns true if the name can be used for a draft. Draft names are
+    /// used as a segment in API paths, so they are restricted to the
+    /// characters allowed in handles, except for '/'.
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.len() <= 255
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    }
+}
+
+impl fmt::Display for RoaDraft {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# draft: {}", self.name)?;
+        write!(f, "{}", self.updates)
+    }
+}
+
+//------------ RoaDraftList ------------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoaDraftList(Vec<RoaDraft>);
+
+impl RoaDraftList {
+    pub fn new(drafts: Vec<RoaDraft>) -> Self {
+        RoaDraftList(drafts)
+    }
+
+    pub fn unpack(self) -> Vec<RoaDraft> {
+        self.0
+    }
+}
+
+impl fmt::Display for RoaDraftList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for draft in self.0.iter() {
+            writeln!(
+                f,
+                "{} (added: {}, removed: {})",
+                draft.name(),
+                draft.updates().added().len(),
+                draft.updates().removed().len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+//------------ TimedRoaChange ----------------------------------------------
+
+/// The kind of change that is planned for a time-bounded ROA configuration.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimedRoaChangeKind {
+    Activate,
+    Expire,
+}
+
+impl fmt::Display for TimedRoaChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimedRoaChangeKind::Activate => write!(f, "activate"),
+            TimedRoaChangeKind::Expire => write!(f, "expire"),
+        }
+    }
+}
+
+/// An upcoming activation or expiration of a ROA configuration.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TimedRoaChange {
+    when: Timestamp,
+    kind: TimedRoaChangeKind,
+    configuration: RoaConfiguration,
+}
+
+impl TimedRoaChange {
+    pub fn new(when: Timestamp, kind: TimedRoaChangeKind, configuration: RoaConfiguration) -> Self {
+        TimedRoaChange {
+            when,
+            kind,
+            configuration,
+        }
+    }
+
+    pub fn when(&self) -> Timestamp {
+        self.when
+    }
+
+    pub fn kind(&self) -> TimedRoaChangeKind {
+        self.kind
+    }
+
+    pub fn configuration(&self) -> &RoaConfiguration {
+        &self.configuration
+    }
+}
+
+impl fmt::Display for TimedRoaChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.when.to_rfc3339(), self.kind, self.configuration)
+    }
+}
+
+/// The upcoming changes for time-bounded ROA configurations in a CA,
+/// ordered by time.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TimedRoaChanges(Vec<TimedRoaChange>);
+
+impl TimedRoaChanges {
+    pub fn new(mut changes: Vec<TimedRoaChange>) -> Self {
+        changes.sort_by_key(|change| change.when);
+        TimedRoaChanges(changes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the time of the first upcoming change, if any.
+    pub fn next(&self) -> Option<Timestamp> {
+        self.0.first().map(|change| change.when)
+    }
+
+    pub fn unpack(self) -> Vec<TimedRoaChange> {
+        self.0
+    }
+}
+
+impl fmt::Display for TimedRoaChanges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in self.0.iter() {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ RoaAs0Policy ------------------------------------------------
+
+/// Opt-in policy for a CA to protect the address space that it holds, but
+/// which is not covered by any configured ROA, using AS0 ROAs (RFC 6483 and
+/// RFC 7607). These ROAs are updated automatically when the resources of the
+/// CA, or its ROA configurations, change.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoaAs0Policy {
+    enabled: bool,
+}
+
+impl RoaAs0Policy {
+    pub fn new(enabled: bool) -> Self {
+        RoaAs0Policy { enabled }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+//------------ RoaAs0Status ------------------------------------------------
+
+/// The AS0 policy of a CA, and the AS0 payloads which are issued because
+/// of it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoaAs0Status {
+    enabled: bool,
+    payloads: Vec<RoaPayload>,
+}
+
+impl RoaAs0Status {
+    pub fn new(enabled: bool, mut payloads: Vec<RoaPayload>) -> Self {
+        payloads.sort();
+        RoaAs0Status { enabled, payloads }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn payloads(&self) -> &Vec<RoaPayload> {
+        &self.payloads
+    }
+}
+
+impl fmt::Display for RoaAs0Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.enabled {
+            writeln!(f, "AS0 ROAs for unrouted space are disabled")
+        } else if self.payloads.is_empty() {
+            writeln!(
+                f,
+                "AS0 ROAs for unrouted space are enabled, all held space is covered by ROAs"
+            )
+        } else {
+            writeln!(f, "AS0 ROAs for unrouted space are enabled, issued for:")?;
+            for payload in &self.payloads {
+                writeln!(f, "{}", payload)?;
+            }
+            Ok(())
+        }
+    }
+}
+
 //------------ TypedPrefix -------------------------------------------------
 #[derive(Clone, Copy, Eq, Hash, PartialEq)]
 pub enum TypedPrefix {
@@ -649,6 +1227,67 @@ impl TypedPrefix {
         }
     }
 
+    pub fn is_v4(&self) -> bool {
+        matches!(self, TypedPrefix::V4(_))
+    }
+
+    /// Returns the fewest prefixes which exactly cover the IPv4 and IPv6
+    /// address space in the resource set.
+    pub fn for_resource_set(resources: &ResourceSet) -> Vec<TypedPrefix> {
+        let mut prefixes = vec![];
+
+        for block in resources.ipv4().iter() {
+            let min = u32::from(block.min().to_v4()) as u128;
+            let max = u32::from(block.max().to_v4()) as u128;
+            for (addr, len) in Self::range_to_prefixes(min, max, 32) {
+                let prefix = format!("{}/{}", Ipv4Addr::from(addr as u32), len);
+                // cannot fail, the address is aligned to the prefix length
+                prefixes.push(TypedPrefix::V4(Ipv4Prefix(Prefix::from_v4_str(&prefix).unwrap())));
+            }
+        }
+
+        for block in resources.ipv6().iter() {
+            let min = u128::from(block.min().to_v6());
+            let max = u128::from(block.max().to_v6());
+            for (addr, len) in Self::range_to_prefixes(min, max, 128) {
+                let prefix = format!("{}/{}", Ipv6Addr::from(addr), len);
+                // cannot fail, the address is aligned to the prefix length
+                prefixes.push(TypedPrefix::V6(Ipv6Prefix(Prefix::from_v6_str(&prefix).unwrap())));
+            }
+        }
+
+        prefixes
+    }
+
+    /// Splits the inclusive range of addresses into aligned (address, length)
+    /// pairs, using the largest possible prefix each time.
+    fn range_to_prefixes(min: u128, max: u128, addr_bits: u32) -> Vec<(u128, u8)> {
+        fn host_mask(host_bits: u32) -> u128 {
+            if host_bits >= 128 {
+                u128::MAX
+            } else {
+                (1u128 << host_bits) - 1
+            }
+        }
+
+        let mut res = vec![];
+        let mut start = min;
+        loop {
+            let mut host_bits = start.trailing_zeros().min(addr_bits);
+            while host_bits > 0 && (start | host_mask(host_bits)) > max {
+                host_bits -= 1;
+            }
+            let end = start | host_mask(host_bits);
+            res.push((start, (addr_bits - host_bits) as u8));
+
+            if end >= max {
+                break;
+            }
+            start = end + 1;
+        }
+        res
+    }
+
     fn matches_type(&self, other: &TypedPrefix) -> bool {
         match &self {
             TypedPrefix::V4(_) => match other {
@@ -880,6 +1519,7 @@ pub enum AuthorizationFmtError {
     Asn(String),
     Auth(String),
     Delta(String),
+    Tag(String),
 }
 
 impl fmt::Display for AuthorizationFmtError {
@@ -889,6 +1529,7 @@ impl fmt::Display for AuthorizationFmtError {
             AuthorizationFmtError::Asn(s) => write!(f, "Invalid asn in string: {}", s),
             AuthorizationFmtError::Auth(s) => write!(f, "Invalid authorization string: {}", s),
             AuthorizationFmtError::Delta(s) => write!(f, "Invalid authorization delta string: {}", s),
+            AuthorizationFmtError::Tag(s) => write!(f, "Invalid tag string, expected key=value: {}", s),
         }
     }
 }
@@ -909,6 +1550,10 @@ impl AuthorizationFmtError {
     pub fn delta(s: &str) -> Self {
         AuthorizationFmtError::Delta(s.to_string())
     }
+
+    fn tag(s: &str) -> Self {
+        AuthorizationFmtError::Tag(s.to_string())
+    }
 }
 
 //------------ Tests -------------------------------------------------------
@@ -947,6 +1592,61 @@ mod tests {
         assert_eq!(parsed, re_parsed);
     }
 
+    #[test]
+    fn merge_delta() {
+        // 192.168.2.0/24-24 is currently configured, the draft only
+        // amends its comment.
+        let configured = [roa_payload("192.168.2.0/24-24 => 64496")];
+
+        let mut draft = RoaConfigurationUpdates::new(
+            vec![
+                roa_configuration("192.168.0.0/16-16 => 64496"),
+                roa_configuration("192.168.1.0/24-24 => 64496"),
+                roa_configuration("192.168.2.0/24-24 => 64496 # amended"),
+            ],
+            vec![roa_payload("10.0.0.0/8-8 => 64496")],
+        );
+
+        draft.merge(
+            RoaConfigurationUpdates::new(
+                vec![roa_configuration("192.168.0.0/16-16 => 64496 # with comment")],
+                vec![
+                    roa_payload("192.168.1.0/24-24 => 64496"),
+                    roa_payload("192.168.2.0/24-24 => 64496"),
+                    roa_payload("10.0.0.0/8-8 => 64496"),
+                    roa_payload("10.1.0.0/16-16 => 64496"),
+                ],
+            ),
+            |payload| configured.contains(payload),
+        );
+
+        // The addition of 192.168.1.0/24-24 is cancelled, but the configured
+        // 192.168.2.0/24-24 must still be removed.
+        let expected = RoaConfigurationUpdates::new(
+            vec![roa_configuration("192.168.0.0/16-16 => 64496 # with comment")],
+            vec![
+                roa_payload("10.0.0.0/8-8 => 64496"),
+                roa_payload("192.168.2.0/24-24 => 64496"),
+                roa_payload("10.1.0.0/16-16 => 64496"),
+            ],
+        );
+
+        assert_eq!(expected, draft);
+    }
+
+    #[test]
+    fn roa_draft_names() {
+        assert!(RoaDraft::is_valid_name("staged"));
+        assert!(RoaDraft::is_valid_name("Ticket-1234_v2"));
+
+        assert!(!RoaDraft::is_valid_name(""));
+        assert!(!RoaDraft::is_valid_name("with/slash"));
+        assert!(!RoaDraft::is_valid_name("with space"));
+        assert!(!RoaDraft::is_valid_name("../escape"));
+        assert!(!RoaDraft::is_valid_name("caf\u{e9}"));
+        assert!(!RoaDraft::is_valid_name(&"a".repeat(256)));
+    }
+
     #[test]
     fn parse_type_prefix() {
         assert!(TypedPrefix::from_str("192.168.0.0/16").is_ok());
@@ -982,6 +1682,126 @@ mod tests {
         parse_ser_de_print_configuration("2001:db8::/32-48 => 64496");
     }
 
+    #[test]
+    fn serde_roa_configuration_metadata() {
+        // no metadata is not serialized, but empty metadata is kept so
+        // that updates can use it to clear existing metadata
+        let def = roa_configuration("192.168.0.0/16 => 64496 # comment");
+        let json = serde_json::to_string(&def).unwrap();
+        assert!(!json.contains("metadata"));
+
+        let cleared = def.clone().with_metadata(RoaMetadata::default());
+        let json = serde_json::to_string(&cleared).unwrap();
+        assert!(json.contains("metadata"));
+        assert_eq!(serde_json::from_str::<RoaConfiguration>(&json).unwrap(), cleared);
+
+        let mut metadata = RoaMetadata::default();
+        metadata.set_owner(Some("noc".to_string()));
+        metadata.set_ticket(Some("TICKET-1".to_string()));
+        metadata.add_tag_str("service=cdn").unwrap();
+        metadata.add_tag_str("reviewed").unwrap();
+
+        let def = def.with_metadata(metadata);
+        let json = serde_json::to_string(&def).unwrap();
+        let de: RoaConfiguration = serde_json::from_str(&json).unwrap();
+        assert_eq!(def, de);
+        assert_eq!(de.metadata().unwrap().tag("reviewed"), Some(&String::new()));
+    }
+
+    #[test]
+    fn configured_roa_text_shows_metadata() {
+        let configured = ConfiguredRoa::new(roa_configuration("192.168.0.0/16-16 => 64496 # cdn"), vec![]);
+        assert_eq!(configured.to_string(), "192.168.0.0/16-16 => 64496 # cdn");
+
+        let mut metadata = RoaMetadata::default();
+        metadata.set_owner(Some("noc".to_string()));
+        metadata.set_ticket(Some("TICKET-1".to_string()));
+        metadata.set_customer(Some("acme".to_string()));
+        metadata.add_tag_str("service=cdn").unwrap();
+        metadata.add_tag_str("reviewed").unwrap();
+
+        let configured = ConfiguredRoa::new(
+            roa_configuration("192.168.0.0/16-16 => 64496 # cdn").with_metadata(metadata.clone()),
+            vec![],
+        );
+        assert_eq!(
+            configured.to_string(),
+            "192.168.0.0/16-16 => 64496 # cdn owner=noc ticket=TICKET-1 customer=acme tag:reviewed tag:service=cdn"
+        );
+
+        // without a comment the metadata still follows a '#', so that the
+        // line can be parsed as a configuration again
+        let configured = ConfiguredRoa::new(
+            roa_configuration("192.168.0.0/16-16 => 64496").with_metadata(metadata),
+            vec![],
+        );
+        assert_eq!(
+            configured.to_string(),
+            "192.168.0.0/16-16 => 64496 # owner=noc ticket=TICKET-1 customer=acme tag:reviewed tag:service=cdn"
+        );
+
+        // empty metadata is not shown
+        let configured = ConfiguredRoa::new(
+            roa_configuration("192.168.0.0/16-16 => 64496").with_metadata(RoaMetadata::default()),
+            vec![],
+        );
+        assert_eq!(configured.to_string(), "192.168.0.0/16-16 => 64496");
+    }
+
+    #[test]
+    fn roa_configuration_time_bounds() {
+        let now = Timestamp::now();
+        let def = roa_configuration("192.168.0.0/16 => 64496");
+        assert!(def.time_bounds_valid(now));
+        assert!(!def.is_scheduled(now));
+        assert!(!def.is_expired(now));
+
+        let scheduled = def
+            .clone()
+            .with_time_bounds(Some(now.plus_hours(1)), Some(now.plus_hours(2)));
+        assert!(scheduled.time_bounds_valid(now));
+        assert!(scheduled.is_scheduled(now));
+        assert!(!scheduled.is_expired(now));
+        assert!(scheduled.is_expired(now.plus_hours(2)));
+
+        let backwards = def
+            .clone()
+            .with_time_bounds(Some(now.plus_hours(2)), Some(now.plus_hours(1)));
+        assert!(!backwards.time_bounds_valid(now));
+
+        let expired = def.with_time_bounds(None, Some(now.minus_hours(1)));
+        assert!(!expired.time_bounds_valid(now));
+        assert!(expired.is_expired(now));
+    }
+
+    #[test]
+    fn filter_roa_configurations() {
+        let mut metadata = RoaMetadata::default();
+        metadata.set_owner(Some("noc".to_string()));
+        metadata.add_tag_str("service=cdn").unwrap();
+
+        let tagged = roa_configuration("192.168.0.0/16 => 64496").with_metadata(metadata);
+        let untagged = roa_configuration("192.168.1.0/24 => 64496");
+
+        let filter = RoaConfigurationFilter::default();
+        assert!(filter.is_empty());
+        assert!(filter.matches(&tagged));
+        assert!(filter.matches(&untagged));
+
+        let mut filter = RoaConfigurationFilter::default();
+        filter.set_owner(Some("noc".to_string()));
+        filter.add_tag_str("service").unwrap();
+        assert!(filter.matches(&tagged));
+        assert!(!filter.matches(&untagged));
+
+        let mut filter = RoaConfigurationFilter::default();
+        filter.add_tag_str("service=dns").unwrap();
+        assert!(!filter.matches(&tagged));
+
+        let mut filter = RoaConfigurationFilter::default();
+        assert!(filter.add_tag_str("=cdn").is_err());
+    }
+
     #[test]
     fn serde_roa_payload() {
         fn parse_ser_de_print_payload(s: &str) {
@@ -1071,4 +1891,26 @@ mod tests {
         check("10.0.0.0/15-17 => 64496", 4);
         check("10.0.0.0/15-18 => 64496", 8);
     }
+
+    #[test]
+    fn prefixes_for_resource_set() {
+        fn check(v4: &str, v6: &str, expected: &[&str]) {
+            let resources = ResourceSet::from_strs("", v4, v6).unwrap();
+            let prefixes: Vec<String> = TypedPrefix::for_resource_set(&resources)
+                .iter()
+                .map(|pfx| pfx.to_string())
+                .collect();
+            assert_eq!(prefixes, expected);
+        }
+
+        check("10.0.0.0/16", "", &["10.0.0.0/16"]);
+        check("10.0.0.0-10.0.2.255", "", &["10.0.0.0/23", "10.0.2.0/24"]);
+        check(
+            "10.0.0.1-10.0.0.6",
+            "",
+            &["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/31", "10.0.0.6/32"],
+        );
+        check("0.0.0.0/0", "::/0", &["0.0.0.0/0", "::/0"]);
+        check("", "2001:db8::/32", &["2001:db8::/32"]);
+    }
 }
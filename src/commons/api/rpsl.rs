@@ -0,0 +1,464 @@
+//! Support for importing ROA configurations from RPSL route and route6
+//! objects, as found in IRR databases (RFC 2622, RFC 4012).
+
+use std::{collections::HashSet, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use rpki::repository::resources::ResourceSet;
+
+use super::{AsNumber, RoaConfiguration, RoaConfigurationUpdates, RoaDraftName, RoaPayload, TypedPrefix};
+
+//------------ RpslImportRequest ---------------------------------------------
+
+/// A request to import route and route6 objects from RPSL text.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RpslImportRequest {
+    rpsl: String,
+    #[serde(default)]
+    action: RpslImportAction,
+}
+
+impl RpslImportRequest {
+    pub fn new(rpsl: String, action: RpslImportAction) -> Self {
+        RpslImportRequest { rpsl, action }
+    }
+
+    pub fn rpsl(&self) -> &str {
+        &self.rpsl
+    }
+
+    pub fn action(&self) -> &RpslImportAction {
+        &self.action
+    }
+
+    pub fn unpack(self) -> (String, RpslImportAction) {
+        (self.rpsl, self.action)
+    }
+}
+
+//------------ RpslImportAction ----------------------------------------------
+
+/// What to do with the ROA configurations derived from an RPSL import.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpslImportAction {
+    /// Only report the proposed updates, do not change anything.
+    Propose,
+    /// Apply the proposed updates immediately.
+    Apply,
+    /// Merge the proposed updates into the named ROA draft.
+    Stage(RoaDraftName),
+}
+
+impl Default for RpslImportAction {
+    fn default() -> Self {
+        RpslImportAction::Propose
+    }
+}
+
+impl fmt::Display for RpslImportAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpslImportAction::Propose => write!(f, "propose"),
+            RpslImportAction::Apply => write!(f, "apply"),
+            RpslImportAction::Stage(name) => write!(f, "stage in draft '{}'", name),
+        }
+    }
+}
+
+//------------ RpslRoute -----------------------------------------------------
+
+/// The relevant content of an RPSL route or route6 object.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RpslRoute {
+    prefix: TypedPrefix,
+    origin: AsNumber,
+    descr: Option<String>,
+}
+
+impl RpslRoute {
+    /// Returns the ROA configuration for this route object. The max length
+    /// is set to the prefix length, because a route object only covers the
+    /// exact prefix. The first descr attribute, if any, is used as comment.
+    pub fn roa_configuration(&self) -> RoaConfiguration {
+        let payload = RoaPayload::new(self.origin, self.prefix, None).into_explicit_max_length();
+        RoaConfiguration::new(payload, self.descr.clone())
+    }
+}
+
+//------------ RpslSkipReason ------------------------------------------------
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpslSkipReason {
+    InvalidPrefix,
+    InvalidAsn,
+    MissingOrigin,
+    NotHeld,
+    Duplicate,
+    AlreadyConfigured,
+}
+
+impl fmt::Display for RpslSkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpslSkipReason::InvalidPrefix => write!(f, "invalid prefix"),
+            RpslSkipReason::InvalidAsn => write!(f, "invalid origin ASN"),
+            RpslSkipReason::MissingOrigin => write!(f, "no origin"),
+            RpslSkipReason::NotHeld => write!(f, "prefix not held"),
+            RpslSkipReason::Duplicate => write!(f, "duplicate object"),
+            RpslSkipReason::AlreadyConfigured => write!(f, "already configured"),
+        }
+    }
+}
+
+//------------ RpslSkippedObject ---------------------------------------------
+
+/// An RPSL route or route6 object which did not result in a ROA
+/// configuration, and why.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RpslSkippedObject {
+    class: String,
+    key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    origin: Option<String>,
+    reason: RpslSkipReason,
+}
+
+impl RpslSkippedObject {
+    pub fn reason(&self) -> RpslSkipReason {
+        self.reason
+    }
+}
+
+impl fmt::Display for RpslSkippedObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.class, self.key)?;
+        if let Some(origin) = &self.origin {
+            write!(f, " origin: {}", origin)?;
+        }
+        write!(f, " ({})", self.reason)
+    }
+}
+
+//------------ RpslRouteObjects ----------------------------------------------
+
+/// The route and route6 objects found in RPSL text. Objects of other
+/// classes are ignored. Route objects which cannot be parsed are kept
+/// as skipped objects, so that they can be reported.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RpslRouteObjects {
+    routes: Vec<RpslRoute>,
+    skipped: Vec<RpslSkippedObject>,
+}
+
+impl RpslRouteObjects {
+    /// Parses RPSL text. Objects are separated by empty lines, lines
+    /// starting with '%' or '#' are comments, and lines starting with
+    /// white space or '+' continue the value of the previous attribute.
+    pub fn parse(rpsl: &str) -> Self {
+        let mut objects = RpslRouteObjects::default();
+        let mut attributes: Vec<(String, String)> = vec![];
+
+        for line in rpsl.lines() {
+            if line.trim().is_empty() {
+                objects.add_object(&attributes);
+                attributes.clear();
+            } else if line.starts_with('%') || line.starts_with('#') {
+                continue;
+            } else if line.starts_with(|c: char| c == ' ' || c == '\t' || c == '+') {
+                if let Some((_, value)) = attributes.last_mut() {
+                    let continued = Self::strip_comment(&line[1..]);
+                    if !continued.is_empty() {
+                        if !value.is_empty() {
+                            value.push(' ');
+                        }
+                        value.push_str(continued);
+                    }
+                }
+            } else if let Some(idx) = line.find(':') {
+                let name = line[..idx].trim().to_ascii_lowercase();
+                let value = Self::strip_comment(&line[idx + 1..]).to_string();
+                attributes.push((name, value));
+            }
+        }
+        objects.add_object(&attributes);
+
+        objects
+    }
+
+    fn strip_comment(value: &str) -> &str {
+        match value.find('#') {
+            Some(idx) => value[..idx].trim(),
+            None => value.trim(),
+        }
+    }
+
+    fn add_object(&mut self, attributes: &[(String, String)]) {
+        let (class, key) = match attributes.first() {
+            Some((class, key)) if class == "route" || class == "route6" => (class, key),
+            _ => return,
+        };
+
+        let origin = attributes
+            .iter()
+            .find(|(name, _)| name == "origin")
+            .map(|(_, value)| value.clone());
+        let descr = attributes
+            .iter()
+            .find(|(name, value)| name == "descr" && !value.is_empty())
+            .map(|(_, value)| value.clone());
+
+        let skip = |reason: RpslSkipReason| RpslSkippedObject {
+            class: class.clone(),
+            key: key.clone(),
+            origin: origin.clone(),
+            reason,
+        };
+
+        let prefix = match TypedPrefix::from_str(key) {
+            Ok(prefix) if prefix.is_v4() == (class == "route") => prefix,
+            _ => return self.skipped.push(skip(RpslSkipReason::InvalidPrefix)),
+        };
+
+        let origin_asn = match &origin {
+            None => return self.skipped.push(skip(RpslSkipReason::MissingOrigin)),
+            Some(origin) => match Self::parse_origin(origin) {
+                Some(asn) => asn,
+                None => return self.skipped.push(skip(RpslSkipReason::InvalidAsn)),
+            },
+        };
+
+        self.routes.push(RpslRoute {
+            prefix,
+            origin: origin_asn,
+            descr,
+        });
+    }
+
+    /// Parses an origin of the form "AS64496". AS0 is not accepted, because
+    /// it does not express an intent to originate the prefix.
+    fn parse_origin(origin: &str) -> Option<AsNumber> {
+        // Use get, rather than slicing, so that origins starting with a
+        // multi-byte character are rejected rather than cause a panic.
+        let number = origin
+            .get(..2)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("as"))
+            .and_then(|_| origin.get(2..))?;
+        match u32::from_str(number) {
+            Ok(0) | Err(_) => None,
+            Ok(number) => Some(AsNumber::new(number)),
+        }
+    }
+
+    pub fn routes(&self) -> &Vec<RpslRoute> {
+        &self.routes
+    }
+
+    pub fn skipped(&self) -> &Vec<RpslSkippedObject> {
+        &self.skipped
+    }
+
+    /// Turns the parsed route objects into a report with the updates
+    /// needed to add ROA configurations for them. Route objects for
+    /// prefixes which are not held, and route objects for which a ROA
+    /// configuration already exists or was already added, are skipped.
+    pub fn into_import_report(self, resources: &ResourceSet, configured: &[RoaPayload]) -> RpslImportReport {
+        let RpslRouteObjects { routes, mut skipped } = self;
+
+        let configured: HashSet<RoaPayload> = configured.iter().map(|p| p.into_explicit_max_length()).collect();
+        let mut added: HashSet<RoaPayload> = HashSet::new();
+        let mut updates = RoaConfigurationUpdates::empty();
+
+        for route in routes {
+            let config = route.roa_configuration();
+            let payload = config.payload();
+
+            let reason = if !resources.contains_roa_address(&payload.as_roa_ip_address()) {
+                Some(RpslSkipReason::NotHeld)
+            } else if configured.contains(&payload) {
+                Some(RpslSkipReason::AlreadyConfigured)
+            } else if !added.insert(payload) {
+                Some(RpslSkipReason::Duplicate)
+            } else {
+                None
+            };
+
+            match reason {
+                None => updates.add(config),
+                Some(reason) => skipped.push(RpslSkippedObject {
+                    class: if route.prefix.is_v4() { "route" } else { "route6" }.to_string(),
+                    key: route.prefix.to_string(),
+                    origin: Some(format!("AS{}", route.origin)),
+                    reason,
+                }),
+            }
+        }
+
+        RpslImportReport { updates, skipped }
+    }
+}
+
+//------------ RpslImportReport ----------------------------------------------
+
+/// The proposed ROA configuration updates for an RPSL import, and the
+/// route objects which were skipped.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RpslImportReport {
+    updates: RoaConfigurationUpdates,
+    skipped: Vec<RpslSkippedObject>,
+}
+
+impl RpslImportReport {
+    pub fn updates(&self) -> &RoaConfigurationUpdates {
+        &self.updates
+    }
+
+    pub fn skipped(&self) -> &Vec<RpslSkippedObject> {
+        &self.skipped
+    }
+}
+
+impl fmt::Display for RpslImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.updates.is_empty() {
+            writeln!(f, "# no ROA configurations to add")?;
+        } else {
+            write!(f, "{}", self.updates)?;
+        }
+        for skipped in &self.skipped {
+            writeln!(f, "# skipped {}", skipped)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test::{resources, roa_configuration, roa_payload};
+
+    #[test]
+    fn parse_rpsl_route_objects() {
+        let rpsl = concat!(
+            "% Comments from a whois server\n",
+            "\n",
+            "route:      10.0.0.0/24\n",
+            "descr:      Example route\n",
+            "origin:     AS64496 # end of line comment\n",
+            "mnt-by:     MAINT-EXAMPLE\n",
+            "source:     TEST\n",
+            "\n",
+            "\n",
+            "route6:     2001:db8::/32\n",
+            "descr:\n",
+            "+           continued description\n",
+            "origin:     as64497\n",
+            "\n",
+            "aut-num:    AS64496\n",
+            "as-name:    EXAMPLE\n",
+            "\n",
+            "route:      10.1.0.0/24\n",
+            "origin:     AS0\n",
+            "\n",
+            "route:      10.2.0.0/24\n",
+            "origin:     64496\n",
+            "\n",
+            "route:      2001:db8:1::/48\n",
+            "origin:     AS64496\n",
+            "\n",
+            "route6:     2001:db8:2::/48\n",
+        );
+
+        let objects = RpslRouteObjects::parse(rpsl);
+
+        let configs: Vec<RoaConfiguration> = objects.routes().iter().map(|r| r.roa_configuration()).collect();
+        assert_eq!(
+            configs,
+            vec![
+                roa_configuration("10.0.0.0/24-24 => 64496 # Example route"),
+                roa_configuration("2001:db8::/32-32 => 64497 # continued description"),
+            ]
+        );
+
+        let reasons: Vec<RpslSkipReason> = objects.skipped().iter().map(|s| s.reason()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                RpslSkipReason::InvalidAsn,
+                RpslSkipReason::InvalidAsn,
+                RpslSkipReason::InvalidPrefix,
+                RpslSkipReason::MissingOrigin,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rpsl_origin() {
+        assert_eq!(RpslRouteObjects::parse_origin("AS64496"), Some(AsNumber::new(64496)));
+        assert_eq!(RpslRouteObjects::parse_origin("as64496"), Some(AsNumber::new(64496)));
+        assert_eq!(RpslRouteObjects::parse_origin("AS0"), None);
+        assert_eq!(RpslRouteObjects::parse_origin("AS"), None);
+        assert_eq!(RpslRouteObjects::parse_origin("A"), None);
+        assert_eq!(RpslRouteObjects::parse_origin("64496"), None);
+
+        // multi-byte characters must not cause a panic
+        assert_eq!(RpslRouteObjects::parse_origin("ÄS64496"), None);
+        assert_eq!(RpslRouteObjects::parse_origin("Aé64496"), None);
+        assert_eq!(RpslRouteObjects::parse_origin("€"), None);
+
+        let objects = RpslRouteObjects::parse("route: 10.0.0.0/24\norigin: ÄS64496\n");
+        assert!(objects.routes().is_empty());
+        assert_eq!(objects.skipped()[0].reason(), RpslSkipReason::InvalidAsn);
+    }
+
+    #[test]
+    fn rpsl_import_report() {
+        let rpsl = concat!(
+            "route: 10.0.0.0/24\norigin: AS64496\n\n",
+            "route: 10.0.0.0/24\norigin: AS64496\n\n",
+            "route: 10.0.1.0/24\norigin: AS64496\n\n",
+            "route: 192.168.0.0/24\norigin: AS64496\n\n",
+            "route6: 2001:db8::/32\norigin: AS64496\n",
+        );
+
+        let resources = resources("", "10.0.0.0/16", "2001:db8::/32");
+        let configured = vec![roa_payload("10.0.1.0/24 => 64496")];
+
+        let report = RpslRouteObjects::parse(rpsl).into_import_report(&resources, &configured);
+
+        let expected_updates = RoaConfigurationUpdates::new(
+            vec![
+                roa_configuration("10.0.0.0/24-24 => 64496"),
+                roa_configuration("2001:db8::/32-32 => 64496"),
+            ],
+            vec![],
+        );
+        assert_eq!(report.updates(), &expected_updates);
+
+        let reasons: Vec<RpslSkipReason> = report.skipped().iter().map(|s| s.reason()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                RpslSkipReason::Duplicate,
+                RpslSkipReason::AlreadyConfigured,
+                RpslSkipReason::NotHeld,
+            ]
+        );
+    }
+
+    #[test]
+    fn serde_rpsl_import_request() {
+        let json = r#"{ "rpsl": "route: 10.0.0.0/24\norigin: AS64496\n" }"#;
+        let request: RpslImportRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.action(), &RpslImportAction::Propose);
+
+        let json = r#"{ "rpsl": "", "action": { "stage": "import" } }"#;
+        let request: RpslImportRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.action(), &RpslImportAction::Stage("import".to_string()));
+    }
+}
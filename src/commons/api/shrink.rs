@@ -0,0 +1,107 @@
+//! Reports on the effect of a parent shrinking the resources of a CA.
+use std::fmt;
+
+use rpki::{
+    ca::idexchange::{ChildHandle, ParentHandle},
+    repository::resources::ResourceSet,
+};
+
+use crate::commons::api::{RoaConfiguration, Timestamp};
+
+//------------ ResourceShrink ------------------------------------------------
+
+/// Describes the effect of losing resources under a parent. This is kept
+/// by the CA until the operator clears it, so that it can be reported as
+/// an issue.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResourceShrink {
+    parent: ParentHandle,
+    time: Timestamp,
+    lost: ResourceSet,
+
+    /// ROA configurations for which ROAs are no longer published.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    roas_not_held: Vec<RoaConfiguration>,
+
+    /// Children whose certificates were shrunk.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    children: Vec<ChildResourcesLost>,
+}
+
+impl ResourceShrink {
+    pub fn new(
+        parent: ParentHandle,
+        lost: ResourceSet,
+        roas_not_held: Vec<RoaConfiguration>,
+        children: Vec<ChildResourcesLost>,
+    ) -> Self {
+        ResourceShrink {
+            parent,
+            time: Timestamp::now(),
+            lost,
+            roas_not_held,
+            children,
+        }
+    }
+
+    pub fn parent(&self) -> &ParentHandle {
+        &self.parent
+    }
+
+    pub fn time(&self) -> Timestamp {
+        self.time
+    }
+
+    pub fn lost(&self) -> &ResourceSet {
+        &self.lost
+    }
+
+    pub fn roas_not_held(&self) -> &Vec<RoaConfiguration> {
+        &self.roas_not_held
+    }
+
+    pub fn children(&self) -> &Vec<ChildResourcesLost> {
+        &self.children
+    }
+}
+
+impl fmt::Display for ResourceShrink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Parent '{}' removed resources at {}: {}",
+            self.parent,
+            self.time.to_rfc3339(),
+            self.lost
+        )?;
+        for roa in &self.roas_not_held {
+            writeln!(f, "  ROA no longer held: {}", roa)?;
+        }
+        for child in &self.children {
+            writeln!(f, "  Child '{}' lost: {}", child.child, child.lost)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ ChildResourcesLost --------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ChildResourcesLost {
+    child: ChildHandle,
+    lost: ResourceSet,
+}
+
+impl ChildResourcesLost {
+    pub fn new(child: ChildHandle, lost: ResourceSet) -> Self {
+        ChildResourcesLost { child, lost }
+    }
+
+    pub fn child(&self) -> &ChildHandle {
+        &self.child
+    }
+
+    pub fn lost(&self) -> &ResourceSet {
+        &self.lost
+    }
+}
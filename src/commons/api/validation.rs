@@ -0,0 +1,129 @@
+//! The result of validating the content of the repository.
+//!
+//! Krill can act as a relying party against its own repository, so that
+//! issues with published objects are found before others find them.
+use std::fmt;
+
+use rpki::{ca::idexchange::CaHandle, uri};
+
+use crate::commons::api::Timestamp;
+
+//------------ RepositoryValidationReport ------------------------------------
+
+/// The issues found when validating the content of the repository.
+///
+/// Issues found below the certificates of local CAs are reported for those
+/// CAs. Other issues, e.g. differences between the rsync and RRDP content,
+/// or issues with the objects of remote publishers, are reported for the
+/// repository.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RepositoryValidationReport {
+    validated: Timestamp,
+    cas: Vec<CaValidationResult>,
+    repository_issues: Vec<ValidationIssue>,
+}
+
+impl RepositoryValidationReport {
+    pub fn new(cas: Vec<CaValidationResult>, repository_issues: Vec<ValidationIssue>) -> Self {
+        RepositoryValidationReport {
+            validated: Timestamp::now(),
+            cas,
+            repository_issues,
+        }
+    }
+
+    pub fn validated(&self) -> Timestamp {
+        self.validated
+    }
+
+    pub fn cas(&self) -> &Vec<CaValidationResult> {
+        &self.cas
+    }
+
+    pub fn repository_issues(&self) -> &Vec<ValidationIssue> {
+        &self.repository_issues
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.repository_issues.is_empty() && self.cas.iter().all(|ca| ca.issues.is_empty())
+    }
+}
+
+impl fmt::Display for RepositoryValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Validated: {}", self.validated.to_rfc3339())?;
+        for ca in &self.cas {
+            if ca.issues.is_empty() {
+                writeln!(f, "CA '{}': no issues found", ca.ca)?;
+            } else {
+                writeln!(f, "CA '{}':", ca.ca)?;
+                for issue in &ca.issues {
+                    writeln!(f, "   {}", issue)?;
+                }
+            }
+        }
+        if !self.repository_issues.is_empty() {
+            writeln!(f, "Repository:")?;
+            for issue in &self.repository_issues {
+                writeln!(f, "   {}", issue)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//------------ CaValidationResult --------------------------------------------
+
+/// The issues found for the objects published by a local CA.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CaValidationResult {
+    ca: CaHandle,
+    issues: Vec<ValidationIssue>,
+}
+
+impl CaValidationResult {
+    pub fn new(ca: CaHandle, issues: Vec<ValidationIssue>) -> Self {
+        CaValidationResult { ca, issues }
+    }
+
+    pub fn ca(&self) -> &CaHandle {
+        &self.ca
+    }
+
+    pub fn issues(&self) -> &Vec<ValidationIssue> {
+        &self.issues
+    }
+}
+
+//------------ ValidationIssue -----------------------------------------------
+
+/// An issue with a published object, or an object which should have been
+/// published.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    uri: uri::Rsync,
+    msg: String,
+}
+
+impl ValidationIssue {
+    pub fn new(uri: uri::Rsync, msg: impl fmt::Display) -> Self {
+        ValidationIssue {
+            uri,
+            msg: msg.to_string(),
+        }
+    }
+
+    pub fn uri(&self) -> &uri::Rsync {
+        &self.uri
+    }
+
+    pub fn msg(&self) -> &str {
+        &self.msg
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.uri, self.msg)
+    }
+}
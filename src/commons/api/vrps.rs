@@ -0,0 +1,280 @@
+//! Export the Validated ROA Payloads (VRPs) which a CA intends to publish,
+//! in formats understood by relying party software and IRR tooling.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{AsNumber, ConfiguredRoa, RoaConfiguration};
+
+//------------ VrpExportFormat -----------------------------------------------
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VrpExportFormat {
+    /// Local assertions in a SLURM file (RFC 8416)
+    Slurm,
+    Csv,
+    /// RPSL route and route6 objects, e.g. to keep an IRR in sync
+    Rpsl,
+    Json,
+}
+
+impl Default for VrpExportFormat {
+    fn default() -> Self {
+        VrpExportFormat::Json
+    }
+}
+
+impl FromStr for VrpExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "slurm" => Ok(VrpExportFormat::Slurm),
+            "csv" => Ok(VrpExportFormat::Csv),
+            "rpsl" => Ok(VrpExportFormat::Rpsl),
+            "json" => Ok(VrpExportFormat::Json),
+            _ => Err(format!("Unsupported VRP export format: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for VrpExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VrpExportFormat::Slurm => write!(f, "slurm"),
+            VrpExportFormat::Csv => write!(f, "csv"),
+            VrpExportFormat::Rpsl => write!(f, "rpsl"),
+            VrpExportFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+//------------ VrpExport -----------------------------------------------------
+
+/// The effective VRPs for the configured ROAs of a CA, i.e. using the
+/// explicit max length for each configuration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VrpExport {
+    configurations: Vec<RoaConfiguration>,
+}
+
+impl VrpExport {
+    pub fn new(configured: Vec<ConfiguredRoa>) -> Self {
+        let mut configurations: Vec<RoaConfiguration> = configured
+            .into_iter()
+            .map(|configured| configured.roa_configuration().clone().into_explicit_max_length())
+            .collect();
+        configurations.sort();
+
+        VrpExport { configurations }
+    }
+
+    pub fn export(&self, format: VrpExportFormat) -> String {
+        match format {
+            VrpExportFormat::Slurm => self.slurm(),
+            VrpExportFormat::Csv => self.csv(),
+            VrpExportFormat::Rpsl => self.rpsl(),
+            VrpExportFormat::Json => self.json(),
+        }
+    }
+
+    /// A SLURM file with a locally added prefix assertion for each VRP, so
+    /// that relying party software can use them before they are published.
+    fn slurm(&self) -> String {
+        let prefix_assertions = self
+            .configurations
+            .iter()
+            .map(|config| {
+                let payload = config.payload();
+                SlurmPrefixAssertion {
+                    asn: payload.asn(),
+                    prefix: payload.prefix().to_string(),
+                    max_prefix_length: payload.effective_max_length(),
+                    comment: config.comment().cloned(),
+                }
+            })
+            .collect();
+
+        let slurm = SlurmFile {
+            slurm_version: 1,
+            validation_output_filters: SlurmFilters::default(),
+            locally_added_assertions: SlurmAssertions {
+                prefix_assertions,
+                bgpsec_assertions: vec![],
+            },
+        };
+
+        serde_json::to_string_pretty(&slurm).unwrap()
+    }
+
+    fn csv(&self) -> String {
+        let mut csv = "ASN,IP Prefix,Max Length\n".to_string();
+        for config in &self.configurations {
+            let payload = config.payload();
+            csv.push_str(&format!(
+                "AS{},{},{}\n",
+                payload.asn(),
+                payload.prefix(),
+                payload.effective_max_length()
+            ));
+        }
+        csv
+    }
+
+    /// Route and route6 objects for each VRP. Maintainer and source
+    /// attributes are left out, as they depend on the IRR used. A route
+    /// object cannot express a max length, so a remark is added for VRPs
+    /// which allow more specific announcements.
+    fn rpsl(&self) -> String {
+        let mut objects = vec![];
+        for config in &self.configurations {
+            let payload = config.payload();
+            let prefix = payload.prefix();
+            let class = if prefix.is_v4() { "route:" } else { "route6:" };
+
+            let mut object = format!("{:<16}{}\n", class, prefix);
+            if let Some(comment) = config.comment() {
+                object.push_str(&format!("{:<16}{}\n", "descr:", comment));
+            }
+            object.push_str(&format!("{:<16}AS{}\n", "origin:", payload.asn()));
+            if payload.effective_max_length() > prefix.prefix().addr_len() {
+                object.push_str(&format!(
+                    "{:<16}max-length {} in RPKI\n",
+                    "remarks:",
+                    payload.effective_max_length()
+                ));
+            }
+            objects.push(object);
+        }
+        objects.join("\n")
+    }
+
+    /// The JSON format used by common relying party software.
+    fn json(&self) -> String {
+        let roas: Vec<JsonVrp> = self
+            .configurations
+            .iter()
+            .map(|config| {
+                let payload = config.payload();
+                JsonVrp {
+                    asn: format!("AS{}", payload.asn()),
+                    prefix: payload.prefix().to_string(),
+                    max_length: payload.effective_max_length(),
+                }
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&JsonVrps { roas }).unwrap()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlurmFile {
+    slurm_version: u8,
+    validation_output_filters: SlurmFilters,
+    locally_added_assertions: SlurmAssertions,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlurmFilters {
+    prefix_filters: Vec<()>,
+    bgpsec_filters: Vec<()>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlurmAssertions {
+    prefix_assertions: Vec<SlurmPrefixAssertion>,
+    bgpsec_assertions: Vec<()>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlurmPrefixAssertion {
+    asn: AsNumber,
+    prefix: String,
+    max_prefix_length: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[derive(Serialize)]
+struct JsonVrps {
+    roas: Vec<JsonVrp>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonVrp {
+    asn: String,
+    prefix: String,
+    max_length: u8,
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test::roa_configuration;
+
+    fn export() -> VrpExport {
+        VrpExport::new(vec![
+            ConfiguredRoa::new(roa_configuration("2001:db8::/32 => 64497"), vec![]),
+            ConfiguredRoa::new(roa_configuration("10.0.0.0/16-20 => 64496 # cdn"), vec![]),
+        ])
+    }
+
+    #[test]
+    fn export_csv() {
+        let expected = concat!(
+            "ASN,IP Prefix,Max Length\n",
+            "AS64496,10.0.0.0/16,20\n",
+            "AS64497,2001:db8::/32,32\n",
+        );
+        assert_eq!(export().export(VrpExportFormat::Csv), expected);
+    }
+
+    #[test]
+    fn export_rpsl() {
+        let expected = concat!(
+            "route:          10.0.0.0/16\n",
+            "descr:          cdn\n",
+            "origin:         AS64496\n",
+            "remarks:        max-length 20 in RPKI\n",
+            "\n",
+            "route6:         2001:db8::/32\n",
+            "origin:         AS64497\n",
+        );
+        assert_eq!(export().export(VrpExportFormat::Rpsl), expected);
+    }
+
+    #[test]
+    fn export_slurm() {
+        let slurm: serde_json::Value = serde_json::from_str(&export().export(VrpExportFormat::Slurm)).unwrap();
+
+        assert_eq!(slurm["slurmVersion"], 1);
+        let assertions = slurm["locallyAddedAssertions"]["prefixAssertions"].as_array().unwrap();
+        assert_eq!(assertions.len(), 2);
+        assert_eq!(assertions[0]["asn"], 64496);
+        assert_eq!(assertions[0]["prefix"], "10.0.0.0/16");
+        assert_eq!(assertions[0]["maxPrefixLength"], 20);
+        assert_eq!(assertions[0]["comment"], "cdn");
+        assert_eq!(assertions[1]["maxPrefixLength"], 32);
+        assert!(assertions[1].get("comment").is_none());
+    }
+
+    #[test]
+    fn export_json() {
+        let json: serde_json::Value = serde_json::from_str(&export().export(VrpExportFormat::Json)).unwrap();
+        let roas = json["roas"].as_array().unwrap();
+        assert_eq!(roas[0]["asn"], "AS64496");
+        assert_eq!(roas[0]["maxLength"], 20);
+        assert_eq!(roas[1]["prefix"], "2001:db8::/32");
+    }
+}
@@ -1,17 +1,18 @@
-use std::fmt;
+use std::{collections::HashSet, fmt, str::FromStr};
 
 use chrono::Duration;
 use tokio::sync::RwLock;
 
-use rpki::{repository::resources::ResourceSet, repository::x509::Time};
+use rpki::{repository::resources::Asn, repository::resources::ResourceSet, repository::x509::Time};
 
 use crate::{
     commons::{
-        api::{AsNumber, ConfiguredRoa, RoaPayload},
+        api::{AsNumber, AspaDefinition, ConfiguredRoa, ResourceShrink, RoaPayload, TypedPrefix},
         bgp::{
-            make_roa_tree, make_validated_announcement_tree, Announcement, AnnouncementValidity, Announcements,
-            BgpAnalysisEntry, BgpAnalysisReport, BgpAnalysisState, BgpAnalysisSuggestion, IpRange, RisDumpError,
-            RisDumpLoader, ValidatedAnnouncement,
+            make_roa_tree, make_validated_announcement_tree, Announcement, AnnouncementValidity, Announcements, AsPath,
+            AsPathError, AsPathLoader, AsPaths, AspaAnalysisEntry, AspaAnalysisReport, AspaSeenUpstream,
+            AspaSuggestion, BgpAnalysisEntry, BgpAnalysisReport, BgpAnalysisState, BgpAnalysisSuggestion, IpRange,
+            ResourceShrinkReport, RisDumpError, RisDumpLoader, ShrinkAffectedAnnouncement, ValidatedAnnouncement,
         },
     },
     constants::{test_announcements_enabled, BGP_RIS_REFRESH_MINUTES},
@@ -23,10 +24,12 @@ use crate::{
 pub struct BgpAnalyser {
     dump_loader: Option<RisDumpLoader>,
     seen: RwLock<Announcements>,
+    path_loader: Option<AsPathLoader>,
+    paths: RwLock<AsPaths>,
 }
 
 impl BgpAnalyser {
-    pub fn new(ris_enabled: bool, ris_v4_uri: &str, ris_v6_uri: &str) -> Self {
+    pub fn new(ris_enabled: bool, ris_v4_uri: &str, ris_v6_uri: &str, aspa_paths_uri: Option<&str>) -> Self {
         if test_announcements_enabled() {
             Self::with_test_announcements()
         } else {
@@ -38,11 +41,45 @@ impl BgpAnalyser {
             BgpAnalyser {
                 dump_loader,
                 seen: RwLock::new(Announcements::default()),
+                path_loader: aspa_paths_uri.map(AsPathLoader::new),
+                paths: RwLock::new(AsPaths::default()),
             }
         }
     }
 
+    /// Updates the announcements and AS paths, if they are configured and
+    /// the refresh interval has passed. Returns true if anything changed.
     pub async fn update(&self) -> Result<bool, BgpAnalyserError> {
+        let announcements_updated = self.update_announcements().await?;
+        let paths_updated = self.update_paths().await?;
+        Ok(announcements_updated || paths_updated)
+    }
+
+    async fn update_paths(&self) -> Result<bool, BgpAnalyserError> {
+        if let Some(loader) = &self.path_loader {
+            let mut paths = self.paths.write().await;
+            if let Some(last_time) = paths.last_checked() {
+                if (last_time + Duration::minutes(BGP_RIS_REFRESH_MINUTES)) > Time::now() {
+                    trace!("Will not check AS paths until the refresh interval has passed");
+                    return Ok(false);
+                }
+            }
+            let updated = loader.download_updates().await?;
+            if paths.equivalent(&updated) {
+                debug!("AS paths unchanged");
+                paths.update_checked();
+                Ok(false)
+            } else {
+                info!("Updated AS paths ({} upstreams)", updated.size());
+                paths.update(updated);
+                Ok(true)
+            }
+        } else {
+            Ok(false)
+        }
+    }
+
+    async fn update_announcements(&self) -> Result<bool, BgpAnalyserError> {
         if let Some(loader) = &self.dump_loader {
             let mut seen = self.seen.write().await;
             if let Some(last_time) = seen.last_checked() {
@@ -177,34 +214,33 @@ impl BgpAnalyser {
                     nr_of_specific_ann > 0 && nr_of_specific_ann < roa.nr_of_specific_prefixes()
                 };
 
-                if roa.asn() == AsNumber::zero() {
+                let max_length_exposed = roa.asn() != AsNumber::zero() && {
+                    let announcements: Vec<Announcement> = covered.iter().map(|va| va.announcement()).collect();
+                    max_length_exposed(&roa.payload(), &announcements)
+                };
+
+                let entry = if roa.asn() == AsNumber::zero() {
                     // see if this AS0 ROA is redundant, if it is mark it as such
                     if other_roas_covering_this_prefix.is_empty() {
                         // will disallow all covered announcements by definition (because AS0 announcements cannot exist)
                         let announcements = covered.iter().map(|va| va.announcement()).collect();
-                        entries.push(BgpAnalysisEntry::roa_as0(roa, announcements));
+                        BgpAnalysisEntry::roa_as0(roa, announcements)
                     } else {
-                        entries.push(BgpAnalysisEntry::roa_as0_redundant(
-                            roa,
-                            other_roas_covering_this_prefix,
-                        ));
+                        BgpAnalysisEntry::roa_as0_redundant(roa, other_roas_covering_this_prefix)
                     }
                 } else if !other_roas_including_this_definition.is_empty() {
-                    entries.push(BgpAnalysisEntry::roa_redundant(
-                        roa,
-                        authorizes,
-                        disallows,
-                        other_roas_including_this_definition,
-                    ))
+                    BgpAnalysisEntry::roa_redundant(roa, authorizes, disallows, other_roas_including_this_definition)
                 } else if authorizes.is_empty() && disallows.is_empty() {
-                    entries.push(BgpAnalysisEntry::roa_unseen(roa))
+                    BgpAnalysisEntry::roa_unseen(roa)
                 } else if authorizes_excess {
-                    entries.push(BgpAnalysisEntry::roa_too_permissive(roa, authorizes, disallows))
+                    BgpAnalysisEntry::roa_too_permissive(roa, authorizes, disallows)
                 } else if authorizes.is_empty() {
-                    entries.push(BgpAnalysisEntry::roa_disallowing(roa, disallows))
+                    BgpAnalysisEntry::roa_disallowing(roa, disallows)
                 } else {
-                    entries.push(BgpAnalysisEntry::roa_seen(roa, authorizes, disallows))
-                }
+                    BgpAnalysisEntry::roa_seen(roa, authorizes, disallows)
+                };
+
+                entries.push(entry.with_max_length_exposed(max_length_exposed));
             }
 
             // Loop over all validated announcements and report
@@ -288,6 +324,125 @@ impl BgpAnalyser {
         suggestion
     }
 
+    /// Returns the given ROA payloads which use a max length that allows
+    /// more specific announcements which are not all seen in BGP. Returns
+    /// nothing if no announcement information is known.
+    pub async fn max_length_exposed(&self, payloads: &[RoaPayload]) -> Vec<RoaPayload> {
+        let seen = self.seen.read().await;
+        if seen.last_checked().is_none() {
+            return vec![];
+        }
+
+        payloads
+            .iter()
+            .filter(|payload| payload.asn() != AsNumber::zero())
+            .filter(|payload| {
+                let announcements: Vec<Announcement> =
+                    seen.contained_by(payload.prefix()).into_iter().copied().collect();
+                max_length_exposed(payload, &announcements)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Compares the ASPA definitions with seen AS paths, and reports on
+    /// held customer ASNs seen in AS paths which have no definition.
+    pub async fn aspa_analyse(
+        &self,
+        definitions: &[AspaDefinition],
+        resources_held: &ResourceSet,
+    ) -> AspaAnalysisReport {
+        let paths = self.paths.read().await;
+
+        let entries = if paths.last_checked().is_none() {
+            definitions
+                .iter()
+                .map(|definition| AspaAnalysisEntry::no_path_info(definition.customer(), Some(definition.clone())))
+                .collect()
+        } else {
+            let mut entries: Vec<AspaAnalysisEntry> = definitions
+                .iter()
+                .map(|definition| {
+                    let customer = definition.customer();
+                    AspaAnalysisEntry::new(
+                        customer,
+                        Some(definition.clone()),
+                        Self::seen_upstreams(&paths, customer),
+                    )
+                })
+                .collect();
+
+            for asn in paths.asns() {
+                if resources_held.contains_asn(asn) && !definitions.iter().any(|def| def.customer() == asn) {
+                    entries.push(AspaAnalysisEntry::new(asn, None, Self::seen_upstreams(&paths, asn)));
+                }
+            }
+
+            entries
+        };
+
+        AspaAnalysisReport::new(entries)
+    }
+
+    /// Reports on resources removed by parents. Announcements which were
+    /// valid while the lost resources were still held, but which are not
+    /// found or invalid with the ROAs for the resources held now, are
+    /// included.
+    pub async fn resources_shrunk(
+        &self,
+        roas: &[ConfiguredRoa],
+        resources_held: &ResourceSet,
+        shrinks: Vec<ResourceShrink>,
+    ) -> ResourceShrinkReport {
+        let lost = shrinks
+            .iter()
+            .fold(ResourceSet::default(), |lost, shrink| lost.union(shrink.lost()));
+
+        if lost.is_empty() {
+            return ResourceShrinkReport::new(shrinks, vec![]);
+        }
+
+        let held_before = resources_held.union(&lost);
+
+        let valid_before: HashSet<Announcement> = self
+            .analyse(roas, &held_before, None)
+            .await
+            .matching_announcements(BgpAnalysisState::AnnouncementValid)
+            .into_iter()
+            .collect();
+
+        let announcements = self
+            .analyse(roas, resources_held, Some(held_before))
+            .await
+            .into_entries()
+            .into_iter()
+            .filter(|entry| {
+                matches!(
+                    entry.state(),
+                    BgpAnalysisState::AnnouncementNotFound
+                        | BgpAnalysisState::AnnouncementInvalidAsn
+                        | BgpAnalysisState::AnnouncementInvalidLength
+                        | BgpAnalysisState::AnnouncementDisallowed
+                ) && valid_before.contains(&entry.announcement())
+            })
+            .map(|entry| ShrinkAffectedAnnouncement::new(entry.announcement(), entry.state()))
+            .collect();
+
+        ResourceShrinkReport::new(shrinks, announcements)
+    }
+
+    pub async fn aspa_suggest(&self, definitions: &[AspaDefinition], resources_held: &ResourceSet) -> AspaSuggestion {
+        AspaSuggestion::from(&self.aspa_analyse(definitions, resources_held).await)
+    }
+
+    fn seen_upstreams(paths: &AsPaths, customer: Asn) -> Vec<AspaSeenUpstream> {
+        paths
+            .upstreams(customer)
+            .into_iter()
+            .map(|(asn, count)| AspaSeenUpstream::new(asn, count))
+            .collect()
+    }
+
     fn test_announcements() -> Vec<Announcement> {
         use crate::test::announcement;
 
@@ -304,27 +459,77 @@ impl BgpAnalyser {
         ]
     }
 
+    fn test_paths() -> AsPaths {
+        let mut paths = AsPaths::default();
+        for path in &[
+            "64500 64501 64496",
+            "64502 64496",
+            "64500 64503 64497",
+            "64502 64497 64497",
+        ] {
+            paths.add(&AsPath::from_str(path).unwrap());
+        }
+        paths
+    }
+
     fn with_test_announcements() -> Self {
         let mut announcements = Announcements::default();
         announcements.update(Self::test_announcements());
+        let mut paths = AsPaths::default();
+        paths.update(Self::test_paths());
         BgpAnalyser {
             dump_loader: None,
             seen: RwLock::new(announcements),
+            path_loader: None,
+            paths: RwLock::new(paths),
         }
     }
 }
 
+/// Returns true if the payload uses a max length which allows more specific
+/// announcements, and not all of these are seen from its ASN. RFC 9319 warns
+/// that such ROAs can be abused for forged-origin sub-prefix hijacks.
+///
+/// The announcements are expected to be contained by the payload prefix.
+fn max_length_exposed(payload: &RoaPayload, announcements: &[Announcement]) -> bool {
+    let prefix_len = payload.prefix().addr_len();
+    let max_length = payload.effective_max_length();
+
+    if max_length <= prefix_len {
+        return false;
+    }
+
+    let announced: HashSet<&TypedPrefix> = announcements
+        .iter()
+        .filter(|ann| {
+            let len = ann.prefix().addr_len();
+            ann.asn() == &payload.asn() && len > prefix_len && len <= max_length
+        })
+        .map(|ann| ann.prefix())
+        .collect();
+
+    // There are 2^1 + 2^2 + .. + 2^n = 2^(n+1) - 2 more specific prefixes
+    // for a prefix with a max length n bits longer than its own length. This
+    // overflows for large IPv6 ranges, which can never be fully announced.
+    match 1u128.checked_shl(u32::from(max_length - prefix_len) + 1) {
+        Some(more_specifics) => (announced.len() as u128) < more_specifics - 2,
+        None => true,
+    }
+}
+
 //------------ Error --------------------------------------------------------
 
 #[derive(Debug)]
 pub enum BgpAnalyserError {
     RisDump(RisDumpError),
+    AsPath(AsPathError),
 }
 
 impl fmt::Display for BgpAnalyserError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             BgpAnalyserError::RisDump(e) => write!(f, "BGP RIS update error: {}", e),
+            BgpAnalyserError::AsPath(e) => write!(f, "AS path update error: {}", e),
         }
     }
 }
@@ -335,6 +540,12 @@ impl From<RisDumpError> for BgpAnalyserError {
     }
 }
 
+impl From<AsPathError> for BgpAnalyserError {
+    fn from(e: AsPathError) -> Self {
+        BgpAnalyserError::AsPath(e)
+    }
+}
+
 //------------ Tests --------------------------------------------------------
 
 #[cfg(test)]
@@ -352,7 +563,7 @@ mod tests {
         let bgp_ris_dump_v4_uri = "http://www.ris.ripe.net/dumps/riswhoisdump.IPv4.gz";
         let bgp_ris_dump_v6_uri = "http://www.ris.ripe.net/dumps/riswhoisdump.IPv6.gz";
 
-        let analyser = BgpAnalyser::new(true, bgp_ris_dump_v4_uri, bgp_ris_dump_v6_uri);
+        let analyser = BgpAnalyser::new(true, bgp_ris_dump_v4_uri, bgp_ris_dump_v6_uri, None);
 
         assert!(analyser.seen.read().await.is_empty());
         assert!(analyser.seen.read().await.last_checked().is_none());
@@ -449,7 +660,7 @@ mod tests {
 
         let resources_held = ResourceSet::from_strs("", "10.0.0.0/16", "").unwrap();
 
-        let analyser = BgpAnalyser::new(false, "", "");
+        let analyser = BgpAnalyser::new(false, "", "", None);
         let table = analyser.analyse(&roas, &resources_held, None).await;
         let table_entries = table.entries();
         assert_eq!(3, table_entries.len());
@@ -463,6 +674,109 @@ mod tests {
         assert_eq!(roas_no_info, roas);
     }
 
+    #[tokio::test]
+    async fn analyse_max_length_exposed() {
+        // Only 10.0.2.0/23 is seen, 10.0.0.0/23 could be hijacked
+        let exposed = roa_payload("10.0.0.0/22-23 => 64496");
+
+        // Both 192.168.0.0/24 and 192.168.1.0/24 are seen
+        let fully_announced = roa_payload("192.168.0.0/23-24 => 64497");
+        let no_max_length = roa_payload("10.0.0.0/22 => 64497");
+        let as0 = roa_payload("10.0.4.0/22-24 => 0");
+
+        let payloads = &[exposed, fully_announced, no_max_length, as0];
+
+        let analyser = BgpAnalyser::with_test_announcements();
+        assert_eq!(analyser.max_length_exposed(payloads).await, vec![exposed]);
+
+        let analyser = BgpAnalyser::new(false, "", "", None);
+        assert!(analyser.max_length_exposed(payloads).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn analyse_resources_shrunk() {
+        let roa_lost = configured_roa("10.0.0.0/22-24 => 64496");
+        let roa_held = configured_roa("10.0.0.0/24 => 64497");
+        let roas = &[roa_lost, roa_held];
+
+        let resources_held = resources("", "10.0.0.0/23", "");
+        let lost = resources("", "10.0.2.0/23", "");
+        let shrink = ResourceShrink::new(ca_handle("parent").convert(), lost, vec![], vec![]);
+
+        let analyser = BgpAnalyser::with_test_announcements();
+        let report = analyser
+            .resources_shrunk(roas, &resources_held, vec![shrink.clone()])
+            .await;
+
+        assert_eq!(report.shrinks(), &vec![shrink]);
+        assert_eq!(
+            report.announcements(),
+            &vec![
+                ShrinkAffectedAnnouncement::new(
+                    announcement("10.0.0.0/22 => 64496"),
+                    BgpAnalysisState::AnnouncementNotFound
+                ),
+                ShrinkAffectedAnnouncement::new(
+                    announcement("10.0.0.0/24 => 64496"),
+                    BgpAnalysisState::AnnouncementInvalidAsn
+                ),
+                ShrinkAffectedAnnouncement::new(
+                    announcement("10.0.2.0/23 => 64496"),
+                    BgpAnalysisState::AnnouncementNotFound
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn analyse_aspas() {
+        use crate::commons::{
+            api::AspaDefinitionUpdates,
+            bgp::{AspaAnalysisState, AspaSuggestion},
+        };
+
+        let invalidating = AspaDefinition::from_str("AS64496 => AS64501").unwrap();
+        let unseen = AspaDefinition::from_str("AS64498 => AS64500").unwrap();
+        let definitions = &[invalidating.clone(), unseen.clone()];
+
+        let resources_held = ResourceSet::from_strs("AS64496-AS64498", "", "").unwrap();
+
+        let analyser = BgpAnalyser::with_test_announcements();
+        let report = analyser.aspa_analyse(definitions, &resources_held).await;
+
+        let entries = report.matching_entries(AspaAnalysisState::DefinitionInvalidating);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].definition(), Some(&invalidating));
+        assert_eq!(
+            entries[0].unauthorized(),
+            &vec![AspaSeenUpstream::new(Asn::from_u32(64502), 1)]
+        );
+
+        let entries = report.matching_entries(AspaAnalysisState::DefinitionUnseen);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].definition(), Some(&unseen));
+
+        let entries = report.matching_entries(AspaAnalysisState::NoDefinition);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].customer(), Asn::from_u32(64497));
+
+        let suggestion = analyser.aspa_suggest(definitions, &resources_held).await;
+        let (add_or_replace, remove) = AspaDefinitionUpdates::from(suggestion).unpack();
+        assert_eq!(
+            add_or_replace,
+            vec![
+                AspaDefinition::from_str("AS64496 => AS64501, AS64502").unwrap(),
+                AspaDefinition::from_str("AS64497 => AS64502, AS64503").unwrap(),
+            ]
+        );
+        assert!(remove.is_empty());
+
+        let analyser = BgpAnalyser::new(false, "", "", None);
+        let report = analyser.aspa_analyse(definitions, &resources_held).await;
+        assert_eq!(report.matching_entries(AspaAnalysisState::NoPathInfo).len(), 2);
+        assert_eq!(AspaSuggestion::from(&report), AspaSuggestion::default());
+    }
+
     #[tokio::test]
     async fn make_bgp_analysis_suggestion() {
         let roa_too_permissive = configured_roa("10.0.0.0/22-23 => 64496");
@@ -0,0 +1,637 @@
+//! Support loading AS paths for ASPA analysis
+//!
+//! AS paths can be loaded from MRT RIB dumps (RFC 6396) as published by
+//! route collectors, e.g.: https://data.ris.ripe.net/rrc00/latest-bview.gz
+//!
+//! Or from a simple text file with one AS path per line, the AS nearest to
+//! the collector first and the origin AS last. Lines in the "one-line" output
+//! format of bgpdump are also understood.
+
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, Cursor, Read},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use bytes::Bytes;
+use libflate::gzip::Decoder;
+use tokio::sync::mpsc;
+
+use rpki::repository::{resources::Asn, x509::Time};
+
+use crate::commons::error::KrillIoError;
+
+//------------ AsPath --------------------------------------------------------
+
+/// An AS path as seen by a route collector, starting with the AS nearest
+/// to the collector and ending with the origin AS. Prepends are removed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AsPath(Vec<Asn>);
+
+impl AsPath {
+    pub fn new(mut asns: Vec<Asn>) -> Self {
+        asns.dedup();
+        AsPath(asns)
+    }
+
+    pub fn origin(&self) -> Option<Asn> {
+        self.0.last().copied()
+    }
+
+    /// Returns each AS in this path, except the AS nearest to the collector,
+    /// together with the AS to which it propagated this path. This is
+    /// either a provider, or a peer of the AS. The origin AS comes first.
+    pub fn upstreams(&self) -> impl Iterator<Item = (Asn, Asn)> + '_ {
+        self.0.windows(2).rev().map(|pair| (pair[1], pair[0]))
+    }
+}
+
+impl FromStr for AsPath {
+    type Err = AsPathError;
+
+    /// Parses a path of white space separated ASNs. Paths containing AS
+    /// sets are rejected, because the order of the ASNs is unknown.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut asns = vec![];
+        for asn_str in s.split_whitespace() {
+            if asn_str.contains('{') {
+                return Err(AsPathError::AsSet);
+            }
+            let asn =
+                Asn::from_str(asn_str).map_err(|_| AsPathError::parse_error(format!("invalid ASN: {}", asn_str)))?;
+            asns.push(asn);
+        }
+        Ok(AsPath::new(asns))
+    }
+}
+
+impl fmt::Display for AsPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let asns: Vec<String> = self.0.iter().map(|asn| asn.to_string()).collect();
+        write!(f, "{}", asns.join(" "))
+    }
+}
+
+//------------ AsPaths -------------------------------------------------------
+
+/// A summary of loaded AS paths. For each AS in a path only the next AS
+/// toward the collector is kept, as this is what matters for the ASPA
+/// object signed by that AS, be it the origin or a transit AS. A full RIB
+/// dump would take far too much memory otherwise.
+///
+/// The number of distinct upstreams kept for an AS is limited. Once the
+/// limit is reached, paths through other upstreams are no longer counted
+/// for that AS.
+#[derive(Clone, Debug, Default)]
+pub struct AsPaths {
+    upstreams: HashMap<Asn, HashMap<Asn, usize>>,
+    last_updated: Option<Time>,
+    last_checked: Option<Time>,
+}
+
+impl AsPaths {
+    pub fn add(&mut self, path: &AsPath) {
+        for (asn, upstream) in path.upstreams() {
+            let upstreams = self.upstreams.entry(asn).or_default();
+            if let Some(count) = upstreams.get_mut(&upstream) {
+                *count += 1;
+            } else if upstreams.len() < MAX_UPSTREAMS_PER_ASN {
+                upstreams.insert(upstream, 1);
+            }
+        }
+    }
+
+    pub fn update(&mut self, paths: AsPaths) {
+        self.upstreams = paths.upstreams;
+        let now = Time::now();
+        self.last_updated = Some(now);
+        self.last_checked = Some(now);
+    }
+
+    pub fn update_checked(&mut self) {
+        self.last_checked = Some(Time::now())
+    }
+
+    pub fn equivalent(&self, paths: &AsPaths) -> bool {
+        self.upstreams == paths.upstreams
+    }
+
+    /// Returns all ASNs seen in paths with an upstream, i.e. all origin and
+    /// transit ASNs.
+    pub fn asns(&self) -> Vec<Asn> {
+        let mut asns: Vec<Asn> = self.upstreams.keys().copied().collect();
+        asns.sort();
+        asns
+    }
+
+    /// Returns the ASNs seen upstream of the given ASN, and the number of
+    /// paths in which they were seen, ordered by ASN.
+    pub fn upstreams(&self, asn: Asn) -> Vec<(Asn, usize)> {
+        let mut upstreams: Vec<(Asn, usize)> = match self.upstreams.get(&asn) {
+            None => vec![],
+            Some(upstreams) => upstreams.iter().map(|(asn, count)| (*asn, *count)).collect(),
+        };
+        upstreams.sort();
+        upstreams
+    }
+
+    pub fn size(&self) -> usize {
+        self.upstreams.values().map(|upstreams| upstreams.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.upstreams.is_empty()
+    }
+
+    pub fn last_checked(&self) -> Option<Time> {
+        self.last_checked
+    }
+
+    pub fn last_updated(&self) -> Option<Time> {
+        self.last_updated
+    }
+}
+
+//------------ AsPathLoader --------------------------------------------------
+
+/// Loads AS paths from an http(s) URI, or a local file. The data may be
+/// gzipped.
+///
+/// RIB dumps can be large, so the data is parsed while it is read, rather
+/// than loaded into memory first.
+pub struct AsPathLoader {
+    uri: String,
+}
+
+impl AsPathLoader {
+    pub fn new(uri: &str) -> Self {
+        AsPathLoader { uri: uri.to_string() }
+    }
+
+    pub async fn download_updates(&self) -> Result<AsPaths, AsPathError> {
+        if self.uri.starts_with("http://") || self.uri.starts_with("https://") {
+            let mut response = reqwest::get(&self.uri).await?.error_for_status()?;
+
+            // Parse the chunks in a blocking task, as they are received.
+            let (sender, receiver) = mpsc::channel(DOWNLOAD_CHUNKS_BUFFERED);
+            let parser = tokio::task::spawn_blocking(move || Self::parse(ChunkReader::new(receiver)));
+
+            while let Some(chunk) = response.chunk().await? {
+                if sender.send(chunk).await.is_err() {
+                    break; // the parser stopped, it will report why
+                }
+            }
+            drop(sender);
+
+            parser.await.map_err(AsPathError::parse_error)?
+        } else {
+            let path = PathBuf::from(self.uri.strip_prefix("file://").unwrap_or(&self.uri));
+            let file = File::open(&path)
+                .map_err(|e| KrillIoError::new(format!("Could not open AS path file: {}", path.display()), e))?;
+            Self::parse(file)
+        }
+    }
+
+    fn parse(reader: impl Read) -> Result<AsPaths, AsPathError> {
+        let (magic, reader) = Self::peek(reader, 2)?;
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            let decoder = Decoder::new(reader)
+                .map_err(|e| AsPathError::UnzipError(format!("Could not unzip AS path file: {}", e)))?;
+            Self::parse_unzipped(decoder)
+        } else {
+            Self::parse_unzipped(reader)
+        }
+    }
+
+    fn parse_unzipped(reader: impl Read) -> Result<AsPaths, AsPathError> {
+        let (header, reader) = Self::peek(reader, MRT_HEADER_LEN)?;
+        if Self::is_mrt(&header) {
+            Self::parse_mrt(BufReader::new(reader))
+        } else {
+            Self::parse_text(BufReader::new(reader))
+        }
+    }
+
+    /// Returns up to the first len bytes of the reader, and a reader which
+    /// will still produce all bytes.
+    fn peek<R: Read>(mut reader: R, len: usize) -> Result<(Vec<u8>, impl Read), AsPathError> {
+        let mut start = vec![];
+        reader
+            .by_ref()
+            .take(len as u64)
+            .read_to_end(&mut start)
+            .map_err(AsPathError::read_error)?;
+        Ok((start.clone(), Cursor::new(start).chain(reader)))
+    }
+
+    /// RIB dumps start with the TABLE_DUMP_V2 peer index table.
+    fn is_mrt(bytes: &[u8]) -> bool {
+        bytes.len() >= MRT_HEADER_LEN
+            && u16::from_be_bytes([bytes[4], bytes[5]]) == MRT_TYPE_TABLE_DUMP_V2
+            && u16::from_be_bytes([bytes[6], bytes[7]]) == MRT_SUBTYPE_PEER_INDEX_TABLE
+    }
+
+    /// Parses a path per line. Empty lines and comments starting with '#'
+    /// are skipped, and so are paths containing AS sets. Lines containing
+    /// a '|' are expected to use the bgpdump "-m" format, where the path
+    /// is the seventh field.
+    fn parse_text(reader: impl BufRead) -> Result<AsPaths, AsPathError> {
+        let mut res = AsPaths::default();
+
+        for line_res in reader.lines() {
+            let line = line_res.map_err(AsPathError::read_error)?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let path_str = if line.contains('|') {
+                line.split('|').nth(6).ok_or(AsPathError::MissingColumn)?
+            } else {
+                line
+            };
+
+            match AsPath::from_str(path_str) {
+                Ok(path) => res.add(&path),
+                Err(AsPathError::AsSet) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Parses the RIB entries for IPv4 and IPv6 unicast in a TABLE_DUMP_V2
+    /// MRT file, one record at a time. Other record types are skipped.
+    fn parse_mrt(mut reader: impl BufRead) -> Result<AsPaths, AsPathError> {
+        let mut res = AsPaths::default();
+        let mut body = vec![];
+
+        while !reader.fill_buf().map_err(AsPathError::read_error)?.is_empty() {
+            let mut header = [0; MRT_HEADER_LEN];
+            reader.read_exact(&mut header).map_err(AsPathError::read_error)?;
+
+            let mut header = MrtInput::new(&header);
+            let _timestamp = header.u32()?;
+            let record_type = header.u16()?;
+            let subtype = header.u16()?;
+            let len = header.u32()? as u64;
+
+            // Read the body as it arrives, rather than trusting the length
+            // for an allocation up front.
+            body.clear();
+            if reader
+                .by_ref()
+                .take(len)
+                .read_to_end(&mut body)
+                .map_err(AsPathError::read_error)? as u64
+                != len
+            {
+                return Err(AsPathError::Truncated);
+            }
+
+            if record_type != MRT_TYPE_TABLE_DUMP_V2
+                || (subtype != MRT_SUBTYPE_RIB_IPV4_UNICAST && subtype != MRT_SUBTYPE_RIB_IPV6_UNICAST)
+            {
+                continue;
+            }
+
+            let mut body = MrtInput::new(&body);
+            let _sequence = body.u32()?;
+            let prefix_len = body.u8()? as usize;
+            body.take((prefix_len + 7) / 8)?;
+
+            let entries = body.u16()?;
+            for _ in 0..entries {
+                let _peer_index = body.u16()?;
+                let _originated = body.u32()?;
+                let attributes_len = body.u16()? as usize;
+                let attributes = body.take(attributes_len)?;
+
+                if let Some(path) = Self::parse_mrt_as_path(attributes)? {
+                    res.add(&path);
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Finds the AS_PATH attribute, which always uses 4 octet ASNs in
+    /// TABLE_DUMP_V2 RIB entries. Returns None for paths containing an AS
+    /// set. Confederation segments are left out, as they are internal.
+    fn parse_mrt_as_path(attributes: &[u8]) -> Result<Option<AsPath>, AsPathError> {
+        let mut attributes = MrtInput::new(attributes);
+
+        while !attributes.is_empty() {
+            let flags = attributes.u8()?;
+            let attribute_type = attributes.u8()?;
+            let len = if flags & BGP_ATTR_FLAG_EXTENDED_LENGTH != 0 {
+                attributes.u16()? as usize
+            } else {
+                attributes.u8()? as usize
+            };
+            let value = attributes.take(len)?;
+
+            if attribute_type != BGP_ATTR_TYPE_AS_PATH {
+                continue;
+            }
+
+            let mut segments = MrtInput::new(value);
+            let mut asns = vec![];
+            while !segments.is_empty() {
+                let segment_type = segments.u8()?;
+                let count = segments.u8()?;
+                let mut segment = vec![];
+                for _ in 0..count {
+                    segment.push(Asn::from_u32(segments.u32()?));
+                }
+
+                match segment_type {
+                    AS_PATH_SEGMENT_SET => return Ok(None),
+                    AS_PATH_SEGMENT_SEQUENCE => asns.append(&mut segment),
+                    _ => {}
+                }
+            }
+            return Ok(Some(AsPath::new(asns)));
+        }
+
+        Ok(None)
+    }
+}
+
+/// The number of downloaded chunks which may wait to be parsed.
+const DOWNLOAD_CHUNKS_BUFFERED: usize = 16;
+
+// Limits the memory used for ASNs which propagate paths to many others,
+// e.g. large transit networks with many peers.
+const MAX_UPSTREAMS_PER_ASN: usize = 256;
+
+const MRT_HEADER_LEN: usize = 12;
+const MRT_TYPE_TABLE_DUMP_V2: u16 = 13;
+const MRT_SUBTYPE_PEER_INDEX_TABLE: u16 = 1;
+const MRT_SUBTYPE_RIB_IPV4_UNICAST: u16 = 2;
+const MRT_SUBTYPE_RIB_IPV6_UNICAST: u16 = 4;
+
+const BGP_ATTR_FLAG_EXTENDED_LENGTH: u8 = 0x10;
+const BGP_ATTR_TYPE_AS_PATH: u8 = 2;
+
+const AS_PATH_SEGMENT_SET: u8 = 1;
+const AS_PATH_SEGMENT_SEQUENCE: u8 = 2;
+
+/// Reads big-endian values from MRT data, failing on truncated input.
+struct MrtInput<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> MrtInput<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        MrtInput { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AsPathError> {
+        if self.bytes.len() < len {
+            Err(AsPathError::Truncated)
+        } else {
+            let (taken, rest) = self.bytes.split_at(len);
+            self.bytes = rest;
+            Ok(taken)
+        }
+    }
+
+    fn u8(&mut self) -> Result<u8, AsPathError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, AsPathError> {
+        Ok(u16::from_be_bytes(<[u8; 2]>::try_from(self.take(2)?).unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, AsPathError> {
+        Ok(u32::from_be_bytes(<[u8; 4]>::try_from(self.take(4)?).unwrap()))
+    }
+}
+
+//------------ ChunkReader ---------------------------------------------------
+
+/// Reads the chunks of a download as they are received. This blocks, so
+/// it must only be used from a blocking task.
+struct ChunkReader {
+    receiver: mpsc::Receiver<Bytes>,
+    chunk: Bytes,
+}
+
+impl ChunkReader {
+    fn new(receiver: mpsc::Receiver<Bytes>) -> Self {
+        ChunkReader {
+            receiver,
+            chunk: Bytes::new(),
+        }
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.chunk.is_empty() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => self.chunk = chunk,
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len());
+        buf[..len].copy_from_slice(&self.chunk.split_to(len));
+        Ok(len)
+    }
+}
+
+//------------ Error --------------------------------------------------------
+
+#[derive(Debug)]
+pub enum AsPathError {
+    ReqwestError(reqwest::Error),
+    MissingColumn,
+    AsSet,
+    Truncated,
+    ParseError(String),
+    IoError(KrillIoError),
+    UnzipError(String),
+}
+
+impl fmt::Display for AsPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsPathError::ReqwestError(e) => write!(f, "Cannot get uri: {}", e),
+            AsPathError::MissingColumn => write!(f, "Missing AS path column in input"),
+            AsPathError::AsSet => write!(f, "AS path contains an AS set"),
+            AsPathError::Truncated => write!(f, "Truncated AS path data"),
+            AsPathError::ParseError(s) => write!(f, "Error parsing AS paths: {}", s),
+            AsPathError::IoError(e) => write!(f, "IO error: {}", e),
+            AsPathError::UnzipError(s) => write!(f, "Error unzipping: {}", s),
+        }
+    }
+}
+
+impl AsPathError {
+    fn parse_error(e: impl fmt::Display) -> Self {
+        AsPathError::ParseError(format!("{}", e))
+    }
+
+    fn read_error(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            AsPathError::Truncated
+        } else {
+            AsPathError::parse_error(e)
+        }
+    }
+}
+
+impl From<reqwest::Error> for AsPathError {
+    fn from(e: reqwest::Error) -> AsPathError {
+        AsPathError::ReqwestError(e)
+    }
+}
+
+impl From<KrillIoError> for AsPathError {
+    fn from(e: KrillIoError) -> Self {
+        AsPathError::IoError(e)
+    }
+}
+
+//------------ Tests --------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use libflate::gzip::Encoder;
+
+    use super::*;
+
+    fn asn(nr: u32) -> Asn {
+        Asn::from_u32(nr)
+    }
+
+    #[test]
+    fn parse_text_paths() {
+        let text = concat!(
+            "# paths seen by a collector\n",
+            "64500 64501 64496 64496\n",
+            "AS64502 AS64496\n",
+            "64503 {64504,64505} 64496\n",
+            "TABLE_DUMP2|1672531200|B|192.0.2.1|64500|10.0.0.0/24|64500 64502 64496|IGP|192.0.2.1|0|0||NAG||\n",
+        );
+
+        let paths = AsPathLoader::parse(text.as_bytes()).unwrap();
+
+        assert_eq!(paths.asns(), vec![asn(64496), asn(64501), asn(64502)]);
+        assert_eq!(paths.upstreams(asn(64496)), vec![(asn(64501), 1), (asn(64502), 2)]);
+        assert_eq!(paths.upstreams(asn(64501)), vec![(asn(64500), 1)]);
+        assert_eq!(paths.upstreams(asn(64502)), vec![(asn(64500), 1)]);
+    }
+
+    #[test]
+    fn transit_upstreams() {
+        let mut paths = AsPaths::default();
+        for path in [
+            "64500 64510 64496",
+            "64501 64510 64497",
+            "64500 64510 64497",
+            "64500 64510",
+        ] {
+            paths.add(&AsPath::from_str(path).unwrap());
+        }
+
+        // 64510 is seen as an origin, and as a transit for 64496 and 64497
+        assert_eq!(paths.upstreams(asn(64510)), vec![(asn(64500), 3), (asn(64501), 1)]);
+        assert_eq!(paths.upstreams(asn(64497)), vec![(asn(64510), 2)]);
+
+        // the number of distinct upstreams per ASN is limited, but paths
+        // through known upstreams are still counted
+        let mut paths = AsPaths::default();
+        for upstream in 0..(MAX_UPSTREAMS_PER_ASN as u32 + 10) {
+            paths.add(&AsPath::new(vec![asn(65000 + upstream), asn(64510), asn(64496)]));
+        }
+        paths.add(&AsPath::new(vec![asn(65000), asn(64510)]));
+
+        let upstreams = paths.upstreams(asn(64510));
+        assert_eq!(upstreams.len(), MAX_UPSTREAMS_PER_ASN);
+        assert_eq!(upstreams[0], (asn(65000), 2));
+    }
+
+    #[test]
+    fn parse_mrt_rib_dump() {
+        fn record(subtype: u16, body: &[u8]) -> Vec<u8> {
+            let mut record = vec![0, 0, 0, 0];
+            record.extend_from_slice(&MRT_TYPE_TABLE_DUMP_V2.to_be_bytes());
+            record.extend_from_slice(&subtype.to_be_bytes());
+            record.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            record.extend_from_slice(body);
+            record
+        }
+
+        fn rib_entry(segments: &[(u8, &[u32])]) -> Vec<u8> {
+            let mut as_path = vec![];
+            for (segment_type, asns) in segments {
+                as_path.push(*segment_type);
+                as_path.push(asns.len() as u8);
+                for asn in asns.iter() {
+                    as_path.extend_from_slice(&asn.to_be_bytes());
+                }
+            }
+
+            // ORIGIN attribute, followed by an AS_PATH using an extended length
+            let mut attributes = vec![0x40, 1, 1, 0];
+            attributes.extend_from_slice(&[0x50, BGP_ATTR_TYPE_AS_PATH]);
+            attributes.extend_from_slice(&(as_path.len() as u16).to_be_bytes());
+            attributes.extend_from_slice(&as_path);
+
+            let mut entry = vec![0, 0, 0, 0, 0, 0];
+            entry.extend_from_slice(&(attributes.len() as u16).to_be_bytes());
+            entry.extend_from_slice(&attributes);
+            entry
+        }
+
+        let mut rib = vec![0, 0, 0, 1, 24, 10, 0, 0, 0, 2];
+        rib.append(&mut rib_entry(&[(AS_PATH_SEGMENT_SEQUENCE, &[64500, 64501, 64496])]));
+        rib.append(&mut rib_entry(&[
+            (AS_PATH_SEGMENT_SEQUENCE, &[64500, 64502]),
+            (AS_PATH_SEGMENT_SET, &[64496, 64497]),
+        ]));
+
+        let mut mrt = record(MRT_SUBTYPE_PEER_INDEX_TABLE, &[0; 8]);
+        mrt.append(&mut record(MRT_SUBTYPE_RIB_IPV4_UNICAST, &rib));
+
+        let paths = AsPathLoader::parse(mrt.as_slice()).unwrap();
+        assert_eq!(paths.asns(), vec![asn(64496), asn(64501)]);
+        assert_eq!(paths.upstreams(asn(64496)), vec![(asn(64501), 1)]);
+
+        // The same data, gzipped and received in small chunks
+        let mut encoder = Encoder::new(vec![]).unwrap();
+        encoder.write_all(&mrt).unwrap();
+        let gzipped = encoder.finish().into_result().unwrap();
+
+        let (sender, receiver) = mpsc::channel(gzipped.len());
+        for chunk in gzipped.chunks(3) {
+            sender.blocking_send(Bytes::copy_from_slice(chunk)).unwrap();
+        }
+        drop(sender);
+        assert!(AsPathLoader::parse(ChunkReader::new(receiver))
+            .unwrap()
+            .equivalent(&paths));
+
+        let truncated = record(MRT_SUBTYPE_PEER_INDEX_TABLE, &[0; 8]);
+        assert!(matches!(
+            AsPathLoader::parse(&truncated[..truncated.len() - 1]),
+            Err(AsPathError::Truncated)
+        ));
+    }
+}
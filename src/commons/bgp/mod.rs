@@ -4,6 +4,9 @@ pub use self::analyser::*;
 mod announcements;
 pub use self::announcements::*;
 
+mod aspaths;
+pub use self::aspaths::*;
+
 mod iptree;
 pub use self::iptree::*;
 
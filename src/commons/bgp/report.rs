@@ -1,7 +1,12 @@
 use std::{cmp::Ordering, collections::HashMap, fmt};
 
+use rpki::repository::{aspa::ProviderAs, resources::Asn};
+
 use crate::commons::{
-    api::{BgpStats, ConfiguredRoa, RoaConfiguration, RoaConfigurationUpdates, RoaPayload},
+    api::{
+        AspaCustomer, AspaDefinition, AspaDefinitionUpdates, BgpStats, ConfiguredRoa, ResourceShrink, RoaConfiguration,
+        RoaConfigurationUpdates, RoaPayload,
+    },
     bgp::Announcement,
 };
 
@@ -340,6 +345,9 @@ impl From<BgpAnalysisReport> for BgpStats {
     fn from(r: BgpAnalysisReport) -> BgpStats {
         let mut stats = BgpStats::default();
         for e in r.0.iter() {
+            if e.max_length_exposed {
+                stats.increment_roas_max_length_exposed();
+            }
             match e.state {
                 BgpAnalysisState::AnnouncementValid => stats.increment_valid(),
                 BgpAnalysisState::AnnouncementInvalidAsn => stats.increment_invalid_asn(),
@@ -508,6 +516,19 @@ impl fmt::Display for BgpAnalysisReport {
                 writeln!(f)?;
             }
 
+            let max_length_exposed: Vec<_> = entries.iter().filter(|e| e.max_length_exposed()).collect();
+            if !max_length_exposed.is_empty() {
+                writeln!(
+                    f,
+                    "ROA configurations using a max length which allows more specific announcements that are not seen (see RFC 9319):"
+                )?;
+                writeln!(f)?;
+                for roa in max_length_exposed {
+                    writeln!(f, "\tConfiguration: {}", roa.configured_roa())?;
+                }
+                writeln!(f)?;
+            }
+
             if let Some(as0) = entry_map.get(&BgpAnalysisState::RoaAs0) {
                 writeln!(f, "AS0 ROA configurations disallowing announcements for prefixes")?;
                 writeln!(f)?;
@@ -617,6 +638,12 @@ pub struct BgpAnalysisEntry {
     authorizes: Vec<Announcement>,
     #[serde(skip_serializing_if = "Vec::is_empty", default = "Vec::new")]
     disallows: Vec<Announcement>,
+    #[serde(skip_serializing_if = "is_false", default)]
+    max_length_exposed: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
 }
 
 /// This type is used to allow us to mix both configured ROAs
@@ -692,6 +719,18 @@ impl BgpAnalysisEntry {
         &self.disallows
     }
 
+    /// Returns true if this is a ROA configuration with a max length that
+    /// allows more specific announcements which are not all seen in BGP.
+    /// See RFC 9319.
+    pub fn max_length_exposed(&self) -> bool {
+        self.max_length_exposed
+    }
+
+    pub fn with_max_length_exposed(mut self, max_length_exposed: bool) -> Self {
+        self.max_length_exposed = max_length_exposed;
+        self
+    }
+
     pub fn roa_seen(
         configured_roa: ConfiguredRoa,
         mut authorizes: Vec<Announcement>,
@@ -707,6 +746,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes,
             disallows,
+            max_length_exposed: false,
         }
     }
 
@@ -720,6 +760,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes: vec![],
             disallows,
+            max_length_exposed: false,
         }
     }
 
@@ -733,6 +774,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes: vec![],
             disallows,
+            max_length_exposed: false,
         }
     }
 
@@ -746,6 +788,7 @@ impl BgpAnalysisEntry {
             made_redundant_by,
             authorizes: vec![],
             disallows: vec![],
+            max_length_exposed: false,
         }
     }
 
@@ -766,6 +809,7 @@ impl BgpAnalysisEntry {
             made_redundant_by,
             authorizes,
             disallows,
+            max_length_exposed: false,
         }
     }
 
@@ -784,6 +828,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes,
             disallows,
+            max_length_exposed: false,
         }
     }
 
@@ -796,6 +841,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes: vec![],
             disallows: vec![],
+            max_length_exposed: false,
         }
     }
 
@@ -808,6 +854,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes: vec![],
             disallows: vec![],
+            max_length_exposed: false,
         }
     }
 
@@ -820,6 +867,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes: vec![],
             disallows: vec![],
+            max_length_exposed: false,
         }
     }
 
@@ -832,6 +880,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes: vec![],
             disallows: vec![],
+            max_length_exposed: false,
         }
     }
 
@@ -861,6 +910,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes: vec![],
             disallows: vec![],
+            max_length_exposed: false,
         }
     }
 
@@ -873,6 +923,7 @@ impl BgpAnalysisEntry {
             made_redundant_by: vec![],
             authorizes: vec![],
             disallows: vec![],
+            max_length_exposed: false,
         }
     }
 }
@@ -923,6 +974,390 @@ impl BgpAnalysisState {
     }
 }
 
+//------------ ResourceShrinkReport ----------------------------------------
+
+/// Reports on resources which were removed by parents, and which were not
+/// yet cleared by the operator. Includes the seen announcements which were
+/// valid while the resources were still held, but which are now not found
+/// or invalid.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResourceShrinkReport {
+    shrinks: Vec<ResourceShrink>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Vec::new")]
+    announcements: Vec<ShrinkAffectedAnnouncement>,
+}
+
+impl ResourceShrinkReport {
+    pub fn new(shrinks: Vec<ResourceShrink>, mut announcements: Vec<ShrinkAffectedAnnouncement>) -> Self {
+        announcements.sort_by_key(|affected| affected.announcement);
+        ResourceShrinkReport { shrinks, announcements }
+    }
+
+    pub fn shrinks(&self) -> &Vec<ResourceShrink> {
+        &self.shrinks
+    }
+
+    pub fn announcements(&self) -> &Vec<ShrinkAffectedAnnouncement> {
+        &self.announcements
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shrinks.is_empty()
+    }
+
+    pub fn nr_roas_not_held(&self) -> usize {
+        self.shrinks.iter().map(|shrink| shrink.roas_not_held().len()).sum()
+    }
+
+    pub fn nr_children_shrunk(&self) -> usize {
+        self.shrinks.iter().map(|shrink| shrink.children().len()).sum()
+    }
+}
+
+impl fmt::Display for ResourceShrinkReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.shrinks.is_empty() {
+            return writeln!(f, "No resources were removed by parents");
+        }
+
+        for shrink in self.shrinks.iter() {
+            write!(f, "{}", shrink)?;
+        }
+
+        if !self.announcements.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Announcements which are no longer valid:")?;
+            for affected in self.announcements.iter() {
+                let state = match affected.state {
+                    BgpAnalysisState::AnnouncementInvalidAsn => "invalid ASN",
+                    BgpAnalysisState::AnnouncementInvalidLength => "invalid length",
+                    BgpAnalysisState::AnnouncementDisallowed => "disallowed",
+                    _ => "not found",
+                };
+                writeln!(f, "  {} ({})", affected.announcement, state)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//------------ ShrinkAffectedAnnouncement ----------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ShrinkAffectedAnnouncement {
+    announcement: Announcement,
+    state: BgpAnalysisState,
+}
+
+impl ShrinkAffectedAnnouncement {
+    pub fn new(announcement: Announcement, state: BgpAnalysisState) -> Self {
+        ShrinkAffectedAnnouncement { announcement, state }
+    }
+
+    pub fn announcement(&self) -> Announcement {
+        self.announcement
+    }
+
+    pub fn state(&self) -> BgpAnalysisState {
+        self.state
+    }
+}
+
+//------------ AspaAnalysisReport ------------------------------------------
+
+/// Compares ASPA definitions with the AS paths seen in BGP, for customer
+/// ASNs which have a definition, or which are held and seen in AS paths.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaAnalysisReport(Vec<AspaAnalysisEntry>);
+
+impl AspaAnalysisReport {
+    pub fn new(mut entries: Vec<AspaAnalysisEntry>) -> Self {
+        entries.sort_by_key(|entry| entry.customer);
+        AspaAnalysisReport(entries)
+    }
+
+    pub fn entries(&self) -> &Vec<AspaAnalysisEntry> {
+        &self.0
+    }
+
+    pub fn matching_entries(&self, state: AspaAnalysisState) -> Vec<&AspaAnalysisEntry> {
+        self.0.iter().filter(|entry| entry.state == state).collect()
+    }
+}
+
+impl fmt::Display for AspaAnalysisReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.matching_entries(AspaAnalysisState::NoPathInfo).is_empty() {
+            return write!(f, "no AS path information known");
+        }
+
+        let invalidating = self.matching_entries(AspaAnalysisState::DefinitionInvalidating);
+        if !invalidating.is_empty() {
+            writeln!(f, "ASPA definitions which would invalidate seen AS paths:")?;
+            for entry in invalidating {
+                writeln!(f)?;
+                writeln!(f, "\tDefinition: {}", entry.definition.as_ref().unwrap())?;
+                writeln!(f)?;
+                writeln!(f, "\t\tSeen upstream ASNs which are not authorized:")?;
+                for upstream in entry.unauthorized.iter() {
+                    writeln!(f, "\t\t{}", upstream)?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        let valid = self.matching_entries(AspaAnalysisState::DefinitionValid);
+        if !valid.is_empty() {
+            writeln!(f, "ASPA definitions which authorize all seen upstream ASNs:")?;
+            writeln!(f)?;
+            for entry in valid {
+                writeln!(f, "\t{}", entry.definition.as_ref().unwrap())?;
+            }
+            writeln!(f)?;
+        }
+
+        let unseen = self.matching_entries(AspaAnalysisState::DefinitionUnseen);
+        if !unseen.is_empty() {
+            writeln!(f, "ASPA definitions for customer ASNs which are not seen in AS paths:")?;
+            writeln!(f)?;
+            for entry in unseen {
+                writeln!(f, "\t{}", entry.definition.as_ref().unwrap())?;
+            }
+            writeln!(f)?;
+        }
+
+        let no_definition = self.matching_entries(AspaAnalysisState::NoDefinition);
+        if !no_definition.is_empty() {
+            writeln!(f, "Held ASNs seen in AS paths which have no ASPA definition:")?;
+            for entry in no_definition {
+                writeln!(f)?;
+                writeln!(f, "\tCustomer: {}", entry.customer)?;
+                writeln!(f)?;
+                writeln!(f, "\t\tSeen upstream ASNs:")?;
+                for upstream in entry.upstreams.iter() {
+                    writeln!(f, "\t\t{}", upstream)?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+//------------ AspaAnalysisEntry -------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaAnalysisEntry {
+    customer: AspaCustomer,
+    state: AspaAnalysisState,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    definition: Option<AspaDefinition>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Vec::new")]
+    upstreams: Vec<AspaSeenUpstream>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Vec::new")]
+    unauthorized: Vec<AspaSeenUpstream>,
+}
+
+impl AspaAnalysisEntry {
+    pub fn no_path_info(customer: AspaCustomer, definition: Option<AspaDefinition>) -> Self {
+        AspaAnalysisEntry {
+            customer,
+            state: AspaAnalysisState::NoPathInfo,
+            definition,
+            upstreams: vec![],
+            unauthorized: vec![],
+        }
+    }
+
+    /// Compares the definition, if any, with the ASNs seen upstream of the
+    /// customer ASN. The AFI limits of providers are not taken into account,
+    /// as AS paths are not always known per address family.
+    pub fn new(customer: AspaCustomer, definition: Option<AspaDefinition>, upstreams: Vec<AspaSeenUpstream>) -> Self {
+        let unauthorized: Vec<AspaSeenUpstream> = match &definition {
+            None => vec![],
+            Some(definition) => upstreams
+                .iter()
+                .filter(|upstream| {
+                    !definition
+                        .providers()
+                        .iter()
+                        .any(|provider| provider.provider() == upstream.asn)
+                })
+                .copied()
+                .collect(),
+        };
+
+        let state = if definition.is_none() {
+            AspaAnalysisState::NoDefinition
+        } else if upstreams.is_empty() {
+            AspaAnalysisState::DefinitionUnseen
+        } else if unauthorized.is_empty() {
+            AspaAnalysisState::DefinitionValid
+        } else {
+            AspaAnalysisState::DefinitionInvalidating
+        };
+
+        AspaAnalysisEntry {
+            customer,
+            state,
+            definition,
+            upstreams,
+            unauthorized,
+        }
+    }
+
+    pub fn customer(&self) -> AspaCustomer {
+        self.customer
+    }
+
+    pub fn state(&self) -> AspaAnalysisState {
+        self.state
+    }
+
+    pub fn definition(&self) -> Option<&AspaDefinition> {
+        self.definition.as_ref()
+    }
+
+    pub fn upstreams(&self) -> &Vec<AspaSeenUpstream> {
+        &self.upstreams
+    }
+
+    /// Returns the seen upstream ASNs which are not authorized as provider,
+    /// i.e. paths through these ASNs would be invalid when they are verified
+    /// in the upstream direction.
+    pub fn unauthorized(&self) -> &Vec<AspaSeenUpstream> {
+        &self.unauthorized
+    }
+}
+
+//------------ AspaSeenUpstream --------------------------------------------
+
+/// An ASN seen upstream of a customer ASN, i.e. a provider or peer, and the
+/// number of AS paths in which it was seen.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaSeenUpstream {
+    asn: Asn,
+    paths: usize,
+}
+
+impl AspaSeenUpstream {
+    pub fn new(asn: Asn, paths: usize) -> Self {
+        AspaSeenUpstream { asn, paths }
+    }
+
+    pub fn asn(&self) -> Asn {
+        self.asn
+    }
+
+    pub fn paths(&self) -> usize {
+        self.paths
+    }
+}
+
+impl fmt::Display for AspaSeenUpstream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} in {} path(s)", self.asn, self.paths)
+    }
+}
+
+//------------ AspaAnalysisState -------------------------------------------
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialOrd, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AspaAnalysisState {
+    DefinitionValid,
+    DefinitionInvalidating,
+    DefinitionUnseen,
+    NoDefinition,
+    NoPathInfo,
+}
+
+//------------ AspaSuggestion ----------------------------------------------
+
+/// Suggested ASPA definitions for held customer ASNs which are seen as
+/// origin, authorizing all seen upstream ASNs. Existing providers are kept,
+/// because providers which are only used as backup may not be seen.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaSuggestion(Vec<AspaSuggestionEntry>);
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaSuggestionEntry {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    current: Option<AspaDefinition>,
+    suggested: AspaDefinition,
+}
+
+impl AspaSuggestion {
+    pub fn entries(&self) -> &Vec<AspaSuggestionEntry> {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<&AspaAnalysisReport> for AspaSuggestion {
+    fn from(report: &AspaAnalysisReport) -> Self {
+        let mut entries = vec![];
+
+        for entry in report.entries() {
+            let (current, mut providers) = match entry.state {
+                AspaAnalysisState::NoDefinition => (None, vec![]),
+                AspaAnalysisState::DefinitionInvalidating => {
+                    let current = entry.definition.clone().unwrap();
+                    let providers = current.providers().clone();
+                    (Some(current), providers)
+                }
+                _ => continue,
+            };
+
+            for upstream in entry.upstreams.iter() {
+                if !providers.iter().any(|provider| provider.provider() == upstream.asn) {
+                    providers.push(ProviderAs::new(upstream.asn));
+                }
+            }
+            providers.sort_by_key(|provider| provider.provider());
+
+            entries.push(AspaSuggestionEntry {
+                current,
+                suggested: AspaDefinition::new(entry.customer, providers),
+            });
+        }
+
+        AspaSuggestion(entries)
+    }
+}
+
+impl From<AspaSuggestion> for AspaDefinitionUpdates {
+    fn from(suggestion: AspaSuggestion) -> Self {
+        let add_or_replace = suggestion.0.into_iter().map(|entry| entry.suggested).collect();
+        AspaDefinitionUpdates::new(add_or_replace, vec![])
+    }
+}
+
+impl fmt::Display for AspaSuggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return writeln!(f, "No changes to ASPA definitions suggested");
+        }
+
+        for entry in self.0.iter() {
+            match &entry.current {
+                None => writeln!(f, "Add: {}", entry.suggested)?,
+                Some(current) => {
+                    writeln!(f, "Replace: {}", current)?;
+                    writeln!(f, "   With: {}", entry.suggested)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 //------------ Tests --------------------------------------------------------
 
 #[cfg(test)]
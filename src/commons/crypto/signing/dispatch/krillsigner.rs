@@ -1,8 +1,10 @@
 use std::{path::Path, sync::Arc, time::Duration};
 
+use bcder::Oid;
+use bytes::Bytes;
 use rpki::{
     ca::{
-        csr::{Csr, RpkiCaCsr},
+        csr::{BgpsecCsr, Csr, RpkiCaCsr},
         idcert::IdCert,
         idexchange::RepoInfo,
         provisioning, publication,
@@ -13,9 +15,10 @@ use rpki::{
         cert::TbsCert,
         crl::{CrlEntry, TbsCertList},
         manifest::ManifestContent,
+        resources::Asn,
         roa::RoaBuilder,
         rta,
-        sigobj::SignedObjectBuilder,
+        sigobj::{SignedObject, SignedObjectBuilder},
         x509::{Serial, Time, Validity},
         Cert, Crl, Manifest, Roa,
     },
@@ -234,6 +237,18 @@ impl KrillSigner {
             .map_err(crypto::Error::signer)
     }
 
+    /// Creates a key pair for a BGPsec router and returns a CSR for it, and
+    /// the private key in an encrypted PKCS#8 PEM container. The private key
+    /// is not kept by Krill.
+    pub fn create_bgpsec_router_key(&self, asn: Asn, passphrase: &str) -> CryptoResult<(BgpsecCsr, String)> {
+        let (csr, private_key) = self
+            .router
+            .create_router_key(asn, passphrase)
+            .map_err(crypto::Error::signer)?;
+        let csr = BgpsecCsr::decode(csr.as_ref()).map_err(crypto::Error::decode)?;
+        Ok((csr, private_key))
+    }
+
     pub fn sign_csr(&self, base_repo: &RepoInfo, name_space: &str, key: &KeyIdentifier) -> CryptoResult<RpkiCaCsr> {
         let signing_key_id = self.router.get_key_info(key).map_err(crypto::Error::key_error)?;
         let mft_file_name = ObjectName::mft_for_key(&signing_key_id.key_identifier());
@@ -293,6 +308,20 @@ impl KrillSigner {
             .map_err(crypto::Error::signing)
     }
 
+    /// Signs an RFC 6488 signed object with the given content type, for
+    /// object types which have no builder of their own in rpki-rs.
+    pub fn sign_object(
+        &self,
+        content_type: Oid<Bytes>,
+        content: Bytes,
+        object_builder: SignedObjectBuilder,
+        key_id: &KeyIdentifier,
+    ) -> CryptoResult<SignedObject> {
+        object_builder
+            .finalize(content_type, content, &self.router, key_id)
+            .map_err(crypto::Error::signing)
+    }
+
     pub fn sign_rta(&self, rta_builder: &mut rta::RtaBuilder, ee: Cert) -> CryptoResult<()> {
         let key = ee.subject_key_identifier();
         rta_builder.push_cert(ee);
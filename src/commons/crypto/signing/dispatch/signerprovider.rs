@@ -1,6 +1,11 @@
-use rpki::crypto::{
-    signer::{KeyError, SigningAlgorithm},
-    KeyIdentifier, PublicKey, PublicKeyFormat, RpkiSignature, Signature, SignatureAlgorithm, SigningError,
+use bytes::Bytes;
+
+use rpki::{
+    crypto::{
+        signer::{KeyError, SigningAlgorithm},
+        KeyIdentifier, PublicKey, PublicKeyFormat, RpkiSignature, Signature, SignatureAlgorithm, SigningError,
+    },
+    repository::resources::Asn,
 };
 
 use crate::commons::crypto::{
@@ -123,6 +128,20 @@ impl SignerProvider {
         }
     }
 
+    /// Creates a BGPsec router key pair and returns a CSR for it, and the
+    /// private key in an encrypted PKCS#8 container. Only signers which keep
+    /// keys in software can export private keys.
+    pub fn create_router_key(&self, asn: Asn, passphrase: &str) -> Result<(Bytes, String), SignerError> {
+        match self {
+            SignerProvider::OpenSsl(_, signer) => signer.create_router_key(asn, passphrase),
+            #[cfg(feature = "hsm")]
+            _ => Err(SignerError::Other(format!(
+                "Signer '{}' cannot export private keys for BGPsec routers",
+                self.get_name()
+            ))),
+        }
+    }
+
     pub fn set_handle(&self, handle: SignerHandle) {
         match self {
             SignerProvider::OpenSsl(_, signer) => signer.set_handle(handle),
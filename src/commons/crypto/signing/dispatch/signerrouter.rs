@@ -1,8 +1,14 @@
 use std::sync::Arc;
 use std::{collections::HashMap, sync::RwLock};
 
-use rpki::crypto::{
-    signer::KeyError, KeyIdentifier, PublicKey, PublicKeyFormat, Signature, SignatureAlgorithm, Signer, SigningError,
+use bytes::Bytes;
+
+use rpki::{
+    crypto::{
+        signer::KeyError, KeyIdentifier, PublicKey, PublicKeyFormat, Signature, SignatureAlgorithm, Signer,
+        SigningError,
+    },
+    repository::resources::Asn,
 };
 
 use crate::commons::{
@@ -160,6 +166,13 @@ impl SignerRouter {
         self.active_signers.read().unwrap().clone()
     }
 
+    /// BGPsec router keys are handed out and not kept, much like one-off
+    /// signing keys, so they are created by the one-off signer.
+    pub fn create_router_key(&self, asn: Asn, passphrase: &str) -> Result<(Bytes, String), SignerError> {
+        self.bind_ready_signers();
+        self.one_off_signer.create_router_key(asn, passphrase)
+    }
+
     /// Locate the [SignerProvider] that owns a given [KeyIdentifier], if the signer is active.
     ///
     /// If the signer that owns the key has not yet been promoted from the pending set to the active set or if no
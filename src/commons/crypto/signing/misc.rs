@@ -4,7 +4,11 @@
 use std::convert::TryFrom;
 
 use rpki::{
-    ca::{csr::RpkiCaCsr, provisioning::RequestResourceLimit},
+    ca::{
+        csr::RpkiCaCsr,
+        idexchange::RepoInfo,
+        provisioning::{RequestResourceLimit, ResourceClassName},
+    },
     crypto::{KeyIdentifier, PublicKey},
     repository::{
         cert::{KeyUsage, Overclaim, TbsCert},
@@ -17,7 +21,7 @@ use rpki::{
 
 use crate::{
     commons::{
-        api::{IssuedCertificate, ReceivedCert},
+        api::{IssuedCertificate, ObjectName, ReceivedCert, TaCertDetails, TrustAnchorLocator},
         crypto::KrillSigner,
         error::Error,
         util::AllowedUri,
@@ -73,6 +77,10 @@ impl CsrInfo {
         (self.ca_repository, self.rpki_manifest, self.rpki_notify, self.key)
     }
 
+    pub fn key(&self) -> &PublicKey {
+        &self.key
+    }
+
     pub fn key_id(&self) -> KeyIdentifier {
         self.key.key_identifier()
     }
@@ -140,6 +148,59 @@ impl SignSupport {
             .map_err(|e| Error::Custom(format!("Signed certificate has issue: {}", e)))
     }
 
+    /// Create a self-signed certificate for a Trust Anchor key, holding all
+    /// resources, and the TAL which refers to it. Used by the embedded TA as
+    /// well as the offline TA signer.
+    pub fn make_ta_cert_details(
+        key: &KeyIdentifier,
+        uris: Vec<uri::Https>,
+        rsync_uri: uri::Rsync,
+        repo_info: &RepoInfo,
+        signer: &KrillSigner,
+    ) -> KrillResult<TaCertDetails> {
+        let resources = ResourceSet::all();
+
+        let cert = {
+            let serial = signer.random_serial()?;
+
+            let pub_key = signer.get_key_info(key).map_err(Error::signer)?;
+            let name = pub_key.to_subject_name();
+
+            let mut cert = TbsCert::new(
+                serial,
+                name.clone(),
+                Validity::new(Time::five_minutes_ago(), Time::years_from_now(100)),
+                Some(name),
+                pub_key.clone(),
+                KeyUsage::Ca,
+                Overclaim::Refuse,
+            );
+
+            cert.set_basic_ca(Some(true));
+
+            let ns = ResourceClassName::default().to_string();
+
+            cert.set_ca_repository(Some(repo_info.ca_repository(&ns)));
+            cert.set_rpki_manifest(Some(
+                repo_info.resolve(&ns, ObjectName::mft_for_key(&pub_key.key_identifier()).as_ref()),
+            ));
+            cert.set_rpki_notify(repo_info.rpki_notify().cloned());
+
+            cert.set_as_resources(resources.to_as_resources());
+            cert.set_v4_resources(resources.to_ip_resources_v4());
+            cert.set_v6_resources(resources.to_ip_resources_v6());
+
+            signer.sign_cert(cert, key)?
+        };
+
+        let tal = TrustAnchorLocator::new(uris, rsync_uri.clone(), cert.subject_public_key_info());
+
+        let rcvd_cert =
+            ReceivedCert::create(cert, rsync_uri, resources, RequestResourceLimit::default()).map_err(Error::custom)?;
+
+        Ok(TaCertDetails::new(rcvd_cert, tal))
+    }
+
     /// Create an EE certificate for use in ResourceTaggedAttestations.
     /// Note that for RPKI signed objects such as ROAs and Manifests, the
     /// EE certificate is created by the rpki.rs library instead.
@@ -6,19 +6,22 @@ use hyper::StatusCode;
 
 use rpki::{
     ca::{
-        idexchange::{CaHandle, ChildHandle, ParentHandle, PublisherHandle},
+        idexchange::{CaHandle, ChildHandle, ParentHandle, PublisherHandle, ServiceUri},
         provisioning,
         provisioning::ResourceClassName,
         publication,
     },
     crypto::KeyIdentifier,
-    repository::error::ValidationError,
+    repository::{error::ValidationError, resources::Asn},
     uri,
 };
 
 use crate::{
     commons::{
-        api::{rrdp::PublicationDeltaError, AspaCustomer, AspaProvidersUpdateConflict, ErrorResponse, RoaPayload},
+        api::{
+            rrdp::PublicationDeltaError, AspaCustomer, AspaProvidersUpdateConflict, ErrorResponse, ObjectName,
+            RoaDraftName, RoaPayload,
+        },
         crypto::SignerError,
         eventsourcing::{AggregateStoreError, KeyValueError},
         util::httpclient,
@@ -39,6 +42,10 @@ pub struct RoaDeltaError {
     notheld: Vec<RoaConfiguration>,
     unknowns: Vec<RoaPayload>,
     invalid_length: Vec<RoaConfiguration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    invalid_time: Vec<RoaConfiguration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    max_length_exposed: Vec<RoaConfiguration>,
 }
 
 impl RoaDeltaError {
@@ -58,11 +65,21 @@ impl RoaDeltaError {
         self.invalid_length.push(invalid);
     }
 
+    pub fn add_invalid_time(&mut self, invalid: RoaConfiguration) {
+        self.invalid_time.push(invalid);
+    }
+
+    pub fn add_max_length_exposed(&mut self, exposed: RoaConfiguration) {
+        self.max_length_exposed.push(exposed);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.duplicates.is_empty()
             && self.notheld.is_empty()
             && self.unknowns.is_empty()
             && self.invalid_length.is_empty()
+            && self.invalid_time.is_empty()
+            && self.max_length_exposed.is_empty()
     }
 }
 
@@ -98,6 +115,24 @@ impl fmt::Display for RoaDeltaError {
                 writeln!(f, "  {}", unk)?;
             }
         }
+        if !self.invalid_time.is_empty() {
+            writeln!(
+                f,
+                "The following ROAs have a 'not_after' time in the past, or before their 'not_before' time:"
+            )?;
+            for inv in self.invalid_time.iter() {
+                writeln!(f, "  {}", inv)?;
+            }
+        }
+        if !self.max_length_exposed.is_empty() {
+            writeln!(
+                f,
+                "The following ROAs have a max length which allows more specific announcements that are not seen (see RFC 9319):"
+            )?;
+            for exp in self.max_length_exposed.iter() {
+                writeln!(f, "  {}", exp)?;
+            }
+        }
         Ok(())
     }
 }
@@ -175,6 +210,7 @@ pub enum Error {
     ApiUnknownResource,
     ApiInvalidHandle,
     ApiInvalidSeconds,
+    ApiInvalidQuery(String),
     PostTooBig,
     PostCannotRead,
     ApiInvalidCredentials(String),
@@ -224,6 +260,9 @@ pub enum Error {
     CaRepoIssue(CaHandle, String),
     CaRepoResponseInvalid(CaHandle, String),
     CaRepoResponseWrongXml(CaHandle),
+    CaRepoMirrorMismatch(CaHandle, ServiceUri),
+    CaRepoMirrorUnknown(CaHandle, ServiceUri),
+    CaObjectUnknown(CaHandle, KeyIdentifier, ObjectName),
 
     // CA Parent Issues
     CaParentDuplicateName(CaHandle, ParentHandle),
@@ -255,6 +294,9 @@ pub enum Error {
     CaChildMustHaveResources(CaHandle, ChildHandle),
     CaChildExtraResources(CaHandle, ChildHandle),
     CaChildUnauthorized(CaHandle, ChildHandle),
+    CaChildRequestRateExceeded(CaHandle, ChildHandle),
+    CaOnboardingTokenUnknown(CaHandle),
+    CaOnboardingTokenExpired(CaHandle),
 
     //-----------------------------------------------------------------
     // RouteAuthorizations - ROAs
@@ -264,6 +306,8 @@ pub enum Error {
     CaAuthorizationInvalidMaxLength(CaHandle, RoaPayloadJsonMapKey),
     CaAuthorizationNotEntitled(CaHandle, RoaPayloadJsonMapKey),
     RoaDeltaError(CaHandle, RoaDeltaError),
+    CaRoaDraftUnknown(CaHandle, RoaDraftName),
+    CaRoaDraftNameInvalid(CaHandle, RoaDraftName),
 
     //-----------------------------------------------------------------
     // Autonomous System Provider Authorization - ASPA
@@ -280,6 +324,19 @@ pub enum Error {
     BgpSecDefinitionUnknown(CaHandle, BgpSecAsnKey),
     BgpSecDefinitionInvalidlySigned(CaHandle, BgpSecDefinition, String),
     BgpSecDefinitionNotEntitled(CaHandle, BgpSecAsnKey),
+    BgpSecRouterKeyNoPassphrase(CaHandle, Asn),
+
+    //-----------------------------------------------------------------
+    // Signed Prefix Lists
+    //-----------------------------------------------------------------
+    PrefixListAsnNotEntitled(CaHandle, Asn),
+    PrefixListAsnUnknown(CaHandle, Asn),
+    PrefixListEmpty(CaHandle, Asn),
+
+    //-----------------------------------------------------------------
+    // Ghostbusters
+    //-----------------------------------------------------------------
+    GhostbusterContactInvalid(CaHandle, String),
 
     //-----------------------------------------------------------------
     // Key Usage Issues
@@ -306,6 +363,18 @@ pub enum Error {
     TaNameReserved,
     TaAlreadyInitialized,
 
+    //-----------------------------------------------------------------
+    // Offline TA proxy issues
+    //-----------------------------------------------------------------
+    TaProxyNotInitialized,
+    TaProxyAlreadyInitialized,
+    TaProxyHasNoSigner,
+    TaProxyAlreadyHasSigner,
+    TaProxyHasOpenRequest,
+    TaProxyHasNoOpenRequest,
+    TaProxyResponseNonceMismatch(String, String),
+    TaSignerRequestReplayed(u64, u64),
+
     //-----------------------------------------------------------------
     // Resource Tagged Attestation issues
     //-----------------------------------------------------------------
@@ -344,6 +413,7 @@ impl fmt::Display for Error {
             Error::ApiUnknownResource => write!(f, "Unknown resource"),
             Error::ApiInvalidHandle => write!(f, "Invalid path argument for handle"),
             Error::ApiInvalidSeconds => write!(f, "Invalid path argument for seconds"),
+            Error::ApiInvalidQuery(e) => write!(f, "Invalid query parameter: {}", e),
             Error::PostTooBig => write!(f, "POST body exceeds configured limit"),
             Error::PostCannotRead => write!(f, "POST body cannot be read"),
             Error::ApiInvalidCredentials(e) => write!(f, "Invalid credentials: {}", e),
@@ -396,6 +466,9 @@ impl fmt::Display for Error {
             re-install of Krill you will need to send XML to all other parties again: parent(s), children, and repository", ca,        e),
             Error::CaRepoResponseInvalid(ca, e) => write!(f, "CA '{}' got invalid repository response: {}", ca, e),
             Error::CaRepoResponseWrongXml(ca) => write!(f, "CA '{}' got parent instead of repository response", ca),
+            Error::CaRepoMirrorMismatch(ca, uri) => write!(f, "Repository at '{}' cannot mirror the repository of CA '{}', it uses a different rsync base URI", uri, ca),
+            Error::CaRepoMirrorUnknown(ca, uri) => write!(f, "CA '{}' does not publish to a repository mirror at '{}'", ca, uri),
+            Error::CaObjectUnknown(ca, ki, name) => write!(f, "CA '{}' does not publish an object named '{}' under key '{}'", ca, name, ki),
 
             // CA Parent Issues
             Error::CaParentDuplicateName(ca, parent) => write!(f, "CA '{}' already has a parent named '{}'", ca, parent),
@@ -436,6 +509,9 @@ impl fmt::Display for Error {
             Error::CaChildMustHaveResources(ca, child) => write!(f, "Child '{}' for CA '{}' MUST have resources specified", child, ca),
             Error::CaChildExtraResources(ca, child) => write!(f, "Child '{}' cannot have resources not held by CA '{}'", child, ca),
             Error::CaChildUnauthorized(ca, child) => write!(f, "CA '{}' does not know id certificate for child '{}'", ca, child),
+            Error::CaChildRequestRateExceeded(ca, child) => write!(f, "CA '{}' received too many requests from child '{}', try again later", ca, child),
+            Error::CaOnboardingTokenUnknown(ca) => write!(f, "CA '{}' has no unused onboarding token matching the one given", ca),
+            Error::CaOnboardingTokenExpired(ca) => write!(f, "The onboarding token for CA '{}' has expired", ca),
 
             //-----------------------------------------------------------------
             // RouteAuthorizations - ROAs
@@ -445,6 +521,12 @@ impl fmt::Display for Error {
             Error::CaAuthorizationInvalidMaxLength(_ca, roa) => write!(f, "Invalid max length in ROA: '{}'", roa),
             Error::CaAuthorizationNotEntitled(_ca, roa) => write!(f, "Prefix in ROA '{}' not held by you", roa),
             Error::RoaDeltaError(_ca, e) => write!(f, "ROA delta rejected:\n\n'{}' ", e),
+            Error::CaRoaDraftUnknown(_ca, name) => write!(f, "Unknown ROA draft '{}'", name),
+            Error::CaRoaDraftNameInvalid(_ca, name) => write!(
+                f,
+                "Invalid ROA draft name '{}', names must have pattern: [-_A-Za-z0-9]{{1,255}}",
+                name
+            ),
 
             //-----------------------------------------------------------------
             // Autonomous System Provider Authorization - ASPAs
@@ -461,6 +543,19 @@ impl fmt::Display for Error {
             Error::BgpSecDefinitionUnknown(_ca, key) => write!(f, "Cannot remove BGPSec CSR for unknown combination of ASN '{}' and key '{}'", key.asn(), key.key_identifier()),
             Error::BgpSecDefinitionInvalidlySigned(_ca, def, msg) => write!(f, "Invalidly signed BGPSec CSR remove BGPSec CSR for ASN '{}' and key '{}', error: {}", def.asn(), def.csr().public_key().key_identifier(), msg),
             Error::BgpSecDefinitionNotEntitled(_ca, key) => write!(f, "AS '{}' is not held by you", key.asn()),
+            Error::BgpSecRouterKeyNoPassphrase(_ca, asn) => write!(f, "A passphrase is needed to export the BGPSec router key for AS '{}'", asn),
+
+            //-----------------------------------------------------------------
+            // Signed Prefix Lists
+            //-----------------------------------------------------------------
+            Error::PrefixListAsnNotEntitled(_ca, asn) => write!(f, "AS '{}' is not held by you", asn),
+            Error::PrefixListAsnUnknown(_ca, asn) => write!(f, "No current prefix list exists for AS '{}'", asn),
+            Error::PrefixListEmpty(_ca, asn) => write!(f, "The prefix list for AS '{}' must contain at least one prefix", asn),
+
+            //-----------------------------------------------------------------
+            // Ghostbusters
+            //-----------------------------------------------------------------
+            Error::GhostbusterContactInvalid(_ca, msg) => write!(f, "Invalid Ghostbuster contact: {}", msg),
 
 
             //-----------------------------------------------------------------
@@ -489,6 +584,26 @@ impl fmt::Display for Error {
             Error::TaNameReserved => write!(f, "Name reserved for embedded Trust Anchor"),
             Error::TaAlreadyInitialized => write!(f, "TrustAnchor was already initialized"),
 
+            //-----------------------------------------------------------------
+            // Offline TA proxy issues
+            //-----------------------------------------------------------------
+            Error::TaProxyNotInitialized => write!(f, "Trust Anchor proxy is not initialized"),
+            Error::TaProxyAlreadyInitialized => write!(f, "Trust Anchor proxy was already initialized"),
+            Error::TaProxyHasNoSigner => write!(f, "Trust Anchor proxy has no associated signer"),
+            Error::TaProxyAlreadyHasSigner => write!(f, "Trust Anchor proxy already has an associated signer"),
+            Error::TaProxyHasOpenRequest => write!(f, "Trust Anchor proxy already has an open request for the signer"),
+            Error::TaProxyHasNoOpenRequest => write!(f, "Trust Anchor proxy has no open request for the signer"),
+            Error::TaProxyResponseNonceMismatch(expected, found) => write!(
+                f,
+                "Trust Anchor signer response is for request '{}', expected '{}'",
+                found, expected
+            ),
+            Error::TaSignerRequestReplayed(sequence, last) => write!(
+                f,
+                "Trust Anchor signer refuses request with sequence number '{}', it already processed request '{}'",
+                sequence, last
+            ),
+
             //-----------------------------------------------------------------
             // Resource Tagged Attestation issues
             //-----------------------------------------------------------------
@@ -630,6 +745,9 @@ impl Error {
             | Error::CaUnknown(_)
             | Error::CaChildUnknown(_, _)
             | Error::CaParentUnknown(_, _)
+            | Error::CaRoaDraftUnknown(_, _)
+            | Error::CaObjectUnknown(_, _, _)
+            | Error::CaRepoMirrorUnknown(_, _)
             | Error::ApiUnknownResource => StatusCode::NOT_FOUND,
 
             Error::ApiInvalidCredentials(_)
@@ -637,7 +755,10 @@ impl Error {
             | Error::ApiAuthTransientError(_)
             | Error::ApiAuthSessionExpired(_)
             | Error::ApiLoginError(_) => StatusCode::UNAUTHORIZED,
-            Error::ApiInsufficientRights(_) => StatusCode::FORBIDDEN,
+            Error::ApiInsufficientRights(_)
+            | Error::CaOnboardingTokenUnknown(_)
+            | Error::CaOnboardingTokenExpired(_) => StatusCode::FORBIDDEN,
+            Error::CaChildRequestRateExceeded(_, _) => StatusCode::TOO_MANY_REQUESTS,
 
             _ => StatusCode::BAD_REQUEST,
         }
@@ -694,6 +815,8 @@ impl Error {
 
             Error::ApiInvalidSeconds => ErrorResponse::new("api-invalid-path-seconds", &self),
 
+            Error::ApiInvalidQuery(_) => ErrorResponse::new("api-invalid-query", &self),
+
             Error::PostTooBig => ErrorResponse::new("api-post-body-exceeds-limit", &self),
 
             Error::PostCannotRead => ErrorResponse::new("api-post-body-cannot-read", &self),
@@ -765,6 +888,19 @@ impl Error {
 
             Error::CaRepoResponseWrongXml(ca) => ErrorResponse::new("ca-repo-response-wrong-xml", &self).with_ca(ca),
 
+            Error::CaRepoMirrorMismatch(ca, uri) => ErrorResponse::new("ca-repo-mirror-mismatch", &self)
+                .with_ca(ca)
+                .with_uri(uri),
+
+            Error::CaRepoMirrorUnknown(ca, uri) => ErrorResponse::new("ca-repo-mirror-unknown", &self)
+                .with_ca(ca)
+                .with_uri(uri),
+
+            Error::CaObjectUnknown(ca, ki, name) => ErrorResponse::new("ca-object-unknown", &self)
+                .with_ca(ca)
+                .with_key_identifier(ki)
+                .with_object_name(name),
+
             Error::CaParentDuplicateName(ca, parent) => ErrorResponse::new("ca-parent-duplicate", &self)
                 .with_ca(ca)
                 .with_parent(parent),
@@ -827,6 +963,11 @@ impl Error {
             Error::CaChildUnauthorized(ca, child) => ErrorResponse::new("ca-child-unauthorized", &self)
                 .with_ca(ca)
                 .with_child(child),
+            Error::CaChildRequestRateExceeded(ca, child) => ErrorResponse::new("ca-child-request-rate-exceeded", &self)
+                .with_ca(ca)
+                .with_child(child),
+            Error::CaOnboardingTokenUnknown(ca) => ErrorResponse::new("ca-onboarding-token-unknown", &self).with_ca(ca),
+            Error::CaOnboardingTokenExpired(ca) => ErrorResponse::new("ca-onboarding-token-expired", &self).with_ca(ca),
 
             // RouteAuthorizations
             Error::CaAuthorizationUnknown(ca, auth) => {
@@ -848,6 +989,14 @@ impl Error {
                 .with_ca(ca)
                 .with_roa_delta_error(roa_delta_error),
 
+            Error::CaRoaDraftUnknown(ca, name) => ErrorResponse::new("ca-roa-draft-unknown", &self)
+                .with_ca(ca)
+                .with_roa_draft(name),
+
+            Error::CaRoaDraftNameInvalid(ca, name) => ErrorResponse::new("ca-roa-draft-name-invalid", &self)
+                .with_ca(ca)
+                .with_roa_draft(name),
+
             //-----------------------------------------------------------------
             // Autonomous System Provider Authorization - ASPA
             //-----------------------------------------------------------------
@@ -883,6 +1032,29 @@ impl Error {
             Error::BgpSecDefinitionNotEntitled(ca, key) => ErrorResponse::new("ca-bgpsec-not-entitled", &self)
                 .with_ca(ca)
                 .with_asn(key.asn()),
+            Error::BgpSecRouterKeyNoPassphrase(ca, asn) => ErrorResponse::new("ca-bgpsec-router-key-no-passphrase", &self)
+                .with_ca(ca)
+                .with_asn(*asn),
+
+            //-----------------------------------------------------------------
+            // Signed Prefix Lists
+            //-----------------------------------------------------------------
+            Error::PrefixListAsnNotEntitled(ca, asn) => ErrorResponse::new("ca-prefix-list-not-entitled", &self)
+                .with_ca(ca)
+                .with_asn(*asn),
+            Error::PrefixListAsnUnknown(ca, asn) => ErrorResponse::new("ca-prefix-list-unknown-as", &self)
+                .with_ca(ca)
+                .with_asn(*asn),
+            Error::PrefixListEmpty(ca, asn) => ErrorResponse::new("ca-prefix-list-empty", &self)
+                .with_ca(ca)
+                .with_asn(*asn),
+
+            //-----------------------------------------------------------------
+            // Ghostbusters
+            //-----------------------------------------------------------------
+            Error::GhostbusterContactInvalid(ca, msg) => {
+                ErrorResponse::new("ca-ghostbuster-invalid", &self).with_ca(ca).with_cause(msg)
+            }
 
             //-----------------------------------------------------------------
             // Key Usage Issues (key-*)
@@ -909,6 +1081,18 @@ impl Error {
             Error::TaNameReserved => ErrorResponse::new("ta-name-reserved", &self),
             Error::TaAlreadyInitialized => ErrorResponse::new("ta-initialized", &self),
 
+            //-----------------------------------------------------------------
+            // Offline TA proxy issues
+            //-----------------------------------------------------------------
+            Error::TaProxyNotInitialized => ErrorResponse::new("ta-proxy-not-initialized", &self),
+            Error::TaProxyAlreadyInitialized => ErrorResponse::new("ta-proxy-initialized", &self),
+            Error::TaProxyHasNoSigner => ErrorResponse::new("ta-proxy-no-signer", &self),
+            Error::TaProxyAlreadyHasSigner => ErrorResponse::new("ta-proxy-has-signer", &self),
+            Error::TaProxyHasOpenRequest => ErrorResponse::new("ta-proxy-open-request", &self),
+            Error::TaProxyHasNoOpenRequest => ErrorResponse::new("ta-proxy-no-open-request", &self),
+            Error::TaProxyResponseNonceMismatch(_, _) => ErrorResponse::new("ta-proxy-response-nonce-mismatch", &self),
+            Error::TaSignerRequestReplayed(_, _) => ErrorResponse::new("ta-signer-request-replayed", &self),
+
             //-----------------------------------------------------------------
             // Resource Tagged Attestation issues
             //-----------------------------------------------------------------
@@ -966,6 +1150,7 @@ mod tests {
 
     use super::*;
     use crate::test::roa_payload;
+    use crate::test::service_uri;
     use crate::test::test_id_certificate;
 
     fn verify(expected_json: &str, e: Error) {
@@ -1114,6 +1299,14 @@ mod tests {
             include_str!("../../test-resources/errors/ca-repo-response-wrong-xml.json"),
             Error::CaRepoResponseWrongXml(ca.clone()),
         );
+        verify(
+            include_str!("../../test-resources/errors/ca-repo-mirror-mismatch.json"),
+            Error::CaRepoMirrorMismatch(ca.clone(), service_uri("https://mirror.example.com/rfc8181/ca/")),
+        );
+        verify(
+            include_str!("../../test-resources/errors/ca-repo-mirror-unknown.json"),
+            Error::CaRepoMirrorUnknown(ca.clone(), service_uri("https://mirror.example.com/rfc8181/ca/")),
+        );
 
         verify(
             include_str!("../../test-resources/errors/ca-parent-duplicate.json"),
@@ -1163,7 +1356,19 @@ mod tests {
         );
         verify(
             include_str!("../../test-resources/errors/ca-child-unauthorized.json"),
-            Error::CaChildUnauthorized(ca.clone(), child),
+            Error::CaChildUnauthorized(ca.clone(), child.clone()),
+        );
+        verify(
+            include_str!("../../test-resources/errors/ca-child-request-rate-exceeded.json"),
+            Error::CaChildRequestRateExceeded(ca.clone(), child),
+        );
+        verify(
+            include_str!("../../test-resources/errors/ca-onboarding-token-unknown.json"),
+            Error::CaOnboardingTokenUnknown(ca.clone()),
+        );
+        verify(
+            include_str!("../../test-resources/errors/ca-onboarding-token-expired.json"),
+            Error::CaOnboardingTokenExpired(ca.clone()),
         );
 
         verify(
@@ -1181,7 +1386,7 @@ mod tests {
         );
         verify(
             include_str!("../../test-resources/errors/ca-roa-not-entitled.json"),
-            Error::CaAuthorizationNotEntitled(ca, auth),
+            Error::CaAuthorizationNotEntitled(ca.clone(), auth),
         );
 
         verify(
@@ -1209,6 +1414,10 @@ mod tests {
             include_str!("../../test-resources/errors/key-no-match.json"),
             Error::KeyUseNoMatch(ki),
         );
+        verify(
+            include_str!("../../test-resources/errors/ca-object-unknown.json"),
+            Error::CaObjectUnknown(ca, ki, ObjectName::from("AS64496.roa")),
+        );
 
         verify(
             include_str!("../../test-resources/errors/rc-unknown.json"),
@@ -1231,6 +1440,10 @@ mod tests {
             include_str!("../../test-resources/errors/ta-initialized.json"),
             Error::TaAlreadyInitialized,
         );
+        verify(
+            include_str!("../../test-resources/errors/ta-proxy-not-initialized.json"),
+            Error::TaProxyNotInitialized,
+        );
 
         verify(
             include_str!("../../test-resources/errors/general-error.json"),
@@ -107,6 +107,30 @@ pub async fn get_text(uri: &str, token: Option<&Token>) -> Result<String, Error>
     text_response(uri, res).await
 }
 
+/// Performs a get request and expects a binary response, e.g. a DER
+/// encoded object.
+pub async fn get_binary(uri: &str, token: Option<&Token>) -> Result<Bytes, Error> {
+    if env::var(KRILL_CLI_API_ENV).is_ok() {
+        report_get_and_exit(uri, token);
+    }
+
+    let headers = headers(uri, None, token)?;
+    let res = client(uri)?
+        .get(uri)
+        .headers(headers)
+        .send()
+        .await
+        .map_err(|e| Error::execute(uri, e))?;
+
+    match res.status() {
+        StatusCode::OK => res
+            .bytes()
+            .await
+            .map_err(|e| Error::response(uri, format!("cannot get body: {}", e))),
+        _ => Err(Error::from_res(uri, res).await),
+    }
+}
+
 /// Checks that there is a 200 OK response at the given URI. Discards the
 /// response body.
 pub async fn get_ok(uri: &str, token: Option<&Token>) -> Result<(), Error> {
@@ -7,8 +7,10 @@ pub const KRILL_VERSION_PATCH: &str = env!("CARGO_PKG_VERSION_PATCH");
 pub const KRILL_SERVER_APP: &str = "Krill";
 pub const KRILL_UP_APP: &str = "Krill Upgrade Helper";
 pub const KRILL_CLIENT_APP: &str = "Krill Client";
+pub const KRILL_TA_APP: &str = "Krill Trust Anchor Signer";
 
 pub const KRILL_DEFAULT_CONFIG_FILE: &str = "./defaults/krill.conf";
+pub const KRILL_TA_DEFAULT_CONFIG_FILE: &str = "./defaults/krillta.conf";
 
 const KRILL_ENV_TEST: &str = "KRILL_TEST";
 const KRILL_ENV_TEST_ANN: &str = "KRILL_TEST_ANN";
@@ -55,6 +57,10 @@ pub const REPOSITORY_RSYNC_DIR: &str = "rsync";
 
 pub const STATUS_DIR: &str = "status";
 
+pub const TA_PROXY_DIR: &str = "ta_proxy";
+pub const TA_PROXY_PUBLISHER: &str = "ta";
+pub const TA_OBJECTS_EXPIRY_WARNING_WEEKS: i64 = 4;
+
 pub const KRILL_CLI_SERVER_ARG: &str = "server";
 pub const KRILL_CLI_SERVER_ENV: &str = "KRILL_CLI_SERVER";
 pub const KRILL_CLI_SERVER_DFLT: &str = "https://localhost:3000/";
@@ -77,6 +83,8 @@ pub const SCHEDULER_USE_JITTER_CAS_THRESHOLD: usize = 50;
 pub const SCHEDULER_USE_JITTER_CAS_PARENTS_THRESHOLD: usize = 5;
 pub const SCHEDULER_INTERVAL_REPUBLISH_MINS: i64 = 5;
 pub const SCHEDULER_INTERVAL_RENEW_MINS: i64 = 60;
+pub const SCHEDULER_INTERVAL_TA_PROXY_CHECK_HOURS: i64 = 24;
+pub const SCHEDULER_INTERVAL_VALIDATE_REPO_HOURS: i64 = 1;
 
 pub const KRILL_HTTPS_ROOT_CERTS_ENV: &str = "KRILL_HTTPS_ROOT_CERTS";
 
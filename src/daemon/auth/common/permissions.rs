@@ -72,6 +72,10 @@ iterable_enum! {
         ASPAS_ANALYSIS,
         BGPSEC_READ,
         BGPSEC_UPDATE,
+        PREFIX_LISTS_READ,
+        PREFIX_LISTS_UPDATE,
+        RSC_SIGN,
+        GEOFEED_SIGN,
         RTA_LIST,
         RTA_READ,
         RTA_UPDATE
@@ -6,7 +6,7 @@ use chrono::Duration;
 use rpki::{
     ca::{
         idexchange,
-        idexchange::{CaHandle, ChildHandle, ParentHandle},
+        idexchange::{CaHandle, ChildHandle, ParentHandle, ServiceUri},
         provisioning,
         provisioning::{
             IssuanceRequest, IssuanceResponse, ProvisioningCms, RequestResourceLimit, ResourceClassEntitlements,
@@ -15,10 +15,10 @@ use rpki::{
     },
     crypto::{KeyIdentifier, PublicKey},
     repository::{
-        cert::{Cert, KeyUsage, Overclaim, TbsCert},
+        cert::Cert,
         resources::ResourceSet,
         rta::RtaBuilder,
-        x509::{Serial, Time, Validity},
+        x509::{Time, Validity},
     },
     uri,
 };
@@ -26,13 +26,16 @@ use rpki::{
 use crate::{
     commons::{
         api::{
-            AspaCustomer, AspaDefinitionList, AspaDefinitionUpdates, AspaProvidersUpdate, BgpSecAsnKey,
-            BgpSecCsrInfoList, BgpSecDefinitionUpdates, CertAuthInfo, ConfiguredRoa, IdCertInfo, IssuedCertificate,
-            ObjectName, ParentCaContact, ReceivedCert, RepositoryContact, Revocation, RoaConfiguration,
-            RoaConfigurationUpdates, RtaList, RtaName, RtaPrepResponse, StorableCaCommand, TaCertDetails,
-            TrustAnchorLocator,
+            AsRelationships, AspaCustomer, AspaDefinition, AspaDefinitionList, AspaDefinitionUpdates, AspaImportReport,
+            AspaProvidersUpdate, BgpSecAsnKey, BgpSecCsrInfoList, BgpSecDefinitionUpdates, CertAuthInfo,
+            ChildResourcesLost, ConfiguredRoa, GhostbusterContact, IdCertInfo, IssuedCertificate, ObjectName,
+            OnboardingTokenId, OnboardingTokenInfo, OnboardingTokenList, ParentCaContact, PrefixListDefinition,
+            PrefixListDefinitionList, PrefixListDefinitionUpdates, ReceivedCert, RepositoryContact, ResourceShrink,
+            Revocation, RoaAs0Policy, RoaAs0Status, RoaConfiguration, RoaConfigurationUpdates, RoaDraft, RoaDraftList,
+            RoaDraftName, RoaPayload, RpslImportReport, RpslRouteObjects, RtaList, RtaName, RtaPrepResponse,
+            StorableCaCommand, TaCertDetails, TimedRoaChanges, Timestamp,
         },
-        crypto::{CsrInfo, KrillSigner},
+        crypto::{CsrInfo, KrillSigner, SignSupport},
         error::{Error, RoaDeltaError},
         eventsourcing::{Aggregate, StoredEvent},
         KrillResult,
@@ -41,9 +44,10 @@ use crate::{
     daemon::{
         ca::{
             events::ChildCertificateUpdates, ta_handle, AspaDefinitions, BgpSecDefinitions, CaEvt, CaEvtDet,
-            ChildDetails, Cmd, CmdDet, DropReason, Ini, PreparedRta, ResourceClass, ResourceTaggedAttestation,
-            Rfc8183Id, RoaPayloadJsonMapKey, Routes, RtaContentRequest, RtaPrepareRequest, Rtas, SignedRta,
-            StoredBgpSecCsr,
+            CertifiedKey, ChildDetails, Cmd, CmdDet, DropReason, Geofeed, GeofeedSignRequest, Ini,
+            PrefixListDefinitions, PreparedRta, ResourceClass, ResourceSignedChecklist, ResourceTaggedAttestation,
+            Rfc8183Id, RoaDrafts, RoaPayloadJsonMapKey, Routes, RscRequest, RtaContentRequest, RtaPrepareRequest, Rtas,
+            ScheduledRoas, SignedGeofeed, SignedRta, StoredBgpSecCsr, TakInfo, TrustAnchorKey, TrustAnchorKeys,
         },
         config::{Config, IssuanceTimingConfig},
     },
@@ -63,14 +67,38 @@ pub struct CertAuth {
     id: Rfc8183Id, // Used for RFC 6492 (up-down) and RFC 8181 (publication)
 
     repository: Option<RepositoryContact>,
+
+    // Repositories which get the same objects as the repository above.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    repository_mirrors: Vec<RepositoryContact>,
+
     parents: HashMap<ParentHandle, ParentCaContact>,
 
+    // Limits on the resources requested from parents, if any.
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    parent_resource_limits: HashMap<ParentHandle, ResourceSet>,
+
     next_class_name: u32,
     resources: HashMap<ResourceClassName, ResourceClass>,
 
+    // Resources removed by parents, kept until the operator clears them.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    resource_shrinks: Vec<ResourceShrink>,
+
     children: HashMap<ChildHandle, ChildDetails>,
+
+    // Unused tokens which let new children add themselves.
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    onboarding_tokens: HashMap<OnboardingTokenId, OnboardingTokenInfo>,
+
     routes: Routes,
 
+    #[serde(skip_serializing_if = "ScheduledRoas::is_empty", default)]
+    scheduled_roas: ScheduledRoas,
+
+    #[serde(skip_serializing_if = "RoaDrafts::is_empty", default)]
+    roa_drafts: RoaDrafts,
+
     #[serde(skip_serializing_if = "Rtas::is_empty", default)]
     rtas: Rtas,
 
@@ -79,6 +107,22 @@ pub struct CertAuth {
 
     #[serde(skip_serializing_if = "BgpSecDefinitions::is_empty", default)]
     bgpsec_defs: BgpSecDefinitions,
+
+    #[serde(skip_serializing_if = "PrefixListDefinitions::is_empty", default)]
+    prefix_lists: PrefixListDefinitions,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    ghostbuster: Option<GhostbusterContact>,
+
+    // The certificate and TAL for the new key of a trust anchor,
+    // while it is rolling its key.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    ta_next_key: Option<TaCertDetails>,
+
+    // The certificate and TAL for the old key of a trust anchor, after
+    // its new key was activated and until the key roll is finished.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    ta_old_key: Option<TaCertDetails>,
 }
 
 impl Aggregate for CertAuth {
@@ -93,17 +137,27 @@ impl Aggregate for CertAuth {
         let id = details.unpack();
 
         let repository = None;
+        let repository_mirrors = vec![];
         let parents = HashMap::new();
+        let parent_resource_limits = HashMap::new();
 
         let next_class_name = 0;
         let resources = HashMap::new();
+        let resource_shrinks = vec![];
 
         let children = HashMap::new();
+        let onboarding_tokens = HashMap::new();
 
         let routes = Routes::default();
+        let scheduled_roas = ScheduledRoas::default();
+        let roa_drafts = RoaDrafts::default();
         let rtas = Rtas::default();
         let aspas = AspaDefinitions::default();
         let bgpsec_defs = BgpSecDefinitions::default();
+        let prefix_lists = PrefixListDefinitions::default();
+        let ghostbuster = None;
+        let ta_next_key = None;
+        let ta_old_key = None;
 
         Ok(CertAuth {
             handle,
@@ -112,17 +166,27 @@ impl Aggregate for CertAuth {
             id,
 
             repository,
+            repository_mirrors,
             parents,
+            parent_resource_limits,
 
             next_class_name,
             resources,
+            resource_shrinks,
 
             children,
+            onboarding_tokens,
 
             routes,
+            scheduled_roas,
+            roa_drafts,
             rtas,
             aspas,
             bgpsec_defs,
+            prefix_lists,
+            ghostbuster,
+            ta_next_key,
+            ta_old_key,
         })
     }
 
@@ -144,6 +208,24 @@ impl Aggregate for CertAuth {
                 self.next_class_name += 1;
                 self.resources.insert(rcn.clone(), ResourceClass::for_ta(rcn, key_id));
             }
+            CaEvtDet::TrustAnchorNextKeyMade { ta_cert_details } => {
+                self.ta_next_key = Some(ta_cert_details);
+            }
+            CaEvtDet::TrustAnchorNextKeyActivated { .. } => {
+                if let Some(ta_cert_details) = self.ta_next_key.take() {
+                    let old = self
+                        .parents
+                        .insert(ta_handle().into_converted(), ParentCaContact::Ta(ta_cert_details));
+                    if let Some(ParentCaContact::Ta(old_details)) = old {
+                        self.ta_old_key = Some(old_details);
+                    }
+                }
+            }
+            CaEvtDet::TakObjectsUpdated { .. } => {
+                // no action needed, TAK objects are only tracked for publication
+                // by the `CaObjectsStore`, they are signed again whenever the
+                // keys of the trust anchor change.
+            }
 
             //-----------------------------------------------------------------------
             // Being a parent
@@ -223,6 +305,12 @@ impl Aggregate for CertAuth {
             CaEvtDet::ChildSuspended { child } => self.children.get_mut(&child).unwrap().suspend(),
 
             CaEvtDet::ChildUnsuspended { child } => self.children.get_mut(&child).unwrap().unsuspend(),
+            CaEvtDet::OnboardingTokenAdded { token } => {
+                self.onboarding_tokens.insert(token.id().clone(), token);
+            }
+            CaEvtDet::OnboardingTokenRemoved { id } | CaEvtDet::OnboardingTokenRedeemed { id, .. } => {
+                self.onboarding_tokens.remove(&id);
+            }
 
             //-----------------------------------------------------------------------
             // Being a child
@@ -238,8 +326,17 @@ impl Aggregate for CertAuth {
             }
             CaEvtDet::ParentRemoved { parent } => {
                 self.parents.remove(&parent);
+                self.parent_resource_limits.remove(&parent);
                 self.resources.retain(|_, rc| rc.parent_handle() != &parent);
             }
+            CaEvtDet::ParentResourceLimitUpdated { parent, limit } => match limit {
+                Some(limit) => {
+                    self.parent_resource_limits.insert(parent, limit);
+                }
+                None => {
+                    self.parent_resource_limits.remove(&parent);
+                }
+            },
 
             CaEvtDet::ResourceClassAdded {
                 resource_class_name,
@@ -283,6 +380,12 @@ impl Aggregate for CertAuth {
                     .unwrap()
                     .received_cert(ki, rcvd_cert);
             }
+            CaEvtDet::ResourcesShrunk { shrink } => {
+                self.resource_shrinks.push(shrink);
+            }
+            CaEvtDet::ResourceShrinksCleared => {
+                self.resource_shrinks.clear();
+            }
 
             //-----------------------------------------------------------------------
             // Key Life Cycle
@@ -325,6 +428,7 @@ impl Aggregate for CertAuth {
             }
             CaEvtDet::KeyRollFinished { resource_class_name } => {
                 self.resources.get_mut(&resource_class_name).unwrap().old_key_removed();
+                self.ta_old_key = None;
             }
             CaEvtDet::UnexpectedKeyFound { .. } => {
                 // no action needed, this is marked to flag that a key may be removed on the
@@ -337,9 +441,20 @@ impl Aggregate for CertAuth {
             //-----------------------------------------------------------------------
             CaEvtDet::RouteAuthorizationAdded { auth } => self.routes.add(auth),
             CaEvtDet::RouteAuthorizationComment { auth, comment } => self.routes.comment(&auth, comment),
+            CaEvtDet::RouteAuthorizationMetadata { auth, metadata } => self.routes.metadata(&auth, metadata),
             CaEvtDet::RouteAuthorizationRemoved { auth } => {
                 self.routes.remove(&auth);
             }
+            CaEvtDet::RouteAuthorizationTimeBounds {
+                auth,
+                not_before,
+                not_after,
+            } => self.routes.time_bounds(&auth, not_before, not_after),
+            CaEvtDet::RouteAuthorizationScheduled { configuration } => self.scheduled_roas.schedule(configuration),
+            CaEvtDet::RouteAuthorizationUnscheduled { auth } => {
+                self.scheduled_roas.unschedule(&auth);
+            }
+            CaEvtDet::RouteAuthorizationActivationRefused { auth } => self.scheduled_roas.refuse(&auth),
 
             CaEvtDet::RoasUpdated {
                 resource_class_name,
@@ -350,6 +465,12 @@ impl Aggregate for CertAuth {
                 .unwrap()
                 .roas_updated(updates),
 
+            CaEvtDet::RoaDraftUpdated { name, updates } => self.roa_drafts.set(name, updates),
+            CaEvtDet::RoaDraftRemoved { name } => {
+                self.roa_drafts.remove(&name);
+            }
+            CaEvtDet::RouteAuthorizationAs0Policy { enabled } => self.routes.set_as0_unrouted(enabled),
+
             //-----------------------------------------------------------------------
             // Autonomous System Provider Authorization
             //-----------------------------------------------------------------------
@@ -381,6 +502,34 @@ impl Aggregate for CertAuth {
                 rc.bgpsec_certificates_updated(updates);
             }
 
+            //-----------------------------------------------------------------------
+            // Signed Prefix Lists
+            //-----------------------------------------------------------------------
+            CaEvtDet::PrefixListDefinitionAdded { definition } => self.prefix_lists.add_or_replace(definition),
+            CaEvtDet::PrefixListDefinitionUpdated { definition } => self.prefix_lists.add_or_replace(definition),
+            CaEvtDet::PrefixListDefinitionRemoved { asn } => self.prefix_lists.remove(asn),
+            CaEvtDet::PrefixListObjectsUpdated {
+                resource_class_name,
+                updates,
+            } => self
+                .resources
+                .get_mut(&resource_class_name)
+                .unwrap()
+                .prefix_list_objects_updated(updates),
+
+            //-----------------------------------------------------------------------
+            // Ghostbusters
+            //-----------------------------------------------------------------------
+            CaEvtDet::GhostbusterContactUpdated { contact } => self.ghostbuster = contact,
+            CaEvtDet::GhostbusterObjectUpdated {
+                resource_class_name,
+                ghostbuster,
+            } => self
+                .resources
+                .get_mut(&resource_class_name)
+                .unwrap()
+                .ghostbuster_updated(ghostbuster),
+
             //-----------------------------------------------------------------------
             // Publication
             //-----------------------------------------------------------------------
@@ -392,6 +541,10 @@ impl Aggregate for CertAuth {
                 }
                 self.repository = Some(contact);
             }
+            CaEvtDet::RepoMirrorAdded { contact } => self.repository_mirrors.push(contact),
+            CaEvtDet::RepoMirrorRemoved { service_uri } => self
+                .repository_mirrors
+                .retain(|mirror| mirror.server_info().service_uri() != &service_uri),
 
             //-----------------------------------------------------------------------
             // Resource Tagged Attestations
@@ -428,12 +581,16 @@ impl Aggregate for CertAuth {
             CmdDet::ChildRemove(child) => self.child_remove(&child),
             CmdDet::ChildSuspendInactive(child) => self.child_suspend_inactive(&child),
             CmdDet::ChildUnsuspend(child) => self.child_unsuspend(&child),
+            CmdDet::OnboardingTokenAdd(token) => self.onboarding_token_add(token),
+            CmdDet::OnboardingTokenRemove(id) => self.onboarding_token_remove(id),
+            CmdDet::OnboardingTokenRedeem(id, child, id_cert) => self.onboarding_token_redeem(id, child, id_cert),
 
             // being a child
             CmdDet::GenerateNewIdKey(signer) => self.generate_new_id_key(signer),
             CmdDet::AddParent(parent, info) => self.add_parent(parent, info),
             CmdDet::UpdateParentContact(parent, info) => self.update_parent(parent, info),
             CmdDet::RemoveParent(parent) => self.remove_parent(parent),
+            CmdDet::UpdateParentResourceLimit(parent, limit) => self.update_parent_resource_limit(parent, limit),
 
             CmdDet::UpdateEntitlements(parent, entitlements, signer) => {
                 self.update_entitlements(parent, entitlements, signer)
@@ -442,15 +599,17 @@ impl Aggregate for CertAuth {
                 self.update_received_cert(class_name, rcvd_cert, &config, signer)
             }
             CmdDet::DropResourceClass(rcn, reason, signer) => self.drop_resource_class(rcn, reason, signer),
+            CmdDet::ResourceShrinksClear => self.resource_shrinks_clear(),
 
             // Key rolls
             CmdDet::KeyRollInitiate(duration, signer) => self.keyroll_initiate(duration, signer),
             CmdDet::KeyRollActivate(duration, config, signer) => self.keyroll_activate(duration, config, signer),
             CmdDet::KeyRollFinish(rcn, response) => self.keyroll_finish(rcn, response),
+            CmdDet::TaKeyRollFinish(signer) => self.ta_keyroll_finish(&signer),
 
             // Route Authorizations
-            CmdDet::RouteAuthorizationsUpdate(updates, config, signer) => {
-                self.route_authorizations_update(updates, &config, signer)
+            CmdDet::RouteAuthorizationsUpdate(updates, max_length_exposed, config, signer) => {
+                self.route_authorizations_update(updates, &max_length_exposed, &config, signer)
             }
             CmdDet::RouteAuthorizationsRenew(config, signer) => {
                 self.route_authorizations_renew(false, &config, &signer)
@@ -458,6 +617,17 @@ impl Aggregate for CertAuth {
             CmdDet::RouteAuthorizationsForceRenew(config, signer) => {
                 self.route_authorizations_renew(true, &config, &signer)
             }
+            CmdDet::RouteAuthorizationsDraftUpdate(name, updates, max_length_exposed) => {
+                self.roa_draft_update(name, updates, &max_length_exposed)
+            }
+            CmdDet::RouteAuthorizationsDraftRemove(name) => self.roa_draft_remove(name),
+            CmdDet::RouteAuthorizationsActivationRefused(refused) => self.roa_activation_refused(refused),
+            CmdDet::RouteAuthorizationsDraftCommit(name, max_length_exposed, config, signer) => {
+                self.roa_draft_commit(name, &max_length_exposed, &config, signer)
+            }
+            CmdDet::RouteAuthorizationsAs0Policy(policy, config, signer) => {
+                self.route_authorizations_as0_policy(policy, &config, &signer)
+            }
 
             // ASPA
             CmdDet::AspasUpdate(updates, config, signer) => self.aspas_definitions_update(updates, &config, &signer),
@@ -472,8 +642,18 @@ impl Aggregate for CertAuth {
             }
             CmdDet::BgpSecRenew(config, signer) => self.bgpsec_renew(&config, &signer),
 
+            // Signed Prefix Lists
+            CmdDet::PrefixListsUpdate(updates, config, signer) => self.prefix_lists_update(updates, &config, &signer),
+            CmdDet::PrefixListsRenew(config, signer) => self.prefix_lists_renew(&config, &signer),
+
+            // Ghostbusters
+            CmdDet::GhostbusterUpdate(contact, config, signer) => self.ghostbuster_update(contact, &config, &signer),
+            CmdDet::GhostbusterRenew(config, signer) => self.ghostbuster_renew(&config, &signer),
+
             // Republish
             CmdDet::RepoUpdate(contact, signer) => self.update_repo(contact, &signer),
+            CmdDet::RepoMirrorAdd(contact) => self.add_repo_mirror(contact),
+            CmdDet::RepoMirrorRemove(service_uri) => self.remove_repo_mirror(service_uri),
 
             // Resource Tagged Attestations
             CmdDet::RtaMultiPrepare(name, request, signer) => self.rta_multi_prep(name, request, signer.deref()),
@@ -552,6 +732,10 @@ impl CertAuth {
             for rc in self.resources.values() {
                 roa_infos.append(&mut rc.matching_roa_infos(&roa_configuration));
             }
+            let roa_infos = roa_infos
+                .into_iter()
+                .map(|info| info.with_metadata(&self.routes))
+                .collect();
             configured_roas.push(ConfiguredRoa::new(roa_configuration, roa_infos))
         }
 
@@ -597,6 +781,10 @@ impl CertAuth {
     pub fn repository_contact(&self) -> KrillResult<&RepositoryContact> {
         self.repository.as_ref().ok_or(Error::RepoNotSet)
     }
+
+    pub fn repository_mirrors(&self) -> &Vec<RepositoryContact> {
+        &self.repository_mirrors
+    }
 }
 
 /// # Being a Trust Anchor
@@ -612,59 +800,218 @@ impl CertAuth {
             return Err(Error::custom("Cannot turn CA with resources into TA"));
         }
 
+        let key = signer.create_key()?;
+        let ta_cert_details = self.ta_cert_details_for_key(&key, uris, rsync_uri, &signer)?;
+
+        info!("Created Trust Anchor");
+
+        Ok(vec![StoredEvent::new(
+            &self.handle,
+            self.version,
+            CaEvtDet::TrustAnchorMade { ta_cert_details },
+        )])
+    }
+
+    /// Returns the certificate and TAL for the current key of this TA.
+    pub fn ta_cert_details(&self) -> KrillResult<&TaCertDetails> {
+        match self.parents.get(&ta_handle().into_converted()) {
+            Some(ParentCaContact::Ta(details)) => Ok(details),
+            _ => Err(Error::custom("CA is not a Trust Anchor")),
+        }
+    }
+
+    /// Returns the certificate and TAL for the new key of this TA, if it
+    /// is rolling its key.
+    pub fn ta_next_key(&self) -> Option<&TaCertDetails> {
+        self.ta_next_key.as_ref()
+    }
+
+    /// Returns the certificate and TAL for the old key of this TA, if its
+    /// new key was activated but the key roll is not yet finished.
+    pub fn ta_old_key(&self) -> Option<&TaCertDetails> {
+        self.ta_old_key.as_ref()
+    }
+
+    /// Creates a self-signed certificate with all resources for the key,
+    /// and a TAL for it.
+    fn ta_cert_details_for_key(
+        &self,
+        key: &KeyIdentifier,
+        uris: Vec<uri::Https>,
+        rsync_uri: uri::Rsync,
+        signer: &KrillSigner,
+    ) -> KrillResult<TaCertDetails> {
         let repo_info = self.repository_contact()?.repo_info();
+        SignSupport::make_ta_cert_details(key, uris, rsync_uri, repo_info, signer)
+    }
 
-        let key = signer.create_key()?;
+    /// Returns the URIs for the certificate of a new TA key. These are the
+    /// URIs of the current TAL, using the file name for the new key.
+    fn ta_next_key_uris(&self, key: &KeyIdentifier) -> KrillResult<(Vec<uri::Https>, uri::Rsync)> {
+        let tal = self.ta_cert_details()?.tal();
+        let name = ObjectName::cer_for_key(key);
 
-        let resources = ResourceSet::all();
+        let mut uris = vec![];
+        for uri in tal.uris() {
+            let uri = uri.to_string();
+            let base = &uri[..uri.rfind('/').map(|idx| idx + 1).unwrap_or(uri.len())];
+            let next = uri::Https::from_string(format!("{}{}", base, name))
+                .map_err(|e| Error::Custom(format!("Cannot derive TA certificate URI from {}: {}", uri, e)))?;
+            uris.push(next);
+        }
 
-        let cert = {
-            let serial: Serial = signer.random_serial()?;
+        let rsync_uri = tal
+            .rsync_uri()
+            .parent()
+            .and_then(|parent| parent.join(name.as_ref()).ok())
+            .ok_or_else(|| Error::Custom(format!("Cannot derive TA certificate URI from {}", tal.rsync_uri())))?;
 
-            let pub_key = signer.get_key_info(&key).map_err(Error::signer)?;
-            let name = pub_key.to_subject_name();
+        Ok((uris, rsync_uri))
+    }
 
-            let mut cert = TbsCert::new(
-                serial,
-                name.clone(),
-                Validity::new(Time::five_minutes_ago(), Time::years_from_now(100)),
-                Some(name),
-                pub_key.clone(),
-                KeyUsage::Ca,
-                Overclaim::Refuse,
-            );
+    /// Signs TAK objects for the current TA key and, if the TA is rolling
+    /// its key, for its new or old key. The TAK of the current key lists
+    /// the new key as its successor, and the TAK of the new key lists the
+    /// current key as its predecessor.
+    ///
+    /// The TAK objects are valid for as long as the TA certificate of the
+    /// signing key, so they never need to be renewed.
+    fn ta_sign_taks(
+        &self,
+        rc: &ResourceClass,
+        current: &TaCertDetails,
+        next: Option<&TaCertDetails>,
+        signer: &KrillSigner,
+    ) -> KrillResult<CaEvtDet> {
+        let validity = |key: &CertifiedKey| Validity::new(Time::five_minutes_ago(), key.incoming_cert().expires());
+
+        let current_ta_key = TrustAnchorKey::for_ta(current);
+
+        let taks = match next {
+            None => {
+                let current_key = rc.get_current_key()?;
+                let keys = TrustAnchorKeys::new(current_ta_key, None, None);
+                vec![TakInfo::sign(&keys, current_key, validity(current_key), signer)?]
+            }
+            Some(next) => {
+                // Depending on the stage of the roll the key for the current TAL
+                // is still the current key, or it has become the old key.
+                let next_ta_key = TrustAnchorKey::for_ta(next);
+                let (current_key, next_key) = match rc.get_new_key() {
+                    Ok(new_key) => (rc.get_current_key()?, new_key),
+                    Err(_) => (rc.get_old_key()?.key(), rc.get_current_key()?),
+                };
+
+                let current_keys = TrustAnchorKeys::new(current_ta_key.clone(), None, Some(next_ta_key.clone()));
+                let next_keys = TrustAnchorKeys::new(next_ta_key, Some(current_ta_key), None);
+
+                vec![
+                    TakInfo::sign(&current_keys, current_key, validity(current_key), signer)?,
+                    TakInfo::sign(&next_keys, next_key, validity(next_key), signer)?,
+                ]
+            }
+        };
+
+        Ok(CaEvtDet::TakObjectsUpdated {
+            resource_class_name: ResourceClassName::default(),
+            taks,
+        })
+    }
 
-            cert.set_basic_ca(Some(true));
+    /// Initiates a key roll for this TA. A new key is created and certified
+    /// by the TA itself, with a TAL using the URIs of the current TAL, but a
+    /// different file name. Both keys will publish a TAK object listing the
+    /// other key, so that relying parties can learn about the new key.
+    fn ta_keyroll_initiate(&self, duration: Duration, signer: &KrillSigner) -> KrillResult<Vec<CaEvt>> {
+        let rc = self.ta_resource_class()?;
+        if !rc.key_roll_due(duration) {
+            return Ok(vec![]);
+        }
 
-            let ns = ResourceClassName::default().to_string();
+        let key = signer.create_key()?;
+        let (uris, rsync_uri) = self.ta_next_key_uris(&key)?;
+        let next = self.ta_cert_details_for_key(&key, uris, rsync_uri, signer)?;
 
-            cert.set_ca_repository(Some(repo_info.ca_repository(&ns)));
-            cert.set_rpki_manifest(Some(
-                repo_info.resolve(&ns, ObjectName::mft_for_key(&pub_key.key_identifier()).as_ref()),
-            ));
-            cert.set_rpki_notify(repo_info.rpki_notify().cloned());
+        let mut evt_dets = rc.ta_keyroll_initiate(CertifiedKey::create(next.cert().clone()))?;
 
-            cert.set_as_resources(resources.to_as_resources());
-            cert.set_v4_resources(resources.to_ip_resources_v4());
-            cert.set_v6_resources(resources.to_ip_resources_v6());
+        // The TAKs can only be signed when the new key is known to the
+        // resource class, so apply the events to a scratch copy first.
+        let mut staged = rc.clone();
+        staged.pending_key_id_added(key);
+        staged.pending_key_to_new(CertifiedKey::create(next.cert().clone()));
+        let taks = self.ta_sign_taks(&staged, self.ta_cert_details()?, Some(&next), signer)?;
+
+        evt_dets.insert(1, CaEvtDet::TrustAnchorNextKeyMade { ta_cert_details: next });
+        evt_dets.push(taks);
+
+        info!("Started key roll for Trust Anchor, new key: {}", key);
+
+        Ok(self.events_from_details(evt_dets))
+    }
 
-            signer.sign_cert(cert, &key)?
+    /// Activates the new key of this TA. Its certificate and TAL become the
+    /// current ones, and all objects are re-issued under the new key. The
+    /// old key keeps publishing its TAK object until the roll is finished,
+    /// so that relying parties still using the old TAL can find the new key.
+    fn ta_keyroll_activate(
+        &self,
+        staging_time: Duration,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<Vec<CaEvt>> {
+        let next = match self.ta_next_key.as_ref() {
+            Some(next) => next,
+            None => return Ok(vec![]),
         };
 
-        let tal = TrustAnchorLocator::new(uris, rsync_uri.clone(), cert.subject_public_key_info());
+        let rc = self.ta_resource_class()?;
+        let mut evt_dets = rc.keyroll_activate(staging_time, issuance_timing, signer)?;
+        if evt_dets.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let key = next.cert().key_identifier();
 
-        let rcvd_cert =
-            ReceivedCert::create(cert, rsync_uri, resources, RequestResourceLimit::default()).map_err(Error::custom)?;
+        // Sign the TAKs as they should be after the activation.
+        let mut activated = rc.clone();
+        if let Some(CaEvtDet::KeyRollActivated { revoke_req, .. }) = evt_dets.first() {
+            activated.new_key_activated(revoke_req.clone());
+        }
+        let taks = self.ta_sign_taks(&activated, self.ta_cert_details()?, Some(next), signer)?;
 
-        let ta_cert_details = TaCertDetails::new(rcvd_cert, tal);
+        evt_dets.push(CaEvtDet::TrustAnchorNextKeyActivated { key });
+        evt_dets.push(taks);
 
-        info!("Created Trust Anchor");
+        info!("Activated new key for Trust Anchor: {}", key);
 
-        Ok(vec![StoredEvent::new(
-            &self.handle,
-            self.version,
-            CaEvtDet::TrustAnchorMade { ta_cert_details },
-        )])
+        Ok(self.events_from_details(evt_dets))
+    }
+
+    /// Finishes the key roll of this TA. The old key is removed, and the
+    /// current key publishes a TAK object which lists itself only.
+    ///
+    /// Unlike other CAs, the TA has no parent to confirm that the old key
+    /// was revoked, so this needs to be done explicitly once relying parties
+    /// had a chance to pick up the new TAL.
+    fn ta_keyroll_finish(&self, signer: &KrillSigner) -> KrillResult<Vec<CaEvt>> {
+        let rc = self.ta_resource_class()?;
+        let finished = rc.keyroll_finish()?;
+
+        let mut current = rc.clone();
+        current.old_key_removed();
+        let taks = self.ta_sign_taks(&current, self.ta_cert_details()?, None, signer)?;
+
+        info!("Finished key roll for Trust Anchor");
+
+        Ok(self.events_from_details(vec![finished, taks]))
+    }
+
+    fn ta_resource_class(&self) -> KrillResult<&ResourceClass> {
+        if !self.is_ta() {
+            return Err(Error::custom("CA is not a Trust Anchor"));
+        }
+        let rcn = ResourceClassName::default();
+        self.resources.get(&rcn).ok_or(Error::ResourceClassUnknown(rcn))
     }
 }
 
@@ -1176,6 +1523,94 @@ impl CertAuth {
     fn has_child(&self, child_handle: &ChildHandle) -> bool {
         self.children.contains_key(child_handle)
     }
+
+    /// Returns the onboarding tokens which were not yet used, including
+    /// tokens which have expired but were not yet removed.
+    pub fn onboarding_tokens(&self) -> OnboardingTokenList {
+        let mut tokens: Vec<_> = self.onboarding_tokens.values().cloned().collect();
+        tokens.sort_by_key(|token| token.expires());
+        OnboardingTokenList::new(tokens)
+    }
+
+    /// Adds an onboarding token, provided that the resources are held by
+    /// this CA and that it does not expire in the past. Tokens which have
+    /// expired in the meantime are removed.
+    fn onboarding_token_add(&self, token: OnboardingTokenInfo) -> KrillResult<Vec<CaEvt>> {
+        if token.resources().is_empty() {
+            return Err(Error::custom("An onboarding token must have resources specified"));
+        }
+        if !self.all_resources().contains(token.resources()) {
+            return Err(Error::Custom(format!(
+                "An onboarding token cannot have resources not held by CA '{}'",
+                self.handle
+            )));
+        }
+        if token.is_expired() {
+            return Err(Error::custom("An onboarding token must expire in the future"));
+        }
+        if self.onboarding_tokens.contains_key(token.id()) {
+            return Err(Error::custom("Duplicate onboarding token"));
+        }
+
+        let mut details: Vec<_> = self
+            .onboarding_tokens
+            .values()
+            .filter(|existing| existing.is_expired())
+            .map(|expired| CaEvtDet::OnboardingTokenRemoved {
+                id: expired.id().clone(),
+            })
+            .collect();
+
+        info!(
+            "CA '{}' added onboarding token '{}' for resources '{}'",
+            self.handle,
+            token.id(),
+            token.resources()
+        );
+        details.push(CaEvtDet::OnboardingTokenAdded { token });
+
+        Ok(self.events_from_details(details))
+    }
+
+    /// Removes an unused onboarding token.
+    fn onboarding_token_remove(&self, id: OnboardingTokenId) -> KrillResult<Vec<CaEvt>> {
+        if !self.onboarding_tokens.contains_key(&id) {
+            Err(Error::CaOnboardingTokenUnknown(self.handle.clone()))
+        } else {
+            info!("CA '{}' removed onboarding token '{}'", self.handle, id);
+            Ok(self.events_from_details(vec![CaEvtDet::OnboardingTokenRemoved { id }]))
+        }
+    }
+
+    /// Adds a new child with the resources bound to the onboarding token,
+    /// and removes the token so that it cannot be used again. Nothing is
+    /// changed if the token is unknown or expired, or if the child cannot
+    /// be added.
+    fn onboarding_token_redeem(
+        &self,
+        id: OnboardingTokenId,
+        child: ChildHandle,
+        id_cert: IdCertInfo,
+    ) -> KrillResult<Vec<CaEvt>> {
+        let token = self
+            .onboarding_tokens
+            .get(&id)
+            .ok_or_else(|| Error::CaOnboardingTokenUnknown(self.handle.clone()))?;
+
+        if token.is_expired() {
+            return Err(Error::CaOnboardingTokenExpired(self.handle.clone()));
+        }
+
+        // Use the normal checks for adding a child, but note that the
+        // resources held by this CA may have changed since the token
+        // was added.
+        let child_added = self.child_add(child.clone(), id_cert, token.resources().clone())?;
+
+        let mut details: Vec<_> = child_added.into_iter().map(|evt| evt.into_details()).collect();
+        details.push(CaEvtDet::OnboardingTokenRedeemed { id, child });
+
+        Ok(self.events_from_details(details))
+    }
 }
 
 /// # Being a child
@@ -1243,6 +1678,15 @@ impl CertAuth {
         Ok(rc.parent_handle())
     }
 
+    /// Returns the certificates received for the current keys of all
+    /// resource classes.
+    pub fn current_certificates(&self) -> Vec<&ReceivedCert> {
+        self.resources
+            .values()
+            .filter_map(|rc| rc.current_certificate())
+            .collect()
+    }
+
     /// Adds a parent. This method will return an error in case a parent
     /// by this name (handle) is already known. Or in case the same response
     /// is used for more than one parent.
@@ -1297,6 +1741,69 @@ impl CertAuth {
         }
     }
 
+    /// Returns the limit on the resources requested from the parent, if any.
+    pub fn parent_resource_limit(&self, parent: &ParentHandle) -> Option<&ResourceSet> {
+        self.parent_resource_limits.get(parent)
+    }
+
+    /// Sets or removes the limit on the resources requested from an existing
+    /// parent. Returns an error if the parent is not known, and no events if
+    /// the limit is unchanged.
+    fn update_parent_resource_limit(
+        &self,
+        parent: ParentHandle,
+        limit: Option<ResourceSet>,
+    ) -> KrillResult<Vec<CaEvt>> {
+        if !self.parent_known(&parent) {
+            Err(Error::CaParentUnknown(self.handle.clone(), parent))
+        } else if self.parent_resource_limits.get(&parent) == limit.as_ref() {
+            Ok(vec![])
+        } else {
+            match &limit {
+                Some(limit) => info!(
+                    "CA '{}' limited resources requested from parent '{}' to '{}'",
+                    self.handle, parent, limit
+                ),
+                None => info!("CA '{}' removed resource limit for parent '{}'", self.handle, parent),
+            }
+            Ok(self.events_from_details(vec![CaEvtDet::ParentResourceLimitUpdated { parent, limit }]))
+        }
+    }
+
+    /// Returns the limit to include in certificate requests to the parent
+    /// for the given entitled resources. If no limit was configured for the
+    /// parent then all entitled resources are requested. Returns None if the
+    /// configured limit leaves nothing to request.
+    fn parent_request_limit(&self, parent: &ParentHandle, entitled: &ResourceSet) -> Option<RequestResourceLimit> {
+        match self.parent_resource_limits.get(parent) {
+            None => Some(RequestResourceLimit::default()),
+            Some(limit) => {
+                let requested = entitled.intersection(limit);
+                if requested.is_empty() {
+                    None
+                } else {
+                    let mut request_limit = RequestResourceLimit::default();
+                    request_limit.with_asn(requested.asn().clone());
+                    request_limit.with_ipv4(requested.ipv4().clone());
+                    request_limit.with_ipv6(requested.ipv6().clone());
+                    Some(request_limit)
+                }
+            }
+        }
+    }
+
+    /// Returns the limit to include in new certificate requests for the keys
+    /// of an existing resource class, e.g. when a key roll is initiated.
+    /// Returns None if the configured limit leaves nothing to request, in
+    /// which case no new certificate should be requested for the class. It
+    /// will be removed when the entitlements are next updated.
+    fn rc_request_limit(&self, rc: &ResourceClass) -> Option<RequestResourceLimit> {
+        match rc.current_resources() {
+            Some(resources) => self.parent_request_limit(rc.parent_handle(), resources),
+            None => self.parent_request_limit(rc.parent_handle(), &ResourceSet::all()),
+        }
+    }
+
     /// Maps a parent and parent's resource class name to a ResourceClassName and
     /// ResourceClass of our own.
     fn find_parent_rc(&self, parent: &ParentHandle, parent_rcn: &ResourceClassName) -> Option<&ResourceClass> {
@@ -1325,11 +1832,12 @@ impl CertAuth {
     fn make_request_events(
         &self,
         entitlement: &ResourceClassEntitlements,
+        limit: &RequestResourceLimit,
         rc: &ResourceClass,
         signer: &KrillSigner,
     ) -> KrillResult<Vec<CaEvtDet>> {
         let repo = self.repository_contact()?;
-        rc.make_entitlement_events(self.handle(), entitlement, repo.repo_info(), signer)
+        rc.make_entitlement_events(self.handle(), entitlement, limit, repo.repo_info(), signer)
     }
 
     /// Returns the open revocation requests for the given parent.
@@ -1371,6 +1879,10 @@ impl CertAuth {
     ///    certificates will be requested in case resource entitlements, or
     ///    validity times (not after) changed.
     ///
+    /// Entitlements are restricted to the resource limit for the parent, if
+    /// one was set. Classes for which nothing remains to be requested are
+    /// treated as if they were not included in the entitlements.
+    ///
     /// Note that when we receive the updated certificate, we will republish
     /// and shrink/revoke child certificates and ROAs as needed.
     fn update_entitlements(
@@ -1388,7 +1900,17 @@ impl CertAuth {
         // up and un-publish everything there was.
         let current_resource_classes = &self.resources;
 
-        let entitled_classes: Vec<&ResourceClassName> = entitlements.classes().iter().map(|c| c.class_name()).collect();
+        let requested: Vec<(&ResourceClassEntitlements, RequestResourceLimit)> = entitlements
+            .classes()
+            .iter()
+            .filter_map(|ent| {
+                self.parent_request_limit(&parent_handle, ent.resource_set())
+                    .map(|limit| (ent, limit))
+            })
+            .collect();
+
+        let entitled_classes: Vec<&ResourceClassName> = requested.iter().map(|(ent, _)| ent.class_name()).collect();
+        let mut removed_classes = vec![];
 
         for (rcn, rc) in current_resource_classes.iter().filter(|(_name, class)| {
             // Find the classes for this parent, not included
@@ -1404,18 +1926,24 @@ impl CertAuth {
                 parent: parent_handle.clone(),
                 revoke_requests,
             });
+            removed_classes.push(rcn);
+        }
+
+        let remaining = self.all_resources_except(&removed_classes);
+        if let Some(shrunk) = self.resources_shrunk(&parent_handle, &remaining) {
+            event_details.push(shrunk);
         }
 
         // Now check all the entitlements and either create an RC for them, or update.
         let mut next_class_name = self.next_class_name;
 
-        for ent in entitlements.classes() {
+        for (ent, limit) in requested.iter() {
             let parent_rc_name = ent.class_name();
 
             match self.find_parent_rc(&parent_handle, parent_rc_name) {
                 Some(rc) => {
                     // We have a matching RC, make requests (note this may be a no-op).
-                    event_details.append(&mut self.make_request_events(ent, rc, signer.deref())?);
+                    event_details.append(&mut self.make_request_events(ent, limit, rc, signer.deref())?);
                 }
                 None => {
                     // Create a resource class with a pending key
@@ -1442,7 +1970,7 @@ impl CertAuth {
                         parent_resource_class_name: parent_rc_name.clone(),
                         pending_key,
                     };
-                    let mut request_events = self.make_request_events(ent, &rc, signer.deref())?;
+                    let mut request_events = self.make_request_events(ent, limit, &rc, signer.deref())?;
 
                     event_details.push(added);
                     event_details.append(&mut request_events);
@@ -1473,18 +2001,49 @@ impl CertAuth {
     ) -> KrillResult<Vec<CaEvt>> {
         debug!("CA {}: Updating received cert for class: {}", self.handle, rcn);
 
-        let rc = self.resources.get(&rcn).ok_or(Error::ResourceClassUnknown(rcn))?;
+        let rc = self
+            .resources
+            .get(&rcn)
+            .ok_or(Error::ResourceClassUnknown(rcn.clone()))?;
+
+        // Resources are only lost when the certificate for the current key
+        // shrinks. A certificate for a new key is expected to follow suit.
+        let shrunk = match rc.current_key() {
+            Some(current) if current.key_id() == &rcvd_cert.key_identifier() => {
+                let remaining = self.all_resources_except(&[&rcn]).union(rcvd_cert.resources());
+                self.resources_shrunk(rc.parent_handle(), &remaining)
+            }
+            _ => None,
+        };
 
-        let evt_details = rc.update_received_cert(
+        let mut evt_details = rc.update_received_cert(
             self.handle(),
             rcvd_cert,
             &self.routes,
+            &self.delegated_resources(),
             &self.aspas,
             &self.bgpsec_defs,
+            &self.prefix_lists,
+            self.ghostbuster.as_ref(),
             config,
             signer.deref(),
         )?;
 
+        // A TA publishes a TAK for its first key as soon as that key is active.
+        if self.is_ta() {
+            let activated = evt_details.iter().find_map(|details| match details {
+                CaEvtDet::KeyPendingToActive { current_key, .. } => Some(current_key.clone()),
+                _ => None,
+            });
+            if let Some(current_key) = activated {
+                let mut active = rc.clone();
+                active.pending_key_to_active(current_key);
+                evt_details.push(self.ta_sign_taks(&active, self.ta_cert_details()?, None, signer.deref())?);
+            }
+        }
+
+        evt_details.extend(shrunk);
+
         let mut res = vec![];
         let mut version = self.version;
 
@@ -1514,11 +2073,99 @@ impl CertAuth {
             .ok_or_else(|| Error::ResourceClassUnknown(rcn.clone()))?;
         let revoke_requests = rc.revoke(signer.deref())?;
 
-        Ok(self.events_from_details(vec![CaEvtDet::ResourceClassRemoved {
-            resource_class_name: rcn,
+        let mut details = vec![CaEvtDet::ResourceClassRemoved {
+            resource_class_name: rcn.clone(),
             parent: rc.parent_handle().clone(),
             revoke_requests,
-        }]))
+        }];
+
+        let remaining = self.all_resources_except(&[&rcn]);
+        details.extend(self.resources_shrunk(rc.parent_handle(), &remaining));
+
+        Ok(self.events_from_details(details))
+    }
+
+    /// Returns the resources which were removed by parents, and which were
+    /// not yet cleared by the operator.
+    pub fn resource_shrinks(&self) -> &Vec<ResourceShrink> {
+        &self.resource_shrinks
+    }
+
+    fn resource_shrinks_clear(&self) -> KrillResult<Vec<CaEvt>> {
+        if self.resource_shrinks.is_empty() {
+            Ok(vec![])
+        } else {
+            Ok(self.events_from_details(vec![CaEvtDet::ResourceShrinksCleared]))
+        }
+    }
+
+    /// Returns the resources delegated to all children of this CA.
+    fn delegated_resources(&self) -> ResourceSet {
+        self.children.values().fold(ResourceSet::default(), |delegated, child| {
+            delegated.union(child.resources())
+        })
+    }
+
+    /// Returns the resources held under all resource classes, except the
+    /// given classes.
+    fn all_resources_except(&self, excluded: &[&ResourceClassName]) -> ResourceSet {
+        let mut resources = ResourceSet::default();
+        for (rcn, rc) in self.resources.iter() {
+            if !excluded.contains(&rcn) {
+                if let Some(rc_resources) = rc.current_resources() {
+                    resources = resources.union(rc_resources);
+                }
+            }
+        }
+        resources
+    }
+
+    /// Returns an event describing the effect of losing resources under the
+    /// parent, if only the remaining resources will be held. I.e. the ROA
+    /// configurations which will no longer be held, and the children which
+    /// will have their certificates shrunk.
+    fn resources_shrunk(&self, parent: &ParentHandle, remaining: &ResourceSet) -> Option<CaEvtDet> {
+        let held = self.all_resources();
+        let lost = ResourceSet::new(
+            held.asn().difference(remaining.asn()),
+            held.ipv4().difference(remaining.ipv4()).into(),
+            held.ipv6().difference(remaining.ipv6()).into(),
+        );
+        if lost.is_empty() {
+            return None;
+        }
+
+        let roas_not_held = self
+            .routes
+            .roa_configurations()
+            .into_iter()
+            .filter(|config| {
+                let address = config.payload().as_roa_ip_address();
+                held.contains_roa_address(&address) && !remaining.contains_roa_address(&address)
+            })
+            .collect();
+
+        let mut children: Vec<_> = self
+            .children
+            .iter()
+            .filter_map(|(child, details)| {
+                let child_lost = details.resources().intersection(&lost);
+                if child_lost.is_empty() {
+                    None
+                } else {
+                    Some(ChildResourcesLost::new(child.clone(), child_lost))
+                }
+            })
+            .collect();
+        children.sort_by(|a, b| a.child().as_str().cmp(b.child().as_str()));
+
+        warn!(
+            "CA '{}' lost resources under parent '{}': {}",
+            self.handle, parent, lost
+        );
+
+        let shrink = ResourceShrink::new(parent.clone(), lost, roas_not_held, children);
+        Some(CaEvtDet::ResourcesShrunk { shrink })
     }
 }
 
@@ -1527,7 +2174,7 @@ impl CertAuth {
 impl CertAuth {
     fn keyroll_initiate(&self, duration: Duration, signer: Arc<KrillSigner>) -> KrillResult<Vec<CaEvt>> {
         if self.is_ta() {
-            return Ok(vec![]);
+            return self.ta_keyroll_initiate(duration, &signer);
         }
 
         let mut version = self.version;
@@ -1536,7 +2183,20 @@ impl CertAuth {
         for (rcn, rc) in self.resources.iter() {
             let mut started = false;
             let repo = self.repository_contact()?;
-            for details in rc.keyroll_initiate(repo.repo_info(), duration, &signer)?.into_iter() {
+            let limit = match self.rc_request_limit(rc) {
+                Some(limit) => limit,
+                None => {
+                    debug!(
+                        "Skip key roll for ca: {}, rc: {}, nothing to request under resource limit",
+                        &self.handle, rcn
+                    );
+                    continue;
+                }
+            };
+            for details in rc
+                .keyroll_initiate(limit, repo.repo_info(), duration, &signer)?
+                .into_iter()
+            {
                 started = true;
                 res.push(StoredEvent::new(self.handle(), version, details));
                 version += 1;
@@ -1562,7 +2222,7 @@ impl CertAuth {
         signer: Arc<KrillSigner>,
     ) -> KrillResult<Vec<CaEvt>> {
         if self.is_ta() {
-            return Ok(vec![]);
+            return self.ta_keyroll_activate(staging_time, &config.issuance_timing, &signer);
         }
 
         let mut version = self.version;
@@ -1628,6 +2288,18 @@ impl CertAuth {
             if existing_contact == &contact {
                 return Err(Error::CaRepoInUse(self.handle.clone()));
             }
+
+            // Mirrors must be removed first if they cannot mirror the new repository.
+            if let Some(mirror) = self
+                .repository_mirrors
+                .iter()
+                .find(|mirror| mirror.repo_info().base_uri() != contact.repo_info().base_uri())
+            {
+                return Err(Error::CaRepoMirrorMismatch(
+                    self.handle.clone(),
+                    mirror.server_info().service_uri().clone(),
+                ));
+            }
             // Initiate rolls in all RCs so we can use the new repo in the new key.
             let info = contact.repo_info().clone();
             for rc in self.resources.values() {
@@ -1640,7 +2312,9 @@ impl CertAuth {
                     return Err(Error::KeyRollNotAllowed);
                 }
 
-                evt_dets.append(&mut rc.keyroll_initiate(&info, Duration::seconds(0), signer)?);
+                if let Some(limit) = self.rc_request_limit(rc) {
+                    evt_dets.append(&mut rc.keyroll_initiate(limit, &info, Duration::seconds(0), signer)?);
+                }
             }
         }
 
@@ -1654,6 +2328,53 @@ impl CertAuth {
         evt_dets.push(CaEvtDet::RepoUpdated { contact });
         Ok(self.events_from_details(evt_dets))
     }
+
+    /// Add a repository mirror:
+    ///    - Will return an error in case the CA has no repository yet, or if it already uses the repository
+    ///    - Will return an error if the mirror uses another rsync base URI, as it would then reject the objects
+    ///    - Assumes that the repository can be reached (this is checked by CaManager before issuing the command to this CA)
+    pub fn add_repo_mirror(&self, contact: RepositoryContact) -> KrillResult<Vec<CaEvt>> {
+        let repo = self.repository_contact()?;
+        let service_uri = contact.server_info().service_uri();
+
+        if repo.server_info().service_uri() == service_uri
+            || self
+                .repository_mirrors
+                .iter()
+                .any(|mirror| mirror.server_info().service_uri() == service_uri)
+        {
+            return Err(Error::CaRepoInUse(self.handle.clone()));
+        }
+
+        if repo.repo_info().base_uri() != contact.repo_info().base_uri() {
+            return Err(Error::CaRepoMirrorMismatch(self.handle.clone(), service_uri.clone()));
+        }
+
+        info!(
+            "CA '{}' added repository mirror. Service URI will be: {}",
+            self.handle, service_uri
+        );
+
+        Ok(self.events_from_details(vec![CaEvtDet::RepoMirrorAdded { contact }]))
+    }
+
+    /// Remove a repository mirror. The objects published there will be withdrawn.
+    pub fn remove_repo_mirror(&self, service_uri: ServiceUri) -> KrillResult<Vec<CaEvt>> {
+        if !self
+            .repository_mirrors
+            .iter()
+            .any(|mirror| mirror.server_info().service_uri() == &service_uri)
+        {
+            return Err(Error::CaRepoMirrorUnknown(self.handle.clone(), service_uri));
+        }
+
+        info!(
+            "CA '{}' removed repository mirror with service URI: {}",
+            self.handle, service_uri
+        );
+
+        Ok(self.events_from_details(vec![CaEvtDet::RepoMirrorRemoved { service_uri }]))
+    }
 }
 
 /// # Managing Route Authorizations
@@ -1665,16 +2386,45 @@ impl CertAuth {
     fn route_authorizations_update(
         &self,
         route_auth_updates: RoaConfigurationUpdates,
+        max_length_exposed: &[RoaPayload],
         config: &Config,
         signer: Arc<KrillSigner>,
     ) -> KrillResult<Vec<CaEvt>> {
+        let evt_dets =
+            self.route_authorizations_update_details(route_auth_updates, max_length_exposed, config, signer.deref())?;
+        Ok(self.events_from_details(evt_dets))
+    }
+
+    /// Returns the event details for applying the updates, including the
+    /// events for any ROA objects which need to be (re-)issued or removed.
+    fn route_authorizations_update_details(
+        &self,
+        route_auth_updates: RoaConfigurationUpdates,
+        max_length_exposed: &[RoaPayload],
+        config: &Config,
+        signer: &KrillSigner,
+    ) -> KrillResult<Vec<CaEvtDet>> {
         let route_auth_updates = route_auth_updates.into_explicit_max_length();
 
-        let (routes, mut evt_dets) = self.update_authorizations(&route_auth_updates)?;
+        let (routes, mut evt_dets) = self.update_authorizations(&route_auth_updates, max_length_exposed)?;
+        evt_dets.append(&mut self.roas_updated_details(&routes, config, signer)?);
+
+        Ok(evt_dets)
+    }
+
+    /// Returns the event details for the ROA objects which need to be
+    /// (re-)issued or removed in each resource class for the routes.
+    fn roas_updated_details(
+        &self,
+        routes: &Routes,
+        config: &Config,
+        signer: &KrillSigner,
+    ) -> KrillResult<Vec<CaEvtDet>> {
+        let mut evt_dets = vec![];
+        let delegated = self.delegated_resources();
 
-        // for rc in self.resources
         for (rcn, rc) in self.resources.iter() {
-            let updates = rc.update_roas(&routes, config, signer.deref())?;
+            let updates = rc.update_roas(routes, &delegated, config, signer)?;
             if updates.contains_changes() {
                 info!("CA '{}' under RC '{}' updated ROAs: {}", self.handle, rcn, updates);
 
@@ -1685,9 +2435,46 @@ impl CertAuth {
             }
         }
 
+        Ok(evt_dets)
+    }
+
+    /// Enables or disables AS0 ROAs for the held space which is not covered
+    /// by any authorization. This does nothing if the policy is unchanged.
+    fn route_authorizations_as0_policy(
+        &self,
+        policy: RoaAs0Policy,
+        config: &Config,
+        signer: &KrillSigner,
+    ) -> KrillResult<Vec<CaEvt>> {
+        if self.routes.as0_unrouted() == policy.enabled() {
+            return Ok(vec![]);
+        }
+
+        let mut routes = self.routes.clone();
+        routes.set_as0_unrouted(policy.enabled());
+
+        let mut evt_dets = vec![CaEvtDet::RouteAuthorizationAs0Policy {
+            enabled: policy.enabled(),
+        }];
+        evt_dets.append(&mut self.roas_updated_details(&routes, config, signer)?);
+
         Ok(self.events_from_details(evt_dets))
     }
 
+    /// Returns the AS0 policy and the AS0 payloads issued because of it,
+    /// for the unrouted space under each resource class.
+    pub fn roa_as0_status(&self) -> RoaAs0Status {
+        let delegated = self.delegated_resources();
+        let payloads = self
+            .resources
+            .values()
+            .flat_map(|rc| rc.current_resources())
+            .flat_map(|resources| self.routes.as0_unrouted_payloads(resources, &delegated))
+            .collect();
+
+        RoaAs0Status::new(self.routes.as0_unrouted(), payloads)
+    }
+
     /// Renew existing ROA objects if needed.
     pub fn route_authorizations_renew(
         &self,
@@ -1721,30 +2508,45 @@ impl CertAuth {
 
     /// Verifies that the updates are correct, i.e.:
     /// - additions are for prefixes held by this CA
-    /// - removals are for known authorizations
+    /// - removals are for known, or scheduled, authorizations
     /// - additions are new
     ///   - no duplicates, or
     ///   - not covered by remaining after the removals
+    /// - additions have valid time bounds
+    /// - new additions are not in `max_length_exposed`, i.e. they do not
+    ///   use a max length which allows unannounced more specific prefixes
+    ///   when strict max lengths are configured (RFC 9319)
+    ///
+    /// Additions with a 'not_before' time in the future are scheduled,
+    /// rather than added.
     ///
     /// Returns the desired Routes and the event details for
     /// persisting the changes, or an error in case of issues.
     ///
     /// Note: this does not re-issue the actual ROAs, this
     ///       can be used for the 'dry-run' option.
-    pub fn update_authorizations(&self, updates: &RoaConfigurationUpdates) -> KrillResult<(Routes, Vec<CaEvtDet>)> {
+    pub fn update_authorizations(
+        &self,
+        updates: &RoaConfigurationUpdates,
+        max_length_exposed: &[RoaPayload],
+    ) -> KrillResult<(Routes, Vec<CaEvtDet>)> {
         let mut delta_errors = RoaDeltaError::default();
         let mut res = vec![];
 
         let all_resources = self.all_resources();
+        let now = Timestamp::now();
 
         // Keep track of routes as they will be after applying the updates
         let mut desired_routes = self.routes.clone();
+        let mut desired_scheduled = self.scheduled_roas.clone();
 
-        // make sure that all removals are held
+        // make sure that all removals are held, or scheduled
         for roa_payload in updates.removed() {
             let auth = RoaPayloadJsonMapKey::from(*roa_payload);
             if desired_routes.remove(&auth) {
                 res.push(CaEvtDet::RouteAuthorizationRemoved { auth });
+            } else if desired_scheduled.unschedule(&auth) {
+                res.push(CaEvtDet::RouteAuthorizationUnscheduled { auth });
             } else {
                 delta_errors.add_unknown(*roa_payload)
             }
@@ -1754,6 +2556,9 @@ impl CertAuth {
         for roa_configuration in updates.added() {
             let roa_payload = roa_configuration.payload();
             let comment = roa_configuration.comment();
+            let metadata = roa_configuration.metadata();
+            let not_before = roa_configuration.not_before();
+            let not_after = roa_configuration.not_after();
 
             let auth = RoaPayloadJsonMapKey::from(roa_payload);
 
@@ -1763,21 +2568,67 @@ impl CertAuth {
             } else if !all_resources.contains_roa_address(&roa_payload.as_roa_ip_address()) {
                 // We do not hold the prefix
                 delta_errors.add_notheld(roa_configuration.clone());
+            } else if !roa_configuration.time_bounds_valid(now) {
+                // The configuration would never be, or no longer be, active
+                delta_errors.add_invalid_time(roa_configuration.clone());
+            } else if !desired_routes.has(&auth) && max_length_exposed.contains(&roa_payload) {
+                // This would authorize, now or when scheduled, more specific announcements
+                // which are not seen in BGP. This also applies when a scheduled configuration
+                // is activated. Existing authorizations can still be updated.
+                delta_errors.add_max_length_exposed(roa_configuration.clone());
+            } else if roa_configuration.is_scheduled(now) {
+                if desired_routes.has(&auth) {
+                    // We cannot schedule a payload which is currently authorized. Users
+                    // should remove it first, or update its 'not_after' time instead.
+                    delta_errors.add_duplicate(roa_configuration.clone());
+                } else {
+                    // This replaces any existing schedule for the same payload
+                    desired_scheduled.schedule(roa_configuration.clone());
+                    res.push(CaEvtDet::RouteAuthorizationScheduled {
+                        configuration: roa_configuration.clone(),
+                    });
+                }
             } else if let Some(info) = desired_routes.info(&auth) {
-                // We have an existing info for this payload, this may be an attempt to update the comment.
-                if info.comment() != comment {
-                    // Update comment
+                // We have an existing info for this payload, this may be an attempt to update the
+                // comment, metadata and/or time bounds. Metadata is only changed if it is included,
+                // updates without metadata keep the existing metadata.
+                let comment_changed = info.comment() != comment;
+                let metadata_changed = metadata.filter(|metadata| info.metadata() != *metadata);
+                let time_bounds_changed = info.not_before() != not_before || info.not_after() != not_after;
+
+                if comment_changed {
                     res.push(CaEvtDet::RouteAuthorizationComment {
                         auth,
                         comment: comment.cloned(),
                     });
-                } else {
+                }
+                if let Some(metadata) = metadata_changed {
+                    res.push(CaEvtDet::RouteAuthorizationMetadata {
+                        auth,
+                        metadata: metadata.clone(),
+                    });
+                }
+
+                if time_bounds_changed {
+                    res.push(CaEvtDet::RouteAuthorizationTimeBounds {
+                        auth,
+                        not_before,
+                        not_after,
+                    });
+                }
+
+                if !comment_changed && metadata_changed.is_none() && !time_bounds_changed {
                     // Duplicate entry. We could be idempotent, but perhaps it's best to return an error
                     // instead because it seems that the user is out of sync with the current state.
                     delta_errors.add_duplicate(roa_configuration.clone());
                 }
             } else {
-                // Ok, this seems okay now
+                // Ok, this seems okay now. If this configuration was scheduled, then
+                // it is activated now.
+                if desired_scheduled.unschedule(&auth) {
+                    res.push(CaEvtDet::RouteAuthorizationUnscheduled { auth });
+                }
+
                 res.push(CaEvtDet::RouteAuthorizationAdded { auth });
                 desired_routes.add(auth); // track to check if update has duplicates
 
@@ -1788,6 +2639,23 @@ impl CertAuth {
                         comment: comment.cloned(),
                     });
                 }
+
+                if let Some(metadata) = metadata.filter(|metadata| !metadata.is_empty()) {
+                    desired_routes.metadata(&auth, metadata.clone());
+                    res.push(CaEvtDet::RouteAuthorizationMetadata {
+                        auth,
+                        metadata: metadata.clone(),
+                    });
+                }
+
+                if not_before.is_some() || not_after.is_some() {
+                    desired_routes.time_bounds(&auth, not_before, not_after);
+                    res.push(CaEvtDet::RouteAuthorizationTimeBounds {
+                        auth,
+                        not_before,
+                        not_after,
+                    });
+                }
             }
         }
 
@@ -1799,6 +2667,179 @@ impl CertAuth {
     }
 }
 
+/// # Time-bounded ROA configurations
+///
+impl CertAuth {
+    /// Returns the upcoming activations and expirations of time-bounded
+    /// ROA configurations.
+    pub fn timed_roa_changes(&self) -> TimedRoaChanges {
+        let mut changes = self.scheduled_roas.timed_changes();
+        changes.append(&mut self.routes.timed_changes());
+        TimedRoaChanges::new(changes)
+    }
+
+    /// Returns the updates needed to activate scheduled configurations,
+    /// and to remove expired configurations, at the given time. Scheduled
+    /// configurations which expired before they could be activated, e.g.
+    /// because Krill was not running, or for which the prefix is no longer
+    /// held, are simply removed.
+    pub fn timed_roa_updates(&self, now: Timestamp) -> RoaConfigurationUpdates {
+        let mut updates = RoaConfigurationUpdates::empty();
+        let all_resources = self.all_resources();
+
+        for config in self.scheduled_roas.configurations() {
+            if config.is_expired(now) {
+                updates.remove(config.payload());
+            } else if self.scheduled_roas.is_refused(&config.payload().into()) {
+                // The activation was refused before, leave it until the user
+                // removes or reschedules it, or it expires.
+            } else if !config.is_scheduled(now) {
+                if all_resources.contains_roa_address(&config.payload().as_roa_ip_address()) {
+                    updates.add(config.clone());
+                } else {
+                    warn!(
+                        "CA '{}' no longer holds the prefix for scheduled ROA '{}', it will not be added",
+                        self.handle, config
+                    );
+                    updates.remove(config.payload());
+                }
+            }
+        }
+
+        for config in self.routes.roa_configurations() {
+            if config.is_expired(now) {
+                updates.remove(config.payload());
+            }
+        }
+
+        updates
+    }
+
+    /// Returns the scheduled configurations for which the activation was
+    /// refused, because of strict ROA max lengths.
+    pub fn roa_activations_refused(&self) -> Vec<RoaConfiguration> {
+        self.scheduled_roas.refused_configurations().cloned().collect()
+    }
+
+    /// Marks scheduled configurations as refused, so that they are no longer
+    /// activated. Payloads which are not scheduled, or already refused, are
+    /// ignored.
+    fn roa_activation_refused(&self, refused: Vec<RoaPayload>) -> KrillResult<Vec<CaEvt>> {
+        let evt_dets = refused
+            .into_iter()
+            .map(RoaPayloadJsonMapKey::from)
+            .filter(|auth| self.scheduled_roas.has(auth) && !self.scheduled_roas.is_refused(auth))
+            .map(|auth| CaEvtDet::RouteAuthorizationActivationRefused { auth })
+            .collect();
+
+        Ok(self.events_from_details(evt_dets))
+    }
+}
+
+/// # Staging ROA changes in drafts
+///
+impl CertAuth {
+    /// Returns all current drafts.
+    pub fn roa_drafts(&self) -> RoaDraftList {
+        self.roa_drafts.list()
+    }
+
+    /// Returns the named draft, or an error if it is unknown.
+    pub fn roa_draft(&self, name: &str) -> KrillResult<RoaDraft> {
+        self.roa_drafts
+            .get(name)
+            .map(|updates| RoaDraft::new(name.to_string(), updates.clone()))
+            .ok_or_else(|| Error::CaRoaDraftUnknown(self.handle.clone(), name.to_string()))
+    }
+
+    /// Merges the updates into the named draft, or creates a new draft if
+    /// it did not exist yet. The resulting draft is verified against the
+    /// current authorizations, so that any issues are reported early. Note
+    /// that the draft is verified again when it is committed, because the
+    /// authorizations or resources of this CA may have changed by then.
+    fn roa_draft_update(
+        &self,
+        name: RoaDraftName,
+        updates: RoaConfigurationUpdates,
+        max_length_exposed: &[RoaPayload],
+    ) -> KrillResult<Vec<CaEvt>> {
+        let mut draft = match self.roa_drafts.get(&name) {
+            Some(draft) => draft.clone(),
+            None if RoaDraft::is_valid_name(&name) => RoaConfigurationUpdates::default(),
+            None => return Err(Error::CaRoaDraftNameInvalid(self.handle.clone(), name)),
+        };
+        draft.merge(updates.into_explicit_max_length(), |payload| {
+            let auth = RoaPayloadJsonMapKey::from(*payload);
+            self.routes.has(&auth) || self.scheduled_roas.has(&auth)
+        });
+
+        self.update_authorizations(&draft, max_length_exposed)?;
+
+        Ok(vec![StoredEvent::new(
+            &self.handle,
+            self.version,
+            CaEvtDet::RoaDraftUpdated { name, updates: draft },
+        )])
+    }
+
+    /// Discards the named draft.
+    fn roa_draft_remove(&self, name: RoaDraftName) -> KrillResult<Vec<CaEvt>> {
+        if self.roa_drafts.get(&name).is_none() {
+            return Err(Error::CaRoaDraftUnknown(self.handle.clone(), name));
+        }
+
+        Ok(vec![StoredEvent::new(
+            &self.handle,
+            self.version,
+            CaEvtDet::RoaDraftRemoved { name },
+        )])
+    }
+
+    /// Applies the named draft as a single update, and removes it. All
+    /// removals and additions in the draft result in a single set of ROA
+    /// object updates, so that new ROAs are published together with the
+    /// withdrawal of the ROAs they replace.
+    fn roa_draft_commit(
+        &self,
+        name: RoaDraftName,
+        max_length_exposed: &[RoaPayload],
+        config: &Config,
+        signer: Arc<KrillSigner>,
+    ) -> KrillResult<Vec<CaEvt>> {
+        let updates = self
+            .roa_drafts
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| Error::CaRoaDraftUnknown(self.handle.clone(), name.clone()))?;
+
+        let mut evt_dets =
+            self.route_authorizations_update_details(updates, max_length_exposed, config, signer.deref())?;
+        evt_dets.push(CaEvtDet::RoaDraftRemoved { name });
+
+        Ok(self.events_from_details(evt_dets))
+    }
+}
+
+/// # Importing ROA configurations
+///
+impl CertAuth {
+    /// Returns the ROA configuration updates which would add the route and
+    /// route6 objects in the RPSL text, for the prefixes held by this CA.
+    /// Objects for which a configuration is already present, or scheduled,
+    /// are skipped and reported.
+    pub fn rpsl_import_report(&self, rpsl: &str) -> RpslImportReport {
+        let mut configured: Vec<RoaPayload> = self
+            .routes
+            .roa_configurations()
+            .iter()
+            .map(|config| config.payload())
+            .collect();
+        configured.extend(self.scheduled_roas.configurations().map(|config| config.payload()));
+
+        RpslRouteObjects::parse(rpsl).into_import_report(&self.all_resources(), &configured)
+    }
+}
+
 /// # Autonomous System Provider Authorizations
 ///
 impl CertAuth {
@@ -1807,6 +2848,13 @@ impl CertAuth {
         AspaDefinitionList::new(self.aspas.all().cloned().collect())
     }
 
+    /// Returns the changes to the current AspaDefinitions which are proposed
+    /// based on the given AS relationships, for the customer ASNs held.
+    pub fn aspas_import_report(&self, relationships: &str) -> AspaImportReport {
+        let current: Vec<AspaDefinition> = self.aspas.all().cloned().collect();
+        AsRelationships::parse(relationships).into_import_report(&self.all_resources(), &current)
+    }
+
     /// Process AspaDefinitionUpdates:
     /// - add new aspas
     /// - replace existing
@@ -2057,6 +3105,246 @@ impl CertAuth {
     }
 }
 
+/// # Signed Prefix Lists
+///
+impl CertAuth {
+    /// Show current PrefixListDefinitions
+    pub fn prefix_lists_show(&self) -> PrefixListDefinitionList {
+        PrefixListDefinitionList::new(self.prefix_lists.all().cloned().collect())
+    }
+
+    /// Process PrefixListDefinitionUpdates:
+    /// - add new definitions
+    /// - replace existing
+    /// - remove definitions to be removed
+    pub fn prefix_lists_update(
+        &self,
+        updates: PrefixListDefinitionUpdates,
+        config: &Config,
+        signer: &KrillSigner,
+    ) -> KrillResult<Vec<CaEvt>> {
+        let mut res = vec![];
+
+        let (add_or_replace, remove) = updates.unpack();
+
+        // Keep track of a copy of the definitions so we can use it to update
+        // the objects in each resource class.
+        let mut all_prefix_lists = self.prefix_lists.clone();
+
+        for asn in remove {
+            if !all_prefix_lists.has(asn) {
+                return Err(Error::PrefixListAsnUnknown(self.handle().clone(), asn));
+            }
+            res.push(CaEvtDet::PrefixListDefinitionRemoved { asn });
+            all_prefix_lists.remove(asn);
+        }
+
+        for definition in add_or_replace {
+            let asn = definition.asn();
+
+            if definition.is_empty() {
+                return Err(Error::PrefixListEmpty(self.handle().clone(), asn));
+            }
+
+            if !self.all_resources().contains_asn(asn) {
+                return Err(Error::PrefixListAsnNotEntitled(self.handle().clone(), asn));
+            }
+
+            // Ensure that the prefixes are sorted, definitions may have
+            // been deserialized from JSON.
+            let definition = PrefixListDefinition::new(asn, definition.prefixes().clone());
+            all_prefix_lists.add_or_replace(definition.clone());
+
+            match self.prefix_lists.get(asn) {
+                None => res.push(CaEvtDet::PrefixListDefinitionAdded { definition }),
+                Some(existing) => {
+                    if existing != &definition {
+                        res.push(CaEvtDet::PrefixListDefinitionUpdated { definition })
+                    }
+                }
+            }
+        }
+
+        for (rcn, rc) in self.resources.iter() {
+            let updates = rc.update_prefix_lists(&all_prefix_lists, &config.issuance_timing, signer)?;
+            if updates.contains_changes() {
+                res.push(CaEvtDet::PrefixListObjectsUpdated {
+                    resource_class_name: rcn.clone(),
+                    updates,
+                });
+            }
+        }
+
+        Ok(self.events_from_details(res))
+    }
+
+    /// Renew existing Signed Prefix List objects if needed.
+    pub fn prefix_lists_renew(&self, config: &Config, signer: &KrillSigner) -> KrillResult<Vec<CaEvt>> {
+        let mut evt_dets = vec![];
+
+        for (rcn, rc) in self.resources.iter() {
+            let updates = rc.renew_prefix_lists(&config.issuance_timing, signer)?;
+            if updates.contains_changes() {
+                info!(
+                    "CA '{}' reissued prefix lists under RC '{}' before they would expire",
+                    self.handle, rcn
+                );
+
+                evt_dets.push(CaEvtDet::PrefixListObjectsUpdated {
+                    resource_class_name: rcn.clone(),
+                    updates,
+                });
+            }
+        }
+
+        Ok(self.events_from_details(evt_dets))
+    }
+}
+
+/// # Ghostbusters
+///
+impl CertAuth {
+    /// Show the current Ghostbuster contact, if any.
+    pub fn ghostbuster_show(&self) -> Option<GhostbusterContact> {
+        self.ghostbuster.clone()
+    }
+
+    /// Set or remove the contact details of this CA, and (re-)issue or
+    /// withdraw the Ghostbuster record in each resource class.
+    pub fn ghostbuster_update(
+        &self,
+        contact: Option<GhostbusterContact>,
+        config: &Config,
+        signer: &KrillSigner,
+    ) -> KrillResult<Vec<CaEvt>> {
+        if let Some(contact) = &contact {
+            contact
+                .validate()
+                .map_err(|msg| Error::GhostbusterContactInvalid(self.handle.clone(), msg))?;
+        }
+
+        if contact == self.ghostbuster {
+            return Ok(vec![]);
+        }
+
+        let mut res = vec![];
+
+        for (rcn, rc) in self.resources.iter() {
+            if let Some(ghostbuster) = rc.update_ghostbuster(contact.as_ref(), &config.issuance_timing, signer)? {
+                res.push(CaEvtDet::GhostbusterObjectUpdated {
+                    resource_class_name: rcn.clone(),
+                    ghostbuster,
+                });
+            }
+        }
+
+        res.push(CaEvtDet::GhostbusterContactUpdated { contact });
+
+        Ok(self.events_from_details(res))
+    }
+
+    /// Renew the Ghostbuster records if needed.
+    pub fn ghostbuster_renew(&self, config: &Config, signer: &KrillSigner) -> KrillResult<Vec<CaEvt>> {
+        let mut evt_dets = vec![];
+
+        for (rcn, rc) in self.resources.iter() {
+            if let Some(ghostbuster) = rc.renew_ghostbuster(&config.issuance_timing, signer)? {
+                info!(
+                    "CA '{}' reissued Ghostbuster record under RC '{}' before it would expire",
+                    self.handle, rcn
+                );
+
+                evt_dets.push(CaEvtDet::GhostbusterObjectUpdated {
+                    resource_class_name: rcn.clone(),
+                    ghostbuster: Some(ghostbuster),
+                });
+            }
+        }
+
+        Ok(self.events_from_details(evt_dets))
+    }
+}
+
+/// # Resource Signed Checklists
+///
+impl CertAuth {
+    /// Sign an RSC (RFC 9323). An RSC has a single signer, so all resources
+    /// must be held in one resource class. A one-off EE key is used, and
+    /// destroyed again after signing.
+    pub fn rsc_sign(&self, request: RscRequest, signer: &KrillSigner) -> KrillResult<ResourceSignedChecklist> {
+        let (resources, validity, checklist) = request.unpack();
+
+        let content = ResourceSignedChecklist::content(&resources, &checklist)?;
+
+        let rc = self
+            .resources
+            .values()
+            .find(|rc| {
+                rc.current_resources()
+                    .map(|held| held.contains(&resources))
+                    .unwrap_or(false)
+            })
+            .ok_or_else(|| Error::custom("Resources for RSC are not held in a single resource class"))?;
+
+        let key = signer.create_key()?;
+        let rsc = rc
+            .create_rta_ee(&resources, validity, key, signer)
+            .and_then(|ee| ResourceSignedChecklist::sign(content, ee, signer));
+        signer.destroy_key(&key)?;
+
+        if rsc.is_ok() {
+            info!("CA '{}' signed an RSC for resources: {}", self.handle, resources);
+        }
+
+        rsc
+    }
+}
+
+/// # Signed Geofeeds
+///
+impl CertAuth {
+    /// Sign a geofeed (RFC 9632). All prefixes in the geofeed must be held
+    /// by this CA, in a single resource class. A one-off EE key is used for
+    /// the detached signature, and destroyed again after signing.
+    pub fn geofeed_sign(&self, request: GeofeedSignRequest, signer: &KrillSigner) -> KrillResult<SignedGeofeed> {
+        let (validity, csv) = request.unpack();
+
+        let geofeed = Geofeed::parse(&csv)?;
+        let resources = geofeed.resources()?;
+
+        let held = self.all_resources();
+        if !held.contains(&resources) {
+            let not_held = resources.difference(&held);
+            return Err(Error::Custom(format!(
+                "Prefixes in geofeed are not held by CA '{}': {}",
+                self.handle, not_held
+            )));
+        }
+
+        let rc = self
+            .resources
+            .values()
+            .find(|rc| {
+                rc.current_resources()
+                    .map(|held| held.contains(&resources))
+                    .unwrap_or(false)
+            })
+            .ok_or_else(|| Error::custom("Prefixes in geofeed are not held in a single resource class"))?;
+
+        let key = signer.create_key()?;
+        let signed = rc
+            .create_rta_ee(&resources, validity, key, signer)
+            .and_then(|ee| geofeed.sign(ee, signer));
+        signer.destroy_key(&key)?;
+
+        if signed.is_ok() {
+            info!("CA '{}' signed a geofeed for resources: {}", self.handle, resources);
+        }
+
+        signed
+    }
+}
+
 /// # Resource Tagged Attestations
 ///
 impl CertAuth {
@@ -2096,16 +3384,9 @@ impl CertAuth {
         signer: &KrillSigner,
     ) -> KrillResult<Vec<CaEvt>> {
         let builder = rta.to_builder()?;
+        let (resources, keys) = rta.attested()?;
 
-        let resources = {
-            let asns = builder.content().as_resources().clone();
-            let ipv4 = builder.content().v4_resources().clone();
-            let ipv6 = builder.content().v6_resources().clone();
-            ResourceSet::new(asns, ipv4.into(), ipv6.into())
-        };
-
-        let keys = builder.content().subject_keys();
-        let rc2ee = self.rta_ee_map_prepared(&name, &resources, keys, signer)?;
+        let rc2ee = self.rta_ee_map_prepared(&name, &resources, &keys, signer)?;
 
         self.rta_sign_with_ee(name, resources, rc2ee, builder, signer)
     }
@@ -4,7 +4,7 @@ use chrono::Duration;
 
 use rpki::{
     ca::{
-        idexchange::{CaHandle, ChildHandle, ParentHandle},
+        idexchange::{CaHandle, ChildHandle, ParentHandle, ServiceUri},
         provisioning::{
             IssuanceRequest, ResourceClassListResponse as Entitlements, ResourceClassName, RevocationRequest,
             RevocationResponse,
@@ -18,9 +18,10 @@ use crate::{
     commons::{
         actor::Actor,
         api::{
-            AspaCustomer, AspaDefinitionUpdates, AspaProvidersUpdate, BgpSecDefinitionUpdates, IdCertInfo,
-            ParentCaContact, ReceivedCert, RepositoryContact, RoaConfigurationUpdates, RtaName, StorableCaCommand,
-            StorableRcEntitlement,
+            AspaCustomer, AspaDefinitionUpdates, AspaProvidersUpdate, BgpSecDefinitionUpdates, GhostbusterContact,
+            IdCertInfo, OnboardingTokenId, OnboardingTokenInfo, ParentCaContact, PrefixListDefinitionUpdates,
+            ReceivedCert, RepositoryContact, RoaAs0Policy, RoaConfigurationUpdates, RoaDraftName, RoaPayload, RtaName,
+            StorableCaCommand, StorableRcEntitlement,
         },
         crypto::KrillSigner,
         eventsourcing::{self, StoredCommand},
@@ -85,6 +86,16 @@ pub enum CmdDet {
     // about to expire, and do not claim resources no longer associated with this child.
     ChildUnsuspend(ChildHandle),
 
+    // Add a single-use token which lets a new child add itself, with the
+    // resources bound to the token. Expired tokens are removed as well.
+    OnboardingTokenAdd(OnboardingTokenInfo),
+
+    // Remove an unused onboarding token.
+    OnboardingTokenRemove(OnboardingTokenId),
+
+    // Add a new child using an onboarding token, which is then removed.
+    OnboardingTokenRedeem(OnboardingTokenId, ChildHandle, IdCertInfo),
+
     // ------------------------------------------------------------
     // Being a child (only allowed if this CA is not self-signed)
     // ------------------------------------------------------------
@@ -103,6 +114,8 @@ pub enum CmdDet {
     UpdateParentContact(ParentHandle, ParentCaContact),
     // Remove a parent, freeing up its handle for future (re-)use.
     RemoveParent(ParentHandle),
+    // Limit the resources requested from a parent, or remove the limit.
+    UpdateParentResourceLimit(ParentHandle, Option<ResourceSet>),
 
     // Process new entitlements from a parent and remove/create/update
     // ResourceClasses and certificate requests or key revocation requests
@@ -116,6 +129,10 @@ pub enum CmdDet {
     // obtaining a certificate for it.
     DropResourceClass(ResourceClassName, DropReason, Arc<KrillSigner>),
 
+    // Clear the reports of resources which were removed by parents, after
+    // the operator has seen them.
+    ResourceShrinksClear,
+
     // ------------------------------------------------------------
     // Key rolls
     // ------------------------------------------------------------
@@ -140,6 +157,10 @@ pub enum CmdDet {
     // class has been revoked. I.e. remove the old key, and withdraw the crl and mft for it.
     KeyRollFinish(ResourceClassName, RevocationResponse),
 
+    // Finish the keyroll of a trust anchor. A TA has no parent to revoke its old key,
+    // so this is done explicitly once relying parties had time to pick up the new TAL.
+    TaKeyRollFinish(Arc<KrillSigner>),
+
     // ------------------------------------------------------------
     // ROA Support
     // ------------------------------------------------------------
@@ -147,7 +168,9 @@ pub enum CmdDet {
     // Update the authorizations for a CA.
     // Note: ROA *objects* will be created by the CA itself. The command just
     // contains the intent for which announcements should be authorized.
-    RouteAuthorizationsUpdate(RoaConfigurationUpdates, Arc<Config>, Arc<KrillSigner>),
+    // The payloads are those for which BGP shows that their max length
+    // allows unannounced more specifics, and they are rejected if added.
+    RouteAuthorizationsUpdate(RoaConfigurationUpdates, Vec<RoaPayload>, Arc<Config>, Arc<KrillSigner>),
 
     // Re-issue any and all ROA objects which would otherwise expire in
     // some time (default 4 weeks, configurable). Note that this command
@@ -158,6 +181,26 @@ pub enum CmdDet {
     // Re-issue all ROA objects regardless of their expiration time.
     RouteAuthorizationsForceRenew(Arc<Config>, Arc<KrillSigner>),
 
+    // Merge updates into a named draft, creating it if needed. Drafts
+    // are staged in the CA, but do not affect ROAs until they are committed.
+    RouteAuthorizationsDraftUpdate(RoaDraftName, RoaConfigurationUpdates, Vec<RoaPayload>),
+
+    // Discard a named draft.
+    RouteAuthorizationsDraftRemove(RoaDraftName),
+
+    // Refuse to activate scheduled ROA configurations, because BGP shows that
+    // their max length allows unannounced more specifics. These configurations
+    // stay scheduled, and are reported as an issue, but they are not activated.
+    RouteAuthorizationsActivationRefused(Vec<RoaPayload>),
+
+    // Apply all updates in a named draft as a single update, and remove
+    // the draft.
+    RouteAuthorizationsDraftCommit(RoaDraftName, Vec<RoaPayload>, Arc<Config>, Arc<KrillSigner>),
+
+    // Enable or disable AS0 ROAs for held space which is not covered by
+    // any authorization, and update the ROA objects accordingly.
+    RouteAuthorizationsAs0Policy(RoaAs0Policy, Arc<Config>, Arc<KrillSigner>),
+
     // ------------------------------------------------------------
     // ASPA Support
     // ------------------------------------------------------------
@@ -186,12 +229,40 @@ pub enum CmdDet {
     // expire in some time.
     BgpSecRenew(Arc<Config>, Arc<KrillSigner>),
 
+    // ------------------------------------------------------------
+    // Signed Prefix Lists
+    // ------------------------------------------------------------
+
+    // Update PrefixListDefinitions, adding new, replacing existing, or
+    // removing surplus.
+    PrefixListsUpdate(PrefixListDefinitionUpdates, Arc<Config>, Arc<KrillSigner>),
+
+    // Re-issue any and all Signed Prefix List objects which would otherwise
+    // expire in some time, using the same timing as for ROAs.
+    PrefixListsRenew(Arc<Config>, Arc<KrillSigner>),
+
+    // ------------------------------------------------------------
+    // Ghostbusters
+    // ------------------------------------------------------------
+
+    // Set, or remove, the contact details of this CA and update the
+    // Ghostbuster records in all resource classes.
+    GhostbusterUpdate(Option<GhostbusterContact>, Arc<Config>, Arc<KrillSigner>),
+
+    // Re-issue the Ghostbuster records which would otherwise expire
+    // in some time, using the same timing as for ROAs.
+    GhostbusterRenew(Arc<Config>, Arc<KrillSigner>),
+
     // ------------------------------------------------------------
     // Publishing
     // ------------------------------------------------------------
 
     // Update the repository where this CA publishes
     RepoUpdate(RepositoryContact, Arc<KrillSigner>),
+    // Add a repository which mirrors the objects published in the repository
+    RepoMirrorAdd(RepositoryContact),
+    // Remove a repository mirror, identified by its service URI
+    RepoMirrorRemove(ServiceUri),
 
     // ------------------------------------------------------------
     // Resource Tagged Attestations
@@ -259,6 +330,17 @@ impl From<CmdDet> for StorableCaCommand {
             CmdDet::ChildRemove(child) => StorableCaCommand::ChildRemove { child },
             CmdDet::ChildSuspendInactive(child) => StorableCaCommand::ChildSuspendInactive { child },
             CmdDet::ChildUnsuspend(child) => StorableCaCommand::ChildUnsuspend { child },
+            CmdDet::OnboardingTokenAdd(token) => StorableCaCommand::OnboardingTokenAdd {
+                id: token.id().clone(),
+                resources: token.resources().clone(),
+                expires: token.expires(),
+            },
+            CmdDet::OnboardingTokenRemove(id) => StorableCaCommand::OnboardingTokenRemove { id },
+            CmdDet::OnboardingTokenRedeem(id, child, id_cert) => StorableCaCommand::OnboardingTokenRedeem {
+                id,
+                child,
+                ski: id_cert.public_key().key_identifier().to_string(),
+            },
 
             // ------------------------------------------------------------
             // Being a child
@@ -273,6 +355,9 @@ impl From<CmdDet> for StorableCaCommand {
                 contact: contact.into(),
             },
             CmdDet::RemoveParent(parent) => StorableCaCommand::RemoveParent { parent },
+            CmdDet::UpdateParentResourceLimit(parent, limit) => {
+                StorableCaCommand::UpdateParentResourceLimit { parent, limit }
+            }
             CmdDet::UpdateEntitlements(parent, cmd_entitlements, _) => {
                 let mut entitlements = vec![];
                 for entitlement in cmd_entitlements.classes() {
@@ -292,6 +377,7 @@ impl From<CmdDet> for StorableCaCommand {
                 resource_class_name,
                 reason,
             },
+            CmdDet::ResourceShrinksClear => StorableCaCommand::ResourceShrinksClear,
 
             // ------------------------------------------------------------
             // Key rolls
@@ -303,13 +389,23 @@ impl From<CmdDet> for StorableCaCommand {
                 staged_for_seconds: staged_for.num_seconds(),
             },
             CmdDet::KeyRollFinish(resource_class_name, _) => StorableCaCommand::KeyRollFinish { resource_class_name },
+            CmdDet::TaKeyRollFinish(_) => StorableCaCommand::TaKeyRollFinish,
 
             // ------------------------------------------------------------
             // ROA Support
             // ------------------------------------------------------------
-            CmdDet::RouteAuthorizationsUpdate(updates, _, _) => StorableCaCommand::RoaDefinitionUpdates { updates },
+            CmdDet::RouteAuthorizationsUpdate(updates, _, _, _) => StorableCaCommand::RoaDefinitionUpdates { updates },
             CmdDet::RouteAuthorizationsRenew(_, _) => StorableCaCommand::ReissueBeforeExpiring,
             CmdDet::RouteAuthorizationsForceRenew(_, _) => StorableCaCommand::ForceReissue,
+            CmdDet::RouteAuthorizationsDraftUpdate(name, updates, _) => {
+                StorableCaCommand::RoaDraftUpdate { name, updates }
+            }
+            CmdDet::RouteAuthorizationsDraftRemove(name) => StorableCaCommand::RoaDraftRemove { name },
+            CmdDet::RouteAuthorizationsActivationRefused(refused) => {
+                StorableCaCommand::RoaActivationRefused { refused }
+            }
+            CmdDet::RouteAuthorizationsDraftCommit(name, _, _, _) => StorableCaCommand::RoaDraftCommit { name },
+            CmdDet::RouteAuthorizationsAs0Policy(policy, _, _) => StorableCaCommand::RoaAs0Policy { policy },
 
             // ------------------------------------------------------------
             // ASPA Support
@@ -326,12 +422,28 @@ impl From<CmdDet> for StorableCaCommand {
             CmdDet::BgpSecUpdateDefinitions(_, _, _) => StorableCaCommand::BgpSecDefinitionUpdates,
             CmdDet::BgpSecRenew(_, _) => StorableCaCommand::ReissueBeforeExpiring,
 
+            // ------------------------------------------------------------
+            // Signed Prefix Lists
+            // ------------------------------------------------------------
+            CmdDet::PrefixListsUpdate(updates, _, _) => StorableCaCommand::PrefixListsUpdate { updates },
+            CmdDet::PrefixListsRenew(_, _) => StorableCaCommand::ReissueBeforeExpiring,
+
+            // ------------------------------------------------------------
+            // Ghostbusters
+            // ------------------------------------------------------------
+            CmdDet::GhostbusterUpdate(contact, _, _) => StorableCaCommand::GhostbusterUpdate { contact },
+            CmdDet::GhostbusterRenew(_, _) => StorableCaCommand::ReissueBeforeExpiring,
+
             // ------------------------------------------------------------
             // Publishing
             // ------------------------------------------------------------
             CmdDet::RepoUpdate(contact, _) => StorableCaCommand::RepoUpdate {
                 service_uri: contact.server_info().service_uri().clone(),
             },
+            CmdDet::RepoMirrorAdd(contact) => StorableCaCommand::RepoMirrorAdd {
+                service_uri: contact.server_info().service_uri().clone(),
+            },
+            CmdDet::RepoMirrorRemove(service_uri) => StorableCaCommand::RepoMirrorRemove { service_uri },
 
             // ------------------------------------------------------------
             // Resource Tagged Attestations
@@ -425,6 +537,29 @@ impl CmdDet {
         eventsourcing::SentCommand::new(handle, None, CmdDet::ChildUnsuspend(child_handle), actor)
     }
 
+    pub fn onboarding_token_add(handle: &CaHandle, token: OnboardingTokenInfo, actor: &Actor) -> Cmd {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::OnboardingTokenAdd(token), actor)
+    }
+
+    pub fn onboarding_token_remove(handle: &CaHandle, id: OnboardingTokenId, actor: &Actor) -> Cmd {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::OnboardingTokenRemove(id), actor)
+    }
+
+    pub fn onboarding_token_redeem(
+        handle: &CaHandle,
+        id: OnboardingTokenId,
+        child_handle: ChildHandle,
+        id_cert: IdCertInfo,
+        actor: &Actor,
+    ) -> Cmd {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::OnboardingTokenRedeem(id, child_handle, id_cert),
+            actor,
+        )
+    }
+
     pub fn update_id(handle: &CaHandle, signer: Arc<KrillSigner>, actor: &Actor) -> Cmd {
         eventsourcing::SentCommand::new(handle, None, CmdDet::GenerateNewIdKey(signer), actor)
     }
@@ -441,6 +576,15 @@ impl CmdDet {
         eventsourcing::SentCommand::new(handle, None, CmdDet::RemoveParent(parent), actor)
     }
 
+    pub fn update_parent_resource_limit(
+        handle: &CaHandle,
+        parent: ParentHandle,
+        limit: Option<ResourceSet>,
+        actor: &Actor,
+    ) -> Cmd {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::UpdateParentResourceLimit(parent, limit), actor)
+    }
+
     pub fn update_entitlements(
         handle: &CaHandle,
         parent: ParentHandle,
@@ -487,6 +631,10 @@ impl CmdDet {
         )
     }
 
+    pub fn resource_shrinks_clear(handle: &CaHandle, actor: &Actor) -> Cmd {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::ResourceShrinksClear, actor)
+    }
+
     //-------------------------------------------------------------------------------
     // Key Rolls
     //-------------------------------------------------------------------------------
@@ -509,16 +657,88 @@ impl CmdDet {
         eventsourcing::SentCommand::new(handle, None, CmdDet::KeyRollFinish(rcn, res), actor)
     }
 
+    pub fn ta_key_roll_finish(handle: &CaHandle, signer: Arc<KrillSigner>, actor: &Actor) -> Cmd {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::TaKeyRollFinish(signer), actor)
+    }
+
     pub fn update_repo(handle: &CaHandle, contact: RepositoryContact, signer: Arc<KrillSigner>, actor: &Actor) -> Cmd {
         eventsourcing::SentCommand::new(handle, None, CmdDet::RepoUpdate(contact, signer), actor)
     }
 
+    pub fn add_repo_mirror(handle: &CaHandle, contact: RepositoryContact, actor: &Actor) -> Cmd {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::RepoMirrorAdd(contact), actor)
+    }
+
+    pub fn remove_repo_mirror(handle: &CaHandle, service_uri: ServiceUri, actor: &Actor) -> Cmd {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::RepoMirrorRemove(service_uri), actor)
+    }
+
     //-------------------------------------------------------------------------------
     // Route Authorizations
     //-------------------------------------------------------------------------------
     pub fn route_authorizations_update(
         handle: &CaHandle,
         updates: RoaConfigurationUpdates,
+        max_length_exposed: Vec<RoaPayload>,
+        config: Arc<Config>,
+        signer: Arc<KrillSigner>,
+        actor: &Actor,
+    ) -> Cmd {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::RouteAuthorizationsUpdate(updates, max_length_exposed, config, signer),
+            actor,
+        )
+    }
+
+    pub fn route_authorizations_draft_update(
+        handle: &CaHandle,
+        name: RoaDraftName,
+        updates: RoaConfigurationUpdates,
+        max_length_exposed: Vec<RoaPayload>,
+        actor: &Actor,
+    ) -> Cmd {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::RouteAuthorizationsDraftUpdate(name, updates, max_length_exposed),
+            actor,
+        )
+    }
+
+    pub fn route_authorizations_draft_remove(handle: &CaHandle, name: RoaDraftName, actor: &Actor) -> Cmd {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::RouteAuthorizationsDraftRemove(name), actor)
+    }
+
+    pub fn route_authorizations_activation_refused(handle: &CaHandle, refused: Vec<RoaPayload>, actor: &Actor) -> Cmd {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::RouteAuthorizationsActivationRefused(refused),
+            actor,
+        )
+    }
+
+    pub fn route_authorizations_draft_commit(
+        handle: &CaHandle,
+        name: RoaDraftName,
+        max_length_exposed: Vec<RoaPayload>,
+        config: Arc<Config>,
+        signer: Arc<KrillSigner>,
+        actor: &Actor,
+    ) -> Cmd {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::RouteAuthorizationsDraftCommit(name, max_length_exposed, config, signer),
+            actor,
+        )
+    }
+
+    pub fn route_authorizations_as0_policy(
+        handle: &CaHandle,
+        policy: RoaAs0Policy,
         config: Arc<Config>,
         signer: Arc<KrillSigner>,
         actor: &Actor,
@@ -526,7 +746,7 @@ impl CmdDet {
         eventsourcing::SentCommand::new(
             handle,
             None,
-            CmdDet::RouteAuthorizationsUpdate(updates, config, signer),
+            CmdDet::RouteAuthorizationsAs0Policy(policy, config, signer),
             actor,
         )
     }
@@ -578,6 +798,32 @@ impl CmdDet {
         )
     }
 
+    //-------------------------------------------------------------------------------
+    // Signed Prefix Lists
+    //-------------------------------------------------------------------------------
+    pub fn prefix_lists_update(
+        ca: &CaHandle,
+        updates: PrefixListDefinitionUpdates,
+        config: Arc<Config>,
+        signer: Arc<KrillSigner>,
+        actor: &Actor,
+    ) -> Cmd {
+        eventsourcing::SentCommand::new(ca, None, CmdDet::PrefixListsUpdate(updates, config, signer), actor)
+    }
+
+    //-------------------------------------------------------------------------------
+    // Ghostbusters
+    //-------------------------------------------------------------------------------
+    pub fn ghostbuster_update(
+        ca: &CaHandle,
+        contact: Option<GhostbusterContact>,
+        config: Arc<Config>,
+        signer: Arc<KrillSigner>,
+        actor: &Actor,
+    ) -> Cmd {
+        eventsourcing::SentCommand::new(ca, None, CmdDet::GhostbusterUpdate(contact, config, signer), actor)
+    }
+
     //-------------------------------------------------------------------------------
     // Resource Tagged Attestations
     //-------------------------------------------------------------------------------
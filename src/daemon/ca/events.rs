@@ -2,25 +2,30 @@ use std::{collections::HashMap, fmt};
 
 use rpki::{
     ca::{
-        idexchange::{CaHandle, ChildHandle, ParentHandle},
+        idexchange::{CaHandle, ChildHandle, ParentHandle, ServiceUri},
         provisioning::{IssuanceRequest, ParentResourceClassName, ResourceClassName, RevocationRequest},
     },
     crypto::KeyIdentifier,
-    repository::resources::ResourceSet,
+    repository::resources::{Asn, ResourceSet},
 };
 
 use crate::{
     commons::{
         api::{
-            AspaCustomer, AspaDefinition, AspaProvidersUpdate, BgpSecAsnKey, IdCertInfo, IssuedCertificate, ObjectName,
-            ParentCaContact, ReceivedCert, RepositoryContact, RoaAggregateKey, RtaName, SuspendedCert, TaCertDetails,
+            AspaCustomer, AspaDefinition, AspaProvidersUpdate, BgpSecAsnKey, GhostbusterContact, IdCertInfo,
+            IssuedCertificate, ObjectName, OnboardingTokenId, OnboardingTokenInfo, ParentCaContact,
+            PrefixListDefinition, ReceivedCert, RepositoryContact, ResourceShrink, RoaAggregateKey, RoaConfiguration,
+            RoaConfigurationUpdates, RoaDraftName, RoaMetadata, RtaName, SuspendedCert, TaCertDetails, Timestamp,
             UnsuspendedCert,
         },
         crypto::KrillSigner,
         eventsourcing::StoredEvent,
         KrillResult,
     },
-    daemon::ca::{AspaInfo, CertifiedKey, PreparedRta, RoaInfo, RoaPayloadJsonMapKey, SignedRta},
+    daemon::ca::{
+        AspaInfo, CertifiedKey, GhostbusterInfo, PrefixListInfo, PreparedRta, RoaInfo, RoaPayloadJsonMapKey, SignedRta,
+        TakInfo,
+    },
 };
 
 use super::{BgpSecCertInfo, StoredBgpSecCsr};
@@ -277,6 +282,47 @@ impl AspaObjectsUpdates {
     }
 }
 
+//------------ PrefixListObjectsUpdates ------------------------------------
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrefixListObjectsUpdates {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    updated: Vec<PrefixListInfo>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    removed: Vec<Asn>,
+}
+
+impl PrefixListObjectsUpdates {
+    pub fn add_updated(&mut self, update: PrefixListInfo) {
+        self.updated.push(update)
+    }
+
+    pub fn add_removed(&mut self, asn: Asn) {
+        self.removed.push(asn)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.updated.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn contains_changes(&self) -> bool {
+        !self.is_empty()
+    }
+
+    pub fn unpack(self) -> (Vec<PrefixListInfo>, Vec<Asn>) {
+        (self.updated, self.removed)
+    }
+
+    pub fn updated(&self) -> &Vec<PrefixListInfo> {
+        &self.updated
+    }
+
+    pub fn removed(&self) -> &Vec<Asn> {
+        &self.removed
+    }
+}
+
 //------------ BgpSecCertificateUpdates ------------------------------------
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct BgpSecCertificateUpdates {
@@ -429,6 +475,23 @@ pub enum CaEvtDet {
     TrustAnchorMade {
         ta_cert_details: TaCertDetails,
     },
+    TrustAnchorNextKeyMade {
+        // The trust anchor created a new key and self-signed a certificate for it to
+        // start a key roll. The key itself is staged in a separate 'KeyPendingToNew'
+        // event, this keeps the certificate and TAL for it until it is activated.
+        ta_cert_details: TaCertDetails,
+    },
+    TrustAnchorNextKeyActivated {
+        // The new key of the trust anchor was activated, its certificate and TAL
+        // replace the current ones.
+        key: KeyIdentifier,
+    },
+    TakObjectsUpdated {
+        // Tracks the TAK objects published by the trust anchor. There is at most
+        // one TAK for each of its keys, a TAK for any key not listed is withdrawn.
+        resource_class_name: ResourceClassName,
+        taks: Vec<TakInfo>,
+    },
 
     // Being a parent Events
     /// A child was added to this (parent) CA
@@ -479,6 +542,18 @@ pub enum CaEvtDet {
         child: ChildHandle,
     },
 
+    // Onboarding tokens for new children
+    OnboardingTokenAdded {
+        token: OnboardingTokenInfo,
+    },
+    OnboardingTokenRemoved {
+        id: OnboardingTokenId,
+    },
+    OnboardingTokenRedeemed {
+        id: OnboardingTokenId,
+        child: ChildHandle,
+    },
+
     // Being a child Events
     IdUpdated {
         id: Rfc8183Id,
@@ -494,6 +569,10 @@ pub enum CaEvtDet {
     ParentRemoved {
         parent: ParentHandle,
     },
+    ParentResourceLimitUpdated {
+        parent: ParentHandle,
+        limit: Option<ResourceSet>,
+    },
     ResourceClassAdded {
         resource_class_name: ResourceClassName,
         parent: ParentHandle,
@@ -515,6 +594,10 @@ pub enum CaEvtDet {
         rcvd_cert: ReceivedCert,
         ki: KeyIdentifier, // Also in received cert. Drop?
     },
+    ResourcesShrunk {
+        shrink: ResourceShrink,
+    },
+    ResourceShrinksCleared,
 
     // Key life cycle
     KeyRollPendingKeyAdded {
@@ -574,15 +657,58 @@ pub enum CaEvtDet {
         auth: RoaPayloadJsonMapKey,
         comment: Option<String>,
     },
+    RouteAuthorizationMetadata {
+        // Replaces the metadata (owner, ticket, tags etc.) for an authorization.
+        auth: RoaPayloadJsonMapKey,
+        metadata: RoaMetadata,
+    },
     RouteAuthorizationRemoved {
         // Tracks a single authorization (VRP) which is removed. See remark for RouteAuthorizationAdded.
         auth: RoaPayloadJsonMapKey,
     },
+    RouteAuthorizationTimeBounds {
+        // Sets the time bounds for an authorization. It will be removed at the
+        // 'not_after' time, if set.
+        auth: RoaPayloadJsonMapKey,
+        not_before: Option<Timestamp>,
+        not_after: Option<Timestamp>,
+    },
+    RouteAuthorizationScheduled {
+        // Tracks a configuration with a 'not_before' time in the future. It will be
+        // added, using a normal update, when that time has come.
+        configuration: RoaConfiguration,
+    },
+    RouteAuthorizationUnscheduled {
+        // Tracks that a scheduled configuration was removed, or activated.
+        auth: RoaPayloadJsonMapKey,
+    },
+    RouteAuthorizationActivationRefused {
+        // Tracks that a scheduled configuration was not activated at its 'not_before'
+        // time, because strict ROA max lengths are used and BGP does not show all the
+        // more specific announcements it would allow. It stays scheduled, so that it
+        // is reported, until it expires or is removed or scheduled again.
+        auth: RoaPayloadJsonMapKey,
+    },
     RoasUpdated {
         // Tracks ROA *objects* which are (re-)issued in a resource class.
         resource_class_name: ResourceClassName,
         updates: RoaUpdates,
     },
+    RoaDraftUpdated {
+        // Tracks the complete content of a named draft after it was updated. Drafts
+        // are not applied to the authorizations until they are committed, at which
+        // point the normal route authorization events are followed by RoaDraftRemoved.
+        name: RoaDraftName,
+        updates: RoaConfigurationUpdates,
+    },
+    RoaDraftRemoved {
+        name: RoaDraftName,
+    },
+    RouteAuthorizationAs0Policy {
+        // Tracks whether AS0 ROAs are issued for held space which is not
+        // covered by any authorization. These ROAs follow from RoasUpdated.
+        enabled: bool,
+    },
 
     // ASPA
     AspaConfigAdded {
@@ -619,6 +745,34 @@ pub enum CaEvtDet {
         updates: BgpSecCertificateUpdates,
     },
 
+    // Signed Prefix Lists
+    PrefixListDefinitionAdded {
+        definition: PrefixListDefinition,
+    },
+    PrefixListDefinitionUpdated {
+        definition: PrefixListDefinition,
+    },
+    PrefixListDefinitionRemoved {
+        asn: Asn,
+    },
+    PrefixListObjectsUpdated {
+        // Tracks the Signed Prefix List objects (re-)issued in a resource class
+        resource_class_name: ResourceClassName,
+        updates: PrefixListObjectsUpdates,
+    },
+
+    // Ghostbusters
+    GhostbusterContactUpdated {
+        // Sets or removes the contact details of the CA.
+        contact: Option<GhostbusterContact>,
+    },
+    GhostbusterObjectUpdated {
+        // Tracks the Ghostbuster record published in a resource class. If there
+        // is no record, then the current record (if any) is withdrawn.
+        resource_class_name: ResourceClassName,
+        ghostbuster: Option<GhostbusterInfo>,
+    },
+
     // Publishing
     RepoUpdated {
         // Adds the repository contact for this CA so that publication can commence,
@@ -626,6 +780,16 @@ pub enum CaEvtDet {
         // requesting certificates when it knows which URIs it can use.
         contact: RepositoryContact,
     },
+    RepoMirrorAdded {
+        // Adds a repository which gets the same objects as the repository
+        // above. Failures to publish to a mirror are tracked separately.
+        contact: RepositoryContact,
+    },
+    RepoMirrorRemoved {
+        // Removes a repository mirror. Objects are withdrawn from it in the
+        // same way as they are for a deprecated repository.
+        service_uri: ServiceUri,
+    },
 
     // Rta
     //
@@ -780,6 +944,24 @@ impl fmt::Display for CaEvtDet {
                 "turn into TA with key (hash) {}",
                 ta_cert_details.cert().key_identifier()
             ),
+            CaEvtDet::TrustAnchorNextKeyMade { ta_cert_details } => write!(
+                f,
+                "TA key roll: created new key (hash) {}",
+                ta_cert_details.cert().key_identifier()
+            ),
+            CaEvtDet::TrustAnchorNextKeyActivated { key } => {
+                write!(f, "TA key roll: activated new key (hash) {}", key)
+            }
+            CaEvtDet::TakObjectsUpdated {
+                resource_class_name,
+                taks,
+            } => {
+                write!(f, "updated TAK objects under resource class '{}'", resource_class_name)?;
+                for tak in taks {
+                    write!(f, " {}", tak.name())?;
+                }
+                Ok(())
+            }
 
             // Being a parent Events
             CaEvtDet::ChildAdded {
@@ -867,6 +1049,17 @@ impl fmt::Display for CaEvtDet {
             CaEvtDet::ChildRemoved { child } => write!(f, "removed child '{}'", child),
             CaEvtDet::ChildSuspended { child } => write!(f, "suspended child '{}'", child),
             CaEvtDet::ChildUnsuspended { child } => write!(f, "unsuspended child '{}'", child),
+            CaEvtDet::OnboardingTokenAdded { token } => write!(
+                f,
+                "added onboarding token '{}' for resources '{}' expiring '{}'",
+                token.id(),
+                token.resources(),
+                token.expires().to_rfc3339()
+            ),
+            CaEvtDet::OnboardingTokenRemoved { id } => write!(f, "removed onboarding token '{}'", id),
+            CaEvtDet::OnboardingTokenRedeemed { id, child } => {
+                write!(f, "child '{}' used onboarding token '{}'", child, id)
+            }
 
             // Being a child Events
             CaEvtDet::IdUpdated { id } => write!(
@@ -889,6 +1082,10 @@ impl fmt::Display for CaEvtDet {
                 write!(f, "updated parent '{}' contact to '{}' ", parent, contact_str)
             }
             CaEvtDet::ParentRemoved { parent } => write!(f, "removed parent '{}'", parent),
+            CaEvtDet::ParentResourceLimitUpdated { parent, limit } => match limit {
+                Some(limit) => write!(f, "limited resources requested from parent '{}' to '{}'", parent, limit),
+                None => write!(f, "removed resource limit for parent '{}'", parent),
+            },
 
             CaEvtDet::ResourceClassAdded {
                 resource_class_name, ..
@@ -920,6 +1117,10 @@ impl fmt::Display for CaEvtDet {
                 "received certificate for key (hash) '{}' under resource class '{}'",
                 ki, resource_class_name
             ),
+            CaEvtDet::ResourcesShrunk { shrink } => {
+                write!(f, "parent '{}' removed resources: {}", shrink.parent(), shrink.lost())
+            }
+            CaEvtDet::ResourceShrinksCleared => write!(f, "cleared reports of removed resources"),
 
             // Key life cycle
             CaEvtDet::KeyRollPendingKeyAdded {
@@ -981,6 +1182,38 @@ impl fmt::Display for CaEvtDet {
                     write!(f, "removed comment from ROA: '{}'", auth)
                 }
             }
+            CaEvtDet::RouteAuthorizationMetadata { auth, metadata } => {
+                if metadata.is_empty() {
+                    write!(f, "removed metadata from ROA: '{}'", auth)
+                } else {
+                    write!(f, "updated metadata for ROA: '{}' => {}", auth, metadata)
+                }
+            }
+            CaEvtDet::RouteAuthorizationTimeBounds {
+                auth,
+                not_before,
+                not_after,
+            } => {
+                write!(f, "updated time bounds for ROA: '{}'", auth)?;
+                if let Some(not_before) = not_before {
+                    write!(f, " not before: {}", not_before.to_rfc3339())?;
+                }
+                if let Some(not_after) = not_after {
+                    write!(f, " not after: {}", not_after.to_rfc3339())?;
+                }
+                Ok(())
+            }
+            CaEvtDet::RouteAuthorizationScheduled { configuration } => {
+                write!(f, "scheduled ROA: '{}'", configuration)?;
+                if let Some(not_before) = configuration.not_before() {
+                    write!(f, " to be added at: {}", not_before.to_rfc3339())?;
+                }
+                Ok(())
+            }
+            CaEvtDet::RouteAuthorizationUnscheduled { auth } => write!(f, "unscheduled ROA: '{}'", auth),
+            CaEvtDet::RouteAuthorizationActivationRefused { auth } => {
+                write!(f, "refused activation of scheduled ROA: '{}'", auth)
+            }
             CaEvtDet::RouteAuthorizationRemoved { auth } => write!(f, "removed ROA: '{}'", auth),
             CaEvtDet::RoasUpdated {
                 resource_class_name,
@@ -1007,6 +1240,21 @@ impl fmt::Display for CaEvtDet {
                 }
                 Ok(())
             }
+            CaEvtDet::RoaDraftUpdated { name, updates } => write!(
+                f,
+                "updated ROA draft '{}', added: {} removed: {}",
+                name,
+                updates.added().len(),
+                updates.removed().len()
+            ),
+            CaEvtDet::RoaDraftRemoved { name } => write!(f, "removed ROA draft '{}'", name),
+            CaEvtDet::RouteAuthorizationAs0Policy { enabled } => {
+                if *enabled {
+                    write!(f, "enabled AS0 ROAs for unrouted space")
+                } else {
+                    write!(f, "disabled AS0 ROAs for unrouted space")
+                }
+            }
 
             // Autonomous System Provider Authorization
             CaEvtDet::AspaConfigAdded { aspa_config: addition } => write!(f, "{}", addition),
@@ -1085,6 +1333,64 @@ impl fmt::Display for CaEvtDet {
                 Ok(())
             }
 
+            // Signed Prefix Lists
+            CaEvtDet::PrefixListDefinitionAdded { definition } => {
+                write!(f, "added prefix list definition: {}", definition)
+            }
+            CaEvtDet::PrefixListDefinitionUpdated { definition } => {
+                write!(f, "updated prefix list definition: {}", definition)
+            }
+            CaEvtDet::PrefixListDefinitionRemoved { asn } => {
+                write!(f, "removed prefix list definition for ASN: {}", asn)
+            }
+            CaEvtDet::PrefixListObjectsUpdated {
+                resource_class_name,
+                updates,
+            } => {
+                write!(
+                    f,
+                    "updated prefix list objects under resource class '{}'",
+                    resource_class_name
+                )?;
+                if !updates.updated().is_empty() {
+                    write!(f, " updated:")?;
+                    for upd in updates.updated() {
+                        write!(f, " {}", ObjectName::prefix_list(upd.asn()))?;
+                    }
+                }
+                if !updates.removed().is_empty() {
+                    write!(f, " removed:")?;
+                    for rem in updates.removed() {
+                        write!(f, " {}", ObjectName::prefix_list(*rem))?;
+                    }
+                }
+                Ok(())
+            }
+
+            // Ghostbusters
+            CaEvtDet::GhostbusterContactUpdated { contact } => match contact {
+                Some(contact) => write!(f, "updated Ghostbuster contact to: {}", contact.full_name()),
+                None => write!(f, "removed Ghostbuster contact"),
+            },
+            CaEvtDet::GhostbusterObjectUpdated {
+                resource_class_name,
+                ghostbuster,
+            } => {
+                if ghostbuster.is_some() {
+                    write!(
+                        f,
+                        "updated Ghostbuster record under resource class '{}'",
+                        resource_class_name
+                    )
+                } else {
+                    write!(
+                        f,
+                        "removed Ghostbuster record under resource class '{}'",
+                        resource_class_name
+                    )
+                }
+            }
+
             // Publishing
             CaEvtDet::RepoUpdated { contact } => {
                 write!(
@@ -1093,6 +1399,16 @@ impl fmt::Display for CaEvtDet {
                     contact.server_info().service_uri()
                 )
             }
+            CaEvtDet::RepoMirrorAdded { contact } => {
+                write!(
+                    f,
+                    "added repository mirror at remote server: {}",
+                    contact.server_info().service_uri()
+                )
+            }
+            CaEvtDet::RepoMirrorRemoved { service_uri } => {
+                write!(f, "removed repository mirror at remote server: {}", service_uri)
+            }
 
             // Rta
             CaEvtDet::RtaPrepared { name, prepared } => {
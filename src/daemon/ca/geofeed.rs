@@ -0,0 +1,233 @@
+//! Support for signing geofeed files.
+//!
+//! See: https://www.rfc-editor.org/rfc/rfc9632.html
+use std::{fmt, str::FromStr};
+
+use bcder::{ConstOid, Oid};
+
+use rpki::repository::{resources::ResourceSet, x509::Validity, Cert};
+
+use crate::{
+    commons::{api::TypedPrefix, crypto::KrillSigner, error::Error, KrillResult},
+    daemon::ca::rsc::sign_cms,
+};
+
+//------------ Object Identifiers --------------------------------------------
+
+/// id-ct-geofeedCSVwithCRLF, RFC 9632
+const GEOFEED_CSV_WITH_CRLF: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 9, 16, 1, 47]);
+
+/// The first line of the signature block, followed by the address range.
+const SIGNATURE_START: &str = "# RPKI Signature:";
+
+/// The last line of the signature block, followed by the address range.
+const SIGNATURE_END: &str = "# End Signature:";
+
+/// The number of Base64 characters on each line of the signature block.
+const SIGNATURE_LINE_LEN: usize = 64;
+
+//------------ GeofeedSignRequest --------------------------------------------
+
+/// A request to sign a geofeed CSV file, using an EE certificate with the
+/// given validity.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GeofeedSignRequest {
+    validity: Validity,
+    csv: String,
+}
+
+impl GeofeedSignRequest {
+    pub fn new(validity: Validity, csv: String) -> Self {
+        GeofeedSignRequest { validity, csv }
+    }
+
+    pub fn unpack(self) -> (Validity, String) {
+        (self.validity, self.csv)
+    }
+}
+
+//------------ Geofeed -------------------------------------------------------
+
+/// A geofeed CSV file (RFC 8805) as it will be signed.
+///
+/// Lines are terminated with CRLF, as required for the signed content, and
+/// an existing signature block is dropped so that a feed can be re-signed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Geofeed {
+    content: String,
+    prefixes: Vec<TypedPrefix>,
+}
+
+impl Geofeed {
+    /// Parses the CSV and finds the prefix on each line. Comments and
+    /// empty lines are kept in the content, but are not parsed.
+    pub fn parse(csv: &str) -> KrillResult<Self> {
+        let mut content = String::new();
+        let mut prefixes = vec![];
+
+        for (nr, line) in csv.lines().enumerate() {
+            if line.starts_with(SIGNATURE_START) {
+                break;
+            }
+
+            content.push_str(line);
+            content.push_str("\r\n");
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let prefix_str = line.split(',').next().unwrap_or_default().trim();
+            let prefix = TypedPrefix::from_str(prefix_str).map_err(|_| {
+                Error::Custom(format!(
+                    "Invalid prefix in geofeed on line {}: '{}'",
+                    nr + 1,
+                    prefix_str
+                ))
+            })?;
+            prefixes.push(prefix);
+        }
+
+        if prefixes.is_empty() {
+            return Err(Error::custom("A geofeed needs at least one prefix"));
+        }
+
+        Ok(Geofeed { content, prefixes })
+    }
+
+    pub fn prefixes(&self) -> &Vec<TypedPrefix> {
+        &self.prefixes
+    }
+
+    /// Returns the resources covering all prefixes in this geofeed. These
+    /// are used as-is in the EE certificate.
+    pub fn resources(&self) -> KrillResult<ResourceSet> {
+        let v4: Vec<String> = self
+            .prefixes
+            .iter()
+            .filter(|p| p.is_v4())
+            .map(|p| p.to_string())
+            .collect();
+        let v6: Vec<String> = self
+            .prefixes
+            .iter()
+            .filter(|p| !p.is_v4())
+            .map(|p| p.to_string())
+            .collect();
+
+        ResourceSet::from_strs("", &v4.join(", "), &v6.join(", "))
+            .map_err(|e| Error::Custom(format!("Invalid resources in geofeed: {}", e)))
+    }
+
+    /// Signs the content with a detached CMS signature using the key of the
+    /// EE certificate, and appends the signature block.
+    pub fn sign(self, ee: Cert, signer: &KrillSigner) -> KrillResult<SignedGeofeed> {
+        let cms = sign_cms(&GEOFEED_CSV_WITH_CRLF, self.content.as_bytes(), true, &ee, signer)?;
+        let range = Self::address_range(&self.resources()?);
+
+        let mut csv = self.content;
+        csv.push_str(&Self::signature_block(&range, &base64::encode(cms)));
+
+        Ok(SignedGeofeed { csv })
+    }
+
+    /// Returns the address range for the signature block header.
+    fn address_range(resources: &ResourceSet) -> String {
+        let mut ranges = vec![];
+        if !resources.ipv4().is_empty() {
+            ranges.push(resources.ipv4().to_string());
+        }
+        if !resources.ipv6().is_empty() {
+            ranges.push(resources.ipv6().to_string());
+        }
+        ranges.join(", ")
+    }
+
+    /// Formats the signature block, with each line of the Base64 encoded
+    /// CMS prefixed by "# " and terminated with CRLF.
+    fn signature_block(range: &str, cms_base64: &str) -> String {
+        let mut block = format!("{} {}\r\n", SIGNATURE_START, range);
+        for chunk in cms_base64.as_bytes().chunks(SIGNATURE_LINE_LEN) {
+            block.push_str("# ");
+            block.push_str(&String::from_utf8_lossy(chunk));
+            block.push_str("\r\n");
+        }
+        block.push_str(&format!("{} {}\r\n", SIGNATURE_END, range));
+        block
+    }
+}
+
+//------------ SignedGeofeed -------------------------------------------------
+
+/// A geofeed CSV file with the signature block appended, ready to publish.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SignedGeofeed {
+    csv: String,
+}
+
+impl SignedGeofeed {
+    pub fn as_str(&self) -> &str {
+        &self.csv
+    }
+}
+
+impl fmt::Display for SignedGeofeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.csv)
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_geofeed() {
+        let csv = "# our feed\n192.0.2.0/24,NL,NL-NH,Amsterdam,\n\n2001:db8::/32,NL,,,\n";
+        let geofeed = Geofeed::parse(csv).unwrap();
+
+        assert_eq!(
+            geofeed.content,
+            "# our feed\r\n192.0.2.0/24,NL,NL-NH,Amsterdam,\r\n\r\n2001:db8::/32,NL,,,\r\n"
+        );
+        assert_eq!(geofeed.prefixes().len(), 2);
+        assert_eq!(
+            geofeed.resources().unwrap(),
+            ResourceSet::from_strs("", "192.0.2.0/24", "2001:db8::/32").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_geofeed_drops_signature() {
+        let csv = concat!(
+            "192.0.2.0/24,NL,,,\r\n",
+            "# RPKI Signature: 192.0.2.0/24\r\n",
+            "# MIIGlwYJKoZIhvcNAQcCoIIGiDCCBoQCAQMxDTALBglghkgBZQMEAgEwDQYLKoZ\r\n",
+            "# End Signature: 192.0.2.0/24\r\n",
+        );
+        let geofeed = Geofeed::parse(csv).unwrap();
+        assert_eq!(geofeed.content, "192.0.2.0/24,NL,,,\r\n");
+    }
+
+    #[test]
+    fn parse_geofeed_rejects_invalid() {
+        assert!(Geofeed::parse("# only a comment\n").is_err());
+        assert!(Geofeed::parse("192.0.2.0/24,NL,,,\nnot-a-prefix,NL,,,\n").is_err());
+    }
+
+    #[test]
+    fn format_signature_block() {
+        let cms_base64 = "A".repeat(70);
+        let block = Geofeed::signature_block("192.0.2.0/24", &cms_base64);
+
+        let expected = format!(
+            "# RPKI Signature: 192.0.2.0/24\r\n# {}\r\n# {}\r\n# End Signature: 192.0.2.0/24\r\n",
+            "A".repeat(64),
+            "A".repeat(6)
+        );
+        assert_eq!(block, expected);
+    }
+}
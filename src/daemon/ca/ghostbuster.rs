@@ -0,0 +1,254 @@
+//! Ghostbuster records
+//!
+//! A CA publishes its contact details as a vCard in a Ghostbuster record in
+//! each of its resource classes.
+//!
+//! See: https://www.rfc-editor.org/rfc/rfc6493.html
+
+use bcder::{encode::Values, ConstOid, Mode, Oid};
+use bytes::Bytes;
+
+use rpki::{
+    ca::publication::Base64,
+    repository::{
+        sigobj::SignedObjectBuilder,
+        x509::{Serial, Time},
+    },
+};
+
+use crate::{
+    commons::{
+        api::{GhostbusterContact, ObjectName},
+        crypto::KrillSigner,
+        KrillResult,
+    },
+    daemon::{ca::CertifiedKey, config::IssuanceTimingConfig},
+};
+
+//------------ Object Identifiers --------------------------------------------
+
+/// id-ct-rpkiGhostbusters, RFC 6493
+pub const GHOSTBUSTERS: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 9, 16, 1, 35]);
+
+//------------ GhostbusterObject -------------------------------------------
+
+/// The Ghostbuster record held by a resource class in a CA, if any.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GhostbusterObject(Option<GhostbusterInfo>);
+
+impl GhostbusterObject {
+    /// Returns the Ghostbuster record to publish for the given contact,
+    /// or `None` if there is no change. An outer `Some(None)` means that
+    /// the current record should be removed.
+    pub fn update(
+        &self,
+        contact: Option<&GhostbusterContact>,
+        certified_key: &CertifiedKey,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<Option<Option<GhostbusterInfo>>> {
+        match (contact, &self.0) {
+            (None, None) => Ok(None),
+            (None, Some(_)) => Ok(Some(None)),
+            (Some(contact), Some(existing)) if existing.contact() == contact => Ok(None),
+            (Some(contact), _) => {
+                let info = GhostbusterInfo::sign(contact.clone(), certified_key, issuance_timing, signer)?;
+                Ok(Some(Some(info)))
+            }
+        }
+    }
+
+    /// Re-issues the current record. If the renew_threshold is specified,
+    /// then the record will only be renewed if it would expire before that
+    /// time.
+    pub fn renew(
+        &self,
+        certified_key: &CertifiedKey,
+        renew_threshold: Option<Time>,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<Option<GhostbusterInfo>> {
+        match &self.0 {
+            Some(existing) if renew_threshold.map(|t| existing.expires() < t).unwrap_or(true) => {
+                let contact = existing.contact().clone();
+                GhostbusterInfo::sign(contact, certified_key, issuance_timing, signer).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub fn updated(&mut self, ghostbuster: Option<GhostbusterInfo>) {
+        self.0 = ghostbuster;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+//------------ GhostbusterInfo ---------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GhostbusterInfo {
+    // The contact included in this record
+    contact: GhostbusterContact,
+
+    // The serial number (needed for revocation)
+    serial: Serial,
+
+    // The time this object expires
+    expires: Time,
+
+    // The actual Ghostbuster object in base64 format.
+    base64: Base64,
+}
+
+impl GhostbusterInfo {
+    /// Signs a Ghostbuster record for the contact under the given key.
+    pub fn sign(
+        contact: GhostbusterContact,
+        certified_key: &CertifiedKey,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<Self> {
+        let incoming_cert = certified_key.incoming_cert();
+
+        let object_builder = {
+            let crl_uri = incoming_cert.crl_uri();
+            let gbr_uri = incoming_cert.uri_for_name(&ObjectName::ghostbuster());
+            let ca_issuer = incoming_cert.uri().clone();
+
+            let mut object_builder = SignedObjectBuilder::new(
+                signer.random_serial()?,
+                issuance_timing.new_roa_validity(),
+                crl_uri,
+                ca_issuer,
+                gbr_uri,
+            );
+            object_builder.set_issuer(Some(incoming_cert.subject().clone()));
+            object_builder.set_signing_time(Some(Time::now()));
+            object_builder.set_v4_resources_inherit();
+            object_builder.set_v6_resources_inherit();
+            object_builder.set_as_resources_inherit();
+
+            object_builder
+        };
+
+        let content = Bytes::from(contact.to_vcard());
+        let object = signer.sign_object(
+            Oid(GHOSTBUSTERS.0.into()),
+            content,
+            object_builder,
+            certified_key.key_id(),
+        )?;
+        let base64 = Base64::from_content(&object.encode_ref().to_captured(Mode::Der).into_bytes());
+
+        Ok(GhostbusterInfo {
+            contact,
+            serial: object.cert().serial_number(),
+            expires: object.cert().validity().not_after(),
+            base64,
+        })
+    }
+
+    pub fn contact(&self) -> &GhostbusterContact {
+        &self.contact
+    }
+
+    pub fn serial(&self) -> Serial {
+        self.serial
+    }
+
+    pub fn expires(&self) -> Time {
+        self.expires
+    }
+
+    pub fn base64(&self) -> &Base64 {
+        &self.base64
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rpki::{
+        ca::idexchange::RepoInfo,
+        repository::{sigobj::SignedObject, tal::TalInfo},
+    };
+
+    use super::*;
+
+    use crate::{
+        commons::crypto::{KrillSignerBuilder, SignSupport},
+        daemon::config::ConfigDefaults,
+        test,
+    };
+
+    #[test]
+    fn sign_ghostbuster_record() {
+        test::test_under_tmp(|d| {
+            let signers = ConfigDefaults::signers();
+            let signer = KrillSignerBuilder::new(&d, Duration::from_secs(1), &signers)
+                .build()
+                .unwrap();
+            let timing = IssuanceTimingConfig::for_ta_signer(12, 52);
+
+            let key = signer.create_key().unwrap();
+            let repo_info = RepoInfo::new(test::rsync("rsync://localhost/repo/ta/"), None);
+            let tal_https = vec![test::https("https://localhost/ta/ta.cer")];
+            let tal_rsync = test::rsync("rsync://localhost/repo/ta/ta.cer");
+
+            let details = SignSupport::make_ta_cert_details(&key, tal_https, tal_rsync, &repo_info, &signer).unwrap();
+            let certified_key = CertifiedKey::create(details.cert().clone());
+
+            let contact = GhostbusterContact::new(
+                "Network Operations".to_string(),
+                None,
+                None,
+                None,
+                Some("noc@example.com".to_string()),
+            );
+
+            let mut object = GhostbusterObject::default();
+            let info = object
+                .update(Some(&contact), &certified_key, &timing, &signer)
+                .unwrap()
+                .unwrap()
+                .unwrap();
+
+            let decoded = SignedObject::decode(info.base64().to_bytes(), true).unwrap();
+            assert_eq!(decoded.content_type(), &GHOSTBUSTERS);
+            assert_eq!(decoded.content().to_bytes(), Bytes::from(contact.to_vcard()));
+
+            // RFC 6493 requires that the EE certificate inherits all resources
+            assert!(decoded.cert().v4_resources().is_inherited());
+            assert!(decoded.cert().v6_resources().is_inherited());
+            assert!(decoded.cert().as_resources().is_inherited());
+
+            // The record must validate strictly under the issuing certificate
+            let issuer = details
+                .cert()
+                .to_cert()
+                .unwrap()
+                .validate_ta(TalInfo::from_name("ta".to_string()).into(), true)
+                .unwrap();
+            let (_, vcard) = decoded.process(&issuer, true, |_| Ok(())).unwrap();
+            assert_eq!(vcard, Bytes::from(contact.to_vcard()));
+
+            // No change needed for the same contact, but the record is removed
+            // when there is no contact anymore.
+            object.updated(Some(info));
+            assert!(object
+                .update(Some(&contact), &certified_key, &timing, &signer)
+                .unwrap()
+                .is_none());
+            assert_eq!(
+                object.update(None, &certified_key, &timing, &signer).unwrap(),
+                Some(None)
+            );
+        })
+    }
+}
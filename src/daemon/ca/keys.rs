@@ -108,6 +108,18 @@ impl CertifiedKey {
             return true;
         }
 
+        // We may also hold resources that we no longer wish to request, e.g.
+        // because a resource limit was set for the parent.
+        let excess = self.incoming_cert.resources().difference(new_resources);
+
+        if !excess.is_empty() {
+            info!(
+                "Will request new certificate for CA '{}' under RC '{}'. Resources no longer requested: '{}'",
+                handle, rcn, excess
+            );
+            return true;
+        }
+
         // If the remaining validity time eligibility has changed by more than 10% then we will
         // want to request a new certificate.
         //
@@ -337,45 +349,49 @@ impl KeyState {
         handle: &CaHandle,
         rcn: ResourceClassName,
         entitlement: &ResourceClassEntitlements,
+        limit: &RequestResourceLimit,
         base_repo: &RepoInfo,
         name_space: &str,
         signer: &KrillSigner,
     ) -> KrillResult<Vec<CaEvtDet>> {
         let mut keys_for_requests = vec![];
 
+        // Only the resources within the limit are expected on our certificates.
+        let resources = limit.apply_to(entitlement.resource_set())?;
+
         match self {
             KeyState::Pending(pending) => {
                 keys_for_requests.push((base_repo, pending.key_id()));
             }
             KeyState::Active(current) => {
-                if current.wants_update(handle, &rcn, entitlement.resource_set(), entitlement.not_after()) {
+                if current.wants_update(handle, &rcn, &resources, entitlement.not_after()) {
                     let repo = current.old_repo.as_ref().unwrap_or(base_repo);
                     keys_for_requests.push((repo, current.key_id()));
                 }
             }
             KeyState::RollPending(pending, current) => {
                 keys_for_requests.push((base_repo, pending.key_id()));
-                if current.wants_update(handle, &rcn, entitlement.resource_set(), entitlement.not_after()) {
+                if current.wants_update(handle, &rcn, &resources, entitlement.not_after()) {
                     let repo = current.old_repo.as_ref().unwrap_or(base_repo);
                     keys_for_requests.push((repo, current.key_id()));
                 }
             }
             KeyState::RollNew(new, current) => {
-                if new.wants_update(handle, &rcn, entitlement.resource_set(), entitlement.not_after()) {
+                if new.wants_update(handle, &rcn, &resources, entitlement.not_after()) {
                     let repo = new.old_repo.as_ref().unwrap_or(base_repo);
                     keys_for_requests.push((repo, new.key_id()));
                 }
-                if current.wants_update(handle, &rcn, entitlement.resource_set(), entitlement.not_after()) {
+                if current.wants_update(handle, &rcn, &resources, entitlement.not_after()) {
                     let repo = current.old_repo.as_ref().unwrap_or(base_repo);
                     keys_for_requests.push((repo, current.key_id()));
                 }
             }
             KeyState::RollOld(current, old) => {
-                if current.wants_update(handle, &rcn, entitlement.resource_set(), entitlement.not_after()) {
+                if current.wants_update(handle, &rcn, &resources, entitlement.not_after()) {
                     let repo = current.old_repo.as_ref().unwrap_or(base_repo);
                     keys_for_requests.push((repo, current.key_id()));
                 }
-                if old.wants_update(handle, &rcn, entitlement.resource_set(), entitlement.not_after()) {
+                if old.wants_update(handle, &rcn, &resources, entitlement.not_after()) {
                     let repo = old.old_repo.as_ref().unwrap_or(base_repo);
                     keys_for_requests.push((repo, current.key_id()));
                 }
@@ -385,8 +401,14 @@ impl KeyState {
         let mut res = vec![];
 
         for (base_repo, key_id) in keys_for_requests.into_iter() {
-            let req =
-                self.create_issuance_req(base_repo, name_space, entitlement.class_name().clone(), key_id, signer)?;
+            let req = self.create_issuance_req(
+                base_repo,
+                name_space,
+                entitlement.class_name().clone(),
+                limit.clone(),
+                key_id,
+                signer,
+            )?;
 
             res.push(CaEvtDet::CertificateRequested {
                 resource_class_name: rcn.clone(),
@@ -415,6 +437,7 @@ impl KeyState {
     pub fn request_certs_new_repo(
         &self,
         rcn: ResourceClassName,
+        limit: &RequestResourceLimit,
         base_repo: &RepoInfo,
         name_space: &str,
         signer: &KrillSigner,
@@ -430,7 +453,7 @@ impl KeyState {
         };
 
         for ki in keys {
-            let req = self.create_issuance_req(base_repo, name_space, rcn.clone(), ki, signer)?;
+            let req = self.create_issuance_req(base_repo, name_space, rcn.clone(), limit.clone(), ki, signer)?;
             res.push(CaEvtDet::CertificateRequested {
                 resource_class_name: rcn.clone(),
                 req,
@@ -483,17 +506,19 @@ impl KeyState {
         res
     }
 
-    /// Creates a Csr for the given key.
+    /// Creates a Csr for the given key, and includes it in a request with
+    /// the given resource limit.
     fn create_issuance_req(
         &self,
         base_repo: &RepoInfo,
         name_space: &str,
         class_name: ResourceClassName,
+        limit: RequestResourceLimit,
         key: &KeyIdentifier,
         signer: &KrillSigner,
     ) -> KrillResult<IssuanceRequest> {
         let csr = signer.sign_csr(base_repo, name_space, key)?;
-        Ok(IssuanceRequest::new(class_name, RequestResourceLimit::default(), csr))
+        Ok(IssuanceRequest::new(class_name, limit, csr))
     }
 
     /// Returns the revoke request if there is an old key.
@@ -537,6 +562,7 @@ impl KeyState {
         &self,
         resource_class_name: ResourceClassName,
         parent_class_name: ResourceClassName,
+        limit: RequestResourceLimit,
         base_repo: &RepoInfo,
         name_space: &str,
         signer: &KrillSigner,
@@ -546,7 +572,7 @@ impl KeyState {
                 let pending_key_id = signer.create_key()?;
 
                 let req =
-                    self.create_issuance_req(base_repo, name_space, parent_class_name, &pending_key_id, signer)?;
+                    self.create_issuance_req(base_repo, name_space, parent_class_name, limit, &pending_key_id, signer)?;
 
                 Ok(vec![
                     CaEvtDet::KeyRollPendingKeyAdded {
@@ -24,33 +24,44 @@ use crate::{
     commons::{
         actor::Actor,
         api::{
-            rrdp::PublishElement, BgpSecCsrInfoList, BgpSecDefinitionUpdates, ParentServerInfo, PublicationServerInfo,
-            RoaConfigurationUpdates, Timestamp,
+            rrdp::PublishElement, BgpSecCsrInfoList, BgpSecDefinition, BgpSecDefinitionUpdates, BgpSecRouterKey,
+            BgpSecRouterKeyRequest, CaPublishedObjects, ObjectName, ParentServerInfo, PublicationServerInfo,
+            RoaAs0Policy, RoaConfiguration, RoaConfigurationUpdates, RoaDraftName, RoaPayload, Timestamp,
         },
         api::{
             AddChildRequest, AspaCustomer, AspaDefinitionList, AspaDefinitionUpdates, AspaProvidersUpdate,
-            CaCommandDetails, CaCommandResult, CertAuthList, CertAuthSummary, ChildCaInfo, CommandHistory,
-            CommandHistoryCriteria, ParentCaContact, ParentCaReq, ReceivedCert, RepositoryContact, RtaName,
-            StoredEffect, UpdateChildRequest,
+            CaCommandDetails, CaCommandResult, CaValidationResult, CertAuthList, CertAuthSummary, ChildCaInfo,
+            CommandHistory, CommandHistoryCriteria, GhostbusterContact, OnboardingToken, OnboardingTokenId,
+            OnboardingTokenInfo, OnboardingTokenList, OnboardingTokenRedeem, OnboardingTokenRequest, ParentCaContact,
+            ParentCaReq, PrefixListDefinitionList, PrefixListDefinitionUpdates, ReceivedCert, RepositoryContact,
+            RepositoryValidationReport, RtaName, StoredEffect, TaCertDetails, UpdateChildRequest, ValidationIssue,
         },
+        bgp::BgpAnalyser,
         crypto::KrillSigner,
         error::Error,
         eventsourcing::{Aggregate, AggregateStore, CommandKey},
         util::{cmslogger::CmsLogger, httpclient},
         KrillResult,
     },
-    constants::{CASERVER_DIR, STATUS_DIR},
+    constants::{CASERVER_DIR, STATUS_DIR, TA_PROXY_DIR, TA_PROXY_PUBLISHER},
     daemon::{
         auth::common::permissions::Permission,
         auth::Handle,
         ca::{
-            self, ta_handle, CaObjectsStore, CaStatus, CertAuth, Cmd, CmdDet, DeprecatedRepository, IniDet,
-            ResourceTaggedAttestation, RtaContentRequest, RtaPrepareRequest, StatusStore,
+            self, fetch_remote_objects, ta_handle, CaObjectsStore, CaStatus, CertAuth, ChildRequestThrottle, Cmd,
+            CmdDet, DeprecatedRepository, GeofeedSignRequest, IniDet, RepositoryValidator, RequestAllowance,
+            ResourceSignedChecklist, ResourceTaggedAttestation, Rfc8183Id, RscRequest, RtaContentRequest,
+            RtaPrepareRequest, SignedGeofeed, SignedObjectVerification, SignedObjectVerifyRequest, StatusStore,
         },
         config::Config,
         mq::{now, TaskQueue},
+        rtr::RtrCache,
     },
     pubd::RepositoryManager,
+    ta::{
+        TrustAnchorProxy, TrustAnchorProxyInfo, TrustAnchorProxyStatus, TrustAnchorProxyStore,
+        TrustAnchorSignedMessage, TrustAnchorSignerInfo,
+    },
 };
 
 //------------ CaManager -----------------------------------------------------
@@ -61,12 +72,24 @@ pub struct CaManager {
     ca_objects_store: Arc<CaObjectsStore>,
     status_store: Arc<StatusStore>,
 
+    // limits the rate of RFC 6492 requests from children, and caches list responses
+    child_throttle: Arc<ChildRequestThrottle>,
+
+    // state of the proxy for an offline Trust Anchor, if it is used
+    ta_proxy_store: Arc<TrustAnchorProxyStore>,
+
     // shared task queue:
     // - listens for events in the ca_store
     // - processed by the Scheduler
     // - can be used here to schedule tasks through the api
     tasks: Arc<TaskQueue>,
 
+    // cache for the optional RTR server, listens for events in the ca_store
+    rtr_cache: Option<Arc<RtrCache>>,
+
+    // seen BGP announcements, used to enforce strict ROA max lengths
+    bgp_analyser: Arc<BgpAnalyser>,
+
     config: Arc<Config>,
     signer: Arc<KrillSigner>,
 
@@ -80,6 +103,7 @@ impl CaManager {
         config: Arc<Config>,
         tasks: Arc<TaskQueue>,
         signer: Arc<KrillSigner>,
+        bgp_analyser: Arc<BgpAnalyser>,
         system_actor: Actor,
     ) -> KrillResult<Self> {
         // Create the AggregateStore for the event-sourced `CertAuth` structures that handle
@@ -124,15 +148,44 @@ impl CaManager {
         // the RPKI repository.
         ca_store.add_post_save_listener(tasks.clone());
 
+        // If the RTR server is enabled, then register its cache as a post-save listener as well,
+        // so that it can update its VRPs and notify routers when ROAs change in a `CertAuth`.
+        let rtr_cache = match config.rtr.clone() {
+            None => None,
+            Some(rtr_config) => {
+                let rtr_cache = Arc::new(RtrCache::new(rtr_config));
+                for handle in ca_store.list()? {
+                    rtr_cache.update_ca(ca_store.get_latest(&handle)?.as_ref());
+                }
+                ca_store.add_post_save_listener(rtr_cache.clone());
+                Some(rtr_cache)
+            }
+        };
+
         // Create the status store which will maintain the last known connection status between each CA
         // and their parent(s) and repository.
         let status_store = StatusStore::new(&config.data_dir, STATUS_DIR)?;
 
+        // Keep track of the RFC 6492 requests received from children, so that
+        // we can refuse to process requests from children which call too often.
+        let child_throttle = ChildRequestThrottle::new(
+            config.rfc6492_child_max_requests_per_minute,
+            config.rfc6492_list_cache_seconds,
+        );
+
+        // Create the store for the offline Trust Anchor proxy. This is a no-op
+        // unless the proxy is initialized.
+        let ta_proxy_store = TrustAnchorProxyStore::disk(&config.data_dir, TA_PROXY_DIR)?;
+
         Ok(CaManager {
             ca_store: Arc::new(ca_store),
             ca_objects_store,
             status_store: Arc::new(status_store),
+            child_throttle: Arc::new(child_throttle),
+            ta_proxy_store: Arc::new(ta_proxy_store),
             tasks,
+            rtr_cache,
+            bgp_analyser,
             config,
             signer,
             system_actor,
@@ -159,7 +212,7 @@ impl CaManager {
     ) -> KrillResult<()> {
         let ta_handle = ca::ta_handle();
 
-        if self.ca_store.has(&ta_handle)? {
+        if self.ca_store.has(&ta_handle)? || self.ta_proxy_store.is_initialized()? {
             Err(Error::TaAlreadyInitialized)
         } else {
             // init normal CA
@@ -241,6 +294,11 @@ impl CaManager {
         Ok(())
     }
 
+    /// Returns the cache for the RTR server, if it is enabled.
+    pub fn rtr_cache(&self) -> Option<Arc<RtrCache>> {
+        self.rtr_cache.clone()
+    }
+
     /// Get the CAs that the given actor is permitted to see.
     pub fn ca_list(&self, actor: &Actor) -> KrillResult<CertAuthList> {
         Ok(CertAuthList::new(
@@ -335,6 +393,10 @@ impl CaManager {
         self.status_store.remove_ca(ca_handle)?;
         self.tasks.remove_tasks_for_ca(ca_handle);
 
+        if let Some(rtr_cache) = &self.rtr_cache {
+            rtr_cache.remove_ca(ca_handle);
+        }
+
         Ok(())
     }
 }
@@ -436,6 +498,82 @@ impl CaManager {
         ServiceUri::Https(service_uri)
     }
 
+    fn onboarding_uri_for_ca(base_uri: &uri::Https, ca_handle: &CaHandle) -> uri::Https {
+        let onboarding_uri = format!("{}onboarding/{}", base_uri, ca_handle);
+        uri::Https::from_string(onboarding_uri).unwrap()
+    }
+
+    /// Shows the onboarding tokens of the CA which were not yet used.
+    pub async fn ca_onboarding_tokens(&self, ca: &CaHandle) -> KrillResult<OnboardingTokenList> {
+        Ok(self.get_ca(ca).await?.onboarding_tokens())
+    }
+
+    /// Adds a single-use token which lets a new child add itself under the
+    /// CA. Only the hash of the token is kept, so this is the only time that
+    /// the token itself is returned.
+    pub async fn ca_onboarding_token_add(
+        &self,
+        ca: &CaHandle,
+        req: OnboardingTokenRequest,
+        service_uri: &uri::Https,
+        actor: &Actor,
+    ) -> KrillResult<OnboardingToken> {
+        info!("CA '{}' process add onboarding token request: {}", ca, req);
+        let (resources, expires) = req.unpack();
+
+        let mut token_bytes = [0; 32];
+        openssl::rand::rand_bytes(&mut token_bytes)
+            .map_err(|err| Error::Custom(format!("Unable to generate an onboarding token: {}", &err)))?;
+        let token = hex::encode(token_bytes);
+
+        let info = OnboardingTokenInfo::new(OnboardingTokenId::for_token(&token), resources.clone(), expires);
+        self.send_command(CmdDet::onboarding_token_add(ca, info, actor)).await?;
+
+        Ok(OnboardingToken::new(
+            ca.convert(),
+            Self::onboarding_uri_for_ca(service_uri, ca),
+            token,
+            resources,
+            expires,
+        ))
+    }
+
+    /// Removes an unused onboarding token.
+    pub async fn ca_onboarding_token_remove(
+        &self,
+        ca: &CaHandle,
+        id: OnboardingTokenId,
+        actor: &Actor,
+    ) -> KrillResult<()> {
+        self.send_command(CmdDet::onboarding_token_remove(ca, id, actor))
+            .await?;
+        Ok(())
+    }
+
+    /// Adds the child which sent the request using the resources bound to
+    /// the onboarding token, and returns the parent response for it. The
+    /// token cannot be used again after this.
+    pub async fn ca_onboarding_token_redeem(
+        &self,
+        ca: &CaHandle,
+        redeem: OnboardingTokenRedeem,
+        service_uri: &uri::Https,
+        actor: &Actor,
+    ) -> KrillResult<idexchange::ParentResponse> {
+        let (token, child_request) = redeem.unpack();
+        let id = OnboardingTokenId::for_token(&token);
+
+        let child_handle = child_request.child_handle().clone();
+        let id_cert = child_request.validate().map_err(Error::rfc8183)?;
+
+        info!("CA '{}' process onboarding request for child '{}'", ca, child_handle);
+
+        let redeem = CmdDet::onboarding_token_redeem(ca, id, child_handle.clone(), id_cert.into(), actor);
+        self.send_command(redeem).await?;
+
+        self.ca_parent_response(ca, child_handle, service_uri).await
+    }
+
     /// Update a child under this CA. The submitted `UpdateChildRequest` can contain a
     /// new `IdCert`, or `ResourceSet`, or both. When resources are updated, the existing
     /// resource entitlements are replaced by the new value - i.e. this is not a delta
@@ -474,6 +612,7 @@ impl CaManager {
     /// are revoked and withdrawn.
     pub async fn ca_child_remove(&self, ca: &CaHandle, child: ChildHandle, actor: &Actor) -> KrillResult<()> {
         self.status_store.remove_child(ca, &child)?;
+        self.child_throttle.remove_child(ca, &child);
         self.send_command(CmdDet::child_remove(ca, child, actor)).await?;
 
         Ok(())
@@ -482,7 +621,8 @@ impl CaManager {
     /// Processes an RFC 6492 request sent to this CA:
     /// - parses the message bytes
     /// - validates the request
-    /// - processes the child request
+    /// - refuses the request if the child exceeds its request rate
+    /// - processes the child request, or uses a recently cached list response
     /// - signs a response and returns the bytes
     pub async fn rfc6492(
         &self,
@@ -491,9 +631,39 @@ impl CaManager {
         user_agent: Option<String>,
         actor: &Actor,
     ) -> KrillResult<Bytes> {
+        if ca_handle == &ta_handle() && self.ta_proxy_store.is_initialized()? {
+            return self.ta_proxy_rfc6492(msg_bytes);
+        }
+
         let ca = self.get_ca(ca_handle).await?;
 
         let req_msg = self.rfc6492_unwrap_request(&ca, &msg_bytes)?;
+        let child_handle: ChildHandle = req_msg.sender().convert();
+
+        // Refuse the request if the child sends requests too often. This is
+        // checked after the request was verified, so that others cannot use
+        // up the allowance of a child, but before any signing is done.
+        if let RequestAllowance::Refused { first } = self.child_throttle.register_request(ca_handle, &child_handle) {
+            let e = Error::CaChildRequestRateExceeded(ca_handle.clone(), child_handle.clone());
+            if first {
+                warn!("{}", e);
+                self.status_store
+                    .set_child_refused(ca_handle, &child_handle, user_agent, &e)?;
+            }
+            return Err(e);
+        }
+
+        // Return a recent list response if nothing changed in the CA since.
+        if matches!(req_msg.payload(), provisioning::Payload::List) {
+            if let Some(reply_bytes) = self
+                .child_throttle
+                .cached_list_response(ca_handle, &child_handle, ca.version())
+            {
+                self.status_store
+                    .set_child_success(ca_handle, &child_handle, user_agent)?;
+                return Ok(reply_bytes);
+            }
+        }
 
         // Create a logger for CMS (avoid cloning recipient)
         let cms_logger = CmsLogger::for_rfc6492_rcvd(
@@ -506,7 +676,8 @@ impl CaManager {
 
         match res_msg {
             Ok(msg) => {
-                let should_log_cms = !msg.is_list_response();
+                let is_list_response = msg.is_list_response();
+                let should_log_cms = !is_list_response;
                 let reply_bytes = ca.sign_rfc6492_response(msg, self.signer.deref())?;
 
                 if should_log_cms {
@@ -514,6 +685,15 @@ impl CaManager {
                     cms_logger.reply(&reply_bytes)?;
                 }
 
+                if is_list_response {
+                    self.child_throttle.cache_list_response(
+                        ca_handle,
+                        &child_handle,
+                        ca.version(),
+                        reply_bytes.clone(),
+                    );
+                }
+
                 Ok(reply_bytes)
             }
             Err(e) => {
@@ -652,6 +832,28 @@ impl CaManager {
 /// # CAs as children
 ///
 impl CaManager {
+    /// Sends the child request of a CA along with an onboarding token to the
+    /// parent which minted the token, and returns the parent response.
+    pub async fn ca_onboarding_token_use(
+        &self,
+        ca_handle: &CaHandle,
+        token: &OnboardingToken,
+    ) -> KrillResult<idexchange::ParentResponse> {
+        let ca = self.get_ca(ca_handle).await?;
+        let redeem = OnboardingTokenRedeem::new(token.token().to_string(), ca.child_request());
+
+        info!(
+            "CA '{}' sends onboarding request to parent '{}' at: {}",
+            ca_handle,
+            token.parent(),
+            token.uri()
+        );
+
+        httpclient::post_json_with_response(token.uri().as_str(), redeem, None)
+            .await
+            .map_err(Error::HttpClientError)
+    }
+
     /// Adds a new parent, or updates an existing parent of a CA. Adding a parent will trigger that the
     /// CA connects to this new parent in order to learn its resource entitlements and set up the resource
     /// class(es) under it, and request certificate(s).
@@ -696,6 +898,40 @@ impl CaManager {
         Ok(())
     }
 
+    /// Returns the limit on the resources which a CA requests from a parent,
+    /// if any. Returns an error if the parent is not known.
+    pub async fn ca_parent_resource_limit(
+        &self,
+        handle: &CaHandle,
+        parent: &ParentHandle,
+    ) -> KrillResult<Option<ResourceSet>> {
+        let ca = self.get_ca(handle).await?;
+        ca.parent(parent)?;
+        Ok(ca.parent_resource_limit(parent).cloned())
+    }
+
+    /// Sets, or removes, the limit on the resources which a CA requests from a
+    /// parent. This will trigger that the CA synchronizes with the parent, so
+    /// that it can request new certificates as needed.
+    pub async fn ca_parent_update_resource_limit(
+        &self,
+        handle: CaHandle,
+        parent: ParentHandle,
+        limit: Option<ResourceSet>,
+        actor: &Actor,
+    ) -> KrillResult<()> {
+        let cmd = CmdDet::update_parent_resource_limit(&handle, parent, limit, actor);
+        self.send_command(cmd).await?;
+        Ok(())
+    }
+
+    /// Clears the reports of resources which were removed by parents.
+    pub async fn ca_resource_shrinks_clear(&self, handle: &CaHandle, actor: &Actor) -> KrillResult<()> {
+        let cmd = CmdDet::resource_shrinks_clear(handle, actor);
+        self.send_command(cmd).await?;
+        Ok(())
+    }
+
     /// Send revocation requests for a parent of a CA when the parent is removed.
     pub async fn ca_parent_revoke(&self, handle: &CaHandle, parent: &ParentHandle) -> KrillResult<()> {
         let ca = self.get_ca(handle).await?;
@@ -812,7 +1048,11 @@ impl CaManager {
     pub async fn ca_sync_parent(&self, handle: &CaHandle, parent: &ParentHandle, actor: &Actor) -> KrillResult<()> {
         let ca = self.get_ca(handle).await?;
 
-        if ca.has_pending_requests(parent) {
+        if ca.is_ta() {
+            // The TA has no parent to send requests to, its old key is retired
+            // explicitly when a key roll is finished.
+            Ok(())
+        } else if ca.has_pending_requests(parent) {
             self.send_requests(handle, parent, actor).await
         } else {
             self.get_updates_from_parent(handle, parent, actor).await
@@ -1339,6 +1579,10 @@ impl CaManager {
         let service_uri = server_info.service_uri();
         if let Some(parent) = Self::local_parent(service_uri, &self.config.service_uri()) {
             let parent_handle = CaHandle::new(parent.into_name());
+            if parent_handle == ta_handle() && self.ta_proxy_store.is_initialized()? {
+                return self.ta_proxy_process_rfc6492(message);
+            }
+
             let parent = self.get_ca(&parent_handle).await?;
             let user_agent = Some("local-child".to_string());
 
@@ -1454,11 +1698,20 @@ impl CaManager {
     /// fail. When there have been 5 failed attempts, then the old repository
     /// is assumed to be unreachable and it will be dropped - i.e. the CA will
     /// no longer try to clean up objects.
+    ///
+    /// Repository mirrors are synchronized in their own tasks, so that a
+    /// mirror which cannot be reached does not keep the CA from publishing
+    /// in its repository, and vice versa.
     pub async fn cas_repo_sync_single(
         &self,
         repo_manager: &RepositoryManager,
         ca_handle: &CaHandle,
     ) -> KrillResult<()> {
+        for mirror in self.ca_objects_store.ca_objects(ca_handle)?.mirrors() {
+            let mirror_uri = mirror.server_info().service_uri().to_string();
+            self.tasks.sync_repo_mirror(ca_handle.clone(), mirror_uri, now());
+        }
+
         // Note that this is a no-op for new CAs which do not yet have any repository configured.
         for (repo_contact, ca_elements) in self.ca_repo_elements(ca_handle).await? {
             self.ca_repo_sync(repo_manager, ca_handle, &repo_contact, ca_elements)
@@ -1491,7 +1744,6 @@ impl CaManager {
         Ok(())
     }
 
-    #[allow(clippy::mutable_key_type)]
     async fn ca_repo_sync(
         &self,
         repo_manager: &RepositoryManager,
@@ -1504,6 +1756,85 @@ impl CaManager {
             .send_rfc8181_list(repo_manager, ca_handle, repo_contact.server_info())
             .await?;
 
+        let delta = Self::publish_delta(list_reply, publish_elements);
+
+        if !delta.is_empty() {
+            debug!("CA '{}' sends delta", ca_handle);
+            self.send_rfc8181_delta(repo_manager, ca_handle, repo_contact.server_info(), delta)
+                .await?;
+            debug!("CA '{}' sent delta", ca_handle);
+        } else {
+            debug!("CA '{}' empty delta - nothing to publish", ca_handle);
+        }
+
+        Ok(())
+    }
+
+    /// Synchronize a CA with one of its repository mirrors. The outcome is
+    /// kept in the status of the mirror, and does not affect the status of
+    /// the repository.
+    pub async fn cas_repo_mirror_sync_single(
+        &self,
+        repo_manager: &RepositoryManager,
+        ca_handle: &CaHandle,
+        mirror_uri: &str,
+    ) -> KrillResult<()> {
+        let ca_objects = self.ca_objects_store.ca_objects(ca_handle)?;
+
+        let (mirror, publish_elements) = match ca_objects.mirror_elements(mirror_uri) {
+            Some(mirror_and_elements) => mirror_and_elements,
+            None => {
+                debug!("CA '{}' no longer uses repository mirror '{}'", ca_handle, mirror_uri);
+                return Ok(());
+            }
+        };
+
+        match self
+            .ca_repo_mirror_sync(repo_manager, ca_handle, &mirror, publish_elements)
+            .await
+        {
+            Ok(()) => {
+                let next_update = ca_objects
+                    .closest_next_update()
+                    .unwrap_or_else(|| self.config.issuance_timing.republish_worst_case().into());
+                let mirror_uri = mirror.server_info().service_uri().clone();
+                self.status_store
+                    .set_status_repo_mirror_success(ca_handle, mirror_uri, next_update)
+            }
+            Err(e) => {
+                let mirror_uri = mirror.server_info().service_uri().clone();
+                self.status_store
+                    .set_status_repo_mirror_failure(ca_handle, mirror_uri, &e)?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn ca_repo_mirror_sync(
+        &self,
+        repo_manager: &RepositoryManager,
+        ca_handle: &CaHandle,
+        mirror: &RepositoryContact,
+        publish_elements: Vec<PublishElement>,
+    ) -> KrillResult<()> {
+        debug!("CA '{}' sends list query to repo mirror", ca_handle);
+        let list_reply = self.rfc8181_list(repo_manager, ca_handle, mirror.server_info()).await?;
+
+        let delta = Self::publish_delta(list_reply, publish_elements);
+
+        if !delta.is_empty() {
+            debug!("CA '{}' sends delta to repo mirror", ca_handle);
+            self.rfc8181_delta(repo_manager, ca_handle, mirror.server_info(), delta)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the delta needed to get from the currently published objects
+    /// in a list reply, to the given set of objects.
+    #[allow(clippy::mutable_key_type)]
+    fn publish_delta(list_reply: ListReply, publish_elements: Vec<PublishElement>) -> PublishDelta {
         let elements: HashMap<_, _> = list_reply.into_elements().into_iter().map(|el| el.unpack()).collect();
 
         let mut all_objects: HashMap<_, _> = publish_elements.into_iter().map(|el| el.unpack()).collect();
@@ -1525,16 +1856,7 @@ impl CaManager {
             delta.add_publish(Publish::new(None, uri, base64));
         }
 
-        if !delta.is_empty() {
-            debug!("CA '{}' sends delta", ca_handle);
-            self.send_rfc8181_delta(repo_manager, ca_handle, repo_contact.server_info(), delta)
-                .await?;
-            debug!("CA '{}' sent delta", ca_handle);
-        } else {
-            debug!("CA '{}' empty delta - nothing to publish", ca_handle);
-        }
-
-        Ok(())
+        delta
     }
 
     /// Get the current objects for a CA for each repository that it's using.
@@ -1549,6 +1871,149 @@ impl CaManager {
         Ok(self.ca_objects_store.ca_objects(ca)?.repo_elements_map())
     }
 
+    /// Get the decoded objects for a CA, for each resource class and key.
+    pub fn ca_published_objects(&self, ca: &CaHandle) -> KrillResult<CaPublishedObjects> {
+        self.ca_objects_store.ca_objects(ca)?.published_objects()
+    }
+
+    /// Get the DER encoding of an object published by a CA under the given key.
+    pub fn ca_published_object_der(&self, ca: &CaHandle, key: &KeyIdentifier, name: &ObjectName) -> KrillResult<Bytes> {
+        self.ca_objects_store
+            .ca_objects(ca)?
+            .published_object_der(key, name)
+            .ok_or_else(|| Error::CaObjectUnknown(ca.clone(), *key, name.clone()))
+    }
+
+    /// Validates the content of the repository as a relying party would, and
+    /// records the issues found for each local CA and for the repository.
+    ///
+    /// Local CAs which publish in a remote repository are validated using
+    /// the RRDP snapshot of that repository.
+    pub async fn validate_repository(
+        &self,
+        repo_manager: &RepositoryManager,
+    ) -> KrillResult<RepositoryValidationReport> {
+        let handles = self.ca_store.list()?;
+        let mut cas = vec![];
+        for ca in &handles {
+            let certs = self.ca_objects_store.ca_objects(ca)?.signing_certs();
+            cas.push((ca.clone(), certs));
+        }
+
+        let local = if repo_manager.initialized()? {
+            let objects = repo_manager.repository_objects()?;
+            let rsync_base = objects.rsync_base().clone();
+
+            let mut tals = vec![];
+            if let Some(details) = self.ta_proxy_cert_details()? {
+                tals.push(details.tal().clone());
+            } else if self.has_ca(&ta_handle())? {
+                let ta = self.get_ca(&ta_handle()).await?;
+                tals.push(ta.ta_cert_details()?.tal().clone());
+                if let Some(next) = ta.ta_next_key() {
+                    tals.push(next.tal().clone());
+                }
+            }
+
+            let report = RepositoryValidator::new(objects).validate(&tals, cas.clone());
+            Some((rsync_base, report))
+        } else {
+            None
+        };
+
+        let (mut ca_results, repository_issues) = match &local {
+            Some((_, report)) => (report.cas().clone(), report.repository_issues().clone()),
+            None => (vec![], vec![]),
+        };
+
+        // Certificates of local CAs which publish outside of this repository.
+        for (ca, certs) in cas {
+            let mut points: HashMap<uri::Rsync, Vec<ReceivedCert>> = HashMap::new();
+            for cert in certs {
+                let is_local = match &local {
+                    Some((rsync_base, _)) => cert.ca_repository().relative_to(rsync_base).is_some(),
+                    None => false,
+                };
+                if !is_local {
+                    points.entry(cert.ca_repository().clone()).or_default().push(cert);
+                }
+            }
+
+            for (dir, certs) in points {
+                let mut issues = self.validate_remote_publication_point(&ca, &dir, certs).await;
+                match ca_results.iter().position(|result| result.ca() == &ca) {
+                    Some(pos) => {
+                        let mut all = ca_results[pos].issues().clone();
+                        all.append(&mut issues);
+                        ca_results[pos] = CaValidationResult::new(ca.clone(), all);
+                    }
+                    None => ca_results.push(CaValidationResult::new(ca.clone(), issues)),
+                }
+            }
+        }
+        ca_results.sort_by_key(|result| result.ca().to_string());
+
+        let report = RepositoryValidationReport::new(ca_results, repository_issues);
+
+        // CAs which do not publish at all have no issues.
+        for ca in &handles {
+            let issues = report
+                .cas()
+                .iter()
+                .find(|result| result.ca() == ca)
+                .map(|result| result.issues().clone())
+                .unwrap_or_default();
+            self.status_store.set_validation_issues(ca, issues)?;
+        }
+        if local.is_some() {
+            repo_manager.set_validation_issues(report.repository_issues().clone());
+        }
+
+        Ok(report)
+    }
+
+    /// Validates the objects of a local CA in a remote repository, using
+    /// the RRDP snapshot of that repository. All issues are reported for
+    /// the CA.
+    async fn validate_remote_publication_point(
+        &self,
+        ca: &CaHandle,
+        dir: &uri::Rsync,
+        certs: Vec<ReceivedCert>,
+    ) -> Vec<ValidationIssue> {
+        let notify = certs
+            .iter()
+            .filter_map(|cert| cert.to_cert().ok())
+            .find_map(|cert| cert.rpki_notify().cloned());
+
+        let notify = match notify {
+            Some(notify) => notify,
+            None => {
+                return vec![ValidationIssue::new(
+                    dir.clone(),
+                    "cannot validate the remote repository: no RRDP notification URI on the CA certificate",
+                )]
+            }
+        };
+
+        match fetch_remote_objects(&notify, dir).await {
+            Ok(objects) => {
+                let report = RepositoryValidator::new(objects).validate(&[], vec![(ca.clone(), certs)]);
+                let mut issues: Vec<ValidationIssue> = report
+                    .cas()
+                    .iter()
+                    .flat_map(|result| result.issues().iter().cloned())
+                    .collect();
+                issues.extend(report.repository_issues().iter().cloned());
+                issues
+            }
+            Err(e) => vec![ValidationIssue::new(
+                dir.clone(),
+                format!("cannot validate the remote repository: {}", e),
+            )],
+        }
+    }
+
     /// Get deprecated repositories so that they can be cleaned.
     pub fn ca_deprecated_repos(&self, ca: &CaHandle) -> KrillResult<Vec<DeprecatedRepository>> {
         Ok(self.ca_objects_store.ca_objects(ca)?.deprecated_repos().clone())
@@ -1594,6 +2059,33 @@ impl CaManager {
         Ok(())
     }
 
+    /// Add a repository mirror to which a CA publishes the same objects as
+    /// to its repository.
+    pub async fn add_repo_mirror(
+        &self,
+        repo_manager: &RepositoryManager,
+        ca: CaHandle,
+        contact: RepositoryContact,
+        actor: &Actor,
+    ) -> KrillResult<()> {
+        // First verify that this repository can be reached and responds to a list request.
+        self.rfc8181_list(repo_manager, &ca, contact.server_info())
+            .await
+            .map_err(|e| Error::CaRepoIssue(ca.clone(), e.to_string()))?;
+
+        let cmd = CmdDet::add_repo_mirror(&ca, contact, actor);
+        self.send_command(cmd).await?;
+        Ok(())
+    }
+
+    /// Remove a repository mirror. Objects will be withdrawn from it, if it
+    /// can still be reached.
+    pub async fn remove_repo_mirror(&self, ca: CaHandle, service_uri: ServiceUri, actor: &Actor) -> KrillResult<()> {
+        let cmd = CmdDet::remove_repo_mirror(&ca, service_uri.clone(), actor);
+        self.send_command(cmd).await?;
+        self.status_store.remove_status_repo_mirror(&ca, &service_uri)
+    }
+
     async fn send_rfc8181_list(
         &self,
         repo_manager: &RepositoryManager,
@@ -1601,45 +2093,22 @@ impl CaManager {
         server_info: &PublicationServerInfo,
     ) -> KrillResult<ListReply> {
         let uri = server_info.service_uri();
-        let ca = self.get_ca(ca_handle).await?;
-        let signing_key = ca.id_cert().public_key().key_identifier();
-
-        let message = publication::Message::list_query();
-
-        let reply = match self
-            .send_rfc8181_and_validate_response(repo_manager, message, server_info, ca_handle, &signing_key)
-            .await
-        {
-            Ok(reply) => reply,
-            Err(e) => {
-                self.status_store.set_status_repo_failure(ca_handle, uri.clone(), &e)?;
-                return Err(e);
-            }
-        };
 
-        let next_update = self
-            .ca_objects_store
-            .ca_objects(ca_handle)?
-            .closest_next_update()
-            .unwrap_or_else(|| self.config.issuance_timing.republish_worst_case().into());
+        match self.rfc8181_list(repo_manager, ca_handle, server_info).await {
+            Ok(list_reply) => {
+                let next_update = self
+                    .ca_objects_store
+                    .ca_objects(ca_handle)?
+                    .closest_next_update()
+                    .unwrap_or_else(|| self.config.issuance_timing.republish_worst_case().into());
 
-        match reply {
-            publication::Reply::List(list_reply) => {
                 self.status_store
                     .set_status_repo_success(ca_handle, uri.clone(), next_update)?;
                 Ok(list_reply)
             }
-            publication::Reply::Success => {
-                let err = Error::custom("Got success reply to list query?!");
-                self.status_store
-                    .set_status_repo_failure(ca_handle, uri.clone(), &err)?;
-                Err(err)
-            }
-            publication::Reply::ErrorReply(e) => {
-                let err = Error::Custom(format!("Got error reply: {}", e));
-                self.status_store
-                    .set_status_repo_failure(ca_handle, uri.clone(), &err)?;
-                Err(err)
+            Err(e) => {
+                self.status_store.set_status_repo_failure(ca_handle, uri.clone(), &e)?;
+                Err(e)
             }
         }
     }
@@ -1652,28 +2121,11 @@ impl CaManager {
         delta: PublishDelta,
     ) -> KrillResult<()> {
         let uri = server_info.service_uri();
-        let ca = self.get_ca(ca_handle).await?;
-        let signing_key = ca.id_cert().public_key().key_identifier();
-
-        let message = publication::Message::delta(delta);
-
-        let reply = match self
-            .send_rfc8181_and_validate_response(repo_manager, message, server_info, ca_handle, &signing_key)
-            .await
-        {
-            Ok(reply) => reply,
-            Err(e) => {
-                self.status_store.set_status_repo_failure(ca_handle, uri.clone(), &e)?;
-                return Err(e);
-            }
-        };
 
-        match reply {
-            publication::Reply::Success => {
+        match self.rfc8181_delta(repo_manager, ca_handle, server_info, delta).await {
+            Ok(()) => {
                 // Get all the currently published elements in ALL REPOS.
-                // TODO: reflect the status for each REPO in the API / UI?
-                // We probably should.. though it should be extremely rare and short-lived to
-                // have more than one repository.
+                // Note that repository mirrors have their own status.
                 let ca_objects = self.ca_objects_store.ca_objects(ca_handle)?;
                 let published = ca_objects.all_publish_elements();
                 let next_update = ca_objects
@@ -1684,30 +2136,69 @@ impl CaManager {
                     .set_status_repo_published(ca_handle, uri.clone(), published, next_update)?;
                 Ok(())
             }
-            publication::Reply::ErrorReply(e) => {
-                let err = Error::Custom(format!("Got error reply: {}", e));
-                self.status_store
-                    .set_status_repo_failure(ca_handle, uri.clone(), &err)?;
-                Err(err)
-            }
-            publication::Reply::List(_) => {
-                let err = Error::custom("Got list reply to delta query?!");
-                self.status_store
-                    .set_status_repo_failure(ca_handle, uri.clone(), &err)?;
-                Err(err)
+            Err(e) => {
+                self.status_store.set_status_repo_failure(ca_handle, uri.clone(), &e)?;
+                Err(e)
             }
         }
     }
 
-    async fn send_rfc8181_and_validate_response(
+    /// Sends an RFC 8181 list query, without updating the repository status.
+    async fn rfc8181_list(
         &self,
         repo_manager: &RepositoryManager,
-        message: publication::Message,
-        server_info: &PublicationServerInfo,
         ca_handle: &CaHandle,
-        signing_key: &KeyIdentifier,
-    ) -> KrillResult<publication::Reply> {
-        let repo_service_uri = server_info.service_uri();
+        server_info: &PublicationServerInfo,
+    ) -> KrillResult<ListReply> {
+        let ca = self.get_ca(ca_handle).await?;
+        let signing_key = ca.id_cert().public_key().key_identifier();
+
+        let message = publication::Message::list_query();
+
+        let reply = self
+            .send_rfc8181_and_validate_response(repo_manager, message, server_info, ca_handle, &signing_key)
+            .await?;
+
+        match reply {
+            publication::Reply::List(list_reply) => Ok(list_reply),
+            publication::Reply::Success => Err(Error::custom("Got success reply to list query?!")),
+            publication::Reply::ErrorReply(e) => Err(Error::Custom(format!("Got error reply: {}", e))),
+        }
+    }
+
+    /// Sends an RFC 8181 delta, without updating the repository status.
+    async fn rfc8181_delta(
+        &self,
+        repo_manager: &RepositoryManager,
+        ca_handle: &CaHandle,
+        server_info: &PublicationServerInfo,
+        delta: PublishDelta,
+    ) -> KrillResult<()> {
+        let ca = self.get_ca(ca_handle).await?;
+        let signing_key = ca.id_cert().public_key().key_identifier();
+
+        let message = publication::Message::delta(delta);
+
+        let reply = self
+            .send_rfc8181_and_validate_response(repo_manager, message, server_info, ca_handle, &signing_key)
+            .await?;
+
+        match reply {
+            publication::Reply::Success => Ok(()),
+            publication::Reply::ErrorReply(e) => Err(Error::Custom(format!("Got error reply: {}", e))),
+            publication::Reply::List(_) => Err(Error::custom("Got list reply to delta query?!")),
+        }
+    }
+
+    async fn send_rfc8181_and_validate_response(
+        &self,
+        repo_manager: &RepositoryManager,
+        message: publication::Message,
+        server_info: &PublicationServerInfo,
+        ca_handle: &CaHandle,
+        signing_key: &KeyIdentifier,
+    ) -> KrillResult<publication::Reply> {
+        let repo_service_uri = server_info.service_uri();
 
         if repo_service_uri
             .as_str()
@@ -1819,6 +2310,85 @@ impl CaManager {
         .await?;
         Ok(())
     }
+
+    /// Generates a key pair for a BGPsec router and adds a definition for it,
+    /// so that a router certificate is issued. The private key is returned
+    /// encrypted with the requested passphrase. It is not kept by Krill.
+    pub async fn ca_bgpsec_router_key_create(
+        &self,
+        ca: CaHandle,
+        request: BgpSecRouterKeyRequest,
+        actor: &Actor,
+    ) -> KrillResult<BgpSecRouterKey> {
+        let asn = request.asn();
+        if request.passphrase().is_empty() {
+            return Err(Error::BgpSecRouterKeyNoPassphrase(ca, asn));
+        }
+
+        let (csr, private_key) = self.signer.create_bgpsec_router_key(asn, request.passphrase())?;
+        let key_identifier = csr.public_key().key_identifier();
+
+        let updates = BgpSecDefinitionUpdates::new(vec![BgpSecDefinition::new(asn, csr)], vec![]);
+        self.ca_bgpsec_definitions_update(ca, updates, actor).await?;
+
+        Ok(BgpSecRouterKey::new(asn, key_identifier, private_key))
+    }
+}
+
+/// # Signed Prefix List functions
+///
+impl CaManager {
+    /// Show the Signed Prefix List definitions for this CA.
+    pub async fn ca_prefix_lists_show(&self, ca: CaHandle) -> KrillResult<PrefixListDefinitionList> {
+        let ca = self.get_ca(&ca).await?;
+        Ok(ca.prefix_lists_show())
+    }
+
+    /// Add or replace, and remove Signed Prefix List definitions for this CA.
+    pub async fn ca_prefix_lists_update(
+        &self,
+        ca: CaHandle,
+        updates: PrefixListDefinitionUpdates,
+        actor: &Actor,
+    ) -> KrillResult<()> {
+        self.send_command(CmdDet::prefix_lists_update(
+            &ca,
+            updates,
+            self.config.clone(),
+            self.signer.clone(),
+            actor,
+        ))
+        .await?;
+        Ok(())
+    }
+}
+
+/// # Ghostbuster functions
+///
+impl CaManager {
+    /// Show the current Ghostbuster contact for this CA, if any.
+    pub async fn ca_ghostbuster_show(&self, ca: CaHandle) -> KrillResult<Option<GhostbusterContact>> {
+        let ca = self.get_ca(&ca).await?;
+        Ok(ca.ghostbuster_show())
+    }
+
+    /// Set, or remove, the Ghostbuster contact for this CA.
+    pub async fn ca_ghostbuster_update(
+        &self,
+        ca: CaHandle,
+        contact: Option<GhostbusterContact>,
+        actor: &Actor,
+    ) -> KrillResult<()> {
+        self.send_command(CmdDet::ghostbuster_update(
+            &ca,
+            contact,
+            self.config.clone(),
+            self.signer.clone(),
+            actor,
+        ))
+        .await?;
+        Ok(())
+    }
 }
 
 /// # Route Authorization functions
@@ -1838,9 +2408,147 @@ impl CaManager {
         updates: RoaConfigurationUpdates,
         actor: &Actor,
     ) -> KrillResult<()> {
+        let max_length_exposed = self.roa_max_length_exposed(&updates).await;
         self.send_command(CmdDet::route_authorizations_update(
             &ca,
             updates,
+            max_length_exposed,
+            self.config.clone(),
+            self.signer.clone(),
+            actor,
+        ))
+        .await?;
+        Ok(())
+    }
+
+    /// Activates scheduled, and removes expired, time-bounded ROA configurations
+    /// in a CA. This uses a normal update, attributed to the given actor.
+    ///
+    /// If strict ROA max lengths are used, then scheduled configurations which
+    /// allow more specific announcements that are not seen in BGP are not
+    /// activated. They are marked as refused instead, so that they are reported
+    /// as a CA issue and not tried again. The other changes are still applied.
+    ///
+    /// Returns the time of the next upcoming change for the CA, if any.
+    pub async fn ca_routes_timed_update(&self, ca: &CaHandle, actor: &Actor) -> KrillResult<Option<Timestamp>> {
+        let mut cert_auth = self.get_ca(ca).await?;
+        let updates = cert_auth.timed_roa_updates(Timestamp::now());
+
+        if !updates.is_empty() {
+            let max_length_exposed = self.roa_max_length_exposed(&updates).await;
+
+            let (added, removed) = updates.unpack();
+            let (refused, added): (Vec<RoaConfiguration>, Vec<RoaConfiguration>) = added
+                .into_iter()
+                .partition(|config| max_length_exposed.contains(&config.payload().into_explicit_max_length()));
+
+            if !refused.is_empty() {
+                for config in &refused {
+                    warn!(
+                        "CA '{}' will not activate scheduled ROA '{}', it allows more specific announcements that are not seen",
+                        ca, config
+                    );
+                }
+                let refused = refused.iter().map(|config| config.payload()).collect();
+                cert_auth = self
+                    .send_command(CmdDet::route_authorizations_activation_refused(ca, refused, actor))
+                    .await?;
+            }
+
+            let updates = RoaConfigurationUpdates::new(added, removed);
+            if !updates.is_empty() {
+                info!(
+                    "CA '{}' will activate {} and remove {} time-bounded ROA configurations",
+                    ca,
+                    updates.added().len(),
+                    updates.removed().len()
+                );
+                cert_auth = self
+                    .send_command(CmdDet::route_authorizations_update(
+                        ca,
+                        updates,
+                        max_length_exposed,
+                        self.config.clone(),
+                        self.signer.clone(),
+                        actor,
+                    ))
+                    .await?;
+            }
+        }
+
+        Ok(cert_auth.timed_roa_changes().next())
+    }
+
+    /// Returns the payloads of added ROA configurations which use a max length
+    /// that allows more specific announcements which are not all seen in BGP,
+    /// if strict max lengths are configured. The CA rejects adding these. See
+    /// RFC 9319.
+    pub async fn roa_max_length_exposed(&self, updates: &RoaConfigurationUpdates) -> Vec<RoaPayload> {
+        if !self.config.roa_max_length_strict {
+            return vec![];
+        }
+
+        let payloads: Vec<RoaPayload> = updates
+            .added()
+            .iter()
+            .map(|config| config.payload().into_explicit_max_length())
+            .collect();
+
+        self.bgp_analyser.max_length_exposed(&payloads).await
+    }
+
+    /// Merge the updates into the named ROA draft for a CA, creating the
+    /// draft if needed. This does not affect any ROAs until the draft is
+    /// committed.
+    pub async fn ca_routes_draft_update(
+        &self,
+        ca: CaHandle,
+        name: RoaDraftName,
+        updates: RoaConfigurationUpdates,
+        actor: &Actor,
+    ) -> KrillResult<()> {
+        let max_length_exposed = self.roa_max_length_exposed(&updates).await;
+        self.send_command(CmdDet::route_authorizations_draft_update(
+            &ca,
+            name,
+            updates,
+            max_length_exposed,
+            actor,
+        ))
+        .await?;
+        Ok(())
+    }
+
+    /// Discard the named ROA draft for a CA.
+    pub async fn ca_routes_draft_remove(&self, ca: CaHandle, name: RoaDraftName, actor: &Actor) -> KrillResult<()> {
+        self.send_command(CmdDet::route_authorizations_draft_remove(&ca, name, actor))
+            .await?;
+        Ok(())
+    }
+
+    /// Apply the named ROA draft for a CA as a single update, and remove it.
+    pub async fn ca_routes_draft_commit(&self, ca: CaHandle, name: RoaDraftName, actor: &Actor) -> KrillResult<()> {
+        let (_, updates) = self.get_ca(&ca).await?.roa_draft(&name)?.unpack();
+        let max_length_exposed = self.roa_max_length_exposed(&updates).await;
+
+        self.send_command(CmdDet::route_authorizations_draft_commit(
+            &ca,
+            name,
+            max_length_exposed,
+            self.config.clone(),
+            self.signer.clone(),
+            actor,
+        ))
+        .await?;
+        Ok(())
+    }
+
+    /// Enable or disable AS0 ROAs for the held space of a CA which is not
+    /// covered by any ROA configuration.
+    pub async fn ca_routes_as0_policy(&self, ca: CaHandle, policy: RoaAs0Policy, actor: &Actor) -> KrillResult<()> {
+        self.send_command(CmdDet::route_authorizations_as0_policy(
+            &ca,
+            policy,
             self.config.clone(),
             self.signer.clone(),
             actor,
@@ -1891,6 +2599,28 @@ impl CaManager {
             if let Err(e) = self.send_command(cmd).await {
                 error!("Renewing BGPSec certificates for CA '{}' failed with error: {}", ca, e);
             }
+
+            let cmd = Cmd::new(
+                &ca,
+                None,
+                CmdDet::PrefixListsRenew(self.config.clone(), self.signer.clone()),
+                actor,
+            );
+
+            if let Err(e) = self.send_command(cmd).await {
+                error!("Renewing prefix lists for CA '{}' failed with error: {}", ca, e);
+            }
+
+            let cmd = Cmd::new(
+                &ca,
+                None,
+                CmdDet::GhostbusterRenew(self.config.clone(), self.signer.clone()),
+                actor,
+            );
+
+            if let Err(e) = self.send_command(cmd).await {
+                error!("Renewing Ghostbuster records for CA '{}' failed with error: {}", ca, e);
+            }
         }
         Ok(())
     }
@@ -1916,6 +2646,52 @@ impl CaManager {
     }
 }
 
+/// # Resource Signed Checklist functions
+///
+impl CaManager {
+    /// Sign an RSC. This does not change the CA, the one-off EE key used is
+    /// destroyed after signing and the RSC is not published.
+    pub async fn rsc_sign(&self, ca: CaHandle, request: RscRequest) -> KrillResult<ResourceSignedChecklist> {
+        let ca = self.get_ca(&ca).await?;
+        ca.rsc_sign(request, &self.signer)
+    }
+}
+
+/// # Signed Geofeed functions
+///
+impl CaManager {
+    /// Sign a geofeed. This does not change the CA, the one-off EE key used
+    /// is destroyed after signing and the geofeed is not published by Krill.
+    pub async fn geofeed_sign(&self, ca: CaHandle, request: GeofeedSignRequest) -> KrillResult<SignedGeofeed> {
+        let ca = self.get_ca(&ca).await?;
+        ca.geofeed_sign(request, &self.signer)
+    }
+}
+
+/// # Verification of RTAs and RSCs
+///
+impl CaManager {
+    /// Verify an RTA or RSC. The certificates currently held by all CAs in
+    /// this instance are used, next to the certificates in the request, to
+    /// build the certificate paths of the signers.
+    pub async fn verify_signed_object(
+        &self,
+        request: SignedObjectVerifyRequest,
+    ) -> KrillResult<SignedObjectVerification> {
+        let mut local_certs = vec![];
+        for handle in self.ca_store.list()? {
+            let ca = self.get_ca(&handle).await?;
+            for cert in ca.current_certificates() {
+                if let Ok(cert) = cert.to_cert() {
+                    local_certs.push(cert);
+                }
+            }
+        }
+
+        request.verify(local_certs)
+    }
+}
+
 /// # Resource Tagged Attestation functions
 ///
 impl CaManager {
@@ -1978,4 +2754,207 @@ impl CaManager {
         self.send_command(activate_cmd).await?;
         Ok(())
     }
+
+    /// Finishes the key roll of the TA, retiring its old key and the TAK
+    /// object published under it.
+    pub async fn ca_keyroll_finish(&self, handle: CaHandle, actor: &Actor) -> KrillResult<()> {
+        if handle != ta_handle() {
+            return Err(Error::custom("Only a Trust Anchor can finish its key roll explicitly"));
+        }
+        let finish_cmd = CmdDet::ta_key_roll_finish(&handle, self.signer.clone(), actor);
+        self.send_command(finish_cmd).await?;
+        Ok(())
+    }
+}
+
+/// # Offline Trust Anchor proxy
+///
+impl CaManager {
+    /// Initializes the proxy for an offline Trust Anchor. The proxy is added
+    /// as a publisher to the embedded repository, which must be initialized.
+    pub fn ta_proxy_init(&self, repo_manager: &RepositoryManager, actor: &Actor) -> KrillResult<()> {
+        if self.ca_store.has(&ta_handle())? {
+            return Err(Error::TaAlreadyInitialized);
+        }
+        if self.ta_proxy_store.is_initialized()? {
+            return Err(Error::TaProxyAlreadyInitialized);
+        }
+        if !repo_manager.initialized()? {
+            return Err(Error::RepositoryServerNotInitialized);
+        }
+
+        let id = Rfc8183Id::generate(&self.signer)?;
+
+        let publisher = Self::ta_proxy_publisher();
+        let pub_req = idexchange::PublisherRequest::new(id.cert().base64().clone(), publisher.clone(), None);
+        repo_manager.create_publisher(pub_req, actor)?;
+        let repo_info = repo_manager.repo_info_for(&publisher)?;
+
+        info!(
+            "Initialized Trust Anchor proxy, publishing under: {}",
+            repo_info.base_uri()
+        );
+        self.ta_proxy_store.init(TrustAnchorProxy::new(id, repo_info))
+    }
+
+    fn ta_proxy_publisher() -> idexchange::PublisherHandle {
+        idexchange::PublisherHandle::from_str(TA_PROXY_PUBLISHER).unwrap()
+    }
+
+    /// Returns the information needed to initialize the signer.
+    pub fn ta_proxy_info(&self) -> KrillResult<TrustAnchorProxyInfo> {
+        self.ta_proxy_store.get().map(|proxy| proxy.info())
+    }
+
+    /// Returns the status of the proxy, or None if it is not initialized.
+    pub fn ta_proxy_status(&self) -> KrillResult<Option<TrustAnchorProxyStatus>> {
+        if self.ta_proxy_store.is_initialized()? {
+            self.ta_proxy_store.get().map(|proxy| Some(proxy.status()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the TA certificate details from the signer associated with the
+    /// proxy, if there is one.
+    pub fn ta_proxy_cert_details(&self) -> KrillResult<Option<TaCertDetails>> {
+        if self.ta_proxy_store.is_initialized()? {
+            let proxy = self.ta_proxy_store.get()?;
+            Ok(proxy.signer_info().ok().map(|signer| signer.ta_cert_details().clone()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Associates the signer with the proxy. This can only be done once.
+    pub fn ta_proxy_signer_add(&self, signer: TrustAnchorSignerInfo) -> KrillResult<()> {
+        self.ta_proxy_store.with_proxy(|proxy| proxy.add_signer(signer))
+    }
+
+    /// Adds a child to the proxy, and returns the RFC 8183 parent response
+    /// for it.
+    pub fn ta_proxy_children_add(
+        &self,
+        req: AddChildRequest,
+        service_uri: &uri::Https,
+    ) -> KrillResult<idexchange::ParentResponse> {
+        let (child_handle, child_res, id_cert) = req.unpack();
+
+        let id_cert = self.ta_proxy_store.with_proxy(|proxy| {
+            proxy.add_child(child_handle.clone(), id_cert.into(), child_res)?;
+            Ok(proxy.id_cert().clone())
+        })?;
+
+        Ok(idexchange::ParentResponse::new(
+            id_cert.base64().clone(),
+            ta_handle().convert(),
+            child_handle,
+            Self::service_uri_for_ca(service_uri, &ta_handle()),
+            None,
+        ))
+    }
+
+    /// Creates a new request for the signer, containing any pending child
+    /// requests. Returns an error if there already is an open request.
+    pub fn ta_proxy_signer_make_request(&self) -> KrillResult<TrustAnchorSignedMessage> {
+        let proxy = self.ta_proxy_store.with_proxy(|proxy| {
+            proxy.make_signer_request()?;
+            Ok(proxy.clone())
+        })?;
+        proxy.signed_open_request(&self.signer)
+    }
+
+    /// Shows the open request for the signer.
+    pub fn ta_proxy_signer_show_request(&self) -> KrillResult<TrustAnchorSignedMessage> {
+        self.ta_proxy_store.get()?.signed_open_request(&self.signer)
+    }
+
+    /// Processes the response from the signer for the open request, publishes
+    /// the new TA objects, and lets local child CAs synchronize.
+    pub async fn ta_proxy_signer_process_response(
+        &self,
+        repo_manager: &RepositoryManager,
+        response: TrustAnchorSignedMessage,
+    ) -> KrillResult<()> {
+        let updated = self
+            .ta_proxy_store
+            .with_proxy(|proxy| proxy.process_signer_response(&response))?;
+
+        self.ta_proxy_repo_sync(repo_manager)?;
+
+        for child in updated {
+            let ca = child.convert();
+            if self.has_ca(&ca)? {
+                self.tasks.sync_parent(ca, ta_handle().convert(), now());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Publishes the current TA objects received from the signer.
+    pub fn ta_proxy_repo_sync(&self, repo_manager: &RepositoryManager) -> KrillResult<()> {
+        let proxy = self.ta_proxy_store.get()?;
+
+        if let Some(objects) = proxy.objects() {
+            let publisher = Self::ta_proxy_publisher();
+            let list_reply = repo_manager.list(&publisher)?;
+
+            let delta = Self::publish_delta(list_reply, objects.elements().clone());
+            if !delta.is_empty() {
+                debug!("Trust Anchor proxy publishes delta");
+                repo_manager.publish(&publisher, delta)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Processes an RFC 6492 request sent to the proxy by a remote child.
+    fn ta_proxy_rfc6492(&self, msg_bytes: Bytes) -> KrillResult<Bytes> {
+        let proxy = self.ta_proxy_store.get()?;
+
+        let req_msg = match ProvisioningCms::decode(msg_bytes.as_ref()) {
+            Ok(msg) => proxy.verify_rfc6492(msg)?,
+            Err(e) => {
+                return Err(Error::custom(format!(
+                    "Could not decode RFC6492 message for: {}, err: {}",
+                    ta_handle(),
+                    e
+                )))
+            }
+        };
+
+        let cms_logger = CmsLogger::for_rfc6492_rcvd(
+            self.config.rfc6492_log_dir.as_ref(),
+            req_msg.recipient(),
+            req_msg.sender(),
+        );
+
+        match self.ta_proxy_process_rfc6492(req_msg) {
+            Ok(msg) => {
+                let should_log_cms = !msg.is_list_response();
+                let reply_bytes = proxy.sign_rfc6492_response(msg, &self.signer)?;
+
+                if should_log_cms {
+                    cms_logger.received(&msg_bytes)?;
+                    cms_logger.reply(&reply_bytes)?;
+                }
+
+                Ok(reply_bytes)
+            }
+            Err(e) => {
+                cms_logger.received(&msg_bytes)?;
+                cms_logger.err(&e)?;
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Process an RFC 6492 message for the proxy and create an unsigned response.
+    fn ta_proxy_process_rfc6492(&self, req_msg: provisioning::Message) -> KrillResult<provisioning::Message> {
+        self.ta_proxy_store
+            .with_proxy(|proxy| proxy.process_rfc6492(req_msg, &self.config.issuance_timing))
+    }
 }
@@ -16,12 +16,21 @@ pub use self::certauth::CertAuth;
 mod child;
 pub use self::child::*;
 
+mod geofeed;
+pub use self::geofeed::*;
+
+mod ghostbuster;
+pub use self::ghostbuster::*;
+
 mod rc;
 pub use self::rc::ResourceClass;
 
 mod keys;
 pub use self::keys::*;
 
+mod prefixlist;
+pub use self::prefixlist::*;
+
 mod publishing;
 pub use self::publishing::*;
 
@@ -38,12 +47,28 @@ pub use self::events::*;
 mod manager;
 pub use self::manager::CaManager;
 
+mod rsc;
+pub use self::rsc::*;
+
 mod rta;
 pub use self::rta::*;
 
 mod status;
 pub use self::status::*;
 
+mod tak;
+pub use self::tak::*;
+
+mod throttle;
+pub use self::throttle::*;
+
+#[allow(clippy::mutable_key_type)]
+mod validation;
+pub use self::validation::*;
+
+mod verify;
+pub use self::verify::*;
+
 pub const TA_NAME: &str = "ta"; // reserved for TA
 pub const TESTBED_CA_NAME: &str = "testbed"; // reserved for testbed mode
 
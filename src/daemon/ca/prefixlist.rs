@@ -0,0 +1,333 @@
+//! Signed Prefix Lists
+//!
+//! This is still being discussed in the IETF. No RFC just yet.
+//! See the following draft:
+//! https://datatracker.ietf.org/doc/draft-ietf-sidrops-rpki-prefixlist/
+//!
+
+use std::{collections::HashMap, net::IpAddr};
+
+use bcder::{
+    encode::{self, PrimitiveContent, Values},
+    Captured, ConstOid, Mode, OctetString, Oid, Tag,
+};
+use rpki::{
+    ca::publication::Base64,
+    repository::{
+        resources::{Asn, IpResources, ResourceSet},
+        sigobj::SignedObjectBuilder,
+        x509::{Serial, Time},
+    },
+};
+
+use crate::{
+    commons::{
+        api::{ObjectName, PrefixListDefinition, TypedPrefix},
+        crypto::KrillSigner,
+        error::Error,
+        KrillResult,
+    },
+    daemon::{
+        ca::{CertifiedKey, PrefixListObjectsUpdates},
+        config::IssuanceTimingConfig,
+    },
+};
+
+//------------ Object Identifiers --------------------------------------------
+
+/// id-ct-signedPrefixList, draft-ietf-sidrops-rpki-prefixlist
+pub const SIGNED_PREFIX_LIST: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 9, 16, 1, 51]);
+
+//------------ PrefixListDefinitions ---------------------------------------
+
+/// This type contains the Signed Prefix List definitions for a CA, keyed by
+/// the ASN. An object is issued in each [`ResourceClass`] that holds the ASN.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrefixListDefinitions {
+    definitions: HashMap<Asn, PrefixListDefinition>,
+}
+
+impl PrefixListDefinitions {
+    // Add or replace a definition
+    pub fn add_or_replace(&mut self, definition: PrefixListDefinition) {
+        self.definitions.insert(definition.asn(), definition);
+    }
+
+    // Remove an existing definition (if it is present)
+    pub fn remove(&mut self, asn: Asn) {
+        self.definitions.remove(&asn);
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &PrefixListDefinition> {
+        self.definitions.values()
+    }
+
+    pub fn get(&self, asn: Asn) -> Option<&PrefixListDefinition> {
+        self.definitions.get(&asn)
+    }
+
+    pub fn has(&self, asn: Asn) -> bool {
+        self.definitions.contains_key(&asn)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
+
+//------------ PrefixListObjects -------------------------------------------
+
+/// Signed Prefix List objects held by a resource class in a CA.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrefixListObjects(HashMap<Asn, PrefixListInfo>);
+
+impl PrefixListObjects {
+    /// Issue new objects based on the definitions, and remove objects for
+    /// which the ASN is no longer held.
+    ///
+    /// Note: we pass in *all* definitions for the CA, not all definitions
+    ///   will be relevant for the RC (key) holding these objects.
+    pub fn update(
+        &self,
+        all_definitions: &PrefixListDefinitions,
+        certified_key: &CertifiedKey,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<PrefixListObjectsUpdates> {
+        let mut object_updates = PrefixListObjectsUpdates::default();
+        let resources = certified_key.incoming_cert().resources();
+
+        for relevant in all_definitions.all().filter(|def| resources.contains_asn(def.asn())) {
+            let need_to_issue = self
+                .0
+                .get(&relevant.asn())
+                .map(|existing| existing.definition() != relevant)
+                .unwrap_or(true);
+
+            if need_to_issue {
+                let info = PrefixListInfo::sign(relevant.clone(), certified_key, issuance_timing, signer)?;
+                object_updates.add_updated(info);
+            }
+        }
+
+        for asn in self.0.keys() {
+            if !all_definitions.has(*asn) || !resources.contains_asn(*asn) {
+                // definition was removed, or it's overclaiming
+                object_updates.add_removed(*asn);
+            }
+        }
+
+        Ok(object_updates)
+    }
+
+    /// Re-issues objects. If the renew_threshold is specified, then only
+    /// objects which will expire before that time will be renewed.
+    pub fn renew(
+        &self,
+        certified_key: &CertifiedKey,
+        renew_threshold: Option<Time>,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<PrefixListObjectsUpdates> {
+        let mut updates = PrefixListObjectsUpdates::default();
+
+        for existing in self.0.values() {
+            let renew = renew_threshold
+                .map(|threshold| existing.expires() < threshold)
+                .unwrap_or(true); // always renew if no threshold is specified
+
+            if renew {
+                let definition = existing.definition().clone();
+                updates.add_updated(PrefixListInfo::sign(
+                    definition,
+                    certified_key,
+                    issuance_timing,
+                    signer,
+                )?);
+            }
+        }
+
+        Ok(updates)
+    }
+
+    pub fn updated(&mut self, updates: PrefixListObjectsUpdates) {
+        let (updated, removed) = updates.unpack();
+        for info in updated {
+            self.0.insert(info.asn(), info);
+        }
+        for asn in removed {
+            self.0.remove(&asn);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+//------------ PrefixListInfo ----------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PrefixListInfo {
+    // The ASN and the prefixes it may originate
+    definition: PrefixListDefinition,
+
+    // The serial number (needed for revocation)
+    serial: Serial,
+
+    // The time this object expires
+    expires: Time,
+
+    // The actual Signed Prefix List object in base64 format.
+    base64: Base64,
+}
+
+impl PrefixListInfo {
+    /// Signs a Signed Prefix List for the definition under the given key.
+    pub fn sign(
+        definition: PrefixListDefinition,
+        certified_key: &CertifiedKey,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<Self> {
+        let incoming_cert = certified_key.incoming_cert();
+
+        let object_builder = {
+            let crl_uri = incoming_cert.crl_uri();
+            let spl_uri = incoming_cert.uri_for_name(&ObjectName::prefix_list(definition.asn()));
+            let ca_issuer = incoming_cert.uri().clone();
+
+            let mut object_builder = SignedObjectBuilder::new(
+                signer.random_serial()?,
+                issuance_timing.new_roa_validity(),
+                crl_uri,
+                ca_issuer,
+                spl_uri,
+            );
+            object_builder.set_issuer(Some(incoming_cert.subject().clone()));
+            object_builder.set_signing_time(Some(Time::now()));
+
+            // The EE certificate contains the ASN only. The prefixes need
+            // not be held by the CA, so IP resources are left out.
+            let asn_resources = ResourceSet::from_strs(&definition.asn().to_string(), "", "")
+                .map_err(|e| Error::Custom(format!("Cannot use ASN for prefix list: {}", e)))?;
+            object_builder.set_as_resources(asn_resources.to_as_resources());
+            object_builder.set_v4_resources(IpResources::missing());
+            object_builder.set_v6_resources(IpResources::missing());
+
+            object_builder
+        };
+
+        let content = Self::encode_content(&definition).into_bytes();
+        let object = signer.sign_object(
+            Oid(SIGNED_PREFIX_LIST.0.into()),
+            content,
+            object_builder,
+            certified_key.key_id(),
+        )?;
+        let base64 = Base64::from_content(&object.encode_ref().to_captured(Mode::Der).into_bytes());
+
+        Ok(PrefixListInfo {
+            definition,
+            serial: object.cert().serial_number(),
+            expires: object.cert().validity().not_after(),
+            base64,
+        })
+    }
+
+    /// Encodes the SignedPrefixList content. The version is left out as it
+    /// uses the default, and the prefixes are listed per address family in
+    /// ascending order.
+    fn encode_content(definition: &PrefixListDefinition) -> Captured {
+        let mut v4: Vec<&TypedPrefix> = definition.prefixes().iter().filter(|p| p.is_v4()).collect();
+        let mut v6: Vec<&TypedPrefix> = definition.prefixes().iter().filter(|p| !p.is_v4()).collect();
+        v4.sort();
+        v6.sort();
+
+        let mut families = vec![];
+        if !v4.is_empty() {
+            families.push(Self::encode_family([0, 1], &v4));
+        }
+        if !v6.is_empty() {
+            families.push(Self::encode_family([0, 2], &v6));
+        }
+
+        encode::sequence((definition.asn().into_u32().encode(), encode::sequence(families))).to_captured(Mode::Der)
+    }
+
+    fn encode_family(afi: [u8; 2], prefixes: &[&TypedPrefix]) -> Captured {
+        let addresses: Vec<Captured> = prefixes.iter().map(|prefix| Self::encode_prefix(prefix)).collect();
+        encode::sequence((OctetString::encode_slice(afi), encode::sequence(addresses))).to_captured(Mode::Der)
+    }
+
+    /// Encodes the prefix as a BIT STRING, as used for IPAddress in RFC 3779.
+    fn encode_prefix(prefix: &TypedPrefix) -> Captured {
+        let (bytes, skip) = match prefix.ip_addr() {
+            IpAddr::V4(addr) => (u128::from(u32::from(addr)).to_be_bytes(), 12),
+            IpAddr::V6(addr) => (u128::from(addr).to_be_bytes(), 0),
+        };
+
+        let len = prefix.addr_len() as usize;
+        let used = (len + 7) / 8;
+        let unused_bits = (used * 8 - len) as u8;
+
+        let mut content = vec![unused_bits];
+        content.extend_from_slice(&bytes[skip..skip + used]);
+
+        OctetString::encode_slice_as(content, Tag::BIT_STRING).to_captured(Mode::Der)
+    }
+
+    pub fn definition(&self) -> &PrefixListDefinition {
+        &self.definition
+    }
+
+    pub fn asn(&self) -> Asn {
+        self.definition.asn()
+    }
+
+    pub fn serial(&self) -> Serial {
+        self.serial
+    }
+
+    pub fn expires(&self) -> Time {
+        self.expires
+    }
+
+    pub fn base64(&self) -> &Base64 {
+        &self.base64
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn encode_prefix_list_content() {
+        let definition = PrefixListDefinition::from_str("AS65000 => 2001:db8::/32, 10.0.0.0/16, 192.0.2.0/24").unwrap();
+
+        let encoded = PrefixListInfo::encode_content(&definition).into_bytes();
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            0x30, 0x29,
+                0x02, 0x03, 0x00, 0xfd, 0xe8,                   // AS65000
+                0x30, 0x22,
+                    0x30, 0x11,
+                        0x04, 0x02, 0x00, 0x01,                 // IPv4
+                        0x30, 0x0b,
+                            0x03, 0x03, 0x00, 0x0a, 0x00,       // 10.0.0.0/16
+                            0x03, 0x04, 0x00, 0xc0, 0x00, 0x02, // 192.0.2.0/24
+                    0x30, 0x0d,
+                        0x04, 0x02, 0x00, 0x02,                 // IPv6
+                        0x30, 0x07,
+                            0x03, 0x05, 0x00, 0x20, 0x01, 0x0d, 0xb8, // 2001:db8::/32
+        ];
+
+        assert_eq!(encoded.as_ref(), expected);
+    }
+}
@@ -3,20 +3,33 @@
 use std::{
     borrow::BorrowMut,
     collections::HashMap,
+    convert::{Infallible, TryFrom},
+    fmt,
+    net::IpAddr,
     path::Path,
     str::FromStr,
     sync::{Arc, RwLock},
 };
 
+use bcder::{decode::DecodeError, Captured, Mode, Tag};
+use bytes::Bytes;
 use chrono::Duration;
 
 use rpki::{
-    ca::{idexchange::CaHandle, provisioning::ResourceClassName, publication::Base64},
+    ca::{
+        idexchange::{CaHandle, ServiceUri},
+        provisioning::ResourceClassName,
+        publication::Base64,
+    },
     crypto::{DigestAlgorithm, KeyIdentifier},
     repository::{
-        crl::{Crl, TbsCertList},
+        aspa::{Aspa, ProviderAs},
+        cert::Cert,
+        crl::{Crl, CrlEntry, TbsCertList},
         manifest::{FileAndHash, Manifest, ManifestContent},
-        sigobj::SignedObjectBuilder,
+        resources::{AddressFamily, Asn, Prefix, ResourceSet},
+        roa::Roa,
+        sigobj::{SignedObject, SignedObjectBuilder},
         x509::{Name, Serial, Time, Validity},
     },
     rrdp::Hash,
@@ -26,8 +39,10 @@ use rpki::{
 use crate::{
     commons::{
         api::{
-            rrdp::PublishElement, CertInfo, IssuedCertificate, ObjectName, ReceivedCert, RepositoryContact, Revocation,
-            Revocations, Timestamp,
+            rrdp::PublishElement, AsNumber, AspaDefinition, CaPublishedObjects, CertInfo, IssuedCertificate,
+            KeyPublishedObjects, ManifestEntry, ObjectName, PublishedKeyState, PublishedObjectContent,
+            PublishedObjectInfo, ReceivedCert, RepositoryContact, ResourceClassPublishedObjects, Revocation,
+            Revocations, RoaPayload, Timestamp, TypedPrefix,
         },
         crypto::KrillSigner,
         error::Error,
@@ -41,7 +56,10 @@ use crate::{
     },
 };
 
-use super::{AspaInfo, AspaObjectsUpdates, BgpSecCertInfo, BgpSecCertificateUpdates, RoaInfo};
+use super::{
+    AspaInfo, AspaObjectsUpdates, BgpSecCertInfo, BgpSecCertificateUpdates, GhostbusterInfo, PrefixListInfo,
+    PrefixListObjectsUpdates, RoaInfo, TakInfo,
+};
 
 //------------ CaObjectsStore ----------------------------------------------
 
@@ -111,6 +129,13 @@ impl PreSaveEventListener<CertAuth> for CaObjectsStore {
                         objects.update_bgpsec_certs(resource_class_name, updates)?;
                         force_reissue = true;
                     }
+                    super::CaEvtDet::PrefixListObjectsUpdated {
+                        resource_class_name,
+                        updates,
+                    } => {
+                        objects.update_prefix_lists(resource_class_name, updates)?;
+                        force_reissue = true;
+                    }
                     super::CaEvtDet::ChildCertificatesUpdated {
                         resource_class_name,
                         updates,
@@ -118,6 +143,20 @@ impl PreSaveEventListener<CertAuth> for CaObjectsStore {
                         objects.update_certs(resource_class_name, updates)?;
                         force_reissue = true;
                     }
+                    super::CaEvtDet::TakObjectsUpdated {
+                        resource_class_name,
+                        taks,
+                    } => {
+                        objects.update_taks(resource_class_name, taks)?;
+                        force_reissue = true;
+                    }
+                    super::CaEvtDet::GhostbusterObjectUpdated {
+                        resource_class_name,
+                        ghostbuster,
+                    } => {
+                        objects.update_ghostbuster(resource_class_name, ghostbuster.as_ref())?;
+                        force_reissue = true;
+                    }
                     super::CaEvtDet::KeyPendingToActive {
                         resource_class_name,
                         current_key,
@@ -160,6 +199,12 @@ impl PreSaveEventListener<CertAuth> for CaObjectsStore {
                         objects.update_repo(contact);
                         force_reissue = true;
                     }
+                    super::CaEvtDet::RepoMirrorAdded { contact } => {
+                        objects.add_mirror(contact);
+                    }
+                    super::CaEvtDet::RepoMirrorRemoved { service_uri } => {
+                        objects.remove_mirror(service_uri);
+                    }
                     _ => {}
                 }
             }
@@ -263,6 +308,10 @@ pub struct CaObjects {
 
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     deprecated_repos: Vec<DeprecatedRepository>,
+
+    // Repositories which get the same objects as the current repository.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    mirrors: Vec<RepositoryContact>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -310,6 +359,7 @@ impl CaObjects {
             repo,
             classes,
             deprecated_repos,
+            mirrors: vec![],
         }
     }
 
@@ -332,6 +382,20 @@ impl CaObjects {
         res
     }
 
+    /// Returns the repository mirror with the given service URI, and the
+    /// PublishElements for the current repository which it should get.
+    pub fn mirror_elements(&self, service_uri: &str) -> Option<(RepositoryContact, Vec<PublishElement>)> {
+        let mirror = self
+            .mirrors
+            .iter()
+            .find(|mirror| mirror.server_info().service_uri().as_str() == service_uri)?;
+
+        let repo = self.repo.as_ref()?;
+        let elements = self.repo_elements_map().remove(repo).unwrap_or_default();
+
+        Some((mirror.clone(), elements))
+    }
+
     /// Returns all PublishElements in all repositories (if there is more than one).
     pub fn all_publish_elements(&self) -> Vec<PublishElement> {
         let mut all_elements = vec![];
@@ -362,6 +426,10 @@ impl CaObjects {
         closest
     }
 
+    pub fn mirrors(&self) -> &Vec<RepositoryContact> {
+        &self.mirrors
+    }
+
     pub fn deprecated_repos(&self) -> &Vec<DeprecatedRepository> {
         &self.deprecated_repos
     }
@@ -378,6 +446,37 @@ impl CaObjects {
         }
     }
 
+    /// Returns the decoded objects for all keys in all resource classes.
+    pub fn published_objects(&self) -> KrillResult<CaPublishedObjects> {
+        let mut classes = vec![];
+        for (class_name, class_objects) in &self.classes {
+            classes.push(ResourceClassPublishedObjects::new(
+                class_name.clone(),
+                class_objects.published_objects()?,
+            ));
+        }
+        classes.sort_by_key(|class| class.class_name().to_string());
+
+        Ok(CaPublishedObjects::new(self.ca.clone(), classes))
+    }
+
+    /// Returns the DER encoded object with the given name, if it is
+    /// published under the given key.
+    pub fn published_object_der(&self, key: &KeyIdentifier, name: &ObjectName) -> Option<Bytes> {
+        self.classes
+            .values()
+            .find_map(|class_objects| class_objects.published_object_der(key, name))
+    }
+
+    /// Returns the certificates of all keys which publish objects.
+    pub fn signing_certs(&self) -> Vec<ReceivedCert> {
+        self.classes
+            .values()
+            .flat_map(|class_objects| class_objects.key_object_sets())
+            .map(|(_, set)| set.signing_cert.clone())
+            .collect()
+    }
+
     /// Add a new resource class, this returns an error in case the class already exists.
     fn add_class(
         &mut self,
@@ -457,11 +556,30 @@ impl CaObjects {
         self.get_class_mut(rcn).map(|rco| rco.update_bgpsec_certs(updates))
     }
 
+    // Update the Signed Prefix Lists in the current set
+    fn update_prefix_lists(&mut self, rcn: &ResourceClassName, updates: &PrefixListObjectsUpdates) -> KrillResult<()> {
+        self.get_class_mut(rcn).map(|rco| rco.update_prefix_lists(updates))
+    }
+
     // Update the issued certificates in the current set
     fn update_certs(&mut self, rcn: &ResourceClassName, cert_updates: &ChildCertificateUpdates) -> KrillResult<()> {
         self.get_class_mut(rcn).map(|rco| rco.update_certs(cert_updates))
     }
 
+    // Update the Ghostbuster record in the current set
+    fn update_ghostbuster(
+        &mut self,
+        rcn: &ResourceClassName,
+        ghostbuster: Option<&GhostbusterInfo>,
+    ) -> KrillResult<()> {
+        self.get_class_mut(rcn).map(|rco| rco.update_ghostbuster(ghostbuster))
+    }
+
+    // Update the TAK objects in all sets
+    fn update_taks(&mut self, rcn: &ResourceClassName, taks: &[TakInfo]) -> KrillResult<()> {
+        self.get_class_mut(rcn).map(|rco| rco.update_taks(taks))
+    }
+
     // Update the received certificate.
     fn update_received_cert(&mut self, rcn: &ResourceClassName, cert: &ReceivedCert) -> KrillResult<()> {
         self.get_class_mut(rcn)?.update_received_cert(cert)
@@ -501,6 +619,22 @@ impl CaObjects {
         self.repo = Some(repo.clone());
     }
 
+    fn add_mirror(&mut self, contact: &RepositoryContact) {
+        self.mirrors.push(contact.clone());
+    }
+
+    // Removes the mirror, and marks it as deprecated so that its objects are withdrawn.
+    fn remove_mirror(&mut self, service_uri: &ServiceUri) {
+        if let Some(pos) = self
+            .mirrors
+            .iter()
+            .position(|mirror| mirror.server_info().service_uri() == service_uri)
+        {
+            let mirror = self.mirrors.remove(pos);
+            self.deprecated_repos.push(DeprecatedRepository::new(mirror, 0));
+        }
+    }
+
     fn has_old_repo(&self, old_repo: &RepositoryContact) -> bool {
         self.classes.values().any(|rco| rco.has_old_repo(old_repo))
     }
@@ -541,6 +675,35 @@ impl ResourceClassObjects {
         }
     }
 
+    /// Returns the object sets for all keys, and the state of each key.
+    fn key_object_sets(&self) -> Vec<(PublishedKeyState, &KeyObjectSet)> {
+        match &self.keys {
+            ResourceClassKeyState::Current(state) => vec![(PublishedKeyState::Current, &state.current_set)],
+            ResourceClassKeyState::Staging(state) => vec![
+                (PublishedKeyState::Current, &state.current_set),
+                (PublishedKeyState::Staging, &state.staging_set),
+            ],
+            ResourceClassKeyState::Old(state) => vec![
+                (PublishedKeyState::Current, &state.current_set),
+                (PublishedKeyState::Old, &state.old_set),
+            ],
+        }
+    }
+
+    fn published_objects(&self) -> KrillResult<Vec<KeyPublishedObjects>> {
+        self.key_object_sets()
+            .into_iter()
+            .map(|(state, set)| set.published_objects_info(state))
+            .collect()
+    }
+
+    fn published_object_der(&self, key: &KeyIdentifier, name: &ObjectName) -> Option<Bytes> {
+        self.key_object_sets()
+            .into_iter()
+            .find(|(_, set)| set.signing_cert.key_identifier() == *key)
+            .and_then(|(_, set)| set.published_object_der(name))
+    }
+
     fn create(key: &CertifiedKey, timing: &IssuanceTimingConfig, signer: &KrillSigner) -> KrillResult<Self> {
         let current_set = KeyObjectSet::create(key, timing, signer)?;
 
@@ -623,6 +786,14 @@ impl ResourceClassObjects {
         }
     }
 
+    fn update_prefix_lists(&mut self, updates: &PrefixListObjectsUpdates) {
+        match self.keys.borrow_mut() {
+            ResourceClassKeyState::Current(state) => state.current_set.update_prefix_lists(updates),
+            ResourceClassKeyState::Staging(state) => state.current_set.update_prefix_lists(updates),
+            ResourceClassKeyState::Old(state) => state.current_set.update_prefix_lists(updates),
+        }
+    }
+
     fn update_certs(&mut self, cert_updates: &ChildCertificateUpdates) {
         match self.keys.borrow_mut() {
             ResourceClassKeyState::Current(state) => state.current_set.update_certs(cert_updates),
@@ -631,6 +802,30 @@ impl ResourceClassObjects {
         }
     }
 
+    fn update_ghostbuster(&mut self, ghostbuster: Option<&GhostbusterInfo>) {
+        match self.keys.borrow_mut() {
+            ResourceClassKeyState::Current(state) => state.current_set.update_ghostbuster(ghostbuster),
+            ResourceClassKeyState::Staging(state) => state.current_set.update_ghostbuster(ghostbuster),
+            ResourceClassKeyState::Old(state) => state.current_set.update_ghostbuster(ghostbuster),
+        }
+    }
+
+    // Unlike other objects, a TAK is published under each key of a trust
+    // anchor, including its new and old key during a key roll.
+    fn update_taks(&mut self, taks: &[TakInfo]) {
+        match self.keys.borrow_mut() {
+            ResourceClassKeyState::Current(state) => state.current_set.update_tak(taks),
+            ResourceClassKeyState::Staging(state) => {
+                state.staging_set.update_tak(taks);
+                state.current_set.update_tak(taks);
+            }
+            ResourceClassKeyState::Old(state) => {
+                state.old_set.update_tak(taks);
+                state.current_set.update_tak(taks);
+            }
+        }
+    }
+
     fn requires_re_issuance(&self, hours: i64) -> bool {
         match &self.keys {
             ResourceClassKeyState::Current(state) => state.current_set.requires_reissuance(hours),
@@ -882,7 +1077,7 @@ impl KeyObjectSet {
         }
     }
 
-    fn create(key: &CertifiedKey, timing: &IssuanceTimingConfig, signer: &KrillSigner) -> KrillResult<Self> {
+    pub fn create(key: &CertifiedKey, timing: &IssuanceTimingConfig, signer: &KrillSigner) -> KrillResult<Self> {
         let signing_cert = key.incoming_cert().clone();
 
         let signing_key = signing_cert.key_identifier();
@@ -916,13 +1111,20 @@ impl KeyObjectSet {
     fn add_elements(&self, map: &mut HashMap<RepositoryContact, Vec<PublishElement>>, dflt_repo: &RepositoryContact) {
         let repo = self.old_repo.as_ref().unwrap_or(dflt_repo);
 
+        let elements = map.entry(repo.clone()).or_default();
+        elements.append(&mut self.publish_elements());
+    }
+
+    /// Returns the manifest, CRL and all other objects of this set as
+    /// elements to publish.
+    pub fn publish_elements(&self) -> Vec<PublishElement> {
         let crl_uri = self.signing_cert.crl_uri();
         let mft_uri = self.signing_cert.mft_uri();
 
-        let elements = map.entry(repo.clone()).or_insert_with(Vec::new);
-
-        elements.push(self.manifest.publish_element(mft_uri));
-        elements.push(self.crl.publish_element(crl_uri));
+        let mut elements = vec![
+            self.manifest.publish_element(mft_uri),
+            self.crl.publish_element(crl_uri),
+        ];
 
         for (name, object) in &self.published_objects {
             elements.push(PublishElement::new(
@@ -930,6 +1132,40 @@ impl KeyObjectSet {
                 self.signing_cert.uri_for_name(name),
             ));
         }
+
+        elements
+    }
+
+    /// Returns the decoded manifest, CRL and all other objects of this set.
+    fn published_objects_info(&self, state: PublishedKeyState) -> KrillResult<KeyPublishedObjects> {
+        let mut objects = vec![
+            self.manifest.info(self.signing_cert.mft_uri())?,
+            self.crl.info(self.signing_cert.crl_uri())?,
+        ];
+
+        let mut others = self
+            .published_objects
+            .iter()
+            .map(|(name, object)| object.info(name, self.signing_cert.uri_for_name(name)))
+            .collect::<KrillResult<Vec<_>>>()?;
+        others.sort_by(|a, b| a.uri().as_str().cmp(b.uri().as_str()));
+        objects.append(&mut others);
+
+        Ok(KeyPublishedObjects::new(
+            self.signing_cert.key_identifier(),
+            state,
+            objects,
+        ))
+    }
+
+    fn published_object_der(&self, name: &ObjectName) -> Option<Bytes> {
+        if name == &self.manifest.name {
+            Some(self.manifest.base64.to_bytes())
+        } else if name == &self.crl.name {
+            Some(self.crl.base64.to_bytes())
+        } else {
+            self.published_objects.get(name).map(|object| object.base64.to_bytes())
+        }
     }
 
     pub fn requires_reissuance(&self, hours: i64) -> bool {
@@ -999,7 +1235,59 @@ impl KeyObjectSet {
         }
     }
 
-    fn update_certs(&mut self, cert_updates: &ChildCertificateUpdates) {
+    fn update_prefix_lists(&mut self, updates: &PrefixListObjectsUpdates) {
+        for prefix_list_info in updates.updated() {
+            let name = ObjectName::prefix_list(prefix_list_info.asn());
+            let published_object = PublishedObject::for_prefix_list(name.clone(), prefix_list_info);
+            if let Some(old) = self.published_objects.insert(name, published_object) {
+                self.revocations.add(old.revoke());
+            }
+        }
+        for removed in updates.removed() {
+            let name = ObjectName::prefix_list(*removed);
+            if let Some(old) = self.published_objects.remove(&name) {
+                self.revocations.add(old.revoke());
+            }
+        }
+    }
+
+    fn update_ghostbuster(&mut self, ghostbuster: Option<&GhostbusterInfo>) {
+        let name = ObjectName::ghostbuster();
+
+        let replaced = match ghostbuster {
+            Some(ghostbuster) => {
+                let published_object = PublishedObject::for_ghostbuster(name.clone(), ghostbuster);
+                self.published_objects.insert(name, published_object)
+            }
+            None => self.published_objects.remove(&name),
+        };
+
+        if let Some(old) = replaced {
+            self.revocations.add(old.revoke());
+        }
+    }
+
+    // Publishes the TAK signed by the key of this set, or withdraws
+    // the TAK for this key if there is none.
+    fn update_tak(&mut self, taks: &[TakInfo]) {
+        let signing_key = self.signing_cert.key_identifier();
+        let name = ObjectName::tak_for_key(&signing_key);
+
+        let replaced = match taks.iter().find(|tak| tak.key() == signing_key) {
+            Some(tak) => {
+                let published_object =
+                    PublishedObject::new(name.clone(), tak.base64().clone(), tak.serial(), tak.expires());
+                self.published_objects.insert(name, published_object)
+            }
+            None => self.published_objects.remove(&name),
+        };
+
+        if let Some(old) = replaced {
+            self.revocations.add(old.revoke());
+        }
+    }
+
+    pub fn update_certs(&mut self, cert_updates: &ChildCertificateUpdates) {
         for removed in cert_updates.removed() {
             let name = ObjectName::new(removed, "cer");
             if let Some(old) = self.published_objects.remove(&name) {
@@ -1030,7 +1318,7 @@ impl KeyObjectSet {
         }
     }
 
-    fn reissue(&mut self, timing: &IssuanceTimingConfig, signer: &KrillSigner) -> KrillResult<()> {
+    pub fn reissue(&mut self, timing: &IssuanceTimingConfig, signer: &KrillSigner) -> KrillResult<()> {
         self.revision.next(timing);
 
         self.revocations.purge();
@@ -1158,6 +1446,38 @@ impl<T> PublishedItem<T> {
 pub struct PublishedItemManifest;
 pub type PublishedManifest = PublishedItem<PublishedItemManifest>;
 
+impl PublishedManifest {
+    fn info(&self, uri: uri::Rsync) -> KrillResult<PublishedObjectInfo> {
+        let mft = Manifest::decode(self.base64.to_bytes(), true)
+            .map_err(|e| Error::Custom(format!("Cannot decode manifest '{}': {}", uri, e)))?;
+
+        let entries = mft
+            .iter()
+            .map(|entry| {
+                ManifestEntry::new(
+                    String::from_utf8_lossy(entry.file()).to_string(),
+                    hex::encode(entry.hash()),
+                )
+            })
+            .collect();
+
+        let content = PublishedObjectContent::Manifest {
+            number: mft.manifest_number(),
+            this_update: mft.this_update(),
+            next_update: mft.next_update(),
+            entries,
+        };
+
+        Ok(PublishedObjectInfo::new(
+            uri,
+            self.hash,
+            self.serial,
+            mft.cert().validity(),
+            content,
+        ))
+    }
+}
+
 impl From<Manifest> for PublishedManifest {
     fn from(mft: Manifest) -> Self {
         PublishedItem::new(
@@ -1175,6 +1495,35 @@ impl From<Manifest> for PublishedManifest {
 pub struct PublishedItemCrl;
 pub type PublishedCrl = PublishedItem<PublishedItemCrl>;
 
+impl PublishedCrl {
+    fn info(&self, uri: uri::Rsync) -> KrillResult<PublishedObjectInfo> {
+        let crl = Crl::decode(self.base64.to_bytes())
+            .map_err(|e| Error::Custom(format!("Cannot decode CRL '{}': {}", uri, e)))?;
+
+        let revoked = crl
+            .revoked_certs()
+            .iter()
+            .map(revoked_serial)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Error::Custom(format!("Cannot decode CRL '{}': {}", uri, e)))?;
+
+        let content = PublishedObjectContent::Crl {
+            number: crl.crl_number(),
+            this_update: crl.this_update(),
+            next_update: crl.next_update(),
+            revoked,
+        };
+
+        Ok(PublishedObjectInfo::new(
+            uri,
+            self.hash,
+            self.serial,
+            Validity::new(crl.this_update(), crl.next_update()),
+            content,
+        ))
+    }
+}
+
 impl From<Crl> for PublishedCrl {
     fn from(crl: Crl) -> Self {
         PublishedItem::new(
@@ -1206,6 +1555,24 @@ impl PublishedObject {
         )
     }
 
+    pub fn for_prefix_list(name: ObjectName, prefix_list: &PrefixListInfo) -> Self {
+        PublishedObject::new(
+            name,
+            prefix_list.base64().clone(),
+            prefix_list.serial(),
+            prefix_list.expires(),
+        )
+    }
+
+    pub fn for_ghostbuster(name: ObjectName, ghostbuster: &GhostbusterInfo) -> Self {
+        PublishedObject::new(
+            name,
+            ghostbuster.base64().clone(),
+            ghostbuster.serial(),
+            ghostbuster.expires(),
+        )
+    }
+
     pub fn for_cert_info<T>(cert: &CertInfo<T>) -> Self {
         PublishedObject::new(
             cert.name().clone(),
@@ -1218,6 +1585,119 @@ impl PublishedObject {
     pub fn for_bgpsec_cert_info(cert: &BgpSecCertInfo) -> Self {
         PublishedObject::new(cert.name(), cert.base64().clone(), cert.serial(), cert.expires())
     }
+
+    /// Decodes the object, using the extension of its name to determine
+    /// its type. Router certificates use names starting with "ROUTER-".
+    fn info(&self, name: &ObjectName, uri: uri::Rsync) -> KrillResult<PublishedObjectInfo> {
+        let name: &str = name.as_ref();
+        let bytes = self.base64.to_bytes();
+        let decode_err = |e: &dyn fmt::Display| Error::Custom(format!("Cannot decode object '{}': {}", uri, e));
+
+        let (validity, content) = if name.ends_with(".cer") {
+            let cert = Cert::decode(bytes).map_err(|e| decode_err(&e))?;
+            let subject_key = cert.subject_key_identifier();
+            let resources = ResourceSet::try_from(&cert).map_err(|e| decode_err(&e))?;
+            let content = if name.starts_with("ROUTER-") {
+                PublishedObjectContent::RouterCertificate { subject_key, resources }
+            } else {
+                PublishedObjectContent::ChildCertificate { subject_key, resources }
+            };
+            (cert.validity(), content)
+        } else if name.ends_with(".roa") {
+            // Check the object as a ROA first, the content is decoded separately
+            // because the rpki crate only exposes it after full validation.
+            Roa::decode(bytes.clone(), true).map_err(|e| decode_err(&e))?;
+            let object = SignedObject::decode(bytes, true).map_err(|e| decode_err(&e))?;
+            let (asn, prefixes) = decode_roa_content(&object).map_err(|e| decode_err(&e))?;
+            let asn = AsNumber::new(asn.into_u32());
+
+            // ROAs leave out the max length if it equals the prefix length, use
+            // the explicit max length that Krill uses for ROA configurations.
+            let authorizations = prefixes
+                .into_iter()
+                .map(|(prefix, max_length)| {
+                    TypedPrefix::from_str(&prefix)
+                        .map(|prefix| RoaPayload::new(asn, prefix, max_length).into_explicit_max_length())
+                        .map_err(|e| decode_err(&e))
+                })
+                .collect::<KrillResult<Vec<_>>>()?;
+            (object.cert().validity(), PublishedObjectContent::Roa { authorizations })
+        } else if name.ends_with(".asa") {
+            Aspa::decode(bytes.clone(), true).map_err(|e| decode_err(&e))?;
+            let object = SignedObject::decode(bytes, true).map_err(|e| decode_err(&e))?;
+            let definition = decode_aspa_content(&object).map_err(|e| decode_err(&e))?;
+            (object.cert().validity(), PublishedObjectContent::Aspa { definition })
+        } else {
+            let object = SignedObject::decode(bytes, true).map_err(|e| decode_err(&e))?;
+            (object.cert().validity(), PublishedObjectContent::Other)
+        };
+
+        Ok(PublishedObjectInfo::new(uri, self.hash, self.serial, validity, content))
+    }
+}
+
+/// Returns the serial number of a revoked certificate. CrlEntry does not
+/// give access to it, so it is taken from the encoded entry.
+fn revoked_serial(entry: CrlEntry) -> Result<Serial, DecodeError<Infallible>> {
+    let captured = Captured::from_values(Mode::Der, entry.encode());
+    Mode::Der.decode(captured.as_slice(), |cons| {
+        cons.take_sequence(|cons| {
+            let serial = Serial::take_from(cons)?;
+            cons.skip_all()?;
+            Ok(serial)
+        })
+    })
+}
+
+/// Decodes the RouteOriginAttestation in a ROA, as defined in RFC 6482,
+/// into its ASN and the prefixes in "address/length" notation with their
+/// optional max length.
+#[allow(clippy::type_complexity)]
+fn decode_roa_content(object: &SignedObject) -> Result<(Asn, Vec<(String, Option<u8>)>), DecodeError<Infallible>> {
+    object.decode_content(|cons| {
+        cons.take_sequence(|cons| {
+            cons.take_opt_constructed_if(Tag::CTX_0, |cons| cons.skip_u8_if(0))?;
+            let asn = Asn::take_from(cons)?;
+            let mut prefixes = vec![];
+            cons.take_sequence(|cons| {
+                while let Some(()) = cons.take_opt_sequence(|cons| {
+                    let family = AddressFamily::take_from(cons)?;
+                    cons.take_sequence(|cons| {
+                        while let Some((prefix, max_length)) =
+                            cons.take_opt_sequence(|cons| Ok((Prefix::take_from(cons)?, cons.take_opt_u8()?)))?
+                        {
+                            let addr = match family {
+                                AddressFamily::Ipv4 => IpAddr::V4(prefix.addr().to_v4()),
+                                AddressFamily::Ipv6 => IpAddr::V6(prefix.addr().to_v6()),
+                            };
+                            prefixes.push((format!("{}/{}", addr, prefix.addr_len()), max_length));
+                        }
+                        Ok(())
+                    })
+                })? {}
+                Ok(())
+            })?;
+            Ok((asn, prefixes))
+        })
+    })
+}
+
+/// Decodes the ASProviderAttestation in an ASPA object.
+fn decode_aspa_content(object: &SignedObject) -> Result<AspaDefinition, DecodeError<Infallible>> {
+    object.decode_content(|cons| {
+        cons.take_sequence(|cons| {
+            cons.take_opt_constructed_if(Tag::CTX_0, |cons| cons.skip_u8_if(0))?;
+            let customer = Asn::take_from(cons)?;
+            let mut providers = vec![];
+            cons.take_sequence(|cons| {
+                while let Some(provider) = ProviderAs::take_opt_from(cons)? {
+                    providers.push(provider);
+                }
+                Ok(())
+            })?;
+            Ok(AspaDefinition::new(customer, providers))
+        })
+    })
 }
 
 //------------ CrlBuilder --------------------------------------------------
@@ -18,7 +18,10 @@ use rpki::{
 
 use crate::{
     commons::{
-        api::{IssuedCertificate, ReceivedCert, ResourceClassInfo, RoaConfiguration, SuspendedCert, UnsuspendedCert},
+        api::{
+            GhostbusterContact, IssuedCertificate, ReceivedCert, ResourceClassInfo, RoaConfiguration, SuspendedCert,
+            UnsuspendedCert,
+        },
         crypto::{CsrInfo, KrillSigner, SignSupport},
         error::Error,
         KrillResult,
@@ -27,13 +30,16 @@ use crate::{
         ca::events::RoaUpdates,
         ca::{
             self, ta_handle, AspaObjects, AspaObjectsUpdates, CaEvtDet, CertifiedKey, ChildCertificates, CurrentKey,
-            KeyState, NewKey, OldKey, PendingKey, Roas, Routes,
+            GhostbusterInfo, GhostbusterObject, KeyState, NewKey, OldKey, PendingKey, Roas, Routes,
         },
         config::{Config, IssuanceTimingConfig},
     },
 };
 
-use super::{AspaDefinitions, BgpSecCertificateUpdates, BgpSecCertificates, BgpSecDefinitions, RoaInfo};
+use super::{
+    AspaDefinitions, BgpSecCertificateUpdates, BgpSecCertificates, BgpSecDefinitions, PrefixListDefinitions,
+    PrefixListObjects, PrefixListObjectsUpdates, RoaInfo,
+};
 
 //------------ ResourceClass -----------------------------------------------
 
@@ -65,6 +71,12 @@ pub struct ResourceClass {
     #[serde(skip_serializing_if = "BgpSecCertificates::is_empty", default)]
     bgpsec_certificates: BgpSecCertificates,
 
+    #[serde(skip_serializing_if = "PrefixListObjects::is_empty", default)]
+    prefix_lists: PrefixListObjects,
+
+    #[serde(skip_serializing_if = "GhostbusterObject::is_empty", default)]
+    ghostbuster: GhostbusterObject,
+
     #[serde(skip_serializing_if = "ChildCertificates::is_empty", default)]
     certificates: ChildCertificates,
 
@@ -92,6 +104,8 @@ impl ResourceClass {
             aspas: AspaObjects::default(),
             certificates: ChildCertificates::default(),
             bgpsec_certificates: BgpSecCertificates::default(),
+            prefix_lists: PrefixListObjects::default(),
+            ghostbuster: GhostbusterObject::default(),
             last_key_change: Time::now(),
             key_state: KeyState::create(pending_key),
         }
@@ -107,6 +121,8 @@ impl ResourceClass {
             aspas: AspaObjects::default(),
             certificates: ChildCertificates::default(),
             bgpsec_certificates: BgpSecCertificates::default(),
+            prefix_lists: PrefixListObjects::default(),
+            ghostbuster: GhostbusterObject::default(),
             last_key_change: Time::now(),
             key_state: KeyState::create(pending_key),
         }
@@ -164,6 +180,21 @@ impl ResourceClass {
         matches!(&self.key_state, KeyState::Active(_))
     }
 
+    /// Returns true if a key roll can be initiated, and the current key is
+    /// older than the given duration.
+    pub fn key_roll_due(&self, duration: Duration) -> bool {
+        self.key_roll_possible() && (duration <= Duration::seconds(0) || self.last_key_change + duration <= Time::now())
+    }
+
+    /// Gets the old key for a key roll, or returns an error if there is none.
+    pub fn get_old_key(&self) -> KrillResult<&OldKey> {
+        if let KeyState::RollOld(_, old_key) = &self.key_state {
+            Ok(old_key)
+        } else {
+            Err(Error::KeyUseNoOldKey)
+        }
+    }
+
     /// Gets the new key for a key roll, or returns an error if there is none.
     pub fn get_new_key(&self) -> KrillResult<&NewKey> {
         if let KeyState::RollNew(new_key, _) = &self.key_state {
@@ -202,8 +233,11 @@ impl ResourceClass {
         handle: &CaHandle,
         rcvd_cert: ReceivedCert,
         all_routes: &Routes,
+        delegated: &ResourceSet,
         all_aspas: &AspaDefinitions,
         all_bgpsecs: &BgpSecDefinitions,
+        all_prefix_lists: &PrefixListDefinitions,
+        ghostbuster: Option<&GhostbusterContact>,
         config: &Config,
         signer: &KrillSigner,
     ) -> KrillResult<Vec<CaEvtDet>> {
@@ -226,11 +260,17 @@ impl ResourceClass {
 
                     let current_key = CertifiedKey::create(rcvd_cert);
 
-                    let roa_updates = self.roas.update(all_routes, &current_key, config, signer)?;
+                    let roa_updates = self.roas.update(all_routes, delegated, &current_key, config, signer)?;
                     let aspa_updates = self.aspas.update(all_aspas, &current_key, config, signer)?;
                     let bgpsec_updates = self
                         .bgpsec_certificates
                         .update(all_bgpsecs, &current_key, config, signer)?;
+                    let prefix_list_updates =
+                        self.prefix_lists
+                            .update(all_prefix_lists, &current_key, &config.issuance_timing, signer)?;
+                    let ghostbuster_update =
+                        self.ghostbuster
+                            .update(ghostbuster, &current_key, &config.issuance_timing, signer)?;
 
                     let mut events = vec![CaEvtDet::KeyPendingToActive {
                         resource_class_name: self.name.clone(),
@@ -258,6 +298,20 @@ impl ResourceClass {
                         })
                     }
 
+                    if prefix_list_updates.contains_changes() {
+                        events.push(CaEvtDet::PrefixListObjectsUpdated {
+                            resource_class_name: self.name.clone(),
+                            updates: prefix_list_updates,
+                        })
+                    }
+
+                    if let Some(ghostbuster) = ghostbuster_update {
+                        events.push(CaEvtDet::GhostbusterObjectUpdated {
+                            resource_class_name: self.name.clone(),
+                            ghostbuster,
+                        })
+                    }
+
                     Ok(events)
                 }
             }
@@ -266,8 +320,10 @@ impl ResourceClass {
                 current,
                 rcvd_cert,
                 all_routes,
+                delegated,
                 all_aspas,
                 all_bgpsecs,
+                all_prefix_lists,
                 config,
                 signer,
             ),
@@ -284,8 +340,10 @@ impl ResourceClass {
                         current,
                         rcvd_cert,
                         all_routes,
+                        delegated,
                         all_aspas,
                         all_bgpsecs,
+                        all_prefix_lists,
                         config,
                         signer,
                     )
@@ -304,8 +362,10 @@ impl ResourceClass {
                         current,
                         rcvd_cert,
                         all_routes,
+                        delegated,
                         all_aspas,
                         all_bgpsecs,
+                        all_prefix_lists,
                         config,
                         signer,
                     )
@@ -318,8 +378,10 @@ impl ResourceClass {
                     current,
                     rcvd_cert,
                     all_routes,
+                    delegated,
                     all_aspas,
                     all_bgpsecs,
+                    all_prefix_lists,
                     config,
                     signer,
                 )
@@ -334,8 +396,10 @@ impl ResourceClass {
         current_key: &CurrentKey,
         rcvd_cert: ReceivedCert,
         all_routes: &Routes,
+        delegated: &ResourceSet,
         all_aspas: &AspaDefinitions,
         all_bgpsecs: &BgpSecDefinitions,
+        all_prefix_lists: &PrefixListDefinitions,
         config: &Config,
         signer: &KrillSigner,
     ) -> KrillResult<Vec<CaEvtDet>> {
@@ -380,7 +444,7 @@ impl ResourceClass {
             // Re-issue ROAs based on updated resources.
             // Note that route definitions will not have changed in this case, but the decision logic is all the same.
             {
-                let updates = self.roas.update(all_routes, &updated_key, config, signer)?;
+                let updates = self.roas.update(all_routes, delegated, &updated_key, config, signer)?;
                 if !updates.is_empty() {
                     res.push(CaEvtDet::RoasUpdated {
                         resource_class_name: self.name.clone(),
@@ -414,6 +478,20 @@ impl ResourceClass {
                     })
                 }
             }
+
+            // Re-issue Signed Prefix Lists based on updated resources.
+            // Note that definitions will not have changed in this case, but the decision logic is all the same.
+            {
+                let updates =
+                    self.prefix_lists
+                        .update(all_prefix_lists, &updated_key, &config.issuance_timing, signer)?;
+                if !updates.is_empty() {
+                    res.push(CaEvtDet::PrefixListObjectsUpdated {
+                        resource_class_name: self.name.clone(),
+                        updates,
+                    })
+                }
+            }
         } else {
             info!(
                 "Received new certificate for CA '{}' under RC '{}', valid until: {}",
@@ -436,6 +514,7 @@ impl ResourceClass {
         &self,
         handle: &CaHandle,
         entitlement: &ResourceClassEntitlements,
+        limit: &RequestResourceLimit,
         base_repo: &RepoInfo,
         signer: &KrillSigner,
     ) -> KrillResult<Vec<CaEvtDet>> {
@@ -443,6 +522,7 @@ impl ResourceClass {
             handle,
             self.name.clone(),
             entitlement,
+            limit,
             base_repo,
             &self.name_space,
             signer,
@@ -452,11 +532,12 @@ impl ResourceClass {
     /// Request new certificates for all keys when the base repo changes.
     pub fn make_request_events_new_repo(
         &self,
+        limit: &RequestResourceLimit,
         base_repo: &RepoInfo,
         signer: &KrillSigner,
     ) -> KrillResult<Vec<CaEvtDet>> {
         self.key_state
-            .request_certs_new_repo(self.name.clone(), base_repo, &self.name_space, signer)
+            .request_certs_new_repo(self.name.clone(), limit, base_repo, &self.name_space, signer)
     }
 
     /// This function returns all current certificate requests.
@@ -569,6 +650,7 @@ impl ResourceClass {
     /// Initiate a key roll
     pub fn keyroll_initiate(
         &self,
+        limit: RequestResourceLimit,
         base_repo: &RepoInfo,
         duration: Duration,
         signer: &KrillSigner,
@@ -580,12 +662,33 @@ impl ResourceClass {
         self.key_state.keyroll_initiate(
             self.name.clone(),
             self.parent_rc_name.clone(),
+            limit,
             base_repo,
             &self.name_space,
             signer,
         )
     }
 
+    /// Initiate a key roll for a trust anchor. The trust anchor certifies
+    /// its new key itself, so the new key is staged straight away rather
+    /// than requesting a certificate for it.
+    pub fn ta_keyroll_initiate(&self, new_key: CertifiedKey) -> KrillResult<Vec<CaEvtDet>> {
+        if !self.key_roll_possible() {
+            return Err(Error::KeyRollNotAllowed);
+        }
+
+        Ok(vec![
+            CaEvtDet::KeyRollPendingKeyAdded {
+                resource_class_name: self.name.clone(),
+                pending_key_id: *new_key.key_id(),
+            },
+            CaEvtDet::KeyPendingToNew {
+                resource_class_name: self.name.clone(),
+                new_key,
+            },
+        ])
+    }
+
     /// Activate a new key, if it's been longer than the staging period.
     pub fn keyroll_activate(
         &self,
@@ -638,6 +741,21 @@ impl ResourceClass {
                     });
                 }
 
+                let prefix_list_updates = self.prefix_lists.renew(new_key, None, issuance_timing, signer)?;
+                if !prefix_list_updates.is_empty() {
+                    events.push(CaEvtDet::PrefixListObjectsUpdated {
+                        resource_class_name: self.name.clone(),
+                        updates: prefix_list_updates,
+                    });
+                }
+
+                if let Some(ghostbuster) = self.ghostbuster.renew(new_key, None, issuance_timing, signer)? {
+                    events.push(CaEvtDet::GhostbusterObjectUpdated {
+                        resource_class_name: self.name.clone(),
+                        ghostbuster: Some(ghostbuster),
+                    });
+                }
+
                 Ok(events)
             }
         } else {
@@ -750,12 +868,19 @@ impl ResourceClass {
         self.roas.renew(true, key, issuance_timing, signer)
     }
 
-    /// Updates the ROAs in accordance with the current authorizations
-    pub fn update_roas(&self, routes: &Routes, config: &Config, signer: &KrillSigner) -> KrillResult<RoaUpdates> {
+    /// Updates the ROAs in accordance with the current authorizations,
+    /// and the resources delegated to children.
+    pub fn update_roas(
+        &self,
+        routes: &Routes,
+        delegated: &ResourceSet,
+        config: &Config,
+        signer: &KrillSigner,
+    ) -> KrillResult<RoaUpdates> {
         if let Ok(key) = self.get_current_key() {
             let resources = key.incoming_cert().resources();
             let routes = routes.filter(resources);
-            self.roas.update(&routes, key, config, signer)
+            self.roas.update(&routes, delegated, key, config, signer)
         } else {
             debug!("no ROAs to update - resource class has no current key");
             Ok(RoaUpdates::default())
@@ -813,6 +938,87 @@ impl ResourceClass {
     }
 }
 
+/// # Signed Prefix Lists
+///
+impl ResourceClass {
+    /// Renew all Signed Prefix Lists under the current key for which the
+    /// not-after time is closer than the ROA re-issuance threshold.
+    pub fn renew_prefix_lists(
+        &self,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<PrefixListObjectsUpdates> {
+        if let Ok(key) = self.get_current_key() {
+            let renew_threshold = Some(issuance_timing.new_roa_issuance_threshold());
+            self.prefix_lists.renew(key, renew_threshold, issuance_timing, signer)
+        } else {
+            debug!("no prefix lists to renew - resource class has no current key");
+            Ok(PrefixListObjectsUpdates::default())
+        }
+    }
+
+    /// Updates the Signed Prefix Lists in accordance with the supplied definitions
+    pub fn update_prefix_lists(
+        &self,
+        all_prefix_lists: &PrefixListDefinitions,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<PrefixListObjectsUpdates> {
+        if let Ok(key) = self.get_current_key() {
+            self.prefix_lists.update(all_prefix_lists, key, issuance_timing, signer)
+        } else {
+            debug!("no prefix lists to update - resource class has no current key");
+            Ok(PrefixListObjectsUpdates::default())
+        }
+    }
+
+    /// Apply Signed Prefix List object changes from events
+    pub fn prefix_list_objects_updated(&mut self, updates: PrefixListObjectsUpdates) {
+        self.prefix_lists.updated(updates)
+    }
+}
+
+/// # Ghostbusters
+///
+impl ResourceClass {
+    /// Returns the Ghostbuster record to publish for the given contact,
+    /// if it changed. The record is removed if there is no contact.
+    pub fn update_ghostbuster(
+        &self,
+        contact: Option<&GhostbusterContact>,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<Option<Option<GhostbusterInfo>>> {
+        if let Ok(key) = self.get_current_key() {
+            self.ghostbuster.update(contact, key, issuance_timing, signer)
+        } else {
+            debug!("no Ghostbuster record to update - resource class has no current key");
+            Ok(None)
+        }
+    }
+
+    /// Renew the Ghostbuster record if it would expire before the
+    /// ROA re-issuance threshold.
+    pub fn renew_ghostbuster(
+        &self,
+        issuance_timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<Option<GhostbusterInfo>> {
+        if let Ok(key) = self.get_current_key() {
+            let renew_threshold = Some(issuance_timing.new_roa_issuance_threshold());
+            self.ghostbuster.renew(key, renew_threshold, issuance_timing, signer)
+        } else {
+            debug!("no Ghostbuster record to renew - resource class has no current key");
+            Ok(None)
+        }
+    }
+
+    /// Apply the Ghostbuster record change from an event
+    pub fn ghostbuster_updated(&mut self, ghostbuster: Option<GhostbusterInfo>) {
+        self.ghostbuster.updated(ghostbuster)
+    }
+}
+
 /// # BGPSec
 ///
 impl ResourceClass {
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, fmt, ops::Deref, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    ops::Deref,
+    str::FromStr,
+};
 
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
@@ -16,7 +21,11 @@ use rpki::{
 
 use crate::{
     commons::{
-        api::{ObjectName, Revocation, RoaAggregateKey, RoaConfiguration, RoaPayload},
+        api::{
+            AsNumber, ObjectName, Revocation, RoaAggregateKey, RoaConfiguration, RoaConfigurationUpdates, RoaDraft,
+            RoaDraftList, RoaDraftName, RoaMetadata, RoaPayload, TimedRoaChange, TimedRoaChangeKind, Timestamp,
+            TypedPrefix,
+        },
         crypto::KrillSigner,
         error::Error,
         KrillResult,
@@ -100,6 +109,10 @@ impl<'de> Deserialize<'de> for RoaPayloadJsonMapKey {
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Routes {
     map: HashMap<RoaPayloadJsonMapKey, RouteInfo>,
+
+    // Issue AS0 ROAs for held space not covered by any authorization
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    as0_unrouted: bool,
 }
 
 impl Routes {
@@ -115,7 +128,47 @@ impl Routes {
                 }
             })
             .collect();
-        Routes { map: filtered }
+        Routes {
+            map: filtered,
+            as0_unrouted: self.as0_unrouted,
+        }
+    }
+
+    pub fn as0_unrouted(&self) -> bool {
+        self.as0_unrouted
+    }
+
+    pub fn set_as0_unrouted(&mut self, as0_unrouted: bool) {
+        self.as0_unrouted = as0_unrouted;
+    }
+
+    /// Returns AS0 payloads for the address space in the given resources
+    /// which is not covered by any authorization, and which is not delegated
+    /// to any child, if this is enabled.
+    pub fn as0_unrouted_payloads(&self, resources: &ResourceSet, delegated: &ResourceSet) -> Vec<RoaPayload> {
+        if !self.as0_unrouted {
+            return vec![];
+        }
+
+        let mut unrouted = resources_minus(resources, delegated);
+        for auth in self.map.keys() {
+            unrouted = resources_minus(&unrouted, &ResourceSet::from(auth.prefix()));
+        }
+
+        TypedPrefix::for_resource_set(&unrouted)
+            .into_iter()
+            .map(|prefix| RoaPayload::new(AsNumber::zero(), prefix, Some(prefix.addr_len())))
+            .collect()
+    }
+
+    /// Returns these routes, including the AS0 payloads for any unrouted
+    /// space in the given resources if this is enabled. These are not
+    /// configured by users, but should be issued as ROAs all the same.
+    pub fn with_as0_unrouted(mut self, resources: &ResourceSet, delegated: &ResourceSet) -> Self {
+        for payload in self.as0_unrouted_payloads(resources, delegated) {
+            self.add(RoaPayloadJsonMapKey::from(payload));
+        }
+        self
     }
 
     pub fn all(&self) -> impl Iterator<Item = (&RoaPayloadJsonMapKey, &RouteInfo)> {
@@ -125,7 +178,15 @@ impl Routes {
     pub fn roa_configurations(&self) -> Vec<RoaConfiguration> {
         self.map
             .iter()
-            .map(|(payload_key, route_info)| RoaConfiguration::new(payload_key.0, route_info.comment().cloned()))
+            .map(|(payload_key, route_info)| {
+                let configuration = RoaConfiguration::new(payload_key.0, route_info.comment().cloned())
+                    .with_time_bounds(route_info.not_before(), route_info.not_after());
+                if route_info.metadata().is_empty() {
+                    configuration
+                } else {
+                    configuration.with_metadata(route_info.metadata().clone())
+                }
+            })
             .collect()
     }
 
@@ -180,12 +241,52 @@ impl Routes {
         }
     }
 
+    /// Updates the metadata for an authorization
+    pub fn metadata(&mut self, auth: &RoaPayloadJsonMapKey, metadata: RoaMetadata) {
+        if let Some(info) = self.map.get_mut(auth) {
+            info.set_metadata(metadata)
+        }
+    }
+
+    /// Updates the time bounds for an authorization
+    pub fn time_bounds(
+        &mut self,
+        auth: &RoaPayloadJsonMapKey,
+        not_before: Option<Timestamp>,
+        not_after: Option<Timestamp>,
+    ) {
+        if let Some(info) = self.map.get_mut(auth) {
+            info.set_time_bounds(not_before, not_after)
+        }
+    }
+
+    /// Returns the upcoming expirations of authorizations.
+    pub fn timed_changes(&self) -> Vec<TimedRoaChange> {
+        self.roa_configurations()
+            .into_iter()
+            .flat_map(|config| {
+                config
+                    .not_after()
+                    .map(|not_after| TimedRoaChange::new(not_after, TimedRoaChangeKind::Expire, config))
+            })
+            .collect()
+    }
+
     /// Removes an authorization
     pub fn remove(&mut self, auth: &RoaPayloadJsonMapKey) -> bool {
         self.map.remove(auth).is_some()
     }
 }
 
+/// Returns the resources in `set` which are not in `other`.
+fn resources_minus(set: &ResourceSet, other: &ResourceSet) -> ResourceSet {
+    ResourceSet::new(
+        set.asn().difference(other.asn()),
+        set.ipv4().difference(other.ipv4()).into(),
+        set.ipv6().difference(other.ipv6()).into(),
+    )
+}
+
 //------------ RouteInfo ---------------------------------------------------
 
 /// Meta-information about a configured RouteAuthorization.
@@ -196,6 +297,15 @@ pub struct RouteInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     comment: Option<String>,
 
+    #[serde(skip_serializing_if = "RoaMetadata::is_empty", default)]
+    metadata: RoaMetadata,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    not_before: Option<Timestamp>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    not_after: Option<Timestamp>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     group: Option<u32>,
 }
@@ -213,6 +323,27 @@ impl RouteInfo {
         self.comment = comment;
     }
 
+    pub fn metadata(&self) -> &RoaMetadata {
+        &self.metadata
+    }
+
+    pub fn set_metadata(&mut self, metadata: RoaMetadata) {
+        self.metadata = metadata;
+    }
+
+    pub fn not_before(&self) -> Option<Timestamp> {
+        self.not_before
+    }
+
+    pub fn not_after(&self) -> Option<Timestamp> {
+        self.not_after
+    }
+
+    pub fn set_time_bounds(&mut self, not_before: Option<Timestamp>, not_after: Option<Timestamp>) {
+        self.not_before = not_before;
+        self.not_after = not_after;
+    }
+
     /// The idea was to allow grouping of specific payloads.
     /// But perhaps we should deprecate this as it's not used.
     pub fn group(&self) -> Option<u32> {
@@ -225,11 +356,142 @@ impl Default for RouteInfo {
         RouteInfo {
             since: Time::now(),
             comment: None,
+            metadata: RoaMetadata::default(),
+            not_before: None,
+            not_after: None,
             group: None,
         }
     }
 }
 
+//------------ ScheduledRoas -----------------------------------------------
+
+/// ROA configurations which will be added to the authorizations of a CA
+/// at their 'not_before' time.
+///
+/// Configurations for which the activation was refused, because strict
+/// ROA max lengths are used and BGP does not show all the more specific
+/// announcements they allow, are kept until they expire or are removed or
+/// scheduled again by the user. They are not activated in the meantime.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ScheduledRoas {
+    map: HashMap<RoaPayloadJsonMapKey, RoaConfiguration>,
+    #[serde(skip_serializing_if = "HashSet::is_empty", default)]
+    refused: HashSet<RoaPayloadJsonMapKey>,
+}
+
+impl ScheduledRoas {
+    pub fn has(&self, auth: &RoaPayloadJsonMapKey) -> bool {
+        self.map.contains_key(auth)
+    }
+
+    /// Schedules a configuration. This replaces any existing schedule for
+    /// the same payload, and lets it be activated again if its activation
+    /// was refused before.
+    pub fn schedule(&mut self, configuration: RoaConfiguration) {
+        let auth = configuration.payload().into();
+        self.refused.remove(&auth);
+        self.map.insert(auth, configuration);
+    }
+
+    /// Removes a scheduled configuration, returns true if it was present.
+    pub fn unschedule(&mut self, auth: &RoaPayloadJsonMapKey) -> bool {
+        self.refused.remove(auth);
+        self.map.remove(auth).is_some()
+    }
+
+    /// Marks a scheduled configuration as refused, so that it will not be
+    /// activated.
+    pub fn refuse(&mut self, auth: &RoaPayloadJsonMapKey) {
+        if self.map.contains_key(auth) {
+            self.refused.insert(*auth);
+        }
+    }
+
+    pub fn is_refused(&self, auth: &RoaPayloadJsonMapKey) -> bool {
+        self.refused.contains(auth)
+    }
+
+    pub fn configurations(&self) -> impl Iterator<Item = &RoaConfiguration> {
+        self.map.values()
+    }
+
+    /// Returns the scheduled configurations for which the activation was
+    /// refused.
+    pub fn refused_configurations(&self) -> impl Iterator<Item = &RoaConfiguration> {
+        self.map
+            .iter()
+            .filter(move |(auth, _)| self.refused.contains(auth))
+            .map(|(_, config)| config)
+    }
+
+    /// Returns the upcoming activations, and expirations, of scheduled
+    /// configurations. Refused configurations are not activated, but they
+    /// still expire.
+    pub fn timed_changes(&self) -> Vec<TimedRoaChange> {
+        let mut changes = vec![];
+        for (auth, config) in self.map.iter() {
+            if let Some(not_before) = config.not_before().filter(|_| !self.refused.contains(auth)) {
+                changes.push(TimedRoaChange::new(
+                    not_before,
+                    TimedRoaChangeKind::Activate,
+                    config.clone(),
+                ));
+            }
+            if let Some(not_after) = config.not_after() {
+                changes.push(TimedRoaChange::new(
+                    not_after,
+                    TimedRoaChangeKind::Expire,
+                    config.clone(),
+                ));
+            }
+        }
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+//------------ RoaDrafts ---------------------------------------------------
+
+/// Named ROA configuration updates which are staged, but not yet applied,
+/// in a CA.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoaDrafts {
+    map: HashMap<RoaDraftName, RoaConfigurationUpdates>,
+}
+
+impl RoaDrafts {
+    pub fn get(&self, name: &str) -> Option<&RoaConfigurationUpdates> {
+        self.map.get(name)
+    }
+
+    pub fn set(&mut self, name: RoaDraftName, updates: RoaConfigurationUpdates) {
+        self.map.insert(name, updates);
+    }
+
+    /// Removes a draft, returns true if it was present.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.map.remove(name).is_some()
+    }
+
+    pub fn list(&self) -> RoaDraftList {
+        let mut drafts: Vec<RoaDraft> = self
+            .map
+            .iter()
+            .map(|(name, updates)| RoaDraft::new(name.clone(), updates.clone()))
+            .collect();
+        drafts.sort_by(|a, b| a.name().cmp(b.name()));
+        RoaDraftList::new(drafts)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
 //------------ RoaInfo -----------------------------------------------------
 
 /// This type defines information about a ROA *object*
@@ -252,6 +514,13 @@ pub struct RoaInfo {
 
     // The ROA's hash
     hash: Hash,
+
+    // The metadata of the configurations for the authorizations on this ROA.
+    // This is not kept in the stored state, but it is added when ROAs are
+    // reported through the API, so that users can see which configurations
+    // (and owners) share an aggregated ROA.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    metadata: BTreeMap<RoaPayloadJsonMapKey, RoaMetadata>,
 }
 
 impl RoaInfo {
@@ -269,9 +538,29 @@ impl RoaInfo {
             uri,
             base64,
             hash,
+            metadata: BTreeMap::new(),
         }
     }
 
+    /// Adds the metadata for all authorizations on this ROA that have any.
+    pub fn with_metadata(mut self, routes: &Routes) -> Self {
+        self.metadata = self
+            .authorizations
+            .iter()
+            .flat_map(|auth| {
+                routes
+                    .info(auth)
+                    .filter(|info| !info.metadata().is_empty())
+                    .map(|info| (*auth, info.metadata().clone()))
+            })
+            .collect();
+        self
+    }
+
+    pub fn metadata(&self) -> &BTreeMap<RoaPayloadJsonMapKey, RoaMetadata> {
+        &self.metadata
+    }
+
     pub fn authorizations(&self) -> &Vec<RoaPayloadJsonMapKey> {
         &self.authorizations
     }
@@ -530,15 +819,18 @@ impl Roas {
     }
 
     /// Process updates, return [`RoaUpdates`] and create new ROA objects if
-    /// authorizations change, or if ROAs are about to expire.
+    /// authorizations change, or if ROAs are about to expire. The delegated
+    /// resources are excluded from any AS0 ROAs for unrouted space.
     pub fn update(
         &self,
         all_routes: &Routes,
+        delegated: &ResourceSet,
         certified_key: &CertifiedKey,
         config: &Config,
         signer: &KrillSigner,
     ) -> KrillResult<RoaUpdates> {
-        let relevant_routes = all_routes.filter(certified_key.incoming_cert().resources());
+        let resources = certified_key.incoming_cert().resources();
+        let relevant_routes = all_routes.filter(resources).with_as0_unrouted(resources, delegated);
 
         match self.mode(
             relevant_routes.len(),
@@ -674,7 +966,7 @@ impl Roas {
 mod tests {
 
     use super::*;
-    use crate::commons::api::AsNumber;
+    use crate::{commons::api::AsNumber, test::roa_configuration};
 
     fn authorization(s: &str) -> RoaPayloadJsonMapKey {
         let def = RoaPayload::from_str(s).unwrap();
@@ -729,4 +1021,76 @@ mod tests {
 
         assert_eq!(agg_2, &vec![auth2_1])
     }
+
+    #[test]
+    fn routes_as0_unrouted() {
+        let mut routes = Routes::default();
+        routes.add(authorization("10.0.0.0/24-24 => 64496"));
+        routes.add(authorization("10.0.2.0/23-24 => 64496"));
+
+        let resources = ResourceSet::from_strs("", "10.0.0.0/22", "2001:db8::/32").unwrap();
+        let no_children = ResourceSet::default();
+        assert!(routes.as0_unrouted_payloads(&resources, &no_children).is_empty());
+
+        routes.set_as0_unrouted(true);
+        let expected = vec![
+            RoaPayload::from_str("10.0.1.0/24-24 => 0").unwrap(),
+            RoaPayload::from_str("2001:db8::/32-32 => 0").unwrap(),
+        ];
+        assert_eq!(routes.as0_unrouted_payloads(&resources, &no_children), expected);
+
+        let issued = routes.clone().with_as0_unrouted(&resources, &no_children);
+        assert_eq!(issued.len(), 4);
+
+        // Space delegated to a child is the child's to authorize
+        let child = ResourceSet::from_strs("", "10.0.1.0/25", "2001:db8:1::/48").unwrap();
+        let expected = vec![
+            RoaPayload::from_str("10.0.1.128/25-25 => 0").unwrap(),
+            RoaPayload::from_str("2001:db8::/48-48 => 0").unwrap(),
+            RoaPayload::from_str("2001:db8:2::/47-47 => 0").unwrap(),
+            RoaPayload::from_str("2001:db8:4::/46-46 => 0").unwrap(),
+        ];
+        let payloads = routes.as0_unrouted_payloads(&resources, &child);
+        assert_eq!(payloads.len(), 17);
+        assert_eq!(payloads[..4], expected[..]);
+        assert!(payloads
+            .iter()
+            .all(|payload| !child.contains_roa_address(&payload.as_roa_ip_address())));
+
+        // The policy is kept in JSON, but only when it is enabled
+        let json = serde_json::to_string(&routes).unwrap();
+        assert_eq!(serde_json::from_str::<Routes>(&json).unwrap(), routes);
+        routes.set_as0_unrouted(false);
+        assert!(!serde_json::to_string(&routes).unwrap().contains("as0_unrouted"));
+    }
+
+    #[test]
+    fn scheduled_roas_refused() {
+        let not_before = Timestamp::now_plus_hours(1);
+        let not_after = Timestamp::now_plus_hours(2);
+        let config = roa_configuration("10.0.0.0/16-24 => 64496").with_time_bounds(Some(not_before), Some(not_after));
+        let auth = authorization("10.0.0.0/16-24 => 64496");
+
+        let mut scheduled = ScheduledRoas::default();
+        scheduled.schedule(config.clone());
+        assert_eq!(scheduled.timed_changes().len(), 2);
+
+        // A refused configuration is no longer activated, but it still expires
+        scheduled.refuse(&auth);
+        assert!(scheduled.is_refused(&auth));
+        assert_eq!(scheduled.refused_configurations().count(), 1);
+        let changes = scheduled.timed_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind(), TimedRoaChangeKind::Expire);
+
+        // Scheduling it again lets it be activated
+        scheduled.schedule(config);
+        assert!(!scheduled.is_refused(&auth));
+        assert_eq!(scheduled.timed_changes().len(), 2);
+
+        // Only scheduled configurations can be refused
+        let other = authorization("10.1.0.0/16 => 64496");
+        scheduled.refuse(&other);
+        assert!(!scheduled.is_refused(&other));
+    }
 }
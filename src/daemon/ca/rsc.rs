@@ -0,0 +1,588 @@
+//! Support for signing Resource Signed Checklists (RSC).
+//!
+//! See: https://www.rfc-editor.org/rfc/rfc9323.html
+use std::{
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use bcder::{
+    decode::{Constructed, DecodeError, Source},
+    encode::{self, PrimitiveContent, Values},
+    BitString, Captured, ConstOid, Mode, OctetString, Oid, Tag,
+};
+use bytes::Bytes;
+
+use rpki::{
+    ca::publication::Base64,
+    crypto::DigestAlgorithm,
+    repository::{resources::ResourceSet, x509::Validity, Cert},
+};
+
+use crate::commons::{crypto::KrillSigner, error::Error, util::ext_serde, KrillResult};
+
+//------------ Object Identifiers --------------------------------------------
+
+/// id-signedData, RFC 5652
+pub(super) const SIGNED_DATA: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 7, 2]);
+
+/// id-ct-signedChecklist, RFC 9323
+pub(super) const SIGNED_CHECKLIST: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 9, 16, 1, 48]);
+
+/// id-sha256, RFC 5754
+const SHA256: ConstOid = Oid(&[96, 134, 72, 1, 101, 3, 4, 2, 1]);
+
+/// rsaEncryption, RFC 8017
+const RSA_ENCRYPTION: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 1, 1]);
+
+/// id-contentType, RFC 5652
+pub(super) const CONTENT_TYPE: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 9, 3]);
+
+/// id-messageDigest, RFC 5652
+pub(super) const MESSAGE_DIGEST: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 9, 4]);
+
+//------------ RscRequest ----------------------------------------------------
+
+/// A request to sign an RSC over the given checklist, for the given
+/// resources.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RscRequest {
+    resources: ResourceSet,
+    validity: Validity,
+    checklist: Vec<RscChecklistItem>,
+}
+
+impl RscRequest {
+    pub fn new(resources: ResourceSet, validity: Validity, checklist: Vec<RscChecklistItem>) -> Self {
+        RscRequest {
+            resources,
+            validity,
+            checklist,
+        }
+    }
+
+    pub fn unpack(self) -> (ResourceSet, Validity, Vec<RscChecklistItem>) {
+        (self.resources, self.validity, self.checklist)
+    }
+}
+
+impl fmt::Display for RscRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "resources: {}", &self.resources)?;
+        writeln!(
+            f,
+            "validity, {}-{}",
+            self.validity.not_before().to_rfc3339(),
+            self.validity.not_after().to_rfc3339()
+        )?;
+        writeln!(f, "checklist:")?;
+        for item in self.checklist.iter() {
+            writeln!(f, "  {}", item)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ RscChecklistItem ----------------------------------------------
+
+/// An entry in the checklist of an RSC. Either the SHA-256 digest of a
+/// file is given, or the file itself in which case Krill will calculate the
+/// digest. The file name is optional.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RscChecklistItem {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_name: Option<String>,
+    #[serde(flatten)]
+    input: RscChecklistInput,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RscChecklistInput {
+    /// The hex encoded SHA-256 digest of the file.
+    Digest(String),
+
+    /// The content of the file.
+    Content(#[serde(deserialize_with = "ext_serde::de_bytes", serialize_with = "ext_serde::ser_bytes")] Bytes),
+}
+
+impl RscChecklistItem {
+    pub fn for_digest(file_name: Option<String>, digest: String) -> Self {
+        RscChecklistItem {
+            file_name,
+            input: RscChecklistInput::Digest(digest),
+        }
+    }
+
+    pub fn for_content(file_name: Option<String>, content: Bytes) -> Self {
+        RscChecklistItem {
+            file_name,
+            input: RscChecklistInput::Content(content),
+        }
+    }
+
+    pub fn file_name(&self) -> Option<&String> {
+        self.file_name.as_ref()
+    }
+
+    /// Returns the SHA-256 digest for this item, calculating it for content.
+    pub fn digest(&self) -> KrillResult<Bytes> {
+        match &self.input {
+            RscChecklistInput::Digest(hex_str) => {
+                let digest = hex::decode(hex_str)
+                    .map_err(|_| Error::Custom(format!("Invalid hex encoded digest for RSC: {}", hex_str)))?;
+                if digest.len() != 32 {
+                    return Err(Error::Custom(format!(
+                        "Digest for RSC is not a SHA-256 digest: {}",
+                        hex_str
+                    )));
+                }
+                Ok(Bytes::from(digest))
+            }
+            RscChecklistInput::Content(content) => {
+                let digest = DigestAlgorithm::default().digest(content.as_ref());
+                Ok(Bytes::copy_from_slice(digest.as_ref()))
+            }
+        }
+    }
+
+    /// Checks that the file name is a portable filename, as required by
+    /// section 4.4 of RFC 9323.
+    fn verify_file_name(&self) -> KrillResult<()> {
+        if let Some(name) = &self.file_name {
+            let portable = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-';
+            if name.is_empty() || !name.chars().all(portable) {
+                return Err(Error::Custom(format!("Invalid file name for RSC: '{}'", name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RscChecklistItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.file_name.as_deref().unwrap_or("<no name>");
+        match &self.input {
+            RscChecklistInput::Digest(digest) => write!(f, "{} {}", name, digest),
+            RscChecklistInput::Content(content) => write!(f, "{} ({} bytes)", name, content.len()),
+        }
+    }
+}
+
+//------------ ResourceSignedChecklist ---------------------------------------
+
+/// A signed and DER encoded Resource Signed Checklist.
+#[derive(Clone, Debug, Deserialize, Eq, Serialize, PartialEq)]
+pub struct ResourceSignedChecklist {
+    #[serde(deserialize_with = "ext_serde::de_bytes", serialize_with = "ext_serde::ser_bytes")]
+    bytes: Bytes,
+}
+
+impl AsRef<Bytes> for ResourceSignedChecklist {
+    fn as_ref(&self) -> &Bytes {
+        &self.bytes
+    }
+}
+
+impl ResourceSignedChecklist {
+    /// Encodes the RpkiSignedChecklist content for the resources and checklist.
+    pub fn content(resources: &ResourceSet, checklist: &[RscChecklistItem]) -> KrillResult<Bytes> {
+        if resources.is_empty() {
+            return Err(Error::custom("An RSC needs at least one resource"));
+        }
+        if checklist.is_empty() {
+            return Err(Error::custom("An RSC needs at least one entry in its checklist"));
+        }
+
+        let mut entries = vec![];
+        for item in checklist {
+            item.verify_file_name()?;
+            let digest = item.digest()?;
+            let file_name = item
+                .file_name()
+                .map(|name| OctetString::encode_slice_as(name.as_bytes(), Tag::IA5_STRING));
+            entries
+                .push(encode::sequence((file_name, OctetString::encode_slice(digest.as_ref()))).to_captured(Mode::Der));
+        }
+
+        // version is DEFAULT 0, so it is omitted
+        let content = encode::sequence((
+            Self::encode_resources(resources),
+            encode::sequence(SHA256.encode()),
+            encode::sequence(entries),
+        ));
+
+        Ok(content.to_captured(Mode::Der).into_bytes())
+    }
+
+    /// Signs the content with the key of the EE certificate, and wraps it all
+    /// up in a CMS signed object as described in RFC 6488, except that the EE
+    /// certificate has no SIA as there is no publication point for an RSC.
+    pub fn sign(content: Bytes, ee: Cert, signer: &KrillSigner) -> KrillResult<Self> {
+        let bytes = sign_cms(&SIGNED_CHECKLIST, content.as_ref(), false, &ee, signer)?;
+        Ok(ResourceSignedChecklist { bytes })
+    }
+
+    /// Encodes the ResourceBlock. Unlike RFC 3779 certificate extensions it
+    /// cannot use inherit, and families without resources are left out.
+    fn encode_resources(resources: &ResourceSet) -> Captured {
+        let as_ids: Vec<Captured> = resources
+            .asn()
+            .iter()
+            .map(|block| {
+                let (min, max) = (block.min().into_u32(), block.max().into_u32());
+                if min == max {
+                    min.encode().to_captured(Mode::Der)
+                } else {
+                    encode::sequence((min.encode(), max.encode())).to_captured(Mode::Der)
+                }
+            })
+            .collect();
+
+        let as_id = if as_ids.is_empty() {
+            None
+        } else {
+            Some(encode::sequence_as(
+                Tag::CTX_0,
+                encode::sequence(encode::sequence_as(Tag::CTX_0, encode::sequence(as_ids))),
+            ))
+        };
+
+        let v4: Vec<(u128, u128)> = resources
+            .ipv4()
+            .iter()
+            .map(|block| {
+                (
+                    u32::from(block.min().to_v4()) as u128,
+                    u32::from(block.max().to_v4()) as u128,
+                )
+            })
+            .collect();
+        let v6: Vec<(u128, u128)> = resources
+            .ipv6()
+            .iter()
+            .map(|block| (u128::from(block.min().to_v6()), u128::from(block.max().to_v6())))
+            .collect();
+
+        let mut families = vec![];
+        if !v4.is_empty() {
+            families.push(Self::encode_family([0, 1], &v4, 32));
+        }
+        if !v6.is_empty() {
+            families.push(Self::encode_family([0, 2], &v6, 128));
+        }
+
+        let ip_blocks = if families.is_empty() {
+            None
+        } else {
+            Some(encode::sequence_as(Tag::CTX_1, encode::sequence(families)))
+        };
+
+        encode::sequence((as_id, ip_blocks)).to_captured(Mode::Der)
+    }
+
+    /// Encodes the address ranges of one family, using a prefix if a range
+    /// is exactly one prefix and an IPAddressRange otherwise.
+    fn encode_family(afi: [u8; 2], ranges: &[(u128, u128)], addr_bits: u32) -> Captured {
+        let addresses: Vec<Captured> = ranges
+            .iter()
+            .map(|(min, max)| {
+                let host_bits = (min ^ max).count_ones();
+                let is_prefix = max - min == Self::host_mask(host_bits) && min & Self::host_mask(host_bits) == 0;
+                if is_prefix {
+                    Self::encode_bits(*min, addr_bits - host_bits, addr_bits)
+                } else {
+                    let min_len = addr_bits - min.trailing_zeros().min(addr_bits);
+                    let max_len = addr_bits - max.trailing_ones().min(addr_bits);
+                    encode::sequence((
+                        Self::encode_bits(*min, min_len, addr_bits),
+                        Self::encode_bits(*max & !Self::host_mask(addr_bits - max_len), max_len, addr_bits),
+                    ))
+                    .to_captured(Mode::Der)
+                }
+            })
+            .collect();
+
+        encode::sequence((OctetString::encode_slice(afi), encode::sequence(addresses))).to_captured(Mode::Der)
+    }
+
+    /// Encodes the first `len` bits of an address as a BIT STRING.
+    fn encode_bits(addr: u128, len: u32, addr_bits: u32) -> Captured {
+        let bytes = addr.to_be_bytes();
+        let skip = (128 - addr_bits) as usize / 8;
+        let used = ((len + 7) / 8) as usize;
+        let unused_bits = (used as u32 * 8 - len) as u8;
+
+        let mut content = vec![unused_bits];
+        content.extend_from_slice(&bytes[skip..skip + used]);
+
+        OctetString::encode_slice_as(content, Tag::BIT_STRING).to_captured(Mode::Der)
+    }
+
+    /// Decodes the resources listed in RpkiSignedChecklist content.
+    pub fn content_resources(content: Bytes) -> KrillResult<ResourceSet> {
+        let (asns, families) = Mode::Der
+            .decode(content, |cons| {
+                cons.take_sequence(|cons| {
+                    cons.take_opt_constructed_if(Tag::CTX_0, |cons| cons.skip_all())?;
+                    let resources = cons.take_sequence(|cons| {
+                        let asns = cons.take_opt_constructed_if(Tag::CTX_0, Self::take_as_ids)?;
+                        let families = cons.take_opt_constructed_if(Tag::CTX_1, Self::take_families)?;
+                        Ok((asns.unwrap_or_default(), families.unwrap_or_default()))
+                    })?;
+                    cons.skip_all()?;
+                    Ok(resources)
+                })
+            })
+            .map_err(|e| Error::custom(format!("Cannot decode RSC content: {}", e)))?;
+
+        let asns: Vec<String> = asns
+            .into_iter()
+            .map(|(min, max)| {
+                if min == max {
+                    format!("AS{}", min)
+                } else {
+                    format!("AS{}-AS{}", min, max)
+                }
+            })
+            .collect();
+
+        let mut v4 = vec![];
+        let mut v6 = vec![];
+        for (afi, addresses) in families {
+            let addr_bits = match afi.as_ref() {
+                [0, 1] => 32,
+                [0, 2] => 128,
+                _ => return Err(Error::custom("Unsupported address family in RSC")),
+            };
+            for (min, max) in addresses {
+                let min = Self::decode_bits(&min, addr_bits, false)?;
+                let max = Self::decode_bits(&max, addr_bits, true)?;
+                if addr_bits == 32 {
+                    v4.push(format!("{}-{}", Ipv4Addr::from(min as u32), Ipv4Addr::from(max as u32)));
+                } else {
+                    v6.push(format!("{}-{}", Ipv6Addr::from(min), Ipv6Addr::from(max)));
+                }
+            }
+        }
+
+        ResourceSet::from_strs(&asns.join(", "), &v4.join(", "), &v6.join(", "))
+            .map_err(|e| Error::custom(format!("Invalid resources in RSC: {}", e)))
+    }
+
+    /// Takes the ConstrainedASIdentifiers as (min, max) pairs.
+    fn take_as_ids<S: Source>(cons: &mut Constructed<S>) -> Result<Vec<(u32, u32)>, DecodeError<S::Error>> {
+        cons.take_sequence(|cons| {
+            cons.take_constructed_if(Tag::CTX_0, |cons| {
+                cons.take_sequence(|cons| {
+                    let mut as_ids = vec![];
+                    loop {
+                        if let Some(asn) = cons.take_opt_u32()? {
+                            as_ids.push((asn, asn));
+                        } else if let Some(range) =
+                            cons.take_opt_sequence(|cons| Ok((cons.take_u32()?, cons.take_u32()?)))?
+                        {
+                            as_ids.push(range);
+                        } else {
+                            break;
+                        }
+                    }
+                    Ok(as_ids)
+                })
+            })
+        })
+    }
+
+    /// Takes the ConstrainedIPAddrBlocks as address family identifiers with
+    /// the (min, max) bit strings of their addresses. A prefix is returned as
+    /// a range with itself.
+    #[allow(clippy::type_complexity)]
+    fn take_families<S: Source>(
+        cons: &mut Constructed<S>,
+    ) -> Result<Vec<(Bytes, Vec<(BitString, BitString)>)>, DecodeError<S::Error>> {
+        cons.take_sequence(|cons| {
+            let mut families = vec![];
+            while let Some(family) = cons.take_opt_sequence(|cons| {
+                let afi = OctetString::take_from(cons)?.to_bytes();
+                let addresses = cons.take_sequence(|cons| {
+                    let mut addresses = vec![];
+                    loop {
+                        if let Some(prefix) = cons.take_opt_value_if(Tag::BIT_STRING, BitString::from_content)? {
+                            addresses.push((prefix.clone(), prefix));
+                        } else if let Some(range) = cons
+                            .take_opt_sequence(|cons| Ok((BitString::take_from(cons)?, BitString::take_from(cons)?)))?
+                        {
+                            addresses.push(range);
+                        } else {
+                            break;
+                        }
+                    }
+                    Ok(addresses)
+                })?;
+                Ok((afi, addresses))
+            })? {
+                families.push(family);
+            }
+            Ok(families)
+        })
+    }
+
+    /// Decodes an address from its leading bits, setting the remaining bits
+    /// to one for the upper bound of a range, and to zero otherwise.
+    fn decode_bits(bits: &BitString, addr_bits: u32, fill: bool) -> KrillResult<u128> {
+        let bytes = bits.octet_bytes();
+        let len = (bytes.len() as u32 * 8).saturating_sub(bits.unused() as u32);
+        if bytes.len() > 16 || len > addr_bits {
+            return Err(Error::custom("Invalid address in RSC"));
+        }
+
+        let mut addr = 0u128;
+        for (i, byte) in bytes.iter().enumerate() {
+            addr |= (*byte as u128) << (120 - 8 * i);
+        }
+        let addr = addr >> (128 - addr_bits);
+
+        let host_mask = Self::host_mask(addr_bits - len);
+        if fill {
+            Ok(addr | host_mask)
+        } else {
+            Ok(addr & !host_mask)
+        }
+    }
+
+    fn host_mask(host_bits: u32) -> u128 {
+        if host_bits >= 128 {
+            u128::MAX
+        } else {
+            (1u128 << host_bits) - 1
+        }
+    }
+}
+
+impl fmt::Display for ResourceSignedChecklist {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Base64::from_content(self.as_ref()))
+    }
+}
+
+//------------ sign_cms ------------------------------------------------------
+
+/// Signs the content with the key of the EE certificate and returns the DER
+/// encoded CMS ContentInfo. The signed attributes contain the content type
+/// and message digest only. If `detached` is set, the content itself is left
+/// out of the encapsulated content info, as is done for signed geofeeds.
+pub(super) fn sign_cms(
+    content_type: &ConstOid,
+    content: &[u8],
+    detached: bool,
+    ee: &Cert,
+    signer: &KrillSigner,
+) -> KrillResult<Bytes> {
+    let digest = DigestAlgorithm::default().digest(content);
+
+    let signed_attrs = (
+        encode::sequence((CONTENT_TYPE.encode(), encode::set(content_type.encode()))),
+        encode::sequence((
+            MESSAGE_DIGEST.encode(),
+            encode::set(OctetString::encode_slice(digest.as_ref())),
+        )),
+    );
+
+    // The signature is calculated over the DER encoded SET OF attributes
+    let signed_attrs_der = encode::set(&signed_attrs).to_captured(Mode::Der);
+    let ee_key = ee.subject_key_identifier();
+    let signature = signer.sign(&ee_key, signed_attrs_der.as_slice())?;
+
+    let signer_info = encode::sequence((
+        3u8.encode(),
+        OctetString::encode_slice_as(ee_key.as_slice(), Tag::CTX_0),
+        encode::sequence(SHA256.encode()),
+        encode::sequence_as(Tag::CTX_0, &signed_attrs),
+        encode::sequence((RSA_ENCRYPTION.encode(), ().encode())),
+        OctetString::encode_slice(signature.value().as_ref()),
+    ));
+
+    let encapsulated_content = if detached {
+        None
+    } else {
+        Some(encode::sequence_as(Tag::CTX_0, OctetString::encode_slice(content)))
+    };
+
+    let signed_data = encode::sequence((
+        3u8.encode(),
+        encode::set(encode::sequence(SHA256.encode())),
+        encode::sequence((content_type.encode(), encapsulated_content)),
+        encode::sequence_as(Tag::CTX_0, ee.to_captured()),
+        encode::set(signer_info),
+    ));
+
+    let content_info = encode::sequence((SIGNED_DATA.encode(), encode::sequence_as(Tag::CTX_0, signed_data)));
+
+    Ok(content_info.to_captured(Mode::Der).into_bytes())
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_rsc_content() {
+        let resources = ResourceSet::from_strs("AS65000", "10.0.0.0/8", "").unwrap();
+        let checklist = vec![RscChecklistItem::for_digest(Some("a.txt".to_string()), "00".repeat(32))];
+
+        let content = ResourceSignedChecklist::content(&resources, &checklist).unwrap();
+
+        let expected = hex::decode(concat!(
+            "3059",
+            "301da00b3009a0073005020300fde8a10e300c300a0402000130040302000a",
+            "300b0609608648016503040201",
+            "302b30291605612e7478740420",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        ))
+        .unwrap();
+
+        assert_eq!(content.as_ref(), expected.as_slice());
+    }
+
+    #[test]
+    fn encode_rsc_address_range() {
+        let min = u32::from(std::net::Ipv4Addr::new(192, 168, 0, 0)) as u128;
+        let max = u32::from(std::net::Ipv4Addr::new(192, 168, 2, 255)) as u128;
+
+        let family = ResourceSignedChecklist::encode_family([0, 1], &[(min, max)], 32);
+
+        let expected = hex::decode("301304020001300d300b030303c0a8030400c0a802").unwrap();
+        assert_eq!(family.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn decode_rsc_content_resources() {
+        let resources = ResourceSet::from_strs(
+            "AS65000, AS65001-AS65003",
+            "10.0.0.0/8, 192.168.0.0-192.168.2.255",
+            "2001:db8::/32",
+        )
+        .unwrap();
+        let checklist = vec![RscChecklistItem::for_digest(None, "00".repeat(32))];
+
+        let content = ResourceSignedChecklist::content(&resources, &checklist).unwrap();
+        let decoded = ResourceSignedChecklist::content_resources(content).unwrap();
+
+        assert_eq!(decoded, resources);
+    }
+
+    #[test]
+    fn reject_invalid_checklist() {
+        let resources = ResourceSet::from_strs("AS65000", "", "").unwrap();
+
+        let bad_name = vec![RscChecklistItem::for_digest(Some("../a".to_string()), "00".repeat(32))];
+        assert!(ResourceSignedChecklist::content(&resources, &bad_name).is_err());
+
+        let bad_digest = vec![RscChecklistItem::for_digest(None, "00".repeat(20))];
+        assert!(ResourceSignedChecklist::content(&resources, &bad_digest).is_err());
+
+        assert!(ResourceSignedChecklist::content(&resources, &[]).is_err());
+    }
+}
@@ -223,6 +223,20 @@ impl ResourceTaggedAttestation {
         Ok(rta::RtaBuilder::from_rta(rta))
     }
 
+    /// Returns the resources attested to, and the keys expected to sign.
+    pub fn attested(&self) -> KrillResult<(ResourceSet, Vec<KeyIdentifier>)> {
+        let builder = self.to_builder()?;
+        let content = builder.content();
+
+        let resources = ResourceSet::new(
+            content.as_resources().clone(),
+            content.v4_resources().clone().into(),
+            content.v6_resources().clone().into(),
+        );
+
+        Ok((resources, content.subject_keys().to_vec()))
+    }
+
     pub fn rta_builder(
         resources: &ResourceSet,
         content: Bytes,
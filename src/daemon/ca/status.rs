@@ -8,7 +8,7 @@ use rpki::ca::{
 use crate::commons::{
     api::{
         rrdp::PublishElement, ChildConnectionStats, ChildStatus, ChildrenConnectionStats, ErrorResponse, ParentStatus,
-        ParentStatuses, RepoStatus, Timestamp,
+        ParentStatuses, RepoStatus, Timestamp, ValidationIssue,
     },
     error::Error,
     eventsourcing::{KeyStoreKey, KeyValueStore},
@@ -19,6 +19,7 @@ use crate::commons::{
 const PARENTS_PREFIX: &str = "parents-";
 const CHILDREN_PREFIX: &str = "children-";
 const JSON_SUFFIX: &str = ".json";
+const VALIDATION_KEY: &str = "validation.json";
 
 //------------ CaStatus ------------------------------------------------------
 
@@ -28,6 +29,8 @@ pub struct CaStatus {
     parents: ParentStatuses,
     #[serde(skip_serializing_if = "HashMap::is_empty", default = "HashMap::new")]
     children: HashMap<ChildHandle, ChildStatus>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    validation: Vec<ValidationIssue>,
 }
 
 impl CaStatus {
@@ -38,7 +41,8 @@ impl CaStatus {
             .into_iter()
             .map(|(handle, status)| {
                 let state = status.child_state();
-                ChildConnectionStats::new(handle, status.into(), state)
+                let last_refused = status.last_refused();
+                ChildConnectionStats::new(handle, status.into(), state, last_refused)
             })
             .collect();
         ChildrenConnectionStats::new(children)
@@ -55,6 +59,11 @@ impl CaStatus {
     pub fn children(&self) -> &HashMap<ChildHandle, ChildStatus> {
         &self.children
     }
+
+    /// The issues found when the repository content was last validated.
+    pub fn validation(&self) -> &Vec<ValidationIssue> {
+        &self.validation
+    }
 }
 
 //------------ StatusStore ---------------------------------------------------
@@ -142,10 +151,15 @@ impl StatusStore {
             }
         }
 
+        // validation issues, these will be replaced when the repository
+        // content is validated again.
+        let validation: Vec<ValidationIssue> = self.store.get(&Self::validation_key(ca))?.unwrap_or_default();
+
         let status = CaStatus {
             repo,
             parents,
             children,
+            validation,
         };
 
         // Update the cache. Note that this is what we will use at runtime.
@@ -195,6 +209,10 @@ impl StatusStore {
         KeyStoreKey::scoped(ca.to_string(), format!("{}{}{}", CHILDREN_PREFIX, child, JSON_SUFFIX))
     }
 
+    fn validation_key(ca: &CaHandle) -> KeyStoreKey {
+        KeyStoreKey::scoped(ca.to_string(), VALIDATION_KEY.to_string())
+    }
+
     /// Returns the stored CaStatus for a CA, or a default (empty) status if it can't be found
     pub fn get_ca_status(&self, ca: &CaHandle) -> CaStatus {
         self.cache.read().unwrap().get(ca).cloned().unwrap_or_default()
@@ -250,6 +268,19 @@ impl StatusStore {
         self.update_ca_child_status(ca, child, |status| status.set_failure(user_agent, error_response))
     }
 
+    /// Records that a request from a child was refused, because it sent too
+    /// many requests.
+    pub fn set_child_refused(
+        &self,
+        ca: &CaHandle,
+        child: &ChildHandle,
+        user_agent: Option<String>,
+        error: &Error,
+    ) -> KrillResult<()> {
+        let error_response = Self::error_to_error_res(error);
+        self.update_ca_child_status(ca, child, |status| status.set_refused(user_agent, error_response))
+    }
+
     /// Marks a child as suspended. Note that it will be implicitly unsuspended whenever a new success or
     /// or failure is recorded for the child.
     pub fn set_child_suspended(&self, ca: &CaHandle, child: &ChildHandle) -> KrillResult<()> {
@@ -299,6 +330,40 @@ impl StatusStore {
         self.update_repo_status(ca, |status| status.set_published(uri, published, next_update))
     }
 
+    pub fn set_status_repo_mirror_failure(&self, ca: &CaHandle, uri: ServiceUri, error: &Error) -> KrillResult<()> {
+        let error_response = Self::error_to_error_res(error);
+        self.update_repo_status(ca, |status| status.set_mirror_failure(uri, error_response))
+    }
+
+    pub fn set_status_repo_mirror_success(
+        &self,
+        ca: &CaHandle,
+        uri: ServiceUri,
+        next_update: Timestamp,
+    ) -> KrillResult<()> {
+        self.update_repo_status(ca, |status| status.set_mirror_updated(uri, next_update))
+    }
+
+    pub fn remove_status_repo_mirror(&self, ca: &CaHandle, uri: &ServiceUri) -> KrillResult<()> {
+        self.update_repo_status(ca, |status| status.remove_mirror(uri))
+    }
+
+    /// Replaces the issues found when validating the published objects of a CA.
+    pub fn set_validation_issues(&self, ca: &CaHandle, issues: Vec<ValidationIssue>) -> KrillResult<()> {
+        let mut cache = self.cache.write().unwrap();
+
+        if !cache.contains_key(ca) {
+            cache.insert(ca.clone(), CaStatus::default());
+        }
+
+        let ca_status = cache.get_mut(ca).unwrap(); // safe, we just set it if missing
+        ca_status.validation = issues;
+
+        self.store.store(&Self::validation_key(ca), ca_status.validation())?;
+
+        Ok(())
+    }
+
     fn update_repo_status<F>(&self, ca: &CaHandle, op: F) -> KrillResult<()>
     where
         F: FnOnce(&mut RepoStatus),
@@ -0,0 +1,369 @@
+//! Support for Trust Anchor Key (TAK) objects.
+//!
+//! A trust anchor publishes a TAK object under each of its keys to tell
+//! relying parties which key is current, and - while a key roll is in
+//! progress - which key is about to replace it, or which key it replaced.
+//! Relying parties can use this to update their TALs automatically.
+//!
+//! See: https://www.rfc-editor.org/rfc/rfc9691.html
+use std::fmt;
+
+use bcder::{
+    decode::{Constructed, DecodeError, Source},
+    encode::{self, Values},
+    Captured, ConstOid, Mode, OctetString, Oid, Tag,
+};
+use bytes::Bytes;
+
+use rpki::{
+    ca::publication::Base64,
+    crypto::{KeyIdentifier, PublicKey},
+    repository::{
+        sigobj::{SignedObject, SignedObjectBuilder},
+        x509::{Serial, Time, Validity},
+    },
+};
+
+use crate::{
+    commons::{
+        api::{ObjectName, TaCertDetails},
+        crypto::KrillSigner,
+        error::Error,
+        KrillResult,
+    },
+    daemon::ca::CertifiedKey,
+};
+
+//------------ Object Identifiers --------------------------------------------
+
+/// id-ct-signedTAL, RFC 9691
+pub const SIGNED_TAL: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 9, 16, 1, 50]);
+
+//------------ TrustAnchorKey ------------------------------------------------
+
+/// A single key listed on a TAK object, i.e. the contents of a TAL.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrustAnchorKey {
+    comments: Vec<String>,
+    certificate_uris: Vec<String>,
+    key: PublicKey,
+}
+
+impl TrustAnchorKey {
+    pub fn new(comments: Vec<String>, certificate_uris: Vec<String>, key: PublicKey) -> Self {
+        TrustAnchorKey {
+            comments,
+            certificate_uris,
+            key,
+        }
+    }
+
+    /// Lists the URIs and key from the TAL of the given TA certificate.
+    pub fn for_ta(details: &TaCertDetails) -> Self {
+        let tal = details.tal();
+        let mut certificate_uris: Vec<String> = tal.uris().iter().map(|uri| uri.to_string()).collect();
+        certificate_uris.push(tal.rsync_uri().to_string());
+
+        TrustAnchorKey::new(vec![], certificate_uris, details.cert().csr_info().key().clone())
+    }
+
+    pub fn comments(&self) -> &Vec<String> {
+        &self.comments
+    }
+
+    pub fn certificate_uris(&self) -> &Vec<String> {
+        &self.certificate_uris
+    }
+
+    pub fn key(&self) -> &PublicKey {
+        &self.key
+    }
+
+    pub fn key_identifier(&self) -> KeyIdentifier {
+        self.key.key_identifier()
+    }
+
+    fn encode(&self) -> Captured {
+        let comments: Vec<Captured> = self
+            .comments
+            .iter()
+            .map(|comment| OctetString::encode_slice_as(comment.as_bytes(), Tag::UTF8_STRING).to_captured(Mode::Der))
+            .collect();
+
+        let uris: Vec<Captured> = self
+            .certificate_uris
+            .iter()
+            .map(|uri| OctetString::encode_slice_as(uri.as_bytes(), Tag::IA5_STRING).to_captured(Mode::Der))
+            .collect();
+
+        encode::sequence((
+            encode::sequence(comments),
+            encode::sequence(uris),
+            self.key.encode_ref(),
+        ))
+        .to_captured(Mode::Der)
+    }
+
+    fn take_from<S: Source>(cons: &mut Constructed<S>) -> Result<Self, DecodeError<S::Error>> {
+        cons.take_sequence(|cons| {
+            let comments = cons.take_sequence(|cons| Self::take_strings(cons, Tag::UTF8_STRING))?;
+            let certificate_uris = cons.take_sequence(|cons| Self::take_strings(cons, Tag::IA5_STRING))?;
+            let key = PublicKey::take_from(cons)?;
+
+            Ok(TrustAnchorKey {
+                comments,
+                certificate_uris,
+                key,
+            })
+        })
+    }
+
+    fn take_strings<S: Source>(cons: &mut Constructed<S>, tag: Tag) -> Result<Vec<String>, DecodeError<S::Error>> {
+        let mut strings = vec![];
+        while let Some(string) = cons.take_opt_primitive_if(tag, |prim| {
+            let bytes = prim.take_all()?;
+            String::from_utf8(bytes.to_vec()).map_err(|_| prim.content_err("invalid string in TAK"))
+        })? {
+            strings.push(string);
+        }
+        Ok(strings)
+    }
+}
+
+impl fmt::Display for TrustAnchorKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "key: {} uris:", self.key_identifier())?;
+        for uri in &self.certificate_uris {
+            write!(f, " {}", uri)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ TrustAnchorKeys -----------------------------------------------
+
+/// The content of a TAK object: the current key of the TA, and optionally
+/// the key it replaced and the key that will replace it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrustAnchorKeys {
+    current: TrustAnchorKey,
+    predecessor: Option<TrustAnchorKey>,
+    successor: Option<TrustAnchorKey>,
+}
+
+impl TrustAnchorKeys {
+    pub fn new(
+        current: TrustAnchorKey,
+        predecessor: Option<TrustAnchorKey>,
+        successor: Option<TrustAnchorKey>,
+    ) -> Self {
+        TrustAnchorKeys {
+            current,
+            predecessor,
+            successor,
+        }
+    }
+
+    pub fn current(&self) -> &TrustAnchorKey {
+        &self.current
+    }
+
+    pub fn predecessor(&self) -> Option<&TrustAnchorKey> {
+        self.predecessor.as_ref()
+    }
+
+    pub fn successor(&self) -> Option<&TrustAnchorKey> {
+        self.successor.as_ref()
+    }
+
+    /// Encodes the TAK content. The version is DEFAULT 0, so it is omitted.
+    pub fn content(&self) -> Bytes {
+        encode::sequence((
+            self.current.encode(),
+            self.predecessor
+                .as_ref()
+                .map(|key| encode::sequence_as(Tag::CTX_0, key.encode())),
+            self.successor
+                .as_ref()
+                .map(|key| encode::sequence_as(Tag::CTX_1, key.encode())),
+        ))
+        .to_captured(Mode::Der)
+        .into_bytes()
+    }
+
+    /// Decodes TAK content, e.g. taken from a published TAK object.
+    pub fn decode(content: Bytes) -> KrillResult<Self> {
+        Mode::Der
+            .decode(content, |cons| {
+                cons.take_sequence(|cons| {
+                    if let Some(version) = cons.take_opt_u32()? {
+                        if version != 0 {
+                            return Err(cons.content_err("unsupported TAK version"));
+                        }
+                    }
+                    let current = TrustAnchorKey::take_from(cons)?;
+                    let predecessor = cons.take_opt_constructed_if(Tag::CTX_0, TrustAnchorKey::take_from)?;
+                    let successor = cons.take_opt_constructed_if(Tag::CTX_1, TrustAnchorKey::take_from)?;
+
+                    Ok(TrustAnchorKeys {
+                        current,
+                        predecessor,
+                        successor,
+                    })
+                })
+            })
+            .map_err(|e| Error::custom(format!("Cannot decode TAK content: {}", e)))
+    }
+
+    /// Decodes the content of a DER encoded TAK object. Note that this does
+    /// not validate the object.
+    pub fn from_object(bytes: &[u8]) -> KrillResult<Self> {
+        let object = SignedObject::decode(bytes, false)
+            .map_err(|e| Error::custom(format!("Cannot decode TAK object: {}", e)))?;
+
+        if object.content_type() != &SIGNED_TAL {
+            return Err(Error::custom("Signed object is not a TAK"));
+        }
+
+        Self::decode(object.content().to_bytes())
+    }
+}
+
+impl fmt::Display for TrustAnchorKeys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "current: {}", self.current)?;
+        if let Some(predecessor) = &self.predecessor {
+            writeln!(f, "predecessor: {}", predecessor)?;
+        }
+        if let Some(successor) = &self.successor {
+            writeln!(f, "successor: {}", successor)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ TakInfo -------------------------------------------------------
+
+/// A TAK object signed under one of the keys of a trust anchor.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TakInfo {
+    // The key which signed this TAK, it is published under this key.
+    key: KeyIdentifier,
+
+    // The serial number (needed for revocation)
+    serial: Serial,
+
+    // The time this object expires
+    expires: Time,
+
+    // The actual TAK object in base64 format.
+    base64: Base64,
+}
+
+impl TakInfo {
+    /// Signs a TAK object for the given keys under the given certified key.
+    pub fn sign(
+        keys: &TrustAnchorKeys,
+        certified_key: &CertifiedKey,
+        validity: Validity,
+        signer: &KrillSigner,
+    ) -> KrillResult<Self> {
+        let key = certified_key.key_id();
+        let incoming_cert = certified_key.incoming_cert();
+
+        let object_builder = {
+            let crl_uri = incoming_cert.crl_uri();
+            let tak_uri = incoming_cert.uri_for_name(&ObjectName::tak_for_key(key));
+            let ca_issuer = incoming_cert.uri().clone();
+
+            let mut object_builder =
+                SignedObjectBuilder::new(signer.random_serial()?, validity, crl_uri, ca_issuer, tak_uri);
+            object_builder.set_issuer(Some(incoming_cert.subject().clone()));
+            object_builder.set_signing_time(Some(Time::now()));
+            object_builder.set_v4_resources_inherit();
+            object_builder.set_v6_resources_inherit();
+            object_builder.set_as_resources_inherit();
+
+            object_builder
+        };
+
+        let object = signer.sign_object(Oid(SIGNED_TAL.0.into()), keys.content(), object_builder, key)?;
+        let base64 = Base64::from_content(&object.encode_ref().to_captured(Mode::Der).into_bytes());
+
+        Ok(TakInfo {
+            key: *key,
+            serial: object.cert().serial_number(),
+            expires: object.cert().validity().not_after(),
+            base64,
+        })
+    }
+
+    pub fn key(&self) -> KeyIdentifier {
+        self.key
+    }
+
+    pub fn serial(&self) -> Serial {
+        self.serial
+    }
+
+    pub fn expires(&self) -> Time {
+        self.expires
+    }
+
+    pub fn base64(&self) -> &Base64 {
+        &self.base64
+    }
+
+    pub fn name(&self) -> ObjectName {
+        ObjectName::tak_for_key(&self.key)
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use bcder::encode::PrimitiveContent;
+    use rpki::repository::Cert;
+
+    use super::*;
+
+    fn ta_key() -> PublicKey {
+        let der = include_bytes!("../../../test-resources/ta.cer");
+        let cert = Cert::decode(Bytes::from_static(der)).unwrap();
+        cert.subject_public_key_info().clone()
+    }
+
+    #[test]
+    fn tak_content_roundtrip() {
+        let current = TrustAnchorKey::new(
+            vec!["Krill TA".to_string()],
+            vec![
+                "https://localhost/ta/ta.cer".to_string(),
+                "rsync://localhost/ta/ta.cer".to_string(),
+            ],
+            ta_key(),
+        );
+        let successor = TrustAnchorKey::new(vec![], vec!["rsync://localhost/ta/next.cer".to_string()], ta_key());
+
+        let keys = TrustAnchorKeys::new(current, None, Some(successor));
+        let decoded = TrustAnchorKeys::decode(keys.content()).unwrap();
+
+        assert_eq!(decoded, keys);
+        assert!(decoded.predecessor().is_none());
+        assert_eq!(
+            decoded.successor().unwrap().certificate_uris(),
+            &vec!["rsync://localhost/ta/next.cer".to_string()]
+        );
+    }
+
+    #[test]
+    fn tak_content_rejects_unknown_version() {
+        let current = TrustAnchorKey::new(vec![], vec!["rsync://localhost/ta/ta.cer".to_string()], ta_key());
+        let content = encode::sequence((1u8.encode(), current.encode()))
+            .to_captured(Mode::Der)
+            .into_bytes();
+
+        assert!(TrustAnchorKeys::decode(content).is_err());
+    }
+}
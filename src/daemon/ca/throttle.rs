@@ -0,0 +1,267 @@
+//! Protection against children which send RFC 6492 requests too often.
+use std::{collections::HashMap, sync::RwLock};
+
+use bytes::Bytes;
+
+use rpki::ca::idexchange::{CaHandle, ChildHandle};
+
+use crate::commons::api::Timestamp;
+
+/// The length of the window in which the requests of a child are counted.
+const REQUEST_WINDOW_SECONDS: i64 = 60;
+
+type ChildKey = (CaHandle, ChildHandle);
+
+//------------ RequestAllowance ----------------------------------------------
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RequestAllowance {
+    Allowed,
+
+    /// The request should be refused. If this is the first refusal in the
+    /// current window then `first` is true, so that it can be recorded
+    /// without doing so again for every following request.
+    Refused {
+        first: bool,
+    },
+}
+
+//------------ ChildRequestThrottle ------------------------------------------
+
+/// Limits the number of RFC 6492 requests which are processed for each child
+/// per minute, and caches signed list responses for a short while.
+///
+/// This state is only kept in memory. It is not an issue if it is lost when
+/// Krill restarts, as it only concerns the last minute or so.
+pub struct ChildRequestThrottle {
+    // 0 means that there is no limit
+    max_requests_per_minute: u32,
+
+    // 0 means that list responses are not cached
+    list_cache_seconds: u32,
+
+    windows: RwLock<HashMap<ChildKey, RequestWindow>>,
+    list_responses: RwLock<HashMap<ChildKey, CachedListResponse>>,
+}
+
+impl ChildRequestThrottle {
+    pub fn new(max_requests_per_minute: u32, list_cache_seconds: u32) -> Self {
+        ChildRequestThrottle {
+            max_requests_per_minute,
+            list_cache_seconds,
+            windows: RwLock::new(HashMap::new()),
+            list_responses: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Registers a request from a child and returns whether it can be
+    /// processed.
+    pub fn register_request(&self, ca: &CaHandle, child: &ChildHandle) -> RequestAllowance {
+        self.register_request_at(ca, child, Timestamp::now())
+    }
+
+    fn register_request_at(&self, ca: &CaHandle, child: &ChildHandle, now: Timestamp) -> RequestAllowance {
+        if self.max_requests_per_minute == 0 {
+            return RequestAllowance::Allowed;
+        }
+
+        let mut windows = self.windows.write().unwrap();
+        let window = windows
+            .entry((ca.clone(), child.clone()))
+            .or_insert_with(|| RequestWindow::new(now));
+
+        if window.has_ended(now) {
+            *window = RequestWindow::new(now);
+        }
+
+        window.requests = window.requests.saturating_add(1);
+
+        if window.requests <= self.max_requests_per_minute {
+            RequestAllowance::Allowed
+        } else {
+            RequestAllowance::Refused {
+                first: window.requests == self.max_requests_per_minute + 1,
+            }
+        }
+    }
+
+    /// Returns a recently signed list response for the child, provided that
+    /// the CA did not change since it was signed, i.e. the CA version is the
+    /// same.
+    pub fn cached_list_response(&self, ca: &CaHandle, child: &ChildHandle, ca_version: u64) -> Option<Bytes> {
+        self.cached_list_response_at(ca, child, ca_version, Timestamp::now())
+    }
+
+    fn cached_list_response_at(
+        &self,
+        ca: &CaHandle,
+        child: &ChildHandle,
+        ca_version: u64,
+        now: Timestamp,
+    ) -> Option<Bytes> {
+        self.list_responses
+            .read()
+            .unwrap()
+            .get(&(ca.clone(), child.clone()))
+            .filter(|cached| cached.ca_version == ca_version && now < cached.expires)
+            .map(|cached| cached.response.clone())
+    }
+
+    /// Caches a signed list response for the child.
+    pub fn cache_list_response(&self, ca: &CaHandle, child: &ChildHandle, ca_version: u64, response: Bytes) {
+        self.cache_list_response_at(ca, child, ca_version, response, Timestamp::now())
+    }
+
+    fn cache_list_response_at(
+        &self,
+        ca: &CaHandle,
+        child: &ChildHandle,
+        ca_version: u64,
+        response: Bytes,
+        now: Timestamp,
+    ) {
+        if self.list_cache_seconds > 0 {
+            let cached = CachedListResponse {
+                ca_version,
+                expires: now.plus_seconds(self.list_cache_seconds as i64),
+                response,
+            };
+            self.list_responses
+                .write()
+                .unwrap()
+                .insert((ca.clone(), child.clone()), cached);
+        }
+    }
+
+    /// Forgets everything about a child, e.g. because it was removed.
+    pub fn remove_child(&self, ca: &CaHandle, child: &ChildHandle) {
+        let key = (ca.clone(), child.clone());
+        self.windows.write().unwrap().remove(&key);
+        self.list_responses.write().unwrap().remove(&key);
+    }
+}
+
+//------------ RequestWindow -------------------------------------------------
+
+struct RequestWindow {
+    start: Timestamp,
+    requests: u32,
+}
+
+impl RequestWindow {
+    fn new(start: Timestamp) -> Self {
+        RequestWindow { start, requests: 0 }
+    }
+
+    fn has_ended(&self, now: Timestamp) -> bool {
+        self.start.plus_seconds(REQUEST_WINDOW_SECONDS) <= now
+    }
+}
+
+//------------ CachedListResponse --------------------------------------------
+
+struct CachedListResponse {
+    ca_version: u64,
+    expires: Timestamp,
+    response: Bytes,
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn ca() -> CaHandle {
+        CaHandle::from_str("ca").unwrap()
+    }
+
+    fn child() -> ChildHandle {
+        ChildHandle::from_str("child").unwrap()
+    }
+
+    #[test]
+    fn refuse_requests_exceeding_limit() {
+        let throttle = ChildRequestThrottle::new(2, 0);
+        let now = Timestamp::new(1_000_000);
+
+        assert_eq!(
+            throttle.register_request_at(&ca(), &child(), now),
+            RequestAllowance::Allowed
+        );
+        assert_eq!(
+            throttle.register_request_at(&ca(), &child(), now),
+            RequestAllowance::Allowed
+        );
+        assert_eq!(
+            throttle.register_request_at(&ca(), &child(), now),
+            RequestAllowance::Refused { first: true }
+        );
+        assert_eq!(
+            throttle.register_request_at(&ca(), &child(), now.plus_seconds(59)),
+            RequestAllowance::Refused { first: false }
+        );
+
+        // other children are not affected
+        let other = ChildHandle::from_str("other").unwrap();
+        assert_eq!(
+            throttle.register_request_at(&ca(), &other, now),
+            RequestAllowance::Allowed
+        );
+
+        // requests are allowed again in the next window
+        assert_eq!(
+            throttle.register_request_at(&ca(), &child(), now.plus_seconds(60)),
+            RequestAllowance::Allowed
+        );
+    }
+
+    #[test]
+    fn no_limit() {
+        let throttle = ChildRequestThrottle::new(0, 0);
+        let now = Timestamp::new(1_000_000);
+
+        for _ in 0..1000 {
+            assert_eq!(
+                throttle.register_request_at(&ca(), &child(), now),
+                RequestAllowance::Allowed
+            );
+        }
+    }
+
+    #[test]
+    fn cache_list_response() {
+        let throttle = ChildRequestThrottle::new(0, 10);
+        let now = Timestamp::new(1_000_000);
+        let response = Bytes::from_static(b"response");
+
+        assert_eq!(throttle.cached_list_response_at(&ca(), &child(), 5, now), None);
+
+        throttle.cache_list_response_at(&ca(), &child(), 5, response.clone(), now);
+        assert_eq!(
+            throttle.cached_list_response_at(&ca(), &child(), 5, now.plus_seconds(9)),
+            Some(response)
+        );
+
+        // expired, or the CA has changed
+        assert_eq!(
+            throttle.cached_list_response_at(&ca(), &child(), 5, now.plus_seconds(10)),
+            None
+        );
+        assert_eq!(throttle.cached_list_response_at(&ca(), &child(), 6, now), None);
+
+        throttle.remove_child(&ca(), &child());
+        assert_eq!(throttle.cached_list_response_at(&ca(), &child(), 5, now), None);
+    }
+
+    #[test]
+    fn no_cache() {
+        let throttle = ChildRequestThrottle::new(0, 0);
+        let now = Timestamp::new(1_000_000);
+
+        throttle.cache_list_response_at(&ca(), &child(), 5, Bytes::from_static(b"response"), now);
+        assert_eq!(throttle.cached_list_response_at(&ca(), &child(), 5, now), None);
+    }
+}
@@ -0,0 +1,709 @@
+//! Validation of the content of the repository, as a relying party would
+//! validate it.
+//!
+//! Validation starts from the certificates which local CAs received from
+//! their parents, and from the TALs of a local Trust Anchor. For each
+//! publication point the manifest, CRL, certificates and signed objects are
+//! validated, and certificates of remote children which publish in this
+//! repository are followed as well.
+//!
+//! Certificates received from parents are trusted as they are: the path
+//! from these certificates up to a trust anchor is not validated here.
+//!
+//! Local CAs which publish in a remote repository are validated using the
+//! RRDP snapshot of that repository, found through the RRDP notification
+//! URI on their certificates. The rsync content of remote repositories is
+//! not fetched.
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    io::Read,
+};
+
+use bcder::{BitString, Mode};
+use bytes::Bytes;
+
+use rpki::{
+    ca::{idexchange::CaHandle, publication::Base64},
+    crypto::{DigestAlgorithm, PublicKey, RpkiSignature, RpkiSignatureAlgorithm},
+    repository::{crl::Crl, resources::ResourceSet, x509::Time, Cert, Manifest},
+    rrdp::{Hash, NotificationFile, ObjectReader, ProcessError, ProcessSnapshot},
+    uri,
+};
+use uuid::Uuid;
+
+use crate::{
+    commons::{
+        api::{CaValidationResult, ReceivedCert, RepositoryValidationReport, TrustAnchorLocator, ValidationIssue},
+        util::httpclient,
+    },
+    daemon::ca::verify::SignedObject,
+    pubd::RepositoryObjects,
+};
+
+//------------ RepositoryValidator -------------------------------------------
+
+pub struct RepositoryValidator {
+    objects: RepositoryObjects,
+    now: Time,
+}
+
+impl RepositoryValidator {
+    pub fn new(objects: RepositoryObjects) -> Self {
+        RepositoryValidator {
+            objects,
+            now: Time::now(),
+        }
+    }
+
+    /// Validates the repository content. Each local CA is given with the
+    /// certificates it received for its keys.
+    pub fn validate(
+        &self,
+        tals: &[TrustAnchorLocator],
+        cas: Vec<(CaHandle, Vec<ReceivedCert>)>,
+    ) -> RepositoryValidationReport {
+        let mut repository_issues = self.compare_rsync_and_rrdp();
+
+        let mut ca_issues: HashMap<CaHandle, Vec<ValidationIssue>> = HashMap::new();
+        let mut todo: Vec<PublicationPoint> = vec![];
+
+        // Local CAs which publish in this repository. Note that all keys of
+        // a CA share a single publication point.
+        for (ca, received) in cas {
+            let mut issues = vec![];
+            let mut points: HashMap<uri::Rsync, Vec<(uri::Rsync, Cert)>> = HashMap::new();
+
+            for cert in received {
+                if !self.in_repository(cert.ca_repository()) {
+                    continue;
+                }
+                match cert.to_cert() {
+                    Ok(decoded) => points
+                        .entry(cert.ca_repository().clone())
+                        .or_default()
+                        .push((cert.uri().clone(), decoded)),
+                    Err(e) => issues.push(ValidationIssue::new(cert.uri().clone(), e)),
+                }
+            }
+
+            if points.is_empty() && issues.is_empty() {
+                continue;
+            }
+
+            for (dir, certs) in points {
+                todo.push(PublicationPoint::new(Some(ca.clone()), dir, certs));
+            }
+            ca_issues.insert(ca, issues);
+        }
+
+        // Trust anchors, their publication point is validated as well if it
+        // is not used by a local CA. A TA certificate which is not published
+        // in this repository, e.g. because it is served elsewhere, cannot be
+        // checked here.
+        for tal in tals.iter().filter(|tal| self.in_repository(tal.rsync_uri())) {
+            match self.validate_tal(tal) {
+                Ok((dir, cert)) => {
+                    if !todo.iter().any(|point| point.dir == dir) {
+                        todo.push(PublicationPoint::new(None, dir, vec![(tal.rsync_uri().clone(), cert)]));
+                    }
+                }
+                Err(issue) => repository_issues.push(issue),
+            }
+        }
+
+        let mut seen: HashSet<uri::Rsync> = todo.iter().map(|point| point.dir.clone()).collect();
+
+        while let Some(point) = todo.pop() {
+            let (mut issues, children) = self.validate_publication_point(&point);
+
+            // Follow the certificates of children which publish in this
+            // repository, unless their publication point is validated already.
+            let mut child_points: HashMap<uri::Rsync, Vec<(uri::Rsync, Cert)>> = HashMap::new();
+            for (uri, cert) in children {
+                if let Some(dir) = cert.ca_repository().cloned() {
+                    if self.in_repository(&dir) && !seen.contains(&dir) {
+                        child_points.entry(dir).or_default().push((uri, cert));
+                    }
+                }
+            }
+            for (dir, certs) in child_points {
+                seen.insert(dir.clone());
+                todo.push(PublicationPoint::new(None, dir, certs));
+            }
+
+            match point.ca {
+                Some(ca) => ca_issues.entry(ca).or_default().append(&mut issues),
+                None => repository_issues.append(&mut issues),
+            }
+        }
+
+        let mut cas: Vec<CaValidationResult> = ca_issues
+            .into_iter()
+            .map(|(ca, issues)| CaValidationResult::new(ca, issues))
+            .collect();
+        cas.sort_by_key(|result| result.ca().to_string());
+
+        RepositoryValidationReport::new(cas, repository_issues)
+    }
+
+    /// The rsync content on disk is expected to match the RRDP snapshot.
+    fn compare_rsync_and_rrdp(&self) -> Vec<ValidationIssue> {
+        let rsync = self.objects.rsync();
+        let rrdp = self.objects.rrdp();
+
+        let mut issues = vec![];
+
+        for (uri, bytes) in rrdp {
+            match rsync.get(uri) {
+                None => issues.push(ValidationIssue::new(
+                    uri.clone(),
+                    "published in RRDP, but missing in rsync",
+                )),
+                Some(rsync_bytes) if rsync_bytes != bytes => {
+                    issues.push(ValidationIssue::new(uri.clone(), "rsync content differs from RRDP"))
+                }
+                Some(_) => {}
+            }
+        }
+
+        for uri in rsync.keys() {
+            if !rrdp.contains_key(uri) {
+                issues.push(ValidationIssue::new(
+                    uri.clone(),
+                    "found in rsync, but not published in RRDP",
+                ));
+            }
+        }
+
+        sort_issues(&mut issues);
+        issues
+    }
+
+    /// Checks that the TA certificate is published at the URI in the TAL,
+    /// and that it matches the TAL. Returns the publication point of the TA
+    /// and its certificate.
+    fn validate_tal(&self, tal: &TrustAnchorLocator) -> Result<(uri::Rsync, Cert), ValidationIssue> {
+        let uri = tal.rsync_uri();
+        let issue = |msg: String| ValidationIssue::new(uri.clone(), msg);
+
+        let bytes = self
+            .objects
+            .rsync()
+            .get(uri)
+            .ok_or_else(|| issue("trust anchor certificate is missing".to_string()))?;
+
+        let cert = Cert::decode(bytes.as_ref()).map_err(|e| issue(format!("cannot decode certificate: {}", e)))?;
+
+        if !tal.matches_key(cert.subject_public_key_info()) {
+            return Err(issue(
+                "trust anchor certificate does not match the key in the TAL".to_string(),
+            ));
+        }
+
+        verify_signed_data(bytes.clone(), cert.subject_public_key_info())
+            .map_err(|e| issue(format!("trust anchor certificate: {}", e)))?;
+
+        self.check_validity(&cert).map_err(issue)?;
+
+        let dir = cert
+            .ca_repository()
+            .cloned()
+            .ok_or_else(|| issue("trust anchor certificate has no CA repository URI".to_string()))?;
+
+        Ok((dir, cert))
+    }
+
+    /// Validates the objects published under each key that uses the
+    /// publication point, and returns the issues found and the child CA
+    /// certificates.
+    fn validate_publication_point(&self, point: &PublicationPoint) -> (Vec<ValidationIssue>, Vec<(uri::Rsync, Cert)>) {
+        let mut issues = vec![];
+        let mut listed = HashSet::new();
+        let mut children = vec![];
+
+        for (uri, cert) in &point.certs {
+            self.validate_key(uri, cert, &mut listed, &mut children, &mut issues);
+        }
+
+        for uri in self.objects.rsync().keys() {
+            if in_dir(uri, &point.dir) && !listed.contains(uri) {
+                issues.push(ValidationIssue::new(uri.clone(), "not listed on any manifest"));
+            }
+        }
+
+        sort_issues(&mut issues);
+        (issues, children)
+    }
+
+    /// Validates the manifest and CRL of a key, and all objects listed on
+    /// the manifest.
+    fn validate_key(
+        &self,
+        cert_uri: &uri::Rsync,
+        ca_cert: &Cert,
+        listed: &mut HashSet<uri::Rsync>,
+        children: &mut Vec<(uri::Rsync, Cert)>,
+        issues: &mut Vec<ValidationIssue>,
+    ) {
+        if let Err(e) = self.check_validity(ca_cert) {
+            issues.push(ValidationIssue::new(cert_uri.clone(), e));
+        }
+
+        let resources = match ResourceSet::try_from(ca_cert) {
+            Ok(resources) => resources,
+            Err(e) => {
+                issues.push(ValidationIssue::new(
+                    cert_uri.clone(),
+                    format!("invalid resources: {}", e),
+                ));
+                return;
+            }
+        };
+
+        let (dir, mft_uri) = match (ca_cert.ca_repository(), ca_cert.rpki_manifest()) {
+            (Some(dir), Some(mft_uri)) => (dir, mft_uri),
+            _ => {
+                issues.push(ValidationIssue::new(
+                    cert_uri.clone(),
+                    "certificate has no CA repository or manifest URI",
+                ));
+                return;
+            }
+        };
+
+        listed.insert(mft_uri.clone());
+
+        let mft_bytes = match self.objects.rsync().get(mft_uri) {
+            Some(bytes) => bytes.clone(),
+            None => {
+                issues.push(ValidationIssue::new(mft_uri.clone(), "manifest is missing"));
+                return;
+            }
+        };
+
+        let manifest = match Manifest::decode(mft_bytes.clone(), false) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                issues.push(ValidationIssue::new(
+                    mft_uri.clone(),
+                    format!("cannot decode manifest: {}", e),
+                ));
+                return;
+            }
+        };
+
+        if manifest.this_update() > self.now {
+            issues.push(ValidationIssue::new(mft_uri.clone(), "manifest is not yet valid"));
+        }
+        if manifest.next_update() < self.now {
+            issues.push(ValidationIssue::new(
+                mft_uri.clone(),
+                format!("manifest is stale since {}", manifest.next_update().to_rfc3339()),
+            ));
+        }
+
+        // Check that all files on the manifest are present and match their
+        // hash. Remember the files that can be validated further.
+        let mut files = vec![];
+        for entry in manifest.iter() {
+            let uri = match dir.join(entry.file().as_ref()) {
+                Ok(uri) => uri,
+                Err(_) => {
+                    issues.push(ValidationIssue::new(
+                        mft_uri.clone(),
+                        format!(
+                            "invalid file name on manifest: {}",
+                            String::from_utf8_lossy(entry.file())
+                        ),
+                    ));
+                    continue;
+                }
+            };
+            listed.insert(uri.clone());
+
+            match self.objects.rsync().get(&uri) {
+                None => issues.push(ValidationIssue::new(uri, "listed on manifest, but missing")),
+                Some(bytes) => {
+                    if DigestAlgorithm::default().digest(bytes.as_ref()).as_ref() != entry.hash().as_ref() {
+                        issues.push(ValidationIssue::new(uri, "hash does not match the manifest"));
+                    } else {
+                        files.push((uri, bytes.clone()));
+                    }
+                }
+            }
+        }
+
+        // The CRL is needed to check whether certificates were revoked.
+        let crls: Vec<&(uri::Rsync, Bytes)> = files.iter().filter(|(uri, _)| has_extension(uri, "crl")).collect();
+        let crl = match crls.as_slice() {
+            [] => {
+                issues.push(ValidationIssue::new(mft_uri.clone(), "manifest does not list a CRL"));
+                None
+            }
+            [(crl_uri, crl_bytes)] => match self.validate_crl(crl_bytes, ca_cert) {
+                Ok(crl) => Some(crl),
+                Err(e) => {
+                    issues.push(ValidationIssue::new(crl_uri.clone(), e));
+                    None
+                }
+            },
+            _ => {
+                issues.push(ValidationIssue::new(
+                    mft_uri.clone(),
+                    "manifest lists more than one CRL",
+                ));
+                None
+            }
+        };
+        let crl = crl.as_ref();
+
+        if let Err(e) = self.validate_signed_object(&mft_bytes, ca_cert, &resources, crl) {
+            issues.push(ValidationIssue::new(mft_uri.clone(), e));
+        }
+
+        for (uri, bytes) in files {
+            if has_extension(&uri, "crl") {
+                continue;
+            } else if has_extension(&uri, "cer") {
+                match Cert::decode(bytes.as_ref()) {
+                    Ok(cert) => match self.validate_issued(&cert, ca_cert, &resources, crl) {
+                        Ok(()) => {
+                            if cert.ca_repository().is_some() {
+                                children.push((uri, cert));
+                            }
+                        }
+                        Err(e) => issues.push(ValidationIssue::new(uri, e)),
+                    },
+                    Err(e) => issues.push(ValidationIssue::new(uri, format!("cannot decode certificate: {}", e))),
+                }
+            } else if let Err(e) = self.validate_signed_object(&bytes, ca_cert, &resources, crl) {
+                issues.push(ValidationIssue::new(uri, e));
+            }
+        }
+    }
+
+    fn validate_crl(&self, bytes: &Bytes, ca_cert: &Cert) -> Result<Crl, String> {
+        let crl = Crl::decode(bytes.clone()).map_err(|e| format!("cannot decode CRL: {}", e))?;
+
+        verify_signed_data(bytes.clone(), ca_cert.subject_public_key_info()).map_err(|e| format!("CRL: {}", e))?;
+
+        if crl.next_update() < self.now {
+            return Err(format!("CRL is stale since {}", crl.next_update().to_rfc3339()));
+        }
+
+        Ok(crl)
+    }
+
+    /// Validates an RPKI signed object, such as a manifest or ROA, and its
+    /// EE certificate.
+    fn validate_signed_object(
+        &self,
+        bytes: &Bytes,
+        ca_cert: &Cert,
+        ca_resources: &ResourceSet,
+        crl: Option<&Crl>,
+    ) -> Result<(), String> {
+        let object = SignedObject::decode(bytes.clone()).map_err(|e| e.to_string())?;
+        let ee = object.verify_single_signer()?;
+        self.validate_issued(ee, ca_cert, ca_resources, crl)
+            .map_err(|e| format!("EE certificate: {}", e))
+    }
+
+    /// Validates a certificate issued by the CA.
+    fn validate_issued(
+        &self,
+        cert: &Cert,
+        ca_cert: &Cert,
+        ca_resources: &ResourceSet,
+        crl: Option<&Crl>,
+    ) -> Result<(), String> {
+        if cert.authority_key_identifier() != Some(ca_cert.subject_key_identifier()) {
+            return Err("not issued by the key of the CA".to_string());
+        }
+
+        verify_signed_data(Base64::from(cert).to_bytes(), ca_cert.subject_public_key_info())?;
+
+        self.check_validity(cert)?;
+
+        if let Some(crl) = crl {
+            if crl.contains(cert.serial_number()) {
+                return Err("certificate is revoked".to_string());
+            }
+        }
+
+        // Certificates which inherit their resources have no resource set
+        // of their own to check.
+        if let Ok(resources) = ResourceSet::try_from(cert) {
+            if !ca_resources.contains(&resources) {
+                return Err(format!(
+                    "claims resources not held by the CA: {}",
+                    resources.difference(ca_resources)
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_validity(&self, cert: &Cert) -> Result<(), String> {
+        let validity = cert.validity();
+        if validity.not_before() > self.now {
+            Err("certificate is not yet valid".to_string())
+        } else if validity.not_after() < self.now {
+            Err(format!("certificate expired at {}", validity.not_after().to_rfc3339()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn in_repository(&self, uri: &uri::Rsync) -> bool {
+        uri.relative_to(self.objects.rsync_base()).is_some()
+    }
+}
+
+//------------ PublicationPoint ----------------------------------------------
+
+/// A publication point, and the certificates of the keys which publish in
+/// it. If the CA is not a local CA, then issues are reported for the
+/// repository.
+struct PublicationPoint {
+    ca: Option<CaHandle>,
+    dir: uri::Rsync,
+    certs: Vec<(uri::Rsync, Cert)>,
+}
+
+impl PublicationPoint {
+    fn new(ca: Option<CaHandle>, dir: uri::Rsync, certs: Vec<(uri::Rsync, Cert)>) -> Self {
+        PublicationPoint { ca, dir, certs }
+    }
+}
+
+//------------ Remote repositories -------------------------------------------
+
+/// Fetches the objects published under the given publication point in a
+/// remote repository, using the RRDP snapshot of that repository. Objects
+/// published by others in that repository are skipped.
+pub async fn fetch_remote_objects(notify: &uri::Https, dir: &uri::Rsync) -> Result<RepositoryObjects, String> {
+    let notification = httpclient::get_binary(notify.as_str(), None)
+        .await
+        .map_err(|e| e.to_string())?;
+    let notification = NotificationFile::parse(notification.as_ref())
+        .map_err(|e| format!("cannot parse RRDP notification file at {}: {}", notify, e))?;
+
+    let snapshot_info = notification.snapshot();
+    let snapshot = httpclient::get_binary(snapshot_info.uri().as_str(), None)
+        .await
+        .map_err(|e| e.to_string())?;
+    if Hash::from_data(snapshot.as_ref()) != snapshot_info.hash() {
+        return Err(format!(
+            "RRDP snapshot at {} does not match the hash in the notification file",
+            snapshot_info.uri()
+        ));
+    }
+
+    let mut point = SnapshotPublicationPoint::new(dir);
+    point
+        .process(snapshot.as_ref())
+        .map_err(|e| format!("cannot parse RRDP snapshot at {}: {}", snapshot_info.uri(), e))?;
+
+    Ok(point.into_objects())
+}
+
+/// Keeps the objects from an RRDP snapshot which are published under a
+/// single publication point.
+struct SnapshotPublicationPoint<'a> {
+    dir: &'a uri::Rsync,
+    objects: HashMap<uri::Rsync, Bytes>,
+}
+
+impl<'a> SnapshotPublicationPoint<'a> {
+    fn new(dir: &'a uri::Rsync) -> Self {
+        SnapshotPublicationPoint {
+            dir,
+            objects: HashMap::new(),
+        }
+    }
+
+    /// The objects are used for both rsync and RRDP, so that only the
+    /// published objects themselves are validated.
+    fn into_objects(self) -> RepositoryObjects {
+        RepositoryObjects::new(self.dir.clone(), self.objects.clone(), self.objects)
+    }
+}
+
+impl ProcessSnapshot for SnapshotPublicationPoint<'_> {
+    type Err = ProcessError;
+
+    fn meta(&mut self, _session_id: Uuid, _serial: u64) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    fn publish(&mut self, uri: uri::Rsync, data: &mut ObjectReader) -> Result<(), Self::Err> {
+        if uri.relative_to(self.dir).is_some() {
+            let mut bytes = vec![];
+            data.read_to_end(&mut bytes)?;
+            self.objects.insert(uri, Bytes::from(bytes));
+        }
+        Ok(())
+    }
+}
+
+//------------ Helpers -------------------------------------------------------
+
+/// Verifies the signature of a certificate or CRL, i.e. the signature over
+/// the to-be-signed part of its outer SEQUENCE (RFC 5280).
+fn verify_signed_data(bytes: Bytes, key: &PublicKey) -> Result<(), String> {
+    let (tbs, signature) = Mode::Der
+        .decode(bytes, |cons| {
+            cons.take_sequence(|cons| {
+                let tbs = cons.capture_one()?;
+                cons.take_sequence(|cons| cons.skip_all())?;
+                let signature = BitString::take_from(cons)?;
+                Ok((tbs, signature))
+            })
+        })
+        .map_err(|e| format!("cannot decode: {}", e))?;
+
+    let signature = RpkiSignature::new(RpkiSignatureAlgorithm::default(), signature.octet_bytes());
+    key.verify(tbs.as_slice(), &signature)
+        .map_err(|_| "invalid signature".to_string())
+}
+
+/// Returns whether the URI is for a file directly in the given directory.
+fn in_dir(uri: &uri::Rsync, dir: &uri::Rsync) -> bool {
+    match uri.relative_to(dir) {
+        Some(rel) => !rel.is_empty() && !rel.contains('/'),
+        None => false,
+    }
+}
+
+fn has_extension(uri: &uri::Rsync, extension: &str) -> bool {
+    uri.path().rsplit('.').next() == Some(extension)
+}
+
+fn sort_issues(issues: &mut [ValidationIssue]) {
+    issues.sort_by_key(|issue| issue.uri().to_string());
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rpki::rrdp;
+
+    use super::*;
+
+    fn rsync(s: &str) -> uri::Rsync {
+        uri::Rsync::from_str(s).unwrap()
+    }
+
+    fn validator(rsync_objects: &[(&str, &str)], rrdp_objects: &[(&str, &str)]) -> RepositoryValidator {
+        let map = |objects: &[(&str, &str)]| {
+            objects
+                .iter()
+                .map(|(uri, content)| (rsync(uri), Bytes::copy_from_slice(content.as_bytes())))
+                .collect()
+        };
+        let objects = RepositoryObjects::new(rsync("rsync://localhost/repo/"), map(rsync_objects), map(rrdp_objects));
+        RepositoryValidator::new(objects)
+    }
+
+    #[test]
+    fn rsync_and_rrdp_differences_are_repository_issues() {
+        let validator = validator(
+            &[
+                ("rsync://localhost/repo/ca/same.roa", "same"),
+                ("rsync://localhost/repo/ca/changed.roa", "old"),
+                ("rsync://localhost/repo/ca/rsync-only.roa", "rsync"),
+            ],
+            &[
+                ("rsync://localhost/repo/ca/same.roa", "same"),
+                ("rsync://localhost/repo/ca/changed.roa", "new"),
+                ("rsync://localhost/repo/ca/rrdp-only.roa", "rrdp"),
+            ],
+        );
+
+        let report = validator.validate(&[], vec![]);
+        assert!(report.cas().is_empty());
+
+        let uris: Vec<String> = report
+            .repository_issues()
+            .iter()
+            .map(|issue| issue.uri().to_string())
+            .collect();
+        assert_eq!(
+            uris,
+            vec![
+                "rsync://localhost/repo/ca/changed.roa",
+                "rsync://localhost/repo/ca/rrdp-only.roa",
+                "rsync://localhost/repo/ca/rsync-only.roa",
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_publication_point_skips_other_publishers() {
+        let element = |uri: &str| rrdp::PublishElement::new(rsync(uri), Bytes::copy_from_slice(uri.as_bytes()));
+        let snapshot = rrdp::Snapshot::new(
+            Uuid::new_v4(),
+            1,
+            vec![
+                element("rsync://remote/repo/ca/object.roa"),
+                element("rsync://remote/repo/ca/child/object.roa"),
+                element("rsync://remote/repo/other/object.roa"),
+            ],
+        );
+        let mut xml = vec![];
+        snapshot.write_xml(&mut xml).unwrap();
+
+        let dir = rsync("rsync://remote/repo/ca/");
+        let mut point = SnapshotPublicationPoint::new(&dir);
+        point.process(xml.as_slice()).unwrap();
+        let objects = point.into_objects();
+
+        let mut uris: Vec<String> = objects.rsync().keys().map(|uri| uri.to_string()).collect();
+        uris.sort();
+        assert_eq!(
+            uris,
+            vec![
+                "rsync://remote/repo/ca/child/object.roa",
+                "rsync://remote/repo/ca/object.roa",
+            ]
+        );
+        assert_eq!(objects.rsync(), objects.rrdp());
+        assert_eq!(objects.rsync_base(), &dir);
+    }
+
+    #[test]
+    fn tal_certificates_outside_repository_are_skipped() {
+        let cert = Cert::decode(Bytes::from_static(include_bytes!("../../../test-resources/ta.cer"))).unwrap();
+        let tal = |uri: &str| TrustAnchorLocator::new(vec![], rsync(uri), cert.subject_public_key_info());
+
+        let validator = validator(&[], &[]);
+        let report = validator.validate(
+            &[
+                tal("rsync://localhost/ta/ta.cer"),
+                tal("rsync://localhost/repo/ta/ta.cer"),
+            ],
+            vec![],
+        );
+
+        let uris: Vec<String> = report
+            .repository_issues()
+            .iter()
+            .map(|issue| issue.uri().to_string())
+            .collect();
+        assert_eq!(uris, vec!["rsync://localhost/repo/ta/ta.cer"]);
+    }
+
+    #[test]
+    fn in_dir_only_matches_files_directly_in_dir() {
+        let dir = rsync("rsync://localhost/repo/ca/");
+        assert!(in_dir(&rsync("rsync://localhost/repo/ca/object.roa"), &dir));
+        assert!(!in_dir(&rsync("rsync://localhost/repo/ca/child/object.roa"), &dir));
+        assert!(!in_dir(&rsync("rsync://localhost/repo/other/object.roa"), &dir));
+    }
+}
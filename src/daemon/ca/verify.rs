@@ -0,0 +1,518 @@
+//! Verification of Resource Tagged Attestations (RTA) and Resource Signed
+//! Checklists (RSC) received from others.
+//!
+//! These objects are not published in the RPKI, so the certificate path of
+//! each signer is built from the certificates embedded in the object, the
+//! certificates supplied by the user, and the certificates held by the CAs
+//! in this Krill instance. Revocation is not checked, as CRLs are not
+//! available for all certificates on the path.
+use std::{convert::TryFrom, fmt, sync::Arc};
+
+use bcder::{encode, encode::Values, Captured, ConstOid, Mode, OctetString, Oid, Tag};
+use bytes::Bytes;
+
+use rpki::{
+    ca::publication::Base64,
+    crypto::{DigestAlgorithm, KeyIdentifier, PublicKey, RpkiSignature, RpkiSignatureAlgorithm},
+    repository::{
+        resources::ResourceSet,
+        tal::{Tal, TalInfo},
+        Cert,
+    },
+};
+
+use crate::{
+    commons::{error::Error, KrillResult},
+    daemon::ca::{
+        rsc::{CONTENT_TYPE, MESSAGE_DIGEST, SIGNED_CHECKLIST, SIGNED_DATA},
+        ResourceSignedChecklist, ResourceTaggedAttestation,
+    },
+};
+
+/// id-ct-resourceTaggedAttestation, draft-ietf-sidrops-rpki-rta
+const RESOURCE_TAGGED_ATTESTATION: ConstOid = Oid(&[42, 134, 72, 134, 247, 13, 1, 9, 16, 1, 36]);
+
+/// The maximum number of CA certificates on a path, to guard against loops.
+const MAX_PATH_LEN: usize = 32;
+
+//------------ SignedObjectVerifyRequest -------------------------------------
+
+/// A request to verify an RTA or RSC against the given trust anchors.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SignedObjectVerifyRequest {
+    object: Base64,
+
+    /// Trust Anchor Locators (RFC 8630), as text.
+    #[serde(default)]
+    tals: Vec<String>,
+
+    /// Trust anchor certificates, trusted as they are.
+    #[serde(default)]
+    ta_certs: Vec<Base64>,
+
+    /// Other CA certificates which may be needed to build the paths.
+    #[serde(default)]
+    certs: Vec<Base64>,
+}
+
+impl SignedObjectVerifyRequest {
+    pub fn new(object: Bytes, tals: Vec<String>, ta_certs: Vec<Bytes>, certs: Vec<Bytes>) -> Self {
+        SignedObjectVerifyRequest {
+            object: Base64::from_content(&object),
+            tals,
+            ta_certs: ta_certs.iter().map(|cert| Base64::from_content(cert)).collect(),
+            certs: certs.iter().map(|cert| Base64::from_content(cert)).collect(),
+        }
+    }
+
+    /// Verifies the object, using the given certificates held by local CAs
+    /// in addition to the certificates in this request.
+    pub fn verify(self, local_certs: Vec<Cert>) -> KrillResult<SignedObjectVerification> {
+        let mut anchors = vec![];
+        let mut pool = vec![];
+
+        for tal in &self.tals {
+            let tal = Tal::read_named(String::new(), &mut tal.as_bytes())
+                .map_err(|e| Error::custom(format!("Cannot parse TAL: {}", e)))?;
+            anchors.push(TrustAnchor::new(tal.key_info()));
+        }
+
+        for ta_cert in &self.ta_certs {
+            let cert = Self::decode_cert(ta_cert)?;
+            anchors.push(TrustAnchor::new(cert.subject_public_key_info()));
+            pool.push(cert);
+        }
+
+        if anchors.is_empty() {
+            return Err(Error::custom("At least one TAL or trust anchor certificate is needed"));
+        }
+
+        let bytes = self.object.to_bytes();
+        let object = SignedObject::decode(bytes.clone())?;
+
+        let (object_type, resources, expected_keys) = if object.content_type == RESOURCE_TAGGED_ATTESTATION {
+            let (resources, keys) = ResourceTaggedAttestation::new(bytes).attested()?;
+            (SignedObjectType::Rta, resources, keys)
+        } else if object.content_type == SIGNED_CHECKLIST {
+            let resources = ResourceSignedChecklist::content_resources(object.content.clone())?;
+            (SignedObjectType::Rsc, resources, vec![])
+        } else {
+            return Err(Error::custom("Object is neither an RTA nor an RSC"));
+        };
+
+        pool.extend(object.certs.iter().cloned());
+        for cert in &self.certs {
+            pool.push(Self::decode_cert(cert)?);
+        }
+        pool.extend(local_certs);
+
+        let mut signers: Vec<SignerVerification> = object
+            .signer_infos
+            .iter()
+            .map(|info| object.verify_signer(info, &pool, &anchors))
+            .collect();
+
+        // An RTA lists the keys of all signers, so a missing signature means
+        // that it was not (yet) signed by all parties.
+        for key in expected_keys {
+            if !signers.iter().any(|signer| signer.key == key) {
+                signers.push(SignerVerification::invalid(
+                    key,
+                    "no signature for this key".to_string(),
+                ));
+            }
+        }
+
+        Ok(SignedObjectVerification::new(object_type, resources, signers))
+    }
+
+    fn decode_cert(cert: &Base64) -> KrillResult<Cert> {
+        Cert::decode(cert.to_bytes().as_ref()).map_err(|e| Error::custom(format!("Cannot decode certificate: {}", e)))
+    }
+}
+
+//------------ TrustAnchor ---------------------------------------------------
+
+/// A trust anchor, identified by its public key.
+struct TrustAnchor {
+    key: KeyIdentifier,
+    info: Arc<TalInfo>,
+}
+
+impl TrustAnchor {
+    fn new(key: &PublicKey) -> Self {
+        let key = key.key_identifier();
+        let info = TalInfo::from_name(key.to_string());
+        TrustAnchor { key, info: info.into() }
+    }
+}
+
+//------------ SignedObject --------------------------------------------------
+
+/// The parts of a CMS signed object (RFC 5652) needed for verification.
+pub(super) struct SignedObject {
+    content_type: Oid<Bytes>,
+    content: Bytes,
+    certs: Vec<Cert>,
+    signer_infos: Vec<SignerInfo>,
+}
+
+/// A SignerInfo, identified by the subject key identifier of its EE certificate.
+struct SignerInfo {
+    key: KeyIdentifier,
+    signed_attrs: Captured,
+    signature: Bytes,
+}
+
+impl SignedObject {
+    pub(super) fn decode(bytes: Bytes) -> KrillResult<Self> {
+        let (content_info_type, object) = Mode::Der
+            .decode(bytes, |cons| {
+                cons.take_sequence(|cons| {
+                    let content_info_type = Oid::take_from(cons)?;
+                    let object = cons.take_constructed_if(Tag::CTX_0, |cons| {
+                        cons.take_sequence(|cons| {
+                            cons.skip_u8_if(3)?;
+                            cons.take_set(|cons| cons.skip_all())?;
+                            let (content_type, content) = cons.take_sequence(|cons| {
+                                let content_type = Oid::take_from(cons)?;
+                                let content = cons.take_constructed_if(Tag::CTX_0, OctetString::take_from)?;
+                                Ok((content_type, content.to_bytes()))
+                            })?;
+                            let certs = cons.take_opt_constructed_if(Tag::CTX_0, |cons| {
+                                let mut certs = vec![];
+                                while let Some(cert) = Cert::take_opt_from(cons)? {
+                                    certs.push(cert);
+                                }
+                                Ok(certs)
+                            })?;
+                            cons.take_opt_constructed_if(Tag::CTX_1, |cons| cons.skip_all())?;
+                            let signer_infos = cons.take_set(|cons| {
+                                let mut signer_infos = vec![];
+                                while let Some(info) = cons.take_opt_sequence(|cons| {
+                                    cons.skip_u8_if(3)?;
+                                    let key = cons.take_primitive_if(Tag::CTX_0, |prim| prim.take_all())?;
+                                    cons.take_sequence(|cons| cons.skip_all())?;
+                                    let signed_attrs =
+                                        cons.take_constructed_if(Tag::CTX_0, |cons| cons.capture_all())?;
+                                    cons.take_sequence(|cons| cons.skip_all())?;
+                                    let signature = OctetString::take_from(cons)?;
+                                    cons.take_opt_constructed_if(Tag::CTX_1, |cons| cons.skip_all())?;
+                                    Ok((key, signed_attrs, signature.to_bytes()))
+                                })? {
+                                    signer_infos.push(info);
+                                }
+                                Ok(signer_infos)
+                            })?;
+                            Ok((content_type, content, certs.unwrap_or_default(), signer_infos))
+                        })
+                    })?;
+                    Ok((content_info_type, object))
+                })
+            })
+            .map_err(|e| Error::custom(format!("Cannot decode signed object: {}", e)))?;
+
+        if content_info_type != SIGNED_DATA {
+            return Err(Error::custom("Object is not a CMS signed object"));
+        }
+
+        let (content_type, content, certs, signer_infos) = object;
+
+        let mut infos = vec![];
+        for (key, signed_attrs, signature) in signer_infos {
+            let key = KeyIdentifier::try_from(key.as_ref())
+                .map_err(|_| Error::custom("Signer identifier is not a key identifier"))?;
+            infos.push(SignerInfo {
+                key,
+                signed_attrs,
+                signature,
+            });
+        }
+
+        Ok(SignedObject {
+            content_type,
+            content,
+            certs,
+            signer_infos: infos,
+        })
+    }
+
+    /// Verifies the signature of an RPKI signed object (RFC 6488) and
+    /// returns its EE certificate. Such objects have exactly one signer,
+    /// and include the EE certificate of that signer.
+    pub(super) fn verify_single_signer(&self) -> Result<&Cert, String> {
+        let info = match self.signer_infos.as_slice() {
+            [info] => info,
+            _ => return Err("signed object must have exactly one signer".to_string()),
+        };
+
+        let ee = self
+            .certs
+            .iter()
+            .find(|cert| cert.subject_key_identifier() == info.key)
+            .ok_or_else(|| "EE certificate not included".to_string())?;
+
+        self.verify_signature(info, ee)?;
+        Ok(ee)
+    }
+
+    fn verify_signer(&self, info: &SignerInfo, pool: &[Cert], anchors: &[TrustAnchor]) -> SignerVerification {
+        let key = info.key;
+        let ee = match self.certs.iter().find(|cert| cert.subject_key_identifier() == key) {
+            Some(ee) => ee,
+            None => return SignerVerification::invalid(key, "EE certificate not included".to_string()),
+        };
+
+        match self
+            .verify_signature(info, ee)
+            .and_then(|_| Self::validate_path(ee, pool, anchors))
+        {
+            Ok((trust_anchor, resources)) => SignerVerification::valid(key, trust_anchor, resources),
+            Err(reason) => SignerVerification::invalid(key, reason),
+        }
+    }
+
+    /// Verifies the signed attributes and the signature over them.
+    fn verify_signature(&self, info: &SignerInfo, ee: &Cert) -> Result<(), String> {
+        let (content_type, digest) = Mode::Der
+            .decode(info.signed_attrs.clone().into_bytes(), |cons| {
+                let mut content_type = None;
+                let mut digest = None;
+                while let Some(()) = cons.take_opt_sequence(|cons| {
+                    let oid = Oid::take_from(cons)?;
+                    if oid == CONTENT_TYPE {
+                        content_type = Some(cons.take_set(Oid::take_from)?);
+                    } else if oid == MESSAGE_DIGEST {
+                        digest = Some(cons.take_set(OctetString::take_from)?.to_bytes());
+                    } else {
+                        cons.skip_all()?;
+                    }
+                    Ok(())
+                })? {}
+                Ok((content_type, digest))
+            })
+            .map_err(|e| format!("cannot decode signed attributes: {}", e))?;
+
+        if content_type.as_ref() != Some(&self.content_type) {
+            return Err("content type attribute does not match content".to_string());
+        }
+
+        let expected_digest = DigestAlgorithm::default().digest(self.content.as_ref());
+        if digest.as_deref() != Some(expected_digest.as_ref()) {
+            return Err("message digest attribute does not match content".to_string());
+        }
+
+        // The signature is calculated over the DER encoded SET OF attributes
+        let message = encode::set(&info.signed_attrs).to_captured(Mode::Der);
+        let signature = RpkiSignature::new(RpkiSignatureAlgorithm::default(), info.signature.clone());
+        ee.subject_public_key_info()
+            .verify(message.as_slice(), &signature)
+            .map_err(|_| "invalid signature".to_string())
+    }
+
+    /// Builds the path from the EE certificate up to a trust anchor, and then
+    /// validates it from the top down. Returns the key of the trust anchor
+    /// and the resources of the EE certificate.
+    fn validate_path(
+        ee: &Cert,
+        pool: &[Cert],
+        anchors: &[TrustAnchor],
+    ) -> Result<(KeyIdentifier, ResourceSet), String> {
+        let mut path: Vec<&Cert> = vec![];
+        let mut current = ee;
+
+        let anchor = loop {
+            if path.len() >= MAX_PATH_LEN {
+                return Err("certificate path is too long".to_string());
+            }
+
+            let issuer_key = current
+                .authority_key_identifier()
+                .ok_or_else(|| "certificate has no authority key identifier".to_string())?;
+
+            let issuer = pool
+                .iter()
+                .find(|cert| cert.subject_key_identifier() == issuer_key)
+                .ok_or_else(|| format!("no certificate found for issuing key {}", issuer_key))?;
+            path.push(issuer);
+
+            if let Some(anchor) = anchors.iter().find(|anchor| anchor.key == issuer_key) {
+                break anchor;
+            }
+            current = issuer;
+        };
+
+        let (ta, cas) = path.split_last().ok_or_else(|| "empty certificate path".to_string())?;
+
+        let mut issuer = (*ta)
+            .clone()
+            .validate_ta(anchor.info.clone(), false)
+            .map_err(|e| format!("invalid trust anchor certificate {}: {}", anchor.key, e))?;
+
+        for ca in cas.iter().rev() {
+            issuer = (*ca)
+                .clone()
+                .validate_ca(&issuer, false)
+                .map_err(|e| format!("invalid CA certificate {}: {}", ca.subject_key_identifier(), e))?;
+        }
+
+        ee.clone()
+            .validate_detached_ee(&issuer, false)
+            .map_err(|e| format!("invalid EE certificate: {}", e))?;
+
+        let resources = ResourceSet::try_from(ee).map_err(|e| format!("invalid resources on EE certificate: {}", e))?;
+
+        Ok((anchor.key, resources))
+    }
+}
+
+//------------ SignedObjectType ----------------------------------------------
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignedObjectType {
+    Rta,
+    Rsc,
+}
+
+impl fmt::Display for SignedObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignedObjectType::Rta => write!(f, "RTA"),
+            SignedObjectType::Rsc => write!(f, "RSC"),
+        }
+    }
+}
+
+//------------ SignedObjectVerification --------------------------------------
+
+/// The outcome of verifying an RTA or RSC. The object is valid if it has at
+/// least one signature, all signatures are valid, and the resources of the
+/// EE certificates together cover the attested resources.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SignedObjectVerification {
+    object_type: SignedObjectType,
+    resources: ResourceSet,
+    signers: Vec<SignerVerification>,
+    valid: bool,
+}
+
+impl SignedObjectVerification {
+    fn new(object_type: SignedObjectType, resources: ResourceSet, signers: Vec<SignerVerification>) -> Self {
+        let mut covered = ResourceSet::default();
+        for signer in &signers {
+            if let SignerStatus::Valid { resources, .. } = &signer.status {
+                covered = covered.union(resources);
+            }
+        }
+
+        let valid =
+            !signers.is_empty() && signers.iter().all(|signer| signer.is_valid()) && covered.contains(&resources);
+
+        SignedObjectVerification {
+            object_type,
+            resources,
+            signers,
+            valid,
+        }
+    }
+
+    pub fn object_type(&self) -> SignedObjectType {
+        self.object_type
+    }
+
+    pub fn resources(&self) -> &ResourceSet {
+        &self.resources
+    }
+
+    pub fn signers(&self) -> &Vec<SignerVerification> {
+        &self.signers
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+}
+
+impl fmt::Display for SignedObjectVerification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Object type: {}", self.object_type)?;
+        writeln!(f, "Resources: {}", self.resources)?;
+        writeln!(f, "Signatures:")?;
+        for signer in &self.signers {
+            writeln!(f, "  {}", signer)?;
+        }
+        if self.valid {
+            writeln!(f, "Result: valid")
+        } else {
+            writeln!(f, "Result: INVALID")
+        }
+    }
+}
+
+//------------ SignerVerification --------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SignerVerification {
+    key: KeyIdentifier,
+    status: SignerStatus,
+}
+
+impl SignerVerification {
+    fn valid(key: KeyIdentifier, trust_anchor: KeyIdentifier, resources: ResourceSet) -> Self {
+        SignerVerification {
+            key,
+            status: SignerStatus::Valid {
+                trust_anchor,
+                resources,
+            },
+        }
+    }
+
+    fn invalid(key: KeyIdentifier, reason: String) -> Self {
+        SignerVerification {
+            key,
+            status: SignerStatus::Invalid { reason },
+        }
+    }
+
+    pub fn key(&self) -> KeyIdentifier {
+        self.key
+    }
+
+    pub fn status(&self) -> &SignerStatus {
+        &self.status
+    }
+
+    pub fn is_valid(&self) -> bool {
+        matches!(self.status, SignerStatus::Valid { .. })
+    }
+}
+
+impl fmt::Display for SignerVerification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.status {
+            SignerStatus::Valid {
+                trust_anchor,
+                resources,
+            } => write!(
+                f,
+                "{}: valid, trust anchor key: {}, resources: {}",
+                self.key, trust_anchor, resources
+            ),
+            SignerStatus::Invalid { reason } => write!(f, "{}: INVALID, {}", self.key, reason),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerStatus {
+    Valid {
+        trust_anchor: KeyIdentifier,
+        resources: ResourceSet,
+    },
+    Invalid {
+        reason: String,
+    },
+}
@@ -145,6 +145,14 @@ impl ConfigDefaults {
         None
     }
 
+    fn rfc6492_child_max_requests_per_minute() -> u32 {
+        60
+    }
+
+    fn rfc6492_list_cache_seconds() -> u32 {
+        10
+    }
+
     fn post_protocol_msg_timeout_seconds() -> u64 {
         240 // 4 minutes by default should be plenty in most cases
     }
@@ -179,6 +187,18 @@ impl ConfigDefaults {
         90
     }
 
+    fn rtr_refresh_interval() -> u32 {
+        3600
+    }
+
+    fn rtr_retry_interval() -> u32 {
+        600
+    }
+
+    fn rtr_expire_interval() -> u32 {
+        7200
+    }
+
     fn timing_publish_next_hours() -> u32 {
         24
     }
@@ -488,6 +508,12 @@ pub struct Config {
     #[serde(default = "ConfigDefaults::rfc6492_log_dir")]
     pub rfc6492_log_dir: Option<PathBuf>,
 
+    #[serde(default = "ConfigDefaults::rfc6492_child_max_requests_per_minute")]
+    pub rfc6492_child_max_requests_per_minute: u32,
+
+    #[serde(default = "ConfigDefaults::rfc6492_list_cache_seconds")]
+    pub rfc6492_list_cache_seconds: u32,
+
     // RIS BGP
     #[serde(default = "ConfigDefaults::bgp_risdumps_enabled")]
     pub bgp_risdumps_enabled: bool,
@@ -496,6 +522,14 @@ pub struct Config {
     #[serde(default = "ConfigDefaults::bgp_risdumps_v6_uri")]
     pub bgp_risdumps_v6_uri: String,
 
+    // AS paths for ASPA analysis, from an MRT RIB dump or a path per line
+    #[serde(default)]
+    pub bgp_aspa_paths_uri: Option<String>,
+
+    // Reject ROA updates which allow unannounced more specifics (RFC 9319)
+    #[serde(default)]
+    pub roa_max_length_strict: bool,
+
     // ROA Aggregation per ASN
     #[serde(default = "ConfigDefaults::roa_aggregate_threshold")]
     pub roa_aggregate_threshold: usize,
@@ -515,6 +549,8 @@ pub struct Config {
     pub testbed: Option<TestBed>,
 
     pub benchmark: Option<Benchmark>,
+
+    pub rtr: Option<RtrConfig>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -544,6 +580,26 @@ pub struct IssuanceTimingConfig {
 }
 
 impl IssuanceTimingConfig {
+    /// Timing for an offline Trust Anchor signer. Its manifest and CRL are
+    /// only re-issued when the signer processes a request, so they use a
+    /// validity of weeks rather than hours, without jitter.
+    pub fn for_ta_signer(mft_next_update_weeks: u32, child_cert_valid_weeks: u32) -> Self {
+        IssuanceTimingConfig {
+            timing_publish_next_hours: mft_next_update_weeks * 7 * 24,
+            timing_publish_next_jitter_hours: 0,
+            timing_publish_hours_before_next: ConfigDefaults::timing_publish_hours_before_next(),
+            timing_child_certificate_valid_weeks: child_cert_valid_weeks,
+            timing_child_certificate_reissue_weeks_before:
+                ConfigDefaults::timing_child_certificate_reissue_weeks_before(),
+            timing_roa_valid_weeks: ConfigDefaults::timing_roa_valid_weeks(),
+            timing_roa_reissue_weeks_before: ConfigDefaults::timing_roa_reissue_weeks_before(),
+            timing_aspa_valid_weeks: ConfigDefaults::timing_aspa_valid_weeks(),
+            timing_aspa_reissue_weeks_before: ConfigDefaults::timing_aspa_reissue_weeks_before(),
+            timing_bgpsec_valid_weeks: ConfigDefaults::timing_bgpsec_valid_weeks(),
+            timing_bgpsec_reissue_weeks_before: ConfigDefaults::timing_bgpsec_reissue_weeks_before(),
+        }
+    }
+
     //-- Publishing Manifests and CRLs
 
     /// Returns the next update time based on configuration:
@@ -747,6 +803,20 @@ pub struct Benchmark {
     pub ca_roas: usize,
 }
 
+/// Configuration for the RPKI-to-Router server. This is intended for lab
+/// use only: it serves the VRPs configured in the hosted CAs, rather than
+/// VRPs which were validated by relying party software.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RtrConfig {
+    pub listen: SocketAddr,
+    #[serde(default = "ConfigDefaults::rtr_refresh_interval")]
+    pub refresh_interval: u32,
+    #[serde(default = "ConfigDefaults::rtr_retry_interval")]
+    pub retry_interval: u32,
+    #[serde(default = "ConfigDefaults::rtr_expire_interval")]
+    pub expire_interval: u32,
+}
+
 /// # Accessors
 impl Config {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
@@ -1039,10 +1109,14 @@ impl Config {
             rfc8181_log_dir,
             post_limit_rfc6492,
             rfc6492_log_dir,
+            rfc6492_child_max_requests_per_minute: 0, // no limits or caching in testing
+            rfc6492_list_cache_seconds: 0,
             post_protocol_msg_timeout_seconds,
             bgp_risdumps_enabled,
             bgp_risdumps_v4_uri,
             bgp_risdumps_v6_uri,
+            bgp_aspa_paths_uri: None,
+            roa_max_length_strict: false,
             roa_aggregate_threshold,
             roa_deaggregate_threshold,
             issuance_timing,
@@ -1050,6 +1124,7 @@ impl Config {
             metrics,
             testbed,
             benchmark: None,
+            rtr: None,
         }
     }
 
@@ -1275,6 +1350,24 @@ impl Config {
             }
         }
 
+        if let Some(rtr) = &self.rtr {
+            // See section 6 of RFC 8210 for the allowed ranges.
+            if !(1..=86400).contains(&rtr.refresh_interval) {
+                return Err(ConfigError::other("[rtr] refresh_interval must be between 1 and 86400"));
+            }
+            if !(1..=7200).contains(&rtr.retry_interval) {
+                return Err(ConfigError::other("[rtr] retry_interval must be between 1 and 7200"));
+            }
+            if !(600..=172800).contains(&rtr.expire_interval)
+                || rtr.expire_interval <= rtr.refresh_interval
+                || rtr.expire_interval <= rtr.retry_interval
+            {
+                return Err(ConfigError::other(
+                    "[rtr] expire_interval must be between 600 and 172800, and larger than the refresh and retry interval",
+                ));
+            }
+        }
+
         if self.signers.is_empty() {
             // Since Config.signers defaults via Serde to ConfigDefaults::signers() which creates a vector with a
             // single signer, this can only happen if we were invoked on a config object created or modified by test
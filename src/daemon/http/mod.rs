@@ -6,6 +6,7 @@ use serde::{de::DeserializeOwned, Serialize};
 use hyper::{body::HttpBody, header::USER_AGENT, http::uri::PathAndQuery, Body, HeaderMap, Method, StatusCode};
 
 use rpki::ca::{provisioning, publication};
+use urlparse::urlparse;
 
 use crate::{
     commons::{
@@ -32,7 +33,9 @@ pub type RoutingResult = Result<HttpResponse, Request>;
 
 #[derive(Clone, Copy)]
 enum ContentType {
+    Binary,
     Cert,
+    Csv,
     Json,
     Rfc8181,
     Rfc6492,
@@ -50,7 +53,9 @@ enum ContentType {
 impl AsRef<str> for ContentType {
     fn as_ref(&self) -> &str {
         match self {
+            ContentType::Binary => "application/octet-stream",
             ContentType::Cert => "application/x-x509-ca-cert",
+            ContentType::Csv => "text/csv",
             ContentType::Json => "application/json",
             ContentType::Rfc8181 => publication::CONTENT_TYPE,
             ContentType::Rfc6492 => provisioning::CONTENT_TYPE,
@@ -214,6 +219,15 @@ impl HttpResponse {
         }
     }
 
+    /// Returns a response for JSON which was already serialized.
+    pub fn json_bytes(body: Vec<u8>) -> Self {
+        Self::ok_response(ContentType::Json, body)
+    }
+
+    pub fn csv(body: Vec<u8>) -> Self {
+        Self::ok_response(ContentType::Csv, body)
+    }
+
     pub fn text(body: Vec<u8>) -> Self {
         Self::ok_response(ContentType::Text, body)
     }
@@ -256,6 +270,10 @@ impl HttpResponse {
         Self::ok_response(ContentType::Cert, body)
     }
 
+    pub fn binary(body: Vec<u8>) -> Self {
+        Self::ok_response(ContentType::Binary, body)
+    }
+
     pub fn html(content: &[u8]) -> Self {
         Self::ok_response(ContentType::Html, content.to_vec())
     }
@@ -395,6 +413,20 @@ impl Request {
         self.request.method()
     }
 
+    /// Returns all values for a query parameter, or an empty vec if the
+    /// parameter was not used.
+    pub fn query_values(&self, key: &str) -> Vec<String> {
+        urlparse(self.request.uri().to_string())
+            .get_parsed_query()
+            .and_then(|mut query| query.remove(key))
+            .unwrap_or_default()
+    }
+
+    /// Returns the first value for a query parameter, if present.
+    pub fn query_value(&self, key: &str) -> Option<String> {
+        self.query_values(key).into_iter().next()
+    }
+
     /// Returns whether the request is a GET request.
     pub fn is_get(&self) -> bool {
         self.request.method() == Method::GET
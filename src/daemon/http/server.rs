@@ -24,22 +24,24 @@ use hyper::{
     Method,
 };
 
-use tokio::select;
 use tokio::signal::unix::SignalKind;
+use tokio::{select, sync::watch};
 
 use rpki::{
     ca::{
         idexchange,
         idexchange::{CaHandle, ChildHandle, ParentHandle, PublisherHandle},
     },
+    crypto::KeyIdentifier,
     repository::resources::Asn,
 };
 
 use crate::{
     commons::{
         api::{
-            ApiRepositoryContact, AspaDefinitionUpdates, BgpStats, CommandHistoryCriteria, ParentCaReq, PublisherList,
-            RepositoryContact, RoaConfigurationUpdates, RtaName, Token,
+            ApiRepositoryContact, AspaDefinitionUpdates, BgpStats, CommandHistoryCriteria, ObjectName, ParentCaReq,
+            PublisherList, RepositoryContact, RoaConfigurationFilter, RoaConfigurationUpdates, RoaDraftName, RtaName,
+            Token, VrpExportFormat,
         },
         bgp::BgpAnalysisAdvice,
         error::Error,
@@ -60,6 +62,7 @@ use crate::{
             RoutingResult,
         },
         krillserver::KrillServer,
+        rtr::rtr_listener,
     },
     upgrades::{finalise_data_migration, post_start_upgrade, prepare_upgrade_data_migrations, UpgradeMode},
 };
@@ -161,6 +164,14 @@ pub async fn start_krill_daemon(config: Arc<Config>) -> Result<(), Error> {
     let scheduler = krill_server.build_scheduler();
     let scheduler_future = scheduler.run();
 
+    // Start the RTR server for lab use, if it is enabled. Unlike the http server and
+    // scheduler Krill can run without it, so it is not included in the select below.
+    // It is stopped when Krill stops.
+    let (rtr_shutdown, rtr_shutdown_signal) = watch::channel(false);
+    if let Some(rtr_cache) = krill_server.rtr_cache() {
+        tokio::spawn(rtr_listener(rtr_cache, rtr_shutdown_signal));
+    }
+
     // Start creating the server.
     let krill_server = Arc::new(krill_server);
 
@@ -198,6 +209,8 @@ pub async fn start_krill_daemon(config: Arc<Config>) -> Result<(), Error> {
         );
     }
 
+    let _ = rtr_shutdown.send(true);
+
     Err(Error::custom("stopping krill process"))
 }
 
@@ -334,6 +347,9 @@ async fn map_requests(req: hyper::Request<hyper::Body>, state: State) -> Result<
     if let Err(req) = res {
         res = rfc6492(req).await;
     }
+    if let Err(req) = res {
+        res = onboarding(req).await;
+    }
     if let Err(req) = res {
         res = statics(req).await;
     }
@@ -449,6 +465,7 @@ pub async fn metrics(req: Request) -> RoutingResult {
             announcements_invalid_length: HashMap<CaHandle, usize>,
             announcements_not_found: HashMap<CaHandle, usize>,
             roas_too_permissive: HashMap<CaHandle, usize>,
+            roas_max_length_exposed: HashMap<CaHandle, usize>,
             roas_redundant: HashMap<CaHandle, usize>,
             roas_stale: HashMap<CaHandle, usize>,
             roas_total: HashMap<CaHandle, usize>,
@@ -464,6 +481,8 @@ pub async fn metrics(req: Request) -> RoutingResult {
                 self.announcements_not_found
                     .insert(ca.clone(), stats.announcements_not_found);
                 self.roas_too_permissive.insert(ca.clone(), stats.roas_too_permissive);
+                self.roas_max_length_exposed
+                    .insert(ca.clone(), stats.roas_max_length_exposed);
                 self.roas_redundant.insert(ca.clone(), stats.roas_redundant);
                 self.roas_stale.insert(ca.clone(), stats.roas_stale);
                 self.roas_total.insert(ca.clone(), stats.roas_total);
@@ -740,6 +759,61 @@ pub async fn metrics(req: Request) -> RoutingResult {
                     }
                 }
 
+                {
+                    // Resources removed by parents, which were not yet cleared
+
+                    let mut shrink_reports = HashMap::new();
+                    for ca in cas_stats.keys() {
+                        if let Ok(report) = server.ca_resource_shrinks(ca).await {
+                            shrink_reports.insert(ca.clone(), report);
+                        }
+                    }
+
+                    res.push('\n');
+                    res.push_str("# HELP krill_ca_resources_shrunk number of times resources were removed by parents, and not yet cleared\n");
+                    res.push_str("# TYPE krill_ca_resources_shrunk gauge\n");
+                    for (ca, report) in shrink_reports.iter() {
+                        res.push_str(&format!(
+                            "krill_ca_resources_shrunk{{ca=\"{}\"}} {}\n",
+                            ca,
+                            report.shrinks().len()
+                        ));
+                    }
+
+                    res.push('\n');
+                    res.push_str("# HELP krill_ca_resources_shrunk_roas_not_held number of ROA configurations which are no longer held because resources were removed by parents\n");
+                    res.push_str("# TYPE krill_ca_resources_shrunk_roas_not_held gauge\n");
+                    for (ca, report) in shrink_reports.iter() {
+                        res.push_str(&format!(
+                            "krill_ca_resources_shrunk_roas_not_held{{ca=\"{}\"}} {}\n",
+                            ca,
+                            report.nr_roas_not_held()
+                        ));
+                    }
+
+                    res.push('\n');
+                    res.push_str("# HELP krill_ca_resources_shrunk_children number of children which lost resources because resources were removed by parents\n");
+                    res.push_str("# TYPE krill_ca_resources_shrunk_children gauge\n");
+                    for (ca, report) in shrink_reports.iter() {
+                        res.push_str(&format!(
+                            "krill_ca_resources_shrunk_children{{ca=\"{}\"}} {}\n",
+                            ca,
+                            report.nr_children_shrunk()
+                        ));
+                    }
+
+                    res.push('\n');
+                    res.push_str("# HELP krill_ca_resources_shrunk_announcements number of seen announcements which are no longer valid because resources were removed by parents\n");
+                    res.push_str("# TYPE krill_ca_resources_shrunk_announcements gauge\n");
+                    for (ca, report) in shrink_reports.iter() {
+                        res.push_str(&format!(
+                            "krill_ca_resources_shrunk_announcements{{ca=\"{}\"}} {}\n",
+                            ca,
+                            report.announcements().len()
+                        ));
+                    }
+                }
+
                 if !server.config.metrics.metrics_hide_roa_details {
                     // BGP Announcement metrics
 
@@ -750,6 +824,7 @@ pub async fn metrics(req: Request) -> RoutingResult {
                         announcements_invalid_length: HashMap::new(),
                         announcements_not_found: HashMap::new(),
                         roas_too_permissive: HashMap::new(),
+                        roas_max_length_exposed: HashMap::new(),
                         roas_redundant: HashMap::new(),
                         roas_stale: HashMap::new(),
                         roas_total: HashMap::new(),
@@ -803,6 +878,16 @@ pub async fn metrics(req: Request) -> RoutingResult {
                         res.push_str(&format!("krill_cas_bgp_roas_too_permissive{{ca=\"{}\"}} {}\n", ca, nr));
                     }
 
+                    res.push('\n');
+                    res.push_str("# HELP krill_cas_bgp_roas_max_length_exposed number of ROAs for this CA with a max length which allows unannounced more specifics (0 may also indicate that no BGP info is available)\n");
+                    res.push_str("# TYPE krill_cas_bgp_roas_max_length_exposed gauge\n");
+                    for (ca, nr) in all_bgp_stats.roas_max_length_exposed.iter() {
+                        res.push_str(&format!(
+                            "krill_cas_bgp_roas_max_length_exposed{{ca=\"{}\"}} {}\n",
+                            ca, nr
+                        ));
+                    }
+
                     res.push('\n');
                     res.push_str("# HELP krill_cas_bgp_roas_redundant number of ROAs for this CA which are redundant (0 may also indicate that no BGP info is available)\n");
                     res.push_str("# TYPE krill_cas_bgp_roas_redundant gauge\n");
@@ -927,6 +1012,7 @@ async fn ta(req: Request) -> RoutingResult {
             "/ta/ta.tal" => tal(req).await,
             "/testbed.tal" => tal(req).await,
             "/ta/ta.cer" => ta_cer(req).await,
+            path if path.starts_with("/ta/") => ta_key_file(req).await,
             _ => Err(req),
         },
         _ => Err(req),
@@ -947,6 +1033,39 @@ pub async fn ta_cer(req: Request) -> RoutingResult {
     }
 }
 
+/// Serves the certificate or TAL for a specific TA key, using the file
+/// names from the TALs of the TA. While the TA is rolling its key this
+/// includes the certificate for its new key, which is listed in the TAK
+/// objects published by the TA.
+async fn ta_key_file(req: Request) -> RoutingResult {
+    let path = req.path.full().to_string();
+
+    let all_keys = match req.state().ta_all_keys().await {
+        Ok(all_keys) => all_keys,
+        Err(_) => return Err(req),
+    };
+
+    for details in all_keys {
+        for uri in details.tal().uris() {
+            let uri = uri.to_string();
+            let cer = match uri.rfind('/') {
+                Some(idx) => &uri[idx..],
+                None => continue,
+            };
+
+            if path == format!("/ta{}", cer) {
+                return Ok(HttpResponse::cert(details.cert().to_bytes().to_vec()));
+            } else if let Some(stem) = cer.strip_suffix(".cer") {
+                if path == format!("/ta{}.tal", stem) {
+                    return Ok(HttpResponse::text(format!("{}", details.tal()).into_bytes()));
+                }
+            }
+        }
+    }
+
+    Err(req)
+}
+
 //------------ Provisioning (RFC6492) ----------------------------------------
 
 /// Process an RFC 6492 request
@@ -977,6 +1096,31 @@ pub async fn rfc6492(req: Request) -> RoutingResult {
     }
 }
 
+//------------ Child Onboarding ----------------------------------------------
+
+/// Lets a new child add itself under a CA using an onboarding token which
+/// was minted by the CA. The token is the credential here, so this does
+/// not require a logged in user.
+pub async fn onboarding(req: Request) -> RoutingResult {
+    if req.path().segment() == "onboarding" && req.is_post() {
+        let mut path = req.path().clone();
+        let ca = match path.path_arg() {
+            Some(ca) => ca,
+            None => return render_error(Error::ApiInvalidHandle),
+        };
+
+        let actor = req.actor();
+        let state = req.state().clone();
+
+        match req.json().await {
+            Ok(redeem) => render_json_res(state.ca_onboarding_token_redeem(&ca, redeem, &actor).await),
+            Err(e) => render_error(e),
+        }
+    } else {
+        Err(req)
+    }
+}
+
 /// Return various stats as json
 async fn stats(req: Request) -> RoutingResult {
     match *req.method() {
@@ -1117,6 +1261,8 @@ async fn api(req: Request) -> RoutingResult {
                         Some("bulk") => api_bulk(req, &mut path).await,
                         Some("cas") => api_cas(req, &mut path).await,
                         Some("pubd") => aa!(req, Permission::PUB_ADMIN, api_publication_server(req, &mut path).await),
+                        Some("ta") => aa!(req, Permission::CA_ADMIN, api_ta(req, &mut path).await),
+                        Some("verify") => api_verify(req).await,
                         _ => render_unknown_method(),
                     }
                 })
@@ -1164,17 +1310,24 @@ async fn api_cas(req: Request, path: &mut RequestPath) -> RoutingResult {
                 Some("aspas") => api_ca_aspas(req, path, ca).await,
                 Some("bgpsec") => api_ca_bgpsec(req, path, ca).await,
                 Some("children") => api_ca_children(req, path, ca).await,
+                Some("ghostbuster") => api_ca_ghostbuster(req, path, ca).await,
                 Some("history") => api_ca_history(req, path, ca).await,
 
                 Some("id") => api_ca_id(req, path, ca).await,
                 Some("issues") => api_ca_issues(req, ca).await,
                 Some("keys") => api_ca_keys(req, path, ca).await,
+                Some("objects") => api_ca_objects(req, path, ca).await,
+                Some("onboarding") => api_ca_onboarding(req, path, ca).await,
                 Some("parents") => api_ca_parents(req, path, ca).await,
+                Some("prefix_lists") => api_ca_prefix_lists(req, path, ca).await,
                 Some("repo") => api_ca_repo(req, path, ca).await,
                 Some("routes") => api_ca_routes(req, path, ca).await,
+                Some("shrinks") => api_ca_shrinks(req, ca).await,
                 Some("stats") => api_ca_stats(req, path, ca).await,
                 Some("sync") => api_ca_sync(req, path, ca).await,
 
+                Some("geofeed") => api_ca_geofeed(req, path, ca).await,
+                Some("rsc") => api_ca_rsc(req, path, ca).await,
                 Some("rta") => api_ca_rta(req, path, ca).await,
 
                 _ => render_unknown_method(),
@@ -1193,6 +1346,7 @@ async fn api_ca_keys(req: Request, path: &mut RequestPath, ca: CaHandle) -> Rout
         Method::POST => match path.next() {
             Some("roll_init") => api_ca_kr_init(req, ca).await,
             Some("roll_activate") => api_ca_kr_activate(req, ca).await,
+            Some("roll_finish") => api_ca_kr_finish(req, ca).await,
             _ => render_unknown_method(),
         },
         _ => render_unknown_method(),
@@ -1201,10 +1355,14 @@ async fn api_ca_keys(req: Request, path: &mut RequestPath, ca: CaHandle) -> Rout
 
 async fn api_ca_parents(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
     if let Some(parent) = path.path_arg() {
-        match *req.method() {
-            Method::GET => api_ca_my_parent_contact(req, ca, parent).await,
-            Method::POST => api_ca_parent_add_or_update(req, ca, Some(parent)).await,
-            Method::DELETE => api_ca_remove_parent(req, ca, parent).await,
+        match path.next() {
+            None => match *req.method() {
+                Method::GET => api_ca_my_parent_contact(req, ca, parent).await,
+                Method::POST => api_ca_parent_add_or_update(req, ca, Some(parent)).await,
+                Method::DELETE => api_ca_remove_parent(req, ca, parent).await,
+                _ => render_unknown_method(),
+            },
+            Some("limit") => api_ca_parent_resource_limit(req, ca, parent).await,
             _ => render_unknown_method(),
         }
     } else {
@@ -1224,6 +1382,17 @@ async fn api_ca_repo(req: Request, path: &mut RequestPath, ca: CaHandle) -> Rout
             _ => render_unknown_method(),
         },
         Some("status") => api_ca_repo_status(req, ca).await,
+        Some("mirrors") => match path.next() {
+            None => match *req.method() {
+                Method::POST => api_ca_repo_mirror_add(req, ca).await,
+                _ => render_unknown_method(),
+            },
+            Some("remove") => match *req.method() {
+                Method::POST => api_ca_repo_mirror_remove(req, ca).await,
+                _ => render_unknown_method(),
+            },
+            _ => render_unknown_method(),
+        },
         _ => render_unknown_method(),
     }
 }
@@ -1240,10 +1409,57 @@ async fn api_ca_routes(req: Request, path: &mut RequestPath, ca: CaHandle) -> Ro
             _ => render_unknown_method(),
         },
         Some("analysis") => api_ca_routes_analysis(req, path, ca).await,
+        Some("drafts") => api_ca_routes_drafts(req, path, ca).await,
+        Some("export") => match *req.method() {
+            Method::GET => api_ca_routes_export(req, ca).await,
+            _ => render_unknown_method(),
+        },
+        Some("import") => match path.next() {
+            Some("rpsl") => match *req.method() {
+                Method::POST => api_ca_routes_import_rpsl(req, ca).await,
+                _ => render_unknown_method(),
+            },
+            _ => render_unknown_method(),
+        },
+        Some("schedule") => match *req.method() {
+            Method::GET => api_ca_routes_schedule(req, ca).await,
+            _ => render_unknown_method(),
+        },
+        Some("as0") => match *req.method() {
+            Method::GET => api_ca_routes_as0_status(req, ca).await,
+            Method::POST => api_ca_routes_as0_policy(req, ca).await,
+            _ => render_unknown_method(),
+        },
         _ => render_unknown_method(),
     }
 }
 
+async fn api_ca_routes_drafts(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
+    match path.path_arg::<RoaDraftName>() {
+        None => match *req.method() {
+            Method::GET => api_ca_routes_drafts_list(req, ca).await,
+            _ => render_unknown_method(),
+        },
+        Some(name) => match path.next() {
+            None => match *req.method() {
+                Method::GET => api_ca_routes_draft_show(req, ca, name).await,
+                Method::POST => api_ca_routes_draft_update(req, ca, name).await,
+                Method::DELETE => api_ca_routes_draft_remove(req, ca, name).await,
+                _ => render_unknown_method(),
+            },
+            Some("dryrun") => match *req.method() {
+                Method::GET => api_ca_routes_draft_dry_run(req, ca, name).await,
+                _ => render_unknown_method(),
+            },
+            Some("commit") => match *req.method() {
+                Method::POST => api_ca_routes_draft_commit(req, ca, name).await,
+                _ => render_unknown_method(),
+            },
+            _ => render_unknown_method(),
+        },
+    }
+}
+
 async fn api_ca_stats(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
     match path.next() {
         Some("children") => match path.next() {
@@ -1287,6 +1503,10 @@ async fn api_publication_server(req: Request, path: &mut RequestPath) -> Routing
             Method::POST => render_empty_res(req.state().repository_session_reset()),
             _ => render_unknown_method(),
         },
+        Some("validate") => match *req.method() {
+            Method::POST => render_json_res(req.state().repository_validate().await),
+            _ => render_unknown_method(),
+        },
         _ => render_unknown_method(),
     }
 }
@@ -1482,6 +1702,54 @@ async fn api_all_ca_issues(req: Request) -> RoutingResult {
     }
 }
 
+/// Shows, or clears, the resources removed by parents of a CA.
+async fn api_ca_objects(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
+    // Handles /api/v1/cas/{ca}/objects:
+    //    GET /api/v1/cas/{ca}/objects               -> Show all published objects, decoded
+    //    GET /api/v1/cas/{ca}/objects/{key}/{name}  -> Download a published object in DER form
+    match *req.method() {
+        Method::GET => match path.path_arg::<KeyIdentifier>() {
+            None => aa!(
+                req,
+                Permission::CA_READ,
+                Handle::from(&ca),
+                render_json_res(req.state().ca_published_objects(&ca).await)
+            ),
+            Some(key) => match path.next().map(ObjectName::from) {
+                Some(name) => aa!(req, Permission::CA_READ, Handle::from(&ca), {
+                    match req.state().ca_published_object_der(&ca, &key, &name).await {
+                        Ok(bytes) => Ok(HttpResponse::binary(bytes.to_vec())),
+                        Err(e) => render_error(e),
+                    }
+                }),
+                None => render_unknown_method(),
+            },
+        },
+        _ => render_unknown_method(),
+    }
+}
+
+async fn api_ca_shrinks(req: Request, ca: CaHandle) -> RoutingResult {
+    match *req.method() {
+        Method::GET => aa!(
+            req,
+            Permission::CA_READ,
+            Handle::from(&ca),
+            render_json_res(req.state().ca_resource_shrinks(&ca).await)
+        ),
+        Method::DELETE => {
+            let actor = req.actor();
+            aa!(
+                req,
+                Permission::CA_UPDATE,
+                Handle::from(&ca),
+                render_empty_res(req.state().ca_resource_shrinks_clear(&ca, &actor).await)
+            )
+        }
+        _ => render_unknown_method(),
+    }
+}
+
 /// Returns the health (state) for a given CA.
 async fn api_ca_issues(req: Request, ca: CaHandle) -> RoutingResult {
     match *req.method() {
@@ -1558,6 +1826,39 @@ async fn api_ca_my_parent_contact(req: Request, ca: CaHandle, parent: ParentHand
     )
 }
 
+async fn api_ca_parent_resource_limit(req: Request, ca: CaHandle, parent: ParentHandle) -> RoutingResult {
+    // Handles /api/v1/cas/{ca}/parents/{parent}/limit:
+    //    GET    -> Show the limit on resources requested from the parent
+    //    POST   -> Set the limit on resources requested from the parent
+    //    DELETE -> Remove the limit, i.e. request all entitled resources
+    match *req.method() {
+        Method::GET => aa!(req, Permission::CA_READ, Handle::from(&ca), {
+            render_json_res(req.state().ca_parent_resource_limit(&ca, &parent).await)
+        }),
+        Method::POST => aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+            let actor = req.actor();
+            let server = req.state().clone();
+            match req.json().await {
+                Ok(limit) => render_empty_res(
+                    server
+                        .ca_parent_update_resource_limit(ca, parent, Some(limit), &actor)
+                        .await,
+                ),
+                Err(e) => render_error(e),
+            }
+        }),
+        Method::DELETE => aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+            let actor = req.actor();
+            render_empty_res(
+                req.state()
+                    .ca_parent_update_resource_limit(ca, parent, None, &actor)
+                    .await,
+            )
+        }),
+        _ => render_unknown_method(),
+    }
+}
+
 async fn api_ca_my_parent_statuses(req: Request, ca: CaHandle) -> RoutingResult {
     aa!(
         req,
@@ -1574,6 +1875,11 @@ async fn api_ca_aspas(req: Request, path: &mut RequestPath, ca: CaHandle) -> Rou
             Method::POST => api_ca_aspas_definitions_update(req, ca).await,
             _ => render_unknown_method(),
         },
+        Some("analysis") => api_ca_aspas_analysis(req, path, ca).await,
+        Some("import") => match *req.method() {
+            Method::POST => api_ca_aspas_import(req, ca).await,
+            _ => render_unknown_method(),
+        },
         // We may need other functions in future, such as 'analyze' or 'try'.
         // So keep the base namespace clean and use '/api/v1/aspas/as/<asn>/..'
         // for functions on specific ASPA definitions for the given (customer)
@@ -1600,12 +1906,17 @@ async fn api_ca_bgpsec(req: Request, path: &mut RequestPath, ca: CaHandle) -> Ro
     // Handles /api/v1/cas/{ca}/bgpsec/:
     //    GET  /api/v1/cas/{ca}/bgpsec/ -> List BGPSec Definitions
     //    POST /api/v1/cas/{ca}/bgpsec/ -> Send BgpSecDefinitionUpdates
+    //    POST /api/v1/cas/{ca}/bgpsec/router_key -> Generate a router key and certificate
     match path.next() {
         None => match *req.method() {
             Method::GET => api_ca_bgpsec_definitions_show(req, ca).await,
             Method::POST => api_ca_bgpsec_definitions_update(req, ca).await,
             _ => render_unknown_method(),
         },
+        Some("router_key") => match *req.method() {
+            Method::POST => api_ca_bgpsec_router_key_create(req, ca).await,
+            _ => render_unknown_method(),
+        },
         _ => render_unknown_method(),
     }
 }
@@ -1627,6 +1938,113 @@ async fn api_ca_bgpsec_definitions_update(req: Request, ca: CaHandle) -> Routing
     })
 }
 
+async fn api_ca_bgpsec_router_key_create(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::BGPSEC_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        let server = req.state().clone();
+        match req.json().await {
+            Ok(request) => render_json_res(server.ca_bgpsec_router_key_create(ca, request, &actor).await),
+            Err(e) => render_error(e),
+        }
+    })
+}
+
+async fn api_ca_prefix_lists(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
+    // Handles /api/v1/cas/{ca}/prefix_lists:
+    //    GET  /api/v1/cas/{ca}/prefix_lists -> List Signed Prefix List definitions
+    //    POST /api/v1/cas/{ca}/prefix_lists -> Send PrefixListDefinitionUpdates
+    match path.next() {
+        None => match *req.method() {
+            Method::GET => aa!(req, Permission::PREFIX_LISTS_READ, Handle::from(&ca), {
+                render_json_res(req.state().ca_prefix_lists_show(ca).await)
+            }),
+            Method::POST => aa!(req, Permission::PREFIX_LISTS_UPDATE, Handle::from(&ca), {
+                let actor = req.actor();
+                let server = req.state().clone();
+                match req.json().await {
+                    Ok(updates) => render_empty_res(server.ca_prefix_lists_update(ca, updates, &actor).await),
+                    Err(e) => render_error(e),
+                }
+            }),
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
+async fn api_ca_ghostbuster(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
+    // Handles /api/v1/cas/{ca}/ghostbuster:
+    //    GET    /api/v1/cas/{ca}/ghostbuster -> Show the Ghostbuster contact
+    //    POST   /api/v1/cas/{ca}/ghostbuster -> Set the Ghostbuster contact
+    //    DELETE /api/v1/cas/{ca}/ghostbuster -> Remove the Ghostbuster contact
+    match path.next() {
+        None => match *req.method() {
+            Method::GET => aa!(req, Permission::CA_READ, Handle::from(&ca), {
+                render_json_res(req.state().ca_ghostbuster_show(ca).await)
+            }),
+            Method::POST => aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+                let actor = req.actor();
+                let server = req.state().clone();
+                match req.json().await {
+                    Ok(contact) => render_empty_res(server.ca_ghostbuster_update(ca, Some(contact), &actor).await),
+                    Err(e) => render_error(e),
+                }
+            }),
+            Method::DELETE => aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+                let actor = req.actor();
+                render_empty_res(req.state().ca_ghostbuster_update(ca, None, &actor).await)
+            }),
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
+async fn api_ca_onboarding(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
+    // Handles /api/v1/cas/{ca}/onboarding:
+    //    GET    /api/v1/cas/{ca}/onboarding/tokens      -> Show unused onboarding tokens
+    //    POST   /api/v1/cas/{ca}/onboarding/tokens      -> Add an onboarding token for a new child
+    //    DELETE /api/v1/cas/{ca}/onboarding/tokens/{id} -> Remove an unused onboarding token
+    //    POST   /api/v1/cas/{ca}/onboarding/redeem      -> Use a token minted by a parent to add it
+    match path.next() {
+        Some("tokens") => match path.path_arg() {
+            None => match *req.method() {
+                Method::GET => aa!(req, Permission::CA_READ, Handle::from(&ca), {
+                    render_json_res(req.state().ca_onboarding_tokens(&ca).await)
+                }),
+                Method::POST => aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+                    let actor = req.actor();
+                    let server = req.state().clone();
+                    match req.json().await {
+                        Ok(token_req) => render_json_res(server.ca_onboarding_token_add(&ca, token_req, &actor).await),
+                        Err(e) => render_error(e),
+                    }
+                }),
+                _ => render_unknown_method(),
+            },
+            Some(id) => match *req.method() {
+                Method::DELETE => aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+                    let actor = req.actor();
+                    render_empty_res(req.state().ca_onboarding_token_remove(&ca, id, &actor).await)
+                }),
+                _ => render_unknown_method(),
+            },
+        },
+        Some("redeem") => match *req.method() {
+            Method::POST => aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+                let actor = req.actor();
+                let server = req.state().clone();
+                match req.json().await {
+                    Ok(token) => render_empty_res(server.ca_parent_onboard(ca, token, &actor).await),
+                    Err(e) => render_error(e),
+                }
+            }),
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
 async fn api_ca_children(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
     match path.path_arg() {
         Some(child) => match path.next() {
@@ -1828,6 +2246,34 @@ async fn api_ca_repo_update(req: Request, ca: CaHandle) -> RoutingResult {
     })
 }
 
+async fn api_ca_repo_mirror_add(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        let server = req.state().clone();
+
+        match req
+            .api_bytes()
+            .await
+            .map(|bytes| extract_repository_contact(&ca, bytes))
+        {
+            Ok(Ok(contact)) => render_empty_res(server.ca_repo_mirror_add(ca, contact, &actor).await),
+            Ok(Err(e)) | Err(e) => render_error(e),
+        }
+    })
+}
+
+async fn api_ca_repo_mirror_remove(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        let server = req.state().clone();
+
+        match req.json().await {
+            Ok(service_uri) => render_empty_res(server.ca_repo_mirror_remove(ca, service_uri, &actor).await),
+            Err(e) => render_error(e),
+        }
+    })
+}
+
 async fn api_ca_parent_add_or_update(
     req: Request,
     ca: CaHandle,
@@ -1910,8 +2356,46 @@ async fn api_ca_kr_activate(req: Request, ca: CaHandle) -> RoutingResult {
     })
 }
 
+async fn api_ca_kr_finish(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::CA_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        render_empty_res(req.state().ca_keyroll_finish(ca, &actor).await)
+    })
+}
+
 // -- ASPA functions
 
+/// Compare ASPA definitions with seen AS paths, or suggest definitions
+async fn api_ca_aspas_analysis(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::ASPAS_ANALYSIS, Handle::from(&ca), {
+        match path.next() {
+            Some("full") => match *req.method() {
+                Method::GET => render_json_res(req.state().ca_aspas_analysis(ca).await),
+                _ => render_unknown_method(),
+            },
+            Some("suggest") => match *req.method() {
+                Method::GET => render_json_res(req.state().ca_aspas_suggest(ca).await),
+                _ => render_unknown_method(),
+            },
+            _ => render_unknown_method(),
+        }
+    })
+}
+
+/// Import AS relationships to derive ASPA definitions. Depending on the
+/// action in the request the changes are only reported, or applied.
+async fn api_ca_aspas_import(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::ASPAS_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        let state = req.state().clone();
+
+        match req.json().await {
+            Err(e) => render_error(e),
+            Ok(request) => render_json_res(state.ca_aspas_import(ca, request, &actor).await),
+        }
+    })
+}
+
 /// List the current ASPA definitions for a CA
 async fn api_ca_aspas_definitions_show(req: Request, ca: CaHandle) -> RoutingResult {
     aa!(req, Permission::ASPAS_READ, Handle::from(&ca), {
@@ -2011,13 +2495,86 @@ async fn api_ca_routes_try_update(req: Request, ca: CaHandle) -> RoutingResult {
 /// show the route authorizations for this CA
 async fn api_ca_routes_show(req: Request, ca: CaHandle) -> RoutingResult {
     aa!(req, Permission::ROUTES_READ, Handle::from(&ca), {
-        match req.state().ca_routes_show(&ca).await {
+        let filter = match roa_configuration_filter(&req) {
+            Ok(filter) => filter,
+            Err(e) => return render_error(e),
+        };
+
+        match req.state().ca_routes_show(&ca, &filter).await {
             Ok(roas) => render_json(roas),
             Err(_) => render_unknown_resource(),
         }
     })
 }
 
+/// Show the upcoming activations and expirations of time-bounded ROAs
+async fn api_ca_routes_schedule(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::ROUTES_READ, Handle::from(&ca), {
+        render_json_res(req.state().ca_routes_timed_changes(&ca).await)
+    })
+}
+
+/// Show whether AS0 ROAs are issued for unrouted space, and for which prefixes
+async fn api_ca_routes_as0_status(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::ROUTES_READ, Handle::from(&ca), {
+        render_json_res(req.state().ca_routes_as0_status(&ca).await)
+    })
+}
+
+/// Enable or disable AS0 ROAs for unrouted space
+async fn api_ca_routes_as0_policy(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::ROUTES_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        let state = req.state().clone();
+
+        match req.json().await {
+            Err(e) => render_error(e),
+            Ok(policy) => render_empty_res(state.ca_routes_as0_policy(ca, policy, &actor).await),
+        }
+    })
+}
+
+/// Export the effective VRPs for the configured ROAs in the format given
+/// in the query, e.g.: ?format=slurm. Defaults to JSON.
+async fn api_ca_routes_export(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::ROUTES_READ, Handle::from(&ca), {
+        let format = match req.query_value("format") {
+            None => VrpExportFormat::default(),
+            Some(format) => match VrpExportFormat::from_str(&format) {
+                Ok(format) => format,
+                Err(e) => return render_error(Error::ApiInvalidQuery(e)),
+            },
+        };
+
+        match req.state().ca_routes_export(&ca, format).await {
+            Err(e) => render_error(e),
+            Ok(export) => {
+                let body = export.into_bytes();
+                Ok(match format {
+                    VrpExportFormat::Slurm | VrpExportFormat::Json => HttpResponse::json_bytes(body),
+                    VrpExportFormat::Csv => HttpResponse::csv(body),
+                    VrpExportFormat::Rpsl => HttpResponse::text(body),
+                })
+            }
+        }
+    })
+}
+
+/// Parses the optional query parameters used to select ROA configurations
+/// by their metadata, e.g.: ?owner=noc&tag=service=cdn&tag=reviewed
+fn roa_configuration_filter(req: &Request) -> Result<RoaConfigurationFilter, Error> {
+    let mut filter = RoaConfigurationFilter::default();
+    filter.set_owner(req.query_value("owner"));
+    filter.set_ticket(req.query_value("ticket"));
+    filter.set_customer(req.query_value("customer"));
+    for tag in req.query_values("tag") {
+        filter
+            .add_tag_str(&tag)
+            .map_err(|e| Error::ApiInvalidQuery(e.to_string()))?;
+    }
+    Ok(filter)
+}
+
 /// Show the state of ROAs vs BGP for this CA
 async fn api_ca_routes_analysis(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
     aa!(req, Permission::ROUTES_ANALYSIS, Handle::from(&ca), {
@@ -2049,6 +2606,150 @@ async fn api_ca_routes_analysis(req: Request, path: &mut RequestPath, ca: CaHand
     })
 }
 
+/// Import RPSL route and route6 objects as ROA configurations. Depending
+/// on the action in the request the updates are only reported, applied or
+/// staged in a ROA draft.
+async fn api_ca_routes_import_rpsl(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::ROUTES_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        let state = req.state().clone();
+
+        match req.json().await {
+            Err(e) => render_error(e),
+            Ok(request) => render_json_res(state.ca_routes_import_rpsl(ca, request, &actor).await),
+        }
+    })
+}
+
+// -- ROA drafts
+
+/// List the staged ROA drafts for this CA
+async fn api_ca_routes_drafts_list(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(
+        req,
+        Permission::ROUTES_READ,
+        Handle::from(&ca),
+        render_json_res(req.state().ca_routes_drafts(&ca).await)
+    )
+}
+
+/// Show a staged ROA draft for this CA
+async fn api_ca_routes_draft_show(req: Request, ca: CaHandle, name: RoaDraftName) -> RoutingResult {
+    aa!(
+        req,
+        Permission::ROUTES_READ,
+        Handle::from(&ca),
+        render_json_res(req.state().ca_routes_draft_show(&ca, &name).await)
+    )
+}
+
+/// Merge the updates in the POST into a ROA draft, creating it if needed
+async fn api_ca_routes_draft_update(req: Request, ca: CaHandle, name: RoaDraftName) -> RoutingResult {
+    aa!(req, Permission::ROUTES_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        let state = req.state().clone();
+
+        match req.json().await {
+            Err(e) => render_error(e),
+            Ok(updates) => render_empty_res(state.ca_routes_draft_update(ca, name, updates, &actor).await),
+        }
+    })
+}
+
+/// Discard a ROA draft
+async fn api_ca_routes_draft_remove(req: Request, ca: CaHandle, name: RoaDraftName) -> RoutingResult {
+    aa!(req, Permission::ROUTES_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        render_empty_res(req.state().ca_routes_draft_remove(ca, name, &actor).await)
+    })
+}
+
+/// Show the BGP analysis of the effect of all updates in a ROA draft
+async fn api_ca_routes_draft_dry_run(req: Request, ca: CaHandle, name: RoaDraftName) -> RoutingResult {
+    aa!(
+        req,
+        Permission::ROUTES_ANALYSIS,
+        Handle::from(&ca),
+        render_json_res(req.state().ca_routes_draft_bgp_dry_run(&ca, &name).await)
+    )
+}
+
+/// Apply all updates in a ROA draft as a single update
+async fn api_ca_routes_draft_commit(req: Request, ca: CaHandle, name: RoaDraftName) -> RoutingResult {
+    aa!(req, Permission::ROUTES_UPDATE, Handle::from(&ca), {
+        let actor = req.actor();
+        render_empty_res(req.state().ca_routes_draft_commit(ca, name, &actor).await)
+    })
+}
+
+//------------ Admin: Offline Trust Anchor proxy -----------------------------
+
+async fn api_ta(req: Request, path: &mut RequestPath) -> RoutingResult {
+    match path.next() {
+        Some("proxy") => api_ta_proxy(req, path).await,
+        _ => render_unknown_method(),
+    }
+}
+
+async fn api_ta_proxy(req: Request, path: &mut RequestPath) -> RoutingResult {
+    match path.next() {
+        Some("init") => match *req.method() {
+            Method::POST => {
+                let actor = req.actor();
+                render_empty_res(req.state().ta_proxy_init(&actor))
+            }
+            _ => render_unknown_method(),
+        },
+        Some("id") => match *req.method() {
+            Method::GET => render_json_res(req.state().ta_proxy_info()),
+            _ => render_unknown_method(),
+        },
+        Some("status") => match *req.method() {
+            Method::GET => render_json_res(req.state().ta_proxy_status()),
+            _ => render_unknown_method(),
+        },
+        Some("children") => match *req.method() {
+            Method::POST => {
+                let server = req.state().clone();
+                match req.json().await {
+                    Ok(child_req) => render_json_res(server.ta_proxy_children_add(child_req)),
+                    Err(e) => render_error(e),
+                }
+            }
+            _ => render_unknown_method(),
+        },
+        Some("signer") => match path.next() {
+            Some("add") => match *req.method() {
+                Method::POST => {
+                    let server = req.state().clone();
+                    match req.json().await {
+                        Ok(signer_info) => render_empty_res(server.ta_proxy_signer_add(signer_info)),
+                        Err(e) => render_error(e),
+                    }
+                }
+                _ => render_unknown_method(),
+            },
+            Some("request") => match *req.method() {
+                Method::GET => render_json_res(req.state().ta_proxy_signer_show_request()),
+                Method::POST => render_empty_res(req.state().ta_proxy_signer_make_request().map(|_| ())),
+                _ => render_unknown_method(),
+            },
+            Some("response") => match *req.method() {
+                Method::POST => {
+                    let server = req.state().clone();
+                    match req.json().await {
+                        Ok(response) => render_empty_res(server.ta_proxy_signer_process_response(response).await),
+                        Err(e) => render_error(e),
+                    }
+                }
+                _ => render_unknown_method(),
+            },
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
 //------------ Admin: Force republish ----------------------------------------
 
 async fn api_republish_all(req: Request, force: bool) -> RoutingResult {
@@ -2114,6 +2815,72 @@ async fn rrdp(req: Request) -> RoutingResult {
     }
 }
 
+//------------ Support Resource Signed Checklists (RSC) ------------------------
+
+async fn api_ca_rsc(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
+    // Handles /api/v1/cas/{ca}/rsc/:
+    //    POST /api/v1/cas/{ca}/rsc/sign -> Sign an RSC and return it
+    match path.next() {
+        Some("sign") => match *req.method() {
+            Method::POST => api_ca_rsc_sign(req, ca).await,
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
+async fn api_ca_rsc_sign(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::RSC_SIGN, Handle::from(&ca), {
+        let state = req.state().clone();
+
+        match req.json().await {
+            Err(e) => render_error(e),
+            Ok(request) => render_json_res(state.rsc_sign(ca, request).await),
+        }
+    })
+}
+
+//------------ Verify RTAs and RSCs -------------------------------------------
+
+async fn api_verify(req: Request) -> RoutingResult {
+    match *req.method() {
+        Method::POST => aa!(req, Permission::CA_READ, {
+            let state = req.state().clone();
+
+            match req.json().await {
+                Err(e) => render_error(e),
+                Ok(request) => render_json_res(state.verify_signed_object(request).await),
+            }
+        }),
+        _ => render_unknown_method(),
+    }
+}
+
+//------------ Support Signed Geofeeds -----------------------------------------
+
+async fn api_ca_geofeed(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
+    // Handles /api/v1/cas/{ca}/geofeed/:
+    //    POST /api/v1/cas/{ca}/geofeed/sign -> Sign a geofeed CSV and return it
+    match path.next() {
+        Some("sign") => match *req.method() {
+            Method::POST => api_ca_geofeed_sign(req, ca).await,
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
+async fn api_ca_geofeed_sign(req: Request, ca: CaHandle) -> RoutingResult {
+    aa!(req, Permission::GEOFEED_SIGN, Handle::from(&ca), {
+        let state = req.state().clone();
+
+        match req.json().await {
+            Err(e) => render_error(e),
+            Ok(request) => render_json_res(state.geofeed_sign(ca, request).await),
+        }
+    })
+}
+
 //------------ Support Resource Tagged Attestations (RTA) ----------------------
 
 async fn api_ca_rta(req: Request, path: &mut RequestPath, ca: CaHandle) -> RoutingResult {
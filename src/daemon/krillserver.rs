@@ -9,8 +9,9 @@ use futures::future::join_all;
 use rpki::{
     ca::{
         idexchange,
-        idexchange::{CaHandle, ChildHandle, ParentHandle, PublisherHandle},
+        idexchange::{CaHandle, ChildHandle, ParentHandle, PublisherHandle, ServiceUri},
     },
+    crypto::KeyIdentifier,
     repository::resources::ResourceSet,
     uri,
 };
@@ -20,14 +21,22 @@ use crate::{
         actor::{Actor, ActorDef},
         api::{
             AddChildRequest, AllCertAuthIssues, AspaCustomer, AspaDefinitionList, AspaDefinitionUpdates,
-            AspaProvidersUpdate, BgpSecCsrInfoList, BgpSecDefinitionUpdates, CaCommandDetails, CaRepoDetails,
-            CertAuthInfo, CertAuthInit, CertAuthIssues, CertAuthList, CertAuthStats, ChildCaInfo,
-            ChildrenConnectionStats, CommandHistory, CommandHistoryCriteria, ConfiguredRoa, ParentCaContact,
-            ParentCaReq, PublicationServerUris, PublisherDetails, ReceivedCert, RepositoryContact, RoaConfiguration,
-            RoaConfigurationUpdates, RoaPayload, RtaList, RtaName, RtaPrepResponse, ServerInfo, TaCertDetails,
-            Timestamp, UpdateChildRequest,
+            AspaImportAction, AspaImportReport, AspaImportRequest, AspaProvidersUpdate, BgpSecCsrInfoList,
+            BgpSecDefinitionUpdates, BgpSecRouterKey, BgpSecRouterKeyRequest, CaCommandDetails, CaPublishedObjects,
+            CaRepoDetails, CertAuthInfo, CertAuthInit, CertAuthIssues, CertAuthList, CertAuthStats, ChildCaInfo,
+            ChildrenConnectionStats, CommandHistory, CommandHistoryCriteria, ConfiguredRoa, GhostbusterContact,
+            ObjectName, OnboardingToken, OnboardingTokenId, OnboardingTokenList, OnboardingTokenRedeem,
+            OnboardingTokenRequest, ParentCaContact, ParentCaReq, PrefixListDefinitionList,
+            PrefixListDefinitionUpdates, PublicationServerUris, PublisherDetails, ReceivedCert, RepositoryContact,
+            RepositoryValidationReport, RoaAs0Policy, RoaAs0Status, RoaConfiguration, RoaConfigurationFilter,
+            RoaConfigurationUpdates, RoaDraft, RoaDraftList, RoaDraftName, RoaPayload, RpslImportAction,
+            RpslImportReport, RpslImportRequest, RtaList, RtaName, RtaPrepResponse, ServerInfo, TaCertDetails,
+            TimedRoaChanges, Timestamp, UpdateChildRequest, VrpExport, VrpExportFormat,
+        },
+        bgp::{
+            AspaAnalysisReport, AspaSuggestion, BgpAnalyser, BgpAnalysisReport, BgpAnalysisSuggestion,
+            ResourceShrinkReport,
         },
-        bgp::{BgpAnalyser, BgpAnalysisReport, BgpAnalysisSuggestion},
         crypto::KrillSignerBuilder,
         error::Error,
         eventsourcing::CommandKey,
@@ -37,15 +46,18 @@ use crate::{
     daemon::{
         auth::{providers::AdminTokenAuthProvider, Authorizer, LoggedInUser},
         ca::{
-            self, ta_handle, testbed_ca_handle, CaStatus, ResourceTaggedAttestation, RtaContentRequest,
-            RtaPrepareRequest,
+            self, ta_handle, testbed_ca_handle, CaStatus, GeofeedSignRequest, ResourceSignedChecklist,
+            ResourceTaggedAttestation, RscRequest, RtaContentRequest, RtaPrepareRequest, SignedGeofeed,
+            SignedObjectVerification, SignedObjectVerifyRequest,
         },
         config::{AuthType, Config},
         http::HttpResponse,
         mq::TaskQueue,
+        rtr::RtrCache,
         scheduler::Scheduler,
     },
     pubd::{RepoStats, RepositoryManager},
+    ta::{TrustAnchorProxyInfo, TrustAnchorProxyStatus, TrustAnchorSignedMessage, TrustAnchorSignerInfo},
 };
 
 #[cfg(feature = "multi-user")]
@@ -163,8 +175,23 @@ impl KrillServer {
         // this should be removed in future after people have had a chance to separate.
         let repo_manager = Arc::new(RepositoryManager::build(config.clone(), mq.clone(), signer.clone())?);
 
-        let ca_manager =
-            Arc::new(ca::CaManager::build(config.clone(), mq.clone(), signer, system_actor.clone()).await?);
+        let bgp_analyser = Arc::new(BgpAnalyser::new(
+            config.bgp_risdumps_enabled,
+            &config.bgp_risdumps_v4_uri,
+            &config.bgp_risdumps_v6_uri,
+            config.bgp_aspa_paths_uri.as_deref(),
+        ));
+
+        let ca_manager = Arc::new(
+            ca::CaManager::build(
+                config.clone(),
+                mq.clone(),
+                signer,
+                bgp_analyser.clone(),
+                system_actor.clone(),
+            )
+            .await?,
+        );
 
         if let Some(testbed) = config.testbed() {
             let uris = testbed.publication_server_uris();
@@ -274,12 +301,6 @@ impl KrillServer {
             join_all(setup_benchmark_ca_fns).await;
         }
 
-        let bgp_analyser = Arc::new(BgpAnalyser::new(
-            config.bgp_risdumps_enabled,
-            &config.bgp_risdumps_v4_uri,
-            &config.bgp_risdumps_v6_uri,
-        ));
-
         mq.server_started();
 
         Ok(KrillServer {
@@ -298,6 +319,11 @@ impl KrillServer {
         })
     }
 
+    /// Returns the cache for the lab RTR server, if it is enabled.
+    pub fn rtr_cache(&self) -> Option<Arc<RtrCache>> {
+        self.ca_manager.rtr_cache()
+    }
+
     pub fn build_scheduler(&self) -> Scheduler {
         Scheduler::build(
             self.mq.clone(),
@@ -541,6 +567,10 @@ impl KrillServer {
 ///
 impl KrillServer {
     pub async fn ta(&self) -> KrillResult<TaCertDetails> {
+        if let Some(details) = self.ca_manager.ta_proxy_cert_details()? {
+            return Ok(details);
+        }
+
         let ta_handle = ta_handle();
         let ta = self.ca_manager.get_ca(&ta_handle).await?;
 
@@ -553,8 +583,33 @@ impl KrillServer {
         }
     }
 
+    /// Returns the TA certificate and TAL for all keys of the TA, i.e. its
+    /// current key and, while it is rolling its key, the new key or the old
+    /// key. The oldest key comes first.
+    pub async fn ta_all_keys(&self) -> KrillResult<Vec<TaCertDetails>> {
+        if let Some(details) = self.ca_manager.ta_proxy_cert_details()? {
+            return Ok(vec![details]);
+        }
+
+        let ta = self.ca_manager.get_ca(&ta_handle()).await?;
+
+        let mut res = vec![];
+        if let Some(old) = ta.ta_old_key() {
+            res.push(old.clone());
+        }
+        res.push(ta.ta_cert_details()?.clone());
+        if let Some(next) = ta.ta_next_key() {
+            res.push(next.clone());
+        }
+        Ok(res)
+    }
+
+    /// Returns the certificate for the oldest key of the TA. The old key
+    /// stays available at the URI in its TAL until its key roll is finished,
+    /// even though the TAL served by Krill is for the new key.
     pub async fn trust_anchor_cert(&self) -> Option<ReceivedCert> {
-        self.ta().await.ok().map(|details| details.cert().clone())
+        let all_keys = self.ta_all_keys().await.ok()?;
+        all_keys.first().map(|details| details.cert().clone())
     }
 
     /// Adds a child to a CA and returns the ParentCaInfo that the child
@@ -612,6 +667,47 @@ impl KrillServer {
             .await
             .map(|status| status.get_children_connection_stats())
     }
+
+    /// Shows the unused onboarding tokens of the CA.
+    pub async fn ca_onboarding_tokens(&self, ca: &CaHandle) -> KrillResult<OnboardingTokenList> {
+        self.ca_manager.ca_onboarding_tokens(ca).await
+    }
+
+    /// Adds an onboarding token which lets a new child add itself under
+    /// the CA.
+    pub async fn ca_onboarding_token_add(
+        &self,
+        ca: &CaHandle,
+        req: OnboardingTokenRequest,
+        actor: &Actor,
+    ) -> KrillResult<OnboardingToken> {
+        self.ca_manager
+            .ca_onboarding_token_add(ca, req, &self.service_uri, actor)
+            .await
+    }
+
+    /// Removes an unused onboarding token.
+    pub async fn ca_onboarding_token_remove(
+        &self,
+        ca: &CaHandle,
+        id: OnboardingTokenId,
+        actor: &Actor,
+    ) -> KrillEmptyResult {
+        self.ca_manager.ca_onboarding_token_remove(ca, id, actor).await
+    }
+
+    /// Adds a new child using an onboarding token and returns the parent
+    /// response that it needs to add this CA as its parent.
+    pub async fn ca_onboarding_token_redeem(
+        &self,
+        ca: &CaHandle,
+        redeem: OnboardingTokenRedeem,
+        actor: &Actor,
+    ) -> KrillResult<idexchange::ParentResponse> {
+        self.ca_manager
+            .ca_onboarding_token_redeem(ca, redeem, &self.service_uri, actor)
+            .await
+    }
 }
 
 /// # Being a child
@@ -642,6 +738,14 @@ impl KrillServer {
         self.ca_manager.ca_parent_add_or_update(ca, parent_req, actor).await
     }
 
+    /// Uses an onboarding token minted by a parent to become a child of
+    /// that parent, and then adds the parent using its response.
+    pub async fn ca_parent_onboard(&self, ca: CaHandle, token: OnboardingToken, actor: &Actor) -> KrillEmptyResult {
+        let response = self.ca_manager.ca_onboarding_token_use(&ca, &token).await?;
+        let parent_req = ParentCaReq::new(token.parent().clone(), response);
+        self.ca_parent_add_or_update(ca, parent_req, actor).await
+    }
+
     pub async fn ca_parent_remove(&self, handle: CaHandle, parent: ParentHandle, actor: &Actor) -> KrillEmptyResult {
         self.ca_manager.ca_parent_remove(handle, parent, actor).await
     }
@@ -649,6 +753,44 @@ impl KrillServer {
     pub async fn ca_parent_revoke(&self, handle: &CaHandle, parent: &ParentHandle) -> KrillEmptyResult {
         self.ca_manager.ca_parent_revoke(handle, parent).await
     }
+
+    pub async fn ca_parent_resource_limit(
+        &self,
+        handle: &CaHandle,
+        parent: &ParentHandle,
+    ) -> KrillResult<Option<ResourceSet>> {
+        self.ca_manager.ca_parent_resource_limit(handle, parent).await
+    }
+
+    pub async fn ca_parent_update_resource_limit(
+        &self,
+        handle: CaHandle,
+        parent: ParentHandle,
+        limit: Option<ResourceSet>,
+        actor: &Actor,
+    ) -> KrillEmptyResult {
+        self.ca_manager
+            .ca_parent_update_resource_limit(handle, parent, limit, actor)
+            .await
+    }
+
+    /// Returns the resources removed by parents which were not yet cleared,
+    /// and the announcements which are no longer valid as a result.
+    pub async fn ca_resource_shrinks(&self, handle: &CaHandle) -> KrillResult<ResourceShrinkReport> {
+        let ca = self.ca_manager.get_ca(handle).await?;
+        Ok(self
+            .bgp_analyser
+            .resources_shrunk(
+                ca.configured_roas().as_slice(),
+                &ca.all_resources(),
+                ca.resource_shrinks().clone(),
+            )
+            .await)
+    }
+
+    pub async fn ca_resource_shrinks_clear(&self, handle: &CaHandle, actor: &Actor) -> KrillEmptyResult {
+        self.ca_manager.ca_resource_shrinks_clear(handle, actor).await
+    }
 }
 
 /// # Stats and status of CAS
@@ -709,6 +851,27 @@ impl KrillServer {
             }
         }
 
+        let cert_auth = self.ca_manager.get_ca(ca).await?;
+
+        for shrink in cert_auth.resource_shrinks() {
+            issues.add_resource_shrink(shrink.clone());
+        }
+
+        issues.add_validation_issues(ca_status.validation().clone());
+
+        let configured_roas = cert_auth.configured_roas();
+        let payloads: Vec<RoaPayload> = configured_roas.iter().map(|roa| roa.payload()).collect();
+        let max_length_exposed = self.bgp_analyser.max_length_exposed(&payloads).await;
+        for roa in configured_roas {
+            if max_length_exposed.contains(&roa.payload()) {
+                issues.add_roa_max_length_exposed(roa.roa_configuration().clone());
+            }
+        }
+
+        for configuration in cert_auth.roa_activations_refused() {
+            issues.add_roa_activation_refused(configuration);
+        }
+
         Ok(issues)
     }
 }
@@ -812,7 +975,7 @@ impl KrillServer {
     pub async fn ca_repo_details(&self, ca_handle: &CaHandle) -> KrillResult<CaRepoDetails> {
         let ca = self.ca_manager.get_ca(ca_handle).await?;
         let contact = ca.repository_contact()?;
-        Ok(CaRepoDetails::new(contact.clone()))
+        Ok(CaRepoDetails::new(contact.clone(), ca.repository_mirrors().clone()))
     }
 
     /// Update the repository for a CA, or return an error. (see `CertAuth::repo_update`)
@@ -822,6 +985,45 @@ impl KrillServer {
             .await
     }
 
+    /// Add a repository mirror for a CA, or return an error. (see `CertAuth::add_repo_mirror`)
+    pub async fn ca_repo_mirror_add(
+        &self,
+        ca: CaHandle,
+        contact: RepositoryContact,
+        actor: &Actor,
+    ) -> KrillEmptyResult {
+        self.ca_manager
+            .add_repo_mirror(self.repo_manager.as_ref(), ca, contact, actor)
+            .await
+    }
+
+    /// Remove a repository mirror for a CA, or return an error. (see `CertAuth::remove_repo_mirror`)
+    pub async fn ca_repo_mirror_remove(
+        &self,
+        ca: CaHandle,
+        service_uri: ServiceUri,
+        actor: &Actor,
+    ) -> KrillEmptyResult {
+        self.ca_manager.remove_repo_mirror(ca, service_uri, actor).await
+    }
+
+    /// Returns the decoded objects published by a CA.
+    pub async fn ca_published_objects(&self, ca: &CaHandle) -> KrillResult<CaPublishedObjects> {
+        self.ca_manager.get_ca(ca).await?; // verify that the CA exists
+        self.ca_manager.ca_published_objects(ca)
+    }
+
+    /// Returns the DER encoding of an object published by a CA.
+    pub async fn ca_published_object_der(
+        &self,
+        ca: &CaHandle,
+        key: &KeyIdentifier,
+        name: &ObjectName,
+    ) -> KrillResult<Bytes> {
+        self.ca_manager.get_ca(ca).await?; // verify that the CA exists
+        self.ca_manager.ca_published_object_der(ca, key, name)
+    }
+
     pub async fn ca_update_id(&self, ca: CaHandle, actor: &Actor) -> KrillEmptyResult {
         self.ca_manager.ca_update_id(ca, actor).await
     }
@@ -836,6 +1038,10 @@ impl KrillServer {
             .await
     }
 
+    pub async fn ca_keyroll_finish(&self, ca: CaHandle, actor: &Actor) -> KrillEmptyResult {
+        self.ca_manager.ca_keyroll_finish(ca, actor).await
+    }
+
     pub async fn rfc6492(
         &self,
         ca: CaHandle,
@@ -847,6 +1053,44 @@ impl KrillServer {
     }
 }
 
+/// # Offline Trust Anchor proxy
+///
+impl KrillServer {
+    pub fn ta_proxy_init(&self, actor: &Actor) -> KrillEmptyResult {
+        self.ca_manager.ta_proxy_init(&self.repo_manager, actor)
+    }
+
+    pub fn ta_proxy_info(&self) -> KrillResult<TrustAnchorProxyInfo> {
+        self.ca_manager.ta_proxy_info()
+    }
+
+    pub fn ta_proxy_status(&self) -> KrillResult<TrustAnchorProxyStatus> {
+        self.ca_manager.ta_proxy_status()?.ok_or(Error::TaProxyNotInitialized)
+    }
+
+    pub fn ta_proxy_signer_add(&self, signer: TrustAnchorSignerInfo) -> KrillEmptyResult {
+        self.ca_manager.ta_proxy_signer_add(signer)
+    }
+
+    pub fn ta_proxy_children_add(&self, req: AddChildRequest) -> KrillResult<idexchange::ParentResponse> {
+        self.ca_manager.ta_proxy_children_add(req, &self.service_uri)
+    }
+
+    pub fn ta_proxy_signer_make_request(&self) -> KrillResult<TrustAnchorSignedMessage> {
+        self.ca_manager.ta_proxy_signer_make_request()
+    }
+
+    pub fn ta_proxy_signer_show_request(&self) -> KrillResult<TrustAnchorSignedMessage> {
+        self.ca_manager.ta_proxy_signer_show_request()
+    }
+
+    pub async fn ta_proxy_signer_process_response(&self, response: TrustAnchorSignedMessage) -> KrillEmptyResult {
+        self.ca_manager
+            .ta_proxy_signer_process_response(&self.repo_manager, response)
+            .await
+    }
+}
+
 /// # Handle ASPA requests
 ///
 impl KrillServer {
@@ -872,6 +1116,43 @@ impl KrillServer {
     ) -> KrillEmptyResult {
         self.ca_manager.ca_aspas_update_aspa(ca, customer, update, actor).await
     }
+
+    /// Imports AS relationships to derive the provider lists for the customer
+    /// ASNs held by the CA. The resulting changes are only proposed, or
+    /// applied, depending on the requested action.
+    pub async fn ca_aspas_import(
+        &self,
+        ca: CaHandle,
+        request: AspaImportRequest,
+        actor: &Actor,
+    ) -> KrillResult<AspaImportReport> {
+        let (relationships, action) = request.unpack();
+        let report = self.ca_manager.get_ca(&ca).await?.aspas_import_report(&relationships);
+
+        if action == AspaImportAction::Apply && !report.is_empty() {
+            self.ca_aspas_definitions_update(ca, report.updates(), actor).await?;
+        }
+
+        Ok(report)
+    }
+
+    pub async fn ca_aspas_analysis(&self, ca: CaHandle) -> KrillResult<AspaAnalysisReport> {
+        let definitions = self.ca_manager.ca_aspas_definitions_show(ca.clone()).await?;
+        let resources_held = self.ca_manager.get_ca(&ca).await?.all_resources();
+        Ok(self
+            .bgp_analyser
+            .aspa_analyse(definitions.as_slice(), &resources_held)
+            .await)
+    }
+
+    pub async fn ca_aspas_suggest(&self, ca: CaHandle) -> KrillResult<AspaSuggestion> {
+        let definitions = self.ca_manager.ca_aspas_definitions_show(ca.clone()).await?;
+        let resources_held = self.ca_manager.get_ca(&ca).await?.all_resources();
+        Ok(self
+            .bgp_analyser
+            .aspa_suggest(definitions.as_slice(), &resources_held)
+            .await)
+    }
 }
 
 /// # Handle BGPSec requests
@@ -889,6 +1170,49 @@ impl KrillServer {
     ) -> KrillResult<()> {
         self.ca_manager.ca_bgpsec_definitions_update(ca, updates, actor).await
     }
+
+    pub async fn ca_bgpsec_router_key_create(
+        &self,
+        ca: CaHandle,
+        request: BgpSecRouterKeyRequest,
+        actor: &Actor,
+    ) -> KrillResult<BgpSecRouterKey> {
+        self.ca_manager.ca_bgpsec_router_key_create(ca, request, actor).await
+    }
+}
+
+/// # Handle Signed Prefix List requests
+///
+impl KrillServer {
+    pub async fn ca_prefix_lists_show(&self, ca: CaHandle) -> KrillResult<PrefixListDefinitionList> {
+        self.ca_manager.ca_prefix_lists_show(ca).await
+    }
+
+    pub async fn ca_prefix_lists_update(
+        &self,
+        ca: CaHandle,
+        updates: PrefixListDefinitionUpdates,
+        actor: &Actor,
+    ) -> KrillResult<()> {
+        self.ca_manager.ca_prefix_lists_update(ca, updates, actor).await
+    }
+}
+
+/// # Handle Ghostbuster requests
+///
+impl KrillServer {
+    pub async fn ca_ghostbuster_show(&self, ca: CaHandle) -> KrillResult<Option<GhostbusterContact>> {
+        self.ca_manager.ca_ghostbuster_show(ca).await
+    }
+
+    pub async fn ca_ghostbuster_update(
+        &self,
+        ca: CaHandle,
+        contact: Option<GhostbusterContact>,
+        actor: &Actor,
+    ) -> KrillResult<()> {
+        self.ca_manager.ca_ghostbuster_update(ca, contact, actor).await
+    }
 }
 
 /// # Handle route authorization requests
@@ -903,10 +1227,28 @@ impl KrillServer {
         self.ca_manager.ca_routes_update(ca, updates, actor).await
     }
 
-    pub async fn ca_routes_show(&self, handle: &CaHandle) -> KrillResult<Vec<ConfiguredRoa>> {
+    pub async fn ca_routes_show(
+        &self,
+        handle: &CaHandle,
+        filter: &RoaConfigurationFilter,
+    ) -> KrillResult<Vec<ConfiguredRoa>> {
+        let ca = self.ca_manager.get_ca(handle).await?;
+
+        Ok(ca
+            .configured_roas()
+            .into_iter()
+            .filter(|configured| filter.matches(configured.roa_configuration()))
+            .collect())
+    }
+
+    pub async fn ca_routes_export(&self, handle: &CaHandle, format: VrpExportFormat) -> KrillResult<String> {
         let ca = self.ca_manager.get_ca(handle).await?;
+        Ok(VrpExport::new(ca.configured_roas()).export(format))
+    }
 
-        Ok(ca.configured_roas())
+    pub async fn ca_routes_timed_changes(&self, handle: &CaHandle) -> KrillResult<TimedRoaChanges> {
+        let ca = self.ca_manager.get_ca(handle).await?;
+        Ok(ca.timed_roa_changes())
     }
 
     pub async fn ca_routes_bgp_analysis(&self, handle: &CaHandle) -> KrillResult<BgpAnalysisReport> {
@@ -930,7 +1272,8 @@ impl KrillServer {
         let resources_held = ca.all_resources();
         let limit = Some(updates.affected_prefixes());
 
-        let (would_be_routes, _) = ca.update_authorizations(&updates)?;
+        let max_length_exposed = self.ca_manager.roa_max_length_exposed(&updates).await;
+        let (would_be_routes, _) = ca.update_authorizations(&updates, &max_length_exposed)?;
         let would_be_configurations = would_be_routes.roa_configurations();
         let configured_roas = ca.configured_roas_for_configs(would_be_configurations);
 
@@ -940,6 +1283,13 @@ impl KrillServer {
             .await)
     }
 
+    /// Performs a dry run for all updates in the named draft.
+    pub async fn ca_routes_draft_bgp_dry_run(&self, handle: &CaHandle, name: &str) -> KrillResult<BgpAnalysisReport> {
+        let ca = self.ca_manager.get_ca(handle).await?;
+        let (_, updates) = ca.roa_draft(name)?.unpack();
+        self.ca_routes_bgp_dry_run(handle, updates).await
+    }
+
     pub async fn ca_routes_bgp_suggest(
         &self,
         handle: &CaHandle,
@@ -955,6 +1305,67 @@ impl KrillServer {
             .await)
     }
 
+    pub async fn ca_routes_drafts(&self, handle: &CaHandle) -> KrillResult<RoaDraftList> {
+        let ca = self.ca_manager.get_ca(handle).await?;
+        Ok(ca.roa_drafts())
+    }
+
+    pub async fn ca_routes_draft_show(&self, handle: &CaHandle, name: &str) -> KrillResult<RoaDraft> {
+        let ca = self.ca_manager.get_ca(handle).await?;
+        ca.roa_draft(name)
+    }
+
+    pub async fn ca_routes_draft_update(
+        &self,
+        ca: CaHandle,
+        name: RoaDraftName,
+        updates: RoaConfigurationUpdates,
+        actor: &Actor,
+    ) -> KrillEmptyResult {
+        self.ca_manager.ca_routes_draft_update(ca, name, updates, actor).await
+    }
+
+    pub async fn ca_routes_draft_remove(&self, ca: CaHandle, name: RoaDraftName, actor: &Actor) -> KrillEmptyResult {
+        self.ca_manager.ca_routes_draft_remove(ca, name, actor).await
+    }
+
+    pub async fn ca_routes_draft_commit(&self, ca: CaHandle, name: RoaDraftName, actor: &Actor) -> KrillEmptyResult {
+        self.ca_manager.ca_routes_draft_commit(ca, name, actor).await
+    }
+
+    pub async fn ca_routes_as0_status(&self, handle: &CaHandle) -> KrillResult<RoaAs0Status> {
+        let ca = self.ca_manager.get_ca(handle).await?;
+        Ok(ca.roa_as0_status())
+    }
+
+    pub async fn ca_routes_as0_policy(&self, ca: CaHandle, policy: RoaAs0Policy, actor: &Actor) -> KrillEmptyResult {
+        self.ca_manager.ca_routes_as0_policy(ca, policy, actor).await
+    }
+
+    /// Imports RPSL route and route6 objects for the prefixes held by the CA.
+    /// The resulting updates are only proposed, applied, or merged into a
+    /// ROA draft, depending on the requested action.
+    pub async fn ca_routes_import_rpsl(
+        &self,
+        ca: CaHandle,
+        request: RpslImportRequest,
+        actor: &Actor,
+    ) -> KrillResult<RpslImportReport> {
+        let (rpsl, action) = request.unpack();
+        let report = self.ca_manager.get_ca(&ca).await?.rpsl_import_report(&rpsl);
+
+        if !report.updates().is_empty() {
+            let updates = report.updates().clone();
+            match action {
+                RpslImportAction::Propose => {}
+                RpslImportAction::Apply => self.ca_routes_update(ca, updates, actor).await?,
+                RpslImportAction::Stage(name) => self.ca_routes_draft_update(ca, name, updates, actor).await?,
+            }
+        }
+
+        Ok(report)
+    }
+
     /// Re-issue ROA objects so that they will use short subjects (see issue #700)
     pub async fn force_renew_roas(&self) -> KrillResult<()> {
         self.ca_manager.force_renew_roas_all(self.system_actor()).await
@@ -980,6 +1391,42 @@ impl KrillServer {
     pub fn repository_session_reset(&self) -> KrillResult<()> {
         self.repo_manager.rrdp_session_reset()
     }
+
+    /// Validate the content of the repository, as a relying party would, and
+    /// record the issues found for the CAs and the repository.
+    pub async fn repository_validate(&self) -> KrillResult<RepositoryValidationReport> {
+        self.ca_manager.validate_repository(&self.repo_manager).await
+    }
+}
+
+/// # Handle Resource Signed Checklist requests
+///
+impl KrillServer {
+    /// Sign an RSC and return it, it is not kept by Krill
+    pub async fn rsc_sign(&self, ca: CaHandle, request: RscRequest) -> KrillResult<ResourceSignedChecklist> {
+        self.ca_manager.rsc_sign(ca, request).await
+    }
+}
+
+/// # Handle Signed Geofeed requests
+///
+impl KrillServer {
+    /// Sign a geofeed and return it with the signature appended, it is not
+    /// kept by Krill
+    pub async fn geofeed_sign(&self, ca: CaHandle, request: GeofeedSignRequest) -> KrillResult<SignedGeofeed> {
+        self.ca_manager.geofeed_sign(ca, request).await
+    }
+}
+
+/// # Verify RTAs and RSCs
+///
+impl KrillServer {
+    pub async fn verify_signed_object(
+        &self,
+        request: SignedObjectVerifyRequest,
+    ) -> KrillResult<SignedObjectVerification> {
+        self.ca_manager.verify_signed_object(request).await
+    }
 }
 
 /// # Handle Resource Tagged Attestation requests
@@ -9,7 +9,7 @@ use priority_queue::PriorityQueue;
 
 use rpki::{
     ca::{
-        idexchange::{CaHandle, ParentHandle},
+        idexchange::{CaHandle, ParentHandle, ServiceUri},
         provisioning::{ResourceClassName, RevocationRequest},
     },
     repository::x509::Time,
@@ -35,6 +35,14 @@ pub enum Task {
         ca: CaHandle,
     },
 
+    // The mirror is identified by its service URI, as a string because
+    // ServiceUri cannot be hashed. The mirror is looked up by this string
+    // when the task is processed.
+    SyncRepoMirror {
+        ca: CaHandle,
+        mirror: String,
+    },
+
     SyncParent {
         ca: CaHandle,
         parent: ParentHandle,
@@ -44,6 +52,10 @@ pub enum Task {
         ca: CaHandle,
     },
 
+    UpdateTimedRoas {
+        ca: CaHandle,
+    },
+
     RepublishIfNeeded,
     RenewObjectsIfNeeded,
 
@@ -53,6 +65,10 @@ pub enum Task {
 
     RrdpUpdateIfNeeded,
 
+    CheckTaProxy,
+
+    ValidateRepository,
+
     #[cfg(feature = "multi-user")]
     SweepLoginCache,
 
@@ -74,13 +90,17 @@ impl fmt::Display for Task {
         match self {
             Task::QueueStartTasks => write!(f, "Server just started"),
             Task::SyncRepo { ca } => write!(f, "synchronize repo for '{}'", ca),
+            Task::SyncRepoMirror { ca, mirror } => write!(f, "synchronize repo mirror '{}' for '{}'", mirror, ca),
             Task::SyncParent { ca, parent } => write!(f, "synchronize CA '{}' with parent '{}'", ca, parent),
             Task::SuspendChildrenIfNeeded { ca } => write!(f, "verify if CA '{}' has children to suspend", ca),
+            Task::UpdateTimedRoas { ca } => write!(f, "activate or expire time-bounded ROAs for CA '{}'", ca),
             Task::RepublishIfNeeded => write!(f, "let CAs republish their mft/crls if needed"),
             Task::RenewObjectsIfNeeded => write!(f, "let CAs renew their signed objects if needed"),
             Task::RefreshAnnouncementsInfo => write!(f, "check for new announcement info"),
             Task::UpdateSnapshots => write!(f, "update repository content snapshot on disk"),
             Task::RrdpUpdateIfNeeded => write!(f, "create new RRDP delta, if needed"),
+            Task::CheckTaProxy => write!(f, "check the Trust Anchor proxy objects expiry"),
+            Task::ValidateRepository => write!(f, "validate the content of the repository"),
 
             #[cfg(feature = "multi-user")]
             Task::SweepLoginCache => write!(f, "sweep up expired logins"),
@@ -188,6 +208,7 @@ impl TaskQueue {
                 Task::SyncRepo { ca }
                 | Task::SyncParent { ca, .. }
                 | Task::SuspendChildrenIfNeeded { ca }
+                | Task::UpdateTimedRoas { ca }
                 | Task::ResourceClassRemoved { ca, .. }
                 | Task::UnexpectedKey { ca, .. } => {
                     if ca == removed_ca {
@@ -213,6 +234,10 @@ impl TaskQueue {
         self.schedule(Task::SyncRepo { ca }, priority);
     }
 
+    pub fn sync_repo_mirror(&self, ca: CaHandle, mirror: String, priority: Priority) {
+        self.schedule(Task::SyncRepoMirror { ca, mirror }, priority);
+    }
+
     pub fn sync_parent(&self, ca: CaHandle, parent: ParentHandle, priority: Priority) {
         self.schedule(Task::SyncParent { ca, parent }, priority);
     }
@@ -221,6 +246,10 @@ impl TaskQueue {
         self.schedule(Task::SuspendChildrenIfNeeded { ca }, priority);
     }
 
+    pub fn update_timed_roas(&self, ca: CaHandle, priority: Priority) {
+        self.schedule(Task::UpdateTimedRoas { ca }, priority);
+    }
+
     pub fn republish_if_needed(&self, priority: Priority) {
         self.schedule(Task::RepublishIfNeeded, priority);
     }
@@ -241,6 +270,14 @@ impl TaskQueue {
         self.schedule(Task::RrdpUpdateIfNeeded, priority)
     }
 
+    pub fn check_ta_proxy(&self, priority: Priority) {
+        self.schedule(Task::CheckTaProxy, priority)
+    }
+
+    pub fn validate_repository(&self, priority: Priority) {
+        self.schedule(Task::ValidateRepository, priority)
+    }
+
     #[cfg(feature = "multi-user")]
     pub fn sweep_login_cache(&self, priority: Priority) {
         self.schedule(Task::SweepLoginCache, priority);
@@ -251,11 +288,20 @@ impl TaskQueue {
         let sync = Task::SyncParent { ca, parent };
         q.remove(&sync);
     }
+
+    fn drop_sync_repo_mirror(&self, ca: CaHandle, mirror: &ServiceUri) {
+        let mut q = self.q.write().unwrap();
+        let mirror = mirror.to_string();
+        let sync = Task::SyncRepoMirror { ca, mirror };
+        q.remove(&sync);
+    }
 }
 
 /// Implement listening for CertAuth Published events.
 impl eventsourcing::PostSaveEventListener<CertAuth> for TaskQueue {
     fn listen(&self, ca: &CertAuth, events: &[CaEvt]) {
+        let mut timed_roas_changed = false;
+
         for event in events {
             trace!("Seen CertAuth event '{}'", event);
 
@@ -266,25 +312,40 @@ impl eventsourcing::PostSaveEventListener<CertAuth> for TaskQueue {
                 | CaEvtDet::AspaObjectsUpdated { .. }
                 | CaEvtDet::ChildCertificatesUpdated { .. }
                 | CaEvtDet::BgpSecCertificatesUpdated { .. }
+                | CaEvtDet::PrefixListObjectsUpdated { .. }
+                | CaEvtDet::GhostbusterObjectUpdated { .. }
                 | CaEvtDet::ChildKeyRevoked { .. }
                 | CaEvtDet::KeyPendingToNew { .. }
                 | CaEvtDet::KeyPendingToActive { .. }
-                | CaEvtDet::KeyRollFinished { .. } => self.sync_repo(handle.clone(), now()),
+                | CaEvtDet::KeyRollFinished { .. }
+                | CaEvtDet::TakObjectsUpdated { .. } => self.sync_repo(handle.clone(), now()),
 
                 CaEvtDet::KeyRollActivated {
                     resource_class_name, ..
                 } => {
-                    if let Ok(parent) = ca.parent_for_rc(resource_class_name) {
-                        self.sync_parent(handle.clone(), parent.clone(), now());
+                    // A TA has no parent to revoke its old key.
+                    if !ca.is_ta() {
+                        if let Ok(parent) = ca.parent_for_rc(resource_class_name) {
+                            self.sync_parent(handle.clone(), parent.clone(), now());
+                        }
                     }
                     self.sync_repo(handle.clone(), now());
                 }
 
+                CaEvtDet::RouteAuthorizationTimeBounds { .. } | CaEvtDet::RouteAuthorizationScheduled { .. } => {
+                    timed_roas_changed = true;
+                }
+
                 CaEvtDet::ParentRemoved { parent } => {
                     self.drop_sync_parent(handle.clone(), parent.clone());
                     self.sync_repo(handle.clone(), now());
                 }
 
+                CaEvtDet::ParentResourceLimitUpdated { parent, .. } => {
+                    // Re-evaluate the entitlements and request certificates as needed.
+                    self.sync_parent(handle.clone(), parent.clone(), now());
+                }
+
                 CaEvtDet::ResourceClassRemoved {
                     resource_class_name,
                     parent,
@@ -328,6 +389,15 @@ impl eventsourcing::PostSaveEventListener<CertAuth> for TaskQueue {
                         );
                     }
                 }
+                CaEvtDet::RepoMirrorAdded { contact } => {
+                    let mirror = contact.server_info().service_uri().to_string();
+                    self.sync_repo_mirror(handle.clone(), mirror, now());
+                }
+                CaEvtDet::RepoMirrorRemoved { service_uri } => {
+                    // Withdraw the objects from the mirror, it is now a deprecated repository.
+                    self.drop_sync_repo_mirror(handle.clone(), service_uri);
+                    self.sync_repo(handle.clone(), now());
+                }
                 CaEvtDet::RepoUpdated { .. } => {
                     for parent in ca.parents() {
                         self.sync_parent(handle.clone(), parent.clone(), now());
@@ -346,6 +416,12 @@ impl eventsourcing::PostSaveEventListener<CertAuth> for TaskQueue {
                 _ => {}
             }
         }
+
+        if timed_roas_changed {
+            if let Some(next) = ca.timed_roa_changes().next() {
+                self.update_timed_roas(ca.handle().clone(), next.into());
+            }
+        }
     }
 }
 
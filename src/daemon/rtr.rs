@@ -0,0 +1,956 @@
+//! A minimal RPKI-to-Router (RFC 6810, RFC 8210, draft-ietf-sidrops-8210bis)
+//! server for lab use.
+//!
+//! The server provides the VRPs and ASPAs configured in the hosted CAs to
+//! routers, so that router behaviour can be tested against the intended
+//! state of these CAs without the need for a full validation pipeline. It
+//! supports protocol versions 0, 1 and 2. ASPA data can only be conveyed in
+//! version 2, so it is not sent to routers using an earlier version.
+//!
+//! This MUST NOT be used in production, as the data is not validated.
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, RwLock},
+};
+
+use rpki::{ca::idexchange::CaHandle, repository::resources::Asn};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpListener, TcpStream,
+    },
+    select,
+    sync::{mpsc, watch},
+};
+
+use crate::{
+    commons::{api::RoaPayload, eventsourcing},
+    daemon::{
+        ca::{CaEvt, CaEvtDet, CertAuth},
+        config::RtrConfig,
+    },
+};
+
+/// The number of deltas kept, so that routers can do incremental updates.
+const RTR_DELTAS_KEPT: usize = 100;
+
+/// The maximum length of a PDU we accept from a router. The largest PDU
+/// we expect is an Error Report, which should not come near this.
+const RTR_MAX_PDU_LEN: u32 = 64 * 1024;
+
+const RTR_VERSION_MAX: u8 = 2;
+
+/// The first version which supports ASPA PDUs.
+const RTR_VERSION_ASPA: u8 = 2;
+
+const PDU_SERIAL_NOTIFY: u8 = 0;
+const PDU_SERIAL_QUERY: u8 = 1;
+const PDU_RESET_QUERY: u8 = 2;
+const PDU_CACHE_RESPONSE: u8 = 3;
+const PDU_IPV4_PREFIX: u8 = 4;
+const PDU_IPV6_PREFIX: u8 = 6;
+const PDU_END_OF_DATA: u8 = 7;
+const PDU_CACHE_RESET: u8 = 8;
+const PDU_ERROR_REPORT: u8 = 10;
+const PDU_ASPA: u8 = 11;
+
+const ERR_CORRUPT_DATA: u16 = 0;
+const ERR_INVALID_REQUEST: u16 = 3;
+const ERR_UNSUPPORTED_VERSION: u16 = 4;
+const ERR_UNSUPPORTED_PDU_TYPE: u16 = 5;
+
+//------------ RtrCache ------------------------------------------------------
+
+/// Keeps the current VRPs and ASPAs of all CAs, and the recent changes to
+/// them.
+pub struct RtrCache {
+    config: RtrConfig,
+    state: RwLock<RtrState>,
+    serial_sender: watch::Sender<u32>,
+}
+
+/// The provider ASNs per customer ASN, sorted and without duplicates.
+type RtrAspas = HashMap<Asn, Vec<Asn>>;
+
+struct RtrState {
+    session_id: u16,
+    serial: u32,
+    cas: HashMap<CaHandle, RtrCaData>,
+    vrps: HashSet<RoaPayload>,
+    aspas: RtrAspas,
+    deltas: VecDeque<RtrDelta>,
+}
+
+/// The VRPs and ASPAs configured in a single CA.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct RtrCaData {
+    vrps: HashSet<RoaPayload>,
+    aspas: RtrAspas,
+}
+
+impl RtrCaData {
+    fn is_empty(&self) -> bool {
+        self.vrps.is_empty() && self.aspas.is_empty()
+    }
+}
+
+/// The changes which resulted in 'serial'.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct RtrDelta {
+    serial: u32,
+    announced: HashSet<RoaPayload>,
+    withdrawn: HashSet<RoaPayload>,
+    aspas: HashMap<Asn, RtrAspaChange>,
+}
+
+/// The providers for a customer ASN before and after a change. None means
+/// that there was, or is, no ASPA for the customer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct RtrAspaChange {
+    before: Option<Vec<Asn>>,
+    after: Option<Vec<Asn>>,
+}
+
+impl RtrDelta {
+    /// Adds the changes of the next delta, so that the result reflects
+    /// the combined changes.
+    fn append(&mut self, next: &RtrDelta) {
+        self.serial = next.serial;
+        for vrp in &next.announced {
+            if !self.withdrawn.remove(vrp) {
+                self.announced.insert(*vrp);
+            }
+        }
+        for vrp in &next.withdrawn {
+            if !self.announced.remove(vrp) {
+                self.withdrawn.insert(*vrp);
+            }
+        }
+        for (customer, change) in &next.aspas {
+            match self.aspas.get_mut(customer) {
+                None => {
+                    self.aspas.insert(*customer, change.clone());
+                }
+                Some(existing) => {
+                    existing.after = change.after.clone();
+                    if existing.before == existing.after {
+                        self.aspas.remove(customer);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The response to a query from a router.
+#[derive(Debug, Eq, PartialEq)]
+enum RtrUpdate {
+    Full(u16, u32, Vec<RoaPayload>, RtrAspas),
+    Delta(u16, RtrDelta),
+    Reset,
+}
+
+impl RtrCache {
+    pub fn new(config: RtrConfig) -> Self {
+        let (serial_sender, _) = watch::channel(0);
+        RtrCache {
+            config,
+            state: RwLock::new(RtrState {
+                session_id: rand::random(),
+                serial: 0,
+                cas: HashMap::new(),
+                vrps: HashSet::new(),
+                aspas: HashMap::new(),
+                deltas: VecDeque::new(),
+            }),
+            serial_sender,
+        }
+    }
+
+    /// Replaces the VRPs and ASPAs for the CA, based on its configured ROAs
+    /// and ASPAs. If this results in a change of the overall VRPs or ASPAs,
+    /// then the serial is incremented, and connected routers are notified.
+    ///
+    /// Note that the ASPA PDU has no address family, so any address family
+    /// limit on a provider is not conveyed.
+    pub fn update_ca(&self, ca: &CertAuth) {
+        let vrps = ca
+            .configured_roas()
+            .into_iter()
+            .map(|configured| configured.payload().into_explicit_max_length())
+            .chain(ca.roa_as0_status().payloads().iter().copied())
+            .collect();
+
+        let aspas = ca
+            .aspas_definitions_show()
+            .as_slice()
+            .iter()
+            .map(|def| {
+                let mut providers: Vec<Asn> = def.providers().iter().map(|provider| provider.provider()).collect();
+                providers.sort();
+                providers.dedup();
+                (def.customer(), providers)
+            })
+            .collect();
+
+        self.set_data(ca.handle(), RtrCaData { vrps, aspas });
+    }
+
+    /// Removes the VRPs and ASPAs for a CA which is deleted.
+    pub fn remove_ca(&self, ca: &CaHandle) {
+        self.set_data(ca, RtrCaData::default());
+    }
+
+    fn set_data(&self, ca: &CaHandle, data: RtrCaData) {
+        let mut state = self.state.write().unwrap();
+        if let Some(serial) = state.update_ca(ca.clone(), data) {
+            debug!("RTR serial is now {} after update in CA '{}'", serial, ca);
+            let _ = self.serial_sender.send(serial);
+        }
+    }
+
+    fn update_since(&self, session_id: u16, serial: u32) -> RtrUpdate {
+        self.state.read().unwrap().update_since(session_id, serial)
+    }
+
+    fn full_update(&self) -> RtrUpdate {
+        self.state.read().unwrap().full_update()
+    }
+
+    fn session_and_serial(&self) -> (u16, u32) {
+        let state = self.state.read().unwrap();
+        (state.session_id, state.serial)
+    }
+}
+
+impl RtrState {
+    fn update_ca(&mut self, ca: CaHandle, data: RtrCaData) -> Option<u32> {
+        if data.is_empty() {
+            self.cas.remove(&ca);
+        } else {
+            self.cas.insert(ca, data);
+        }
+
+        let current: HashSet<RoaPayload> = self.cas.values().flat_map(|data| &data.vrps).copied().collect();
+        let announced: HashSet<RoaPayload> = current.difference(&self.vrps).copied().collect();
+        let withdrawn: HashSet<RoaPayload> = self.vrps.difference(&current).copied().collect();
+
+        // The same customer ASN may be used in more than one CA, in which
+        // case the providers are combined.
+        let mut current_aspas = RtrAspas::new();
+        for (customer, providers) in self.cas.values().flat_map(|data| &data.aspas) {
+            current_aspas.entry(*customer).or_default().extend(providers);
+        }
+        for providers in current_aspas.values_mut() {
+            providers.sort();
+            providers.dedup();
+        }
+
+        let aspas: HashMap<Asn, RtrAspaChange> = current_aspas
+            .keys()
+            .chain(self.aspas.keys())
+            .filter_map(|customer| {
+                let before = self.aspas.get(customer);
+                let after = current_aspas.get(customer);
+                if before == after {
+                    None
+                } else {
+                    let change = RtrAspaChange {
+                        before: before.cloned(),
+                        after: after.cloned(),
+                    };
+                    Some((*customer, change))
+                }
+            })
+            .collect();
+
+        if announced.is_empty() && withdrawn.is_empty() && aspas.is_empty() {
+            return None;
+        }
+
+        self.serial = self.serial.wrapping_add(1);
+        self.vrps = current;
+        self.aspas = current_aspas;
+        self.deltas.push_back(RtrDelta {
+            serial: self.serial,
+            announced,
+            withdrawn,
+            aspas,
+        });
+        if self.deltas.len() > RTR_DELTAS_KEPT {
+            self.deltas.pop_front();
+        }
+
+        Some(self.serial)
+    }
+
+    fn full_update(&self) -> RtrUpdate {
+        RtrUpdate::Full(
+            self.session_id,
+            self.serial,
+            self.vrps.iter().copied().collect(),
+            self.aspas.clone(),
+        )
+    }
+
+    /// Returns the combined delta since the given serial, or a reset if
+    /// the session changed, or if the serial is too old or unknown.
+    fn update_since(&self, session_id: u16, serial: u32) -> RtrUpdate {
+        if session_id != self.session_id {
+            return RtrUpdate::Reset;
+        }
+
+        let mut combined = RtrDelta {
+            serial,
+            ..Default::default()
+        };
+
+        if serial != self.serial {
+            match self
+                .deltas
+                .iter()
+                .position(|delta| delta.serial == serial.wrapping_add(1))
+            {
+                None => return RtrUpdate::Reset,
+                Some(start) => {
+                    for delta in self.deltas.iter().skip(start) {
+                        combined.append(delta);
+                    }
+                }
+            }
+        }
+
+        RtrUpdate::Delta(self.session_id, combined)
+    }
+}
+
+/// Update the VRPs and ASPAs for a CA whenever its ROAs or ASPAs are updated.
+impl eventsourcing::PostSaveEventListener<CertAuth> for RtrCache {
+    fn listen(&self, ca: &CertAuth, events: &[CaEvt]) {
+        if events.iter().any(|event| {
+            matches!(
+                event.details(),
+                CaEvtDet::RoasUpdated { .. } | CaEvtDet::AspaObjectsUpdated { .. }
+            )
+        }) {
+            self.update_ca(ca);
+        }
+    }
+}
+
+//------------ Server --------------------------------------------------------
+
+/// Listens for router connections on the configured address, until a
+/// shutdown is signalled, or the shutdown sender is dropped. Connected
+/// routers are disconnected on shutdown as well.
+pub async fn rtr_listener(cache: Arc<RtrCache>, mut shutdown: watch::Receiver<bool>) {
+    let listen = cache.config.listen;
+    let listener = match TcpListener::bind(listen).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!(
+                "Could not bind RTR server to address and port: {}, Error: {}",
+                listen, e
+            );
+            return;
+        }
+    };
+
+    warn!("RTR server listening on {} - ONLY USE THIS FOR TESTING!", listen);
+
+    loop {
+        select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, addr)) => {
+                    tokio::spawn(rtr_connection(cache.clone(), stream, addr, shutdown.clone()));
+                }
+                Err(e) => error!("Could not accept RTR connection: {}", e),
+            },
+            _ = shutdown.changed() => {
+                info!("RTR server on {} stopped", listen);
+                return;
+            }
+        }
+    }
+}
+
+async fn rtr_connection(
+    cache: Arc<RtrCache>,
+    stream: TcpStream,
+    addr: SocketAddr,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    info!("RTR connection from {}", addr);
+
+    let (reader, mut writer) = stream.into_split();
+
+    // Read PDUs in a separate task, because reading is not cancel safe.
+    let (pdu_sender, mut pdus) = mpsc::channel(8);
+    tokio::spawn(read_pdus(reader, pdu_sender));
+
+    let mut serials = cache.serial_sender.subscribe();
+    let mut version = None;
+
+    loop {
+        select! {
+            pdu = pdus.recv() => {
+                let pdu = match pdu {
+                    Some(Ok(pdu)) => pdu,
+                    Some(Err(e)) => {
+                        debug!("RTR connection from {} closed: {}", addr, e);
+                        return;
+                    }
+                    None => {
+                        debug!("RTR connection from {} closed", addr);
+                        return;
+                    }
+                };
+
+                match handle_pdu(&cache, &mut version, &pdu) {
+                    Ok(response) => {
+                        if writer.write_all(&response).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(error_report) => {
+                        warn!("Closing RTR connection from {} after error", addr);
+                        let _ = writer.write_all(&error_report).await;
+                        return;
+                    }
+                }
+            }
+            changed = serials.changed() => {
+                if changed.is_err() || notify(&cache, version, &mut writer).await.is_err() {
+                    return;
+                }
+            }
+            _ = shutdown.changed() => {
+                debug!("Closing RTR connection from {} on shutdown", addr);
+                return;
+            }
+        }
+    }
+}
+
+/// Sends a Serial Notify, if the router has already queried us.
+async fn notify(cache: &RtrCache, version: Option<u8>, writer: &mut OwnedWriteHalf) -> std::io::Result<()> {
+    match version {
+        None => Ok(()),
+        Some(version) => {
+            let (session_id, serial) = cache.session_and_serial();
+            writer.write_all(&serial_notify(version, session_id, serial)).await
+        }
+    }
+}
+
+async fn read_pdus(mut reader: OwnedReadHalf, sender: mpsc::Sender<Result<Vec<u8>, String>>) {
+    loop {
+        let pdu = read_pdu(&mut reader).await;
+        let stop = pdu.is_err();
+        if sender.send(pdu).await.is_err() || stop {
+            return;
+        }
+    }
+}
+
+async fn read_pdu(reader: &mut OwnedReadHalf) -> Result<Vec<u8>, String> {
+    let mut pdu = vec![0; 8];
+    reader.read_exact(&mut pdu).await.map_err(|e| e.to_string())?;
+
+    let len = u32::from_be_bytes([pdu[4], pdu[5], pdu[6], pdu[7]]);
+    if !(8..=RTR_MAX_PDU_LEN).contains(&len) {
+        return Err(format!("invalid PDU length {}", len));
+    }
+
+    pdu.resize(len as usize, 0);
+    reader.read_exact(&mut pdu[8..]).await.map_err(|e| e.to_string())?;
+    Ok(pdu)
+}
+
+/// Returns the response for a PDU received from a router, or an Error
+/// Report if the PDU could not be handled and the session must be closed.
+/// The version used by the router is established by its first PDU.
+fn handle_pdu(cache: &RtrCache, version: &mut Option<u8>, pdu: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+    let pdu_version = pdu[0];
+    let pdu_type = pdu[1];
+
+    if pdu_type == PDU_ERROR_REPORT {
+        // The router reports a problem, we just log it and do not respond.
+        warn!(
+            "Received RTR error report with code {}",
+            u16::from_be_bytes([pdu[2], pdu[3]])
+        );
+        return Ok(vec![]);
+    }
+
+    let version = match version {
+        Some(version) if *version != pdu_version => {
+            return Err(error_report(*version, ERR_UNSUPPORTED_VERSION, pdu, "Version changed"))
+        }
+        Some(version) => *version,
+        None if pdu_version > RTR_VERSION_MAX => {
+            return Err(error_report(
+                RTR_VERSION_MAX,
+                ERR_UNSUPPORTED_VERSION,
+                pdu,
+                "Unsupported version",
+            ))
+        }
+        None => {
+            *version = Some(pdu_version);
+            pdu_version
+        }
+    };
+
+    let update = match pdu_type {
+        PDU_RESET_QUERY if pdu.len() == 8 => cache.full_update(),
+        PDU_SERIAL_QUERY if pdu.len() == 12 => {
+            let session_id = u16::from_be_bytes([pdu[2], pdu[3]]);
+            let serial = u32::from_be_bytes([pdu[8], pdu[9], pdu[10], pdu[11]]);
+            cache.update_since(session_id, serial)
+        }
+        PDU_RESET_QUERY | PDU_SERIAL_QUERY => {
+            return Err(error_report(version, ERR_CORRUPT_DATA, pdu, "Invalid PDU length"))
+        }
+        PDU_SERIAL_NOTIFY | PDU_CACHE_RESPONSE | PDU_IPV4_PREFIX | PDU_IPV6_PREFIX | PDU_END_OF_DATA
+        | PDU_CACHE_RESET | PDU_ASPA => return Err(error_report(version, ERR_INVALID_REQUEST, pdu, "Unexpected PDU")),
+        _ => {
+            return Err(error_report(
+                version,
+                ERR_UNSUPPORTED_PDU_TYPE,
+                pdu,
+                "Unsupported PDU type",
+            ))
+        }
+    };
+
+    Ok(response(&cache.config, version, update))
+}
+
+//------------ Encoding ------------------------------------------------------
+
+fn header(version: u8, pdu_type: u8, session_or_code: u16, len: u32) -> Vec<u8> {
+    let mut pdu = Vec::with_capacity(len as usize);
+    pdu.push(version);
+    pdu.push(pdu_type);
+    pdu.extend_from_slice(&session_or_code.to_be_bytes());
+    pdu.extend_from_slice(&len.to_be_bytes());
+    pdu
+}
+
+fn serial_notify(version: u8, session_id: u16, serial: u32) -> Vec<u8> {
+    let mut pdu = header(version, PDU_SERIAL_NOTIFY, session_id, 12);
+    pdu.extend_from_slice(&serial.to_be_bytes());
+    pdu
+}
+
+fn prefix(version: u8, announce: bool, vrp: &RoaPayload) -> Vec<u8> {
+    let typed_prefix = vrp.prefix();
+    let flags = u8::from(announce);
+    let prefix_len = typed_prefix.prefix().addr_len();
+    let max_len = vrp.effective_max_length();
+
+    let mut pdu = match typed_prefix.ip_addr() {
+        IpAddr::V4(addr) => {
+            let mut pdu = header(version, PDU_IPV4_PREFIX, 0, 20);
+            pdu.extend_from_slice(&[flags, prefix_len, max_len, 0]);
+            pdu.extend_from_slice(&addr.octets());
+            pdu
+        }
+        IpAddr::V6(addr) => {
+            let mut pdu = header(version, PDU_IPV6_PREFIX, 0, 32);
+            pdu.extend_from_slice(&[flags, prefix_len, max_len, 0]);
+            pdu.extend_from_slice(&addr.octets());
+            pdu
+        }
+    };
+    pdu.extend_from_slice(&Asn::from(vrp.asn()).into_u32().to_be_bytes());
+    pdu
+}
+
+/// Encodes an ASPA PDU. A withdrawal has no providers.
+fn aspa(version: u8, customer: Asn, providers: Option<&Vec<Asn>>) -> Vec<u8> {
+    let flags = u8::from(providers.is_some());
+    let providers = providers.map(Vec::as_slice).unwrap_or_default();
+    let len = 12 + 4 * providers.len();
+
+    let mut pdu = header(version, PDU_ASPA, 0, len as u32);
+    pdu[2] = flags;
+    pdu.extend_from_slice(&customer.into_u32().to_be_bytes());
+    for provider in providers {
+        pdu.extend_from_slice(&provider.into_u32().to_be_bytes());
+    }
+    pdu
+}
+
+fn end_of_data(config: &RtrConfig, version: u8, session_id: u16, serial: u32) -> Vec<u8> {
+    if version == 0 {
+        let mut pdu = header(version, PDU_END_OF_DATA, session_id, 12);
+        pdu.extend_from_slice(&serial.to_be_bytes());
+        pdu
+    } else {
+        let mut pdu = header(version, PDU_END_OF_DATA, session_id, 24);
+        pdu.extend_from_slice(&serial.to_be_bytes());
+        pdu.extend_from_slice(&config.refresh_interval.to_be_bytes());
+        pdu.extend_from_slice(&config.retry_interval.to_be_bytes());
+        pdu.extend_from_slice(&config.expire_interval.to_be_bytes());
+        pdu
+    }
+}
+
+fn error_report(version: u8, code: u16, erroneous_pdu: &[u8], text: &str) -> Vec<u8> {
+    let len = 16 + erroneous_pdu.len() + text.len();
+    let mut pdu = header(version, PDU_ERROR_REPORT, code, len as u32);
+    pdu.extend_from_slice(&(erroneous_pdu.len() as u32).to_be_bytes());
+    pdu.extend_from_slice(erroneous_pdu);
+    pdu.extend_from_slice(&(text.len() as u32).to_be_bytes());
+    pdu.extend_from_slice(text.as_bytes());
+    pdu
+}
+
+/// Encodes the response for an update: a Cache Response followed by
+/// the prefixes, the ASPAs if the version supports them, and End of Data,
+/// or a Cache Reset.
+fn response(config: &RtrConfig, version: u8, update: RtrUpdate) -> Vec<u8> {
+    let (session_id, serial, announced, withdrawn, mut aspas) = match update {
+        RtrUpdate::Reset => return header(version, PDU_CACHE_RESET, 0, 8),
+        RtrUpdate::Full(session_id, serial, vrps, aspas) => (
+            session_id,
+            serial,
+            vrps,
+            vec![],
+            aspas
+                .into_iter()
+                .map(|(customer, providers)| (customer, Some(providers)))
+                .collect::<Vec<_>>(),
+        ),
+        RtrUpdate::Delta(session_id, delta) => (
+            session_id,
+            delta.serial,
+            delta.announced.into_iter().collect(),
+            delta.withdrawn.into_iter().collect(),
+            delta
+                .aspas
+                .into_iter()
+                .map(|(customer, change)| (customer, change.after))
+                .collect::<Vec<_>>(),
+        ),
+    };
+
+    let mut response = header(version, PDU_CACHE_RESPONSE, session_id, 8);
+    for vrp in &withdrawn {
+        response.append(&mut prefix(version, false, vrp));
+    }
+    for vrp in &announced {
+        response.append(&mut prefix(version, true, vrp));
+    }
+    if version >= RTR_VERSION_ASPA {
+        aspas.sort_by_key(|(customer, _)| *customer);
+        for (customer, providers) in &aspas {
+            response.append(&mut aspa(version, *customer, providers.as_ref()));
+        }
+    }
+    response.append(&mut end_of_data(config, version, session_id, serial));
+    response
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr;
+
+    use crate::test::{ca_handle, roa_payload};
+
+    fn config() -> RtrConfig {
+        RtrConfig {
+            listen: SocketAddr::from_str("127.0.0.1:3323").unwrap(),
+            refresh_interval: 3600,
+            retry_interval: 600,
+            expire_interval: 7200,
+        }
+    }
+
+    fn state() -> RtrState {
+        RtrState {
+            session_id: 1,
+            serial: 0,
+            cas: HashMap::new(),
+            vrps: HashSet::new(),
+            aspas: HashMap::new(),
+            deltas: VecDeque::new(),
+        }
+    }
+
+    fn vrps(payloads: &[&str]) -> HashSet<RoaPayload> {
+        payloads
+            .iter()
+            .map(|s| roa_payload(s).into_explicit_max_length())
+            .collect()
+    }
+
+    fn data(payloads: &[&str]) -> RtrCaData {
+        RtrCaData {
+            vrps: vrps(payloads),
+            aspas: HashMap::new(),
+        }
+    }
+
+    fn aspa_data(aspas: &[(u32, &[u32])]) -> RtrCaData {
+        RtrCaData {
+            vrps: HashSet::new(),
+            aspas: aspas
+                .iter()
+                .map(|(customer, providers)| (Asn::from_u32(*customer), asns(providers)))
+                .collect(),
+        }
+    }
+
+    fn asns(asns: &[u32]) -> Vec<Asn> {
+        asns.iter().copied().map(Asn::from_u32).collect()
+    }
+
+    #[test]
+    fn serial_increments_on_change() {
+        let mut state = state();
+        let ca1 = ca_handle("ca1");
+        let ca2 = ca_handle("ca2");
+
+        assert_eq!(state.update_ca(ca1.clone(), data(&["10.0.0.0/16 => 64496"])), Some(1));
+        assert_eq!(state.update_ca(ca1.clone(), data(&["10.0.0.0/16 => 64496"])), None);
+
+        // The same VRP in another CA does not change the union
+        assert_eq!(state.update_ca(ca2, data(&["10.0.0.0/16 => 64496"])), None);
+        assert_eq!(state.update_ca(ca1, data(&["10.1.0.0/16 => 64496"])), Some(2));
+
+        assert_eq!(state.vrps, vrps(&["10.0.0.0/16 => 64496", "10.1.0.0/16 => 64496"]));
+    }
+
+    #[test]
+    fn remove_deleted_ca() {
+        let cache = RtrCache::new(config());
+        let serials = cache.serial_sender.subscribe();
+        let ca = ca_handle("ca");
+
+        cache.set_data(&ca, data(&["10.0.0.0/16 => 64496"]));
+        cache.remove_ca(&ca);
+
+        assert_eq!(cache.session_and_serial().1, 2);
+        assert!(serials.has_changed().unwrap());
+        match cache.full_update() {
+            RtrUpdate::Full(_, serial, vrps, aspas) => {
+                assert_eq!(serial, 2);
+                assert!(vrps.is_empty());
+                assert!(aspas.is_empty());
+            }
+            _ => panic!("Expected full update"),
+        }
+    }
+
+    #[tokio::test]
+    async fn listener_stops_on_shutdown() {
+        let mut config = config();
+        config.listen = SocketAddr::from_str("127.0.0.1:0").unwrap();
+        let cache = Arc::new(RtrCache::new(config));
+
+        let (shutdown, shutdown_signal) = watch::channel(false);
+        let listener = tokio::spawn(rtr_listener(cache, shutdown_signal));
+
+        shutdown.send(true).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), listener)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn combined_deltas() {
+        let mut state = state();
+        let ca = ca_handle("ca");
+
+        state.update_ca(ca.clone(), data(&["10.0.0.0/16 => 64496"]));
+        state.update_ca(ca.clone(), data(&["10.0.0.0/16 => 64496", "10.1.0.0/16 => 64496"]));
+        state.update_ca(ca, data(&["10.1.0.0/16 => 64496", "2001:db8::/32 => 64496"]));
+
+        let expected = RtrDelta {
+            serial: 3,
+            announced: vrps(&["10.1.0.0/16 => 64496", "2001:db8::/32 => 64496"]),
+            withdrawn: vrps(&["10.0.0.0/16 => 64496"]),
+            aspas: HashMap::new(),
+        };
+        assert_eq!(state.update_since(1, 1), RtrUpdate::Delta(1, expected));
+
+        let up_to_date = RtrDelta {
+            serial: 3,
+            ..Default::default()
+        };
+        assert_eq!(state.update_since(1, 3), RtrUpdate::Delta(1, up_to_date));
+
+        // unknown serial or session
+        assert_eq!(state.update_since(1, 10), RtrUpdate::Reset);
+        assert_eq!(state.update_since(2, 3), RtrUpdate::Reset);
+    }
+
+    #[test]
+    fn combined_aspa_deltas() {
+        let mut state = state();
+        let ca1 = ca_handle("ca1");
+        let ca2 = ca_handle("ca2");
+
+        assert_eq!(state.update_ca(ca1.clone(), aspa_data(&[(64496, &[65001])])), Some(1));
+
+        // Providers for the same customer in another CA are combined
+        assert_eq!(
+            state.update_ca(ca2.clone(), aspa_data(&[(64496, &[65002, 65001])])),
+            Some(2)
+        );
+        assert_eq!(state.aspas.get(&Asn::from_u32(64496)), Some(&asns(&[65001, 65002])));
+
+        assert_eq!(state.update_ca(ca2, aspa_data(&[(64497, &[65003])])), Some(3));
+        assert_eq!(state.update_ca(ca1, RtrCaData::default()), Some(4));
+
+        // 64496 changed, and was then withdrawn, 64497 is new
+        let mut expected = HashMap::new();
+        expected.insert(
+            Asn::from_u32(64496),
+            RtrAspaChange {
+                before: Some(asns(&[65001])),
+                after: None,
+            },
+        );
+        expected.insert(
+            Asn::from_u32(64497),
+            RtrAspaChange {
+                before: None,
+                after: Some(asns(&[65003])),
+            },
+        );
+        match state.update_since(1, 1) {
+            RtrUpdate::Delta(_, delta) => {
+                assert_eq!(delta.serial, 4);
+                assert_eq!(delta.aspas, expected);
+            }
+            _ => panic!("Expected delta"),
+        }
+
+        // 64496 was added and withdrawn, so there is nothing to report
+        match state.update_since(1, 0) {
+            RtrUpdate::Delta(_, delta) => {
+                assert!(!delta.aspas.contains_key(&Asn::from_u32(64496)));
+                assert_eq!(delta.aspas.len(), 1);
+            }
+            _ => panic!("Expected delta"),
+        }
+    }
+
+    #[test]
+    fn encode_response() {
+        let vrp = roa_payload("10.0.0.0/16-24 => 64496");
+        let update = RtrUpdate::Full(7, 1, vec![vrp], HashMap::new());
+
+        let v0 = response(&config(), 0, update);
+        assert_eq!(v0.len(), 8 + 20 + 12);
+        assert_eq!(&v0[0..8], &[0, PDU_CACHE_RESPONSE, 0, 7, 0, 0, 0, 8]);
+        assert_eq!(
+            &v0[8..28],
+            &[
+                0,
+                PDU_IPV4_PREFIX,
+                0,
+                0,
+                0,
+                0,
+                0,
+                20,
+                1,
+                16,
+                24,
+                0,
+                10,
+                0,
+                0,
+                0,
+                0,
+                0,
+                251,
+                240
+            ]
+        );
+        assert_eq!(&v0[28..40], &[0, PDU_END_OF_DATA, 0, 7, 0, 0, 0, 12, 0, 0, 0, 1]);
+
+        let v1 = response(&config(), 1, RtrUpdate::Full(7, 1, vec![], HashMap::new()));
+        assert_eq!(v1.len(), 8 + 24);
+        assert_eq!(&v1[20..24], &3600_u32.to_be_bytes());
+
+        assert_eq!(
+            response(&config(), 1, RtrUpdate::Reset),
+            vec![1, PDU_CACHE_RESET, 0, 0, 0, 0, 0, 8]
+        );
+    }
+
+    #[test]
+    fn encode_aspa_response() {
+        let aspas = aspa_data(&[(64496, &[65001, 65002])]).aspas;
+
+        // ASPA is not conveyed in version 1
+        let v1 = response(&config(), 1, RtrUpdate::Full(7, 1, vec![], aspas.clone()));
+        assert_eq!(v1.len(), 8 + 24);
+
+        let v2 = response(&config(), 2, RtrUpdate::Full(7, 1, vec![], aspas));
+        assert_eq!(v2.len(), 8 + 20 + 24);
+        assert_eq!(
+            &v2[8..28],
+            &[2, PDU_ASPA, 1, 0, 0, 0, 0, 20, 0, 0, 251, 240, 0, 0, 253, 233, 0, 0, 253, 234]
+        );
+        assert_eq!(&v2[28..30], &[2, PDU_END_OF_DATA]);
+
+        let mut delta = RtrDelta {
+            serial: 2,
+            ..Default::default()
+        };
+        delta.aspas.insert(
+            Asn::from_u32(64496),
+            RtrAspaChange {
+                before: Some(asns(&[65001])),
+                after: None,
+            },
+        );
+        let withdraw = response(&config(), 2, RtrUpdate::Delta(7, delta));
+        assert_eq!(&withdraw[8..20], &[2, PDU_ASPA, 0, 0, 0, 0, 0, 12, 0, 0, 251, 240]);
+    }
+
+    #[test]
+    fn handle_queries() {
+        let cache = RtrCache::new(config());
+        let (session_id, _) = cache.session_and_serial();
+        let mut version = None;
+
+        // unsupported version
+        let reset_v3 = [3, PDU_RESET_QUERY, 0, 0, 0, 0, 0, 8];
+        let error = handle_pdu(&cache, &mut version, &reset_v3).unwrap_err();
+        assert_eq!(
+            &error[0..4],
+            &[RTR_VERSION_MAX, PDU_ERROR_REPORT, 0, ERR_UNSUPPORTED_VERSION as u8]
+        );
+        assert_eq!(version, None);
+
+        let reset_v1 = [1, PDU_RESET_QUERY, 0, 0, 0, 0, 0, 8];
+        let response = handle_pdu(&cache, &mut version, &reset_v1).unwrap();
+        assert_eq!(response[1], PDU_CACHE_RESPONSE);
+        assert_eq!(version, Some(1));
+
+        let mut serial_query = vec![1, PDU_SERIAL_QUERY];
+        serial_query.extend_from_slice(&session_id.to_be_bytes());
+        serial_query.extend_from_slice(&[0, 0, 0, 12, 0, 0, 0, 0]);
+        let response = handle_pdu(&cache, &mut version, &serial_query).unwrap();
+        assert_eq!(response.len(), 8 + 24);
+
+        // the version may not change in a session
+        let error = handle_pdu(&cache, &mut version, &[0, PDU_RESET_QUERY, 0, 0, 0, 0, 0, 8]).unwrap_err();
+        assert_eq!(error[1], PDU_ERROR_REPORT);
+    }
+}
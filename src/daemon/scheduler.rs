@@ -5,15 +5,19 @@ use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use tokio::time::sleep;
 
-use rpki::ca::{
-    idexchange::{CaHandle, ParentHandle},
-    provisioning::{ResourceClassName, RevocationRequest},
+use rpki::{
+    ca::{
+        idexchange::{CaHandle, ParentHandle},
+        provisioning::{ResourceClassName, RevocationRequest},
+    },
+    repository::x509::Time,
 };
 
 use crate::{
     commons::{actor::Actor, api::Timestamp, bgp::BgpAnalyser, KrillResult},
     constants::{
-        SCHEDULER_INTERVAL_RENEW_MINS, SCHEDULER_INTERVAL_REPUBLISH_MINS, SCHEDULER_RESYNC_REPO_CAS_THRESHOLD,
+        SCHEDULER_INTERVAL_RENEW_MINS, SCHEDULER_INTERVAL_REPUBLISH_MINS, SCHEDULER_INTERVAL_TA_PROXY_CHECK_HOURS,
+        SCHEDULER_INTERVAL_VALIDATE_REPO_HOURS, SCHEDULER_RESYNC_REPO_CAS_THRESHOLD,
         SCHEDULER_USE_JITTER_CAS_THRESHOLD,
     },
     daemon::{
@@ -73,10 +77,14 @@ impl Scheduler {
 
                     Task::SyncRepo { ca } => self.sync_repo(ca).await,
 
+                    Task::SyncRepoMirror { ca, mirror } => self.sync_repo_mirror(ca, mirror).await,
+
                     Task::SyncParent { ca, parent } => self.sync_parent(ca, parent).await,
 
                     Task::SuspendChildrenIfNeeded { ca } => self.suspend_children_if_needed(ca).await,
 
+                    Task::UpdateTimedRoas { ca } => self.update_timed_roas(ca).await,
+
                     Task::RepublishIfNeeded => self.republish_if_needed().await,
 
                     Task::RenewObjectsIfNeeded => self.renew_objects_if_needed().await,
@@ -90,6 +98,10 @@ impl Scheduler {
 
                     Task::RrdpUpdateIfNeeded => self.update_rrdp_if_needed(),
 
+                    Task::CheckTaProxy => self.check_ta_proxy(),
+
+                    Task::ValidateRepository => self.validate_repository().await,
+
                     Task::ResourceClassRemoved {
                         ca,
                         parent,
@@ -183,6 +195,13 @@ impl Scheduler {
             if self.config.suspend_child_after_inactive_seconds().is_some() {
                 self.tasks.suspend_children(ca.handle().clone(), now())
             }
+
+            // Plan the next activation or expiration of time-bounded ROAs, if any.
+            // If changes were missed while the server was down, then this will be
+            // 'now', or rather some time in the past.
+            if let Some(next) = ca.timed_roa_changes().next() {
+                self.tasks.update_timed_roas(ca.handle().clone(), next.into());
+            }
         }
 
         self.tasks.republish_if_needed(now());
@@ -194,6 +213,11 @@ impl Scheduler {
 
         self.tasks.update_snapshots(in_hours(24));
 
+        self.tasks.check_ta_proxy(now());
+
+        // Give CAs some time to synchronise with the repository first.
+        self.tasks.validate_repository(in_minutes(10));
+
         Ok(())
     }
 
@@ -218,6 +242,28 @@ impl Scheduler {
         Ok(())
     }
 
+    /// Try to synchronize a CA with one of its repository mirrors, reschedule if this fails
+    async fn sync_repo_mirror(&self, ca: CaHandle, mirror: String) -> KrillResult<()> {
+        debug!("Synchronize CA {} with repository mirror {}", ca, mirror);
+
+        if let Err(e) = self
+            .ca_manager
+            .cas_repo_mirror_sync_single(self.repo_manager.as_ref(), &ca, &mirror)
+            .await
+        {
+            let next = self.config.requeue_remote_failed();
+
+            error!(
+                "Failed to publish for '{}' to repository mirror '{}'. Will reschedule to: '{}'. Error: {}",
+                ca, mirror, next, e
+            );
+
+            self.tasks.sync_repo_mirror(ca, mirror, next);
+        }
+
+        Ok(())
+    }
+
     /// Try to synchronize a CA with a specific parent, reschedule if this fails
     async fn sync_parent(&self, ca: CaHandle, parent: ParentHandle) -> KrillResult<()> {
         info!("Synchronize CA '{}' with its parent '{}'", ca, parent);
@@ -249,6 +295,24 @@ impl Scheduler {
         Ok(())
     }
 
+    /// Activate scheduled, and remove expired, ROA configurations for a CA
+    async fn update_timed_roas(&self, ca: CaHandle) -> KrillResult<()> {
+        debug!("Verify if CA '{}' has time-bounded ROAs to activate or expire", ca);
+        match self.ca_manager.ca_routes_timed_update(&ca, &self.system_actor).await {
+            Ok(Some(next)) => self.tasks.update_timed_roas(ca, next.into()),
+            Ok(None) => {}
+            Err(e) => {
+                error!(
+                    "Could not update time-bounded ROAs for CA '{}', will retry in an hour. Error: {}",
+                    ca, e
+                );
+                self.tasks.update_timed_roas(ca, in_hours(1));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Let CAs that need it republish their CRL/MFT
     async fn republish_if_needed(&self) -> KrillResult<()> {
         let cas = self.ca_manager.republish_all(false).await?; // can only fail on critical errors
@@ -331,6 +395,57 @@ impl Scheduler {
         Ok(())
     }
 
+    /// Publish the objects of an offline Trust Anchor, if it is used, and
+    /// warn if they are about to expire. The proxy cannot re-issue the TA
+    /// manifest and CRL itself, so this needs the operator to exchange a
+    /// request and response with the signer in time.
+    fn check_ta_proxy(&self) -> KrillResult<()> {
+        match self.ca_manager.ta_proxy_status() {
+            Err(e) => error!("Could not check the Trust Anchor proxy: {}", e),
+            Ok(None) => {} // No proxy is used
+            Ok(Some(status)) => {
+                if let Err(e) = self.ca_manager.ta_proxy_repo_sync(&self.repo_manager) {
+                    error!("Could not publish Trust Anchor objects: {}", e);
+                }
+
+                match status.next_update() {
+                    None => warn!("Trust Anchor proxy did not receive a manifest and CRL from the signer yet"),
+                    Some(next_update) if next_update < Time::now() => error!(
+                        "Trust Anchor manifest and CRL expired at {}! Process a new request with the signer",
+                        next_update.to_rfc3339()
+                    ),
+                    Some(next_update) if status.needs_signer_update() => warn!(
+                        "Trust Anchor manifest and CRL will expire at {}. Process a new request with the signer",
+                        next_update.to_rfc3339()
+                    ),
+                    Some(_) => {}
+                }
+            }
+        }
+
+        self.tasks
+            .check_ta_proxy(in_hours(SCHEDULER_INTERVAL_TA_PROXY_CHECK_HOURS));
+
+        Ok(())
+    }
+
+    /// Validate the content of the repository, if this Krill instance has
+    /// a Publication Server, and record the issues found.
+    async fn validate_repository(&self) -> KrillResult<()> {
+        match self.ca_manager.validate_repository(&self.repo_manager).await {
+            Err(e) => error!("Could not validate the repository content: {}", e),
+            Ok(report) if !report.is_clean() => warn!(
+                "Found issues when validating the repository content, see 'krillc issues' and 'krillc pubserver server stats'"
+            ),
+            Ok(_) => debug!("Validated the repository content, no issues found"),
+        }
+
+        self.tasks
+            .validate_repository(in_hours(SCHEDULER_INTERVAL_VALIDATE_REPO_HOURS));
+
+        Ok(())
+    }
+
     async fn resource_class_removed(
         &self,
         ca: CaHandle,
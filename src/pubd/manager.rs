@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use bytes::Bytes;
 
@@ -15,14 +15,14 @@ use rpki::{
 use crate::{
     commons::{
         actor::Actor,
-        api::{PublicationServerUris, PublisherDetails},
+        api::{PublicationServerUris, PublisherDetails, ValidationIssue},
         crypto::KrillSigner,
         error::Error,
         util::cmslogger::CmsLogger,
         KrillResult,
     },
     daemon::{config::Config, mq::TaskQueue},
-    pubd::{RepoStats, RepositoryAccessProxy, RepositoryContentProxy},
+    pubd::{RepoStats, RepositoryAccessProxy, RepositoryContentProxy, RepositoryObjects},
 };
 
 use super::RrdpUpdateNeeded;
@@ -41,6 +41,10 @@ pub struct RepositoryManager {
 
     config: Arc<Config>,
     signer: Arc<KrillSigner>,
+
+    // The repository issues found when the content was last validated. These
+    // are not persisted, the content is validated again soon after start up.
+    validation_issues: RwLock<Vec<ValidationIssue>>,
 }
 
 /// # Constructing
@@ -58,6 +62,7 @@ impl RepositoryManager {
             tasks,
             config,
             signer,
+            validation_issues: RwLock::new(vec![]),
         })
     }
 }
@@ -193,7 +198,8 @@ impl RepositoryManager {
     }
 
     pub fn repo_stats(&self) -> KrillResult<RepoStats> {
-        self.content.stats()
+        let issues = self.validation_issues.read().unwrap().clone();
+        self.content.stats().map(|stats| stats.with_validation_issues(issues))
     }
 
     /// Returns a list reply for a known publisher in a repository.
@@ -254,6 +260,21 @@ impl RepositoryManager {
     }
 }
 
+/// # Validating the repository content
+///
+impl RepositoryManager {
+    /// Returns the objects in the repository, as written to disk for rsync
+    /// and as included in the current RRDP snapshot.
+    pub fn repository_objects(&self) -> KrillResult<RepositoryObjects> {
+        self.content.repository_objects()
+    }
+
+    /// Replaces the repository issues found when validating its content.
+    pub fn set_validation_issues(&self, issues: Vec<ValidationIssue>) {
+        *self.validation_issues.write().unwrap() = issues;
+    }
+}
+
 //------------ Tests ---------------------------------------------------------
 
 #[cfg(test)]
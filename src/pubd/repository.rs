@@ -7,6 +7,7 @@ use std::{
     sync::{Arc, RwLock},
 };
 
+use bytes::Bytes;
 use chrono::Duration;
 use rpki::{
     ca::{
@@ -31,7 +32,7 @@ use crate::{
             },
             IdCertInfo,
         },
-        api::{PublicationServerUris, StorableRepositoryCommand},
+        api::{PublicationServerUris, StorableRepositoryCommand, ValidationIssue},
         crypto::KrillSigner,
         error::{Error, KrillIoError},
         eventsourcing::{Aggregate, AggregateStore, WalChange, WalCommand, WalSet, WalStore, WalSupport},
@@ -209,6 +210,13 @@ impl RepositoryContentProxy {
         let content = self.get_default_content()?;
         content.objects_for_publisher(name).map(|o| o.clone())
     }
+
+    /// Get the objects in the repository, as written to disk for rsync and
+    /// as included in the current RRDP snapshot.
+    pub fn repository_objects(&self) -> KrillResult<RepositoryObjects> {
+        let content = self.get_default_content()?;
+        content.repository_objects()
+    }
 }
 
 //------------ RepositoryContentCommand ------------------------------------
@@ -470,6 +478,19 @@ impl RepositoryContent {
         self.rsync.write(self.rrdp.serial, self.rrdp.snapshot())
     }
 
+    pub fn repository_objects(&self) -> KrillResult<RepositoryObjects> {
+        let rsync = self.rsync.read()?;
+        let rrdp = self
+            .rrdp
+            .snapshot()
+            .elements()
+            .into_iter()
+            .map(|publish| (publish.uri().clone(), publish.base64().to_bytes()))
+            .collect();
+
+        Ok(RepositoryObjects::new(self.rsync.base_uri.clone(), rsync, rrdp))
+    }
+
     fn add_publisher(&self, publisher: PublisherHandle) -> KrillResult<Vec<RepositoryContentChange>> {
         Ok(vec![RepositoryContentChange::PublisherAdded { publisher }])
     }
@@ -538,6 +559,7 @@ impl RepositoryContent {
             last_update: Some(self.rrdp.last_update),
             rsync_base: self.rsync.base_uri.clone(),
             rrdp_base: self.rrdp.rrdp_base_uri.clone(),
+            validation_issues: vec![],
         }
     }
 
@@ -672,6 +694,67 @@ impl RsyncdStore {
     }
 }
 
+/// # Reading
+///
+impl RsyncdStore {
+    /// Reads all files in the current rsync directory, as they would be
+    /// served by rsyncd.
+    pub fn read(&self) -> KrillResult<HashMap<uri::Rsync, Bytes>> {
+        let _lock = self
+            .lock
+            .read()
+            .map_err(|_| Error::custom("Could not get read lock for rsync repo"))?;
+
+        let mut current_dir = self.rsync_dir.clone();
+        current_dir.push("current");
+
+        if !current_dir.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let files = file::crawl_incl_rsync_base(&current_dir, &self.base_uri)
+            .map_err(|e| Error::Custom(format!("Could not read rsync repo: {}", e)))?;
+
+        Ok(files
+            .into_iter()
+            .map(|file| (file.uri().clone(), file.to_bytes()))
+            .collect())
+    }
+}
+
+//------------ RepositoryObjects ---------------------------------------------
+
+/// The objects in the repository, as written to disk for rsync, and as
+/// included in the current RRDP snapshot. These are expected to be the same.
+#[derive(Clone, Debug)]
+pub struct RepositoryObjects {
+    rsync_base: uri::Rsync,
+    rsync: HashMap<uri::Rsync, Bytes>,
+    rrdp: HashMap<uri::Rsync, Bytes>,
+}
+
+impl RepositoryObjects {
+    pub fn new(rsync_base: uri::Rsync, rsync: HashMap<uri::Rsync, Bytes>, rrdp: HashMap<uri::Rsync, Bytes>) -> Self {
+        RepositoryObjects {
+            rsync_base,
+            rsync,
+            rrdp,
+        }
+    }
+
+    pub fn rsync_base(&self) -> &uri::Rsync {
+        &self.rsync_base
+    }
+
+    pub fn rsync(&self) -> &HashMap<uri::Rsync, Bytes> {
+        &self.rsync
+    }
+
+    pub fn rrdp(&self) -> &HashMap<uri::Rsync, Bytes> {
+        &self.rrdp
+    }
+}
+
 //------------ RrdpUpdateNeeded ----------------------------------------------
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -1609,6 +1692,8 @@ pub struct RepoStats {
     last_update: Option<Time>,
     rsync_base: uri::Rsync,
     rrdp_base: uri::Https,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    validation_issues: Vec<ValidationIssue>,
 }
 
 impl RepoStats {
@@ -1669,6 +1754,16 @@ impl RepoStats {
     pub fn session(&self) -> RrdpSession {
         self.session
     }
+
+    /// Sets the repository issues found when its content was last validated.
+    pub fn with_validation_issues(mut self, issues: Vec<ValidationIssue>) -> Self {
+        self.validation_issues = issues;
+        self
+    }
+
+    pub fn validation_issues(&self) -> &Vec<ValidationIssue> {
+        &self.validation_issues
+    }
 }
 
 impl fmt::Display for RepoStats {
@@ -1699,6 +1794,14 @@ impl fmt::Display for RepoStats {
             )?;
         }
 
+        if !self.validation_issues.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Validation issues:")?;
+            for issue in &self.validation_issues {
+                writeln!(f, "    {}", issue)?;
+            }
+        }
+
         Ok(())
     }
 }
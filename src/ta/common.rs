@@ -0,0 +1,340 @@
+//! Types exchanged between the online Trust Anchor proxy and the offline
+//! Trust Anchor signer.
+
+use std::fmt;
+
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Serialize};
+
+use rpki::{
+    ca::{
+        idexchange::{ChildHandle, RepoInfo},
+        provisioning::{IssuanceRequest, RevocationRequest},
+        publication::Base64,
+    },
+    crypto::{KeyIdentifier, PublicKey, RpkiSignature, RpkiSignatureAlgorithm},
+    repository::{resources::ResourceSet, x509::Time},
+};
+
+use crate::commons::{
+    api::{rrdp::PublishElement, IdCertInfo, IssuedCertificate, TaCertDetails},
+    crypto::KrillSigner,
+    error::Error,
+    KrillResult,
+};
+
+//------------ TrustAnchorProxyInfo ----------------------------------------
+
+/// The information about the online proxy that the signer needs when it is
+/// initialised: the ID certificate used to verify requests, and the
+/// repository where the TA objects will be published.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorProxyInfo {
+    id: IdCertInfo,
+    repo_info: RepoInfo,
+}
+
+impl TrustAnchorProxyInfo {
+    pub fn new(id: IdCertInfo, repo_info: RepoInfo) -> Self {
+        TrustAnchorProxyInfo { id, repo_info }
+    }
+
+    pub fn id(&self) -> &IdCertInfo {
+        &self.id
+    }
+
+    pub fn repo_info(&self) -> &RepoInfo {
+        &self.repo_info
+    }
+}
+
+impl fmt::Display for TrustAnchorProxyInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string_pretty(self).unwrap())
+    }
+}
+
+//------------ TrustAnchorSignerInfo ---------------------------------------
+
+/// The information about the offline signer that the proxy needs: the ID
+/// certificate used to verify responses, and the TA certificate and TAL.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorSignerInfo {
+    id: IdCertInfo,
+    ta_cert_details: TaCertDetails,
+}
+
+impl TrustAnchorSignerInfo {
+    pub fn new(id: IdCertInfo, ta_cert_details: TaCertDetails) -> Self {
+        TrustAnchorSignerInfo { id, ta_cert_details }
+    }
+
+    pub fn id(&self) -> &IdCertInfo {
+        &self.id
+    }
+
+    pub fn ta_cert_details(&self) -> &TaCertDetails {
+        &self.ta_cert_details
+    }
+}
+
+impl fmt::Display for TrustAnchorSignerInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string_pretty(self).unwrap())
+    }
+}
+
+//------------ ProvisioningRequest -----------------------------------------
+
+/// An RFC 6492 request from a child CA which needs the TA key.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisioningRequest {
+    Issuance(IssuanceRequest),
+    Revocation(RevocationRequest),
+}
+
+impl ProvisioningRequest {
+    /// The key of the child CA that this request is about.
+    pub fn key(&self) -> KeyIdentifier {
+        match self {
+            ProvisioningRequest::Issuance(req) => req.csr().public_key().key_identifier(),
+            ProvisioningRequest::Revocation(req) => req.key(),
+        }
+    }
+}
+
+//------------ TrustAnchorChildRequests ------------------------------------
+
+/// The pending requests for a child CA, and the resources it is entitled to.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorChildRequests {
+    child: ChildHandle,
+    resources: ResourceSet,
+    requests: Vec<ProvisioningRequest>,
+}
+
+impl TrustAnchorChildRequests {
+    pub fn new(child: ChildHandle, resources: ResourceSet, requests: Vec<ProvisioningRequest>) -> Self {
+        TrustAnchorChildRequests {
+            child,
+            resources,
+            requests,
+        }
+    }
+
+    pub fn child(&self) -> &ChildHandle {
+        &self.child
+    }
+
+    pub fn resources(&self) -> &ResourceSet {
+        &self.resources
+    }
+
+    pub fn requests(&self) -> &Vec<ProvisioningRequest> {
+        &self.requests
+    }
+}
+
+//------------ TrustAnchorSignerRequest ------------------------------------
+
+/// A request from the proxy to the signer. The signer will always re-issue
+/// the TA manifest and CRL, and process the requests for child CAs, if any.
+///
+/// The sequence number is increased by the proxy for each request. The
+/// signer only processes requests with a sequence number higher than that
+/// of the last request it processed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorSignerRequest {
+    nonce: String,
+    sequence: u64,
+    children: Vec<TrustAnchorChildRequests>,
+}
+
+impl TrustAnchorSignerRequest {
+    pub fn new(sequence: u64, children: Vec<TrustAnchorChildRequests>) -> Self {
+        let nonce = uuid::Uuid::new_v4().to_string();
+        TrustAnchorSignerRequest {
+            nonce,
+            sequence,
+            children,
+        }
+    }
+
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn children(&self) -> &Vec<TrustAnchorChildRequests> {
+        &self.children
+    }
+}
+
+//------------ TrustAnchorChildResponses -----------------------------------
+
+/// The certificates issued to, and keys revoked for, a child CA.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorChildResponses {
+    child: ChildHandle,
+    issued: Vec<IssuedCertificate>,
+    revoked: Vec<KeyIdentifier>,
+}
+
+impl TrustAnchorChildResponses {
+    pub fn new(child: ChildHandle, issued: Vec<IssuedCertificate>, revoked: Vec<KeyIdentifier>) -> Self {
+        TrustAnchorChildResponses { child, issued, revoked }
+    }
+
+    pub fn child(&self) -> &ChildHandle {
+        &self.child
+    }
+
+    pub fn issued(&self) -> &Vec<IssuedCertificate> {
+        &self.issued
+    }
+
+    pub fn revoked(&self) -> &Vec<KeyIdentifier> {
+        &self.revoked
+    }
+}
+
+//------------ TrustAnchorObjects ------------------------------------------
+
+/// The complete set of objects to publish for the TA, and the time when
+/// the manifest and CRL in this set will expire.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorObjects {
+    elements: Vec<PublishElement>,
+    next_update: Time,
+}
+
+impl TrustAnchorObjects {
+    pub fn new(elements: Vec<PublishElement>, next_update: Time) -> Self {
+        TrustAnchorObjects { elements, next_update }
+    }
+
+    pub fn elements(&self) -> &Vec<PublishElement> {
+        &self.elements
+    }
+
+    pub fn next_update(&self) -> Time {
+        self.next_update
+    }
+}
+
+//------------ TrustAnchorSignerResponse -----------------------------------
+
+/// The response from the signer to a [`TrustAnchorSignerRequest`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorSignerResponse {
+    nonce: String,
+    objects: TrustAnchorObjects,
+    children: Vec<TrustAnchorChildResponses>,
+}
+
+impl TrustAnchorSignerResponse {
+    pub fn new(nonce: String, objects: TrustAnchorObjects, children: Vec<TrustAnchorChildResponses>) -> Self {
+        TrustAnchorSignerResponse {
+            nonce,
+            objects,
+            children,
+        }
+    }
+
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    pub fn objects(&self) -> &TrustAnchorObjects {
+        &self.objects
+    }
+
+    pub fn children(&self) -> &Vec<TrustAnchorChildResponses> {
+        &self.children
+    }
+}
+
+//------------ TrustAnchorSignedMessage ------------------------------------
+
+/// A request or response, exchanged as a file between the proxy and the
+/// signer. The JSON content is signed with the ID key of the sender.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorSignedMessage {
+    content: Base64,
+    signature: Base64,
+}
+
+impl TrustAnchorSignedMessage {
+    pub fn sign<T: Serialize>(content: &T, key: &KeyIdentifier, signer: &KrillSigner) -> KrillResult<Self> {
+        let content = serde_json::to_vec(content).map_err(Error::JsonError)?;
+        let signature = signer.sign(key, &content)?;
+
+        Ok(TrustAnchorSignedMessage {
+            content: Base64::from_content(&content),
+            signature: Base64::from_content(signature.value().as_ref()),
+        })
+    }
+
+    /// Verifies the signature using the public key of the expected sender,
+    /// and returns the content.
+    pub fn validate<T: DeserializeOwned>(&self, key: &PublicKey) -> KrillResult<T> {
+        let content = self.content.to_bytes();
+        let signature = RpkiSignature::new(RpkiSignatureAlgorithm::default(), self.signature.to_bytes());
+
+        key.verify(content.as_ref(), &signature)
+            .map_err(|_| Error::custom("Invalid signature on Trust Anchor message"))?;
+
+        serde_json::from_slice(content.as_ref()).map_err(Error::JsonError)
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        Bytes::from(serde_json::to_vec_pretty(self).unwrap())
+    }
+}
+
+impl fmt::Display for TrustAnchorSignedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string_pretty(self).unwrap())
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    use crate::{commons::crypto::KrillSignerBuilder, daemon::config::ConfigDefaults, test};
+
+    #[test]
+    fn signed_message_round_trip() {
+        test::test_under_tmp(|d| {
+            let signers = ConfigDefaults::signers();
+            let signer = KrillSignerBuilder::new(&d, Duration::from_secs(1), &signers)
+                .build()
+                .unwrap();
+
+            let key = signer.create_key().unwrap();
+            let other = signer.create_key().unwrap();
+
+            let request = TrustAnchorSignerRequest::new(1, vec![]);
+            let signed = TrustAnchorSignedMessage::sign(&request, &key, &signer).unwrap();
+
+            let decoded: TrustAnchorSignedMessage = serde_json::from_slice(signed.to_bytes().as_ref()).unwrap();
+
+            let validated: TrustAnchorSignerRequest = decoded.validate(&signer.get_key_info(&key).unwrap()).unwrap();
+            assert_eq!(validated, request);
+
+            assert!(decoded
+                .validate::<TrustAnchorSignerRequest>(&signer.get_key_info(&other).unwrap())
+                .is_err());
+        });
+    }
+}
@@ -0,0 +1,68 @@
+//! Configuration for the offline Trust Anchor signer (`krillta`).
+
+use std::{path::PathBuf, time::Duration};
+
+use crate::{
+    commons::{crypto::KrillSigner, crypto::KrillSignerBuilder, error::Error, util::file, KrillResult},
+    daemon::config::{ConfigDefaults, IssuanceTimingConfig, SignerConfig},
+};
+
+//------------ TrustAnchorSignerConfig -------------------------------------
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TrustAnchorSignerConfig {
+    /// The directory where the signer keeps its state, and its keys when
+    /// the default OpenSSL based signer is used.
+    pub data_dir: PathBuf,
+
+    /// The signers to use for the TA key. If more than one signer is
+    /// configured, then the first one will be used.
+    #[serde(default = "ConfigDefaults::signers")]
+    pub signers: Vec<SignerConfig>,
+
+    /// The number of weeks that the TA manifest and CRL will be valid for.
+    /// They are re-issued every time a request from the proxy is processed.
+    #[serde(default = "TrustAnchorSignerConfig::dflt_ta_mft_next_update_weeks")]
+    pub ta_mft_next_update_weeks: u32,
+
+    /// The number of weeks that certificates issued to child CAs will be
+    /// valid for.
+    #[serde(default = "TrustAnchorSignerConfig::dflt_ta_child_certificate_valid_weeks")]
+    pub ta_child_certificate_valid_weeks: u32,
+}
+
+impl TrustAnchorSignerConfig {
+    fn dflt_ta_mft_next_update_weeks() -> u32 {
+        12
+    }
+
+    fn dflt_ta_child_certificate_valid_weeks() -> u32 {
+        52
+    }
+
+    pub fn parse(config_file: &str) -> KrillResult<Self> {
+        let bytes = file::read(&PathBuf::from(config_file))?;
+        let config: TrustAnchorSignerConfig = toml::from_slice(bytes.as_ref())
+            .map_err(|e| Error::ConfigError(format!("Cannot parse '{}': {}", config_file, e)))?;
+
+        if config.signers.is_empty() {
+            return Err(Error::ConfigError("No signers configured".to_string()));
+        }
+
+        Ok(config)
+    }
+
+    pub fn signer(&self) -> KrillResult<KrillSigner> {
+        file::create_dir_all(&self.data_dir)?;
+
+        let signer = &self.signers[0];
+        KrillSignerBuilder::new(&self.data_dir, Duration::from_secs(1), &self.signers)
+            .with_default_signer(signer)
+            .with_one_off_signer(signer)
+            .build()
+    }
+
+    pub fn issuance_timing(&self) -> IssuanceTimingConfig {
+        IssuanceTimingConfig::for_ta_signer(self.ta_mft_next_update_weeks, self.ta_child_certificate_valid_weeks)
+    }
+}
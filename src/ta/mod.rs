@@ -0,0 +1,19 @@
+//! Support for an offline Trust Anchor.
+//!
+//! The online Krill server runs a Trust Anchor proxy, which acts as the
+//! parent for the TA child CA(s) and publishes the TA objects. The TA key
+//! itself is kept by a signer, managed using the `krillta` binary on a
+//! separate - typically offline - host. The proxy and signer exchange
+//! signed requests and responses as files.
+
+mod common;
+pub use self::common::*;
+
+mod config;
+pub use self::config::*;
+
+mod proxy;
+pub use self::proxy::*;
+
+mod signer;
+pub use self::signer::*;
@@ -0,0 +1,579 @@
+//! The online Trust Anchor proxy.
+//!
+//! The proxy acts as the parent for the child CA(s) of the Trust Anchor,
+//! and publishes the TA objects. It does not have access to the TA key.
+//! Instead it queues the RFC 6492 requests from its children, and it
+//! creates signed requests for the offline signer. The signed responses
+//! from the signer are imported again to update the issued certificates,
+//! and the manifest and CRL.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use bytes::Bytes;
+use chrono::Duration;
+
+use rpki::{
+    ca::{
+        idexchange::{ChildHandle, RecipientHandle, RepoInfo, SenderHandle},
+        provisioning,
+        provisioning::{
+            ProvisioningCms, ResourceClassEntitlements, ResourceClassListResponse, ResourceClassName,
+            RevocationResponse, SigningCert,
+        },
+    },
+    crypto::KeyIdentifier,
+    repository::{resources::ResourceSet, x509::Time},
+};
+
+use crate::{
+    commons::{
+        api::{IdCertInfo, IssuedCertificate},
+        crypto::KrillSigner,
+        error::Error,
+        eventsourcing::{KeyStoreKey, KeyValueStore},
+        KrillResult,
+    },
+    constants::TA_OBJECTS_EXPIRY_WARNING_WEEKS,
+    daemon::{
+        ca::{ta_handle, Rfc8183Id},
+        config::IssuanceTimingConfig,
+    },
+    ta::{
+        ProvisioningRequest, TrustAnchorChildRequests, TrustAnchorObjects, TrustAnchorProxyInfo,
+        TrustAnchorSignedMessage, TrustAnchorSignerInfo, TrustAnchorSignerRequest, TrustAnchorSignerResponse,
+    },
+};
+
+//------------ TrustAnchorProxy --------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorProxy {
+    // The ID used for RFC 6492 with children, and to sign requests for
+    // the signer.
+    id: Rfc8183Id,
+
+    // The repository where the TA objects are published.
+    repo_info: RepoInfo,
+
+    // The signer, once it has been associated with this proxy.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    signer: Option<TrustAnchorSignerInfo>,
+
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    children: HashMap<ChildHandle, TrustAnchorChild>,
+
+    // The request that was last given to the signer, if no response
+    // was imported for it yet.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    open_request: Option<TrustAnchorSignerRequest>,
+
+    // The sequence number of the last request made for the signer.
+    #[serde(default)]
+    last_sequence: u64,
+
+    // The objects to publish, as received in the last signer response.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    objects: Option<TrustAnchorObjects>,
+}
+
+/// # Create and configure
+///
+impl TrustAnchorProxy {
+    /// Creates a new proxy. The ID is generated separately, because it is
+    /// needed to add the proxy as a publisher to the repository first.
+    pub fn new(id: Rfc8183Id, repo_info: RepoInfo) -> Self {
+        TrustAnchorProxy {
+            id,
+            repo_info,
+            signer: None,
+            children: HashMap::new(),
+            open_request: None,
+            last_sequence: 0,
+            objects: None,
+        }
+    }
+
+    pub fn id_cert(&self) -> &IdCertInfo {
+        self.id.cert()
+    }
+
+    /// Returns the information needed to initialise the signer.
+    pub fn info(&self) -> TrustAnchorProxyInfo {
+        TrustAnchorProxyInfo::new(self.id.cert().clone(), self.repo_info.clone())
+    }
+
+    pub fn signer_info(&self) -> KrillResult<&TrustAnchorSignerInfo> {
+        self.signer.as_ref().ok_or(Error::TaProxyHasNoSigner)
+    }
+
+    pub fn has_signer(&self) -> bool {
+        self.signer.is_some()
+    }
+
+    pub fn add_signer(&mut self, signer: TrustAnchorSignerInfo) -> KrillResult<()> {
+        if self.signer.is_some() {
+            Err(Error::TaProxyAlreadyHasSigner)
+        } else {
+            self.signer = Some(signer);
+            Ok(())
+        }
+    }
+
+    /// Adds a child. The signer must be known, so that we can verify that
+    /// the child's resources are held by the TA.
+    pub fn add_child(&mut self, child: ChildHandle, id_cert: IdCertInfo, resources: ResourceSet) -> KrillResult<()> {
+        let ta_resources = self.signer_info()?.ta_cert_details().cert().resources();
+
+        if resources.is_empty() {
+            Err(Error::CaChildMustHaveResources(ta_handle(), child))
+        } else if !ta_resources.contains(&resources) {
+            Err(Error::CaChildExtraResources(ta_handle(), child))
+        } else {
+            match self.children.entry(child) {
+                Entry::Occupied(entry) => Err(Error::CaChildDuplicate(ta_handle(), entry.key().clone())),
+                Entry::Vacant(entry) => {
+                    info!(
+                        "Trust Anchor proxy added child '{}' with resources '{}'",
+                        entry.key(),
+                        resources
+                    );
+                    entry.insert(TrustAnchorChild::new(id_cert, resources));
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Returns the objects to publish, if any were received from the signer.
+    pub fn objects(&self) -> Option<&TrustAnchorObjects> {
+        self.objects.as_ref()
+    }
+
+    pub fn status(&self) -> TrustAnchorProxyStatus {
+        TrustAnchorProxyStatus {
+            has_signer: self.signer.is_some(),
+            children: self.children.len(),
+            pending_requests: self.children.values().map(|child| child.requests.len()).sum(),
+            open_request: self.open_request.as_ref().map(|req| req.nonce().to_string()),
+            next_update: self.objects.as_ref().map(|objects| objects.next_update()),
+        }
+    }
+}
+
+/// # Being a parent
+///
+impl TrustAnchorProxy {
+    pub fn verify_rfc6492(&self, cms: ProvisioningCms) -> KrillResult<provisioning::Message> {
+        let child_handle = cms.message().sender().convert();
+        let child = self.get_child(&child_handle)?;
+
+        cms.validate(child.id_cert.public_key()).map_err(Error::Rfc6492)?;
+
+        Ok(cms.into_message())
+    }
+
+    pub fn sign_rfc6492_response(&self, message: provisioning::Message, signer: &KrillSigner) -> KrillResult<Bytes> {
+        signer
+            .create_rfc6492_cms(message, &self.id.cert().public_key().key_identifier())
+            .map(|res| res.to_bytes())
+            .map_err(Error::signer)
+    }
+
+    /// Processes an RFC 6492 request from a child and returns the unsigned
+    /// response.
+    ///
+    /// Certificates which were already issued by the signer are returned
+    /// directly. Other issuance and revocation requests are queued for the
+    /// signer, and the child is told that its request is scheduled for
+    /// processing. The child will ask again when it next synchronises with
+    /// this proxy.
+    pub fn process_rfc6492(
+        &mut self,
+        req_msg: provisioning::Message,
+        issuance_timing: &IssuanceTimingConfig,
+    ) -> KrillResult<provisioning::Message> {
+        let (sender, recipient, payload) = req_msg.unpack();
+        let child_handle = sender.convert();
+
+        let signer = self.signer.as_ref().ok_or(Error::TaProxyHasNoSigner)?;
+        let child = self
+            .children
+            .get_mut(&child_handle)
+            .ok_or_else(|| Error::CaChildUnknown(ta_handle(), child_handle.clone()))?;
+
+        match payload {
+            provisioning::Payload::List => {
+                let classes = child.entitlement_class(signer, issuance_timing)?.into_iter().collect();
+                let entitlements = ResourceClassListResponse::new(classes);
+                Ok(provisioning::Message::list_response(sender, recipient, entitlements))
+            }
+            provisioning::Payload::Issue(req) => {
+                if req.class_name() != &ResourceClassName::default() {
+                    return Self::not_performed(sender, recipient, provisioning::NotPerformedResponse::err_1201());
+                }
+
+                let key = req.csr().public_key().key_identifier();
+                let resources = signer
+                    .ta_cert_details()
+                    .cert()
+                    .resources()
+                    .intersection(&child.resources);
+
+                let current = child.issued.get(&key).filter(|issued| {
+                    issued.resources() == &resources
+                        && issued.expires() > issuance_timing.new_child_cert_issuance_threshold()
+                });
+
+                if current.is_some() {
+                    let response = child
+                        .entitlement_class(signer, issuance_timing)?
+                        .and_then(|class| class.into_issuance_response(req.csr().public_key()))
+                        .ok_or(Error::KeyUseNoIssuedCert)?;
+                    Ok(provisioning::Message::issue_response(sender, recipient, response))
+                } else {
+                    info!(
+                        "Trust Anchor proxy queued certificate request for key '{}' of child '{}'",
+                        key, child_handle
+                    );
+                    child.add_request(ProvisioningRequest::Issuance(req));
+                    Self::not_performed(sender, recipient, provisioning::NotPerformedResponse::err_1104())
+                }
+            }
+            provisioning::Payload::Revoke(req) => {
+                if child.issued.contains_key(&req.key()) {
+                    info!(
+                        "Trust Anchor proxy queued revocation request for key '{}' of child '{}'",
+                        req.key(),
+                        child_handle
+                    );
+                    child.add_request(ProvisioningRequest::Revocation(req));
+                    Self::not_performed(sender, recipient, provisioning::NotPerformedResponse::err_1104())
+                } else {
+                    // Nothing (left) to revoke, so the child can consider this done.
+                    let response = RevocationResponse::from(&req);
+                    Ok(provisioning::Message::revoke_response(sender, recipient, response))
+                }
+            }
+            _ => Err(Error::custom("Unsupported RFC6492 message")),
+        }
+    }
+
+    fn not_performed(
+        sender: SenderHandle,
+        recipient: RecipientHandle,
+        not_performed: provisioning::NotPerformedResponse,
+    ) -> KrillResult<provisioning::Message> {
+        provisioning::Message::not_performed_response(sender, recipient, not_performed)
+            .map_err(|_| Error::custom("Could not create RFC6492 not performed response"))
+    }
+
+    fn get_child(&self, child: &ChildHandle) -> KrillResult<&TrustAnchorChild> {
+        self.children
+            .get(child)
+            .ok_or_else(|| Error::CaChildUnknown(ta_handle(), child.clone()))
+    }
+}
+
+/// # Exchanges with the signer
+///
+impl TrustAnchorProxy {
+    /// Creates a new request for the signer, including all pending child
+    /// requests. The manifest and CRL will be re-issued by the signer even
+    /// if there are no pending child requests.
+    pub fn make_signer_request(&mut self) -> KrillResult<TrustAnchorSignerRequest> {
+        self.signer_info()?;
+
+        if self.open_request.is_some() {
+            return Err(Error::TaProxyHasOpenRequest);
+        }
+
+        let children = self
+            .children
+            .iter()
+            .filter(|(_, child)| !child.requests.is_empty())
+            .map(|(handle, child)| {
+                TrustAnchorChildRequests::new(handle.clone(), child.resources.clone(), child.requests.clone())
+            })
+            .collect();
+
+        let sequence = self.last_sequence + 1;
+        let request = TrustAnchorSignerRequest::new(sequence, children);
+        self.last_sequence = sequence;
+        self.open_request = Some(request.clone());
+
+        Ok(request)
+    }
+
+    /// Returns the open request for the signer, signed by this proxy.
+    pub fn signed_open_request(&self, signer: &KrillSigner) -> KrillResult<TrustAnchorSignedMessage> {
+        let request = self.open_request.as_ref().ok_or(Error::TaProxyHasNoOpenRequest)?;
+        TrustAnchorSignedMessage::sign(request, &self.id.cert().public_key().key_identifier(), signer)
+    }
+
+    /// Processes a signed response from the signer for the open request.
+    /// Returns the children which received new certificates or had keys
+    /// revoked, so that they can be told to synchronise.
+    pub fn process_signer_response(&mut self, signed: &TrustAnchorSignedMessage) -> KrillResult<Vec<ChildHandle>> {
+        let signer = self.signer_info()?;
+        let response: TrustAnchorSignerResponse = signed.validate(signer.id().public_key())?;
+
+        let request = self.open_request.take().ok_or(Error::TaProxyHasNoOpenRequest)?;
+        if request.nonce() != response.nonce() {
+            let expected = request.nonce().to_string();
+            let found = response.nonce().to_string();
+            self.open_request = Some(request);
+            return Err(Error::TaProxyResponseNonceMismatch(expected, found));
+        }
+
+        // Requests that were included in the request are now handled. Any
+        // requests received after the request was made remain pending.
+        for child_requests in request.children() {
+            if let Some(child) = self.children.get_mut(child_requests.child()) {
+                child.requests.retain(|req| !child_requests.requests().contains(req));
+            }
+        }
+
+        let mut updated = vec![];
+
+        for child_responses in response.children() {
+            let child_handle = child_responses.child();
+            match self.children.get_mut(child_handle) {
+                None => warn!(
+                    "Trust Anchor signer response includes unknown child '{}', ignoring",
+                    child_handle
+                ),
+                Some(child) => {
+                    for issued in child_responses.issued() {
+                        child.issued.insert(issued.key_identifier(), issued.clone());
+                    }
+                    for key in child_responses.revoked() {
+                        child.issued.remove(key);
+                    }
+                    updated.push(child_handle.clone());
+                }
+            }
+        }
+
+        self.objects = Some(response.objects().clone());
+
+        Ok(updated)
+    }
+}
+
+//------------ TrustAnchorChild --------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct TrustAnchorChild {
+    id_cert: IdCertInfo,
+    resources: ResourceSet,
+
+    // Requests which still need to be processed by the signer.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    requests: Vec<ProvisioningRequest>,
+
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    issued: HashMap<KeyIdentifier, IssuedCertificate>,
+}
+
+impl TrustAnchorChild {
+    fn new(id_cert: IdCertInfo, resources: ResourceSet) -> Self {
+        TrustAnchorChild {
+            id_cert,
+            resources,
+            requests: vec![],
+            issued: HashMap::new(),
+        }
+    }
+
+    /// Adds a request, replacing any earlier request for the same key.
+    fn add_request(&mut self, request: ProvisioningRequest) {
+        let key = request.key();
+        self.requests.retain(|req| req.key() != key);
+        self.requests.push(request);
+    }
+
+    /// Returns the entitlements for this child, using the same logic for
+    /// the not after time as a normal parent CA.
+    fn entitlement_class(
+        &self,
+        signer: &TrustAnchorSignerInfo,
+        issuance_timing: &IssuanceTimingConfig,
+    ) -> KrillResult<Option<ResourceClassEntitlements>> {
+        let ta_rcvd_cert = signer.ta_cert_details().cert();
+        let ta_cert = ta_rcvd_cert
+            .to_cert()
+            .map_err(|e| Error::Custom(format!("Issue with Trust Anchor certificate, error: {}", e)))?;
+
+        let signing_cert = SigningCert::new(ta_rcvd_cert.uri().clone(), ta_cert);
+
+        let child_resources = ta_rcvd_cert.resources().intersection(&self.resources);
+        if child_resources.is_empty() {
+            return Ok(None);
+        }
+
+        let mut not_after = issuance_timing.new_child_cert_not_after();
+        let threshold = issuance_timing.new_child_cert_issuance_threshold();
+
+        let mut issued_certs = vec![];
+        for issued in self.issued.values() {
+            issued_certs.push(issued.to_rfc6492_issued_cert().map_err(|e| {
+                Error::Custom(format!(
+                    "Issue with certificate issued by Trust Anchor, published at '{}', error: {} ",
+                    issued.uri(),
+                    e
+                ))
+            })?);
+
+            let expires = issued.expires();
+            if expires > threshold {
+                not_after = expires;
+            }
+        }
+
+        Ok(Some(ResourceClassEntitlements::new(
+            ResourceClassName::default(),
+            child_resources,
+            not_after,
+            issued_certs,
+            signing_cert,
+        )))
+    }
+}
+
+//------------ TrustAnchorProxyStatus --------------------------------------
+
+/// An overview of the state of the proxy, and a warning in case the TA
+/// manifest and CRL will expire soon.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorProxyStatus {
+    has_signer: bool,
+    children: usize,
+    pending_requests: usize,
+    open_request: Option<String>,
+    next_update: Option<Time>,
+}
+
+impl TrustAnchorProxyStatus {
+    pub fn has_signer(&self) -> bool {
+        self.has_signer
+    }
+
+    pub fn children(&self) -> usize {
+        self.children
+    }
+
+    pub fn pending_requests(&self) -> usize {
+        self.pending_requests
+    }
+
+    pub fn open_request(&self) -> Option<&String> {
+        self.open_request.as_ref()
+    }
+
+    pub fn next_update(&self) -> Option<Time> {
+        self.next_update
+    }
+
+    /// Returns true if the TA manifest and CRL will expire within the
+    /// warning threshold, or if there are none yet.
+    pub fn needs_signer_update(&self) -> bool {
+        match self.next_update {
+            None => true,
+            Some(next_update) => next_update < Time::now() + Duration::weeks(TA_OBJECTS_EXPIRY_WARNING_WEEKS),
+        }
+    }
+}
+
+impl fmt::Display for TrustAnchorProxyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Signer associated: {}", self.has_signer)?;
+        writeln!(f, "Children: {}", self.children)?;
+        writeln!(f, "Pending child requests: {}", self.pending_requests)?;
+        match &self.open_request {
+            Some(nonce) => writeln!(f, "Open signer request: {}", nonce)?,
+            None => writeln!(f, "Open signer request: none")?,
+        }
+        match self.next_update {
+            None => writeln!(f, "WARNING: no TA manifest and CRL were received from the signer yet")?,
+            Some(next_update) => {
+                writeln!(f, "TA manifest and CRL next update: {}", next_update.to_rfc3339())?;
+                if next_update < Time::now() {
+                    writeln!(f, "ERROR: the TA manifest and CRL have expired!")?;
+                } else if self.needs_signer_update() {
+                    writeln!(
+                        f,
+                        "WARNING: the TA manifest and CRL will expire soon, process a new signer request"
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//------------ TrustAnchorProxyStore ---------------------------------------
+
+/// Keeps the state of the proxy on disk. The proxy is updated as a whole,
+/// under a lock, and saved after each change.
+pub struct TrustAnchorProxyStore {
+    store: Arc<RwLock<KeyValueStore>>,
+}
+
+impl TrustAnchorProxyStore {
+    pub fn disk(work_dir: &Path, namespace: &str) -> KrillResult<Self> {
+        let store = KeyValueStore::disk(work_dir, namespace)?;
+        Ok(TrustAnchorProxyStore {
+            store: Arc::new(RwLock::new(store)),
+        })
+    }
+
+    fn key() -> KeyStoreKey {
+        KeyStoreKey::simple("proxy.json".to_string())
+    }
+
+    pub fn is_initialized(&self) -> KrillResult<bool> {
+        self.store
+            .read()
+            .unwrap()
+            .has(&Self::key())
+            .map_err(Error::KeyValueError)
+    }
+
+    pub fn init(&self, proxy: TrustAnchorProxy) -> KrillResult<()> {
+        let store = self.store.write().unwrap();
+        if store.has(&Self::key())? {
+            Err(Error::TaProxyAlreadyInitialized)
+        } else {
+            store.store(&Self::key(), &proxy)?;
+            Ok(())
+        }
+    }
+
+    pub fn get(&self) -> KrillResult<TrustAnchorProxy> {
+        self.store
+            .read()
+            .unwrap()
+            .get(&Self::key())?
+            .ok_or(Error::TaProxyNotInitialized)
+    }
+
+    /// Updates the proxy using the given function, and saves it unless the
+    /// function returns an error.
+    pub fn with_proxy<F, T>(&self, op: F) -> KrillResult<T>
+    where
+        F: FnOnce(&mut TrustAnchorProxy) -> KrillResult<T>,
+    {
+        let store = self.store.write().unwrap();
+
+        let mut proxy: TrustAnchorProxy = store.get(&Self::key())?.ok_or(Error::TaProxyNotInitialized)?;
+        let res = op(&mut proxy)?;
+        store.store(&Self::key(), &proxy)?;
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,315 @@
+//! The offline Trust Anchor signer.
+//!
+//! The signer holds the TA key. It is managed through the `krillta` binary,
+//! which keeps its state in a JSON file in its data directory, on a host
+//! which need not be connected to any network.
+
+use std::{collections::HashMap, convert::TryFrom, path::Path};
+
+use rpki::{ca::idexchange::ChildHandle, crypto::KeyIdentifier, repository::x509::Time, uri};
+
+use crate::{
+    commons::{
+        api::{rrdp::PublishElement, IdCertInfo, IssuedCertificate, TaCertDetails},
+        crypto::{CsrInfo, KrillSigner, SignSupport},
+        error::Error,
+        util::file,
+        KrillResult,
+    },
+    daemon::{
+        ca::{CertifiedKey, ChildCertificateUpdates, KeyObjectSet, Rfc8183Id},
+        config::IssuanceTimingConfig,
+    },
+    ta::{
+        ProvisioningRequest, TrustAnchorChildRequests, TrustAnchorChildResponses, TrustAnchorObjects,
+        TrustAnchorProxyInfo, TrustAnchorSignedMessage, TrustAnchorSignerInfo, TrustAnchorSignerRequest,
+        TrustAnchorSignerResponse,
+    },
+};
+
+const TA_SIGNER_FILE: &str = "ta_signer.json";
+
+//------------ TrustAnchorSigner -------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TrustAnchorSigner {
+    // The ID used to sign responses for the proxy.
+    id: Rfc8183Id,
+
+    // The ID certificate of the proxy, used to verify its requests.
+    proxy_id: IdCertInfo,
+
+    ta_cert_details: TaCertDetails,
+
+    // The manifest, CRL and issued certificates of the TA key.
+    objects: KeyObjectSet,
+
+    // The current certificates issued to each child, by key.
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    children: HashMap<ChildHandle, HashMap<KeyIdentifier, IssuedCertificate>>,
+
+    // The sequence number of the last processed request, so that neither
+    // this request nor any earlier request can be processed again.
+    #[serde(default)]
+    last_sequence: u64,
+}
+
+/// # Create, load and save
+///
+impl TrustAnchorSigner {
+    /// Creates a new TA key and self-signed certificate, and an initial
+    /// manifest and CRL.
+    ///
+    /// The certificate will be published by the proxy, so its rsync URI
+    /// must be inside the proxy's repository. If no rsync URI is given then
+    /// "ta.cer" under the base URI of the proxy's repository is used.
+    pub fn create(
+        proxy: TrustAnchorProxyInfo,
+        tal_https: Vec<uri::Https>,
+        tal_rsync: Option<uri::Rsync>,
+        timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<Self> {
+        let base_uri = proxy.repo_info().base_uri();
+
+        let tal_rsync = match tal_rsync {
+            Some(uri) => uri,
+            None => base_uri
+                .join(b"ta.cer")
+                .map_err(|e| Error::Custom(format!("Cannot derive TA certificate URI: {}", e)))?,
+        };
+
+        if tal_rsync.relative_to(base_uri).is_none() {
+            return Err(Error::publishing_outside_jail(&tal_rsync, base_uri));
+        }
+
+        let id = Rfc8183Id::generate(signer)?;
+        let key = signer.create_key()?;
+
+        let ta_cert_details = SignSupport::make_ta_cert_details(&key, tal_https, tal_rsync, proxy.repo_info(), signer)?;
+
+        let certified_key = CertifiedKey::create(ta_cert_details.cert().clone());
+        let objects = KeyObjectSet::create(&certified_key, timing, signer)?;
+
+        Ok(TrustAnchorSigner {
+            id,
+            proxy_id: proxy.id().clone(),
+            ta_cert_details,
+            objects,
+            children: HashMap::new(),
+            last_sequence: 0,
+        })
+    }
+
+    /// Returns true if a signer was initialised in the given data directory.
+    pub fn exists(data_dir: &Path) -> bool {
+        data_dir.join(TA_SIGNER_FILE).exists()
+    }
+
+    pub fn load(data_dir: &Path) -> KrillResult<Self> {
+        file::load_json(&data_dir.join(TA_SIGNER_FILE)).map_err(Error::IoError)
+    }
+
+    pub fn save(&self, data_dir: &Path) -> KrillResult<()> {
+        file::save_json(self, &data_dir.join(TA_SIGNER_FILE)).map_err(Error::IoError)
+    }
+}
+
+/// # Process requests
+///
+impl TrustAnchorSigner {
+    pub fn info(&self) -> TrustAnchorSignerInfo {
+        TrustAnchorSignerInfo::new(self.id.cert().clone(), self.ta_cert_details.clone())
+    }
+
+    /// Returns the time when the current manifest and CRL will expire.
+    pub fn next_update(&self) -> Time {
+        self.objects.next_update()
+    }
+
+    /// Processes a signed request from the proxy and returns a signed
+    /// response. The manifest and CRL are always re-issued, so that the
+    /// proxy can send a request without any child requests just to renew
+    /// them.
+    ///
+    /// A request with a sequence number that is not higher than that of
+    /// the last processed request is refused, so that a replayed request
+    /// cannot re-issue or revoke certificates.
+    pub fn process(
+        &mut self,
+        signed_request: &TrustAnchorSignedMessage,
+        timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<TrustAnchorSignedMessage> {
+        let request: TrustAnchorSignerRequest = signed_request.validate(self.proxy_id.public_key())?;
+
+        if request.sequence() <= self.last_sequence {
+            return Err(Error::TaSignerRequestReplayed(request.sequence(), self.last_sequence));
+        }
+
+        let mut cert_updates = ChildCertificateUpdates::default();
+        let mut child_responses = vec![];
+
+        for child_requests in request.children() {
+            let response = self.process_child_requests(child_requests, &mut cert_updates, timing, signer)?;
+            child_responses.push(response);
+        }
+
+        self.objects.update_certs(&cert_updates);
+        self.objects.reissue(timing, signer)?;
+        self.last_sequence = request.sequence();
+
+        let response = TrustAnchorSignerResponse::new(request.nonce().to_string(), self.objects(), child_responses);
+        TrustAnchorSignedMessage::sign(&response, &self.id.cert().public_key().key_identifier(), signer)
+    }
+
+    fn process_child_requests(
+        &mut self,
+        child_requests: &TrustAnchorChildRequests,
+        cert_updates: &mut ChildCertificateUpdates,
+        timing: &IssuanceTimingConfig,
+        signer: &KrillSigner,
+    ) -> KrillResult<TrustAnchorChildResponses> {
+        let child = child_requests.child();
+        let signing_key = CertifiedKey::create(self.ta_cert_details.cert().clone());
+
+        let mut issued = vec![];
+        let mut revoked = vec![];
+
+        for request in child_requests.requests() {
+            match request {
+                ProvisioningRequest::Issuance(issuance_request) => {
+                    let (_, limit, csr) = issuance_request.clone().unpack();
+                    let csr = CsrInfo::try_from(&csr)?;
+
+                    let cert = SignSupport::make_issued_cert(
+                        csr,
+                        child_requests.resources(),
+                        limit,
+                        &signing_key,
+                        timing.new_child_cert_validity(),
+                        signer,
+                    )?;
+
+                    info!("Issued certificate '{}' to child '{}'", cert.name(), child);
+
+                    self.children
+                        .entry(child.clone())
+                        .or_default()
+                        .insert(cert.key_identifier(), cert.clone());
+                    cert_updates.issue(cert.clone());
+                    issued.push(cert);
+                }
+                ProvisioningRequest::Revocation(revocation_request) => {
+                    let key = revocation_request.key();
+                    let was_issued = self
+                        .children
+                        .get_mut(child)
+                        .and_then(|certs| certs.remove(&key))
+                        .is_some();
+
+                    if was_issued {
+                        info!("Revoked certificate for key '{}' of child '{}'", key, child);
+                        cert_updates.remove(key);
+                    }
+                    revoked.push(key);
+                }
+            }
+        }
+
+        Ok(TrustAnchorChildResponses::new(child.clone(), issued, revoked))
+    }
+
+    /// Returns all objects to publish, i.e. the manifest, CRL and issued
+    /// certificates, as well as the TA certificate itself.
+    fn objects(&self) -> TrustAnchorObjects {
+        let ta_cert = self.ta_cert_details.cert();
+
+        let mut elements = self.objects.publish_elements();
+        elements.push(PublishElement::new(
+            ta_cert.base64().clone(),
+            self.ta_cert_details.tal().rsync_uri().clone(),
+        ));
+
+        TrustAnchorObjects::new(elements, self.objects.next_update())
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, time::Duration};
+
+    use rpki::ca::idexchange::RepoInfo;
+
+    use super::*;
+
+    use crate::{commons::crypto::KrillSignerBuilder, daemon::config::ConfigDefaults, test};
+
+    #[test]
+    fn process_request_without_children() {
+        test::test_under_tmp(|d| {
+            let signers = ConfigDefaults::signers();
+            let signer = KrillSignerBuilder::new(&d, Duration::from_secs(1), &signers)
+                .build()
+                .unwrap();
+            let timing = IssuanceTimingConfig::for_ta_signer(12, 52);
+
+            let proxy_id = Rfc8183Id::generate(&signer).unwrap();
+            let repo_info = RepoInfo::new(test::rsync("rsync://localhost/repo/ta/"), None);
+            let proxy = TrustAnchorProxyInfo::new(proxy_id.cert().clone(), repo_info);
+
+            let tal_https = vec![test::https("https://localhost/ta/ta.cer")];
+
+            let outside = uri::Rsync::from_str("rsync://localhost/other/ta.cer").unwrap();
+            assert!(
+                TrustAnchorSigner::create(proxy.clone(), tal_https.clone(), Some(outside), &timing, &signer).is_err()
+            );
+
+            let mut ta_signer = TrustAnchorSigner::create(proxy, tal_https, None, &timing, &signer).unwrap();
+            ta_signer.save(&d).unwrap();
+            assert_eq!(TrustAnchorSigner::load(&d).unwrap(), ta_signer);
+
+            let proxy_key = proxy_id.cert().public_key().key_identifier();
+            let request = TrustAnchorSignerRequest::new(1, vec![]);
+            let signed_request = TrustAnchorSignedMessage::sign(&request, &proxy_key, &signer).unwrap();
+
+            let signed_response = ta_signer.process(&signed_request, &timing, &signer).unwrap();
+            let response: TrustAnchorSignerResponse =
+                signed_response.validate(ta_signer.info().id().public_key()).unwrap();
+
+            assert_eq!(response.nonce(), request.nonce());
+            assert!(response.children().is_empty());
+
+            // manifest, CRL and the TA certificate
+            assert_eq!(response.objects().elements().len(), 3);
+            assert!(response.objects().next_update() > Time::now() + chrono::Duration::weeks(11));
+
+            // The same request cannot be processed again, also not after a reload
+            ta_signer.save(&d).unwrap();
+            let mut ta_signer = TrustAnchorSigner::load(&d).unwrap();
+            assert!(matches!(
+                ta_signer.process(&signed_request, &timing, &signer),
+                Err(Error::TaSignerRequestReplayed(1, 1))
+            ));
+
+            // Once a later request is processed, the earlier request still
+            // cannot be replayed
+            let later_request = TrustAnchorSignerRequest::new(2, vec![]);
+            let signed_later_request = TrustAnchorSignedMessage::sign(&later_request, &proxy_key, &signer).unwrap();
+            ta_signer.process(&signed_later_request, &timing, &signer).unwrap();
+
+            assert!(matches!(
+                ta_signer.process(&signed_request, &timing, &signer),
+                Err(Error::TaSignerRequestReplayed(1, 2))
+            ));
+
+            // A request signed by another key is rejected
+            let signed_by_ta =
+                TrustAnchorSignedMessage::sign(&request, &ta_signer.info().id().public_key().key_identifier(), &signer)
+                    .unwrap();
+            assert!(ta_signer.process(&signed_by_ta, &timing, &signer).is_err());
+        });
+    }
+}
@@ -22,33 +22,41 @@ use rpki::{
         provisioning::ResourceClassName,
     },
     crypto::KeyIdentifier,
-    repository::resources::ResourceSet,
+    repository::resources::{Asn, ResourceSet},
     uri,
 };
 
 use crate::{
     cli::{
-        options::{BulkCaCommand, CaCommand, Command, Options, PubServerCommand},
+        options::{BulkCaCommand, CaCommand, Command, Options, PubServerCommand, TrustAnchorCommand},
         report::{ApiResponse, ReportFormat},
         {Error, KrillClient},
     },
     commons::{
         api::{
-            AddChildRequest, AspaCustomer, AspaDefinition, AspaDefinitionList, AspaProvidersUpdate, BgpSecAsnKey,
-            BgpSecCsrInfoList, BgpSecDefinition, CertAuthInfo, CertAuthInit, CertifiedKeyInfo, ConfiguredRoa,
-            ConfiguredRoas, ObjectName, ParentCaContact, ParentCaReq, ParentStatuses, PublicationServerUris,
-            PublisherDetails, PublisherList, ResourceClassKeysInfo, RoaConfiguration, RoaConfigurationUpdates,
-            RoaPayload, RtaList, RtaName, RtaPrepResponse, TypedPrefix, UpdateChildRequest,
+            AddChildRequest, AspaCustomer, AspaDefinition, AspaDefinitionList, AspaImportAction, AspaImportReport,
+            AspaImportRequest, AspaProvidersUpdate, BgpSecAsnKey, BgpSecCsrInfoList, BgpSecDefinition, BgpSecRouterKey,
+            BgpSecRouterKeyRequest, CaPublishedObjects, CaRepoDetails, CertAuthInfo, CertAuthInit, CertAuthIssues,
+            CertifiedKeyInfo, ConfiguredRoa, ConfiguredRoas, GhostbusterContact, ObjectName, OnboardingToken,
+            OnboardingTokenId, OnboardingTokenList, OnboardingTokenRequest, ParentCaContact, ParentCaReq,
+            ParentStatuses, PrefixListDefinition, PrefixListDefinitionList, PublicationServerUris, PublisherDetails,
+            PublisherList, RepoStatus, RepositoryValidationReport, ResourceClassKeysInfo, RoaConfiguration,
+            RoaConfigurationFilter, RoaConfigurationUpdates, RoaPayload, RtaList, RtaName, RtaPrepResponse,
+            TimedRoaChanges, Timestamp, TypedPrefix, UpdateChildRequest,
         },
-        bgp::{Announcement, BgpAnalysisReport, BgpAnalysisSuggestion},
+        bgp::{Announcement, BgpAnalysisReport, BgpAnalysisSuggestion, ResourceShrinkReport},
         crypto::SignSupport,
-        util::httpclient,
+        util::{file, httpclient},
     },
     daemon::{
-        ca::{ResourceTaggedAttestation, RtaContentRequest, RtaPrepareRequest},
+        ca::{
+            GeofeedSignRequest, ResourceTaggedAttestation, RscRequest, RtaContentRequest, RtaPrepareRequest,
+            SignedObjectVerification, SignedObjectVerifyRequest,
+        },
         config::Config,
         http::server,
     },
+    ta::{TrustAnchorProxyInfo, TrustAnchorProxyStatus, TrustAnchorSignedMessage, TrustAnchorSignerInfo},
 };
 
 #[cfg(test)]
@@ -181,6 +189,17 @@ async fn start_krill_with_error_trap(config: Arc<Config>) {
 /// Starts a krill pubd for testing on its own port, and its
 /// own temp dir for storage.
 pub async fn start_krill_pubd(rrdp_delta_rrdp_delta_min_interval_seconds: u32) -> PathBuf {
+    let rsync_base = uri::Rsync::from_str("rsync://localhost/dedicated-repo/").unwrap();
+    start_krill_pubd_with_rsync_base(rrdp_delta_rrdp_delta_min_interval_seconds, rsync_base).await
+}
+
+/// Starts a krill pubd for testing on its own port, and its own temp
+/// dir for storage, using the given rsync base URI. Using the same base
+/// as the embedded repository lets it act as a mirror for that repository.
+pub async fn start_krill_pubd_with_rsync_base(
+    rrdp_delta_rrdp_delta_min_interval_seconds: u32,
+    rsync_base: uri::Rsync,
+) -> PathBuf {
     let dir = tmp_dir();
     let mut config = test_config(&dir, false, false, false, true);
     config.rrdp_updates_config.rrdp_delta_interval_min_seconds = rrdp_delta_rrdp_delta_min_interval_seconds;
@@ -192,7 +211,6 @@ pub async fn start_krill_pubd(rrdp_delta_rrdp_delta_min_interval_seconds: u32) -
 
     // Initialize the repository using separate URIs
     let uris = {
-        let rsync_base = uri::Rsync::from_str("rsync://localhost/dedicated-repo/").unwrap();
         let rrdp_base_uri = uri::Https::from_str("https://localhost:3001/test-rrdp/").unwrap();
         PublicationServerUris::new(rrdp_base_uri, rsync_base)
     };
@@ -433,6 +451,105 @@ pub async fn delete_parent(ca: &CaHandle, parent: &CaHandle) {
     krill_admin(Command::CertAuth(CaCommand::RemoveParent(ca.clone(), parent.convert()))).await;
 }
 
+pub async fn ca_parent_limit_set(ca: &CaHandle, parent: &ParentHandle, limit: ResourceSet) {
+    krill_admin(Command::CertAuth(CaCommand::ParentResourceLimitSet(
+        ca.clone(),
+        parent.clone(),
+        limit,
+    )))
+    .await;
+}
+
+pub async fn ca_parent_limit_remove(ca: &CaHandle, parent: &ParentHandle) {
+    krill_admin(Command::CertAuth(CaCommand::ParentResourceLimitRemove(
+        ca.clone(),
+        parent.clone(),
+    )))
+    .await;
+}
+
+pub async fn ca_parent_limit_show(ca: &CaHandle, parent: &ParentHandle) -> Option<ResourceSet> {
+    match krill_admin(Command::CertAuth(CaCommand::ParentResourceLimitShow(
+        ca.clone(),
+        parent.clone(),
+    )))
+    .await
+    {
+        ApiResponse::ParentResourceLimit(limit) => limit,
+        _ => panic!("Expected parent resource limit"),
+    }
+}
+
+pub async fn ca_published_objects(ca: &CaHandle) -> CaPublishedObjects {
+    match krill_admin(Command::CertAuth(CaCommand::PublishedObjects(ca.clone()))).await {
+        ApiResponse::PublishedObjects(objects) => objects,
+        _ => panic!("Expected published objects"),
+    }
+}
+
+pub async fn ca_published_object_download(ca: &CaHandle, key: KeyIdentifier, name: ObjectName, out: &Path) -> Bytes {
+    krill_admin(Command::CertAuth(CaCommand::PublishedObjectDownload(
+        ca.clone(),
+        key,
+        name,
+        out.to_path_buf(),
+    )))
+    .await;
+    file::read(out).unwrap()
+}
+
+pub async fn ca_issues(ca: &CaHandle) -> CertAuthIssues {
+    match krill_admin(Command::CertAuth(CaCommand::Issues(Some(ca.clone())))).await {
+        ApiResponse::CertAuthIssues(issues) => issues,
+        _ => panic!("Expected CA issues"),
+    }
+}
+
+pub async fn repository_validate() -> RepositoryValidationReport {
+    match krill_embedded_pubd_admin(PubServerCommand::RepositoryValidate).await {
+        ApiResponse::RepositoryValidation(report) => report,
+        _ => panic!("Expected repository validation report"),
+    }
+}
+
+pub async fn ca_resource_shrinks(ca: &CaHandle) -> ResourceShrinkReport {
+    match krill_admin(Command::CertAuth(CaCommand::ResourceShrinks(ca.clone()))).await {
+        ApiResponse::ResourceShrinks(report) => report,
+        _ => panic!("Expected resource shrink report"),
+    }
+}
+
+pub async fn ca_resource_shrinks_clear(ca: &CaHandle) {
+    krill_admin(Command::CertAuth(CaCommand::ResourceShrinksClear(ca.clone()))).await;
+}
+
+pub async fn ca_onboarding_token_add(ca: &CaHandle, resources: ResourceSet) -> OnboardingToken {
+    let req = OnboardingTokenRequest::new(resources, Timestamp::now_plus_hours(1));
+    match krill_admin(Command::CertAuth(CaCommand::OnboardingTokenAdd(ca.clone(), req))).await {
+        ApiResponse::OnboardingToken(token) => token,
+        _ => panic!("Expected onboarding token"),
+    }
+}
+
+pub async fn ca_onboarding_token_remove(ca: &CaHandle, id: OnboardingTokenId) {
+    krill_admin(Command::CertAuth(CaCommand::OnboardingTokenRemove(ca.clone(), id))).await;
+}
+
+pub async fn ca_onboarding_tokens(ca: &CaHandle) -> OnboardingTokenList {
+    match krill_admin(Command::CertAuth(CaCommand::OnboardingTokenList(ca.clone()))).await {
+        ApiResponse::OnboardingTokens(tokens) => tokens,
+        _ => panic!("Expected onboarding tokens"),
+    }
+}
+
+pub async fn ca_parent_onboard(ca: &CaHandle, token: OnboardingToken) {
+    krill_admin(Command::CertAuth(CaCommand::ParentOnboard(ca.clone(), token))).await;
+}
+
+pub async fn ca_parent_onboard_expect_error(ca: &CaHandle, token: OnboardingToken) -> Error {
+    krill_admin_expect_error(Command::CertAuth(CaCommand::ParentOnboard(ca.clone(), token))).await
+}
+
 pub async fn ca_route_authorizations_update(ca: &CaHandle, updates: RoaConfigurationUpdates) {
     krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsUpdate(
         ca.clone(),
@@ -458,12 +575,46 @@ pub async fn ca_route_authorizations_update_expect_error(ca: &CaHandle, updates:
 }
 
 pub async fn ca_configured_roas(ca: &CaHandle) -> ConfiguredRoas {
-    match krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsList(ca.clone()))).await {
+    ca_configured_roas_filtered(ca, RoaConfigurationFilter::default()).await
+}
+
+pub async fn ca_configured_roas_filtered(ca: &CaHandle, filter: RoaConfigurationFilter) -> ConfiguredRoas {
+    match krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsList(
+        ca.clone(),
+        filter,
+    )))
+    .await
+    {
         ApiResponse::RouteAuthorizations(roas) => roas,
         _ => panic!("Expected configured ROAs"),
     }
 }
 
+pub async fn ca_timed_roa_changes(ca: &CaHandle) -> TimedRoaChanges {
+    match krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsSchedule(ca.clone()))).await {
+        ApiResponse::TimedRoaChanges(changes) => changes,
+        _ => panic!("Expected timed ROA changes"),
+    }
+}
+
+/// Waits up to the given number of seconds for a configured ROA with
+/// the payload to be present, or absent. Returns false on timeout.
+pub async fn will_have_configured_roa(ca: &CaHandle, payload: RoaPayload, present: bool, secs: u64) -> bool {
+    for _ in 0..secs {
+        let found = ca_configured_roas(ca)
+            .await
+            .unpack()
+            .iter()
+            .any(|configured| configured.payload() == payload);
+
+        if found == present {
+            return true;
+        }
+        sleep_seconds(1).await;
+    }
+    false
+}
+
 pub async fn ca_route_authorizations_suggestions(ca: &CaHandle) -> BgpAnalysisSuggestion {
     match krill_admin(Command::CertAuth(CaCommand::BgpAnalysisSuggest(ca.clone(), None))).await {
         ApiResponse::BgpAnalysisSuggestions(suggestion) => suggestion,
@@ -487,6 +638,14 @@ pub async fn ca_bgpsec_add(ca: &CaHandle, definition: BgpSecDefinition) {
     krill_admin(Command::CertAuth(CaCommand::BgpSecAdd(ca.clone(), definition))).await;
 }
 
+pub async fn ca_bgpsec_generate(ca: &CaHandle, asn: Asn, passphrase: &str) -> BgpSecRouterKey {
+    let request = BgpSecRouterKeyRequest::new(asn, passphrase.to_string());
+    match krill_admin(Command::CertAuth(CaCommand::BgpSecGenerate(ca.clone(), request))).await {
+        ApiResponse::BgpSecRouterKey(router_key) => router_key,
+        _ => panic!("Expected BGPSec router key"),
+    }
+}
+
 pub async fn ca_bgpsec_add_expect_error(ca: &CaHandle, definition: BgpSecDefinition) {
     krill_admin_expect_error(Command::CertAuth(CaCommand::BgpSecAdd(ca.clone(), definition))).await;
 }
@@ -503,6 +662,48 @@ pub async fn ca_bgpsec_list(ca: &CaHandle) -> BgpSecCsrInfoList {
     }
 }
 
+pub async fn ca_prefix_lists_add(ca: &CaHandle, definition: PrefixListDefinition) {
+    krill_admin(Command::CertAuth(CaCommand::PrefixListsAddOrReplace(
+        ca.clone(),
+        definition,
+    )))
+    .await;
+}
+
+pub async fn ca_prefix_lists_add_expect_error(ca: &CaHandle, definition: PrefixListDefinition) {
+    krill_admin_expect_error(Command::CertAuth(CaCommand::PrefixListsAddOrReplace(
+        ca.clone(),
+        definition,
+    )))
+    .await;
+}
+
+pub async fn ca_prefix_lists_remove(ca: &CaHandle, asn: Asn) {
+    krill_admin(Command::CertAuth(CaCommand::PrefixListsRemove(ca.clone(), asn))).await;
+}
+
+pub async fn ca_prefix_lists_list(ca: &CaHandle) -> PrefixListDefinitionList {
+    match krill_admin(Command::CertAuth(CaCommand::PrefixListsList(ca.clone()))).await {
+        ApiResponse::PrefixListDefinitions(list) => list,
+        _ => panic!("Expected prefix list definitions"),
+    }
+}
+
+pub async fn ca_ghostbuster_set(ca: &CaHandle, contact: GhostbusterContact) {
+    krill_admin(Command::CertAuth(CaCommand::GhostbusterSet(ca.clone(), contact))).await;
+}
+
+pub async fn ca_ghostbuster_remove(ca: &CaHandle) {
+    krill_admin(Command::CertAuth(CaCommand::GhostbusterRemove(ca.clone()))).await;
+}
+
+pub async fn ca_ghostbuster_show(ca: &CaHandle) -> Option<GhostbusterContact> {
+    match krill_admin(Command::CertAuth(CaCommand::GhostbusterShow(ca.clone()))).await {
+        ApiResponse::GhostbusterContact(contact) => contact,
+        _ => panic!("Expected Ghostbuster contact"),
+    }
+}
+
 pub async fn ca_aspas_add(ca: &CaHandle, aspa: AspaDefinition) {
     krill_admin(Command::CertAuth(CaCommand::AspasAddOrReplace(ca.clone(), aspa))).await;
 }
@@ -527,6 +728,14 @@ pub async fn ca_aspas_remove(ca: &CaHandle, customer: AspaCustomer) {
     krill_admin(Command::CertAuth(CaCommand::AspasRemove(ca.clone(), customer))).await;
 }
 
+pub async fn ca_aspas_import(ca: &CaHandle, relationships: &str, action: AspaImportAction) -> AspaImportReport {
+    let request = AspaImportRequest::new(relationships.to_string(), action);
+    match krill_admin(Command::CertAuth(CaCommand::AspasImport(ca.clone(), request))).await {
+        ApiResponse::AspaImportReport(report) => report,
+        _ => panic!("Expected ASPA import report"),
+    }
+}
+
 pub async fn ca_details(ca: &CaHandle) -> CertAuthInfo {
     match krill_admin(Command::CertAuth(CaCommand::Show(ca.clone()))).await {
         ApiResponse::CertAuthInfo(inf) => inf,
@@ -541,6 +750,46 @@ pub async fn ca_details_krill2(ca: &CaHandle) -> CertAuthInfo {
     }
 }
 
+pub async fn rsc_sign(ca: CaHandle, request: RscRequest, out: &Path) -> Bytes {
+    krill_admin(Command::CertAuth(CaCommand::RscSign(ca, request, out.to_path_buf()))).await;
+    file::read(out).unwrap()
+}
+
+pub async fn rsc_sign_expect_error(ca: CaHandle, request: RscRequest, out: &Path) {
+    krill_admin_expect_error(Command::CertAuth(CaCommand::RscSign(ca, request, out.to_path_buf()))).await;
+}
+
+pub async fn geofeed_sign(ca: CaHandle, request: GeofeedSignRequest) -> String {
+    match krill_admin(Command::CertAuth(CaCommand::GeofeedSign(ca, request, None))).await {
+        ApiResponse::GenericBody(geofeed) => geofeed,
+        _ => panic!("Expected signed geofeed"),
+    }
+}
+
+pub async fn geofeed_sign_expect_error(ca: CaHandle, request: GeofeedSignRequest) {
+    krill_admin_expect_error(Command::CertAuth(CaCommand::GeofeedSign(ca, request, None))).await;
+}
+
+pub async fn ta_tal() -> String {
+    httpclient::get_text(&format!("{}ta/ta.tal", KRILL_SERVER_URI), None)
+        .await
+        .unwrap()
+}
+
+pub async fn ta_cer(file_name: &str) -> Bytes {
+    httpclient::get_binary(&format!("{}ta/{}", KRILL_SERVER_URI, file_name), None)
+        .await
+        .unwrap()
+}
+
+pub async fn verify_signed_object(object: Bytes, tals: Vec<String>) -> SignedObjectVerification {
+    let request = SignedObjectVerifyRequest::new(object, tals, vec![], vec![]);
+    match krill_admin(Command::Verify(request)).await {
+        ApiResponse::SignedObjectVerification(verification) => verification,
+        _ => panic!("Expected signed object verification"),
+    }
+}
+
 pub async fn rta_sign_sign(
     ca: CaHandle,
     name: RtaName,
@@ -860,6 +1109,97 @@ pub async fn repo_update_krill2(ca: &CaHandle, response: idexchange::RepositoryR
     krill2_admin(command).await;
 }
 
+pub async fn ca_repo_mirror_add(ca: &CaHandle, response: idexchange::RepositoryResponse) {
+    krill_admin(Command::CertAuth(CaCommand::RepoMirrorAdd(ca.clone(), response))).await;
+}
+
+pub async fn ca_repo_mirror_add_expect_error(ca: &CaHandle, response: idexchange::RepositoryResponse) -> Error {
+    krill_admin_expect_error(Command::CertAuth(CaCommand::RepoMirrorAdd(ca.clone(), response))).await
+}
+
+pub async fn ca_repo_mirror_remove(ca: &CaHandle, service_uri: ServiceUri) {
+    krill_admin(Command::CertAuth(CaCommand::RepoMirrorRemove(ca.clone(), service_uri))).await;
+}
+
+pub async fn ca_repo_details(ca: &CaHandle) -> CaRepoDetails {
+    match krill_admin(Command::CertAuth(CaCommand::RepoDetails(ca.clone()))).await {
+        ApiResponse::RepoDetails(details) => details,
+        _ => panic!("Expected repo details"),
+    }
+}
+
+pub async fn ca_repo_status(ca: &CaHandle) -> RepoStatus {
+    match krill_admin(Command::CertAuth(CaCommand::RepoStatus(ca.clone()))).await {
+        ApiResponse::RepoStatus(status) => status,
+        _ => panic!("Expected repo status"),
+    }
+}
+
+pub async fn embedded_repo_init(uris: PublicationServerUris) {
+    krill_embedded_pubd_admin(PubServerCommand::RepositoryInit(uris)).await;
+}
+
+pub async fn ta_proxy_init() {
+    krill_admin(Command::TrustAnchor(TrustAnchorCommand::ProxyInit)).await;
+}
+
+pub async fn ta_proxy_id() -> TrustAnchorProxyInfo {
+    match krill_admin(Command::TrustAnchor(TrustAnchorCommand::ProxyId)).await {
+        ApiResponse::TrustAnchorProxyInfo(info) => info,
+        _ => panic!("Expected TA proxy info"),
+    }
+}
+
+pub async fn ta_proxy_status() -> TrustAnchorProxyStatus {
+    match krill_admin(Command::TrustAnchor(TrustAnchorCommand::ProxyStatus)).await {
+        ApiResponse::TrustAnchorProxyStatus(status) => status,
+        _ => panic!("Expected TA proxy status"),
+    }
+}
+
+pub async fn ta_proxy_signer_add(info: TrustAnchorSignerInfo) {
+    krill_admin(Command::TrustAnchor(TrustAnchorCommand::ProxySignerAdd(info))).await;
+}
+
+pub async fn ta_proxy_children_add(
+    child: ChildHandle,
+    child_request: idexchange::ChildRequest,
+    resources: ResourceSet,
+) -> idexchange::ParentResponse {
+    let id_cert = child_request.validate().unwrap();
+    let add_child_request = AddChildRequest::new(child, resources, id_cert);
+
+    match krill_admin(Command::TrustAnchor(TrustAnchorCommand::ProxyChildAdd(
+        add_child_request,
+    )))
+    .await
+    {
+        ApiResponse::Rfc8183ParentResponse(response) => response,
+        _ => panic!("Expected RFC 8183 Parent Response"),
+    }
+}
+
+pub async fn ta_proxy_signer_make_request() -> TrustAnchorSignedMessage {
+    match krill_admin(Command::TrustAnchor(TrustAnchorCommand::ProxySignerMakeRequest)).await {
+        ApiResponse::TrustAnchorSignedMessage(request) => request,
+        _ => panic!("Expected TA signer request"),
+    }
+}
+
+pub async fn ta_proxy_signer_process_response(response: TrustAnchorSignedMessage) {
+    krill_admin(Command::TrustAnchor(TrustAnchorCommand::ProxySignerProcessResponse(
+        response,
+    )))
+    .await;
+}
+
+pub async fn ta_proxy_signer_process_response_expect_error(response: TrustAnchorSignedMessage) {
+    krill_admin_expect_error(Command::TrustAnchor(TrustAnchorCommand::ProxySignerProcessResponse(
+        response,
+    )))
+    .await;
+}
+
 pub async fn embedded_repository_response(publisher: PublisherHandle) -> idexchange::RepositoryResponse {
     let command = PubServerCommand::RepositoryResponse(publisher);
     match krill_embedded_pubd_admin(command).await {
@@ -908,6 +1248,11 @@ pub async fn expected_mft_and_crl(ca: &CaHandle, rcn: &ResourceClassName) -> Vec
     vec![mft_file, crl_file]
 }
 
+pub async fn expected_tak(ca: &CaHandle, rcn: &ResourceClassName) -> String {
+    let rc_key = ca_key_for_rcn(ca, rcn).await;
+    ObjectName::tak_for_key(rc_key.key_id()).to_string()
+}
+
 pub async fn expected_mft_and_crl_krill2(ca: &CaHandle, rcn: &ResourceClassName) -> Vec<String> {
     let rc_key = ca_key_for_rcn_krill2(ca, rcn).await;
     let mft_file = rc_key.incoming_cert().mft_name().to_string();
@@ -1002,6 +1347,10 @@ pub async fn ca_roll_activate(ca: &CaHandle) {
     krill_admin(Command::CertAuth(CaCommand::KeyRollActivate(ca.clone()))).await;
 }
 
+pub async fn ca_roll_finish(ca: &CaHandle) {
+    krill_admin(Command::CertAuth(CaCommand::KeyRollFinish(ca.clone()))).await;
+}
+
 pub async fn state_becomes_new_key(ca: &CaHandle) -> bool {
     for _ in 0..30_u8 {
         let ca = ca_details(ca).await;
@@ -9,7 +9,9 @@ use rpki::{
 };
 
 use krill::{
-    commons::api::{AspaCustomer, AspaDefinition, AspaDefinitionList, AspaProvidersUpdate, ObjectName},
+    commons::api::{
+        AspaCustomer, AspaDefinition, AspaDefinitionList, AspaImportAction, AspaProvidersUpdate, ObjectName,
+    },
     daemon::ca::ta_handle,
     test::*,
 };
@@ -56,9 +58,10 @@ async fn functional_aspa() {
     {
         let mut expected_files = expected_mft_and_crl(&ta, &rcn_0).await;
         expected_files.push(expected_issued_cer(&testbed, &rcn_0).await);
+        expected_files.push(expected_tak(&ta, &rcn_0).await);
         assert!(
             will_publish_embedded(
-                "TA should have manifest, crl and cert for testbed",
+                "TA should have manifest, crl, TAK and cert for testbed",
                 &ta,
                 &expected_files
             )
@@ -156,6 +159,35 @@ async fn functional_aspa() {
         expect_aspa_definitions(&ca, AspaDefinitionList::new(aspas)).await;
     }
 
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Import providers from AS relationships, propose then apply     #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+
+        let relationships = "65001|65000|-1\n65002|65000|-1\n65003|64496|-1\n";
+        let imported_aspa = AspaDefinition::from_str("AS65000 => AS65001, AS65002").unwrap();
+
+        let report = ca_aspas_import(&ca, relationships, AspaImportAction::Propose).await;
+        assert_eq!(report.changes().len(), 1);
+        expect_aspa_definitions(
+            &ca,
+            AspaDefinitionList::new(vec![AspaDefinition::from_str("AS65000 => <none>").unwrap()]),
+        )
+        .await;
+
+        ca_aspas_import(&ca, relationships, AspaImportAction::Apply).await;
+
+        let aspas = vec![imported_aspa];
+        expect_aspa_objects(&ca, &aspas).await;
+        expect_aspa_definitions(&ca, AspaDefinitionList::new(aspas)).await;
+
+        let report = ca_aspas_import(&ca, relationships, AspaImportAction::Propose).await;
+        assert!(report.is_empty());
+    }
+
     {
         info("##################################################################");
         info("#                                                                #");
@@ -0,0 +1,68 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+
+use krill::{commons::crypto::SignSupport, daemon::ca::GeofeedSignRequest, test::*};
+use rpki::repository::resources::ResourceSet;
+
+#[tokio::test]
+async fn functional_geofeed() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test signed geofeed (RFC 9632) support.                        #");
+    info("#                                                                #");
+    info("# Uses the following lay-out:                                    #");
+    info("#                                                                #");
+    info("#                  TA                                            #");
+    info("#                   |                                            #");
+    info("#                testbed                                         #");
+    info("#                   |                                            #");
+    info("#                  CA1                                           #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let testbed = ca_handle("testbed");
+
+    let ca1 = ca_handle("CA1");
+    let ca1_res = resources("", "10.0.0.0/16", "2001:db8::/32");
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    set_up_ca_with_repo(&ca1).await;
+    set_up_ca_under_parent_with_resources(&ca1, &testbed, &ca1_res).await;
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Sign a geofeed for prefixes held by CA1                        #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let csv = "# example geofeed\n10.0.0.0/24,NL,NL-NH,Amsterdam,\n2001:db8:1::/48,NL,,,\n";
+        let request = GeofeedSignRequest::new(SignSupport::sign_validity_days(7), csv.to_string());
+
+        let signed = geofeed_sign(ca1.clone(), request).await;
+
+        assert!(signed.starts_with("# example geofeed\r\n10.0.0.0/24,NL,NL-NH,Amsterdam,\r\n"));
+        assert!(signed.contains("# RPKI Signature: 10.0.0.0/24, 2001:db8:1::/48\r\n"));
+        assert!(signed.ends_with("# End Signature: 10.0.0.0/24, 2001:db8:1::/48\r\n"));
+    }
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Signing a geofeed with prefixes not held by CA1 should fail    #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let csv = "10.0.0.0/24,NL,,,\n10.1.0.0/24,NL,,,\n";
+        let request = GeofeedSignRequest::new(SignSupport::sign_validity_days(7), csv.to_string());
+
+        geofeed_sign_expect_error(ca1, request).await;
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
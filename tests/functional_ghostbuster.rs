@@ -0,0 +1,90 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+
+use rpki::{
+    ca::{idexchange::CaHandle, provisioning::ResourceClassName},
+    repository::resources::ResourceSet,
+};
+
+use krill::{
+    commons::api::{GhostbusterContact, ObjectName},
+    test::*,
+};
+
+#[tokio::test]
+async fn functional_ghostbuster() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test Ghostbuster record support.                               #");
+    info("#                                                                #");
+    info("# Uses the following lay-out:                                    #");
+    info("#                                                                #");
+    info("#                  TA                                            #");
+    info("#                   |                                            #");
+    info("#                testbed                                         #");
+    info("#                   |                                            #");
+    info("#                  CA                                            #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let testbed = ca_handle("testbed");
+    let ca = ca_handle("CA");
+    let ca_res = resources("AS65000", "10.0.0.0/16", "");
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    set_up_ca_with_repo(&ca).await;
+    set_up_ca_under_parent_with_resources(&ca, &testbed, &ca_res).await;
+
+    // short hand to expect a published Ghostbuster record under CA, or not
+    async fn expect_ghostbuster_object(ca: &CaHandle, expected: bool) {
+        let rcn_0 = ResourceClassName::from(0);
+
+        let mut expected_files = expected_mft_and_crl(ca, &rcn_0).await;
+        if expected {
+            expected_files.push(ObjectName::ghostbuster().to_string());
+        }
+
+        assert!(
+            will_publish_embedded(
+                "published Ghostbuster record does not match expectations",
+                ca,
+                &expected_files
+            )
+            .await
+        );
+    }
+
+    // There is no contact by default
+    assert_eq!(ca_ghostbuster_show(&ca).await, None);
+    expect_ghostbuster_object(&ca, false).await;
+
+    // Set a contact and expect that it is published
+    {
+        let contact = GhostbusterContact::new(
+            "RPKI Operations".to_string(),
+            Some("Example Inc.".to_string()),
+            None,
+            None,
+            Some("rpki@example.com".to_string()),
+        );
+        ca_ghostbuster_set(&ca, contact.clone()).await;
+
+        assert_eq!(ca_ghostbuster_show(&ca).await, Some(contact));
+        expect_ghostbuster_object(&ca, true).await;
+    }
+
+    // Remove the contact and expect that the record is withdrawn
+    {
+        ca_ghostbuster_remove(&ca).await;
+
+        assert_eq!(ca_ghostbuster_show(&ca).await, None);
+        expect_ghostbuster_object(&ca, false).await;
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
@@ -81,9 +81,10 @@ async fn functional_keyroll() {
     {
         let mut expected_files = expected_mft_and_crl(&ta, &dflt_rc_name).await;
         expected_files.push(expected_issued_cer(&testbed, &dflt_rc_name).await);
+        expected_files.push(expected_tak(&ta, &dflt_rc_name).await);
         assert!(
             will_publish_embedded(
-                "TA should have manifest, crl and cert for testbed",
+                "TA should have manifest, crl, TAK and cert for testbed",
                 &ta,
                 &expected_files
             )
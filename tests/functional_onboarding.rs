@@ -0,0 +1,76 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+
+use rpki::repository::resources::ResourceSet;
+
+use krill::test::*;
+
+#[tokio::test]
+async fn functional_onboarding() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test onboarding children using tokens minted by the parent.    #");
+    info("#                                                                #");
+    info("# Uses the following lay-out:                                    #");
+    info("#                                                                #");
+    info("#                  TA                                            #");
+    info("#                   |                                            #");
+    info("#                testbed                                         #");
+    info("#                 /    \\                                         #");
+    info("#               CA1    CA2                                       #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let testbed = ca_handle("testbed");
+    let ca1 = ca_handle("CA1");
+    let ca2 = ca_handle("CA2");
+    let ca1_res = resources("AS65000", "10.0.0.0/16", "");
+    let ca2_res = resources("AS65001", "10.1.0.0/16", "");
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    set_up_ca_with_repo(&ca1).await;
+    set_up_ca_with_repo(&ca2).await;
+
+    // Mint a token, and expect that the parent only lists it by its id
+    let token = ca_onboarding_token_add(&testbed, ca1_res.clone()).await;
+    {
+        let tokens = ca_onboarding_tokens(&testbed).await;
+        assert_eq!(tokens.tokens().len(), 1);
+        assert_eq!(tokens.tokens()[0].id(), &token.id());
+        assert_eq!(tokens.tokens()[0].resources(), &ca1_res);
+    }
+
+    // Use the token, and expect that the child is added with the resources
+    // bound to the token, and that the token is used up.
+    {
+        ca_parent_onboard(&ca1, token.clone()).await;
+        assert!(ca_contains_resources(&ca1, &ca1_res).await);
+        assert!(ca_onboarding_tokens(&testbed).await.tokens().is_empty());
+    }
+
+    // The token cannot be used again
+    {
+        ca_parent_onboard_expect_error(&ca2, token).await;
+    }
+
+    // A token which was removed cannot be used
+    {
+        let token = ca_onboarding_token_add(&testbed, ca2_res.clone()).await;
+        ca_onboarding_token_remove(&testbed, token.id()).await;
+        ca_parent_onboard_expect_error(&ca2, token).await;
+    }
+
+    // A new token can be used by the second child
+    {
+        let token = ca_onboarding_token_add(&testbed, ca2_res.clone()).await;
+        ca_parent_onboard(&ca2, token).await;
+        assert!(ca_contains_resources(&ca2, &ca2_res).await);
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
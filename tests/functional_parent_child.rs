@@ -69,9 +69,10 @@ async fn functional_parent_child() {
     {
         let mut expected_files = expected_mft_and_crl(&ta, &rcn_0).await;
         expected_files.push(expected_issued_cer(&testbed, &rcn_0).await);
+        expected_files.push(expected_tak(&ta, &rcn_0).await);
         assert!(
             will_publish_embedded(
-                "TA should have manifest, crl and cert for testbed",
+                "TA should have manifest, crl, TAK and cert for testbed",
                 &ta,
                 &expected_files
             )
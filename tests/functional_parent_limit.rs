@@ -0,0 +1,68 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+
+use rpki::{ca::idexchange::ParentHandle, repository::resources::ResourceSet};
+
+use krill::test::*;
+
+#[tokio::test]
+async fn functional_parent_limit() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test limiting the resources requested from a parent.           #");
+    info("#                                                                #");
+    info("# Uses the following lay-out:                                    #");
+    info("#                                                                #");
+    info("#                  TA                                            #");
+    info("#                   |                                            #");
+    info("#                testbed                                         #");
+    info("#                   |                                            #");
+    info("#                  CA                                            #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let testbed = ca_handle("testbed");
+    let ca = ca_handle("CA");
+    let ca_res = resources("AS65000", "10.0.0.0/16", "");
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    set_up_ca_with_repo(&ca).await;
+    set_up_ca_under_parent_with_resources(&ca, &testbed, &ca_res).await;
+
+    let parent: ParentHandle = testbed.convert();
+
+    // There is no limit by default
+    assert_eq!(ca_parent_limit_show(&ca, &parent).await, None);
+
+    // Limit the resources, and expect that only the overlap with the
+    // entitlements is certified.
+    {
+        let limit = resources("", "10.0.0.0/24, 192.168.0.0/16", "");
+        ca_parent_limit_set(&ca, &parent, limit.clone()).await;
+
+        assert_eq!(ca_parent_limit_show(&ca, &parent).await, Some(limit));
+        assert!(ca_equals_resources(&ca, &resources("", "10.0.0.0/24", "")).await);
+    }
+
+    // Limit the resources to nothing we are entitled to, and expect that
+    // the resource class is removed.
+    {
+        ca_parent_limit_set(&ca, &parent, resources("", "192.168.0.0/16", "")).await;
+        assert!(rc_is_removed(&ca).await);
+    }
+
+    // Remove the limit and expect that all entitled resources are certified again
+    {
+        ca_parent_limit_remove(&ca, &parent).await;
+
+        assert_eq!(ca_parent_limit_show(&ca, &parent).await, None);
+        assert!(ca_equals_resources(&ca, &ca_res).await);
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
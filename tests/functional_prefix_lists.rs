@@ -0,0 +1,92 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::{fs, str::FromStr};
+
+use rpki::{
+    ca::{idexchange::CaHandle, provisioning::ResourceClassName},
+    repository::resources::{Asn, ResourceSet},
+};
+
+use krill::{
+    commons::api::{ObjectName, PrefixListDefinition},
+    test::*,
+};
+
+#[tokio::test]
+async fn functional_prefix_lists() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test Signed Prefix List support.                               #");
+    info("#                                                                #");
+    info("# Uses the following lay-out:                                    #");
+    info("#                                                                #");
+    info("#                  TA                                            #");
+    info("#                   |                                            #");
+    info("#                testbed                                         #");
+    info("#                   |                                            #");
+    info("#                  CA                                            #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let testbed = ca_handle("testbed");
+    let ca = ca_handle("CA");
+    let ca_res = resources("AS65000", "10.0.0.0/16", "");
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    set_up_ca_with_repo(&ca).await;
+    set_up_ca_under_parent_with_resources(&ca, &testbed, &ca_res).await;
+
+    let asn = Asn::from_u32(65000);
+
+    // short hand to expect a published Signed Prefix List under CA, or not
+    async fn expect_prefix_list_object(ca: &CaHandle, asn: Asn, expected: bool) {
+        let rcn_0 = ResourceClassName::from(0);
+
+        let mut expected_files = expected_mft_and_crl(ca, &rcn_0).await;
+        if expected {
+            expected_files.push(ObjectName::prefix_list(asn).to_string());
+        }
+
+        assert!(
+            will_publish_embedded(
+                "published Signed Prefix Lists do not match expectations",
+                ca,
+                &expected_files
+            )
+            .await
+        );
+    }
+
+    // There are no definitions by default
+    assert!(ca_prefix_lists_list(&ca).await.as_slice().is_empty());
+    expect_prefix_list_object(&ca, asn, false).await;
+
+    // Prefixes do not need to be held, but the ASN does
+    {
+        let not_held = PrefixListDefinition::from_str("AS65001 => 10.0.0.0/16").unwrap();
+        ca_prefix_lists_add_expect_error(&ca, not_held).await;
+    }
+
+    // Add a definition and expect that it is published
+    {
+        let definition = PrefixListDefinition::from_str("AS65000 => 10.0.0.0/16, 192.0.2.0/24, 2001:db8::/32").unwrap();
+        ca_prefix_lists_add(&ca, definition.clone()).await;
+
+        assert_eq!(ca_prefix_lists_list(&ca).await.as_slice(), &[definition]);
+        expect_prefix_list_object(&ca, asn, true).await;
+    }
+
+    // Remove the definition and expect that the object is withdrawn
+    {
+        ca_prefix_lists_remove(&ca, asn).await;
+
+        assert!(ca_prefix_lists_list(&ca).await.as_slice().is_empty());
+        expect_prefix_list_object(&ca, asn, false).await;
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
@@ -0,0 +1,107 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+use std::str::FromStr;
+
+use rpki::{repository::resources::ResourceSet, rrdp::Hash};
+
+use krill::{
+    commons::api::{ObjectName, PublishedObjectContent, RoaConfiguration, RoaConfigurationUpdates, RoaPayload},
+    test::*,
+};
+
+#[tokio::test]
+async fn functional_published_objects() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test that the objects published by a CA can be listed, and     #");
+    info("# that each object can be downloaded.                            #");
+    info("#                                                                #");
+    info("# Uses the following lay-out:                                    #");
+    info("#                                                                #");
+    info("#                  TA                                            #");
+    info("#                   |                                            #");
+    info("#                testbed                                         #");
+    info("#                   |                                            #");
+    info("#                  CA                                            #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let testbed = ca_handle("testbed");
+    let ca = ca_handle("CA");
+    let ca_res = resources("AS65000", "10.0.0.0/16", "");
+    let dflt_rc_name = rcn(0);
+
+    let roa_payload = RoaPayload::from_str("10.0.0.0/16-16 => 64496").unwrap();
+    let roa_file = ObjectName::from(&roa_payload);
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    set_up_ca_with_repo(&ca).await;
+    set_up_ca_under_parent_with_resources(&ca, &testbed, &ca_res).await;
+
+    {
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(RoaConfiguration::from(roa_payload));
+        ca_route_authorizations_update(&ca, updates).await;
+
+        let mut expected_files = expected_mft_and_crl(&ca, &dflt_rc_name).await;
+        expected_files.push(roa_file.to_string());
+        assert!(will_publish_embedded("CA should publish mft, crl and roa", &ca, &expected_files).await);
+    }
+
+    // The manifest and CRL are listed first, and the manifest lists all
+    // other objects.
+    let objects = ca_published_objects(&ca).await;
+    let key = *ca_key_for_rcn(&ca, &dflt_rc_name).await.key_id();
+    {
+        assert_eq!(objects.classes().len(), 1);
+        let keys = objects.classes()[0].keys();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].key_id(), &key);
+
+        let key_objects = keys[0].objects();
+        assert_eq!(key_objects.len(), 3);
+
+        match key_objects[0].content() {
+            PublishedObjectContent::Manifest { entries, .. } => assert_eq!(entries.len(), key_objects.len() - 1),
+            _ => panic!("Expected manifest to be listed first"),
+        }
+        match key_objects[1].content() {
+            PublishedObjectContent::Crl { revoked, .. } => assert!(revoked.is_empty()),
+            _ => panic!("Expected CRL to be listed second"),
+        }
+        match key_objects[2].content() {
+            PublishedObjectContent::Roa { authorizations } => assert_eq!(authorizations, &vec![roa_payload]),
+            _ => panic!("Expected ROA"),
+        }
+    }
+
+    // The downloaded ROA matches the listed hash
+    {
+        let roa = objects
+            .objects()
+            .find(|object| object.uri().to_string().ends_with(&roa_file.to_string()))
+            .unwrap();
+
+        let out = sub_dir(&krill_dir).join(roa_file.to_string());
+        let bytes = ca_published_object_download(&ca, key, roa_file.clone(), &out).await;
+        assert_eq!(&Hash::from_data(&bytes), roa.hash());
+    }
+
+    // The parent lists the certificate it issued to the CA
+    {
+        let child_key = ca_key_for_rcn(&ca, &dflt_rc_name).await;
+        let testbed_objects = ca_published_objects(&testbed).await;
+        assert!(testbed_objects.objects().any(|object| matches!(
+            object.content(),
+            PublishedObjectContent::ChildCertificate { subject_key, resources }
+                if subject_key == child_key.key_id() && resources == &ca_res
+        )));
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
@@ -0,0 +1,112 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::{fs, str::FromStr};
+
+use rpki::{repository::resources::ResourceSet, uri};
+
+use krill::{
+    commons::api::{ObjectName, RoaConfiguration, RoaConfigurationUpdates, RoaPayload},
+    test::*,
+};
+
+#[tokio::test]
+async fn functional_repo_mirror() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test that a CA can publish its objects to a mirror repository  #");
+    info("# in addition to its primary repository.                         #");
+    info("#                                                                #");
+    info("# The dedicated publication server uses the same rsync base URI  #");
+    info("# as the embedded repository, so that it can act as a mirror.    #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let rsync_base = uri::Rsync::from_str("rsync://localhost/repo/").unwrap();
+    let pubd_dir = start_krill_pubd_with_rsync_base(0, rsync_base).await;
+
+    let testbed = ca_handle("testbed");
+    let ca = ca_handle("CA");
+    let ca_res = resources("AS65000", "10.0.0.0/16", "");
+    let dflt_rc_name = rcn(0);
+
+    let roa_payload = RoaPayload::from_str("10.0.0.0/16-16 => 64496").unwrap();
+    let roa_file = ObjectName::from(&roa_payload);
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    set_up_ca_with_repo(&ca).await;
+    set_up_ca_under_parent_with_resources(&ca, &testbed, &ca_res).await;
+
+    // The primary repository cannot be added as a mirror as well
+    {
+        let response = embedded_repository_response(ca.convert()).await;
+        ca_repo_mirror_add_expect_error(&ca, response).await;
+    }
+
+    // Add the dedicated repository as a mirror
+    let mirror_uri = {
+        let publisher_request = publisher_request(&ca).await;
+        dedicated_repo_add_publisher(publisher_request).await;
+        let response = dedicated_repository_response(&ca).await;
+        let mirror_uri = response.service_uri().clone();
+
+        ca_repo_mirror_add(&ca, response).await;
+
+        let details = ca_repo_details(&ca).await;
+        assert_eq!(details.mirrors().len(), 1);
+        assert_eq!(details.mirrors()[0].server_info().service_uri(), &mirror_uri);
+
+        mirror_uri
+    };
+
+    // Both the primary and the mirror get the objects of the CA
+    {
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(RoaConfiguration::from(roa_payload));
+        ca_route_authorizations_update(&ca, updates).await;
+
+        let mut expected_files = expected_mft_and_crl(&ca, &dflt_rc_name).await;
+        expected_files.push(roa_file.to_string());
+        assert!(will_publish_embedded("CA should publish mft, crl and roa", &ca, &expected_files).await);
+        assert!(will_publish_dedicated("mirror should get mft, crl and roa", &ca, &expected_files).await);
+
+        // The status is saved just after the mirror accepted the delta
+        let mut mirror_synced = false;
+        for _ in 0..30 {
+            let status = ca_repo_status(&ca).await;
+            if let Some(mirror_status) = status.mirror(&mirror_uri) {
+                assert!(mirror_status.to_failure_opt().is_none());
+                if mirror_status.last_success().is_some() {
+                    mirror_synced = true;
+                    break;
+                }
+            }
+            sleep_millis(100).await;
+        }
+        assert!(mirror_synced);
+    }
+
+    // Removing the mirror withdraws the objects from it, and leaves
+    // the primary repository alone.
+    {
+        ca_repo_mirror_remove(&ca, mirror_uri.clone()).await;
+
+        let details = ca_repo_details(&ca).await;
+        assert!(details.mirrors().is_empty());
+
+        assert!(will_publish_dedicated("mirror should be cleaned up", &ca, &[]).await);
+
+        let mut expected_files = expected_mft_and_crl(&ca, &dflt_rc_name).await;
+        expected_files.push(roa_file.to_string());
+        assert!(will_publish_embedded("CA should still publish mft, crl and roa", &ca, &expected_files).await);
+
+        let status = ca_repo_status(&ca).await;
+        assert!(status.mirror(&mirror_uri).is_none());
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+    let _ = fs::remove_dir_all(pubd_dir);
+}
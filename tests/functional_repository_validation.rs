@@ -0,0 +1,112 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+use std::str::FromStr;
+
+use rpki::{repository::resources::ResourceSet, uri};
+
+use krill::{
+    commons::{
+        api::{ObjectName, RoaConfiguration, RoaConfigurationUpdates, RoaPayload},
+        util::file,
+    },
+    constants::{REPOSITORY_DIR, REPOSITORY_RSYNC_DIR},
+    test::*,
+};
+
+#[tokio::test]
+async fn functional_repository_validation() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test that Krill can validate the content of its repository as  #");
+    info("# a relying party would, and that issues are reported.           #");
+    info("#                                                                #");
+    info("# Uses the following lay-out:                                    #");
+    info("#                                                                #");
+    info("#                  TA                                            #");
+    info("#                   |                                            #");
+    info("#                testbed                                         #");
+    info("#                   |                                            #");
+    info("#                  CA                                            #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let testbed = ca_handle("testbed");
+    let ca = ca_handle("CA");
+    let ca_res = resources("AS65000", "10.0.0.0/16", "");
+    let dflt_rc_name = rcn(0);
+
+    let roa_payload = RoaPayload::from_str("10.0.0.0/16-16 => 64496").unwrap();
+    let roa_file = ObjectName::from(&roa_payload);
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    set_up_ca_with_repo(&ca).await;
+    set_up_ca_under_parent_with_resources(&ca, &testbed, &ca_res).await;
+
+    {
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(RoaConfiguration::from(roa_payload));
+        ca_route_authorizations_update(&ca, updates).await;
+
+        let mut expected_files = expected_mft_and_crl(&ca, &dflt_rc_name).await;
+        expected_files.push(roa_file.to_string());
+        assert!(will_publish_embedded("CA should publish mft, crl and roa", &ca, &expected_files).await);
+    }
+
+    // The repository content is valid, and both local CAs are included. The
+    // rsync content is written when the repository creates its next RRDP
+    // delta, which may be a moment after the CA published.
+    {
+        let mut report = repository_validate().await;
+        for _ in 0..30 {
+            if report.is_clean() {
+                break;
+            }
+            sleep_seconds(1).await;
+            report = repository_validate().await;
+        }
+        assert!(report.is_clean(), "{}", report);
+        assert!(report.cas().iter().any(|result| result.ca() == &testbed));
+        assert!(report.cas().iter().any(|result| result.ca() == &ca));
+    }
+
+    // A file which is not on the manifest of the CA, and which was added
+    // to the rsync directory only, is reported.
+    let stray_uri = {
+        let roa_uri = ca_published_objects(&ca)
+            .await
+            .objects()
+            .find(|object| object.uri().to_string().ends_with(&roa_file.to_string()))
+            .unwrap()
+            .uri()
+            .to_string();
+        let stray = roa_uri.replace(&roa_file.to_string(), "stray.roa");
+        uri::Rsync::from_str(&stray).unwrap()
+    };
+    {
+        let path = stray_uri.to_string().replace("rsync://localhost/repo/", "");
+        let stray_path = krill_dir
+            .join(REPOSITORY_DIR)
+            .join(REPOSITORY_RSYNC_DIR)
+            .join("current")
+            .join(path);
+        file::save(b"not an object", &stray_path).unwrap();
+
+        let report = repository_validate().await;
+        assert!(!report.is_clean());
+        assert!(report.repository_issues().iter().any(|issue| issue.uri() == &stray_uri));
+
+        let ca_result = report.cas().iter().find(|result| result.ca() == &ca).unwrap();
+        assert_eq!(ca_result.issues().len(), 1);
+        assert_eq!(ca_result.issues()[0].uri(), &stray_uri);
+
+        let issues = ca_issues(&ca).await;
+        assert_eq!(issues.validation_issues(), ca_result.issues());
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
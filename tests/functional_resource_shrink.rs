@@ -0,0 +1,95 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+
+use rpki::repository::resources::ResourceSet;
+
+use krill::{
+    commons::{
+        api::{ChildResourcesLost, RoaConfigurationUpdates},
+        bgp::BgpAnalysisState,
+    },
+    test::*,
+};
+
+#[tokio::test]
+async fn functional_resource_shrink() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test that the effect of a parent removing resources from a CA  #");
+    info("# is reported: the lost resources, the ROAs no longer held, the  #");
+    info("# children which lost resources and the announcements which are  #");
+    info("# no longer valid.                                               #");
+    info("#                                                                #");
+    info("# Uses the following lay-out:                                    #");
+    info("#                                                                #");
+    info("#                  TA                                            #");
+    info("#                   |                                            #");
+    info("#                testbed                                         #");
+    info("#                   |                                            #");
+    info("#                  CA                                            #");
+    info("#                   |                                            #");
+    info("#                 CHILD                                          #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let testbed = ca_handle("testbed");
+    let ca = ca_handle("CA");
+    let child = ca_handle("CHILD");
+
+    let ca_res = resources("AS65000", "10.0.0.0/16, 192.168.0.0/16", "");
+    let ca_res_shrunk = resources("AS65000", "10.0.0.0/16", "");
+    let lost = resources("", "192.168.0.0/16", "");
+    let child_res = resources("", "10.0.1.0/24, 192.168.1.0/24", "");
+    let child_lost = resources("", "192.168.1.0/24", "");
+
+    // The test announcement for this ROA is valid, until the CA loses the prefix
+    let roa = roa_configuration("192.168.0.0/24-24 => 64496");
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    set_up_ca_with_repo(&ca).await;
+    set_up_ca_under_parent_with_resources(&ca, &testbed, &ca_res).await;
+
+    set_up_ca_with_repo(&child).await;
+    set_up_ca_under_parent_with_resources(&child, &ca, &child_res).await;
+
+    let mut updates = RoaConfigurationUpdates::empty();
+    updates.add(roa.clone());
+    ca_route_authorizations_update(&ca, updates).await;
+
+    assert!(ca_resource_shrinks(&ca).await.is_empty());
+
+    update_child(&testbed, &ca, &ca_res_shrunk).await;
+    assert!(ca_equals_resources(&ca, &ca_res_shrunk).await);
+
+    let report = ca_resource_shrinks(&ca).await;
+    assert_eq!(report.shrinks().len(), 1);
+
+    let shrink = &report.shrinks()[0];
+    assert_eq!(shrink.parent(), &testbed.convert());
+    assert_eq!(shrink.lost(), &lost);
+    assert_eq!(shrink.roas_not_held(), &vec![roa]);
+    assert_eq!(
+        shrink.children(),
+        &vec![ChildResourcesLost::new(child.convert(), child_lost)]
+    );
+
+    assert_eq!(report.announcements().len(), 1);
+    assert_eq!(
+        report.announcements()[0].announcement(),
+        announcement("192.168.0.0/24 => 64496")
+    );
+    assert_eq!(
+        report.announcements()[0].state(),
+        BgpAnalysisState::AnnouncementNotFound
+    );
+
+    ca_resource_shrinks_clear(&ca).await;
+    assert!(ca_resource_shrinks(&ca).await.is_empty());
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
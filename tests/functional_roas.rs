@@ -9,7 +9,15 @@ use rpki::{
 };
 
 use krill::{
-    commons::api::{ObjectName, RoaConfiguration, RoaConfigurationUpdates, RoaPayload},
+    cli::{
+        options::{CaCommand, Command},
+        report::ApiResponse,
+    },
+    commons::api::{
+        AsNumber, ObjectName, RoaAs0Policy, RoaAs0Status, RoaConfiguration, RoaConfigurationFilter,
+        RoaConfigurationUpdates, RoaMetadata, RoaPayload, RpslImportAction, RpslImportRequest, TimedRoaChangeKind,
+        Timestamp, VrpExportFormat,
+    },
     daemon::ca::ta_handle,
     test::*,
 };
@@ -64,9 +72,10 @@ async fn functional_roas() {
     {
         let mut expected_files = expected_mft_and_crl(&ta, &rcn_0).await;
         expected_files.push(expected_issued_cer(&testbed, &rcn_0).await);
+        expected_files.push(expected_tak(&ta, &rcn_0).await);
         assert!(
             will_publish_embedded(
-                "TA should have manifest, crl and cert for testbed",
+                "TA should have manifest, crl, TAK and cert for testbed",
                 &ta,
                 &expected_files
             )
@@ -162,6 +171,17 @@ async fn functional_roas() {
             ],
         )
         .await;
+
+        // The shrink should be reported until it is cleared
+        let shrinks = ca_resource_shrinks(&ca).await;
+        assert_eq!(shrinks.shrinks().len(), 1);
+        assert_eq!(
+            shrinks.shrinks()[0].roas_not_held(),
+            &vec![route_resource_set_10_1_0_0_def_1.clone()]
+        );
+
+        ca_resource_shrinks_clear(&ca).await;
+        assert!(ca_resource_shrinks(&ca).await.is_empty());
     }
 
     {
@@ -225,6 +245,297 @@ async fn functional_roas() {
         expect_roa_objects(&ca, &[route_resource_set_10_0_0_0_def_1.payload()]).await;
     }
 
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Stage ROA changes in a draft. Nothing should be published      #");
+        info("# until the draft is committed.                                  #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let draft = "staged".to_string();
+
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(route_resource_set_10_0_0_0_def_2.clone());
+        krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsDraftUpdate(
+            ca.clone(),
+            draft.clone(),
+            updates,
+        )))
+        .await;
+
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(route_resource_set_10_1_0_0_def_1.clone());
+        krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsDraftUpdate(
+            ca.clone(),
+            draft.clone(),
+            updates,
+        )))
+        .await;
+
+        match krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsDraftDryRun(
+            ca.clone(),
+            draft.clone(),
+        )))
+        .await
+        {
+            ApiResponse::BgpAnalysisFull(_) => {}
+            _ => panic!("Expected BGP analysis report"),
+        }
+
+        expect_configured_roas(&ca, &[route_resource_set_10_0_0_0_def_1.clone()]).await;
+
+        krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsDraftCommit(
+            ca.clone(),
+            draft.clone(),
+        )))
+        .await;
+
+        expect_configured_roas(
+            &ca,
+            &[
+                route_resource_set_10_0_0_0_def_1.clone(),
+                route_resource_set_10_0_0_0_def_2.clone(),
+                route_resource_set_10_1_0_0_def_1.clone(),
+            ],
+        )
+        .await;
+
+        expect_roa_objects(
+            &ca,
+            &[
+                route_resource_set_10_0_0_0_def_1.payload(),
+                route_resource_set_10_0_0_0_def_2.payload(),
+                route_resource_set_10_1_0_0_def_1.payload(),
+            ],
+        )
+        .await;
+
+        // the committed draft is gone
+        krill_admin_expect_error(Command::CertAuth(CaCommand::RouteAuthorizationsDraftShow(
+            ca.clone(),
+            draft,
+        )))
+        .await;
+    }
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Add metadata to an existing ROA configuration, and use it to   #");
+        info("# filter the list of configured ROAs.                            #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let mut metadata = RoaMetadata::default();
+        metadata.set_owner(Some("noc".to_string()));
+        metadata.add_tag_str("service=cdn").unwrap();
+
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(
+            route_resource_set_10_0_0_0_def_1
+                .clone()
+                .with_metadata(metadata.clone()),
+        );
+        ca_route_authorizations_update(&ca, updates).await;
+
+        let mut filter = RoaConfigurationFilter::default();
+        filter.add_tag_str("service=cdn").unwrap();
+        let filtered = ca_configured_roas_filtered(&ca, filter).await.unpack();
+
+        assert_eq!(filtered.len(), 1);
+        let configured = filtered.first().unwrap();
+        assert_eq!(configured.roa_configuration().metadata(), Some(&metadata));
+
+        // the metadata is also reported on the ROA object(s) for the configuration
+        assert!(configured
+            .roa_objects()
+            .iter()
+            .all(|roa| roa.metadata().values().any(|found| found == &metadata)));
+
+        let mut filter = RoaConfigurationFilter::default();
+        filter.set_owner(Some("someone else".to_string()));
+        assert!(ca_configured_roas_filtered(&ca, filter).await.unpack().is_empty());
+
+        // updating the comment without metadata keeps the existing metadata
+        let commented = roa_configuration("10.0.0.0/16-16 => 64496 # cdn");
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(commented.clone());
+        ca_route_authorizations_update(&ca, updates).await;
+
+        let mut filter = RoaConfigurationFilter::default();
+        filter.add_tag_str("service=cdn").unwrap();
+        let filtered = ca_configured_roas_filtered(&ca, filter.clone()).await.unpack();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].roa_configuration().comment(), commented.comment());
+        assert_eq!(filtered[0].roa_configuration().metadata(), Some(&metadata));
+
+        // but empty metadata clears it
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(commented.with_metadata(RoaMetadata::default()));
+        ca_route_authorizations_update(&ca, updates).await;
+        assert!(ca_configured_roas_filtered(&ca, filter).await.unpack().is_empty());
+    }
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Add a time-bounded ROA. It should be scheduled first, then be  #");
+        info("# activated, and finally be removed by the system.               #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let now = Timestamp::now();
+        let timed = route_resource_set_10_0_0_0_def_3
+            .clone()
+            .with_time_bounds(Some(now.plus_seconds(3)), Some(now.plus_seconds(8)));
+
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(timed.clone());
+        ca_route_authorizations_update(&ca, updates).await;
+
+        let changes = ca_timed_roa_changes(&ca).await.unpack();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].kind(), TimedRoaChangeKind::Activate);
+        assert_eq!(changes[1].kind(), TimedRoaChangeKind::Expire);
+
+        assert!(will_have_configured_roa(&ca, timed.payload(), true, 30).await);
+        assert!(will_have_configured_roa(&ca, timed.payload(), false, 30).await);
+        assert!(ca_timed_roa_changes(&ca).await.is_empty());
+
+        // A 'not_after' time in the past is rejected
+        let mut updates = RoaConfigurationUpdates::empty();
+        updates.add(
+            route_resource_set_10_0_0_0_def_3
+                .clone()
+                .with_time_bounds(None, Some(Timestamp::now_minus_seconds(10))),
+        );
+        ca_route_authorizations_update_expect_error(&ca, updates).await;
+    }
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Import ROAs from RPSL route objects. Only objects for held     #");
+        info("# prefixes which are not yet configured should be added.         #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let rpsl = concat!(
+            "route:  10.0.0.0/16\norigin: AS64496\n\n",
+            "route:  10.0.0.0/24\norigin: AS64497\n\n",
+            "route:  192.168.0.0/24\norigin: AS64496\n",
+        )
+        .to_string();
+
+        let import = |action: RpslImportAction| {
+            krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsImportRpsl(
+                ca.clone(),
+                RpslImportRequest::new(rpsl.clone(), action),
+            )))
+        };
+
+        let report = match import(RpslImportAction::Propose).await {
+            ApiResponse::RpslImportReport(report) => report,
+            _ => panic!("Expected RPSL import report"),
+        };
+        assert_eq!(
+            report.updates().added(),
+            &vec![route_resource_set_10_0_0_0_def_4.clone()]
+        );
+        assert_eq!(report.skipped().len(), 2);
+
+        // proposing the import does not change anything
+        let imported = route_resource_set_10_0_0_0_def_4.payload();
+        assert!(will_have_configured_roa(&ca, imported, false, 1).await);
+
+        import(RpslImportAction::Apply).await;
+        assert!(will_have_configured_roa(&ca, imported, true, 1).await);
+    }
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Export the effective VRPs, e.g. as CSV and SLURM               #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        async fn export(ca: &CaHandle, format: VrpExportFormat) -> String {
+            match krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsExport(
+                ca.clone(),
+                format,
+            )))
+            .await
+            {
+                ApiResponse::GenericBody(body) => body,
+                _ => panic!("Expected exported VRPs"),
+            }
+        }
+
+        let csv = export(&ca, VrpExportFormat::Csv).await;
+        assert!(csv.lines().any(|line| line == "AS64497,10.0.0.0/24,24"));
+        assert!(csv.lines().any(|line| line == "AS64496,10.0.0.0/16,16"));
+
+        let slurm: serde_json::Value = serde_json::from_str(&export(&ca, VrpExportFormat::Slurm).await).unwrap();
+        assert_eq!(slurm["slurmVersion"], 1);
+        assert_eq!(
+            slurm["locallyAddedAssertions"]["prefixAssertions"]
+                .as_array()
+                .unwrap()
+                .len(),
+            csv.lines().count() - 1
+        );
+    }
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Enable AS0 ROAs for held space which is not covered by any     #");
+        info("# ROA configuration, and disable them again.                     #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        async fn as0_status(ca: &CaHandle) -> RoaAs0Status {
+            match krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsAs0Status(ca.clone()))).await {
+                ApiResponse::RoaAs0Status(status) => status,
+                _ => panic!("Expected AS0 status"),
+            }
+        }
+
+        krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsAs0Policy(
+            ca.clone(),
+            RoaAs0Policy::new(true),
+        )))
+        .await;
+
+        let status = as0_status(&ca).await;
+        assert!(status.enabled());
+        assert!(!status.payloads().is_empty());
+        assert!(status
+            .payloads()
+            .iter()
+            .all(|payload| payload.asn() == AsNumber::zero()));
+
+        let configured = ca_configured_roas(&ca).await.unpack();
+        for payload in status.payloads() {
+            let as0 = ResourceSet::from(payload.prefix());
+            for configured in configured.iter() {
+                let prefix = ResourceSet::from(configured.payload().prefix());
+                assert!(as0.intersection(&prefix).is_empty());
+            }
+        }
+
+        krill_admin(Command::CertAuth(CaCommand::RouteAuthorizationsAs0Policy(
+            ca.clone(),
+            RoaAs0Policy::new(false),
+        )))
+        .await;
+
+        let status = as0_status(&ca).await;
+        assert!(!status.enabled());
+        assert!(status.payloads().is_empty());
+    }
+
     {
         info("##################################################################");
         info("#                                                                #");
@@ -0,0 +1,120 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+
+use bytes::Bytes;
+use krill::{
+    commons::crypto::SignSupport,
+    daemon::ca::{RscChecklistItem, RscRequest},
+    test::*,
+};
+use rpki::repository::resources::ResourceSet;
+
+#[tokio::test]
+async fn functional_rsc() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test Resource Signed Checklist (RSC) support.                  #");
+    info("#                                                                #");
+    info("# Uses the following lay-out:                                    #");
+    info("#                                                                #");
+    info("#                  TA                                            #");
+    info("#                   |                                            #");
+    info("#                testbed                                         #");
+    info("#                   |                                            #");
+    info("#                  CA1                                           #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let testbed = ca_handle("testbed");
+
+    let ca1 = ca_handle("CA1");
+    let ca1_res = resources("", "10.0.0.0/16", "");
+
+    let out_dir = sub_dir(&krill_dir);
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Wait for the *testbed* CA to get its certificate, this means   #");
+    info("# that all CAs which are set up as part of krill_start under the #");
+    info("# testbed config have been set up.                               #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("#                      Set up CA1 under testbed                  #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        set_up_ca_with_repo(&ca1).await;
+        set_up_ca_under_parent_with_resources(&ca1, &testbed, &ca1_res).await;
+    }
+
+    let content = Bytes::copy_from_slice(include_bytes!("../test-resources/test.tal"));
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Sign an RSC for a prefix held by CA1                           #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let checklist = vec![RscChecklistItem::for_content(
+            Some("test.tal".to_string()),
+            content.clone(),
+        )];
+        let request = RscRequest::new(
+            ipv4_resources("10.0.0.0/24"),
+            SignSupport::sign_validity_days(7),
+            checklist,
+        );
+
+        let out = out_dir.join("test.sig");
+        let rsc = rsc_sign(ca1.clone(), request, &out).await;
+        assert!(!rsc.is_empty());
+        assert_eq!(rsc[0], 0x30);
+
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Verify the RSC against the TA, and against an unrelated TAL    #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let verification = verify_signed_object(rsc.clone(), vec![ta_tal().await]).await;
+        assert!(verification.is_valid());
+        assert_eq!(verification.resources(), &ipv4_resources("10.0.0.0/24"));
+        assert_eq!(verification.signers().len(), 1);
+
+        let other_tal = String::from_utf8(include_bytes!("../test-resources/test.tal").to_vec()).unwrap();
+        let verification = verify_signed_object(rsc, vec![other_tal]).await;
+        assert!(!verification.is_valid());
+    }
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Signing an RSC for resources not held by CA1 should fail       #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let checklist = vec![RscChecklistItem::for_content(None, content)];
+        let request = RscRequest::new(
+            ipv4_resources("10.1.0.0/24"),
+            SignSupport::sign_validity_days(7),
+            checklist,
+        );
+
+        let out = out_dir.join("unheld.sig");
+        rsc_sign_expect_error(ca1, request, &out).await;
+        assert!(!out.exists());
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
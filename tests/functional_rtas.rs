@@ -53,9 +53,10 @@ async fn functional_rtas() {
     {
         let mut expected_files = expected_mft_and_crl(&ta, &rcn_0).await;
         expected_files.push(expected_issued_cer(&testbed, &rcn_0).await);
+        expected_files.push(expected_tak(&ta, &rcn_0).await);
         assert!(
             will_publish_embedded(
-                "TA should have manifest, crl and cert for testbed",
+                "TA should have manifest, crl, TAK and cert for testbed",
                 &ta,
                 &expected_files
             )
@@ -110,7 +111,11 @@ async fn functional_rtas() {
         let rta_list = rta_list(ca1.clone()).await;
         assert_eq!(rta_list, RtaList::new(vec![rta_single.clone()]));
 
-        let _single_rta = rta_show(ca1.clone(), rta_single).await;
+        let single_rta = rta_show(ca1.clone(), rta_single).await;
+
+        let verification = verify_signed_object(single_rta.as_ref().clone(), vec![ta_tal().await]).await;
+        assert!(verification.is_valid());
+        assert_eq!(verification.resources(), &ca1_res);
     }
 
     {
@@ -141,7 +146,12 @@ async fn functional_rtas() {
         let multi_rta_ca2 = rta_show(ca2, multi_rta_name.clone()).await;
         rta_multi_cosign(ca1.clone(), multi_rta_name.clone(), multi_rta_ca2).await;
 
-        let _multi_signed = rta_show(ca1.clone(), multi_rta_name).await;
+        let multi_signed = rta_show(ca1.clone(), multi_rta_name).await;
+
+        // Both CA1 and CA2 need to have signed for the RTA to be valid
+        let verification = verify_signed_object(multi_signed.as_ref().clone(), vec![ta_tal().await]).await;
+        assert!(verification.is_valid());
+        assert_eq!(verification.signers().len(), 2);
     }
 
     let _ = fs::remove_dir_all(krill_dir);
@@ -0,0 +1,168 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+
+use rpki::repository::resources::ResourceSet;
+
+use krill::{
+    commons::api::ObjectName,
+    daemon::ca::{ta_handle, TrustAnchorKeys},
+    test::*,
+};
+
+#[tokio::test]
+async fn functional_ta_keyroll() {
+    let krill_dir = start_krill_with_default_test_config(true, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("#               Test Trust Anchor Key Roll                       #");
+    info("#                                                                #");
+    info("# We will verify that:                                           #");
+    info("#  * the TA can initiate a key roll:                             #");
+    info("#      * create and self-sign a new key, with its own TAL        #");
+    info("#      * publish a TAK under both keys listing the other key     #");
+    info("#  * the TA can activate the new key:                            #");
+    info("#      * the TAL served by Krill is for the new key              #");
+    info("#      * the testbed certificate is issued under the new key     #");
+    info("#      * the old certificate is still served at its old URI      #");
+    info("#  * the TA can finish the roll:                                 #");
+    info("#      * the old key and its TAK are removed                     #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let ta = ta_handle();
+    let testbed = ca_handle("testbed");
+    let dflt_rc_name = rcn(0);
+
+    assert!(ca_contains_resources(&testbed, &ResourceSet::all()).await);
+
+    let old_tal = ta_tal().await;
+    let old_cer = ta_cer("ta.cer").await;
+    let old_key = *ca_key_for_rcn(&ta, &dflt_rc_name).await.key_id();
+    let old_tak = ObjectName::tak_for_key(&old_key).to_string();
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# The TA should publish a TAK listing only its current key.      #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        let mut expected_files = expected_mft_and_crl(&ta, &dflt_rc_name).await;
+        expected_files.push(expected_issued_cer(&testbed, &dflt_rc_name).await);
+        expected_files.push(old_tak.clone());
+        assert!(will_publish_embedded("TA should publish TAK for current key", &ta, &expected_files).await);
+    }
+
+    let new_key = {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Initiate the key roll and verify the TAKs under both keys.     #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        ca_roll_init(&ta).await;
+        assert!(state_becomes_new_key(&ta).await);
+
+        let new_key = *ca_new_key_for_rcn(&ta, &dflt_rc_name).await.key_id();
+        let new_tak = ObjectName::tak_for_key(&new_key).to_string();
+
+        let mut expected_files = expected_mft_and_crl(&ta, &dflt_rc_name).await;
+        expected_files.push(expected_issued_cer(&testbed, &dflt_rc_name).await);
+        expected_files.push(old_tak.clone());
+        expected_files.append(&mut expected_new_key_mft_and_crl(&ta, &dflt_rc_name).await);
+        expected_files.push(new_tak.clone());
+        assert!(will_publish_embedded("TA should publish TAKs under both keys", &ta, &expected_files).await);
+
+        let keys = published_tak(&old_tak).await;
+        assert_eq!(keys.current().key_identifier(), old_key);
+        assert!(keys.predecessor().is_none());
+        assert_eq!(keys.successor().unwrap().key_identifier(), new_key);
+
+        let keys = published_tak(&new_tak).await;
+        assert_eq!(keys.current().key_identifier(), new_key);
+        assert_eq!(keys.predecessor().unwrap().key_identifier(), old_key);
+        assert!(keys.successor().is_none());
+
+        // The TAL for the new key is available, but not yet the default.
+        let new_tal_path = format!("ta/{}.tal", new_key);
+        let new_tal = krill_anon_http_get(&new_tal_path).await.unwrap();
+        assert_ne!(new_tal, old_tal);
+        assert_eq!(ta_tal().await, old_tal);
+        assert_eq!(ta_cer("ta.cer").await, old_cer);
+
+        new_key
+    };
+    let new_cer_name = ObjectName::cer_for_key(&new_key).to_string();
+    let new_cer = ta_cer(&new_cer_name).await;
+    assert_ne!(new_cer, old_cer);
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Activate the new key, it should now be used for the TAL and    #");
+        info("# to issue the testbed certificate.                              #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        ca_roll_activate(&ta).await;
+
+        assert_eq!(ca_key_for_rcn(&ta, &dflt_rc_name).await.key_id(), &new_key);
+        assert_ne!(ta_tal().await, old_tal);
+
+        // Relying parties which still use the old TAL can get the old
+        // certificate at its old URI until the roll is finished.
+        assert_eq!(ta_cer("ta.cer").await, old_cer);
+        assert_eq!(ta_cer(&new_cer_name).await, new_cer);
+
+        let mut expected_files = expected_mft_and_crl(&ta, &dflt_rc_name).await;
+        expected_files.push(expected_issued_cer(&testbed, &dflt_rc_name).await);
+        expected_files.push(ObjectName::tak_for_key(&new_key).to_string());
+        expected_files.push(ObjectName::mft_for_key(&old_key).to_string());
+        expected_files.push(ObjectName::crl_for_key(&old_key).to_string());
+        expected_files.push(old_tak.clone());
+        assert!(will_publish_embedded("TA should re-issue objects under new key", &ta, &expected_files).await);
+    }
+
+    {
+        info("##################################################################");
+        info("#                                                                #");
+        info("# Finish the roll, the old key and its TAK should be removed.    #");
+        info("#                                                                #");
+        info("##################################################################");
+        info("");
+        ca_roll_finish(&ta).await;
+        assert!(state_becomes_active(&ta).await);
+
+        let new_tak = ObjectName::tak_for_key(&new_key).to_string();
+
+        let mut expected_files = expected_mft_and_crl(&ta, &dflt_rc_name).await;
+        expected_files.push(expected_issued_cer(&testbed, &dflt_rc_name).await);
+        expected_files.push(new_tak.clone());
+        assert!(will_publish_embedded("TA should only publish under new key", &ta, &expected_files).await);
+
+        let keys = published_tak(&new_tak).await;
+        assert_eq!(keys.current().key_identifier(), new_key);
+        assert!(keys.predecessor().is_none());
+        assert!(keys.successor().is_none());
+
+        // The old certificate is no longer served.
+        assert_eq!(ta_cer("ta.cer").await, new_cer);
+        assert_eq!(ta_cer(&new_cer_name).await, new_cer);
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+}
+
+async fn published_tak(file_name: &str) -> TrustAnchorKeys {
+    let published = publisher_details(ta_handle().convert()).await;
+    let file = published
+        .current_files()
+        .iter()
+        .find(|file| file.uri().ends_with(file_name))
+        .unwrap();
+
+    TrustAnchorKeys::from_object(file.base64().to_bytes().as_ref()).unwrap()
+}
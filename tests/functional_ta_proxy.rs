@@ -0,0 +1,111 @@
+//! Perform functional tests on a Krill instance, using the API
+//!
+use std::fs;
+
+use krill::{
+    commons::api::{ParentCaReq, PublicationServerUris},
+    daemon::{ca::ta_handle, config::ConfigDefaults},
+    ta::{TrustAnchorSigner, TrustAnchorSignerConfig},
+    test::*,
+};
+
+#[tokio::test]
+async fn functional_ta_proxy() {
+    let krill_dir = start_krill_with_default_test_config(false, false, false, false).await;
+
+    info("##################################################################");
+    info("#                                                                #");
+    info("# Test an offline Trust Anchor: the proxy in Krill exchanges     #");
+    info("# requests and responses with the signer, and a child CA under   #");
+    info("# the proxy gets its certificate once the signer issued it.      #");
+    info("#                                                                #");
+    info("##################################################################");
+    info("");
+
+    let ta = ta_handle();
+    let ca = ca_handle("CA");
+    let ca_res = resources("AS65000", "10.0.0.0/16", "");
+
+    // The proxy needs the embedded repository to publish the TA objects
+    embedded_repo_init(PublicationServerUris::new(
+        https("https://localhost:3000/rrdp/"),
+        rsync("rsync://localhost/repo/"),
+    ))
+    .await;
+
+    ta_proxy_init().await;
+
+    // Set up the signer, as 'krillta init' would, and associate it with the proxy
+    let signer_dir = tmp_dir();
+    let signer_config = TrustAnchorSignerConfig {
+        data_dir: signer_dir.clone(),
+        signers: ConfigDefaults::signers(),
+        ta_mft_next_update_weeks: 12,
+        ta_child_certificate_valid_weeks: 52,
+    };
+    let signer = signer_config.signer().unwrap();
+    let timing = signer_config.issuance_timing();
+
+    let mut ta_signer = {
+        let proxy_info = ta_proxy_id().await;
+        let tal_https = vec![https("https://localhost:3000/ta/ta.cer")];
+        TrustAnchorSigner::create(proxy_info, tal_https, None, &timing, &signer).unwrap()
+    };
+
+    ta_proxy_signer_add(ta_signer.info()).await;
+    assert!(ta_proxy_status().await.has_signer());
+
+    // The first exchange gets the TA manifest, CRL and certificate published
+    {
+        let request = ta_proxy_signer_make_request().await;
+        let response = ta_signer.process(&request, &timing, &signer).unwrap();
+        ta_proxy_signer_process_response(response.clone()).await;
+
+        let status = ta_proxy_status().await;
+        assert!(status.open_request().is_none());
+        assert!(status.next_update().is_some());
+        assert_eq!(publisher_details(ta.convert()).await.current_files().len(), 3);
+
+        // Neither the proxy nor the signer accept the same exchange again
+        ta_proxy_signer_process_response_expect_error(response).await;
+        assert!(ta_signer.process(&request, &timing, &signer).is_err());
+    }
+
+    // Add a child under the proxy. It cannot get its certificate until the
+    // signer processed its request, so the proxy keeps it pending.
+    {
+        set_up_ca_with_repo(&ca).await;
+
+        let child_request = request(&ca).await;
+        let response = ta_proxy_children_add(ca.convert(), child_request, ca_res.clone()).await;
+        add_parent_to_ca(&ca, ParentCaReq::new(ta.convert(), response)).await;
+
+        let mut pending = false;
+        for _ in 0..30 {
+            if ta_proxy_status().await.pending_requests() == 1 {
+                pending = true;
+                break;
+            }
+            sleep_seconds(1).await;
+        }
+        assert!(pending);
+        assert!(ca_current_resources(&ca).await.is_empty());
+    }
+
+    // Exchange the pending request with the signer. The proxy publishes the
+    // child certificate and the child picks it up on its next sync.
+    {
+        let request = ta_proxy_signer_make_request().await;
+        let response = ta_signer.process(&request, &timing, &signer).unwrap();
+        ta_proxy_signer_process_response(response).await;
+
+        assert_eq!(ta_proxy_status().await.pending_requests(), 0);
+        assert!(ca_contains_resources(&ca, &ca_res).await);
+
+        // manifest, CRL, TA certificate and child certificate
+        assert_eq!(publisher_details(ta.convert()).await.current_files().len(), 4);
+    }
+
+    let _ = fs::remove_dir_all(krill_dir);
+    let _ = fs::remove_dir_all(signer_dir);
+}